@@ -1,3 +1,28 @@
 fn main() {
+    // Build date (UTC) reported by app_get_runtime_info; CI may pin it via HELIX_BUILD_DATE
+    let build_date = std::env::var("HELIX_BUILD_DATE").unwrap_or_else(|_| {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        civil_date(secs / 86_400)
+    });
+    println!("cargo:rustc-env=HELIX_BUILD_DATE={}", build_date);
+    println!("cargo:rerun-if-env-changed=HELIX_BUILD_DATE");
+
     tauri_build::build()
 }
+
+/// Days since 1970-01-01 → "YYYY-MM-DD" (Howard Hinnant's civil_from_days).
+fn civil_date(days: u64) -> String {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -1,5 +1,7 @@
 use tauri::State;
-use crate::modules::cloudflared::{CloudflaredConfig, CloudflaredManager, CloudflaredStatus};
+use crate::modules::cloudflared::{
+    CloudflaredConfig, CloudflaredManager, CloudflaredStatus, NamedTunnelConfig, DEFAULT_TUNNEL_ID,
+};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -40,9 +42,7 @@ pub async fn cloudflared_check(
         Ok(CloudflaredStatus {
             installed,
             version,
-            running: false,
-            url: None,
-            error: None,
+            ..CloudflaredStatus::default()
         })
     } else {
         Err("Manager not initialized".to_string())
@@ -65,18 +65,32 @@ pub async fn cloudflared_install(
 }
 
 /// 启动cloudflared隧道
+///
+/// 不传 `tunnel_id`（或传 "default"）时启动快速隧道 / Token 隧道，`config` 缺省时读取已保存的配置；
+/// 传入命名隧道 ID 时按已保存的命名隧道配置启动。
 #[tauri::command]
 pub async fn cloudflared_start(
     state: State<'_, CloudflaredState>,
-    config: CloudflaredConfig,
+    tunnel_id: Option<String>,
+    config: Option<CloudflaredConfig>,
 ) -> Result<CloudflaredStatus, String> {
     state.ensure_manager().await?;
-    
+
     let lock = state.manager.read().await;
-    if let Some(manager) = lock.as_ref() {
-        manager.start(config).await
-    } else {
-        Err("Manager not initialized".to_string())
+    let manager = lock.as_ref().ok_or("Manager not initialized")?;
+
+    match tunnel_id.as_deref() {
+        None | Some(DEFAULT_TUNNEL_ID) => {
+            let config = match config {
+                Some(c) => c,
+                None => crate::modules::config::load_app_config()?.cloudflared,
+            };
+            manager.start(config).await
+        }
+        Some(id) => {
+            let tunnel = find_named_tunnel(id)?;
+            manager.start_named(&tunnel).await
+        }
     }
 }
 
@@ -84,12 +98,13 @@ pub async fn cloudflared_start(
 #[tauri::command]
 pub async fn cloudflared_stop(
     state: State<'_, CloudflaredState>,
+    tunnel_id: Option<String>,
 ) -> Result<CloudflaredStatus, String> {
     state.ensure_manager().await?;
     
     let lock = state.manager.read().await;
     if let Some(manager) = lock.as_ref() {
-        manager.stop().await
+        manager.stop(tunnel_id.as_deref().unwrap_or(DEFAULT_TUNNEL_ID)).await
     } else {
         Err("Manager not initialized".to_string())
     }
@@ -99,22 +114,73 @@ pub async fn cloudflared_stop(
 #[tauri::command]
 pub async fn cloudflared_get_status(
     state: State<'_, CloudflaredState>,
+    tunnel_id: Option<String>,
 ) -> Result<CloudflaredStatus, String> {
     state.ensure_manager().await?;
 
     let lock = state.manager.read().await;
     if let Some(manager) = lock.as_ref() {
-        let (installed, version) = manager.check_installed().await;
-        let mut status = manager.get_status().await;
-        status.installed = installed;
-        status.version = version;
-        if !installed {
-            status.running = false;
-            status.url = None;
-        }
-        Ok(status)
+        Ok(manager.get_status(tunnel_id.as_deref().unwrap_or(DEFAULT_TUNNEL_ID)).await)
     } else {
         Ok(CloudflaredStatus::default())
     }
 }
 
+/// 列出所有隧道（默认隧道 + 命名隧道）及其状态
+#[tauri::command]
+pub async fn cloudflared_list(
+    state: State<'_, CloudflaredState>,
+) -> Result<Vec<CloudflaredStatus>, String> {
+    state.ensure_manager().await?;
+
+    let tunnels = crate::modules::config::load_app_config()?.cloudflared.tunnels;
+    let lock = state.manager.read().await;
+    match lock.as_ref() {
+        Some(manager) => Ok(manager.list(&tunnels).await),
+        None => Err("Manager not initialized".to_string()),
+    }
+}
+
+/// 新增或更新命名隧道配置
+#[tauri::command]
+pub async fn cloudflared_save_tunnel(tunnel: NamedTunnelConfig) -> Result<NamedTunnelConfig, String> {
+    tunnel.validate()?;
+
+    let mut config = crate::modules::config::load_app_config()?;
+    match config.cloudflared.tunnels.iter_mut().find(|t| t.id == tunnel.id) {
+        Some(existing) => *existing = tunnel.clone(),
+        None => config.cloudflared.tunnels.push(tunnel.clone()),
+    }
+    crate::modules::config::save_app_config(&config)?;
+    Ok(tunnel)
+}
+
+/// 删除命名隧道（运行中会先停止）
+#[tauri::command]
+pub async fn cloudflared_delete_tunnel(
+    state: State<'_, CloudflaredState>,
+    tunnel_id: String,
+) -> Result<(), String> {
+    let mut config = crate::modules::config::load_app_config()?;
+    let before = config.cloudflared.tunnels.len();
+    config.cloudflared.tunnels.retain(|t| t.id != tunnel_id);
+    if config.cloudflared.tunnels.len() == before {
+        return Err(format!("Tunnel '{}' not found", tunnel_id));
+    }
+    crate::modules::config::save_app_config(&config)?;
+
+    state.ensure_manager().await?;
+    if let Some(manager) = state.manager.read().await.as_ref() {
+        manager.remove(&tunnel_id).await;
+    }
+    Ok(())
+}
+
+fn find_named_tunnel(tunnel_id: &str) -> Result<NamedTunnelConfig, String> {
+    crate::modules::config::load_app_config()?
+        .cloudflared
+        .tunnels
+        .into_iter()
+        .find(|t| t.id == tunnel_id)
+        .ok_or_else(|| format!("Tunnel '{}' not found", tunnel_id))
+}
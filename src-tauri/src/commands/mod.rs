@@ -18,7 +18,10 @@ pub async fn save_config(
     app: tauri::AppHandle,
     config: AppConfig,
 ) -> Result<(), String> {
+    // Rejects an invalid report time before anything is saved
+    modules::cron::sync_usage_report_task(&config.usage_report).map_err(|e| e.to_string())?;
     modules::save_app_config(&config)?;
+    modules::i18n::set_locale(&config.language);
 
     // 通知托盘配置已更新
     let _ = tauri::Emitter::emit(&app, "config://updated", ());
@@ -75,6 +78,13 @@ pub async fn update_last_check_time() -> Result<(), String> {
     crate::modules::update_checker::update_last_check_time()
 }
 
+/// 下载更新并校验 SHA-256 后交给 updater 插件安装
+#[tauri::command]
+pub async fn install_verified_update(app: tauri::AppHandle) -> Result<UpdateInfo, String> {
+    modules::logger::log_info("收到前端触发的校验安装更新请求");
+    crate::modules::update_checker::install_verified_update(&app).await
+}
+
 /// 检测是否通过 Homebrew Cask 安装
 #[tauri::command]
 pub async fn check_homebrew_installation() -> Result<bool, String> {
@@ -105,7 +115,7 @@ pub async fn save_update_settings(
 
 fn validate_path(path: &str) -> Result<(), String> {
     if path.contains("..") {
-        return Err("非法路径: 不允许目录遍历".to_string());
+        return Err(modules::i18n::t("error.path_traversal", &[]));
     }
 
     let lower_path = path.to_lowercase();
@@ -123,7 +133,7 @@ fn validate_path(path: &str) -> Result<(), String> {
 
     for prefix in sensitive_prefixes {
         if lower_path.starts_with(prefix) {
-            return Err(format!("安全拒绝: 禁止访问系统敏感路径 ({})", prefix));
+            return Err(modules::i18n::t("error.sensitive_path", &[("prefix", prefix)]));
         }
     }
 
@@ -134,18 +144,20 @@ fn validate_path(path: &str) -> Result<(), String> {
 #[tauri::command]
 pub async fn save_text_file(path: String, content: String) -> Result<(), String> {
     validate_path(&path)?;
-    std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+    std::fs::write(&path, content)
+        .map_err(|e| modules::i18n::t("error.file_write_failed", &[("error", &e.to_string())]))
 }
 
 /// 读取文本文件
 #[tauri::command]
 pub async fn read_text_file(path: String) -> Result<String, String> {
     validate_path(&path)?;
-    std::fs::read_to_string(&path).map_err(|e| format!("读取文件失败: {}", e))
+    std::fs::read_to_string(&path)
+        .map_err(|e| modules::i18n::t("error.file_read_failed", &[("error", &e.to_string())]))
 }
 
 // ============================================================================
-// K8s / Aliyun 配置读取命令
+// K8s / Aliyun / AWS 配置读取命令
 // ============================================================================
 
 /// 读取 kubeconfig 信息
@@ -154,8 +166,75 @@ pub async fn get_kube_info(custom_path: Option<String>) -> Result<modules::kubec
     modules::kubeconfig::load_kube_info(custom_path.as_deref())
 }
 
+/// 列出 kubeconfig 中的所有 context
+#[tauri::command]
+pub async fn kube_list_contexts(custom_path: Option<String>) -> Result<Vec<modules::kubeconfig::KubeContext>, String> {
+    modules::kubeconfig::list_contexts(custom_path.as_deref())
+}
+
+/// 切换 kubeconfig 的 current-context
+#[tauri::command]
+pub async fn kube_use_context(name: String, custom_path: Option<String>) -> Result<modules::kubeconfig::KubeContext, String> {
+    modules::kubeconfig::use_context(&name, custom_path.as_deref())
+}
+
 /// 读取阿里云配置信息
 #[tauri::command]
 pub async fn get_aliyun_info() -> Result<modules::aliyun::AliyunInfo, String> {
     modules::aliyun::load_aliyun_info()
 }
+
+/// 读取 AWS 凭证与配置信息
+#[tauri::command]
+pub async fn get_aws_info() -> Result<modules::aws::AwsInfo, String> {
+    modules::aws::load_aws_info().await
+}
+
+// ============================================================================
+// GitHub 命令
+// ============================================================================
+
+/// 保存 GitHub token 与默认仓库
+#[tauri::command]
+pub async fn github_configure(config: modules::github::GithubConfig) -> Result<(), String> {
+    modules::github::save_config(&config)
+}
+
+/// 创建 Issue
+#[tauri::command]
+pub async fn github_create_issue(
+    title: String,
+    body: String,
+    labels: Option<Vec<String>>,
+    repo: Option<String>,
+) -> Result<modules::github::Issue, String> {
+    modules::github::create_issue(repo.as_deref(), &title, &body, &labels.unwrap_or_default()).await
+}
+
+/// 列出 Issue
+#[tauri::command]
+pub async fn github_list_issues(
+    state: Option<String>,
+    limit: Option<u32>,
+    repo: Option<String>,
+) -> Result<Vec<modules::github::Issue>, String> {
+    modules::github::list_issues(repo.as_deref(), state.as_deref().unwrap_or("open"), limit.unwrap_or(30)).await
+}
+
+/// 创建 Pull Request
+#[tauri::command]
+pub async fn github_create_pr(
+    title: String,
+    body: String,
+    head: String,
+    base: String,
+    repo: Option<String>,
+) -> Result<modules::github::PR, String> {
+    modules::github::create_pr(repo.as_deref(), &title, &body, &head, &base).await
+}
+
+/// 获取 PR diff
+#[tauri::command]
+pub async fn github_get_pr_diff(pr_number: u64, repo: Option<String>) -> Result<String, String> {
+    modules::github::get_pr_diff(repo.as_deref(), pr_number).await
+}
@@ -38,3 +38,244 @@ impl Serialize for AppError {
 
 // Implement alias for Result to simplify usage
 pub type AppResult<T> = Result<T, AppError>;
+
+// ============================================================================
+// HelixError — structured error returned by Tauri commands
+// ============================================================================
+
+/// Stable error codes carried by [`HelixError`]. The frontend keys i18n
+/// lookups on them (`errors.<code>`), so never rename a code once shipped.
+///
+/// The generic categories are SCREAMING_CASE; domain codes are dotted.
+///
+/// | code                      | meaning                                           | retriable |
+/// |---------------------------|---------------------------------------------------|-----------|
+/// | `unknown`                 | unconverted `String` error                        | no        |
+/// | `NOT_FOUND`               | the requested record does not exist               | no        |
+/// | `UNAUTHORIZED`            | caller is not allowed to perform the action       | no        |
+/// | `RATE_LIMITED`            | too many requests; `detail.retry_after_ms`        | yes       |
+/// | `NETWORK_ERROR`           | a request to another service failed               | yes       |
+/// | `VALIDATION_ERROR`        | invalid input; `detail.errors` lists each problem | no        |
+/// | `INTERNAL`                | unexpected failure inside Helix                   | no        |
+/// | `config.load_failed`      | helix_config.json could not be read               | no        |
+/// | `network.unreachable`     | could not connect to a remote host                | yes       |
+/// | `network.timeout`         | remote host did not answer in time                | yes       |
+/// | `provider.not_configured` | no API key set for the AI provider                | no        |
+/// | `provider.auth_failed`    | provider rejected the key (401/403, malformed)    | no        |
+/// | `provider.rate_limited`   | provider returned 429 after retries               | yes       |
+/// | `provider.unavailable`    | provider returned 5xx after retries               | yes       |
+/// | `provider.bad_request`    | provider rejected the request (other 4xx)         | no        |
+/// | `provider.bad_response`   | provider response could not be parsed             | no        |
+/// | `agent.timeout`           | agent run exceeded its time limit                 | yes       |
+/// | `agent.failed`            | agent run failed for another reason               | no        |
+/// | `cron.not_found`          | no task with that id                              | no        |
+/// | `cron.no_script`          | task has no script to run                         | no        |
+/// | `cron.already_running`    | task is locked by another run                     | yes       |
+/// | `cron.invalid_expr`       | cron expression does not parse                    | no        |
+/// | `cron.invalid_group`      | group name empty, too long, or reserved           | no        |
+/// | `cron.group_exists`       | group name already taken                          | no        |
+/// | `cron.not_locked`         | force-unlock on a task that holds no lock         | no        |
+/// | `storage.db`              | SQLite query failed                               | no        |
+pub mod codes {
+    pub const UNKNOWN: &str = "unknown";
+    pub const NOT_FOUND: &str = "NOT_FOUND";
+    pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
+    pub const RATE_LIMITED: &str = "RATE_LIMITED";
+    pub const NETWORK_ERROR: &str = "NETWORK_ERROR";
+    pub const VALIDATION_ERROR: &str = "VALIDATION_ERROR";
+    pub const INTERNAL: &str = "INTERNAL";
+    pub const CONFIG_LOAD_FAILED: &str = "config.load_failed";
+    pub const NETWORK_UNREACHABLE: &str = "network.unreachable";
+    pub const NETWORK_TIMEOUT: &str = "network.timeout";
+    pub const PROVIDER_NOT_CONFIGURED: &str = "provider.not_configured";
+    pub const PROVIDER_AUTH_FAILED: &str = "provider.auth_failed";
+    pub const PROVIDER_RATE_LIMITED: &str = "provider.rate_limited";
+    pub const PROVIDER_UNAVAILABLE: &str = "provider.unavailable";
+    pub const PROVIDER_BAD_REQUEST: &str = "provider.bad_request";
+    pub const PROVIDER_BAD_RESPONSE: &str = "provider.bad_response";
+    pub const AGENT_TIMEOUT: &str = "agent.timeout";
+    pub const AGENT_FAILED: &str = "agent.failed";
+    pub const CRON_NOT_FOUND: &str = "cron.not_found";
+    pub const CRON_NO_SCRIPT: &str = "cron.no_script";
+    pub const CRON_ALREADY_RUNNING: &str = "cron.already_running";
+    pub const CRON_INVALID_EXPR: &str = "cron.invalid_expr";
+    pub const CRON_INVALID_GROUP: &str = "cron.invalid_group";
+    pub const CRON_GROUP_EXISTS: &str = "cron.group_exists";
+    pub const CRON_NOT_LOCKED: &str = "cron.not_locked";
+    pub const STORAGE_DB: &str = "storage.db";
+}
+
+/// Error shape the frontend receives from converted commands:
+/// `{ code, message, detail?, retriable }`.
+#[derive(Error, Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[error("{message}")]
+pub struct HelixError {
+    /// One of [`codes`]
+    pub code: String,
+    /// Human-readable message (fallback when the frontend has no translation)
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<serde_json::Value>,
+    /// Whether retrying the same call may succeed
+    #[serde(default)]
+    pub retriable: bool,
+}
+
+impl HelixError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self { code: code.to_string(), message: message.into(), detail: None, retriable: false }
+    }
+
+    pub fn with_detail(mut self, detail: serde_json::Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn retriable(mut self) -> Self {
+        self.retriable = true;
+        self
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(codes::NOT_FOUND, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(codes::UNAUTHORIZED, message)
+    }
+
+    pub fn rate_limited(retry_after_ms: u64) -> Self {
+        Self::new(codes::RATE_LIMITED, format!("Rate limited, retry after {}ms", retry_after_ms))
+            .with_detail(serde_json::json!({ "retry_after_ms": retry_after_ms }))
+            .retriable()
+    }
+
+    pub fn network_error(message: impl Into<String>) -> Self {
+        Self::new(codes::NETWORK_ERROR, message).retriable()
+    }
+
+    /// One error carrying every problem found in the input.
+    pub fn validation_error(errors: Vec<String>) -> Self {
+        Self::new(codes::VALIDATION_ERROR, errors.join("; ")).with_detail(serde_json::json!({ "errors": errors }))
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(codes::INTERNAL, message)
+    }
+
+    /// Suggested wait before retrying, when the error carries one.
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        self.detail.as_ref()?.get("retry_after_ms")?.as_u64()
+    }
+}
+
+/// Shim for call sites that still produce `String` errors.
+impl From<String> for HelixError {
+    fn from(message: String) -> Self {
+        HelixError::new(codes::UNKNOWN, message)
+    }
+}
+
+impl From<&str> for HelixError {
+    fn from(message: &str) -> Self {
+        HelixError::new(codes::UNKNOWN, message)
+    }
+}
+
+impl From<AppError> for HelixError {
+    fn from(err: AppError) -> Self {
+        let message = err.to_string();
+        match err {
+            AppError::Network(_, status) => HelixError::new(codes::NETWORK_UNREACHABLE, message)
+                .with_detail(serde_json::json!({ "status": status }))
+                .retriable(),
+            AppError::Config(_) => HelixError::new(codes::CONFIG_LOAD_FAILED, message),
+            _ => HelixError::new(codes::UNKNOWN, message),
+        }
+    }
+}
+
+pub type HelixResult<T> = Result<T, HelixError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helix_error_serialization() {
+        let err = HelixError::new(codes::PROVIDER_RATE_LIMITED, "slow down").retriable();
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({ "code": "provider.rate_limited", "message": "slow down", "retriable": true })
+        );
+
+        let shim: HelixError = "boom".to_string().into();
+        assert_eq!(shim.code, codes::UNKNOWN);
+        assert_eq!(shim.to_string(), "boom");
+        assert!(!shim.retriable);
+    }
+
+    fn json(err: HelixError) -> serde_json::Value {
+        serde_json::to_value(&err).unwrap()
+    }
+
+    #[test]
+    fn test_not_found_serialization() {
+        assert_eq!(
+            json(HelixError::not_found("Memory 7 not found")),
+            serde_json::json!({ "code": "NOT_FOUND", "message": "Memory 7 not found", "retriable": false })
+        );
+    }
+
+    #[test]
+    fn test_unauthorized_serialization() {
+        assert_eq!(
+            json(HelixError::unauthorized("bad token")),
+            serde_json::json!({ "code": "UNAUTHORIZED", "message": "bad token", "retriable": false })
+        );
+    }
+
+    #[test]
+    fn test_rate_limited_serialization() {
+        assert_eq!(
+            json(HelixError::rate_limited(1500)),
+            serde_json::json!({
+                "code": "RATE_LIMITED",
+                "message": "Rate limited, retry after 1500ms",
+                "detail": { "retry_after_ms": 1500 },
+                "retriable": true
+            })
+        );
+        assert_eq!(HelixError::rate_limited(1500).retry_after_ms(), Some(1500));
+        assert_eq!(HelixError::internal("x").retry_after_ms(), None);
+    }
+
+    #[test]
+    fn test_network_error_serialization() {
+        assert_eq!(
+            json(HelixError::network_error("connection refused")),
+            serde_json::json!({ "code": "NETWORK_ERROR", "message": "connection refused", "retriable": true })
+        );
+    }
+
+    #[test]
+    fn test_validation_error_serialization() {
+        assert_eq!(
+            json(HelixError::validation_error(vec!["key is empty".into(), "content is empty".into()])),
+            serde_json::json!({
+                "code": "VALIDATION_ERROR",
+                "message": "key is empty; content is empty",
+                "detail": { "errors": ["key is empty", "content is empty"] },
+                "retriable": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_internal_serialization() {
+        assert_eq!(
+            json(HelixError::internal("lock poisoned")),
+            serde_json::json!({ "code": "INTERNAL", "message": "lock poisoned", "retriable": false })
+        );
+    }
+}
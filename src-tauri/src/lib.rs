@@ -5,15 +5,36 @@ mod modules;
 mod utils;
 
 use modules::logger;
+use once_cell::sync::Lazy;
 use tauri::Manager;
 use tracing::{error, info, warn};
 
+/// Process start time, used for uptime reporting.
+pub(crate) static APP_START: Lazy<std::time::Instant> = Lazy::new(std::time::Instant::now);
+
 #[derive(Clone, Copy)]
 struct AppRuntimeFlags {
     tray_enabled: bool,
 }
 
-fn env_flag_enabled(name: &str) -> bool {
+/// Snapshot of app health for the frontend dashboard
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AppRuntimeInfo {
+    pub version: String,
+    pub build_date: String,
+    pub uptime_secs: u64,
+    pub tray_enabled: bool,
+    /// 0 when the API server is not listening
+    pub api_server_port: u16,
+    pub active_wechat_sessions: usize,
+    /// Whether a Feishu webhook is configured
+    pub feishu_connected: bool,
+    pub cron_tasks_active: usize,
+    pub memory_entries: i64,
+    pub skills_loaded: usize,
+}
+
+pub(crate) fn env_flag_enabled(name: &str) -> bool {
     std::env::var(name)
         .map(|v| {
             matches!(
@@ -24,8 +45,9 @@ fn env_flag_enabled(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-#[cfg(target_os = "linux")]
-fn is_wayland_session() -> bool {
+/// True in a Wayland desktop session. Also used off the startup path, e.g. to
+/// pick screen-capture tools.
+pub(crate) fn is_wayland_session() -> bool {
     std::env::var("WAYLAND_DISPLAY")
         .map(|v| !v.trim().is_empty())
         .unwrap_or(false)
@@ -108,18 +130,50 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+#[tauri::command]
+async fn app_get_runtime_info(
+    flags: tauri::State<'_, AppRuntimeFlags>,
+) -> Result<AppRuntimeInfo, String> {
+    let cron_tasks_active = modules::cron::list_tasks()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|t| t.status == "active")
+        .count();
+    let active_wechat_sessions = modules::sessions::list_sessions(Some("wechat"), i64::MAX)?.len();
+
+    Ok(AppRuntimeInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        build_date: env!("HELIX_BUILD_DATE").to_string(),
+        uptime_secs: APP_START.elapsed().as_secs(),
+        tray_enabled: flags.tray_enabled,
+        api_server_port: modules::api_server::listening_port().unwrap_or(0),
+        active_wechat_sessions,
+        feishu_connected: modules::notifications::is_channel_configured("feishu"),
+        cron_tasks_active,
+        memory_entries: modules::memory::get_memory_stats()?.total_entries,
+        skills_loaded: modules::skills::list_all_skills().len(),
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Increase file descriptor limit (macOS only)
     #[cfg(target_os = "macos")]
     increase_nofile_limit();
 
+    Lazy::force(&APP_START);
+
     // Initialize logger
     logger::init_logger();
 
     #[cfg(target_os = "linux")]
     configure_linux_gdk_backend();
 
+    // Load user-defined environment variables from ~/.helix/envs.json while
+    // the process is still single-threaded
+    modules::environments::apply_envs_to_process();
+    modules::environments::check_envs_at_startup();
+
     let tray_enabled = should_enable_tray();
 
     tauri::Builder::default()
@@ -142,6 +196,11 @@ pub fn run() {
                 )
                 .build(),
         )
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| modules::hotkey::handle_shortcut(app, shortcut, event))
+                .build(),
+        )
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             let _ = app.get_webview_window("main").map(|window| {
                 let _ = window.show();
@@ -169,9 +228,6 @@ pub fn run() {
             // Start skills hot-reload watcher (scans ~/.helix/skills/ every 5s)
             modules::skills::start_skills_watcher();
 
-            // Load user-defined environment variables from ~/.helix/envs.json
-            modules::environments::apply_envs_to_process();
-
             // Initialize hooks tables
             if let Err(e) = modules::hooks::init_hooks_tables() {
                 error!("Failed to initialize hooks tables: {}", e);
@@ -192,6 +248,26 @@ pub fn run() {
                 error!("Failed to initialize usage tables: {}", e);
             }
 
+            // Initialize prompt template tables
+            if let Err(e) = modules::prompt_templates::init_prompt_tables() {
+                error!("Failed to initialize prompt template tables: {}", e);
+            }
+
+            // Initialize tool artifact tables
+            if let Err(e) = modules::agent::artifacts::init_artifact_tables() {
+                error!("Failed to initialize artifact tables: {}", e);
+            }
+
+            // Initialize session scratchpad tables
+            if let Err(e) = modules::agent::scratchpad::init_scratchpad_tables() {
+                error!("Failed to initialize scratchpad tables: {}", e);
+            }
+
+            // Initialize agent replay tables
+            if let Err(e) = modules::agent::replay::init_replay_tables() {
+                error!("Failed to initialize replay tables: {}", e);
+            }
+
             // Initialize Brain (context management)
             if let Err(e) = modules::ai::context::init_brain() {
                 error!("Failed to initialize brain: {}", e);
@@ -199,6 +275,7 @@ pub fn run() {
 
             // Initialize log bridge with app handle for debug console
             modules::log_bridge::init_log_bridge(app.handle().clone());
+            modules::desktop_notify::init(app.handle());
 
             // Linux: Workaround for transparent window crash/freeze
             #[cfg(target_os = "linux")]
@@ -227,15 +304,26 @@ pub fn run() {
                 info!("Tray disabled for this session");
             }
 
+            // Register the global show/hide and quick-ask hotkeys (failures are reported, not fatal)
+            modules::hotkey::init(app.handle());
+
             // Start smart scheduler
             modules::scheduler::start_scheduler(Some(app.handle().clone()));
 
             // Start cron job scheduler
             modules::cron::start_cron_scheduler();
+            if let Ok(config) = modules::load_app_config() {
+                if let Err(e) = modules::cron::sync_usage_report_task(&config.usage_report) {
+                    warn!("Failed to schedule daily usage report: {}", e);
+                }
+            }
 
             // Start heartbeat system (reads ~/.helix/HEARTBEAT.md periodically)
             modules::cron::start_heartbeat();
 
+            // Start MCP server health checks
+            modules::mcp_client::start_mcp_health_monitor();
+
             // Start embedded HTTP API server with Swagger UI
             modules::api_server::start_api_server(9520);
 
@@ -281,9 +369,18 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             greet,
+            app_get_runtime_info,
+            modules::diagnostics::collect_diagnostics,
+            modules::diagnostics::export_diagnostics,
+            modules::self_test::run_self_test,
             // Config commands
             commands::load_config,
             commands::save_config,
+            modules::hotkey::set_global_hotkey,
+            modules::hotkey::get_global_hotkey_status,
+            modules::hotkey::set_quick_ask_hotkey,
+            modules::quick_ask::quickask_show,
+            modules::quick_ask::quickask_hide,
             // Utility commands
             commands::save_text_file,
             commands::read_text_file,
@@ -292,6 +389,7 @@ pub fn run() {
             commands::set_window_theme,
             // Update commands
             commands::check_for_updates,
+            commands::install_verified_update,
             commands::check_homebrew_installation,
             commands::brew_upgrade_cask,
             commands::get_update_settings,
@@ -307,27 +405,44 @@ pub fn run() {
             commands::cloudflared::cloudflared_start,
             commands::cloudflared::cloudflared_stop,
             commands::cloudflared::cloudflared_get_status,
+            commands::cloudflared::cloudflared_list,
+            commands::cloudflared::cloudflared_save_tunnel,
+            commands::cloudflared::cloudflared_delete_tunnel,
             // Debug console commands
             modules::log_bridge::enable_debug_console,
             modules::log_bridge::disable_debug_console,
             modules::log_bridge::is_debug_console_enabled,
             modules::log_bridge::get_debug_console_logs,
             modules::log_bridge::clear_debug_console_logs,
-            // K8s / Aliyun config commands
+            // K8s / Aliyun / AWS config commands
             commands::get_kube_info,
+            commands::kube_list_contexts,
+            commands::kube_use_context,
             commands::get_aliyun_info,
+            commands::get_aws_info,
+            // GitHub commands
+            commands::github_configure,
+            commands::github_create_issue,
+            commands::github_list_issues,
+            commands::github_create_pr,
+            commands::github_get_pr_diff,
             // AI Chat commands
             modules::ai_chat::team_chat_fetch,
             modules::ai_chat::ai_chat_send,
             modules::ai_chat::ai_get_config,
             modules::ai_chat::ai_set_config,
             modules::ai_chat::ai_test_connection,
+            modules::ai_chat::ai_key_pool_status,
             modules::ai_chat::ai_list_models,
             // Database commands
             modules::database::db_list_accounts,
             modules::database::db_get_messages,
+            modules::database::db_annotate_message,
+            modules::database::db_remove_annotation,
             modules::database::db_set_account_remark,
             modules::database::db_set_auto_reply,
+            modules::database::db_search_files,
+            modules::database::db_get_file_by_name,
             // Agent commands
             modules::agent::agent_chat,
             modules::agent::agent_cancel,
@@ -341,13 +456,50 @@ pub fn run() {
             modules::cron::cron_delete_task,
             modules::cron::cron_run_task,
             modules::cron::cron_get_runs,
+            modules::cron::cron_prune_runs,
+            modules::cron::cron_next_runs,
+            modules::cron::cron_list_locks,
+            modules::cron::cron_force_unlock,
+            modules::cron::heartbeat_get,
+            modules::cron::heartbeat_set,
+            modules::cron::heartbeat_validate,
+            modules::cron::heartbeat_set_config,
+            modules::scheduler::scheduler_status,
+            modules::cron::cron_create_group,
+            modules::cron::cron_list_groups,
+            modules::cron::cron_pause_group,
+            modules::cron::cron_resume_group,
+            modules::cron::cron_delete_group,
             modules::cron::cron_validate_expr,
+            modules::cron::cron_export,
+            modules::cron::cron_import,
+            modules::cron::cron_list_templates,
+            modules::cron::cron_create_from_template,
+            // I18n commands
+            modules::i18n::i18n_set_locale,
+            modules::i18n::i18n_load_locale,
+            modules::i18n::i18n_get,
+            modules::i18n::i18n_list_locales,
+            modules::i18n::i18n_set_active_locale,
             // Notification commands
             modules::notifications::notification_test_send,
+            // SMTP email
+            modules::smtp::smtp_configure,
+            modules::smtp::smtp_test,
+            modules::smtp::smtp_send,
+            modules::smtp::smtp_send_attachment,
+            modules::wecom::wecom_configure,
+            modules::wecom::wecom_get_config,
+            modules::wecom::wecom_test,
+            modules::wecom::wecom_send_text,
+            modules::wecom::wecom_send_markdown,
+            modules::wecom::wecom_send_file,
+            modules::wecom::wecom_upload_media,
             // Skills commands
             modules::skills::skills_list,
             modules::skills::skills_toggle,
             modules::skills::skills_reload,
+            modules::skills::skills_get_load_errors,
             modules::skills::skills_get_body,
             modules::skills::skills_create,
             modules::skills::skills_uninstall,
@@ -363,18 +515,32 @@ pub fn run() {
             // Commands
             modules::commands::commands_list,
             modules::commands::commands_execute,
+            modules::commands::commands_custom_list,
+            modules::commands::commands_custom_create,
+            modules::commands::commands_custom_update,
+            modules::commands::commands_custom_delete,
+            modules::quick_actions::quick_actions,
             // Advanced Memory
             modules::memory::memory_search,
+            modules::memory::memory_search_by_tag,
+            modules::search::helix_search,
             modules::memory::memory_store_entry,
             modules::memory::memory_delete,
+            modules::memory::memory_update_access_count,
+            modules::memory::memory_set_visibility,
+            modules::memory::memory_pin,
             modules::memory::memory_list,
             modules::memory::memory_stats,
+            modules::memory::memory_reindex,
             modules::memory::memory_embed,
+            modules::memory::memory_embed_all,
             modules::memory::memory_save_conversation,
+            modules::memory::memory_import_from_session,
             modules::memory::memory_flush,
             modules::memory::memory_list_files,
             // Security
             modules::security::security_audit,
+            modules::security::security_generate_report,
             // Link Understanding
             modules::link_understanding::link_fetch,
             modules::link_understanding::link_detect,
@@ -390,7 +556,14 @@ pub fn run() {
             modules::sessions::sessions_set_policy,
             modules::sessions::sessions_set_label,
             modules::sessions::sessions_delete,
+            modules::sessions::sessions_fork,
+            modules::sessions::sessions_export_html,
             modules::sessions::sessions_compact,
+            modules::sessions::sessions_compact_dry_run,
+            modules::sessions::sessions_get_context_window,
+            modules::sessions::sessions_add_context_file,
+            modules::sessions::sessions_list_context_files,
+            modules::sessions::sessions_remove_context_file,
             // Messaging
             modules::messaging::messaging_chunk,
             modules::messaging::messaging_template,
@@ -399,11 +572,30 @@ pub fn run() {
             modules::media_understanding::media_extract_file,
             modules::media_understanding::media_describe_image,
             modules::media_understanding::media_transcribe_audio,
+            modules::media_understanding::media_translate_audio,
+            modules::media_understanding::media_ocr_image,
+            // Prompt templates
+            modules::prompt_templates::prompt_create,
+            modules::prompt_templates::prompt_list,
+            modules::prompt_templates::prompt_get,
+            modules::prompt_templates::prompt_update,
+            modules::prompt_templates::prompt_delete,
+            modules::prompt_templates::prompt_render,
+            modules::prompt_templates::prompt_import_defaults,
+            modules::prompt_templates::prompt_search,
+            modules::agent::artifacts::artifacts_list,
+            modules::agent::artifacts::artifacts_read,
+            modules::agent::replay::agent_replay,
             // Providers
             modules::providers::providers_detect,
             modules::providers::providers_resolve,
+            modules::providers::providers_list,
+            modules::providers::providers_add,
+            modules::providers::providers_update,
+            modules::providers::providers_remove,
             // Streaming
             modules::streaming::streaming_test,
+            modules::streaming::providers_benchmark,
             // Usage
             modules::usage::usage_dashboard,
             modules::usage::usage_totals,
@@ -413,22 +605,34 @@ pub fn run() {
             modules::usage::usage_daily,
             modules::usage::usage_log,
             modules::usage::usage_estimate_cost,
+            modules::usage::usage_export_csv,
+            modules::usage::usage_daily_report_preview,
+            modules::usage::usage_by_day_of_week,
+            modules::usage::usage_by_hour_of_day,
             // Model Selection
             modules::model_selection::model_resolve,
             modules::model_selection::model_list_aliases,
             modules::model_selection::model_default,
+            modules::model_selection::model_recommend,
             // Stream Events
             modules::stream_events::stream_clean_text,
             modules::stream_events::stream_strip_thinking,
+            modules::stream_events::stream_extract_code_blocks,
             // EvoMap
             modules::evomap::evomap_hello,
             modules::evomap::evomap_fetch,
             modules::evomap::evomap_publish,
+            modules::evomap::evomap_install,
+            modules::evomap::evomap_trust_add,
+            modules::evomap::evomap_trust_list,
+            modules::evomap::evomap_trust_remove,
             modules::evomap::evomap_list_assets,
             modules::evomap::evomap_status,
             modules::evomap::evomap_toggle,
             // Agent Tools
             modules::agent_tools::tool_image_describe,
+            modules::agent_tools::clipboard_read,
+            modules::agent_tools::clipboard_write,
             // Subagents
             modules::subagents::spawn_subagent,
             modules::subagents::spawn_subagents_batch,
@@ -441,16 +645,26 @@ pub fn run() {
             modules::workspace::workspace_open_dir,
             modules::workspace::workspace_list_session_files,
             modules::workspace::workspace_read_session_file,
+            modules::workspace::workspace_search,
+            modules::workspace::workspace_lint,
             // Environments
             modules::environments::envs_list,
             modules::environments::envs_set,
             modules::environments::envs_delete,
+            modules::environments::envs_reveal,
+            modules::environments::envs_import_dotenv,
+            modules::environments::envs_export,
+            modules::environments::envs_set_schema,
+            modules::environments::envs_get_schema,
+            modules::environments::envs_validate,
             // MCP
             modules::mcp::mcp_list,
             modules::mcp::mcp_create,
             modules::mcp::mcp_toggle,
             modules::mcp::mcp_delete,
             modules::mcp::mcp_update,
+            modules::mcp::mcp_prompts_list,
+            modules::mcp::mcp_prompt_get,
             // AI Context (backward compatible)
             modules::ai::context::get_antigravity_context,
             // Brain — Unified Context Management
@@ -475,7 +689,8 @@ pub fn run() {
                 api.prevent_exit();
             }
             tauri::RunEvent::Exit => {
-                tracing::info!("Application exiting, cleaning up background tasks...");
+                modules::hotkey::unregister_all(app_handle);
+                modules::shutdown::on_exit();
             }
             #[cfg(target_os = "macos")]
             tauri::RunEvent::Reopen { .. } => {
@@ -487,6 +702,7 @@ pub fn run() {
                         .set_activation_policy(tauri::ActivationPolicy::Regular)
                         .unwrap_or(());
                 }
+                modules::desktop_notify::on_app_reopened(app_handle);
             }
             _ => {}
         });
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::modules::cloudflared::CloudflaredConfig;
 
 /// AI model configuration
@@ -45,6 +46,208 @@ pub struct NotificationsConfig {
     pub dingtalk_webhook: Option<String>,
 }
 
+/// Opt-in switches for agent tools that can touch external systems
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ToolPolicyConfig {
+    /// Offer the `kubectl_exec` agent tool
+    #[serde(default)]
+    pub kubectl_enabled: bool,
+    /// Allow kubectl verbs beyond get/describe/logs/top
+    #[serde(default)]
+    pub kubectl_allow_mutations: bool,
+    /// Offer the `aliyun_ecs_list` / `aliyun_oss_upload` agent tools
+    #[serde(default)]
+    pub aliyun_enabled: bool,
+    /// Extra hosts `browser_render` refuses (exact or `*.suffix`)
+    #[serde(default)]
+    pub browser_denylist: Vec<String>,
+}
+
+/// How a named provider spreads requests over its keys
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotationStrategy {
+    /// Least recently used key first
+    #[default]
+    RoundRobin,
+    /// Keys in listed order; the next one only while earlier ones are rate limited
+    Failover,
+}
+
+/// Named credentials for an OpenAI-compatible provider
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NamedProvider {
+    /// Unique name, e.g. "personal" or "team"; `ai_config.provider` selects the default
+    pub name: String,
+    pub base_url: String,
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub rotation: KeyRotationStrategy,
+}
+
+/// Retry policy for transient AI provider failures (429 / 5xx / connection errors)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AiRetryConfig {
+    /// Total attempts against the same provider, including the first (1 = no retry)
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    /// Backoff before the first retry; doubles per attempt
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// Upper bound on a single backoff
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_retry_max_attempts() -> u32 { 3 }
+fn default_retry_base_delay_ms() -> u64 { 500 }
+fn default_retry_max_delay_ms() -> u64 { 8000 }
+
+impl Default for AiRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+        }
+    }
+}
+
+/// Automatic memory retrieval before each agent turn
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoRecallConfig {
+    /// Search memories with the user's message and inject matches into the prompt
+    #[serde(default = "default_auto_recall_enabled")]
+    pub enabled: bool,
+    /// Maximum number of memories injected per turn
+    #[serde(default = "default_auto_recall_top_k")]
+    pub top_k: usize,
+    /// Minimum hybrid search score (after recency decay) for a memory to be injected
+    #[serde(default = "default_auto_recall_min_score")]
+    pub min_score: f64,
+}
+
+fn default_auto_recall_enabled() -> bool { true }
+fn default_auto_recall_top_k() -> usize { 5 }
+fn default_auto_recall_min_score() -> f64 { 0.3 }
+
+impl Default for AutoRecallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_auto_recall_enabled(),
+            top_k: default_auto_recall_top_k(),
+            min_score: default_auto_recall_min_score(),
+        }
+    }
+}
+
+/// End-of-day usage summary pushed to a notification channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageReportConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local time of day to send at, "HH:MM"
+    #[serde(default = "default_usage_report_time")]
+    pub time: String,
+    /// Notification channel: "feishu", "dingtalk", "smtp" or "wecom"
+    #[serde(default = "default_usage_report_channel")]
+    pub channel: String,
+}
+
+fn default_usage_report_time() -> String { "21:00".to_string() }
+fn default_usage_report_channel() -> String { "feishu".to_string() }
+
+impl Default for UsageReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: default_usage_report_time(),
+            channel: default_usage_report_channel(),
+        }
+    }
+}
+
+/// Periodic HEARTBEAT.md check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeartbeatConfig {
+    #[serde(default = "default_heartbeat_enabled")]
+    pub enabled: bool,
+    /// Minutes between heartbeats
+    #[serde(default = "default_heartbeat_interval_minutes")]
+    pub interval_minutes: u32,
+    /// Run once on resume if a heartbeat was due while the machine slept
+    #[serde(default)]
+    pub catch_up: bool,
+}
+
+fn default_heartbeat_enabled() -> bool { true }
+fn default_heartbeat_interval_minutes() -> u32 { 30 }
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_heartbeat_enabled(),
+            interval_minutes: default_heartbeat_interval_minutes(),
+            catch_up: false,
+        }
+    }
+}
+
+/// Background loop behaviour around suspend/resume
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchedulerConfig {
+    /// Delay between cron tasks started together after a resume
+    #[serde(default = "default_resume_stagger_secs")]
+    pub resume_stagger_secs: u64,
+}
+
+fn default_resume_stagger_secs() -> u64 { 10 }
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self { resume_stagger_secs: default_resume_stagger_secs() }
+    }
+}
+
+/// Native desktop notifications for inbound channel messages
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DesktopNotificationsConfig {
+    #[serde(default = "default_desktop_notifications_enabled")]
+    pub enabled: bool,
+    /// Channel id ("wechat", "feishu", ...) → enabled; unlisted channels notify
+    #[serde(default)]
+    pub channels: HashMap<String, bool>,
+    /// Local "HH:MM" start of the daily quiet period (may wrap past midnight)
+    #[serde(default)]
+    pub quiet_hours_start: Option<String>,
+    /// Local "HH:MM" end of the quiet period
+    #[serde(default)]
+    pub quiet_hours_end: Option<String>,
+}
+
+fn default_desktop_notifications_enabled() -> bool { true }
+
+impl Default for DesktopNotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_desktop_notifications_enabled(),
+            channels: HashMap::new(),
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+        }
+    }
+}
+
+/// Token price in USD per 1M tokens
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_m: f64,
+    pub output_per_m: f64,
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -68,7 +271,48 @@ pub struct AppConfig {
     #[serde(default)]
     pub search_api_key: Option<String>, // Brave Search API key
     #[serde(default)]
+    pub github_token: Option<String>, // GitHub token for update checks (avoids API rate limits)
+    #[serde(default)]
     pub app_avatar_url: Option<String>, // User's custom app avatar (data URI or URL)
+    #[serde(default)]
+    pub tool_policy: ToolPolicyConfig, // Opt-in agent tools (kubectl, ...)
+    #[serde(default = "default_global_hotkey")]
+    pub global_hotkey: Option<String>, // Show/hide main window shortcut (None = disabled)
+    // New installs get default_quick_ask_hotkey(); existing configs stay unbound
+    #[serde(default)]
+    pub quick_ask_hotkey: Option<String>, // Quick-ask window shortcut (None = disabled)
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelPrice>, // Price overrides keyed by model name prefix
+    #[serde(default)]
+    pub provider_keys: HashMap<String, Vec<String>>, // Legacy extra API keys per provider, moved into `providers` on next save
+    #[serde(default)]
+    pub providers: Vec<NamedProvider>, // Named provider credentials with key rotation
+    #[serde(default)]
+    pub ai_retry: AiRetryConfig, // Retry/backoff for transient provider errors
+    #[serde(default)]
+    pub auto_recall: AutoRecallConfig, // Inject relevant memories into the agent prompt
+    #[serde(default)]
+    pub usage_report: UsageReportConfig, // Daily usage summary notification
+    #[serde(default = "default_cron_max_runs")]
+    pub cron_max_runs: usize, // Run history kept per cron task (0 = keep all)
+    #[serde(default)]
+    pub heartbeat: HeartbeatConfig, // HEARTBEAT.md check cadence
+    #[serde(default)]
+    pub scheduler: SchedulerConfig, // Suspend/resume handling of background loops
+    #[serde(default)]
+    pub desktop_notifications: DesktopNotificationsConfig, // Native notifications for inbound messages
+}
+
+fn default_cron_max_runs() -> usize {
+    100
+}
+
+fn default_global_hotkey() -> Option<String> {
+    Some("CmdOrCtrl+Shift+H".to_string())
+}
+
+fn default_quick_ask_hotkey() -> Option<String> {
+    Some("CmdOrCtrl+Shift+Space".to_string())
 }
 
 impl AppConfig {
@@ -87,7 +331,21 @@ impl AppConfig {
             ai_config: AiModelConfig::default(),
             notifications: None,
             search_api_key: None,
+            github_token: None,
             app_avatar_url: None,
+            tool_policy: ToolPolicyConfig::default(),
+            global_hotkey: default_global_hotkey(),
+            quick_ask_hotkey: default_quick_ask_hotkey(),
+            model_pricing: HashMap::new(),
+            provider_keys: HashMap::new(),
+            providers: Vec::new(),
+            ai_retry: AiRetryConfig::default(),
+            auto_recall: AutoRecallConfig::default(),
+            usage_report: UsageReportConfig::default(),
+            cron_max_runs: default_cron_max_runs(),
+            heartbeat: HeartbeatConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            desktop_notifications: DesktopNotificationsConfig::default(),
         }
     }
 }
@@ -0,0 +1,397 @@
+//! Tool Artifacts — large tool outputs kept on disk instead of in the context.
+//!
+//! A tool result longer than [`ARTIFACT_THRESHOLD_CHARS`] is written to
+//! `~/.helix/artifacts/<session>/<run>/<n>_<tool>.txt` with a row in the
+//! `artifacts` table of helix.db. The model only sees a head/tail preview and
+//! the artifact id, and re-opens the rest with the `artifact_read` tool.
+//! Artifacts older than [`RETENTION_DAYS`] are removed by the scheduler.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::modules::config::get_data_dir;
+
+/// Tool outputs longer than this (in characters) become artifacts.
+const ARTIFACT_THRESHOLD_CHARS: usize = 4_000;
+/// Characters kept from the start and the end of an output in its preview.
+const PREVIEW_HEAD_CHARS: usize = 1_000;
+const PREVIEW_TAIL_CHARS: usize = 500;
+/// Characters returned by one `artifact_read` call unless a limit is given.
+const DEFAULT_READ_CHARS: usize = 4_000;
+/// Artifacts are deleted after this many days.
+pub const RETENTION_DAYS: u32 = 14;
+
+/// Tools whose output is never turned into an artifact.
+const EXEMPT_TOOLS: &[&str] = &["artifact_read"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Artifact {
+    pub id: i64,
+    pub session_id: String,
+    pub run_id: i64,
+    /// Position of the artifact within its run, starting at 1
+    pub seq: i64,
+    pub tool: String,
+    pub path: String,
+    /// Size of the full output in bytes
+    pub size: i64,
+    pub preview: String,
+    pub created_at: String,
+}
+
+// ============================================================================
+// Database
+// ============================================================================
+
+static ARTIFACT_DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
+    let conn = open_artifact_db().expect("Failed to open artifact database");
+    Mutex::new(conn)
+});
+
+fn open_artifact_db() -> Result<Connection, String> {
+    let data_dir = get_data_dir()?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("create dir: {}", e))?;
+    let db_path = data_dir.join("helix.db");
+    let conn = Connection::open(&db_path).map_err(|e| format!("open DB: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")
+        .map_err(|e| format!("pragmas: {}", e))?;
+    Ok(conn)
+}
+
+const ARTIFACT_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS artifacts (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id  TEXT NOT NULL,
+        run_id      INTEGER NOT NULL,
+        seq         INTEGER NOT NULL,
+        tool        TEXT NOT NULL,
+        path        TEXT NOT NULL,
+        size        INTEGER NOT NULL,
+        preview     TEXT NOT NULL,
+        created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_artifacts_session ON artifacts(session_id, run_id);
+    CREATE INDEX IF NOT EXISTS idx_artifacts_created ON artifacts(created_at);
+";
+
+pub fn init_artifact_tables() -> Result<(), String> {
+    let conn = ARTIFACT_DB.lock();
+    conn.execute_batch(ARTIFACT_SCHEMA)
+        .map_err(|e| format!("create artifact tables: {}", e))?;
+    info!("Artifact tables initialized");
+    Ok(())
+}
+
+/// Root directory of artifact files (`~/.helix/artifacts`).
+fn artifacts_root() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|h| h.join(".helix").join("artifacts"))
+        .ok_or_else(|| "Cannot determine home directory".to_string())
+}
+
+const SELECT_COLUMNS: &str = "id, session_id, run_id, seq, tool, path, size, preview, created_at";
+
+fn row_to_artifact(row: &rusqlite::Row) -> rusqlite::Result<Artifact> {
+    Ok(Artifact {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        run_id: row.get(2)?,
+        seq: row.get(3)?,
+        tool: row.get(4)?,
+        path: row.get(5)?,
+        size: row.get(6)?,
+        preview: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// Keep a path component to `[A-Za-z0-9_-]` so session ids and tool names
+/// cannot escape the artifact directory.
+fn sanitize_component(raw: &str) -> String {
+    let cleaned: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() {
+        "_".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn artifact_path(root: &Path, session_id: &str, run_id: u64, seq: i64, tool: &str) -> PathBuf {
+    root.join(sanitize_component(session_id))
+        .join(run_id.to_string())
+        .join(format!("{}_{}.txt", seq, sanitize_component(tool)))
+}
+
+/// First and last characters of `output`, with the number of omitted characters in between.
+fn build_preview(output: &str) -> String {
+    let total = output.chars().count();
+    if total <= PREVIEW_HEAD_CHARS + PREVIEW_TAIL_CHARS {
+        return output.to_string();
+    }
+    let head: String = output.chars().take(PREVIEW_HEAD_CHARS).collect();
+    let tail: String = output.chars().skip(total - PREVIEW_TAIL_CHARS).collect();
+    format!(
+        "{}\n[... {} characters omitted ...]\n{}",
+        head,
+        total - PREVIEW_HEAD_CHARS - PREVIEW_TAIL_CHARS,
+        tail
+    )
+}
+
+/// What the model sees in place of the full output.
+fn inline_reference(artifact: &Artifact, total_chars: usize) -> String {
+    format!(
+        "{}\n\n[Full output ({} characters) saved as artifact #{}; call artifact_read with id={} and offset/limit to read more]",
+        artifact.preview, total_chars, artifact.id, artifact.id
+    )
+}
+
+fn store_in(
+    conn: &Connection,
+    root: &Path,
+    session_id: &str,
+    run_id: u64,
+    tool: &str,
+    output: &str,
+) -> Result<Artifact, String> {
+    let seq: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(seq), 0) + 1 FROM artifacts WHERE session_id = ?1 AND run_id = ?2",
+            params![session_id, run_id as i64],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("next artifact seq: {}", e))?;
+
+    let path = artifact_path(root, session_id, run_id, seq, tool);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("create artifact dir: {}", e))?;
+    }
+    std::fs::write(&path, output).map_err(|e| format!("write artifact: {}", e))?;
+
+    let path_str = path.to_string_lossy().to_string();
+    let preview = build_preview(output);
+    conn.execute(
+        "INSERT INTO artifacts (session_id, run_id, seq, tool, path, size, preview) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![session_id, run_id as i64, seq, tool, path_str, output.len() as i64, preview],
+    )
+    .map_err(|e| format!("insert artifact: {}", e))?;
+
+    get_in(conn, conn.last_insert_rowid())?.ok_or_else(|| "artifact vanished after insert".to_string())
+}
+
+fn get_in(conn: &Connection, id: i64) -> Result<Option<Artifact>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM artifacts WHERE id = ?1", SELECT_COLUMNS),
+        params![id],
+        row_to_artifact,
+    )
+    .optional()
+    .map_err(|e| format!("get artifact: {}", e))
+}
+
+fn list_in(conn: &Connection, session_id: &str) -> Result<Vec<Artifact>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM artifacts WHERE session_id = ?1 ORDER BY run_id DESC, seq ASC",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| format!("prepare: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id], row_to_artifact)
+        .map_err(|e| format!("list artifacts: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("read artifact row: {}", e))
+}
+
+/// Delete artifacts older than `days` and their files; returns how many were removed.
+fn cleanup_in(conn: &Connection, days: u32) -> Result<usize, String> {
+    let modifier = format!("-{} days", days);
+    let expired: Vec<String> = {
+        let mut stmt = conn
+            .prepare("SELECT path FROM artifacts WHERE created_at < datetime('now', ?1)")
+            .map_err(|e| format!("prepare: {}", e))?;
+        let rows = stmt
+            .query_map(params![modifier], |row| row.get(0))
+            .map_err(|e| format!("list expired artifacts: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("read artifact row: {}", e))?
+    };
+
+    for path in &expired {
+        let path = Path::new(path);
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("[artifacts] Failed to delete {}: {}", path.display(), e);
+            }
+        }
+        // Run and session directories go once empty; remove_dir fails otherwise
+        if let Some(run_dir) = path.parent() {
+            if std::fs::remove_dir(run_dir).is_ok() {
+                if let Some(session_dir) = run_dir.parent() {
+                    let _ = std::fs::remove_dir(session_dir);
+                }
+            }
+        }
+    }
+
+    conn.execute(
+        "DELETE FROM artifacts WHERE created_at < datetime('now', ?1)",
+        params![modifier],
+    )
+    .map_err(|e| format!("delete expired artifacts: {}", e))?;
+    Ok(expired.len())
+}
+
+/// Characters `offset..offset+limit` of `content`, and the total character count.
+fn read_slice(content: &str, offset: usize, limit: usize) -> (String, usize) {
+    let total = content.chars().count();
+    (content.chars().skip(offset).take(limit).collect(), total)
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+/// Store `output` of `tool` as an artifact of the current run if it is large.
+/// Returns the preview-plus-reference text to hand to the model instead, or
+/// None when the output stays inline (small, exempt, outside a run, or on a
+/// storage error).
+pub fn persist_if_large(tool: &str, output: &str) -> Option<String> {
+    if EXEMPT_TOOLS.contains(&tool) {
+        return None;
+    }
+    let total_chars = output.chars().count();
+    if total_chars <= ARTIFACT_THRESHOLD_CHARS {
+        return None;
+    }
+    let session_id = super::core::SESSION_ACCOUNT_ID.try_with(|a| a.clone()).ok()?;
+    let run_id = super::core::session_run_id();
+
+    let stored = artifacts_root().and_then(|root| {
+        let conn = ARTIFACT_DB.lock();
+        store_in(&conn, &root, &session_id, run_id, tool, output)
+    });
+    match stored {
+        Ok(artifact) => {
+            info!("[artifacts] {} output ({} chars) saved as #{}", tool, total_chars, artifact.id);
+            Some(inline_reference(&artifact, total_chars))
+        }
+        Err(e) => {
+            warn!("[artifacts] Keeping {} output inline: {}", tool, e);
+            None
+        }
+    }
+}
+
+/// Remove artifacts past [`RETENTION_DAYS`]; run periodically by the scheduler.
+pub fn cleanup_old_artifacts() -> Result<usize, String> {
+    let removed = cleanup_in(&ARTIFACT_DB.lock(), RETENTION_DAYS)?;
+    if removed > 0 {
+        info!("[artifacts] Removed {} expired artifacts", removed);
+    }
+    Ok(removed)
+}
+
+/// `artifact_read` tool: a character range of an artifact of the current session.
+pub fn tool_artifact_read(args: &serde_json::Value) -> Result<String, String> {
+    let id = args["id"].as_i64().ok_or("Missing 'id'")?;
+    let offset = args["offset"].as_u64().unwrap_or(0) as usize;
+    let limit = args["limit"].as_u64().map(|l| l as usize).unwrap_or(DEFAULT_READ_CHARS).max(1);
+
+    let session_id = super::core::SESSION_ACCOUNT_ID
+        .try_with(|a| a.clone())
+        .map_err(|_| "artifact_read is only available inside an agent run".to_string())?;
+    let artifact = get_in(&ARTIFACT_DB.lock(), id)?
+        .filter(|a| a.session_id == session_id)
+        .ok_or_else(|| format!("Artifact #{} not found in this session", id))?;
+
+    let content = std::fs::read_to_string(&artifact.path)
+        .map_err(|e| format!("Artifact #{} is no longer available: {}", id, e))?;
+    let (slice, total) = read_slice(&content, offset, limit);
+    let end = (offset + slice.chars().count()).min(total);
+    let mut out = format!("[artifact #{} from {}: characters {}-{} of {}]\n{}", id, artifact.tool, offset, end, total, slice);
+    if end < total {
+        out.push_str(&format!("\n[... more available; next offset={}]", end));
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Artifacts of a session, newest run first.
+#[tauri::command]
+pub fn artifacts_list(session_id: String) -> Result<Vec<Artifact>, String> {
+    list_in(&ARTIFACT_DB.lock(), &session_id)
+}
+
+/// Full content of an artifact.
+#[tauri::command]
+pub async fn artifacts_read(id: i64) -> Result<String, String> {
+    let artifact = get_in(&ARTIFACT_DB.lock(), id)?.ok_or_else(|| format!("Artifact #{} not found", id))?;
+    tokio::fs::read_to_string(&artifact.path)
+        .await
+        .map_err(|e| format!("Artifact #{} is no longer available: {}", id, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_and_path() {
+        let short = "x".repeat(PREVIEW_HEAD_CHARS + PREVIEW_TAIL_CHARS);
+        assert_eq!(build_preview(&short), short);
+
+        let long = format!("{}{}{}", "a".repeat(PREVIEW_HEAD_CHARS), "文".repeat(300), "z".repeat(PREVIEW_TAIL_CHARS));
+        let preview = build_preview(&long);
+        assert!(preview.starts_with(&"a".repeat(PREVIEW_HEAD_CHARS)));
+        assert!(preview.contains("\n[... 300 characters omitted ...]\n"));
+        assert!(preview.ends_with(&"z".repeat(PREVIEW_TAIL_CHARS)));
+
+        assert_eq!(
+            artifact_path(Path::new("/a"), "wx:../room", 7, 2, "shell_exec"),
+            PathBuf::from("/a/wx____room/7/2_shell_exec.txt")
+        );
+        assert_eq!(read_slice("héllo", 1, 3), ("éll".to_string(), 5));
+    }
+
+    #[test]
+    fn test_store_list_and_cleanup() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(ARTIFACT_SCHEMA).unwrap();
+        let root = std::env::temp_dir().join(format!("helix-artifacts-{}", std::process::id()));
+        let output = "line\n".repeat(2_000);
+
+        let first = store_in(&conn, &root, "s1", 3, "shell_exec", &output).unwrap();
+        let second = store_in(&conn, &root, "s1", 3, "web_fetch", &output).unwrap();
+        store_in(&conn, &root, "s2", 4, "file_read", &output).unwrap();
+        assert_eq!((first.seq, second.seq), (1, 2));
+        assert_eq!(std::fs::read_to_string(&second.path).unwrap(), output);
+        assert!(inline_reference(&first, 10_000).contains(&format!("artifact #{}", first.id)));
+
+        let listed = list_in(&conn, "s1").unwrap();
+        assert_eq!(listed.iter().map(|a| a.tool.as_str()).collect::<Vec<_>>(), vec!["shell_exec", "web_fetch"]);
+
+        conn.execute("UPDATE artifacts SET created_at = datetime('now', '-30 days') WHERE session_id = 's1'", [])
+            .unwrap();
+        assert_eq!(cleanup_in(&conn, RETENTION_DAYS).unwrap(), 2);
+        assert!(list_in(&conn, "s1").unwrap().is_empty());
+        assert!(!root.join("s1").exists());
+        assert_eq!(list_in(&conn, "s2").unwrap().len(), 1);
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
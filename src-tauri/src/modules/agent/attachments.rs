@@ -0,0 +1,258 @@
+//! Context file attachments — files whose extracted content is injected into
+//! the prompt ahead of the user message.
+//!
+//! Paths come from `agent_chat(attachments)`, or are queued per session with
+//! [`queue_pending`] when a channel receives a file, so that a PDF followed by
+//! a question in the next message is answered against the document.
+//! Extraction goes through `media_understanding::extract_text` under a
+//! per-file and a per-message budget; what was included or skipped is kept as
+//! an [`AttachmentReport`] for the reply.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::modules::media_understanding;
+use crate::utils::truncate::safe_truncate;
+
+/// Characters of extracted text kept per file.
+const MAX_FILE_CHARS: usize = 20_000;
+/// Characters of extracted text kept across all attachments of one message.
+const MAX_TOTAL_CHARS: usize = 60_000;
+/// Files larger than this are not extracted at all.
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+/// Received files are dropped if no message follows within this time.
+const PENDING_TTL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IncludedAttachment {
+    pub path: String,
+    pub name: String,
+    /// "text", "pdf", "docx" or "image"
+    pub kind: String,
+    /// Characters injected into the prompt
+    pub chars: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedAttachment {
+    pub path: String,
+    pub reason: String,
+}
+
+/// What happened to the attachments of one message; returned with the reply.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AttachmentReport {
+    pub included: Vec<IncludedAttachment>,
+    pub skipped: Vec<SkippedAttachment>,
+}
+
+/// Extracted attachments of one message.
+#[derive(Debug, Default)]
+pub struct Attachments {
+    /// Prompt section with one labeled block per included file; empty if none
+    pub context: String,
+    pub report: AttachmentReport,
+}
+
+impl Attachments {
+    /// Short note stored with the user message in history, e.g. `[附件: a.pdf, b.png]`.
+    pub fn history_note(&self) -> Option<String> {
+        if self.report.included.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = self.report.included.iter().map(|a| a.name.as_str()).collect();
+        Some(format!("[附件: {}]", names.join(", ")))
+    }
+}
+
+/// A received file path and when it arrived.
+type PendingFile = (String, Instant);
+
+/// Files received per session, waiting for the next message.
+static PENDING: Lazy<Mutex<HashMap<String, Vec<PendingFile>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Report of the last message per session, until the caller takes it.
+static REPORTS: Lazy<Mutex<HashMap<String, AttachmentReport>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Attach `path` to the next message processed for `session_key`.
+pub fn queue_pending(session_key: &str, path: &str) {
+    PENDING
+        .lock()
+        .entry(session_key.to_string())
+        .or_default()
+        .push((path.to_string(), Instant::now()));
+}
+
+/// Files queued for `session_key` that the next message would pick up, left in the queue.
+pub fn peek_pending(session_key: &str) -> Vec<String> {
+    PENDING
+        .lock()
+        .get(session_key)
+        .map(|queued| {
+            queued
+                .iter()
+                .filter(|(_, at)| at.elapsed() < PENDING_TTL)
+                .map(|(path, _)| path.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Take the files queued for `session_key`, dropping those older than [`PENDING_TTL`].
+pub fn take_pending(session_key: &str) -> Vec<String> {
+    let queued = PENDING.lock().remove(session_key).unwrap_or_default();
+    queued
+        .into_iter()
+        .filter(|(_, at)| at.elapsed() < PENDING_TTL)
+        .map(|(path, _)| path)
+        .collect()
+}
+
+pub fn store_report(session_key: &str, report: AttachmentReport) {
+    REPORTS.lock().insert(session_key.to_string(), report);
+}
+
+/// Get and clear the attachment report of the last message for a session.
+pub fn take_report_for(session_key: &str) -> Option<AttachmentReport> {
+    REPORTS.lock().remove(session_key)
+}
+
+/// `~` is expanded and relative paths are resolved against the workspace.
+fn resolve_path(raw: &str, workspace: Option<&str>) -> PathBuf {
+    let path = PathBuf::from(super::tools::expand_path(raw.trim()));
+    match workspace {
+        Some(ws) if path.is_relative() => Path::new(&super::tools::expand_path(ws)).join(path),
+        _ => path,
+    }
+}
+
+/// Cut `text` to the per-file limit and what is left of the message budget.
+fn fit_to_budget(text: &str, remaining: usize) -> (&str, bool) {
+    let kept = safe_truncate(text, MAX_FILE_CHARS.min(remaining));
+    (kept, kept.len() < text.len())
+}
+
+async fn extract_one(path: &Path, remaining: usize) -> Result<(&'static str, String), String> {
+    let meta = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("Cannot access: {}", e))?;
+    if !meta.is_file() {
+        return Err("Not a file".into());
+    }
+    if meta.len() > MAX_FILE_BYTES {
+        return Err(format!("Too large: {} bytes (limit {})", meta.len(), MAX_FILE_BYTES));
+    }
+    if remaining == 0 {
+        return Err(format!("Attachment budget of {} characters used up", MAX_TOTAL_CHARS));
+    }
+    media_understanding::extract_text(&path.to_string_lossy()).await
+}
+
+/// Extract `paths` into labeled prompt blocks, in order, within the budgets.
+pub async fn extract_attachments(paths: &[String], workspace: Option<&str>) -> Attachments {
+    let mut remaining = MAX_TOTAL_CHARS;
+    let mut blocks = Vec::new();
+    let mut report = AttachmentReport::default();
+    let mut seen = HashSet::new();
+
+    for raw in paths {
+        let path = resolve_path(raw, workspace);
+        let file_label = path.to_string_lossy().to_string();
+        if !seen.insert(file_label.clone()) {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file")
+            .to_string();
+
+        let (kind, text) = match extract_one(&path, remaining).await {
+            Ok((_, text)) if text.trim().is_empty() => {
+                report.skipped.push(SkippedAttachment { path: file_label, reason: "No extractable text".into() });
+                continue;
+            }
+            Ok(extracted) => extracted,
+            Err(reason) => {
+                warn!("[attachments] Skipped {}: {}", file_label, reason);
+                report.skipped.push(SkippedAttachment { path: file_label, reason });
+                continue;
+            }
+        };
+
+        let text = text.trim();
+        let (kept, truncated) = fit_to_budget(text, remaining);
+        let chars = kept.chars().count();
+        remaining -= chars;
+
+        let mut block = format!("<attachment name=\"{}\" type=\"{}\">\n{}", name, kind, kept);
+        if truncated {
+            block.push_str(&format!("\n[截断，共 {} 字符]", text.chars().count()));
+        }
+        block.push_str("\n</attachment>");
+        blocks.push(block);
+
+        report.included.push(IncludedAttachment { path: file_label, name, kind: kind.to_string(), chars, truncated });
+    }
+
+    let context = if blocks.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "## Attached Files\nThe user attached these files; images are given as descriptions.\n\n{}",
+            blocks.join("\n\n")
+        )
+    };
+    Attachments { context, report }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_to_budget_and_resolve_path() {
+        let long = "文".repeat(MAX_FILE_CHARS + 5);
+        let (kept, truncated) = fit_to_budget(&long, MAX_TOTAL_CHARS);
+        assert_eq!(kept.chars().count(), MAX_FILE_CHARS);
+        assert!(truncated);
+        assert_eq!(fit_to_budget("short", 3), ("sho", true));
+        assert_eq!(fit_to_budget("short", 100), ("short", false));
+
+        assert_eq!(resolve_path("docs/a.md", Some("/ws")), PathBuf::from("/ws/docs/a.md"));
+        assert_eq!(resolve_path("/abs/a.md", Some("/ws")), PathBuf::from("/abs/a.md"));
+        assert_eq!(resolve_path("a.md", None), PathBuf::from("a.md"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_attachments_reports_skipped() {
+        let dir = std::env::temp_dir().join(format!("helix-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.md"), "  deploy on friday \n").unwrap();
+        std::fs::write(dir.join("blob.bin"), [0u8, 159, 146, 150]).unwrap();
+
+        let paths = vec!["notes.md".to_string(), "missing.txt".to_string(), "blob.bin".to_string(), "notes.md".to_string()];
+        let attached = extract_attachments(&paths, Some(&dir.to_string_lossy())).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(attached.report.included.len(), 1);
+        assert_eq!(attached.report.included[0].kind, "text");
+        assert!(attached.context.contains("<attachment name=\"notes.md\" type=\"text\">\ndeploy on friday\n</attachment>"));
+        let skipped: Vec<&str> = attached.report.skipped.iter().map(|s| s.path.rsplit('/').next().unwrap()).collect();
+        assert_eq!(skipped, vec!["missing.txt", "blob.bin"]);
+        assert_eq!(attached.history_note().as_deref(), Some("[附件: notes.md]"));
+    }
+
+    #[test]
+    fn test_pending_attachments_are_taken_once() {
+        queue_pending("attach-test", "/tmp/a.pdf");
+        queue_pending("attach-test", "/tmp/b.docx");
+        assert_eq!(take_pending("attach-test"), vec!["/tmp/a.pdf", "/tmp/b.docx"]);
+        assert!(take_pending("attach-test").is_empty());
+    }
+}
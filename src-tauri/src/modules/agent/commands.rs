@@ -2,9 +2,16 @@
 //! text alias detection, and skill-contributed commands.
 //!
 //! Ported from OpenClaw `src/auto-reply/commands-registry.ts`.
+//!
+//! User-defined commands live in `~/.helix/commands/<name>.toml` and render
+//! either a prompt (sent to the model in place of the `/command` line) or a
+//! shell script (run directly, arguments shell-quoted).
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::info;
 
 use super::{skills};
 use crate::modules::database;
@@ -100,9 +107,9 @@ pub fn get_builtin_commands() -> Vec<CommandDef> {
         CommandDef {
             key: "reset".into(),
             name: "重置对话".into(),
-            description: "清除当前对话历史".into(),
+            description: "清除当前对话历史和草稿板".into(),
             category: "session".into(),
-            aliases: vec!["clear".into()],
+            aliases: vec!["clear".into(), "new".into()],
             args: vec![],
             builtin: true,
             enabled: true,
@@ -354,6 +361,7 @@ fn try_text_alias(input: &str) -> Option<ParsedCommand> {
 pub fn execute_command(cmd: &ParsedCommand, account_id: &str) -> Option<String> {
     match cmd.key.as_str() {
         "reset" | "clear" => {
+            super::scratchpad::clear_session(account_id);
             if let Err(e) = database::clear_messages(account_id) {
                 Some(format!("❌ 清除失败: {}", e))
             } else {
@@ -523,6 +531,15 @@ fn build_help_text() -> String {
         }
     }
 
+    let custom = list_custom_commands();
+    if !custom.is_empty() {
+        output.push_str("✨ 自定义:\n");
+        for cmd in &custom {
+            output.push_str(&format!("  {} — {}\n", custom_usage(cmd).trim_start_matches("用法: "), cmd.description));
+        }
+        output.push('\n');
+    }
+
     output.push_str("🔧 Agent 工具:\n");
     output.push_str("  shell_exec, file_read, file_write, file_edit,\n");
     output.push_str("  list_dir, find_files, grep_search,\n");
@@ -593,22 +610,433 @@ fn handle_memo_command(cmd: &ParsedCommand, _account_id: &str) -> Option<String>
     }
 }
 
+// ============================================================================
+// Custom Commands (~/.helix/commands/*.toml)
+// ============================================================================
+
+/// Max runtime for a shell-kind custom command.
+const CUSTOM_SHELL_TIMEOUT_SECS: u64 = 120;
+/// Max characters of shell output returned.
+const CUSTOM_SHELL_OUTPUT_LIMIT: usize = 4000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomCommand {
+    /// Command key, typed as `/<name>`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// "prompt" (rendered and sent to the model) or "shell" (rendered and executed)
+    #[serde(default = "default_custom_kind")]
+    pub kind: String,
+    /// Template with `{{arg}}` placeholders; `{{args}}` is the raw argument string
+    pub template: String,
+    /// Declared arguments, bound positionally in this order
+    #[serde(default)]
+    pub args: Vec<CommandArgDef>,
+}
+
+fn default_custom_kind() -> String {
+    "prompt".to_string()
+}
+
+/// What a custom command resolves to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomResolution {
+    /// Rendered prompt to send to the model instead of the `/command` line
+    Prompt(String),
+    /// Rendered shell script to run
+    Shell(String),
+}
+
+fn get_commands_dir() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(home.join(".helix").join("commands"))
+}
+
+/// Names are lowercase `[a-z0-9_-]` and must not shadow a built-in command or alias.
+fn validate_custom_command(cmd: &CustomCommand) -> Result<(), String> {
+    let name = cmd.name.as_str();
+    if name.is_empty()
+        || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid command name '{}': use lowercase letters, digits, '-' or '_'", name));
+    }
+    if get_builtin_commands()
+        .iter()
+        .any(|b| b.key == name || b.aliases.iter().any(|a| a == name))
+    {
+        return Err(format!("'/{}' is a built-in command", name));
+    }
+    if !matches!(cmd.kind.as_str(), "prompt" | "shell") {
+        return Err(format!("Invalid kind '{}': use prompt or shell", cmd.kind));
+    }
+    if cmd.template.trim().is_empty() {
+        return Err("Template cannot be empty".to_string());
+    }
+    let mut seen = std::collections::HashSet::new();
+    for arg in &cmd.args {
+        if arg.name.is_empty() || arg.name == "args" || !seen.insert(arg.name.as_str()) {
+            return Err(format!("Invalid or duplicate argument name '{}'", arg.name));
+        }
+    }
+    Ok(())
+}
+
+pub fn list_custom_commands() -> Vec<CustomCommand> {
+    let Ok(dir) = get_commands_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut commands: Vec<CustomCommand> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(&p).ok()?;
+            match toml::from_str::<CustomCommand>(&content) {
+                Ok(cmd) => Some(cmd),
+                Err(e) => {
+                    tracing::warn!("Skipping invalid custom command {}: {}", p.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+pub fn get_custom_command(name: &str) -> Option<CustomCommand> {
+    list_custom_commands().into_iter().find(|c| c.name == name)
+}
+
+/// Create or overwrite a custom command.
+pub fn save_custom_command(cmd: &CustomCommand) -> Result<(), String> {
+    validate_custom_command(cmd)?;
+    let dir = get_commands_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create commands dir: {}", e))?;
+    let content = toml::to_string_pretty(cmd).map_err(|e| format!("Failed to serialize command: {}", e))?;
+    std::fs::write(dir.join(format!("{}.toml", cmd.name)), content)
+        .map_err(|e| format!("Failed to write command: {}", e))?;
+    info!("Saved custom command /{}", cmd.name);
+    Ok(())
+}
+
+pub fn delete_custom_command(name: &str) -> Result<(), String> {
+    let path = get_commands_dir()?.join(format!("{}.toml", name));
+    if get_custom_command(name).is_none() || !path.exists() {
+        return Err(format!("Custom command '/{}' not found", name));
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete command: {}", e))?;
+    info!("Deleted custom command /{}", name);
+    Ok(())
+}
+
+/// Bind positional and named values to the declared args: named values win,
+/// positionals fill the remaining args in order (the last arg takes any
+/// extra words), then defaults apply and required/choice/number checks run.
+pub fn bind_custom_args(
+    cmd: &CustomCommand,
+    positional: &[String],
+    named: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    if let Some(unknown) = named.keys().find(|k| !cmd.args.iter().any(|a| &a.name == *k)) {
+        return Err(format!("/{} has no argument '{}'", cmd.name, unknown));
+    }
+    let mut values: HashMap<String, String> = named.clone();
+    let open: Vec<&CommandArgDef> = cmd.args.iter().filter(|a| !named.contains_key(&a.name)).collect();
+    let mut rest = positional.iter();
+    for (i, arg) in open.iter().enumerate() {
+        let value = if i + 1 == open.len() {
+            rest.by_ref().cloned().collect::<Vec<_>>().join(" ")
+        } else {
+            rest.next().cloned().unwrap_or_default()
+        };
+        if !value.is_empty() {
+            values.insert(arg.name.clone(), value);
+        }
+    }
+    if rest.next().is_some() || (open.is_empty() && !positional.is_empty() && !cmd.args.is_empty()) {
+        return Err(format!("Too many arguments for /{}\n{}", cmd.name, custom_usage(cmd)));
+    }
+
+    for arg in &cmd.args {
+        if !values.contains_key(&arg.name) {
+            match &arg.default {
+                Some(default) => {
+                    values.insert(arg.name.clone(), default.clone());
+                }
+                None if arg.required => {
+                    return Err(format!("Missing argument '{}'\n{}", arg.name, custom_usage(cmd)));
+                }
+                None => {}
+            }
+        }
+        if let Some(value) = values.get(&arg.name) {
+            if !arg.choices.is_empty() && !arg.choices.contains(value) {
+                return Err(format!("'{}' must be one of: {}", arg.name, arg.choices.join(", ")));
+            }
+            if arg.arg_type == "number" && value.parse::<f64>().is_err() {
+                return Err(format!("'{}' must be a number, got '{}'", arg.name, value));
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn custom_usage(cmd: &CustomCommand) -> String {
+    let args: Vec<String> = cmd
+        .args
+        .iter()
+        .map(|a| if a.required { format!("<{}>", a.name) } else { format!("[{}]", a.name) })
+        .collect();
+    format!("用法: /{} {}", cmd.name, args.join(" ")).trim_end().to_string()
+}
+
+/// Single-quote a value for `sh`.
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Substitute `{{name}}` placeholders (and `{{args}}` with the raw argument string).
+/// Shell templates get every value shell-quoted.
+pub fn render_custom_template(cmd: &CustomCommand, values: &HashMap<String, String>, raw_args: &str) -> String {
+    let quote = |v: &str| if cmd.kind == "shell" { shell_quote(v) } else { v.to_string() };
+    let mut out = cmd.template.replace("{{args}}", &quote(raw_args));
+    for arg in &cmd.args {
+        let value = values.get(&arg.name).map(String::as_str).unwrap_or("");
+        out = out.replace(&format!("{{{{{}}}}}", arg.name), &quote(value));
+    }
+    out
+}
+
+/// Resolve a custom command invocation to its rendered prompt or script.
+pub fn resolve_custom(
+    cmd: &CustomCommand,
+    positional: &[String],
+    named: &HashMap<String, String>,
+    raw_args: &str,
+) -> Result<CustomResolution, String> {
+    let values = bind_custom_args(cmd, positional, named)?;
+    let rendered = render_custom_template(cmd, &values, raw_args);
+    Ok(match cmd.kind.as_str() {
+        "shell" => CustomResolution::Shell(rendered),
+        _ => CustomResolution::Prompt(rendered),
+    })
+}
+
+/// If `input` is `/name ...` for a custom command, resolve it. Errors (bad
+/// arguments) come back as a user-facing message.
+pub fn resolve_custom_input(input: &str) -> Option<Result<CustomResolution, String>> {
+    let ParsedInput::Command(parsed) = parse_input(input) else {
+        return None;
+    };
+    let cmd = get_custom_command(&parsed.key)?;
+    Some(resolve_custom(&cmd, &parsed.positional_args, &parsed.named_args, &parsed.raw_args))
+}
+
+/// Run a rendered shell command through the `shell_exec` runner (sandbox,
+/// cancellation, timeout) and return its (truncated) output.
+pub async fn run_custom_shell(script: &str) -> String {
+    let working_dir = super::tools::default_working_dir();
+    match super::tools::run_shell(script, &working_dir, CUSTOM_SHELL_TIMEOUT_SECS).await {
+        Ok((code, output)) => {
+            let icon = if code == 0 { "✅" } else { "❌" };
+            let text = output.trim();
+            let truncated = crate::utils::truncate::safe_truncate(text, CUSTOM_SHELL_OUTPUT_LIMIT);
+            format!(
+                "{} {}{}",
+                icon,
+                truncated,
+                if truncated.len() < text.len() { "..." } else { "" }
+            )
+        }
+        Err(e) => format!("❌ 执行失败: {}", e),
+    }
+}
+
+/// Built-in commands followed by the user's custom commands (category "custom").
+pub fn list_all_commands() -> Vec<CommandDef> {
+    let mut commands = get_builtin_commands();
+    commands.extend(list_custom_commands().into_iter().map(|c| CommandDef {
+        key: c.name.clone(),
+        name: c.name,
+        description: c.description,
+        category: "custom".into(),
+        aliases: vec![],
+        args: c.args,
+        builtin: false,
+        enabled: true,
+        auth_required: false,
+    }));
+    commands
+}
+
+/// Agent prompt section listing the user's custom commands.
+pub fn get_custom_commands_prompt() -> String {
+    let commands = list_custom_commands();
+    if commands.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from(
+        "## Custom Commands\n\
+         The user has defined these slash commands (already expanded before reaching you when typed directly). \
+         Suggest them when relevant:\n",
+    );
+    for cmd in &commands {
+        out.push_str(&format!("- `{}` — {} ({})\n", custom_usage(cmd).trim_start_matches("用法: "), cmd.description, cmd.kind));
+    }
+    out.trim_end().to_string()
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
 #[tauri::command]
 pub async fn commands_list() -> Result<Vec<CommandDef>, String> {
-    Ok(get_builtin_commands())
+    Ok(list_all_commands())
 }
 
+/// Run a command. `args` is either a raw argument string or an object of named args.
 #[tauri::command]
-pub async fn commands_execute(command: String, args: Option<String>, account_id: String) -> Result<Option<String>, String> {
+pub async fn commands_execute(command: String, args: Option<Value>, account_id: String) -> Result<Option<String>, String> {
+    let (raw_args, named_args) = match args {
+        Some(Value::Object(map)) => {
+            let named: HashMap<String, String> = map
+                .into_iter()
+                .map(|(k, v)| (k, v.as_str().map(String::from).unwrap_or_else(|| v.to_string())))
+                .collect();
+            (String::new(), named)
+        }
+        Some(Value::String(s)) => (s, HashMap::new()),
+        Some(Value::Null) | None => (String::new(), HashMap::new()),
+        Some(other) => return Err(format!("Invalid args: expected string or object, got {}", other)),
+    };
+    let positional_args: Vec<String> = raw_args.split_whitespace().map(|s| s.to_string()).collect();
+
+    if let Some(custom) = get_custom_command(&command) {
+        return match resolve_custom(&custom, &positional_args, &named_args, &raw_args)? {
+            CustomResolution::Shell(script) => Ok(Some(run_custom_shell(&script).await)),
+            CustomResolution::Prompt(prompt) => super::agent_process_message(&account_id, &prompt, None)
+                .await
+                .map(Some),
+        };
+    }
+
     let parsed = ParsedCommand {
         key: command.clone(),
-        raw_args: args.clone().unwrap_or_default(),
-        positional_args: args.map(|a| a.split_whitespace().map(|s| s.to_string()).collect()).unwrap_or_default(),
-        named_args: HashMap::new(),
+        raw_args,
+        positional_args,
+        named_args,
     };
     Ok(execute_command(&parsed, &account_id))
 }
+
+#[tauri::command]
+pub async fn commands_custom_list() -> Result<Vec<CustomCommand>, String> {
+    Ok(list_custom_commands())
+}
+
+/// Create a custom command; fails if the name is taken.
+#[tauri::command]
+pub async fn commands_custom_create(command: CustomCommand) -> Result<CustomCommand, String> {
+    if get_custom_command(&command.name).is_some() {
+        return Err(format!("Custom command '/{}' already exists", command.name));
+    }
+    save_custom_command(&command)?;
+    Ok(command)
+}
+
+#[tauri::command]
+pub async fn commands_custom_update(command: CustomCommand) -> Result<CustomCommand, String> {
+    if get_custom_command(&command.name).is_none() {
+        return Err(format!("Custom command '/{}' not found", command.name));
+    }
+    save_custom_command(&command)?;
+    Ok(command)
+}
+
+#[tauri::command]
+pub async fn commands_custom_delete(name: String) -> Result<(), String> {
+    delete_custom_command(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deploy() -> CustomCommand {
+        CustomCommand {
+            name: "deploy".into(),
+            description: "Deploy".into(),
+            kind: "shell".into(),
+            template: "./deploy.sh {{env}} {{note}}".into(),
+            args: vec![
+                CommandArgDef {
+                    name: "env".into(),
+                    description: String::new(),
+                    required: true,
+                    arg_type: "choice".into(),
+                    choices: vec!["staging".into(), "prod".into()],
+                    default: None,
+                },
+                CommandArgDef {
+                    name: "note".into(),
+                    description: String::new(),
+                    required: false,
+                    arg_type: "string".into(),
+                    choices: vec![],
+                    default: Some("none".into()),
+                },
+            ],
+        }
+    }
+
+    fn words(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_bind_custom_args() {
+        let cmd = deploy();
+        let v = bind_custom_args(&cmd, &words("staging hot fix"), &HashMap::new()).unwrap();
+        assert_eq!(v["env"], "staging");
+        assert_eq!(v["note"], "hot fix");
+
+        let v = bind_custom_args(&cmd, &[], &HashMap::from([("env".to_string(), "prod".to_string())])).unwrap();
+        assert_eq!(v["note"], "none");
+
+        assert!(bind_custom_args(&cmd, &[], &HashMap::new()).is_err());
+        assert!(bind_custom_args(&cmd, &words("qa"), &HashMap::new()).is_err());
+        assert!(bind_custom_args(&cmd, &[], &HashMap::from([("region".to_string(), "x".to_string())])).is_err());
+    }
+
+    #[test]
+    fn test_render_shell_template_quotes_values() {
+        let cmd = deploy();
+        let values = bind_custom_args(&cmd, &words("staging it's; rm -rf ~"), &HashMap::new()).unwrap();
+        assert_eq!(
+            render_custom_template(&cmd, &values, ""),
+            "./deploy.sh 'staging' 'it'\\''s; rm -rf ~'"
+        );
+
+        let prompt = CustomCommand { kind: "prompt".into(), template: "Deploy to {{env}}".into(), ..cmd };
+        let values = bind_custom_args(&prompt, &words("prod"), &HashMap::new()).unwrap();
+        assert_eq!(render_custom_template(&prompt, &values, "prod"), "Deploy to prod");
+    }
+
+    #[test]
+    fn test_custom_command_rejects_builtin_names() {
+        let mut cmd = deploy();
+        assert!(validate_custom_command(&cmd).is_ok());
+        cmd.name = "reset".into();
+        assert!(validate_custom_command(&cmd).is_err());
+        cmd.name = "clear".into(); // alias of reset
+        assert!(validate_custom_command(&cmd).is_err());
+        cmd.name = "Deploy".into();
+        assert!(validate_custom_command(&cmd).is_err());
+    }
+}
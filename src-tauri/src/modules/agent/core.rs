@@ -4,7 +4,7 @@
 
 use agents_sdk::{
     llm::{ChunkStream, LanguageModel, LlmRequest, LlmResponse},
-    messaging::{AgentMessage, ToolInvocation},
+    messaging::{AgentMessage, MessageContent, MessageRole, ToolInvocation},
     persistence::InMemoryCheckpointer,
     state::AgentStateSnapshot,
     ConfigurableAgentBuilder, OpenAiChatModel, OpenAiConfig,
@@ -16,56 +16,131 @@ use serde_json::{json, Value};
 use std::sync::Arc;
 use tracing::info;
 
+use super::progress::RunProgress;
+use crate::error::{codes, HelixError, HelixResult};
 use crate::modules::config::load_app_config;
 use crate::modules::database;
+use crate::modules::providers::{ProviderConfig, ProviderKind};
+use crate::modules::ai::chat::{is_transient_model_error, provider_error_from_sdk};
+use crate::modules::streaming::{stream_chat_completion, StreamEvent, StreamResult};
 
 use std::collections::HashMap;
 use std::sync::Mutex as StdMutex;
+use tokio_util::sync::CancellationToken;
 
-/// Per-session cancellation flags
-static CANCELLED_SESSIONS: std::sync::LazyLock<StdMutex<HashMap<String, bool>>> =
+/// In-flight agent runs `(run id, cancellation token)`, keyed by session (account) id
+static ACTIVE_RUNS: std::sync::LazyLock<StdMutex<HashMap<String, Vec<(u64, CancellationToken)>>>> =
     std::sync::LazyLock::new(|| StdMutex::new(HashMap::new()));
+static NEXT_RUN_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Messages that cancel the session's active run instead of starting a new one
+const STOP_WORDS: &[&str] = &["stop", "/stop", "取消", "停止"];
+
+/// Context window assumed for the main agent model (tokens)
+pub const AGENT_CONTEXT_LIMIT: usize = 131072;
+/// Conversation turns loaded into each agent input, including the new message
+const AGENT_HISTORY_LIMIT: i64 = 20;
+/// Recent history beyond this many characters is folded away
+const MAX_HISTORY_CHARS: usize = 32000;
 
 tokio::task_local! {
     /// Per-session workspace directory, accessible from tool closures
     pub static SESSION_WORKSPACE: Option<String>;
     /// Per-session account ID, accessible from tool closures
     pub static SESSION_ACCOUNT_ID: String;
+    /// Cancellation token of the current agent run, checked by tool wrappers
+    pub static SESSION_CANCEL: CancellationToken;
+    /// Structured progress stream of the current run (None for headless callers)
+    pub static SESSION_PROGRESS: Option<Arc<RunProgress>>;
+    /// Id of the current agent run, used to group its tool artifacts
+    pub static SESSION_RUN_ID: u64;
+    /// Set for runs started from the local UI; shell custom commands only run there
+    pub static SESSION_LOCAL: bool;
+    /// Set during dry-run replays; tool wrappers record calls without executing them
+    pub static SESSION_DRY_RUN: bool;
 }
 
 /// Cancel a running agent session
 #[tauri::command]
 pub fn agent_cancel(session_id: Option<String>) {
-    if let Ok(mut map) = CANCELLED_SESSIONS.lock() {
-        if let Some(sid) = session_id {
-            map.insert(sid, true);
-        } else {
-            // Cancel all sessions
-            for v in map.values_mut() {
-                *v = true;
+    if let Ok(map) = ACTIVE_RUNS.lock() {
+        match session_id {
+            Some(sid) => {
+                map.get(&sid).into_iter().flatten().for_each(|(_, t)| t.cancel());
             }
+            // Cancel all sessions
+            None => map.values().flatten().for_each(|(_, t)| t.cancel()),
         }
     }
     emit_agent_progress("cancelled", json!({}));
     info!("[agent] Cancellation requested");
 }
 
-/// Check if a session is cancelled
-fn is_session_cancelled(account_id: &str) -> bool {
-    CANCELLED_SESSIONS
-        .lock()
-        .ok()
-        .and_then(|m| m.get(account_id).copied())
-        .unwrap_or(false)
+/// Token of the agent run this task belongs to (a never-cancelled token outside a run).
+pub fn session_cancel_token() -> CancellationToken {
+    SESSION_CANCEL.try_with(|t| t.clone()).unwrap_or_default()
+}
+
+/// Id of the agent run this task belongs to (0 outside a run).
+pub fn session_run_id() -> u64 {
+    SESSION_RUN_ID.try_with(|id| *id).unwrap_or(0)
+}
+
+/// Whether a user message is a stop request ("stop" / "取消").
+fn is_stop_request(input: &str) -> bool {
+    let input = input.trim().to_lowercase();
+    STOP_WORDS.contains(&input.as_str())
+}
+
+/// Cancel the session's active runs, if any. Returns whether one was running.
+fn cancel_active_run(account_id: &str) -> bool {
+    let Ok(map) = ACTIVE_RUNS.lock() else {
+        return false;
+    };
+    let mut cancelled = false;
+    for (_, token) in map.get(account_id).into_iter().flatten() {
+        cancelled |= !token.is_cancelled();
+        token.cancel();
+    }
+    cancelled
+}
+
+/// Register a new run for the session.
+fn begin_run(account_id: &str) -> (u64, CancellationToken) {
+    let run_id = NEXT_RUN_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let token = CancellationToken::new();
+    if let Ok(mut map) = ACTIVE_RUNS.lock() {
+        map.entry(account_id.to_string()).or_default().push((run_id, token.clone()));
+    }
+    (run_id, token)
 }
 
-/// Reset cancellation flag for a session
-fn reset_session_cancelled(account_id: &str) {
-    if let Ok(mut map) = CANCELLED_SESSIONS.lock() {
-        map.insert(account_id.to_string(), false);
+/// Unregister a finished run.
+fn end_run(account_id: &str, run_id: u64) {
+    if let Ok(mut map) = ACTIVE_RUNS.lock() {
+        if let Some(runs) = map.get_mut(account_id) {
+            runs.retain(|(id, _)| *id != run_id);
+            if runs.is_empty() {
+                map.remove(account_id);
+            }
+        }
     }
 }
 
+/// Final response for a cancelled run, listing the tools that did complete.
+fn build_cancelled_response(completed: &[(String, String)]) -> String {
+    let mut out = String::from("🛑 已被用户取消 (cancelled by user)");
+    if !completed.is_empty() {
+        out.push_str("\n\n已完成的工具调用:");
+        for (name, output) in completed {
+            let preview = crate::utils::truncate::safe_truncate(output.trim(), 300);
+            let ellipsis = if preview.len() < output.trim().len() { "..." } else { "" };
+            out.push_str(&format!("\n- {}: {}{}", name, preview, ellipsis));
+        }
+    }
+    out
+}
+
 /// Copy a file from source to destination (used by file download card)
 #[tauri::command]
 pub async fn save_file_to(source: String, destination: String) -> Result<String, String> {
@@ -81,6 +156,18 @@ pub fn emit_agent_progress(event_type: &str, data: Value) {
     crate::modules::infra::log_bridge::emit_custom_event("agent-progress", payload);
 }
 
+/// Progress stream of the run this task belongs to, if events are enabled.
+pub fn session_progress() -> Option<Arc<RunProgress>> {
+    SESSION_PROGRESS.try_with(|p| p.clone()).ok().flatten()
+}
+
+/// Queue a structured `agent://progress/{request_id}` event for the current run.
+pub fn emit_run_event(event_type: &str, data: Value) {
+    if let Some(progress) = session_progress() {
+        progress.emit(event_type, data);
+    }
+}
+
 // ============================================================================
 // System Prompt Builder
 // ============================================================================
@@ -104,6 +191,7 @@ fn build_system_prompt(custom_prompt: &str, workspace: Option<&str>) -> String {
     };
 
     let skills_prompt = super::skills::get_enabled_skills_prompt();
+    let custom_commands_prompt = cmd_module::get_custom_commands_prompt();
 
     // Get MCP client descriptions for injection
     let mcp_prompt = crate::modules::mcp::get_enabled_mcp_tool_descriptions();
@@ -162,6 +250,7 @@ fn build_system_prompt(custom_prompt: &str, workspace: Option<&str>) -> String {
          - `list_dir` / `find_files` / `grep_search` — Explore and search the filesystem\n\n\
          ### Web & Search\n\
          - `web_fetch` — Download web content, call APIs\n\
+         - `http_test` — Check an API endpoint: status vs expected, latency, headers, JSON body preview\n\
          - `web_search` — Search the web\n\n\
          {}\
          ### Process Management\n\
@@ -172,9 +261,18 @@ fn build_system_prompt(custom_prompt: &str, workspace: Option<&str>) -> String {
          - `chat_send_file` — Send a file as a downloadable card in the chat\n\n\
          ### Utilities\n\
          - `get_current_time` — Get the current system time with timezone\n\
-         - `desktop_screenshot` — Capture a screenshot of the desktop\n\n\
+         - `clipboard_read` / `clipboard_write` — Read from or copy text to the system clipboard\n\
+         - `desktop_screenshot` — Capture a screenshot of the desktop\n\
+         - `ocr` — Extract text from an image (screenshots, scanned documents, received images)\n\
+         - `schedule_task` — Create a recurring task or reminder from a schedule like '每天早上8点' or a cron expression\n\
+         - `mcp_resource_fetch` — List or read resources exposed by connected MCP servers\n\
+         - `usage_query` — Look up token usage and cost (today, lifetime, per model, per day)\n\
+         - `artifact_read` — Read the rest of a long tool output that was saved as `artifact #id`\n\
+         - `scratchpad_set` / `scratchpad_get` / `scratchpad_list` — Keep short working notes for this conversation \
+         (plans, intermediate results); cleared by /new, not long-term memory\n\n\
          ### Browser Automation\n\
-         - `browser_use` — Control a browser: launch, goto(url), click(ref_id), fill(ref_id, text), snapshot, screenshot, stop\n\n\
+         - `browser_use` — Control a browser: launch, goto(url), click(ref_id), fill(ref_id, text), snapshot, screenshot, stop\n\
+         - `browser_render` — Render a JS-heavy page headlessly and get its readable text (+ optional screenshot); prefer it over web_fetch when the fetched HTML has no content\n\n\
          {}",
         memory_section, mcp_prompt
     ));
@@ -194,10 +292,51 @@ fn build_system_prompt(custom_prompt: &str, workspace: Option<&str>) -> String {
         sections.push(skills_prompt);
     }
 
+    if !custom_commands_prompt.is_empty() {
+        sections.push(custom_commands_prompt);
+    }
+
     if !mcp_prompt.is_empty() {
         sections.push(mcp_prompt);
     }
 
+    if let Ok(gh) = crate::modules::github::load_config() {
+        sections.push(format!(
+            "## GitHub\n\
+             - `github_create_issue` / `github_list_issues` — File and browse issues\n\
+             - `github_create_pr` / `github_get_pr_diff` — Open pull requests and review their diffs\n\
+             Default repository: `{}/{}` (pass `repo` as owner/repo to target another one)",
+            gh.default_owner, gh.default_repo
+        ));
+    }
+
+    let tool_policy = crate::modules::load_app_config().map(|c| c.tool_policy).unwrap_or_default();
+    if tool_policy.aliyun_enabled {
+        if let Ok(creds) = crate::modules::aliyun::load_credentials(None) {
+            sections.push(format!(
+                "## Aliyun\n\
+                 - `aliyun_ecs_list` — List ECS instances in a region\n\
+                 - `aliyun_oss_upload` — Upload a file to OSS and share it via a signed URL\n\
+                 Default region: `{}`",
+                creds.region_id
+            ));
+        }
+    }
+
+    if tool_policy.kubectl_enabled {
+        let contexts = crate::modules::kubeconfig::list_contexts(None).unwrap_or_default();
+        let names: Vec<String> = contexts
+            .iter()
+            .map(|c| if c.current { format!("`{}` (current)", c.name) } else { format!("`{}`", c.name) })
+            .collect();
+        sections.push(format!(
+            "## Kubernetes\n\
+             - `kubectl_exec` — Run kubectl against a kubeconfig context; prefer it over shell_exec for cluster queries\n\
+             Contexts: {}",
+            if names.is_empty() { "none found".to_string() } else { names.join(", ") }
+        ));
+    }
+
     sections.push(
         "## Response Guidelines\n\
          - When asked to do something on the system, USE YOUR TOOLS immediately\n\
@@ -442,6 +581,160 @@ Skills 定义工具怎么用。这文件记你的具体情况 — 你独有的
     }
 }
 
+// ============================================================================
+// Session Context
+// ============================================================================
+
+/// Model config used for `account_id`: a per-session override (set with
+/// `sessions_set_model`) wins over the global model, and brings its
+/// provider's endpoint and key along.
+fn session_model_config(
+    account_id: &str,
+    config: &crate::models::config::AppConfig,
+) -> Result<crate::models::config::AiModelConfig, String> {
+    match crate::modules::sessions::get_model_for_session(account_id) {
+        Some(model) => crate::modules::model_selection::override_model_config(&model, config),
+        None => Ok(config.ai_config.clone()),
+    }
+}
+
+/// Base system prompt plus the session's pinned files, scratchpad and
+/// auto-recalled memories. Returns the memories that were injected.
+async fn build_session_system_prompt(
+    account_id: &str,
+    user_input: &str,
+    config: &crate::models::config::AppConfig,
+    workspace: Option<&str>,
+) -> (String, Vec<super::memory::MemorySearchResult>) {
+    let mut system_prompt = build_system_prompt(&config.ai_config.system_prompt, workspace);
+    if let Some(pinned) = crate::modules::sessions::build_context_files_prompt(account_id) {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&pinned);
+    }
+    if let Some(scratchpad) = super::scratchpad::build_scratchpad_prompt(account_id) {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&scratchpad);
+    }
+    let mut injected = Vec::new();
+    let recall = super::memory::recall_for_prompt(user_input, account_id, &config.auto_recall).await;
+    if let Some((recalled, memories)) = recall {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&recalled);
+        injected = memories;
+    }
+    (system_prompt, injected)
+}
+
+/// The user message sent to the model: compressed summary, recent history
+/// (newest first until `MAX_HISTORY_CHARS`, oldest turn dropped), attachment
+/// contents and the new input. `history` is oldest first and already ends
+/// with the new message.
+fn build_agent_input(
+    history: &[database::ConversationEntry],
+    compressed_summary: Option<&str>,
+    attachment_context: Option<&str>,
+    user_input: &str,
+) -> String {
+    if history.len() <= 1 && compressed_summary.is_none() && attachment_context.is_none() {
+        return user_input.to_string();
+    }
+    let mut parts = Vec::new();
+
+    // Add compressed summary of older conversations (CoPaw-inspired memory compaction)
+    if let Some(summary) = compressed_summary {
+        parts.push(format!("## Previous Conversation Summary\n{}", summary));
+    }
+
+    // Add recent history with length limit (Antigravity Context Optimization)
+    if history.len() > 1 {
+        let mut context_lines = Vec::new();
+        let mut current_history_chars = 0;
+
+        for h in history.iter().rev().take(history.len().saturating_sub(1)) {
+            let text = format!("**{}**: {}", if h.role == "user" { "User" } else { "Assistant" }, h.content);
+
+            if current_history_chars + text.len() > MAX_HISTORY_CHARS {
+                context_lines.push("**(System)**: ... [较早的历史记录已根据上下文容量限制被自动折叠] ...".to_string());
+                break;
+            }
+
+            current_history_chars += text.len();
+            context_lines.push(text);
+        }
+
+        context_lines.reverse();
+        parts.push(format!("## Recent History\n{}", context_lines.join("\n\n")));
+    }
+
+    // Attached file contents go right before the message that refers to them
+    if let Some(context) = attachment_context {
+        parts.push(context.to_string());
+    }
+
+    parts.push(format!("---\n**User**: {}", user_input));
+    parts.join("\n\n")
+}
+
+/// What the agent would send to the model for a message, without running it.
+#[derive(Debug, Clone)]
+pub struct AgentContextPreview {
+    pub system_prompt: String,
+    pub user_message: String,
+    pub model: String,
+}
+
+/// Assemble the system prompt and user message `process_message` would send
+/// if `user_input` arrived for `account_id` now. Custom prompt commands are
+/// expanded and pending attachments are read but left queued; nothing is
+/// saved. Built-in and shell commands, which skip the model, are previewed
+/// as plain text.
+pub async fn preview_agent_context(
+    account_id: &str,
+    user_input: &str,
+    workspace: Option<&str>,
+) -> Result<AgentContextPreview, String> {
+    let expanded;
+    let user_input = match cmd_module::resolve_custom_input(user_input) {
+        Some(Ok(cmd_module::CustomResolution::Prompt(prompt))) => {
+            expanded = prompt;
+            expanded.as_str()
+        }
+        _ => user_input,
+    };
+
+    let pending = super::attachments::peek_pending(account_id);
+    let attached = if pending.is_empty() {
+        None
+    } else {
+        Some(super::attachments::extract_attachments(&pending, workspace).await)
+    };
+    let attachment_context = attached.as_ref().map(|a| a.context.as_str()).filter(|c| !c.is_empty());
+
+    let config = load_app_config()?;
+    let (system_prompt, _) = build_session_system_prompt(account_id, user_input, &config, workspace).await;
+
+    // The new message is saved before history is loaded, so it takes one of the slots
+    let mut history = database::get_conversation_history(account_id, AGENT_HISTORY_LIMIT - 1)?;
+    let logged_input = match attached.as_ref().and_then(|a| a.history_note()) {
+        Some(note) => format!("{}\n\n{}", user_input, note),
+        None => user_input.to_string(),
+    };
+    history.push(database::ConversationEntry {
+        id: 0,
+        account_id: account_id.to_string(),
+        role: "user".to_string(),
+        content: logged_input,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    });
+    let compressed_summary = super::memory::get_compressed_summary(account_id);
+
+    Ok(AgentContextPreview {
+        system_prompt,
+        user_message: build_agent_input(&history, compressed_summary.as_deref(), attachment_context, user_input),
+        model: session_model_config(account_id, &config)?.model,
+    })
+}
+
 // ============================================================================
 // Command Parsing — delegates to commands.rs
 // ============================================================================
@@ -465,6 +758,54 @@ fn is_handled_command(input: &str) -> bool {
     }
 }
 
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum AgentError {
+    #[error("配置加载失败: {0}")]
+    Config(String),
+    #[error("API Key 未设置，请在设置中配置")]
+    NotConfigured,
+    #[error("Agent execution timed out after 5 minutes")]
+    Timeout,
+    /// The agents-sdk run failed; provider errors surface here as text
+    #[error("Agent error: {0}")]
+    Model(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<String> for AgentError {
+    fn from(message: String) -> Self {
+        AgentError::Internal(message)
+    }
+}
+
+impl From<AgentError> for HelixError {
+    fn from(err: AgentError) -> Self {
+        let message = err.to_string();
+        match err {
+            AgentError::Config(_) => HelixError::new(codes::CONFIG_LOAD_FAILED, message),
+            AgentError::NotConfigured => HelixError::new(codes::PROVIDER_NOT_CONFIGURED, message),
+            AgentError::Timeout => HelixError::new(codes::AGENT_TIMEOUT, message).retriable(),
+            AgentError::Model(detail) => {
+                // The SDK only exposes the provider failure as an anyhow message
+                let lower = detail.to_lowercase();
+                if lower.contains("401") || lower.contains("unauthorized") || lower.contains("invalid api key") {
+                    HelixError::new(codes::PROVIDER_AUTH_FAILED, message)
+                } else if lower.contains("429") || lower.contains("rate limit") || lower.contains("too many requests") {
+                    HelixError::new(codes::PROVIDER_RATE_LIMITED, message).retriable()
+                } else {
+                    HelixError::new(codes::AGENT_FAILED, message)
+                }
+            }
+            AgentError::Internal(_) => HelixError::new(codes::AGENT_FAILED, message),
+        }
+    }
+}
+
 // ============================================================================
 // Core Agent Loop
 // ============================================================================
@@ -476,17 +817,64 @@ pub async fn agent_process_message(
     user_input: &str,
     workspace: Option<String>,
 ) -> Result<String, String> {
-    // 1. Check for handled commands
+    process_message(account_id, user_input, &[], workspace)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub(super) async fn process_message(
+    account_id: &str,
+    user_input: &str,
+    attachments: &[String],
+    workspace: Option<String>,
+) -> Result<String, AgentError> {
+    // 0. "stop" / "取消" cancels this session's in-flight run (e.g. from WeChat)
+    if is_stop_request(user_input) && cancel_active_run(account_id) {
+        info!("[agent] Stop request cancelled active run for {}", account_id);
+        return Ok("🛑 已取消当前任务".to_string());
+    }
+
+    // 1. Expand user-defined commands, then check for handled commands
+    let expanded;
+    let user_input = match cmd_module::resolve_custom_input(user_input) {
+        Some(Ok(cmd_module::CustomResolution::Prompt(prompt))) => {
+            expanded = prompt;
+            expanded.as_str()
+        }
+        Some(Ok(cmd_module::CustomResolution::Shell(script))) => {
+            // Channels and the API server reach here too; never run their shell commands
+            if !SESSION_LOCAL.try_with(|local| *local).unwrap_or(false) {
+                warn!("[agent] Refused shell custom command from non-local session {}", account_id);
+                return Ok("❌ Shell 命令只能在本地界面中执行".to_string());
+            }
+            return Ok(cmd_module::run_custom_shell(&script).await);
+        }
+        Some(Err(usage)) => return Ok(format!("❌ {}", usage)),
+        None => user_input,
+    };
     if let Some(response) = dispatch_commands(user_input, account_id) {
         return Ok(response);
     }
 
+    // 1b. Extract attached files, including ones received on a channel before this message
+    let mut attachment_paths = super::attachments::take_pending(account_id);
+    attachment_paths.extend(attachments.iter().cloned());
+    let attached = if attachment_paths.is_empty() {
+        None
+    } else {
+        let attached = super::attachments::extract_attachments(&attachment_paths, workspace.as_deref()).await;
+        emit_agent_progress("attachments", json!(&attached.report));
+        super::attachments::store_report(account_id, attached.report.clone());
+        Some(attached)
+    };
+    let attachment_context = attached.as_ref().map(|a| a.context.as_str()).filter(|c| !c.is_empty());
+
     // 2. Load config
-    let config = load_app_config().map_err(|e| format!("配置加载失败: {}", e))?;
-    let ai = &config.ai_config;
+    let config = load_app_config().map_err(AgentError::Config)?;
+    let ai = &session_model_config(account_id, &config)?;
 
     if ai.api_key.is_empty() && ai.provider != "ollama" && ai.provider != "custom" {
-        return Err("API Key 未设置，请在设置中配置".to_string());
+        return Err(AgentError::NotConfigured);
     }
 
     // 3. Build agents-sdk model with configurable base URL
@@ -511,13 +899,32 @@ pub async fn agent_process_message(
     let base_model = Arc::new(
         OpenAiChatModel::new(oai_config).map_err(|e| format!("Model init failed: {}", e))?,
     );
+    let stream_provider = ProviderConfig {
+        kind: ProviderKind::OpenAI,
+        base_url: effective_base.clone(),
+        api_key: api_key.to_string(),
+        default_model: Some(ai.model.clone()),
+        extra_headers: HashMap::new(),
+    };
     let model = Arc::new(InterceptingChatModel {
         inner: base_model,
-        limit: 131072, // Default context limit
+        limit: AGENT_CONTEXT_LIMIT,
+        retry: config.ai_retry.clone(),
+        stream: Some(stream_provider),
     });
 
     // 4. Build system prompt
-    let system_prompt = build_system_prompt(&ai.system_prompt, workspace.as_deref());
+    let (system_prompt, injected) =
+        build_session_system_prompt(account_id, user_input, &config, workspace.as_deref()).await;
+    if !injected.is_empty() {
+        let summary: Vec<Value> = injected
+            .iter()
+            .map(|r| json!({ "id": r.entry.id, "key": r.entry.key, "score": r.score, "match_type": r.match_type }))
+            .collect();
+        let summary_json = serde_json::Value::Array(summary.clone()).to_string();
+        info!("[agent] Auto-recalled {} memories for {}: {}", injected.len(), account_id, summary_json);
+        emit_agent_progress("memory_injected", json!({ "memories": summary }));
+    }
 
     // 5. Build tools — direct agents-sdk tool definitions
     let sdk_tools = super::tools::build_tools();
@@ -532,65 +939,23 @@ pub async fn agent_process_message(
         .build()
         .map_err(|e| format!("Agent build failed: {}", e))?;
 
-    // 7. Save user message to DB + Brain log
-    let _ = database::save_conversation_message(account_id, "user", user_input);
-    let _ = crate::modules::ai::context::log_message(account_id, "user", user_input);
+    // 7. Save user message to DB + Brain log (attachments by name only)
+    let logged_input = match attached.as_ref().and_then(|a| a.history_note()) {
+        Some(note) => format!("{}\n\n{}", user_input, note),
+        None => user_input.to_string(),
+    };
+    let _ = database::save_conversation_message(account_id, "user", &logged_input);
+    let _ = crate::modules::ai::context::log_message(account_id, "user", &logged_input);
 
     // 8. Load conversation history and build structured context
-    let history = database::get_conversation_history(account_id, 20)?;
-
-    // Prepend compressed summary if available (CoPaw-inspired memory compaction)
+    let history = database::get_conversation_history(account_id, AGENT_HISTORY_LIMIT)?;
     let compressed_summary = super::memory::get_compressed_summary(account_id);
+    let full_input = build_agent_input(&history, compressed_summary.as_deref(), attachment_context, user_input);
 
-    let full_input = if history.len() <= 1 && compressed_summary.is_none() {
-        user_input.to_string()
-    } else {
-        let mut parts = Vec::new();
-
-        // Add compressed summary of older conversations
-        if let Some(ref summary) = compressed_summary {
-            parts.push(format!("## Previous Conversation Summary\n{}", summary));
-        }
-
-        // Add recent history with length limit (Antigravity Context Optimization)
-        if history.len() > 1 {
-            let mut context_lines = Vec::new();
-            let mut current_history_chars = 0;
-            let max_history_chars = 32000;
-
-            for h in history.iter().rev().take(history.len().saturating_sub(1)) {
-                let text = format!(
-                    "**{}**: {}",
-                    if h.role == "user" {
-                        "User"
-                    } else {
-                        "Assistant"
-                    },
-                    h.content
-                );
-
-                if current_history_chars + text.len() > max_history_chars {
-                    context_lines.push(
-                        "**(System)**: ... [较早的历史记录已根据上下文容量限制被自动折叠] ..."
-                            .to_string(),
-                    );
-                    break;
-                }
-
-                current_history_chars += text.len();
-                context_lines.push(text);
-            }
-
-            context_lines.reverse();
-            parts.push(format!("## Recent History\n{}", context_lines.join("\n\n")));
-        }
-
-        parts.push(format!("---\n**User**: {}", user_input));
-        parts.join("\n\n")
-    };
-
-    reset_session_cancelled(account_id);
+    let (run_id, cancel_token) = begin_run(account_id);
     super::tools::clear_sent_files_for(account_id);
+    super::tools::clear_completed_tools_for(account_id);
+    super::replay::clear_run_calls_for(account_id);
     emit_agent_progress("thinking", json!({ "iteration": 0, "model": &ai.model }));
 
     // 9. Run the agent (with workspace in task-local, catch panics from SDK)
@@ -598,23 +963,61 @@ pub async fn agent_process_message(
     let ws = workspace.clone();
     let input_clone = full_input.clone();
     let acct = account_id.to_string();
+    let run_token = cancel_token.clone();
+    let progress = session_progress();
+    let dry_run = SESSION_DRY_RUN.try_with(|dry_run| *dry_run).unwrap_or(false);
     let spawn_res = tokio::task::spawn(async move {
-        SESSION_WORKSPACE
-            .scope(ws, async {
-                SESSION_ACCOUNT_ID
-                    .scope(acct, async {
-                        agent.handle_message(&input_clone, state).await
+        SESSION_PROGRESS
+            .scope(progress, async {
+                SESSION_CANCEL
+                    .scope(run_token, async {
+                        SESSION_WORKSPACE
+                            .scope(ws, async {
+                                SESSION_ACCOUNT_ID
+                                    .scope(acct, async {
+                                        SESSION_RUN_ID
+                                            .scope(run_id, async {
+                                                SESSION_DRY_RUN
+                                                    .scope(dry_run, agent.handle_message(&input_clone, state))
+                                                    .await
+                                            })
+                                            .await
+                                    })
+                                    .await
+                            })
+                            .await
                     })
                     .await
             })
             .await
     });
+    let abort_handle = spawn_res.abort_handle();
 
-    let response = tokio::time::timeout(std::time::Duration::from_secs(300), spawn_res)
-        .await
-        .map_err(|_| "Agent execution timed out after 5 minutes".to_string())?
+    // Aborting the task drops in-flight provider requests and tool futures
+    let outcome = tokio::select! {
+        _ = cancel_token.cancelled() => None,
+        r = tokio::time::timeout(std::time::Duration::from_secs(300), spawn_res) => Some(r),
+    };
+    end_run(account_id, run_id);
+
+    let Some(outcome) = outcome else {
+        abort_handle.abort();
+        let reply = build_cancelled_response(&super::tools::take_completed_tools_for(account_id));
+        if let Ok(message_id) = database::save_conversation_message(account_id, "assistant", &reply) {
+            super::replay::record_run_calls(account_id, message_id);
+        }
+        info!("[agent] Run for {} cancelled by user", account_id);
+        return Ok(reply);
+    };
+    super::tools::clear_completed_tools_for(account_id);
+
+    let response = outcome
+        .map_err(|_| {
+            abort_handle.abort();
+            AgentError::Timeout
+        })?
         .map_err(|e| format!("Agent panicked: {}", e))?
-        .map_err(|e| format!("Agent error: {}", e))?;
+        .map_err(|e| AgentError::Model(e.to_string()))?;
 
     // Extract text from AgentMessage.content
     let text = match &response.content {
@@ -622,7 +1025,9 @@ pub async fn agent_process_message(
         other => format!("{:?}", other),
     };
     let clean = clean_response(&text);
-    let _ = database::save_conversation_message(account_id, "assistant", &clean);
+    if let Ok(message_id) = database::save_conversation_message(account_id, "assistant", &clean) {
+        super::replay::record_run_calls(account_id, message_id);
+    }
     let _ = crate::modules::ai::context::log_message(account_id, "assistant", &clean);
 
     // 10. Background memory compaction (non-blocking, CoPaw-inspired)
@@ -646,6 +1051,18 @@ pub async fn agent_process_message_with_images(
     images: &[String],
     workspace: Option<String>,
 ) -> Result<String, String> {
+    process_message_with_images(account_id, user_input, images, &[], workspace)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn process_message_with_images(
+    account_id: &str,
+    user_input: &str,
+    images: &[String],
+    attachments: &[String],
+    workspace: Option<String>,
+) -> Result<String, AgentError> {
     // Describe each image using raw HTTP (tool_image_describe in tools.rs)
     let mut descriptions = Vec::new();
     for img_url in images {
@@ -679,7 +1096,7 @@ pub async fn agent_process_message_with_images(
     };
 
     // Delegate to main agent
-    agent_process_message(account_id, &combined, workspace).await
+    process_message(account_id, &combined, attachments, workspace).await
 }
 
 /// Strip thinking tags and clean up response text.
@@ -694,6 +1111,141 @@ fn clean_response(text: &str) -> String {
 pub struct InterceptingChatModel {
     pub inner: Arc<dyn LanguageModel>,
     pub limit: usize,
+    /// Backoff for transient provider failures (429 / 5xx / connection errors)
+    pub retry: crate::models::config::AiRetryConfig,
+    /// Endpoint for runs that stream their answer as `text_delta` events;
+    /// None keeps every call on `inner` (subagents).
+    pub stream: Option<ProviderConfig>,
+}
+
+impl InterceptingChatModel {
+    /// Run `call` until it succeeds, fails with a non-transient error, runs
+    /// out of `retry.max_attempts` or the session is cancelled.
+    async fn with_retry<T, F, Fut>(&self, call: F) -> anyhow::Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<T>>,
+    {
+        let token = session_cancel_token();
+        let max_attempts = self.retry.max_attempts.max(1);
+        let mut attempt: u32 = 0;
+        loop {
+            let result = tokio::select! {
+                _ = token.cancelled() => return Err(anyhow::anyhow!("cancelled by user")),
+                r = call() => r,
+            };
+            let err = match result {
+                Err(e) if attempt + 1 < max_attempts && is_transient_model_error(&e) => e,
+                r => return r,
+            };
+            attempt += 1;
+            let delay = crate::modules::ai::chat::model_retry_delay(&self.retry, attempt);
+            let message = format!("retry {}/{} in {}ms ({})", attempt, max_attempts - 1, delay.as_millis(), err);
+            warn!("[Agent] Model call failed, {}", message);
+            emit_agent_progress("loop_info", json!({ "message": format!("[Retry] {}", message) }));
+            tokio::select! {
+                _ = token.cancelled() => return Err(anyhow::anyhow!("cancelled by user")),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// Make one model call over SSE, forwarding answer text to `progress` as it arrives.
+    async fn generate_streamed(
+        &self,
+        provider: &ProviderConfig,
+        request: &LlmRequest,
+        progress: &Arc<RunProgress>,
+    ) -> anyhow::Result<LlmResponse> {
+        let body = streaming_request_body(provider.default_model.as_deref().unwrap_or_default(), request);
+        let result = stream_chat_completion(provider, &body, |event| {
+            if let StreamEvent::Delta { text } = event {
+                progress.emit("text_delta", json!({ "text": text }));
+            }
+        })
+        .await?;
+        Ok(LlmResponse { message: streamed_message(result) })
+    }
+}
+
+/// OpenAI chat body for `request`, converted the way the SDK's OpenAI model does it.
+fn streaming_request_body(model: &str, request: &LlmRequest) -> Value {
+    let mut messages = vec![json!({ "role": "system", "content": request.system_prompt })];
+    for msg in &request.messages {
+        let role = match msg.role {
+            MessageRole::User | MessageRole::Tool => "user",
+            MessageRole::Agent => "assistant",
+            MessageRole::System => "system",
+        };
+        let content = match &msg.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Json(value) => value.to_string(),
+        };
+        messages.push(json!({ "role": role, "content": content }));
+    }
+    let mut body = json!({ "model": model, "messages": messages, "stream": true });
+    if !request.tools.is_empty() {
+        let tools: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": serde_json::to_value(&tool.parameters).unwrap_or_else(|_| json!({})),
+                    }
+                })
+            })
+            .collect();
+        body["tools"] = Value::Array(tools);
+    }
+    body
+}
+
+/// Agent message for a finished stream, in the shape the SDK's OpenAI model returns.
+fn streamed_message(result: StreamResult) -> AgentMessage {
+    let content = if result.tool_calls.is_empty() {
+        MessageContent::Text(result.content)
+    } else {
+        let calls: Vec<Value> = result
+            .tool_calls
+            .iter()
+            .map(|tc| {
+                let args = serde_json::from_str::<Value>(&tc.arguments).unwrap_or_else(|_| json!({}));
+                json!({ "name": tc.name, "args": args })
+            })
+            .collect();
+        MessageContent::Json(json!({ "tool_calls": calls }))
+    };
+    AgentMessage { role: MessageRole::Agent, content, metadata: None }
+}
+
+/// A model turn in progress, reported as `model_turn_started` / `model_turn_finished`.
+struct ModelTurn {
+    progress: Arc<RunProgress>,
+    turn: u64,
+    started: std::time::Instant,
+}
+
+impl ModelTurn {
+    fn begin(prompt_tokens: usize) -> Option<Self> {
+        let progress = session_progress()?;
+        let (turn, delta) = progress.begin_turn(prompt_tokens);
+        progress.emit(
+            "model_turn_started",
+            json!({ "turn": turn, "prompt_tokens": prompt_tokens, "prompt_tokens_delta": delta }),
+        );
+        Some(Self { progress, turn, started: std::time::Instant::now() })
+    }
+
+    fn finish(self, success: bool) {
+        self.progress.emit(
+            "model_turn_finished",
+            json!({ "turn": self.turn, "duration_ms": self.started.elapsed().as_millis() as u64, "success": success }),
+        );
+    }
 }
 
 #[async_trait]
@@ -730,7 +1282,21 @@ impl LanguageModel for InterceptingChatModel {
         }
 
         request.messages = working_messages;
-        self.inner.generate(request).await
+        let turn = ModelTurn::begin(status.total_tokens);
+        let streaming = self.stream.as_ref().zip(session_progress().filter(|p| p.streams_text()));
+        let result = match streaming {
+            Some((provider, progress)) => {
+                self.with_retry(|| self.generate_streamed(provider, &request, &progress)).await
+            }
+            None => {
+                let call = || async { self.inner.generate(request.clone()).await.map_err(provider_error_from_sdk) };
+                self.with_retry(call).await
+            }
+        };
+        if let Some(turn) = turn {
+            turn.finish(result.is_ok());
+        }
+        result
     }
 
     async fn generate_stream(&self, request: LlmRequest) -> anyhow::Result<ChunkStream> {
@@ -758,7 +1324,15 @@ impl LanguageModel for InterceptingChatModel {
         }
 
         request.messages = working_messages;
-        self.inner.generate_stream(request).await
+        // For streams the turn finishes once the stream is open, not when it is drained
+        let turn = ModelTurn::begin(status.total_tokens);
+        let result = self
+            .with_retry(|| async { self.inner.generate_stream(request.clone()).await.map_err(provider_error_from_sdk) })
+            .await;
+        if let Some(turn) = turn {
+            turn.finish(result.is_ok());
+        }
+        result
     }
 }
 
@@ -766,22 +1340,64 @@ impl LanguageModel for InterceptingChatModel {
 // Tauri Commands
 // ============================================================================
 
-/// Process a message through the full agent (with tools)
+/// Process a message through the full agent (with tools).
+/// Structured progress is emitted as `agent://progress/{request_id}` unless
+/// `events` is false; a request id is generated when none is given. With
+/// `stream` set, the answer is also streamed there as `text_delta` events.
 #[tauri::command]
 pub async fn agent_chat(
     account_id: String,
     content: String,
     images: Option<Vec<String>>,
     workspace: Option<String>,
-) -> Result<Value, String> {
+    request_id: Option<String>,
+    events: Option<bool>,
+    attachments: Option<Vec<String>>,
+    stream: Option<bool>,
+) -> HelixResult<Value> {
     let imgs = images.unwrap_or_default();
-    let reply = if imgs.is_empty() {
-        agent_process_message(&account_id, &content, workspace).await?
-    } else {
-        agent_process_message_with_images(&account_id, &content, &imgs, workspace).await?
-    };
-    let files = super::tools::take_sent_files_for(&account_id);
-    Ok(json!({ "content": reply, "files": files }))
+    let files = attachments.unwrap_or_default();
+    let progress = events
+        .unwrap_or(true)
+        .then(|| RunProgress::new(request_id.as_deref().unwrap_or_default(), stream.unwrap_or(false)));
+    let started = std::time::Instant::now();
+    if let Some(p) = &progress {
+        p.emit(
+            "run_started",
+            json!({ "account_id": &account_id, "images": imgs.len(), "attachments": files.len() }),
+        );
+    }
+
+    let run = SESSION_PROGRESS.scope(progress.clone(), async {
+        if imgs.is_empty() {
+            process_message(&account_id, &content, &files, workspace).await
+        } else {
+            process_message_with_images(&account_id, &content, &imgs, &files, workspace).await
+        }
+    });
+    let result = SESSION_LOCAL
+        .scope(true, run)
+        .await
+        .map_err(HelixError::from);
+
+    if let Some(p) = &progress {
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let data = match &result {
+            Ok(reply) => json!({ "success": true, "duration_ms": duration_ms, "chars": reply.len() }),
+            Err(e) => json!({ "success": false, "duration_ms": duration_ms, "error": e }),
+        };
+        p.emit("run_completed", data);
+    }
+
+    let attachment_report = super::attachments::take_report_for(&account_id);
+    let reply = result?;
+    let sent_files = super::tools::take_sent_files_for(&account_id);
+    Ok(json!({
+        "content": reply,
+        "files": sent_files,
+        "attachments": attachment_report,
+        "request_id": progress.as_ref().map(|p| p.request_id()),
+    }))
 }
 
 /// Get conversation history
@@ -797,3 +1413,93 @@ pub async fn agent_clear_history(account_id: String) -> Result<Value, String> {
     database::clear_messages(&account_id)?;
     Ok(json!({ "ok": true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stop_request_cancels_active_run() {
+        assert!(is_stop_request(" Stop "));
+        assert!(is_stop_request("取消"));
+        assert!(!is_stop_request("stop the server"));
+
+        let session = "test-stop-session";
+        assert!(!cancel_active_run(session));
+        let (run_id, token) = begin_run(session);
+        assert!(cancel_active_run(session));
+        assert!(token.is_cancelled());
+        assert!(!cancel_active_run(session));
+        end_run(session, run_id);
+        assert!(!ACTIVE_RUNS.lock().unwrap().contains_key(session));
+    }
+
+    #[test]
+    fn test_build_cancelled_response() {
+        assert_eq!(build_cancelled_response(&[]), "🛑 已被用户取消 (cancelled by user)");
+        let reply = build_cancelled_response(&[("file_read".to_string(), "x".repeat(400))]);
+        assert!(reply.contains("- file_read: "));
+        assert!(reply.ends_with("..."));
+    }
+
+    #[test]
+    fn test_build_agent_input() {
+        let entry = |role: &str, content: &str| database::ConversationEntry {
+            id: 0,
+            account_id: "chat:a".into(),
+            role: role.into(),
+            content: content.into(),
+            created_at: String::new(),
+        };
+        assert_eq!(build_agent_input(&[entry("user", "hi")], None, None, "hi"), "hi");
+
+        let history = vec![entry("user", "first"), entry("assistant", "answer"), entry("user", "next")];
+        let input = build_agent_input(&history, Some("talked about k8s"), Some("## Attachments"), "next");
+        assert_eq!(
+            input,
+            "## Previous Conversation Summary\ntalked about k8s\n\n\
+             ## Recent History\n**Assistant**: answer\n\n**User**: next\n\n\
+             ## Attachments\n\n---\n**User**: next"
+        );
+
+        // Older turns past the character budget are folded
+        let long = "x".repeat(MAX_HISTORY_CHARS);
+        let history = vec![entry("user", "q"), entry("assistant", &long), entry("user", "a"), entry("user", "now")];
+        let input = build_agent_input(&history, None, None, "now");
+        assert!(input.contains("较早的历史记录"));
+        assert!(!input.contains(&long));
+        assert!(input.contains("**User**: a"));
+    }
+    #[test]
+    fn test_streamed_turn_matches_sdk_shapes() {
+        let message = |role, content| AgentMessage { role, content, metadata: None };
+        let request = LlmRequest::new(
+            "sys",
+            vec![
+                message(MessageRole::User, MessageContent::Text("hi".into())),
+                message(MessageRole::Tool, MessageContent::Json(json!({"ok": 1}))),
+            ],
+        );
+        let body = streaming_request_body("m", &request);
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["messages"][0], json!({ "role": "system", "content": "sys" }));
+        assert_eq!(body["messages"][2], json!({ "role": "user", "content": "{\"ok\":1}" }));
+        assert!(body.get("tools").is_none());
+
+        let usage = crate::modules::streaming::StreamUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 };
+        let call = crate::modules::streaming::AccumulatedToolCall {
+            id: "c1".into(),
+            name: "shell".into(),
+            arguments: r#"{"cmd":"ls"}"#.into(),
+        };
+        let result =
+            StreamResult { content: String::new(), tool_calls: vec![call], usage, stop_reason: "tool_calls".into() };
+        let MessageContent::Json(calls) = streamed_message(result.clone()).content else {
+            panic!("expected tool calls")
+        };
+        assert_eq!(calls, json!({ "tool_calls": [{ "name": "shell", "args": { "cmd": "ls" } }] }));
+
+        let text = StreamResult { content: "done".into(), tool_calls: Vec::new(), ..result };
+        assert!(matches!(streamed_message(text).content, MessageContent::Text(t) if t == "done"));
+    }
+}
@@ -11,6 +11,7 @@ use serde_json::Value;
 use tracing::{info, warn, error};
 
 use crate::modules::config::get_data_dir;
+use crate::modules::environments::WithManagedEnv;
 
 // ============================================================================
 // Types
@@ -226,6 +227,7 @@ pub async fn dispatch_event(event_type: &str, context: Value) {
                     let output = tokio::process::Command::new("sh")
                         .arg("-c")
                         .arg(&payload)
+                        .with_managed_env()
                         .output()
                         .await;
                     match output {
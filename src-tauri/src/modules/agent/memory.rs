@@ -1,5 +1,5 @@
 //! Advanced Memory System — FTS5 full-text search, OpenAI embeddings,
-//! hybrid vector + keyword search, temporal decay, and memory sync.
+//! hybrid vector + keyword search, temporal decay, confidence scoring, and memory sync.
 //!
 //! Ported from OpenClaw `src/memory/`: upgrades Helix's basic
 //! key-value memory store to a full-featured semantic memory engine.
@@ -10,9 +10,12 @@ use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use tracing::info;
+use tracing::{info, warn};
 
+use crate::error::{codes, HelixError, HelixResult};
 use crate::modules::config::get_data_dir;
+use crate::modules::infra::log_bridge::emit_custom_event;
+use crate::utils::truncate::safe_truncate;
 
 // ============================================================================
 // Types
@@ -33,8 +36,51 @@ pub struct MemoryEntry {
     /// Relevance score (set during search)
     #[serde(default)]
     pub score: f64,
+    /// Account/session that owns the entry (None = unowned)
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    /// "private" (owner only), "shared" (any logged-in account), "global" (everyone)
+    #[serde(default = "default_visibility")]
+    pub visibility: String,
+    /// How many times the entry has been returned from search
+    #[serde(default)]
+    pub access_count: i64,
+    /// 0.0–1.0 blend of recency, source trust and access frequency (set at query time)
+    #[serde(default)]
+    pub confidence: f64,
+    /// Exempt from temporal decay and boosted in search results
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+fn default_visibility() -> String {
+    "global".to_string()
 }
 
+pub const VISIBILITIES: &[&str] = &["private", "shared", "global"];
+
+/// Memory sources that are categories rather than a session id.
+const SOURCE_KINDS: &[&str] = &["user", "conversation", "file", "note", "agent"];
+
+/// Half-life of the recency decay applied to search scores and confidence
+const DECAY_HALF_LIFE_DAYS: f64 = 30.0;
+/// Confidence weights: recency, source trust, access frequency
+const CONFIDENCE_WEIGHTS: (f64, f64, f64) = (0.4, 0.3, 0.3);
+/// Access count at which the frequency component reaches 0.5
+const ACCESS_HALF_SATURATION: f64 = 5.0;
+/// Search score multiplier for pinned entries
+const PINNED_BOOST: f64 = 2.0;
+/// Reciprocal-rank-fusion constant; larger values flatten the gap between top ranks
+const RRF_K: f64 = 60.0;
+/// Candidates fetched from each ranker before fusion, as a multiple of the limit
+const RRF_CANDIDATE_FACTOR: i64 = 3;
+/// Vector hits below this cosine similarity are not fused
+const VECTOR_MIN_SIMILARITY: f64 = 0.25;
+/// Budget for embedding the query before searching without vectors
+const QUERY_EMBED_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+/// How long vector search is skipped after the embedding provider failed
+const VECTOR_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemorySearchResult {
     pub entry: MemoryEntry,
@@ -44,6 +90,9 @@ pub struct MemorySearchResult {
     /// Snippet with highlights
     #[serde(default)]
     pub snippet: Option<String>,
+    /// Same as `entry.confidence`
+    #[serde(default)]
+    pub confidence: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +101,28 @@ pub struct MemoryStats {
     pub total_with_embeddings: i64,
     pub sources: HashMap<String, i64>,
     pub db_size_bytes: u64,
+    /// FTS index vs. base table; `None` if the check itself failed
+    #[serde(default)]
+    pub fts: Option<FtsConsistency>,
+}
+
+/// How well the FTS index matches `memory_entries`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FtsConsistency {
+    /// Rows in `memory_entries`
+    pub entries: i64,
+    /// Documents in the FTS index
+    pub indexed: i64,
+    /// Entries with no FTS document (invisible to search)
+    pub missing: i64,
+    /// FTS documents whose entry no longer exists
+    pub orphaned: i64,
+}
+
+impl FtsConsistency {
+    pub fn is_consistent(&self) -> bool {
+        self.missing == 0 && self.orphaned == 0
+    }
 }
 
 // ============================================================================
@@ -73,84 +144,220 @@ fn open_memory_db() -> Result<Connection, String> {
     Ok(conn)
 }
 
+const MEMORY_SCHEMA: &str = "
+    -- Main memory entries table (upgrade from simple key-value)
+    CREATE TABLE IF NOT EXISTS memory_entries (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        key         TEXT NOT NULL,
+        content     TEXT NOT NULL,
+        source      TEXT NOT NULL DEFAULT 'user',
+        tags        TEXT DEFAULT '[]',
+        embedding   BLOB,
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_memory_key ON memory_entries(key);
+    CREATE INDEX IF NOT EXISTS idx_memory_source ON memory_entries(source);
+
+    -- FTS5 virtual table for full-text search
+    CREATE VIRTUAL TABLE IF NOT EXISTS memory_fts USING fts5(
+        key,
+        content,
+        tags,
+        content=memory_entries,
+        content_rowid=id,
+        tokenize='unicode61'
+    );
+
+    -- Triggers to keep FTS in sync
+    CREATE TRIGGER IF NOT EXISTS memory_fts_insert AFTER INSERT ON memory_entries BEGIN
+        INSERT INTO memory_fts(rowid, key, content, tags)
+        VALUES (new.id, new.key, new.content, new.tags);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS memory_fts_delete AFTER DELETE ON memory_entries BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, key, content, tags)
+        VALUES ('delete', old.id, old.key, old.content, old.tags);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS memory_fts_update AFTER UPDATE ON memory_entries BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, key, content, tags)
+        VALUES ('delete', old.id, old.key, old.content, old.tags);
+        INSERT INTO memory_fts(rowid, key, content, tags)
+        VALUES (new.id, new.key, new.content, new.tags);
+    END;
+";
+
 pub fn init_memory_tables() -> Result<(), String> {
     let conn = MEMORY_DB.lock();
-    conn.execute_batch(
-        "
-        -- Main memory entries table (upgrade from simple key-value)
-        CREATE TABLE IF NOT EXISTS memory_entries (
-            id          INTEGER PRIMARY KEY AUTOINCREMENT,
-            key         TEXT NOT NULL,
-            content     TEXT NOT NULL,
-            source      TEXT NOT NULL DEFAULT 'user',
-            tags        TEXT DEFAULT '[]',
-            embedding   BLOB,
-            created_at  TEXT NOT NULL,
-            updated_at  TEXT NOT NULL
+    conn.execute_batch(MEMORY_SCHEMA)
+        .map_err(|e| format!("create memory tables: {}", e))?;
+
+    // Migration: per-owner visibility. Pre-existing entries stay global, except
+    // those the agent's memory_store tool saved with a session id as source.
+    let _ = conn.execute("ALTER TABLE memory_entries ADD COLUMN owner_id TEXT", []);
+    let added_visibility = conn
+        .execute("ALTER TABLE memory_entries ADD COLUMN visibility TEXT NOT NULL DEFAULT 'global'", [])
+        .is_ok();
+    if added_visibility {
+        let placeholders = vec!["?"; SOURCE_KINDS.len()].join(",");
+        let _ = conn.execute(
+            &format!(
+                "UPDATE memory_entries SET owner_id = source, visibility = 'private'
+                 WHERE owner_id IS NULL AND source NOT IN ({})",
+                placeholders
+            ),
+            rusqlite::params_from_iter(SOURCE_KINDS.iter()),
         );
+    }
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_memory_owner ON memory_entries(owner_id, visibility)",
+        [],
+    );
 
-        CREATE INDEX IF NOT EXISTS idx_memory_key ON memory_entries(key);
-        CREATE INDEX IF NOT EXISTS idx_memory_source ON memory_entries(source);
-
-        -- FTS5 virtual table for full-text search
-        CREATE VIRTUAL TABLE IF NOT EXISTS memory_fts USING fts5(
-            key,
-            content,
-            tags,
-            content=memory_entries,
-            content_rowid=id,
-            tokenize='unicode61'
-        );
+    // Migration: search hit counter for confidence scoring
+    let _ = conn.execute(
+        "ALTER TABLE memory_entries ADD COLUMN access_count INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Migration: pinned entries ignore temporal decay
+    let _ = conn.execute(
+        "ALTER TABLE memory_entries ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
+    // Tag search only looks at tagged entries
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_memory_tags ON memory_entries(tags) WHERE tags != '[]'",
+        [],
+    );
+
+    // Catch index drift left by a crash or manual edit before search relies on it
+    match check_fts_consistency_in(&conn) {
+        Ok(fts) if !fts.is_consistent() => {
+            warn!(
+                "[memory] FTS index out of sync ({} missing, {} orphaned); repairing",
+                fts.missing, fts.orphaned
+            );
+            if let Err(e) = repair_fts_in(&conn, false) {
+                warn!("[memory] FTS repair failed: {}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("[memory] FTS consistency check failed: {}", e),
+    }
 
-        -- Triggers to keep FTS in sync
-        CREATE TRIGGER IF NOT EXISTS memory_fts_insert AFTER INSERT ON memory_entries BEGIN
-            INSERT INTO memory_fts(rowid, key, content, tags)
-            VALUES (new.id, new.key, new.content, new.tags);
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS memory_fts_delete AFTER DELETE ON memory_entries BEGIN
-            INSERT INTO memory_fts(memory_fts, rowid, key, content, tags)
-            VALUES ('delete', old.id, old.key, old.content, old.tags);
-        END;
-
-        CREATE TRIGGER IF NOT EXISTS memory_fts_update AFTER UPDATE ON memory_entries BEGIN
-            INSERT INTO memory_fts(memory_fts, rowid, key, content, tags)
-            VALUES ('delete', old.id, old.key, old.content, old.tags);
-            INSERT INTO memory_fts(rowid, key, content, tags)
-            VALUES (new.id, new.key, new.content, new.tags);
-        END;
-        ",
-    )
-    .map_err(|e| format!("create memory tables: {}", e))?;
     info!("Advanced memory tables initialized (FTS5 enabled)");
     Ok(())
 }
 
+// ============================================================================
+// FTS Index Consistency
+// ============================================================================
+
+/// Row-level comparison of `memory_fts` against `memory_entries`, using the
+/// FTS docsize shadow table (one row per indexed document).
+fn check_fts_consistency_in(conn: &Connection) -> Result<FtsConsistency, String> {
+    conn.query_row(
+        "SELECT
+            (SELECT COUNT(*) FROM memory_entries),
+            (SELECT COUNT(*) FROM memory_fts_docsize),
+            (SELECT COUNT(*) FROM memory_entries WHERE id NOT IN (SELECT id FROM memory_fts_docsize)),
+            (SELECT COUNT(*) FROM memory_fts_docsize WHERE id NOT IN (SELECT id FROM memory_entries))",
+        [],
+        |r| {
+            Ok(FtsConsistency {
+                entries: r.get(0)?,
+                indexed: r.get(1)?,
+                missing: r.get(2)?,
+                orphaned: r.get(3)?,
+            })
+        },
+    )
+    .map_err(|e| format!("FTS consistency: {}", e))
+}
+
+/// Bring the FTS index back in line with `memory_entries`. Missing rows are
+/// indexed incrementally; orphaned rows (or `full`) force a full rebuild,
+/// since their original text is needed to delete them from the index.
+fn repair_fts_in(conn: &Connection, full: bool) -> Result<FtsConsistency, String> {
+    let before = check_fts_consistency_in(conn)?;
+    if full || before.orphaned > 0 {
+        conn.execute("INSERT INTO memory_fts(memory_fts) VALUES('rebuild')", [])
+            .map_err(|e| format!("FTS rebuild: {}", e))?;
+    } else if before.missing > 0 {
+        conn.execute(
+            "INSERT INTO memory_fts(rowid, key, content, tags)
+             SELECT id, key, content, tags FROM memory_entries
+             WHERE id NOT IN (SELECT id FROM memory_fts_docsize)",
+            [],
+        )
+        .map_err(|e| format!("FTS reindex: {}", e))?;
+    }
+
+    let after = check_fts_consistency_in(conn)?;
+    if !after.is_consistent() {
+        return Err(format!(
+            "FTS index still inconsistent after repair ({} entries, {} indexed)",
+            after.entries, after.indexed
+        ));
+    }
+    info!(
+        "[memory] FTS index repaired: {} missing, {} orphaned → {} indexed",
+        before.missing, before.orphaned, after.indexed
+    );
+    Ok(after)
+}
+
+pub fn check_fts_consistency() -> Result<FtsConsistency, String> {
+    check_fts_consistency_in(&MEMORY_DB.lock())
+}
+
+/// Rebuild the FTS index from `memory_entries` and verify it.
+pub fn reindex_fts() -> Result<FtsConsistency, String> {
+    repair_fts_in(&MEMORY_DB.lock(), true)
+}
+
 // ============================================================================
 // CRUD
 // ============================================================================
 
+/// SQL condition limiting rows to those visible to the owner bound at `?{param}`:
+/// global for everyone; shared and the owner's private entries once an owner is given.
+fn visibility_clause(prefix: &str, param: usize) -> String {
+    format!(
+        "({p}visibility = 'global' OR (?{n} IS NOT NULL AND ({p}visibility = 'shared' OR {p}owner_id = ?{n})))",
+        p = prefix,
+        n = param
+    )
+}
+
+/// Store (upsert by key + owner) a memory. Owned entries default to private,
+/// unowned ones to global.
 pub fn store_memory(
     key: &str,
     content: &str,
     source: &str,
     tags: &[String],
+    owner_id: Option<&str>,
 ) -> Result<MemoryEntry, String> {
     let now = chrono::Utc::now().to_rfc3339();
     let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
 
     let conn = MEMORY_DB.lock();
 
-    // Upsert: update if key exists, insert if not
-    let existing: Option<i64> = conn
+    // Upsert: update if key exists for this owner, insert if not
+    let existing: Option<(i64, String, bool)> = conn
         .query_row(
-            "SELECT id FROM memory_entries WHERE key = ?1",
-            params![key],
-            |row| row.get(0),
+            "SELECT id, visibility, pinned FROM memory_entries WHERE key = ?1 AND owner_id IS ?2",
+            params![key, owner_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
         )
         .ok();
 
-    if let Some(id) = existing {
+    if let Some((id, visibility, pinned)) = existing {
         conn.execute(
             "UPDATE memory_entries SET content = ?1, source = ?2, tags = ?3, updated_at = ?4 WHERE id = ?5",
             params![content, source, tags_json, now, id],
@@ -166,12 +373,18 @@ pub fn store_memory(
             created_at: now.clone(),
             updated_at: now,
             score: 0.0,
+            owner_id: owner_id.map(String::from),
+            visibility,
+            access_count: 0,
+            confidence: 0.0,
+            pinned,
         })
     } else {
+        let visibility = if owner_id.is_some() { "private" } else { "global" };
         conn.execute(
-            "INSERT INTO memory_entries (key, content, source, tags, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![key, content, source, tags_json, now, now],
+            "INSERT INTO memory_entries (key, content, source, tags, created_at, updated_at, owner_id, visibility)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![key, content, source, tags_json, now, now, owner_id, visibility],
         )
         .map_err(|e| format!("insert memory: {}", e))?;
 
@@ -185,34 +398,83 @@ pub fn store_memory(
             created_at: now.clone(),
             updated_at: now,
             score: 0.0,
+            owner_id: owner_id.map(String::from),
+            visibility: visibility.to_string(),
+            access_count: 0,
+            confidence: 0.0,
+            pinned: false,
         })
     }
 }
 
-pub fn delete_memory(id: i64) -> Result<(), String> {
+/// Change who can see a memory entry.
+pub fn set_visibility(id: i64, visibility: &str) -> HelixResult<()> {
+    if !VISIBILITIES.contains(&visibility) {
+        return Err(HelixError::validation_error(vec![format!(
+            "Invalid visibility '{}': use private, shared or global",
+            visibility
+        )]));
+    }
     let conn = MEMORY_DB.lock();
-    conn.execute("DELETE FROM memory_entries WHERE id = ?1", params![id])
-        .map_err(|e| format!("delete memory: {}", e))?;
+    let owner: Option<String> = conn
+        .query_row(
+            "SELECT owner_id FROM memory_entries WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|_| HelixError::not_found(format!("Memory {} not found", id)))?;
+    if visibility == "private" && owner.is_none() {
+        return Err(HelixError::validation_error(vec![format!("Memory {} has no owner and cannot be private", id)]));
+    }
+    conn.execute(
+        "UPDATE memory_entries SET visibility = ?1 WHERE id = ?2",
+        params![visibility, id],
+    )
+    .map_err(|e| storage_error(format!("update visibility: {}", e)))?;
     Ok(())
 }
 
-pub fn list_memories(source: Option<&str>, limit: i64) -> Result<Vec<MemoryEntry>, String> {
+/// Pin or unpin a memory entry.
+pub fn set_pinned(id: i64, pinned: bool) -> HelixResult<()> {
     let conn = MEMORY_DB.lock();
-    let query = if let Some(src) = source {
-        format!(
-            "SELECT id, key, content, source, tags, created_at, updated_at FROM memory_entries WHERE source = '{}' ORDER BY updated_at DESC LIMIT {}",
-            src, limit
+    let updated = conn
+        .execute(
+            "UPDATE memory_entries SET pinned = ?1 WHERE id = ?2",
+            params![pinned, id],
         )
-    } else {
-        format!(
-            "SELECT id, key, content, source, tags, created_at, updated_at FROM memory_entries ORDER BY updated_at DESC LIMIT {}",
-            limit
-        )
-    };
+        .map_err(|e| storage_error(format!("update pinned: {}", e)))?;
+    if updated == 0 {
+        return Err(HelixError::not_found(format!("Memory {} not found", id)));
+    }
+    Ok(())
+}
+
+pub fn delete_memory(id: i64) -> HelixResult<()> {
+    let conn = MEMORY_DB.lock();
+    let deleted = conn
+        .execute("DELETE FROM memory_entries WHERE id = ?1", params![id])
+        .map_err(|e| storage_error(format!("delete memory: {}", e)))?;
+    if deleted == 0 {
+        return Err(HelixError::not_found(format!("Memory {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Tag a `String` error from the SQLite helpers as a storage failure.
+fn storage_error(message: String) -> HelixError {
+    HelixError::new(codes::STORAGE_DB, message)
+}
 
-    let mut stmt = conn.prepare(&query).map_err(|e| format!("query: {}", e))?;
+pub fn list_memories(source: Option<&str>, limit: i64) -> Result<Vec<MemoryEntry>, String> {
+    let conn = MEMORY_DB.lock();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, key, content, source, tags, created_at, updated_at, owner_id, visibility, access_count, pinned
+             FROM memory_entries WHERE ?1 IS NULL OR source = ?1 ORDER BY updated_at DESC LIMIT ?2",
+        )
+        .map_err(|e| format!("query: {}", e))?;
     let entries = stmt
-        .query_map([], |row| {
+        .query_map(params![source, limit], |row| {
             let tags_str: String = row.get(4)?;
             let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
             Ok(MemoryEntry {
@@ -224,48 +486,64 @@ pub fn list_memories(source: Option<&str>, limit: i64) -> Result<Vec<MemoryEntry
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
                 score: 0.0,
+                owner_id: row.get(7)?,
+                visibility: row.get(8)?,
+                access_count: row.get(9)?,
+                confidence: 0.0,
+                pinned: row.get(10)?,
             })
         })
         .map_err(|e| format!("map: {}", e))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| format!("collect: {}", e))?;
 
-    Ok(entries)
+    let now = chrono::Utc::now();
+    Ok(entries
+        .into_iter()
+        .map(|mut entry| {
+            entry.confidence = compute_confidence(&entry, now);
+            entry
+        })
+        .collect())
 }
 
 // ============================================================================
 // Search — FTS5 Full-Text Search
 // ============================================================================
 
-/// Search memories using FTS5 full-text search.
-pub fn search_fts(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>, String> {
-    let conn = MEMORY_DB.lock();
-
-    // Sanitize query for FTS5: wrap each word in quotes to handle special chars
-    let fts_query = query
+/// Sanitize a query for FTS5: wrap each word in quotes to handle special chars.
+fn fts_match_query(query: &str) -> String {
+    query
         .split_whitespace()
         .map(|w| format!("\"{}\"", w.replace('"', "")))
         .collect::<Vec<_>>()
-        .join(" OR ");
+        .join(" OR ")
+}
+
+/// Search memories using FTS5 full-text search, limited to entries visible to `owner_id`.
+pub fn search_fts(query: &str, limit: i64, owner_id: Option<&str>) -> Result<Vec<MemorySearchResult>, String> {
+    let conn = MEMORY_DB.lock();
 
+    let fts_query = fts_match_query(query);
     if fts_query.is_empty() {
         return Ok(vec![]);
     }
 
     let mut stmt = conn
-        .prepare(
+        .prepare(&format!(
             "SELECT m.id, m.key, m.content, m.source, m.tags, m.created_at, m.updated_at,
-                    rank
+                    rank, m.owner_id, m.visibility, m.access_count, m.pinned
              FROM memory_fts f
              JOIN memory_entries m ON f.rowid = m.id
-             WHERE memory_fts MATCH ?1
+             WHERE memory_fts MATCH ?1 AND {}
              ORDER BY rank
              LIMIT ?2",
-        )
+            visibility_clause("m.", 3)
+        ))
         .map_err(|e| format!("FTS query: {}", e))?;
 
     let results = stmt
-        .query_map(params![fts_query, limit], |row| {
+        .query_map(params![fts_query, limit, owner_id], |row| {
             let tags_str: String = row.get(4)?;
             let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
             let rank: f64 = row.get(7)?;
@@ -279,10 +557,16 @@ pub fn search_fts(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>, St
                     created_at: row.get(5)?,
                     updated_at: row.get(6)?,
                     score: -rank, // FTS5 rank is negative (lower = better)
+                    owner_id: row.get(8)?,
+                    visibility: row.get(9)?,
+                    access_count: row.get(10)?,
+                    confidence: 0.0,
+                    pinned: row.get(11)?,
                 },
                 score: -rank,
                 match_type: "fts".to_string(),
                 snippet: None,
+                confidence: 0.0,
             })
         })
         .map_err(|e| format!("FTS map: {}", e))?
@@ -293,22 +577,23 @@ pub fn search_fts(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>, St
 }
 
 /// Fuzzy search: fall back to LIKE if FTS finds nothing.
-pub fn search_fuzzy(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>, String> {
+pub fn search_fuzzy(query: &str, limit: i64, owner_id: Option<&str>) -> Result<Vec<MemorySearchResult>, String> {
     let conn = MEMORY_DB.lock();
     let pattern = format!("%{}%", query);
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, key, content, source, tags, created_at, updated_at
+        .prepare(&format!(
+            "SELECT id, key, content, source, tags, created_at, updated_at, owner_id, visibility, access_count, pinned
              FROM memory_entries
-             WHERE key LIKE ?1 OR content LIKE ?1
+             WHERE (key LIKE ?1 OR content LIKE ?1) AND {}
              ORDER BY updated_at DESC
              LIMIT ?2",
-        )
+            visibility_clause("", 3)
+        ))
         .map_err(|e| format!("fuzzy query: {}", e))?;
 
     let results = stmt
-        .query_map(params![pattern, limit], |row| {
+        .query_map(params![pattern, limit, owner_id], |row| {
             let tags_str: String = row.get(4)?;
             let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
             Ok(MemorySearchResult {
@@ -321,10 +606,16 @@ pub fn search_fuzzy(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>,
                     created_at: row.get(5)?,
                     updated_at: row.get(6)?,
                     score: 0.5,
+                    owner_id: row.get(7)?,
+                    visibility: row.get(8)?,
+                    access_count: row.get(9)?,
+                    confidence: 0.0,
+                    pinned: row.get(10)?,
                 },
                 score: 0.5,
                 match_type: "fuzzy".to_string(),
                 snippet: None,
+                confidence: 0.0,
             })
         })
         .map_err(|e| format!("fuzzy map: {}", e))?
@@ -335,53 +626,380 @@ pub fn search_fuzzy(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>,
 }
 
 // ============================================================================
-// Hybrid Search (FTS + vector fallback)
+// Search — Tags
 // ============================================================================
 
-/// Hybrid search: try FTS5 first, fall back to fuzzy LIKE, apply temporal decay.
-pub fn search_hybrid(query: &str, limit: i64) -> Result<Vec<MemorySearchResult>, String> {
-    // 1. Try FTS5
-    let mut results = search_fts(query, limit)?;
+/// Score of a tag search hit: one point per matched tag, plus the FTS rank
+/// squashed into [0, 1) so content relevance only orders entries with the same tag hits.
+fn tag_match_score(tag_hits: i64, rank: Option<f64>) -> f64 {
+    let fts = rank.map_or(0.0, |r| {
+        let relevance = (-r).max(0.0);
+        relevance / (1.0 + relevance)
+    });
+    tag_hits as f64 + fts
+}
 
-    // 2. If no FTS results, fall back to fuzzy
-    if results.is_empty() {
-        results = search_fuzzy(query, limit)?;
+fn search_by_tag_in(
+    conn: &Connection,
+    tags: &[String],
+    query: Option<&str>,
+    limit: i64,
+    owner_id: Option<&str>,
+) -> Result<Vec<MemorySearchResult>, String> {
+    let tags: Vec<&str> = tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    if tags.is_empty() {
+        return Ok(vec![]);
     }
+    let tags_json = serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string());
+
+    // Only match content, not the FTS copy of the tags column
+    let fts_match = query
+        .map(fts_match_query)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("content : ({})", q));
 
-    // 3. Apply temporal decay: recent memories get a boost
-    let now = chrono::Utc::now().timestamp() as f64;
-    let half_life_days: f64 = 30.0;
-    let half_life_secs = half_life_days * 86400.0;
+    let (fts_join, fts_filter, rank_column, order) = if fts_match.is_some() {
+        ("JOIN memory_fts f ON f.rowid = m.id", "memory_fts MATCH ?4", "rank", "tag_hits DESC, rank")
+    } else {
+        ("", "?4 IS NULL", "NULL", "tag_hits DESC, m.updated_at DESC")
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT m.id, m.key, m.content, m.source, m.tags, m.created_at, m.updated_at,
+                    m.owner_id, m.visibility, m.access_count, m.pinned, {rank} AS fts_rank,
+                    (SELECT COUNT(DISTINCT t.value) FROM json_each(m.tags) t
+                     WHERE t.value IN (SELECT value FROM json_each(?1))) AS tag_hits
+             FROM memory_entries m {join}
+             WHERE m.tags != '[]' AND tag_hits > 0 AND {filter} AND {visibility}
+             ORDER BY {order}
+             LIMIT ?2",
+            rank = rank_column,
+            join = fts_join,
+            filter = fts_filter,
+            visibility = visibility_clause("m.", 3),
+            order = order,
+        ))
+        .map_err(|e| format!("tag query: {}", e))?;
 
-    for result in &mut results {
-        if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&result.entry.updated_at) {
-            let age_secs = now - ts.timestamp() as f64;
-            let decay = (0.5_f64).powf(age_secs / half_life_secs);
-            result.score *= decay.max(0.1); // floor at 10% of original score
+    let results = stmt
+        .query_map(params![tags_json, limit, owner_id, fts_match], |row| {
+            let tags_str: String = row.get(4)?;
+            let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
+            let rank: Option<f64> = row.get(11)?;
+            let score = tag_match_score(row.get(12)?, rank);
+            Ok(MemorySearchResult {
+                entry: MemoryEntry {
+                    id: row.get(0)?,
+                    key: row.get(1)?,
+                    content: row.get(2)?,
+                    source: row.get(3)?,
+                    tags,
+                    created_at: row.get(5)?,
+                    updated_at: row.get(6)?,
+                    score,
+                    owner_id: row.get(7)?,
+                    visibility: row.get(8)?,
+                    access_count: row.get(9)?,
+                    confidence: 0.0,
+                    pinned: row.get(10)?,
+                },
+                score,
+                match_type: "tag".to_string(),
+                snippet: None,
+                confidence: 0.0,
+            })
+        })
+        .map_err(|e| format!("tag map: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("tag collect: {}", e))?;
+
+    Ok(results)
+}
+
+/// Entries carrying any of `tags`, visible to `owner_id`, best first. Entries
+/// matching more tags rank higher; `query` further requires an FTS5 match on content.
+pub fn search_by_tag(
+    tags: &[String],
+    query: Option<&str>,
+    limit: i64,
+    owner_id: Option<&str>,
+) -> Result<Vec<MemorySearchResult>, String> {
+    let mut results = search_by_tag_in(&MEMORY_DB.lock(), tags, query, limit, owner_id)?;
+    record_search_hits(&mut results);
+    Ok(results)
+}
+
+// ============================================================================
+// Hybrid Search (FTS + vector, reciprocal-rank fusion)
+// ============================================================================
+
+/// Until when vector search is skipped because the embedding provider failed.
+static VECTOR_UNAVAILABLE_UNTIL: Lazy<Mutex<Option<std::time::Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// Embedding of the query, or None when no memory has an embedding yet or the
+/// provider is not configured, unreachable or slow (then retried after a back-off).
+async fn query_embedding(query: &str) -> Option<Vec<f32>> {
+    if query.trim().is_empty() {
+        return None;
+    }
+    if VECTOR_UNAVAILABLE_UNTIL.lock().is_some_and(|until| std::time::Instant::now() < until) {
+        return None;
+    }
+    let has_embeddings: bool = MEMORY_DB
+        .lock()
+        .query_row("SELECT EXISTS(SELECT 1 FROM memory_entries WHERE embedding IS NOT NULL)", [], |r| r.get(0))
+        .unwrap_or(false);
+    if !has_embeddings {
+        return None;
+    }
+
+    let error = match tokio::time::timeout(QUERY_EMBED_TIMEOUT, generate_embedding(query)).await {
+        Ok(Ok(embedding)) => return Some(embedding),
+        Ok(Err(e)) if e.code == codes::PROVIDER_NOT_CONFIGURED => return None,
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("timed out after {:?}", QUERY_EMBED_TIMEOUT),
+    };
+    warn!("[memory] Query embedding failed, searching without vectors for {:?}: {}", VECTOR_BACKOFF, error);
+    *VECTOR_UNAVAILABLE_UNTIL.lock() = Some(std::time::Instant::now() + VECTOR_BACKOFF);
+    None
+}
+
+/// Merge ranked lists by reciprocal-rank fusion. Each result scores
+/// `sum(1 / (RRF_K + rank))` over the lists it appears in, normalized so a
+/// result ranked first by every non-empty list scores 1.0. Results found by more than
+/// one list are marked `hybrid`.
+fn fuse_rrf(lists: Vec<Vec<MemorySearchResult>>, limit: usize) -> Vec<MemorySearchResult> {
+    let max_score = lists.iter().filter(|l| !l.is_empty()).count().max(1) as f64 / (RRF_K + 1.0);
+    let mut fused: Vec<MemorySearchResult> = Vec::new();
+    let mut index: HashMap<i64, usize> = HashMap::new();
+    for list in lists {
+        for (rank, mut result) in list.into_iter().enumerate() {
+            let contribution = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match index.get(&result.entry.id) {
+                Some(&i) => {
+                    fused[i].score += contribution;
+                    fused[i].match_type = "hybrid".to_string();
+                }
+                None => {
+                    index.insert(result.entry.id, fused.len());
+                    result.score = contribution;
+                    fused.push(result);
+                }
+            }
         }
     }
+    for result in fused.iter_mut() {
+        result.score /= max_score;
+        result.entry.score = result.score;
+    }
+    fused.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    fused.truncate(limit);
+    fused
+}
+
+/// Hybrid search with an already computed query embedding: fuse FTS5 and (when
+/// `query_embedding` is given) vector rankings, fall back to fuzzy LIKE when
+/// both are empty, then apply temporal decay and pin boost.
+pub fn search_hybrid_with_embedding(
+    query: &str,
+    query_embedding: Option<&[f32]>,
+    limit: i64,
+    owner_id: Option<&str>,
+) -> Result<Vec<MemorySearchResult>, String> {
+    let candidates = limit.max(1) * RRF_CANDIDATE_FACTOR;
+
+    // 1. Rank with FTS5, and by similarity when an embedding is available
+    let mut lists = vec![search_fts(query, candidates, owner_id)?];
+    if let Some(embedding) = query_embedding {
+        let mut vector = vector_candidates(embedding, candidates, owner_id)?;
+        vector.retain(|r| r.score >= VECTOR_MIN_SIMILARITY);
+        lists.push(vector);
+    }
+
+    // 2. Fuse the rankings; if nothing matched, fall back to fuzzy
+    let mut results = fuse_rrf(lists, limit.max(0) as usize);
+    if results.is_empty() {
+        results = search_fuzzy(query, limit, owner_id)?;
+    }
+
+    // 3-4. Apply temporal decay and pin boost, then re-sort
+    rescore(&mut results, chrono::Utc::now());
+
+    record_search_hits(&mut results);
+    Ok(results)
+}
 
-    // 4. Re-sort by adjusted score
+/// Hybrid search: FTS5 fused with vector similarity when memories have
+/// embeddings and the provider answers, FTS-only otherwise.
+pub async fn search_hybrid(query: &str, limit: i64, owner_id: Option<&str>) -> Result<Vec<MemorySearchResult>, String> {
+    let embedding = query_embedding(query).await;
+    search_hybrid_with_embedding(query, embedding.as_deref(), limit, owner_id)
+}
+
+/// Decay unpinned results by age (floored at 10% of the original score),
+/// boost pinned ones, and sort best first.
+fn rescore(results: &mut [MemorySearchResult], now: chrono::DateTime<chrono::Utc>) {
+    for result in results.iter_mut() {
+        if result.entry.pinned {
+            result.score *= PINNED_BOOST;
+        } else if let Some(decay) = recency_decay(&result.entry.updated_at, now) {
+            result.score *= decay.max(0.1);
+        }
+    }
     results.sort_by(|a, b| {
         b.score
             .partial_cmp(&a.score)
             .unwrap_or(std::cmp::Ordering::Equal)
     });
+}
 
-    Ok(results)
+// ============================================================================
+// Automatic Recall
+// ============================================================================
+
+/// Characters of each memory's content shown in the recalled-memories prompt.
+const AUTO_RECALL_CONTENT_CHARS: usize = 500;
+
+/// Keep results scoring at least `min_score`, best first, at most `top_k`.
+fn select_relevant(mut results: Vec<MemorySearchResult>, top_k: usize, min_score: f64) -> Vec<MemorySearchResult> {
+    results.retain(|r| r.score >= min_score);
+    results.truncate(top_k);
+    results
+}
+
+fn format_recalled_memories(results: &[MemorySearchResult]) -> String {
+    let mut out = String::from(
+        "## Relevant Memories\n\
+         Retrieved automatically for the current message. Use them if they help; \
+         they may be outdated.\n",
+    );
+    for r in results {
+        let content: String = r.entry.content.chars().take(AUTO_RECALL_CONTENT_CHARS).collect();
+        let ellipsis = if r.entry.content.chars().count() > AUTO_RECALL_CONTENT_CHARS { "…" } else { "" };
+        out.push_str(&format!("- **{}**: {}{}\n", r.entry.key, content, ellipsis));
+    }
+    out
+}
+
+/// Search memories visible to `owner_id` with the user's message and build a
+/// prompt section from the relevant ones. Returns the section and the
+/// injected results, or None when auto recall is off or nothing qualifies.
+pub async fn recall_for_prompt(
+    query: &str,
+    owner_id: &str,
+    config: &crate::models::config::AutoRecallConfig,
+) -> Option<(String, Vec<MemorySearchResult>)> {
+    if !config.enabled || config.top_k == 0 || query.trim().is_empty() {
+        return None;
+    }
+    let results = match search_hybrid(query, config.top_k as i64, Some(owner_id)).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("[memory] Auto recall failed: {}", e);
+            return None;
+        }
+    };
+    let relevant = select_relevant(results, config.top_k, config.min_score);
+    if relevant.is_empty() {
+        return None;
+    }
+    Some((format_recalled_memories(&relevant), relevant))
+}
+
+// ============================================================================
+// Confidence Scoring
+// ============================================================================
+
+/// Temporal decay factor (1.0 = now, 0.5 after one half-life). None if the timestamp doesn't parse.
+fn recency_decay(updated_at: &str, now: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let ts = chrono::DateTime::parse_from_rfc3339(updated_at).ok()?;
+    let age_secs = (now.timestamp() - ts.timestamp()).max(0) as f64;
+    Some((0.5_f64).powf(age_secs / (DECAY_HALF_LIFE_DAYS * 86400.0)))
+}
+
+/// How much a memory's origin is trusted. Session-id sources are agent-written.
+fn source_trust(source: &str) -> f64 {
+    match source {
+        "user" => 1.0,
+        "file" | "note" => 0.8,
+        "conversation" => 0.7,
+        _ => 0.5,
+    }
+}
+
+/// Saturating boost for entries that keep getting recalled (0 hits = 0.0).
+fn access_frequency(access_count: i64) -> f64 {
+    let n = access_count.max(0) as f64;
+    n / (n + ACCESS_HALF_SATURATION)
+}
+
+/// Confidence in 0.0–1.0 from recency, source trust and access frequency.
+/// Pinned entries never age.
+pub fn compute_confidence(entry: &MemoryEntry, now: chrono::DateTime<chrono::Utc>) -> f64 {
+    let (w_recency, w_source, w_access) = CONFIDENCE_WEIGHTS;
+    let recency = if entry.pinned {
+        1.0
+    } else {
+        recency_decay(&entry.updated_at, now).unwrap_or(0.0)
+    };
+    let score = w_recency * recency
+        + w_source * source_trust(&entry.source)
+        + w_access * access_frequency(entry.access_count);
+    score.clamp(0.0, 1.0)
+}
+
+/// Increment an entry's search hit counter.
+pub fn update_access_count(id: i64) -> Result<(), String> {
+    let conn = MEMORY_DB.lock();
+    conn.execute(
+        "UPDATE memory_entries SET access_count = access_count + 1 WHERE id = ?1",
+        params![id],
+    )
+    .map_err(|e| format!("update access count: {}", e))?;
+    Ok(())
+}
+
+/// Count a search hit for every returned entry and fill in their confidence.
+fn record_search_hits(results: &mut [MemorySearchResult]) {
+    let now = chrono::Utc::now();
+    for result in results.iter_mut() {
+        if update_access_count(result.entry.id).is_ok() {
+            result.entry.access_count += 1;
+        }
+        result.entry.confidence = compute_confidence(&result.entry, now);
+        result.confidence = result.entry.confidence;
+    }
 }
 
 // ============================================================================
 // Embeddings (OpenAI text-embedding-3-small)
 // ============================================================================
 
+/// Default number of entries sent per request by `memory_embed_all`.
+const EMBED_BATCH_SIZE: usize = 64;
+/// Upper bound on `batch_size`; providers cap the size of an `input` array.
+const EMBED_BATCH_MAX: usize = 512;
+/// Pause between batches so a large backfill stays under provider rate limits.
+const EMBED_BATCH_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+/// Wait before retrying a failed batch when the provider suggests none.
+const EMBED_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Generate embeddings for text using the configured AI provider.
-pub async fn generate_embedding(text: &str) -> Result<Vec<f32>, String> {
-    let config = crate::modules::config::load_app_config().map_err(|e| format!("config: {}", e))?;
+pub async fn generate_embedding(text: &str) -> HelixResult<Vec<f32>> {
+    generate_embeddings(&[text.to_string()])
+        .await?
+        .pop()
+        .ok_or_else(|| HelixError::new(codes::PROVIDER_BAD_RESPONSE, "No embedding in response"))
+}
+
+/// Generate embeddings for several texts in one request; results follow input order.
+pub async fn generate_embeddings(texts: &[String]) -> HelixResult<Vec<Vec<f32>>> {
+    let config = crate::modules::config::load_app_config()
+        .map_err(|e| HelixError::new(codes::CONFIG_LOAD_FAILED, format!("config: {}", e)))?;
     let ai = &config.ai_config;
 
     if ai.api_key.is_empty() {
-        return Err("API key not configured".to_string());
+        return Err(HelixError::new(codes::PROVIDER_NOT_CONFIGURED, "API key not configured"));
     }
 
     let url = format!("{}/embeddings", ai.base_url.trim_end_matches('/'));
@@ -394,7 +1012,7 @@ pub async fn generate_embedding(text: &str) -> Result<Vec<f32>, String> {
 
     let body = json!({
         "model": "text-embedding-3-small",
-        "input": text,
+        "input": texts,
     });
 
     let resp = client
@@ -404,43 +1022,165 @@ pub async fn generate_embedding(text: &str) -> Result<Vec<f32>, String> {
         .json(&body)
         .send()
         .await
-        .map_err(|e| format!("embedding request failed: {}", e))?;
-
-    if !resp.status().is_success() {
-        let err = resp.text().await.unwrap_or_default();
-        return Err(format!(
-            "embedding API error: {}",
-            &err[..err.len().min(200)]
-        ));
+        .map_err(|e| {
+            let message = format!("embedding request failed: {}", e);
+            if e.is_timeout() {
+                HelixError::new(codes::NETWORK_TIMEOUT, message).retriable()
+            } else {
+                HelixError::network_error(message)
+            }
+        })?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let retry_after_ms = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|secs| secs * 1000);
+        let body = resp.text().await.unwrap_or_default();
+        let body = safe_truncate(&body, 200);
+        let message = format!("embedding API error ({}): {}", status, body);
+        let details = json!({ "status": status.as_u16(), "body": body });
+        return Err(match status.as_u16() {
+            429 => HelixError::rate_limited(retry_after_ms.unwrap_or(EMBED_RETRY_DELAY.as_millis() as u64)),
+            401 | 403 => HelixError::new(codes::PROVIDER_AUTH_FAILED, message).with_detail(details),
+            500..=599 => HelixError::new(codes::PROVIDER_UNAVAILABLE, message).with_detail(details).retriable(),
+            _ => HelixError::new(codes::PROVIDER_BAD_REQUEST, message).with_detail(details),
+        });
     }
 
     let data: Value = resp
         .json()
         .await
-        .map_err(|e| format!("parse embedding: {}", e))?;
-    let embedding = data["data"][0]["embedding"]
-        .as_array()
-        .ok_or("No embedding in response")?
-        .iter()
-        .filter_map(|v| v.as_f64().map(|f| f as f32))
-        .collect::<Vec<f32>>();
+        .map_err(|e| HelixError::new(codes::PROVIDER_BAD_RESPONSE, format!("parse embedding: {}", e)))?;
+    parse_embeddings_response(&data, texts.len()).map_err(|e| HelixError::new(codes::PROVIDER_BAD_RESPONSE, e))
+}
 
-    if embedding.is_empty() {
-        return Err("Empty embedding returned".to_string());
+/// Extract `expected` embeddings from an embeddings API response, ordered by
+/// each item's `index` (providers may return them out of order).
+fn parse_embeddings_response(data: &Value, expected: usize) -> Result<Vec<Vec<f32>>, String> {
+    let items = data["data"].as_array().ok_or("No embedding in response")?;
+    let mut embeddings: Vec<Option<Vec<f32>>> = vec![None; expected];
+    for (pos, item) in items.iter().enumerate() {
+        let index = item["index"].as_u64().map(|i| i as usize).unwrap_or(pos);
+        let embedding = item["embedding"]
+            .as_array()
+            .ok_or("No embedding in response")?
+            .iter()
+            .filter_map(|v| v.as_f64().map(|f| f as f32))
+            .collect::<Vec<f32>>();
+        if embedding.is_empty() {
+            return Err("Empty embedding returned".to_string());
+        }
+        match embeddings.get_mut(index) {
+            Some(slot) => *slot = Some(embedding),
+            None => return Err(format!("embedding index {} out of range", index)),
+        }
     }
+    embeddings
+        .into_iter()
+        .enumerate()
+        .map(|(i, e)| e.ok_or_else(|| format!("missing embedding for input {}", i)))
+        .collect()
+}
 
-    Ok(embedding)
+/// Progress of a `memory_embed_all` backfill, emitted as `memory://embed-progress`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbedProgress {
+    pub total: usize,
+    pub embedded: usize,
+    pub failed: usize,
+    pub finished: bool,
+    pub error: Option<HelixError>,
+}
+
+/// Up to `limit` entries without an embedding and with id greater than `after_id`, by id.
+fn unembedded_entries_in(conn: &Connection, after_id: i64, limit: usize) -> Result<Vec<(i64, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM memory_entries WHERE embedding IS NULL AND id > ?1 ORDER BY id LIMIT ?2")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![after_id, limit as i64], |r| Ok((r.get(0)?, r.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Embed every entry that has no embedding yet, `batch_size` entries per request.
+/// A retriable batch failure is retried once after the provider's suggested
+/// wait; any other failure stops the backfill and is reported alongside the
+/// counts so far.
+pub async fn embed_all_missing(batch_size: usize) -> HelixResult<EmbedProgress> {
+    let batch_size = batch_size.clamp(1, EMBED_BATCH_MAX);
+    let total: i64 = MEMORY_DB
+        .lock()
+        .query_row("SELECT COUNT(*) FROM memory_entries WHERE embedding IS NULL", [], |r| r.get(0))
+        .map_err(|e| storage_error(format!("count entries: {}", e)))?;
+    let mut progress = EmbedProgress { total: total as usize, ..Default::default() };
+    info!("[memory] Embedding {} entries in batches of {}", progress.total, batch_size);
+
+    let mut after_id = 0;
+    loop {
+        let batch = unembedded_entries_in(&MEMORY_DB.lock(), after_id, batch_size).map_err(storage_error)?;
+        let Some(&(last_id, _)) = batch.last() else { break };
+        after_id = last_id;
+
+        let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+        let result = match generate_embeddings(&texts).await {
+            Ok(embeddings) => Ok(embeddings),
+            Err(e) if e.retriable => {
+                let wait = e
+                    .retry_after_ms()
+                    .map(std::time::Duration::from_millis)
+                    .unwrap_or(EMBED_RETRY_DELAY);
+                warn!("[memory] Embedding batch failed, retrying in {:?}: {}", wait, e);
+                tokio::time::sleep(wait).await;
+                generate_embeddings(&texts).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(embeddings) => {
+                for ((id, _), embedding) in batch.iter().zip(&embeddings) {
+                    match store_embedding(*id, embedding) {
+                        Ok(()) => progress.embedded += 1,
+                        Err(e) => {
+                            warn!("[memory] {}", e);
+                            progress.failed += 1;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("[memory] Embedding backfill stopped: {}", e);
+                progress.failed += batch.len();
+                progress.error = Some(e);
+                break;
+            }
+        }
+        emit_custom_event("memory://embed-progress", progress.clone());
+        tokio::time::sleep(EMBED_BATCH_DELAY).await;
+    }
+
+    progress.finished = true;
+    emit_custom_event("memory://embed-progress", progress.clone());
+    info!(
+        "[memory] Embedding backfill done: {}/{} embedded, {} failed",
+        progress.embedded, progress.total, progress.failed
+    );
+    Ok(progress)
 }
 
 /// Store embedding for a memory entry.
-pub fn store_embedding(entry_id: i64, embedding: &[f32]) -> Result<(), String> {
+pub fn store_embedding(entry_id: i64, embedding: &[f32]) -> HelixResult<()> {
     let conn = MEMORY_DB.lock();
     let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
     conn.execute(
         "UPDATE memory_entries SET embedding = ?1 WHERE id = ?2",
         params![bytes, entry_id],
     )
-    .map_err(|e| format!("store embedding: {}", e))?;
+    .map_err(|e| storage_error(format!("store embedding: {}", e)))?;
     Ok(())
 }
 
@@ -469,19 +1209,32 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 pub fn search_vector(
     query_embedding: &[f32],
     limit: i64,
+    owner_id: Option<&str>,
+) -> Result<Vec<MemorySearchResult>, String> {
+    let mut scored = vector_candidates(query_embedding, limit, owner_id)?;
+    record_search_hits(&mut scored);
+    Ok(scored)
+}
+
+/// Entries visible to `owner_id` most similar to `query_embedding`, best first.
+fn vector_candidates(
+    query_embedding: &[f32],
+    limit: i64,
+    owner_id: Option<&str>,
 ) -> Result<Vec<MemorySearchResult>, String> {
     let conn = MEMORY_DB.lock();
 
     let mut stmt = conn
-        .prepare(
-            "SELECT id, key, content, source, tags, created_at, updated_at, embedding
+        .prepare(&format!(
+            "SELECT id, key, content, source, tags, created_at, updated_at, embedding, owner_id, visibility, access_count, pinned
              FROM memory_entries
-             WHERE embedding IS NOT NULL",
-        )
+             WHERE embedding IS NOT NULL AND {}",
+            visibility_clause("", 1)
+        ))
         .map_err(|e| format!("vector query: {}", e))?;
 
     let mut scored: Vec<MemorySearchResult> = stmt
-        .query_map([], |row| {
+        .query_map(params![owner_id], |row| {
             let tags_str: String = row.get(4)?;
             let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
             let emb_bytes: Vec<u8> = row.get(7)?;
@@ -504,10 +1257,16 @@ pub fn search_vector(
                     created_at: row.get(5)?,
                     updated_at: row.get(6)?,
                     score: sim as f64,
+                    owner_id: row.get(8)?,
+                    visibility: row.get(9)?,
+                    access_count: row.get(10)?,
+                    confidence: 0.0,
+                    pinned: row.get(11)?,
                 },
                 score: sim as f64,
                 match_type: "vector".to_string(),
                 snippet: None,
+                confidence: 0.0,
             })
         })
         .map_err(|e| format!("vector map: {}", e))?
@@ -521,7 +1280,6 @@ pub fn search_vector(
             .unwrap_or(std::cmp::Ordering::Equal)
     });
     scored.truncate(limit as usize);
-
     Ok(scored)
 }
 
@@ -567,6 +1325,7 @@ pub fn get_memory_stats() -> Result<MemoryStats, String> {
         total_with_embeddings: with_embeddings,
         sources,
         db_size_bytes: db_size,
+        fts: check_fts_consistency_in(&conn).ok(),
     })
 }
 
@@ -590,10 +1349,43 @@ pub fn save_conversation_memory(
     let content = format!("Q: {}\nA: {}", user_msg, assistant_msg);
     let tags = vec!["conversation".to_string(), account_id.to_string()];
 
-    store_memory(&key, &content, "conversation", &tags)?;
+    store_memory(&key, &content, "conversation", &tags, Some(account_id))?;
     Ok(())
 }
 
+/// Import a session's stored history as Q&A memories, one entry per
+/// user → assistant pair. Keys are `session:{id}:{index}`, so re-importing
+/// the same session updates entries instead of duplicating them.
+/// Returns the entry ids so callers can embed them.
+pub fn import_session_history(session_id: &str, max_messages: i64) -> Result<Vec<i64>, String> {
+    let history = crate::modules::database::get_conversation_history(session_id, max_messages)?;
+    let tags = vec!["conversation".to_string(), session_id.to_string()];
+
+    let mut ids = Vec::new();
+    let mut pending_question: Option<&str> = None;
+    for entry in &history {
+        match entry.role.as_str() {
+            "user" => pending_question = Some(&entry.content),
+            "assistant" => {
+                if let Some(question) = pending_question.take() {
+                    let key = format!("session:{}:{}", session_id, ids.len());
+                    let content = format!("Q: {}\nA: {}", question, entry.content);
+                    ids.push(store_memory(&key, &content, "conversation", &tags, Some(session_id))?.id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    info!(
+        "[memory] Imported {} Q&A pairs from session '{}' ({} messages scanned)",
+        ids.len(),
+        session_id,
+        history.len()
+    );
+    Ok(ids)
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -602,8 +1394,24 @@ pub fn save_conversation_memory(
 pub async fn memory_search(
     query: String,
     limit: Option<i64>,
-) -> Result<Vec<MemorySearchResult>, String> {
-    search_hybrid(&query, limit.unwrap_or(20))
+    owner_id: Option<String>,
+) -> HelixResult<Vec<MemorySearchResult>> {
+    search_hybrid(&query, limit.unwrap_or(20), owner_id.as_deref())
+        .await
+        .map_err(storage_error)
+}
+
+#[tauri::command]
+pub async fn memory_search_by_tag(
+    tags: Vec<String>,
+    query: Option<String>,
+    limit: Option<i64>,
+    owner_id: Option<String>,
+) -> HelixResult<Vec<MemorySearchResult>> {
+    if tags.iter().all(|t| t.trim().is_empty()) {
+        return Err(HelixError::validation_error(vec!["tags is empty".to_string()]));
+    }
+    search_by_tag(&tags, query.as_deref(), limit.unwrap_or(20), owner_id.as_deref()).map_err(storage_error)
 }
 
 #[tauri::command]
@@ -612,17 +1420,54 @@ pub async fn memory_store_entry(
     content: String,
     source: Option<String>,
     tags: Option<Vec<String>>,
-) -> Result<MemoryEntry, String> {
+    owner_id: Option<String>,
+) -> HelixResult<MemoryEntry> {
+    let mut problems = Vec::new();
+    if key.trim().is_empty() {
+        problems.push("key is empty".to_string());
+    }
+    if content.trim().is_empty() {
+        problems.push("content is empty".to_string());
+    }
+    if !problems.is_empty() {
+        return Err(HelixError::validation_error(problems));
+    }
+    // Trimmed and deduplicated so memory_search_by_tag matches them exactly
+    let mut clean_tags: Vec<String> = Vec::new();
+    for tag in tags.unwrap_or_default() {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !clean_tags.contains(&tag) {
+            clean_tags.push(tag);
+        }
+    }
     store_memory(
         &key,
         &content,
         &source.unwrap_or_else(|| "user".to_string()),
-        &tags.unwrap_or_default(),
+        &clean_tags,
+        owner_id.as_deref(),
     )
+    .map_err(storage_error)
 }
 
 #[tauri::command]
-pub async fn memory_delete(id: i64) -> Result<(), String> {
+pub async fn memory_set_visibility(id: i64, visibility: String) -> HelixResult<()> {
+    set_visibility(id, &visibility)
+}
+
+/// Pin a memory so it surfaces regardless of age.
+#[tauri::command]
+pub async fn memory_pin(id: i64, pinned: bool) -> HelixResult<()> {
+    set_pinned(id, pinned)
+}
+
+#[tauri::command]
+pub async fn memory_update_access_count(id: i64) -> HelixResult<()> {
+    update_access_count(id).map_err(storage_error)
+}
+
+#[tauri::command]
+pub async fn memory_delete(id: i64) -> HelixResult<()> {
     delete_memory(id)
 }
 
@@ -630,17 +1475,23 @@ pub async fn memory_delete(id: i64) -> Result<(), String> {
 pub async fn memory_list(
     source: Option<String>,
     limit: Option<i64>,
-) -> Result<Vec<MemoryEntry>, String> {
-    list_memories(source.as_deref(), limit.unwrap_or(50))
+) -> HelixResult<Vec<MemoryEntry>> {
+    list_memories(source.as_deref(), limit.unwrap_or(50)).map_err(storage_error)
+}
+
+#[tauri::command]
+pub async fn memory_stats() -> HelixResult<MemoryStats> {
+    get_memory_stats().map_err(storage_error)
 }
 
+/// Rebuild the full-text search index and verify it matches the memory table.
 #[tauri::command]
-pub async fn memory_stats() -> Result<MemoryStats, String> {
-    get_memory_stats()
+pub async fn memory_reindex() -> HelixResult<FtsConsistency> {
+    reindex_fts().map_err(storage_error)
 }
 
 #[tauri::command]
-pub async fn memory_embed(entry_id: i64) -> Result<String, String> {
+pub async fn memory_embed(entry_id: i64) -> HelixResult<String> {
     let content = {
         let conn = MEMORY_DB.lock();
         conn.query_row(
@@ -648,7 +1499,10 @@ pub async fn memory_embed(entry_id: i64) -> Result<String, String> {
             params![entry_id],
             |r| r.get::<_, String>(0),
         )
-        .map_err(|e| format!("find entry: {}", e))?
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => HelixError::not_found(format!("Memory {} not found", entry_id)),
+            e => storage_error(format!("find entry: {}", e)),
+        })?
     };
 
     let embedding = generate_embedding(&content).await?;
@@ -660,13 +1514,47 @@ pub async fn memory_embed(entry_id: i64) -> Result<String, String> {
     ))
 }
 
+/// Embed all entries that have no embedding yet; progress is emitted as
+/// `memory://embed-progress` events.
+#[tauri::command]
+pub async fn memory_embed_all(batch_size: Option<usize>) -> HelixResult<EmbedProgress> {
+    embed_all_missing(batch_size.unwrap_or(EMBED_BATCH_SIZE)).await
+}
+
 #[tauri::command]
 pub async fn memory_save_conversation(
     account_id: String,
     user_msg: String,
     assistant_msg: String,
-) -> Result<(), String> {
-    save_conversation_memory(&account_id, &user_msg, &assistant_msg)
+) -> HelixResult<()> {
+    save_conversation_memory(&account_id, &user_msg, &assistant_msg).map_err(storage_error)
+}
+
+#[tauri::command]
+pub async fn memory_import_from_session(
+    session_id: String,
+    max_messages: Option<i64>,
+) -> HelixResult<usize> {
+    let ids = import_session_history(&session_id, max_messages.unwrap_or(500)).map_err(storage_error)?;
+    let count = ids.len();
+
+    // Embed in the background when an API key is configured; search falls
+    // back to FTS for entries that have no embedding yet.
+    let has_api_key = crate::modules::config::load_app_config()
+        .map(|c| !c.ai_config.api_key.is_empty())
+        .unwrap_or(false);
+    if has_api_key && !ids.is_empty() {
+        tokio::spawn(async move {
+            for id in ids {
+                if let Err(e) = memory_embed(id).await {
+                    tracing::warn!("[memory] Embedding imported entry {} failed: {}", id, e);
+                    break;
+                }
+            }
+        });
+    }
+
+    Ok(count)
 }
 
 // ============================================================================
@@ -974,3 +1862,241 @@ pub async fn compact_conversation_history(account_id: &str) -> Result<usize, Str
 
     Ok(compacted_count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(source: &str, updated_at: &str, access_count: i64) -> MemoryEntry {
+        MemoryEntry {
+            id: 1,
+            key: "k".to_string(),
+            content: "c".to_string(),
+            source: source.to_string(),
+            tags: vec![],
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+            score: 0.0,
+            owner_id: None,
+            visibility: default_visibility(),
+            access_count,
+            confidence: 0.0,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn test_confidence_prefers_recent_accessed_user_memory() {
+        let now = chrono::Utc::now();
+        let recent = (now - chrono::Duration::days(1)).to_rfc3339();
+        let old = (now - chrono::Duration::days(365)).to_rfc3339();
+
+        let fresh_user = compute_confidence(&entry("user", &recent, 3), now);
+        let stale_agent = compute_confidence(&entry("agent", &old, 0), now);
+        assert!(fresh_user > stale_agent, "{} <= {}", fresh_user, stale_agent);
+        assert!((0.0..=1.0).contains(&fresh_user));
+        assert!((0.0..=1.0).contains(&stale_agent));
+    }
+
+    #[test]
+    fn test_select_and_format_recalled_memories() {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = |key: &str, score: f64| {
+            let mut e = entry("user", &now, 0);
+            e.key = key.to_string();
+            e.content = "x".repeat(AUTO_RECALL_CONTENT_CHARS + 10);
+            MemorySearchResult { entry: e, score, match_type: "fts".to_string(), snippet: None, confidence: 0.0 }
+        };
+        let picked = select_relevant(
+            vec![result("lang", 0.9), result("city", 0.000001), result("pet", 0.5), result("job", 0.4)],
+            2,
+            0.3,
+        );
+        let keys: Vec<&str> = picked.iter().map(|r| r.entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["lang", "pet"]);
+
+        let prompt = format_recalled_memories(&picked);
+        assert!(prompt.starts_with("## Relevant Memories"));
+        assert!(prompt.contains("- **pet**: "));
+        assert!(prompt.contains("…"));
+        assert!(!prompt.contains("city"));
+    }
+
+    #[test]
+    fn test_fuse_rrf_rewards_agreement() {
+        let now = chrono::Utc::now().to_rfc3339();
+        let ranked = |ids: &[i64], match_type: &str| -> Vec<MemorySearchResult> {
+            ids.iter()
+                .map(|&id| {
+                    let mut e = entry("user", &now, 0);
+                    e.id = id;
+                    MemorySearchResult { entry: e, score: 0.0, match_type: match_type.to_string(), snippet: None, confidence: 0.0 }
+                })
+                .collect()
+        };
+
+        // 2 is second for both rankers and beats each ranker's own favourite
+        let fused = fuse_rrf(vec![ranked(&[1, 2, 3], "fts"), ranked(&[4, 2], "vector")], 10);
+        let ids: Vec<i64> = fused.iter().map(|r| r.entry.id).collect();
+        assert_eq!(ids, vec![2, 1, 4, 3]);
+        assert_eq!(fused[0].match_type, "hybrid");
+        assert_eq!(fused[1].match_type, "fts");
+        assert!(fused.iter().all(|r| r.score > 0.0 && r.score <= 1.0));
+
+        // FTS-only (empty vector list) keeps the full score range
+        let fused = fuse_rrf(vec![ranked(&[7, 8], "fts"), vec![]], 1);
+        assert_eq!(fused.len(), 1);
+        assert!((fused[0].score - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_confidence_components() {
+        let now = chrono::Utc::now();
+        let just_now = now.to_rfc3339();
+        // Brand new, never recalled user memory: 0.4 * 1.0 + 0.3 * 1.0 + 0
+        let c = compute_confidence(&entry("user", &just_now, 0), now);
+        assert!((c - 0.7).abs() < 1e-6);
+        // Access boost is monotonic and bounded
+        assert!(access_frequency(10) > access_frequency(1));
+        assert!(access_frequency(1_000_000) < 1.0);
+        // Session-id sources count as agent-written
+        assert_eq!(source_trust("3f2a-session"), source_trust("agent"));
+        // Unparseable timestamps contribute no recency
+        let c = compute_confidence(&entry("agent", "garbage", 0), now);
+        assert!((c - 0.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_embeddings_response_orders_by_index() {
+        let data = json!({ "data": [
+            { "index": 1, "embedding": [0.5, 0.25] },
+            { "index": 0, "embedding": [1.0, 2.0] },
+        ]});
+        let embeddings = parse_embeddings_response(&data, 2).unwrap();
+        assert_eq!(embeddings, vec![vec![1.0, 2.0], vec![0.5, 0.25]]);
+        assert!(parse_embeddings_response(&data, 3).is_err());
+        assert!(parse_embeddings_response(&json!({ "data": [{ "index": 0, "embedding": [] }] }), 1).is_err());
+    }
+
+    #[test]
+    fn test_unembedded_entries_paging() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MEMORY_SCHEMA).unwrap();
+        for (i, embedded) in [false, true, false, false].iter().enumerate() {
+            conn.execute(
+                "INSERT INTO memory_entries (key, content, embedding, created_at, updated_at) VALUES (?1, ?2, ?3, 'now', 'now')",
+                params![format!("k{}", i), format!("c{}", i), embedded.then(|| vec![0u8; 4])],
+            )
+            .unwrap();
+        }
+        let first = unembedded_entries_in(&conn, 0, 2).unwrap();
+        assert_eq!(first, vec![(1, "c0".to_string()), (3, "c2".to_string())]);
+        let rest = unembedded_entries_in(&conn, first[1].0, 2).unwrap();
+        assert_eq!(rest, vec![(4, "c3".to_string())]);
+        assert!(unembedded_entries_in(&conn, 4, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_by_tag() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MEMORY_SCHEMA).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE memory_entries ADD COLUMN owner_id TEXT;
+             ALTER TABLE memory_entries ADD COLUMN visibility TEXT NOT NULL DEFAULT 'global';
+             ALTER TABLE memory_entries ADD COLUMN access_count INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE memory_entries ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;",
+        )
+        .unwrap();
+        for (key, content, tags) in [
+            ("a", "deploy with rust toolchain", r#"["work","rust"]"#),
+            ("b", "weekend hiking in rust colored hills", r#"["rust"]"#),
+            ("c", "grocery list", r#"["home"]"#),
+            ("d", "untagged rust notes", "[]"),
+        ] {
+            conn.execute(
+                "INSERT INTO memory_entries (key, content, tags, created_at, updated_at) VALUES (?1, ?2, ?3, 'now', 'now')",
+                params![key, content, tags],
+            )
+            .unwrap();
+        }
+        let keys = |tags: &[&str], query: Option<&str>| -> Vec<String> {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            search_by_tag_in(&conn, &tags, query, 10, None)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.entry.key)
+                .collect()
+        };
+
+        // More matching tags rank first; untagged entries never match
+        assert_eq!(keys(&["rust", "work"], None), vec!["a", "b"]);
+        assert_eq!(keys(&["home"], None), vec!["c"]);
+        // Query filters on content only
+        assert_eq!(keys(&["rust", "home"], Some("hiking")), vec!["b"]);
+        assert!(keys(&["home"], Some("work")).is_empty());
+        assert!(keys(&[" "], None).is_empty());
+
+        assert!(tag_match_score(2, None) > tag_match_score(1, Some(-100.0)));
+        assert!(tag_match_score(1, Some(-2.0)) > tag_match_score(1, Some(-1.0)));
+    }
+
+    #[test]
+    fn test_fts_repair() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MEMORY_SCHEMA).unwrap();
+        let insert = |key: &str, content: &str| {
+            conn.execute(
+                "INSERT INTO memory_entries (key, content, created_at, updated_at) VALUES (?1, ?2, 'now', 'now')",
+                params![key, content],
+            )
+            .unwrap();
+        };
+        let hits = |word: &str| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM memory_fts WHERE memory_fts MATCH ?1", params![word], |r| r.get(0))
+                .unwrap()
+        };
+        insert("lang", "prefers rust");
+        assert!(check_fts_consistency_in(&conn).unwrap().is_consistent());
+
+        // Writes that bypass the trigger leave the entry unsearchable
+        conn.execute_batch("DROP TRIGGER memory_fts_insert").unwrap();
+        insert("city", "lives in hangzhou");
+        let fts = check_fts_consistency_in(&conn).unwrap();
+        assert_eq!((fts.entries, fts.indexed, fts.missing, fts.orphaned), (2, 1, 1, 0));
+        assert_eq!(hits("hangzhou"), 0);
+
+        let fixed = repair_fts_in(&conn, false).unwrap();
+        assert_eq!((fixed.entries, fixed.indexed), (2, 2));
+        assert_eq!(hits("hangzhou"), 1);
+
+        // Orphans need a full rebuild
+        conn.execute_batch("DROP TRIGGER memory_fts_delete; DELETE FROM memory_entries WHERE key = 'lang'").unwrap();
+        assert_eq!(check_fts_consistency_in(&conn).unwrap().orphaned, 1);
+        assert!(repair_fts_in(&conn, false).unwrap().is_consistent());
+        assert_eq!(hits("rust"), 0);
+    }
+
+    #[test]
+    fn test_pinned_memory_ignores_decay() {
+        let now = chrono::Utc::now();
+        let old = (now - chrono::Duration::days(365)).to_rfc3339();
+        let recent = (now - chrono::Duration::days(1)).to_rfc3339();
+        let result = |key: &str, updated_at: &str, pinned: bool| {
+            let mut e = entry("user", updated_at, 0);
+            e.key = key.to_string();
+            e.pinned = pinned;
+            MemorySearchResult { entry: e, score: 1.0, match_type: "fts".to_string(), snippet: None, confidence: 0.0 }
+        };
+
+        let mut results = vec![result("fresh", &recent, false), result("stale", &old, false), result("prefs", &old, true)];
+        rescore(&mut results, now);
+        let keys: Vec<&str> = results.iter().map(|r| r.entry.key.as_str()).collect();
+        assert_eq!(keys, vec!["prefs", "fresh", "stale"]);
+        assert!((results[0].score - PINNED_BOOST).abs() < 1e-9);
+        assert!((results[2].score - 0.1).abs() < 1e-9);
+
+        let mut pinned = entry("user", &old, 0);
+        pinned.pinned = true;
+        assert!((compute_confidence(&pinned, now) - 0.7).abs() < 1e-6);
+    }
+}
@@ -8,6 +8,11 @@ pub mod memory;
 pub mod sandbox;
 pub mod plugins;
 pub mod context_manager;
+pub mod progress;
+pub mod attachments;
+pub mod artifacts;
+pub mod replay;
+pub mod scratchpad;
 
 // Re-export core's public items so modules::agent::agent_chat still works
 pub use core::*;
@@ -11,6 +11,7 @@ use tokio::io::AsyncWriteExt;
 use tracing::{error, info, warn};
 
 use crate::modules::config::get_data_dir;
+use crate::modules::environments::WithManagedEnv;
 
 /// Plugin tool definition — local copy for manifest parsing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +87,7 @@ impl PluginRegistry {
     async fn discover_tools(&mut self, path: &PathBuf) {
         let cmd = tokio::process::Command::new(path)
             .arg("--manifest")
+            .with_managed_env()
             .output()
             .await;
 
@@ -123,7 +125,8 @@ impl PluginRegistry {
             // but we could. For simplicity, we just fetch it again or cache it.
             // Let's refetch it for now, though it's slow.
             // Optimally, PluginRegistry would cache the ToolDefinition.
-            if let Ok(output) = tokio::process::Command::new(&path).arg("--manifest").output().await {
+            let manifest_cmd = tokio::process::Command::new(&path).arg("--manifest").with_managed_env().output();
+            if let Ok(output) = manifest_cmd.await {
                 if let Ok(manifest) = serde_json::from_str::<PluginManifest>(&String::from_utf8_lossy(&output.stdout)) {
                      for tool in manifest.tools {
                          if tool.function.name == name {
@@ -148,6 +151,7 @@ impl PluginRegistry {
         });
 
         let mut child = tokio::process::Command::new(path)
+            .with_managed_env()
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -188,6 +192,24 @@ impl PluginRegistry {
     }
 }
 
+/// File names of the executables in the plugins directory, without running them.
+pub fn list_plugin_files() -> Vec<String> {
+    let Ok(dir) = get_data_dir().map(|d| d.join("plugins")) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && is_executable(p))
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
 #[cfg(unix)]
 fn is_executable(path: &Path) -> bool {
     use std::os::unix::fs::PermissionsExt;
@@ -0,0 +1,235 @@
+//! Structured run progress — `agent://progress/{request_id}` events.
+//!
+//! Each `agent_chat` run gets a [`RunProgress`] that numbers its events with a
+//! monotonically increasing `seq` and batches them, so a fast sequence of tool
+//! calls becomes one IPC message per [`FLUSH_INTERVAL`] instead of dozens.
+//! Payload: `{ request_id, events: [{ seq, type, data, ts }] }`.
+//!
+//! Event types: `run_started`, `model_turn_started`, `model_turn_finished`,
+//! `tool_started`, `tool_finished`, `run_completed`, and — only for runs that
+//! asked for it — `text_delta` (`{ text }`) as the model's answer streams in.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::modules::infra::log_bridge::emit_custom_event;
+use crate::utils::truncate::safe_truncate;
+
+/// Minimum gap between two emitted batches.
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// A batch this large is emitted without waiting for the interval.
+const MAX_BATCH: usize = 50;
+/// Tool arguments are cut to this many characters in `tool_started`.
+const ARGS_PREVIEW_CHARS: usize = 200;
+/// Event that ends a run; flushed immediately.
+const RUN_COMPLETED: &str = "run_completed";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressEvent {
+    pub seq: u64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub data: Value,
+    /// Unix timestamp in milliseconds
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProgressBatch<'a> {
+    request_id: &'a str,
+    events: Vec<ProgressEvent>,
+}
+
+/// Sequencing and batching, kept apart from the event sink.
+#[derive(Default)]
+struct Batcher {
+    next_seq: u64,
+    pending: Vec<ProgressEvent>,
+    last_flush: Option<Instant>,
+}
+
+impl Batcher {
+    /// Queue an event; returns the batch to emit now, if one is due.
+    fn push(&mut self, kind: &str, data: Value, now: Instant) -> Option<Vec<ProgressEvent>> {
+        self.next_seq += 1;
+        self.pending.push(ProgressEvent {
+            seq: self.next_seq,
+            kind: kind.to_string(),
+            data,
+            ts: chrono::Utc::now().timestamp_millis(),
+        });
+        let interval_elapsed = self.last_flush.is_none_or(|t| now.duration_since(t) >= FLUSH_INTERVAL);
+        if kind == RUN_COMPLETED || self.pending.len() >= MAX_BATCH || interval_elapsed {
+            Some(self.take(now))
+        } else {
+            None
+        }
+    }
+
+    fn take(&mut self, now: Instant) -> Vec<ProgressEvent> {
+        self.last_flush = Some(now);
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Progress stream of one agent run.
+pub struct RunProgress {
+    request_id: String,
+    batcher: Mutex<Batcher>,
+    flush_scheduled: AtomicBool,
+    turn: AtomicU64,
+    last_prompt_tokens: AtomicU64,
+    stream_text: bool,
+}
+
+impl RunProgress {
+    pub fn new(request_id: &str, stream_text: bool) -> Arc<Self> {
+        Arc::new(Self {
+            request_id: sanitize_request_id(request_id),
+            batcher: Mutex::new(Batcher::default()),
+            flush_scheduled: AtomicBool::new(false),
+            turn: AtomicU64::new(0),
+            last_prompt_tokens: AtomicU64::new(0),
+            stream_text,
+        })
+    }
+
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// Whether model text should be streamed as `text_delta` events.
+    pub fn streams_text(&self) -> bool {
+        self.stream_text
+    }
+
+    /// Queue an event, emitting now if a batch is due or scheduling a
+    /// trailing flush otherwise.
+    pub fn emit(self: &Arc<Self>, kind: &str, data: Value) {
+        let ready = self.batcher.lock().push(kind, data, Instant::now());
+        match ready {
+            Some(events) => self.send(events),
+            None => {
+                if !self.flush_scheduled.swap(true, Ordering::AcqRel) {
+                    let this = Arc::clone(self);
+                    tokio::spawn(async move {
+                        tokio::time::sleep(FLUSH_INTERVAL).await;
+                        this.flush_scheduled.store(false, Ordering::Release);
+                        this.flush();
+                    });
+                }
+            }
+        }
+    }
+
+    /// Emit whatever is queued.
+    pub fn flush(&self) {
+        let events = self.batcher.lock().take(Instant::now());
+        self.send(events);
+    }
+
+    /// Start the next model turn; returns `(turn, prompt token delta)` against the previous turn.
+    pub fn begin_turn(&self, prompt_tokens: usize) -> (u64, i64) {
+        let turn = self.turn.fetch_add(1, Ordering::AcqRel) + 1;
+        let previous = self.last_prompt_tokens.swap(prompt_tokens as u64, Ordering::AcqRel);
+        (turn, prompt_tokens as i64 - previous as i64)
+    }
+
+    fn send(&self, events: Vec<ProgressEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        emit_custom_event(
+            &format!("agent://progress/{}", self.request_id),
+            ProgressBatch { request_id: &self.request_id, events },
+        );
+    }
+}
+
+/// Tauri event names only allow alphanumerics and `-/:_`.
+fn sanitize_request_id(raw: &str) -> String {
+    let id: String = raw
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(64)
+        .collect();
+    if id.is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        id
+    }
+}
+
+/// Compact, truncated rendering of tool arguments for `tool_started`.
+pub fn args_preview(args: &Value) -> String {
+    if args.is_null() {
+        return String::new();
+    }
+    let text = args.to_string();
+    let preview = safe_truncate(&text, ARGS_PREVIEW_CHARS);
+    if preview.len() < text.len() {
+        format!("{}…", preview)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batcher_sequences_and_batches() {
+        let start = Instant::now();
+        let mut batcher = Batcher::default();
+
+        // First event goes out at once
+        let first = batcher.push("run_started", json!({}), start).unwrap();
+        assert_eq!(first.len(), 1);
+
+        // A burst within the interval is held back...
+        let t = start + Duration::from_millis(10);
+        assert!(batcher.push("tool_started", json!({}), t).is_none());
+        assert!(batcher.push("tool_finished", json!({}), t).is_none());
+
+        // ...and released together once the interval has passed
+        let later = start + FLUSH_INTERVAL + Duration::from_millis(1);
+        let batch = batcher.push("tool_started", json!({}), later).unwrap();
+        assert_eq!(batch.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        // Completion never waits
+        assert!(batcher.push("tool_finished", json!({}), later).is_none());
+        let last = batcher.push(RUN_COMPLETED, json!({}), later).unwrap();
+        assert_eq!(last.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_batcher_caps_batch_size() {
+        let start = Instant::now();
+        let mut batcher = Batcher::default();
+        batcher.push("run_started", json!({}), start);
+        let emitted: usize = (0..MAX_BATCH)
+            .filter_map(|_| batcher.push("tool_started", json!({}), start))
+            .map(|b| b.len())
+            .sum();
+        assert_eq!(emitted, MAX_BATCH);
+    }
+
+    #[test]
+    fn test_request_id_and_args_preview() {
+        assert_eq!(sanitize_request_id("chat-42_a"), "chat-42_a");
+        assert_eq!(sanitize_request_id("a b/c"), "abc");
+        assert_eq!(sanitize_request_id("///").len(), 36);
+
+        assert_eq!(args_preview(&Value::Null), "");
+        assert_eq!(args_preview(&json!({ "path": "/tmp" })), r#"{"path":"/tmp"}"#);
+        let long = args_preview(&json!({ "content": "x".repeat(500) }));
+        assert!(long.ends_with('…'));
+        assert_eq!(long.chars().count(), ARGS_PREVIEW_CHARS + 1);
+    }
+}
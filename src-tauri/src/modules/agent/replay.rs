@@ -0,0 +1,365 @@
+//! Conversation Replay — re-run a session from a given message and compare
+//! the tool calls of the new run against the original one.
+//!
+//! Every agent run records the tools it called in the `agent_tool_calls`
+//! table, keyed by the assistant message that ended the run. `agent_replay`
+//! copies the history before the chosen message into a scratch session,
+//! sends each later user message through the agent again and reports where
+//! the tool calls or the reply diverge. Tools really execute during a replay;
+//! a dry run only records the calls the model plans and stubs their results.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::modules::config::get_data_dir;
+use crate::modules::database::{self, ConversationEntry};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    pub args: Value,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentReplayResult {
+    /// User messages sent through the agent again
+    pub replayed_messages: usize,
+    /// Tool calls of the replayed run, in order (planned but not executed for a dry run)
+    pub tool_calls: Vec<ToolCall>,
+    /// Reply to the last replayed message
+    pub final_response: String,
+    /// Where the replay diverged from the original run, first divergence first
+    pub differences: Vec<String>,
+    /// Session holding the replayed conversation (None for a dry run)
+    pub replay_session_id: Option<String>,
+}
+
+/// One user message to replay and the assistant reply that followed it originally.
+#[derive(Debug, Clone)]
+struct ReplayTurn {
+    input: String,
+    original_reply: Option<ConversationEntry>,
+}
+
+// ============================================================================
+// Database
+// ============================================================================
+
+static REPLAY_DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
+    let conn = open_replay_db().expect("Failed to open replay database");
+    Mutex::new(conn)
+});
+
+fn open_replay_db() -> Result<Connection, String> {
+    let data_dir = get_data_dir()?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("create dir: {}", e))?;
+    let db_path = data_dir.join("helix.db");
+    let conn = Connection::open(&db_path).map_err(|e| format!("open DB: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")
+        .map_err(|e| format!("pragmas: {}", e))?;
+    Ok(conn)
+}
+
+const REPLAY_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS agent_tool_calls (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id  TEXT NOT NULL,
+        message_id  INTEGER NOT NULL,
+        seq         INTEGER NOT NULL,
+        name        TEXT NOT NULL,
+        args        TEXT NOT NULL DEFAULT '{}',
+        success     INTEGER NOT NULL,
+        created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_tool_calls_message ON agent_tool_calls(message_id);
+    CREATE INDEX IF NOT EXISTS idx_tool_calls_session ON agent_tool_calls(session_id);
+";
+
+pub fn init_replay_tables() -> Result<(), String> {
+    let conn = REPLAY_DB.lock();
+    conn.execute_batch(REPLAY_SCHEMA)
+        .map_err(|e| format!("create replay tables: {}", e))?;
+    info!("Replay tables initialized");
+    Ok(())
+}
+
+fn insert_calls_in(conn: &Connection, session_id: &str, message_id: i64, calls: &[ToolCall]) -> Result<(), String> {
+    for (seq, call) in calls.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO agent_tool_calls (session_id, message_id, seq, name, args, success) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![session_id, message_id, seq as i64, call.name, call.args.to_string(), call.success],
+        )
+        .map_err(|e| format!("insert tool call: {}", e))?;
+    }
+    Ok(())
+}
+
+fn calls_for_message_in(conn: &Connection, message_id: i64) -> Result<Vec<ToolCall>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, args, success FROM agent_tool_calls WHERE message_id = ?1 ORDER BY seq")
+        .map_err(|e| format!("prepare: {}", e))?;
+    let rows = stmt
+        .query_map(params![message_id], |row| {
+            let args: String = row.get(1)?;
+            Ok(ToolCall {
+                name: row.get(0)?,
+                args: serde_json::from_str(&args).unwrap_or(Value::Null),
+                success: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("list tool calls: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("read tool call row: {}", e))
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Tool calls of the run in progress, keyed by session (account) id.
+static RUN_CALLS: Lazy<Mutex<HashMap<String, Vec<ToolCall>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Reset tool-call recording at the start of an agent run.
+pub fn clear_run_calls_for(session_id: &str) {
+    RUN_CALLS.lock().remove(session_id);
+}
+
+/// Note a finished tool call of the current run; called by the tool wrapper.
+pub fn note_tool_call(name: &str, args: &Value, success: bool) {
+    if let Ok(session_id) = super::core::SESSION_ACCOUNT_ID.try_with(|a| a.clone()) {
+        RUN_CALLS.lock().entry(session_id).or_default().push(ToolCall {
+            name: name.to_string(),
+            args: args.clone(),
+            success,
+        });
+    }
+}
+
+/// Persist the run's tool calls against the assistant message that ended it.
+pub fn record_run_calls(session_id: &str, message_id: i64) {
+    let calls = RUN_CALLS.lock().remove(session_id).unwrap_or_default();
+    if calls.is_empty() {
+        return;
+    }
+    if let Err(e) = insert_calls_in(&REPLAY_DB.lock(), session_id, message_id, &calls) {
+        warn!("[replay] Failed to record tool calls for {}: {}", session_id, e);
+    }
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// Split `history` at `from_index`: the id of the last message kept as
+/// context, and the user turns to replay with their original replies.
+fn plan_replay(history: &[ConversationEntry], from_index: usize) -> Result<(Option<i64>, Vec<ReplayTurn>), String> {
+    let start = history
+        .get(from_index)
+        .ok_or_else(|| format!("Message index {} out of range ({} messages)", from_index, history.len()))?;
+    if start.role != "user" {
+        return Err(format!("Message {} is a {} message; replay must start at a user message", from_index, start.role));
+    }
+
+    let prefix_end = from_index.checked_sub(1).map(|i| history[i].id);
+    let turns = history[from_index..]
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.role == "user")
+        .map(|(offset, entry)| ReplayTurn {
+            input: entry.content.clone(),
+            original_reply: history
+                .get(from_index + offset + 1)
+                .filter(|next| next.role == "assistant")
+                .cloned(),
+        })
+        .collect();
+    Ok((prefix_end, turns))
+}
+
+/// Describe how `replayed` differs from `original` for turn `turn` (1-based).
+fn diff_turn(turn: usize, original: &[ToolCall], replayed: &[ToolCall], original_reply: &str, replayed_reply: &str) -> Vec<String> {
+    let mut differences = Vec::new();
+    for i in 0..original.len().max(replayed.len()) {
+        let label = format!("Turn {}, tool call {}", turn, i + 1);
+        match (original.get(i), replayed.get(i)) {
+            (Some(o), Some(r)) if o.name != r.name => {
+                differences.push(format!("{}: called `{}` instead of `{}`", label, r.name, o.name));
+            }
+            (Some(o), Some(r)) if o.args != r.args => {
+                differences.push(format!("{}: `{}` args changed from {} to {}", label, r.name, o.args, r.args));
+            }
+            (Some(o), Some(r)) if o.success != r.success => {
+                let outcome = if r.success { "succeeded" } else { "failed" };
+                differences.push(format!("{}: `{}` {} this time", label, r.name, outcome));
+            }
+            (Some(o), None) => differences.push(format!("{}: `{}` was not called", label, o.name)),
+            (None, Some(r)) => differences.push(format!("{}: extra call to `{}`", label, r.name)),
+            _ => {}
+        }
+    }
+    if original_reply.trim() != replayed_reply.trim() {
+        differences.push(format!("Turn {}: final response differs", turn));
+    }
+    differences
+}
+
+/// Conversation entries of `session_id` written after the last one we saw.
+fn entries_after(session_id: &str, last_id: i64) -> Result<Vec<ConversationEntry>, String> {
+    Ok(database::get_conversation_history(session_id, i64::MAX)?
+        .into_iter()
+        .filter(|e| e.id > last_id)
+        .collect())
+}
+
+fn discard_replay_session(session_id: &str) {
+    if let Err(e) = database::clear_messages(session_id) {
+        warn!("[replay] Failed to clear {}: {}", session_id, e);
+    }
+    if let Err(e) = REPLAY_DB
+        .lock()
+        .execute("DELETE FROM agent_tool_calls WHERE session_id = ?1", params![session_id])
+    {
+        warn!("[replay] Failed to clear tool calls of {}: {}", session_id, e);
+    }
+}
+
+async fn replay_turns(replay_key: &str, turns: &[ReplayTurn]) -> Result<AgentReplayResult, String> {
+    let mut result = AgentReplayResult {
+        replayed_messages: 0,
+        tool_calls: Vec::new(),
+        final_response: String::new(),
+        differences: Vec::new(),
+        replay_session_id: None,
+    };
+    let mut last_id = database::get_conversation_history(replay_key, 1)?
+        .last()
+        .map(|e| e.id)
+        .unwrap_or(0);
+
+    for (i, turn) in turns.iter().enumerate() {
+        let reply = super::core::process_message(replay_key, &turn.input, &[], None)
+            .await
+            .map_err(|e| format!("Replay of turn {} failed: {}", i + 1, e))?;
+
+        let written = entries_after(replay_key, last_id)?;
+        last_id = written.last().map(|e| e.id).unwrap_or(last_id);
+        let replayed_calls = match written.iter().rev().find(|e| e.role == "assistant") {
+            Some(entry) => calls_for_message_in(&REPLAY_DB.lock(), entry.id)?,
+            None => Vec::new(),
+        };
+        let (original_calls, original_text) = match &turn.original_reply {
+            Some(entry) => (calls_for_message_in(&REPLAY_DB.lock(), entry.id)?, entry.content.as_str()),
+            None => (Vec::new(), ""),
+        };
+
+        result.differences.extend(diff_turn(i + 1, &original_calls, &replayed_calls, original_text, &reply));
+        result.tool_calls.extend(replayed_calls);
+        result.replayed_messages += 1;
+        result.final_response = reply;
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Re-run `session_id` from the user message at `from_message_index` (0-based,
+/// in history order) and report where the new run diverges from the original.
+/// The replay runs in a `<session>:replay-<id>` session. With `dry_run` no
+/// tool is executed and that session is deleted afterwards.
+#[tauri::command]
+pub async fn agent_replay(session_id: String, from_message_index: u64, dry_run: bool) -> Result<AgentReplayResult, String> {
+    let history = database::get_conversation_history(&session_id, i64::MAX)?;
+    let (prefix_end, turns) = plan_replay(&history, from_message_index as usize)?;
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let replay_key = format!("{}:replay-{}", session_id, &suffix[..8]);
+    if let Some(up_to_id) = prefix_end {
+        database::copy_conversation_until(&session_id, &replay_key, up_to_id)?;
+    }
+    info!(
+        "[replay] Replaying {} turns of '{}' from message {} in '{}'{}",
+        turns.len(),
+        session_id,
+        from_message_index,
+        replay_key,
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let outcome = super::core::SESSION_DRY_RUN
+        .scope(dry_run, replay_turns(&replay_key, &turns))
+        .await;
+    if dry_run {
+        discard_replay_session(&replay_key);
+    }
+    let mut result = outcome?;
+    if !dry_run {
+        result.replay_session_id = Some(replay_key);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: i64, role: &str, content: &str) -> ConversationEntry {
+        ConversationEntry {
+            id,
+            account_id: "s".into(),
+            role: role.into(),
+            content: content.into(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_replay() {
+        let history = vec![
+            entry(1, "user", "hi"),
+            entry(2, "assistant", "hello"),
+            entry(3, "user", "list files"),
+            entry(4, "assistant", "a.txt"),
+            entry(5, "user", "read it"),
+        ];
+        let (prefix_end, turns) = plan_replay(&history, 2).unwrap();
+        assert_eq!(prefix_end, Some(2));
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].input, "list files");
+        assert_eq!(turns[0].original_reply.as_ref().map(|e| e.id), Some(4));
+        assert!(turns[1].original_reply.is_none());
+
+        assert_eq!(plan_replay(&history, 0).unwrap().0, None);
+        assert!(plan_replay(&history, 1).unwrap_err().contains("assistant"));
+        assert!(plan_replay(&history, 9).is_err());
+    }
+
+    #[test]
+    fn test_diff_turn_reports_divergence() {
+        let call = |name: &str, args: Value, success: bool| ToolCall { name: name.into(), args, success };
+        let original = vec![call("list_dir", json!({"path": "~"}), true), call("file_read", json!({"path": "a"}), true)];
+
+        assert!(diff_turn(1, &original, &original, "done", " done ").is_empty());
+
+        let replayed = vec![call("list_dir", json!({"path": "/tmp"}), true), call("file_read", json!({"path": "a"}), false), call("shell_exec", json!({}), true)];
+        assert_eq!(
+            diff_turn(2, &original, &replayed, "done", "other"),
+            vec![
+                "Turn 2, tool call 1: `list_dir` args changed from {\"path\":\"~\"} to {\"path\":\"/tmp\"}",
+                "Turn 2, tool call 2: `file_read` failed this time",
+                "Turn 2, tool call 3: extra call to `shell_exec`",
+                "Turn 2: final response differs",
+            ]
+        );
+        assert_eq!(diff_turn(1, &original[..1], &[], "", ""), vec!["Turn 1, tool call 1: `list_dir` was not called"]);
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Ported from OpenClaw's process-supervisor.
 //! Provides timeouts, process tree killing, and basic resource monitoring
-//! using the `sysinfo` crate.
+//! using the `sysinfo` crate. With `HELIX_SANDBOX=1`, `shell_exec`, custom
+//! shell commands and cron scripts run through `run_sandboxed` for OS-level
+//! isolation (a seccomp-bpf allowlist plus Landlock on Linux, `sandbox_init`
+//! on macOS).
 
 use std::process::Stdio;
 use std::time::Duration;
@@ -10,6 +13,8 @@ use sysinfo::{System, Pid};
 use tokio::io::{AsyncReadExt, BufReader};
 use tracing::{info, warn};
 
+use crate::modules::environments::WithManagedEnv;
+
 pub struct SandboxOptions {
     pub timeout_secs: u64,
     pub max_output_bytes: usize,
@@ -77,6 +82,7 @@ pub async fn exec_sandboxed(command: &str, working_dir: &str, opts: SandboxOptio
 
     let mut child = tokio::process::Command::new(shell)
         .args(&shell_args)
+        .with_managed_env()
         .current_dir(working_dir)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -192,3 +198,727 @@ pub async fn exec_sandboxed(command: &str, working_dir: &str, opts: SandboxOptio
         kill_reason,
     })
 }
+
+// ============================================================================
+// OS-level isolation
+// ============================================================================
+
+/// Restrictions applied by `run_sandboxed`.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxConfig {
+    pub allow_network: bool,
+    /// Pass the app's environment through instead of only `KEPT_ENV`
+    pub allow_env: bool,
+    /// Directories the command may write to (besides the temp directories)
+    pub allowed_paths: Vec<String>,
+}
+
+/// Variables kept when `allow_env` is off.
+const KEPT_ENV: &[&str] = &["PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "TERM", "TMPDIR"];
+
+/// Whether shell commands (`shell_exec`, custom commands, cron scripts) go
+/// through `run_sandboxed` (`HELIX_SANDBOX=1`).
+pub fn sandbox_enabled() -> bool {
+    std::env::var("HELIX_SANDBOX").is_ok_and(|v| v.trim() == "1")
+}
+
+/// `~/.helix/sandbox`, where sandboxed commands without a session workspace
+/// run and write; created if missing.
+pub fn sandbox_dir() -> String {
+    let dir = dirs::home_dir()
+        .map(|h| h.join(".helix").join("sandbox"))
+        .unwrap_or_else(|| std::path::PathBuf::from("/tmp/helix-sandbox"));
+    let _ = std::fs::create_dir_all(&dir);
+    dir.to_string_lossy().to_string()
+}
+
+/// Linux: a default-deny seccomp-bpf filter. Only the syscalls ordinary
+/// shell tools need are allowed; namespaces, tracing, mounts, io_uring,
+/// kernel modules, netlink and everything else fail with `EPERM`. Sockets
+/// are limited to `AF_UNIX`, plus `AF_INET`/`AF_INET6` with network access.
+#[cfg(target_os = "linux")]
+mod seccomp {
+    use seccompiler::{
+        BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter, SeccompRule,
+    };
+    use std::collections::BTreeMap;
+
+    const ALLOWED_SYSCALLS: &[libc::c_long] = &[
+        // I/O and file descriptors
+        libc::SYS_read,
+        libc::SYS_write,
+        libc::SYS_readv,
+        libc::SYS_writev,
+        libc::SYS_pread64,
+        libc::SYS_pwrite64,
+        libc::SYS_preadv,
+        libc::SYS_pwritev,
+        libc::SYS_preadv2,
+        libc::SYS_pwritev2,
+        libc::SYS_lseek,
+        libc::SYS_close,
+        libc::SYS_close_range,
+        libc::SYS_dup,
+        libc::SYS_dup3,
+        libc::SYS_pipe2,
+        libc::SYS_fcntl,
+        libc::SYS_ioctl,
+        libc::SYS_flock,
+        libc::SYS_splice,
+        libc::SYS_tee,
+        libc::SYS_copy_file_range,
+        libc::SYS_ppoll,
+        libc::SYS_pselect6,
+        libc::SYS_epoll_create1,
+        libc::SYS_epoll_ctl,
+        libc::SYS_epoll_pwait,
+        libc::SYS_epoll_pwait2,
+        libc::SYS_eventfd2,
+        libc::SYS_signalfd4,
+        libc::SYS_timerfd_create,
+        libc::SYS_timerfd_settime,
+        libc::SYS_timerfd_gettime,
+        libc::SYS_inotify_init1,
+        libc::SYS_inotify_add_watch,
+        libc::SYS_inotify_rm_watch,
+        // Filesystem (writes are confined by Landlock)
+        libc::SYS_openat,
+        libc::SYS_openat2,
+        libc::SYS_fstat,
+        libc::SYS_newfstatat,
+        libc::SYS_statx,
+        libc::SYS_statfs,
+        libc::SYS_fstatfs,
+        libc::SYS_getdents64,
+        libc::SYS_getcwd,
+        libc::SYS_chdir,
+        libc::SYS_fchdir,
+        libc::SYS_mkdirat,
+        libc::SYS_unlinkat,
+        libc::SYS_renameat2,
+        libc::SYS_linkat,
+        libc::SYS_symlinkat,
+        libc::SYS_readlinkat,
+        libc::SYS_faccessat,
+        libc::SYS_faccessat2,
+        libc::SYS_fchmod,
+        libc::SYS_fchmodat,
+        libc::SYS_fchown,
+        libc::SYS_fchownat,
+        libc::SYS_utimensat,
+        libc::SYS_truncate,
+        libc::SYS_ftruncate,
+        libc::SYS_fallocate,
+        libc::SYS_fsync,
+        libc::SYS_fdatasync,
+        libc::SYS_sync,
+        libc::SYS_syncfs,
+        libc::SYS_umask,
+        libc::SYS_getxattr,
+        libc::SYS_lgetxattr,
+        libc::SYS_fgetxattr,
+        libc::SYS_listxattr,
+        libc::SYS_llistxattr,
+        libc::SYS_flistxattr,
+        // Memory
+        libc::SYS_brk,
+        libc::SYS_mmap,
+        libc::SYS_munmap,
+        libc::SYS_mremap,
+        libc::SYS_mprotect,
+        libc::SYS_madvise,
+        libc::SYS_mincore,
+        libc::SYS_msync,
+        libc::SYS_mlock,
+        libc::SYS_munlock,
+        libc::SYS_memfd_create,
+        libc::SYS_membarrier,
+        // Processes, threads and signals (`clone` is added with a flags check)
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_exit,
+        libc::SYS_exit_group,
+        libc::SYS_wait4,
+        libc::SYS_waitid,
+        libc::SYS_kill,
+        libc::SYS_tkill,
+        libc::SYS_tgkill,
+        libc::SYS_pidfd_open,
+        libc::SYS_pidfd_send_signal,
+        libc::SYS_futex,
+        libc::SYS_set_robust_list,
+        libc::SYS_get_robust_list,
+        libc::SYS_set_tid_address,
+        libc::SYS_rseq,
+        libc::SYS_prctl,
+        libc::SYS_capget,
+        libc::SYS_rt_sigaction,
+        libc::SYS_rt_sigprocmask,
+        libc::SYS_rt_sigreturn,
+        libc::SYS_rt_sigsuspend,
+        libc::SYS_rt_sigpending,
+        libc::SYS_rt_sigtimedwait,
+        libc::SYS_rt_sigqueueinfo,
+        libc::SYS_rt_tgsigqueueinfo,
+        libc::SYS_sigaltstack,
+        libc::SYS_restart_syscall,
+        libc::SYS_sched_yield,
+        libc::SYS_sched_getaffinity,
+        libc::SYS_sched_setaffinity,
+        libc::SYS_sched_getparam,
+        libc::SYS_sched_getscheduler,
+        libc::SYS_sched_get_priority_max,
+        libc::SYS_sched_get_priority_min,
+        libc::SYS_getpriority,
+        libc::SYS_setpriority,
+        libc::SYS_prlimit64,
+        libc::SYS_getrusage,
+        // Identity and time
+        libc::SYS_getpid,
+        libc::SYS_getppid,
+        libc::SYS_gettid,
+        libc::SYS_getuid,
+        libc::SYS_geteuid,
+        libc::SYS_getgid,
+        libc::SYS_getegid,
+        libc::SYS_getgroups,
+        libc::SYS_getresuid,
+        libc::SYS_getresgid,
+        libc::SYS_getpgid,
+        libc::SYS_setpgid,
+        libc::SYS_getsid,
+        libc::SYS_setsid,
+        libc::SYS_uname,
+        libc::SYS_sysinfo,
+        libc::SYS_getcpu,
+        libc::SYS_getrandom,
+        libc::SYS_clock_gettime,
+        libc::SYS_clock_getres,
+        libc::SYS_clock_nanosleep,
+        libc::SYS_gettimeofday,
+        libc::SYS_nanosleep,
+        libc::SYS_getitimer,
+        libc::SYS_setitimer,
+        libc::SYS_times,
+        // Sockets other than `socket` itself, which is checked by family
+        libc::SYS_socketpair,
+        libc::SYS_connect,
+        libc::SYS_bind,
+        libc::SYS_listen,
+        libc::SYS_accept,
+        libc::SYS_accept4,
+        libc::SYS_getsockname,
+        libc::SYS_getpeername,
+        libc::SYS_sendto,
+        libc::SYS_recvfrom,
+        libc::SYS_sendmsg,
+        libc::SYS_recvmsg,
+        libc::SYS_sendmmsg,
+        libc::SYS_recvmmsg,
+        libc::SYS_shutdown,
+        libc::SYS_setsockopt,
+        libc::SYS_getsockopt,
+        // Refused with ENOSYS by `clone3_filter` (its flags cannot be
+        // inspected), so libc falls back to `clone`
+        libc::SYS_clone3,
+    ];
+
+    /// Legacy syscalls that only exist on x86_64; aarch64 libc uses the `*at` forms.
+    #[cfg(target_arch = "x86_64")]
+    const ALLOWED_LEGACY_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_open,
+        libc::SYS_creat,
+        libc::SYS_stat,
+        libc::SYS_lstat,
+        libc::SYS_access,
+        libc::SYS_readlink,
+        libc::SYS_getdents,
+        libc::SYS_mkdir,
+        libc::SYS_rmdir,
+        libc::SYS_unlink,
+        libc::SYS_rename,
+        libc::SYS_renameat,
+        libc::SYS_link,
+        libc::SYS_symlink,
+        libc::SYS_chmod,
+        libc::SYS_chown,
+        libc::SYS_lchown,
+        libc::SYS_utime,
+        libc::SYS_utimes,
+        libc::SYS_futimesat,
+        libc::SYS_pipe,
+        libc::SYS_dup2,
+        libc::SYS_poll,
+        libc::SYS_select,
+        libc::SYS_epoll_create,
+        libc::SYS_epoll_wait,
+        libc::SYS_eventfd,
+        libc::SYS_signalfd,
+        libc::SYS_inotify_init,
+        libc::SYS_sendfile,
+        libc::SYS_fadvise64,
+        libc::SYS_fork,
+        libc::SYS_vfork,
+        libc::SYS_pause,
+        libc::SYS_alarm,
+        libc::SYS_time,
+        libc::SYS_getpgrp,
+        libc::SYS_getrlimit,
+        libc::SYS_setrlimit,
+        libc::SYS_arch_prctl,
+    ];
+    #[cfg(not(target_arch = "x86_64"))]
+    const ALLOWED_LEGACY_SYSCALLS: &[libc::c_long] = &[];
+
+    /// `clone` flags that create namespaces.
+    const NAMESPACE_FLAGS: libc::c_int = libc::CLONE_NEWNS
+        | libc::CLONE_NEWCGROUP
+        | libc::CLONE_NEWUTS
+        | libc::CLONE_NEWIPC
+        | libc::CLONE_NEWUSER
+        | libc::CLONE_NEWPID
+        | libc::CLONE_NEWNET
+        | libc::CLONE_NEWTIME;
+
+    fn arch() -> Result<seccompiler::TargetArch, String> {
+        std::env::consts::ARCH.try_into().map_err(|e| format!("seccomp: {}", e))
+    }
+
+    fn compile(filter: SeccompFilter) -> Result<BpfProgram, String> {
+        BpfProgram::try_from(filter).map_err(|e| format!("seccomp compile: {}", e))
+    }
+
+    /// Socket families allowed for `socket(2)`.
+    fn socket_families(allow_network: bool) -> Vec<libc::c_int> {
+        let mut families = vec![libc::AF_UNIX];
+        if allow_network {
+            families.extend([libc::AF_INET, libc::AF_INET6]);
+        }
+        families
+    }
+
+    /// The allowlist filter; everything not listed fails with `EPERM`.
+    pub fn build_filter(allow_network: bool) -> Result<BpfProgram, String> {
+        let mut rules: BTreeMap<i64, Vec<SeccompRule>> = ALLOWED_SYSCALLS
+            .iter()
+            .chain(ALLOWED_LEGACY_SYSCALLS)
+            .map(|&nr| (nr, Vec::new()))
+            .collect();
+
+        let family_rules = socket_families(allow_network)
+            .into_iter()
+            .map(|family| {
+                let domain = SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)?;
+                SeccompRule::new(vec![domain])
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("seccomp rule: {}", e))?;
+        rules.insert(libc::SYS_socket, family_rules);
+
+        // Threads and plain forks only: no flag may create a namespace
+        let no_namespaces = SeccompCondition::new(
+            0,
+            SeccompCmpArgLen::Qword,
+            SeccompCmpOp::MaskedEq(NAMESPACE_FLAGS as u64),
+            0,
+        )
+        .and_then(|cond| SeccompRule::new(vec![cond]))
+        .map_err(|e| format!("seccomp rule: {}", e))?;
+        rules.insert(libc::SYS_clone, vec![no_namespaces]);
+
+        let filter = SeccompFilter::new(rules, SeccompAction::Errno(libc::EPERM as u32), SeccompAction::Allow, arch()?)
+            .map_err(|e| format!("seccomp filter: {}", e))?;
+        compile(filter)
+    }
+
+    /// Fails `clone3` with `ENOSYS`. Installed before the allowlist: with
+    /// stacked filters the strictest action wins, so this overrides the
+    /// allowlist's Allow for `clone3`.
+    pub fn clone3_filter() -> Result<BpfProgram, String> {
+        let rules = BTreeMap::from([(libc::SYS_clone3, Vec::new())]);
+        let filter = SeccompFilter::new(rules, SeccompAction::Allow, SeccompAction::Errno(libc::ENOSYS as u32), arch()?)
+            .map_err(|e| format!("seccomp filter: {}", e))?;
+        compile(filter)
+    }
+}
+
+/// Linux: Landlock confines writes to `allowed_paths` and the temp
+/// directories, the same rule as the macOS profile. Reads stay unrestricted.
+#[cfg(target_os = "linux")]
+mod landlock {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+
+    const CREATE_RULESET_VERSION: u32 = 1 << 0;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// ABI 2
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    /// ABI 3
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Rights that apply to a file rather than a directory.
+    const FILE_ACCESS: u64 = ACCESS_FS_WRITE_FILE | ACCESS_FS_TRUNCATE;
+    /// Device files the shell writes to, e.g. `>/dev/null`.
+    const WRITABLE_DEVICES: &[&str] = &["/dev/null", "/dev/tty"];
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    fn last_error(what: &str) -> String {
+        format!("landlock {}: {}", what, std::io::Error::last_os_error())
+    }
+
+    fn abi_version() -> libc::c_long {
+        // SAFETY: the version query takes no attribute.
+        unsafe {
+            libc::syscall(libc::SYS_landlock_create_ruleset, std::ptr::null::<RulesetAttr>(), 0, CREATE_RULESET_VERSION)
+        }
+    }
+
+    /// Whether the running kernel supports Landlock (5.13+, enabled in the LSM list).
+    pub fn is_available() -> bool {
+        abi_version() >= 1
+    }
+
+    /// Write access handled by the running kernel's Landlock ABI.
+    fn handled_access() -> Result<u64, String> {
+        let abi = abi_version();
+        if abi < 1 {
+            return Err(format!(
+                "Landlock is not available ({}), cannot confine sandboxed writes",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let mut access = ACCESS_FS_WRITE_FILE
+            | ACCESS_FS_REMOVE_DIR
+            | ACCESS_FS_REMOVE_FILE
+            | ACCESS_FS_MAKE_CHAR
+            | ACCESS_FS_MAKE_DIR
+            | ACCESS_FS_MAKE_REG
+            | ACCESS_FS_MAKE_SOCK
+            | ACCESS_FS_MAKE_FIFO
+            | ACCESS_FS_MAKE_BLOCK
+            | ACCESS_FS_MAKE_SYM;
+        if abi >= 2 {
+            access |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            access |= ACCESS_FS_TRUNCATE;
+        }
+        Ok(access)
+    }
+
+    fn add_rule(ruleset: &OwnedFd, path: &str, access: u64) -> Result<(), String> {
+        let Ok(c_path) = std::ffi::CString::new(std::path::Path::new(path).as_os_str().as_bytes()) else {
+            return Ok(());
+        };
+        // SAFETY: c_path is a valid NUL-terminated string.
+        let fd = unsafe { libc::open(c_path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+        if fd < 0 {
+            // Missing paths simply grant nothing
+            return Ok(());
+        }
+        // SAFETY: open returned a fresh descriptor we own.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let is_dir = std::fs::metadata(path).is_ok_and(|m| m.is_dir());
+        let attr = PathBeneathAttr {
+            allowed_access: if is_dir { access } else { access & FILE_ACCESS },
+            parent_fd: fd.as_raw_fd(),
+        };
+        // SAFETY: attr matches struct landlock_path_beneath_attr and outlives the call.
+        let rc = unsafe {
+            libc::syscall(libc::SYS_landlock_add_rule, ruleset.as_raw_fd(), RULE_PATH_BENEATH, &attr as *const _, 0)
+        };
+        if rc != 0 {
+            return Err(last_error(&format!("rule for {}", path)));
+        }
+        Ok(())
+    }
+
+    /// A ruleset allowing writes below `allowed_paths` and the temp
+    /// directories only. Built before fork; `restrict_self` applies it in the child.
+    pub fn build_ruleset(allowed_paths: &[String]) -> Result<OwnedFd, String> {
+        let access = handled_access()?;
+        let attr = RulesetAttr { handled_access_fs: access };
+        // SAFETY: attr matches struct landlock_ruleset_attr (ABI 1 layout) and outlives the call.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                std::mem::size_of::<RulesetAttr>(),
+                0,
+            )
+        };
+        if fd < 0 {
+            return Err(last_error("create ruleset"));
+        }
+        // SAFETY: the syscall returned a fresh close-on-exec descriptor.
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        let temp_dir = std::env::temp_dir().to_string_lossy().to_string();
+        for path in allowed_paths.iter().map(String::as_str).chain([temp_dir.as_str(), "/tmp"]) {
+            add_rule(&ruleset, path, access)?;
+        }
+        for device in WRITABLE_DEVICES {
+            add_rule(&ruleset, device, access)?;
+        }
+        Ok(ruleset)
+    }
+
+    /// Enforce `ruleset` on the calling process. Runs between fork and exec:
+    /// syscalls only. Requires `no_new_privs`, which the caller sets first.
+    pub fn restrict_self(ruleset: &OwnedFd) -> std::io::Result<()> {
+        // SAFETY: plain syscall on a descriptor we own.
+        let rc = unsafe { libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0) };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// SBPL profile for `sandbox_init`: writes only below `allowed_paths` (and
+/// the temp directories), network denied unless allowed.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn macos_profile(config: &SandboxConfig) -> String {
+    let quote = |path: &str| format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut profile = String::from("(version 1)\n(allow default)\n");
+    if !config.allow_network {
+        profile.push_str("(deny network*)\n");
+    }
+    profile.push_str("(deny file-write*)\n(allow file-write*\n");
+    for path in &config.allowed_paths {
+        let path = std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.clone());
+        profile.push_str(&format!("    (subpath {})\n", quote(&path)));
+    }
+    profile.push_str(
+        "    (subpath \"/private/tmp\")\n    (subpath \"/private/var/folders\")\n    \
+         (literal \"/dev/null\")\n    (literal \"/dev/tty\")\n    (regex #\"^/dev/fd/\"))\n",
+    );
+    profile
+}
+
+/// macOS: `sandbox_init(3)` in the child before exec. Without path
+/// restrictions the built-in `no-network` profile is used.
+#[cfg(target_os = "macos")]
+mod seatbelt {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_int};
+
+    extern "C" {
+        fn sandbox_init(profile: *const c_char, flags: u64, errorbuf: *mut *mut c_char) -> c_int;
+        fn sandbox_free_error(errorbuf: *mut c_char);
+    }
+
+    /// `profile` is the name of a built-in profile rather than SBPL source.
+    const SANDBOX_NAMED: u64 = 0x0001;
+
+    pub struct Profile {
+        source: CString,
+        flags: u64,
+    }
+
+    pub fn profile(config: &super::SandboxConfig) -> Result<Option<Profile>, String> {
+        let (source, flags) = if !config.allowed_paths.is_empty() {
+            (super::macos_profile(config), 0)
+        } else if !config.allow_network {
+            ("no-network".to_string(), SANDBOX_NAMED)
+        } else {
+            return Ok(None);
+        };
+        let source = CString::new(source).map_err(|_| "Sandbox profile contains a NUL byte".to_string())?;
+        Ok(Some(Profile { source, flags }))
+    }
+
+    pub fn apply(profile: &Profile) -> std::io::Result<()> {
+        let mut error: *mut c_char = std::ptr::null_mut();
+        // SAFETY: both pointers are valid for the call; the error buffer is freed with sandbox_free_error.
+        let rc = unsafe { sandbox_init(profile.source.as_ptr(), profile.flags, &mut error) };
+        if rc == 0 {
+            return Ok(());
+        }
+        let message = if error.is_null() {
+            "sandbox_init failed".to_string()
+        } else {
+            // SAFETY: sandbox_init returned a NUL-terminated message we own until freed.
+            let message = unsafe { CStr::from_ptr(error) }.to_string_lossy().to_string();
+            unsafe { sandbox_free_error(error) };
+            message
+        };
+        Err(std::io::Error::other(message))
+    }
+}
+
+/// Warn about a kernel without Landlock once, not on every command.
+#[cfg(target_os = "linux")]
+static LANDLOCK_MISSING_WARNING: std::sync::Once = std::sync::Once::new();
+
+/// Install the platform restrictions on `command`; they take effect in the
+/// child right before exec. On Linux without Landlock only the seccomp
+/// filters apply. Other platforms run unrestricted.
+fn restrict(command: &mut tokio::process::Command, config: &SandboxConfig) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let ruleset = if landlock::is_available() {
+            Some(landlock::build_ruleset(&config.allowed_paths)?)
+        } else {
+            LANDLOCK_MISSING_WARNING.call_once(|| {
+                warn!("Sandbox: Landlock is not available on this kernel, writes are not confined (seccomp only)")
+            });
+            None
+        };
+        let clone3 = seccomp::clone3_filter()?;
+        let program = seccomp::build_filter(config.allow_network)?;
+        // SAFETY: the closure only makes syscalls (prctl, landlock, seccomp) with data prepared before fork.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if let Some(ruleset) = &ruleset {
+                    landlock::restrict_self(ruleset)?;
+                }
+                // The allowlist goes last; it does not allow installing further filters
+                seccompiler::apply_filter(&clone3).map_err(|e| std::io::Error::other(e.to_string()))?;
+                seccompiler::apply_filter(&program).map_err(|e| std::io::Error::other(e.to_string()))
+            });
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(profile) = seatbelt::profile(config)? {
+        // SAFETY: sandbox_init is called with a profile prepared before fork.
+        unsafe {
+            command.pre_exec(move || seatbelt::apply(&profile));
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = (&command, config);
+        warn!("Sandbox: OS-level isolation is not supported on this platform, running unsandboxed");
+    }
+    Ok(())
+}
+
+/// Run a shell command in `working_dir` under OS-level isolation. Returns
+/// the formatted stdout/stderr and the exit code. Dropping the future kills
+/// the command's whole process group.
+pub async fn run_sandboxed(command: &str, working_dir: &str, config: SandboxConfig) -> Result<(String, i32), String> {
+    info!(
+        "Sandbox (network: {}, env: {}) executing: {} (dir: {})",
+        config.allow_network,
+        config.allow_env,
+        crate::utils::truncate::safe_truncate(command, 50),
+        working_dir
+    );
+
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut c = tokio::process::Command::new("zsh");
+        c.arg("-l");
+        c
+    } else {
+        tokio::process::Command::new("sh")
+    };
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if config.allow_env {
+        cmd.with_managed_env();
+    } else {
+        cmd.env_clear();
+        for (key, value) in std::env::vars().filter(|(key, _)| KEPT_ENV.contains(&key.as_str())) {
+            cmd.env(key, value);
+        }
+    }
+    #[cfg(unix)]
+    cmd.process_group(0);
+    restrict(&mut cmd, &config)?;
+
+    let child = cmd.spawn().map_err(|e| format!("Sandboxed command failed: {}", e))?;
+    let mut group = super::tools::ProcessGroupGuard { pgid: child.id() };
+    let output = child.wait_with_output().await.map_err(|e| format!("Sandboxed command failed: {}", e))?;
+    group.disarm();
+
+    let code = output.status.code().unwrap_or(-1);
+    Ok((super::tools::format_shell_output(&output.stdout, &output.stderr), code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macos_profile() {
+        let config = SandboxConfig { allowed_paths: vec!["/work/\"odd\"".into()], ..Default::default() };
+        let profile = macos_profile(&config);
+        assert!(profile.contains("(deny network*)"));
+        assert!(profile.contains("(subpath \"/work/\\\"odd\\\"\")"));
+        let profile = macos_profile(&SandboxConfig { allow_network: true, ..config });
+        assert!(!profile.contains("network") && profile.contains("(deny file-write*)"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_run_sandboxed_linux() {
+        if !landlock::is_available() {
+            println!("Landlock is not available, skipping sandbox test");
+            return;
+        }
+        // Not below the temp directory, which is always writable
+        let home = dirs::home_dir().unwrap().display().to_string();
+        let allowed = std::path::Path::new(&home).join(format!(".helix-sandbox-test-{}", std::process::id()));
+        std::fs::create_dir_all(&allowed).unwrap();
+        let allowed = allowed.display().to_string();
+        let config = SandboxConfig { allowed_paths: vec![allowed.clone()], ..Default::default() };
+        let run = |command: String| {
+            let (allowed, config) = (allowed.clone(), config.clone());
+            async move { run_sandboxed(&command, &allowed, config).await }
+        };
+
+        let (output, code) = run("echo hello; echo ok > inside.txt; cat inside.txt >/dev/null".into()).await.unwrap();
+        assert_eq!(code, 0, "{}", output);
+        assert!(output.contains("hello"));
+
+        // Writes outside the allowed paths are refused by Landlock
+        let (_, code) = run(format!("echo no > '{}/.helix-sandbox-escape'", home)).await.unwrap();
+        assert_ne!(code, 0);
+        assert!(!std::path::Path::new(&home).join(".helix-sandbox-escape").exists());
+
+        // Namespaces (unshare, clone with CLONE_NEWUSER) are not on the allowlist
+        let (_, code) = run("command -v unshare >/dev/null || exit 1; unshare -r true".into()).await.unwrap();
+        assert_ne!(code, 0);
+
+        // No AF_INET sockets without network access; bash's /dev/tcp reports the EPERM
+        if std::process::Command::new("bash").arg("-c").arg("true").status().is_ok_and(|s| s.success()) {
+            let (output, code) = run("bash -c 'exec 3<>/dev/tcp/127.0.0.1/9'".into()).await.unwrap();
+            assert_ne!(code, 0);
+            assert!(output.contains("not permitted"), "{}", output);
+        }
+
+        let _ = std::fs::remove_dir_all(&allowed);
+    }
+}
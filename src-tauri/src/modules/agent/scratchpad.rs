@@ -0,0 +1,286 @@
+//! Session Scratchpad — short-lived key/value notes for the current conversation.
+//!
+//! The agent keeps working state (a plan, intermediate results, ids it is
+//! juggling) with `scratchpad_set` / `scratchpad_get` / `scratchpad_list`.
+//! Entries live in the `scratchpad` table of helix.db keyed by session, are
+//! summarized into the system prompt every turn and are dropped by `/new`
+//! (or `/reset`) and when the session is deleted. Unlike long-term memory
+//! nothing here is written to `memory_entries` or recalled in other sessions.
+//!
+//! Values are capped at [`MAX_VALUE_BYTES`]; a session keeps at most
+//! [`MAX_KEYS`] keys and setting a new one evicts the least recently set.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::modules::config::get_data_dir;
+
+/// Maximum size of one value in bytes; longer values are truncated.
+pub const MAX_VALUE_BYTES: usize = 2_048;
+/// Maximum number of keys per session.
+pub const MAX_KEYS: usize = 30;
+/// Maximum key length in characters.
+const MAX_KEY_CHARS: usize = 64;
+/// Characters of each value shown in the system prompt summary.
+const PROMPT_VALUE_CHARS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScratchpadEntry {
+    pub key: String,
+    pub value: String,
+    pub updated_at: String,
+}
+
+// ============================================================================
+// Database
+// ============================================================================
+
+static SCRATCHPAD_DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
+    let conn = open_scratchpad_db().expect("Failed to open scratchpad database");
+    Mutex::new(conn)
+});
+
+fn open_scratchpad_db() -> Result<Connection, String> {
+    let data_dir = get_data_dir()?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("create dir: {}", e))?;
+    let db_path = data_dir.join("helix.db");
+    let conn = Connection::open(&db_path).map_err(|e| format!("open DB: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")
+        .map_err(|e| format!("pragmas: {}", e))?;
+    Ok(conn)
+}
+
+// `seq` orders entries by when they were last set, for eviction and listing.
+const SCRATCHPAD_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS scratchpad (
+        session_id  TEXT NOT NULL,
+        key         TEXT NOT NULL,
+        value       TEXT NOT NULL,
+        seq         INTEGER NOT NULL,
+        updated_at  TEXT NOT NULL DEFAULT (datetime('now')),
+        PRIMARY KEY (session_id, key)
+    );
+";
+
+pub fn init_scratchpad_tables() -> Result<(), String> {
+    let conn = SCRATCHPAD_DB.lock();
+    conn.execute_batch(SCRATCHPAD_SCHEMA)
+        .map_err(|e| format!("create scratchpad tables: {}", e))?;
+    info!("Scratchpad tables initialized");
+    Ok(())
+}
+
+/// Cut `value` to at most `max_bytes` on a char boundary.
+fn cap_value(value: &str, max_bytes: usize) -> &str {
+    if value.len() <= max_bytes {
+        return value;
+    }
+    let mut end = max_bytes;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+fn validate_key(key: &str) -> Result<&str, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Key is empty".into());
+    }
+    if key.chars().count() > MAX_KEY_CHARS {
+        return Err(format!("Key is longer than {} characters", MAX_KEY_CHARS));
+    }
+    Ok(key)
+}
+
+/// Store `value` under `key`, evicting the least recently set keys beyond
+/// [`MAX_KEYS`]. Returns the evicted keys.
+fn set_in(conn: &Connection, session_id: &str, key: &str, value: &str) -> Result<Vec<String>, String> {
+    conn.execute(
+        "INSERT INTO scratchpad (session_id, key, value, seq)
+         VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(seq), 0) + 1 FROM scratchpad WHERE session_id = ?1))
+         ON CONFLICT(session_id, key) DO UPDATE SET
+             value = excluded.value, seq = excluded.seq, updated_at = datetime('now')",
+        params![session_id, key, value],
+    )
+    .map_err(|e| format!("set scratchpad: {}", e))?;
+
+    let mut stmt = conn
+        .prepare("SELECT key FROM scratchpad WHERE session_id = ?1 ORDER BY seq DESC LIMIT -1 OFFSET ?2")
+        .map_err(|e| format!("prepare: {}", e))?;
+    let evicted: Vec<String> = stmt
+        .query_map(params![session_id, MAX_KEYS as i64], |row| row.get(0))
+        .map_err(|e| format!("query: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    for old in &evicted {
+        conn.execute("DELETE FROM scratchpad WHERE session_id = ?1 AND key = ?2", params![session_id, old])
+            .map_err(|e| format!("evict scratchpad: {}", e))?;
+    }
+    Ok(evicted)
+}
+
+fn get_in(conn: &Connection, session_id: &str, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM scratchpad WHERE session_id = ?1 AND key = ?2",
+        params![session_id, key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("get scratchpad: {}", e))
+}
+
+/// Entries of a session, least recently set first.
+fn list_in(conn: &Connection, session_id: &str) -> Result<Vec<ScratchpadEntry>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value, updated_at FROM scratchpad WHERE session_id = ?1 ORDER BY seq")
+        .map_err(|e| format!("prepare: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_id], |row| {
+            Ok(ScratchpadEntry { key: row.get(0)?, value: row.get(1)?, updated_at: row.get(2)? })
+        })
+        .map_err(|e| format!("query: {}", e))?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+fn clear_in(conn: &Connection, session_id: &str) -> Result<usize, String> {
+    conn.execute("DELETE FROM scratchpad WHERE session_id = ?1", params![session_id])
+        .map_err(|e| format!("clear scratchpad: {}", e))
+}
+
+/// One line per entry, values shortened to [`PROMPT_VALUE_CHARS`].
+fn render_prompt(entries: &[ScratchpadEntry]) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    let mut out = format!(
+        "## Scratchpad ({}/{} keys, this session only; use scratchpad_get for full values)\n",
+        entries.len(),
+        MAX_KEYS
+    );
+    for entry in entries {
+        let value = entry.value.replace('\n', " ");
+        let shown = crate::utils::truncate::safe_truncate(&value, PROMPT_VALUE_CHARS);
+        let ellipsis = if shown.len() < value.len() { "…" } else { "" };
+        out.push_str(&format!("- {}: {}{}\n", entry.key, shown, ellipsis));
+    }
+    Some(out.trim_end().to_string())
+}
+
+// ============================================================================
+// Public API
+// ============================================================================
+
+fn current_session(tool: &str) -> Result<String, String> {
+    super::core::SESSION_ACCOUNT_ID
+        .try_with(|a| a.clone())
+        .map_err(|_| format!("{} is only available inside an agent run", tool))
+}
+
+/// Scratchpad summary block for the system prompt, or None when it is empty.
+pub fn build_scratchpad_prompt(session_id: &str) -> Option<String> {
+    let entries = list_in(&SCRATCHPAD_DB.lock(), session_id)
+        .map_err(|e| warn!("[scratchpad] entries for '{}': {}", session_id, e))
+        .ok()?;
+    render_prompt(&entries)
+}
+
+/// Drop every entry of a session (`/new`, `/reset`, session deletion).
+pub fn clear_session(session_id: &str) {
+    match clear_in(&SCRATCHPAD_DB.lock(), session_id) {
+        Ok(0) => {}
+        Ok(n) => info!("[scratchpad] Cleared {} key(s) of {}", n, session_id),
+        Err(e) => warn!("[scratchpad] Failed to clear {}: {}", session_id, e),
+    }
+}
+
+/// `scratchpad_set` tool.
+pub fn tool_scratchpad_set(args: &serde_json::Value) -> Result<String, String> {
+    let key = validate_key(args["key"].as_str().ok_or("Missing 'key'")?)?;
+    let value = args["value"].as_str().ok_or("Missing 'value'")?;
+    let session_id = current_session("scratchpad_set")?;
+
+    let stored = cap_value(value, MAX_VALUE_BYTES);
+    let evicted = set_in(&SCRATCHPAD_DB.lock(), &session_id, key, stored)?;
+    let mut out = format!("Saved '{}' ({} bytes)", key, stored.len());
+    if stored.len() < value.len() {
+        out.push_str(&format!("; truncated from {} bytes to the {} byte limit", value.len(), MAX_VALUE_BYTES));
+    }
+    if !evicted.is_empty() {
+        out.push_str(&format!("; evicted oldest: {}", evicted.join(", ")));
+    }
+    Ok(out)
+}
+
+/// `scratchpad_get` tool.
+pub fn tool_scratchpad_get(args: &serde_json::Value) -> Result<String, String> {
+    let key = validate_key(args["key"].as_str().ok_or("Missing 'key'")?)?;
+    let session_id = current_session("scratchpad_get")?;
+    get_in(&SCRATCHPAD_DB.lock(), &session_id, key)?
+        .ok_or_else(|| format!("No scratchpad entry '{}' in this session", key))
+}
+
+/// `scratchpad_list` tool.
+pub fn tool_scratchpad_list() -> Result<String, String> {
+    let session_id = current_session("scratchpad_list")?;
+    let entries = list_in(&SCRATCHPAD_DB.lock(), &session_id)?;
+    if entries.is_empty() {
+        return Ok("Scratchpad is empty".into());
+    }
+    let lines: Vec<String> =
+        entries.iter().map(|e| format!("{} ({} bytes, set {})", e.key, e.value.len(), e.updated_at)).collect();
+    Ok(format!("{}/{} keys:\n{}", entries.len(), MAX_KEYS, lines.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCRATCHPAD_SCHEMA).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_set_get_evict_and_clear() {
+        let conn = test_conn();
+        for i in 0..MAX_KEYS {
+            assert!(set_in(&conn, "s1", &format!("k{}", i), "v").unwrap().is_empty());
+        }
+        // Re-setting k0 makes k1 the oldest
+        set_in(&conn, "s1", "k0", "updated").unwrap();
+        assert_eq!(set_in(&conn, "s1", "new", "x").unwrap(), vec!["k1".to_string()]);
+        assert_eq!(get_in(&conn, "s1", "k0").unwrap().as_deref(), Some("updated"));
+        assert_eq!(get_in(&conn, "s1", "k1").unwrap(), None);
+        assert_eq!(list_in(&conn, "s1").unwrap().len(), MAX_KEYS);
+        assert_eq!(list_in(&conn, "s1").unwrap().last().unwrap().key, "new");
+
+        set_in(&conn, "s2", "k0", "other").unwrap();
+        assert_eq!(clear_in(&conn, "s1").unwrap(), MAX_KEYS);
+        assert!(list_in(&conn, "s1").unwrap().is_empty());
+        assert_eq!(get_in(&conn, "s2", "k0").unwrap().as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_cap_value_and_prompt() {
+        let long = "文".repeat(1_000);
+        let capped = cap_value(&long, MAX_VALUE_BYTES);
+        assert!(capped.len() <= MAX_VALUE_BYTES && capped.len() > MAX_VALUE_BYTES - 3);
+        assert_eq!(cap_value("short", MAX_VALUE_BYTES), "short");
+        assert!(validate_key("  ").is_err());
+
+        assert_eq!(render_prompt(&[]), None);
+        let entries = vec![
+            ScratchpadEntry { key: "plan".into(), value: "a\nb".into(), updated_at: String::new() },
+            ScratchpadEntry { key: "log".into(), value: "x".repeat(500), updated_at: String::new() },
+        ];
+        let prompt = render_prompt(&entries).unwrap();
+        assert!(prompt.starts_with("## Scratchpad (2/30 keys"));
+        assert!(prompt.contains("- plan: a b\n"));
+        assert!(prompt.ends_with(&format!("- log: {}…", "x".repeat(PROMPT_VALUE_CHARS))));
+    }
+}
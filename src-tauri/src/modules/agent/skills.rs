@@ -6,7 +6,10 @@
 //! Enabled/disabled is controlled by an `enabled` field in frontmatter (default: true).
 //! No database storage — skills are discovered by scanning the directory each time.
 
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
@@ -29,6 +32,9 @@ pub struct SkillFrontmatter {
     pub icon: Option<String>,
     #[serde(default)]
     pub homepage: Option<String>,
+    /// Capabilities the skill asks for, e.g. `shell` (informational, shown in security reports)
+    #[serde(default)]
+    pub permissions: Option<Vec<String>>,
     /// Whether this skill is enabled (default: true)
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -49,8 +55,28 @@ pub struct Skill {
     pub body: String,
     #[serde(default)]
     pub homepage: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// A SKILL.md that could not be loaded during the last scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillLoadError {
+    pub file: String,
+    pub error: String,
 }
 
+/// Payload of the `skills://changed` event.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SkillChangeEvent {
+    /// "added" | "removed" | "updated"
+    pub event: String,
+    pub skill_name: String,
+}
+
+/// Load errors from the most recent directory scan.
+static LOAD_ERRORS: Lazy<Mutex<Vec<SkillLoadError>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
 // ============================================================================
 // Skills directory
 // ============================================================================
@@ -74,17 +100,20 @@ fn ensure_skills_dir() -> Result<PathBuf, String> {
 // ============================================================================
 
 /// Parse a SKILL.md file into frontmatter + body.
-fn parse_skill_md(content: &str) -> Option<(SkillFrontmatter, String)> {
+fn parse_skill_md(content: &str) -> Result<(SkillFrontmatter, String), String> {
     let content = content.trim();
     if !content.starts_with("---") {
-        return None;
+        return Err("missing YAML frontmatter (file must start with ---)".to_string());
     }
     let rest = &content[3..];
-    let end_pos = rest.find("\n---")?;
+    let end_pos = rest
+        .find("\n---")
+        .ok_or("unterminated YAML frontmatter (missing closing ---)")?;
     let yaml_str = &rest[..end_pos].trim();
     let body = rest[end_pos + 4..].trim().to_string();
-    let frontmatter: SkillFrontmatter = serde_yaml::from_str(yaml_str).ok()?;
-    Some((frontmatter, body))
+    let frontmatter: SkillFrontmatter =
+        serde_yaml::from_str(yaml_str).map_err(|e| format!("invalid frontmatter: {}", e))?;
+    Ok((frontmatter, body))
 }
 
 // ============================================================================
@@ -106,6 +135,7 @@ fn scan_skills() -> Vec<Skill> {
     }
 
     let mut skills = Vec::new();
+    let mut errors = Vec::new();
     let entries = match std::fs::read_dir(&skills_dir) {
         Ok(e) => e,
         Err(e) => {
@@ -119,22 +149,28 @@ fn scan_skills() -> Vec<Skill> {
         if !path.is_dir() { continue; }
         let skill_file = path.join("SKILL.md");
         if skill_file.exists() {
-            if let Some(skill) = load_skill_file(&skill_file) {
-                skills.push(skill);
+            match load_skill_file(&skill_file) {
+                Ok(skill) => skills.push(skill),
+                Err(error) => errors.push(SkillLoadError {
+                    file: skill_file.to_string_lossy().to_string(),
+                    error,
+                }),
             }
         }
     }
 
+    errors.sort_by(|a, b| a.file.cmp(&b.file));
+    *LOAD_ERRORS.lock() = errors;
     skills.sort_by(|a, b| a.name.cmp(&b.name));
     skills
 }
 
 /// Load a single SKILL.md file.
-fn load_skill_file(path: &Path) -> Option<Skill> {
-    let content = std::fs::read_to_string(path).ok()?;
+fn load_skill_file(path: &Path) -> Result<Skill, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("read failed: {}", e))?;
     let (fm, body) = parse_skill_md(&content)?;
 
-    Some(Skill {
+    Ok(Skill {
         name: fm.name.clone(),
         description: fm.description.unwrap_or_default(),
         icon: fm.icon.unwrap_or_else(|| "📦".to_string()),
@@ -145,6 +181,7 @@ fn load_skill_file(path: &Path) -> Option<Skill> {
         enabled: fm.enabled,
         body,
         homepage: fm.homepage.unwrap_or_default(),
+        permissions: fm.permissions.unwrap_or_default(),
     })
 }
 
@@ -189,7 +226,7 @@ pub fn toggle_skill(name: &str, enabled: bool) -> Result<(), String> {
         .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
 
     let (mut fm, body) = parse_skill_md(&content)
-        .ok_or_else(|| "Failed to parse SKILL.md".to_string())?;
+        .map_err(|e| format!("Failed to parse SKILL.md: {}", e))?;
 
     fm.enabled = enabled;
 
@@ -256,6 +293,23 @@ enabled: true
     Ok(skill_file.to_string_lossy().to_string())
 }
 
+/// Write (or overwrite) a skill from frontmatter + markdown body. Returns the SKILL.md path.
+pub fn write_skill(frontmatter: &SkillFrontmatter, body: &str) -> Result<String, String> {
+    let skills_dir = ensure_skills_dir()?;
+    let skill_dir = skills_dir.join(&frontmatter.name);
+    std::fs::create_dir_all(&skill_dir)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let yaml = serde_yaml::to_string(frontmatter)
+        .map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+    let skill_file = skill_dir.join("SKILL.md");
+    std::fs::write(&skill_file, format!("---\n{}---\n\n{}\n", yaml, body))
+        .map_err(|e| format!("Failed to write SKILL.md: {}", e))?;
+
+    info!("Wrote skill: {}", frontmatter.name);
+    Ok(skill_file.to_string_lossy().to_string())
+}
+
 /// Uninstall (delete) a skill.
 fn uninstall_skill(name: &str) -> Result<(), String> {
     let skills_dir = get_skills_dir()?;
@@ -327,6 +381,13 @@ pub async fn skills_reload() -> Result<Vec<Skill>, String> {
     Ok(list_all_skills())
 }
 
+/// SKILL.md files that failed to load, from a fresh scan.
+#[tauri::command]
+pub async fn skills_get_load_errors() -> Result<Vec<SkillLoadError>, String> {
+    scan_skills();
+    Ok(LOAD_ERRORS.lock().clone())
+}
+
 #[tauri::command]
 pub async fn skills_get_body(name: String) -> Result<String, String> {
     let skills = list_all_skills();
@@ -437,36 +498,113 @@ pub async fn skills_get_dir() -> Result<String, String> {
 // Hot-Reload Watcher
 // ============================================================================
 
-/// Start a background task that scans the skills directory every 5 seconds
-/// and emits a `skills-changed` event when the skill list changes.
+/// Snapshot fingerprint of a skill: file mtime/size plus parsed version and enabled flag.
+fn skill_fingerprint(skill: &Skill) -> String {
+    let (mtime, len) = std::fs::metadata(&skill.path)
+        .map(|m| {
+            let mtime = m
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis())
+                .unwrap_or(0);
+            (mtime, m.len())
+        })
+        .unwrap_or((0, 0));
+    format!("{}:{}:{}:{}", skill.version, skill.enabled, mtime, len)
+}
+
+/// Per-skill changes between two `name → fingerprint` snapshots, sorted by name.
+fn diff_skill_snapshots(
+    old: &HashMap<String, String>,
+    new: &HashMap<String, String>,
+) -> Vec<SkillChangeEvent> {
+    let mut changes: Vec<SkillChangeEvent> = Vec::new();
+    for (name, fingerprint) in new {
+        let event = match old.get(name) {
+            None => "added",
+            Some(prev) if prev != fingerprint => "updated",
+            Some(_) => continue,
+        };
+        changes.push(SkillChangeEvent { event: event.to_string(), skill_name: name.clone() });
+    }
+    for name in old.keys().filter(|name| !new.contains_key(*name)) {
+        changes.push(SkillChangeEvent { event: "removed".to_string(), skill_name: name.clone() });
+    }
+    changes.sort_by(|a, b| a.skill_name.cmp(&b.skill_name));
+    changes
+}
+
+/// Poll the skills directory every 5s. Each added/removed/updated skill is
+/// emitted as `skills://changed`, followed by a `skills-changed` summary.
 pub fn start_skills_watcher() {
-    tauri::async_runtime::spawn(async {
-        use std::collections::HashSet;
-        let mut last_snapshot: HashSet<String> = HashSet::new();
+    crate::modules::infra::shutdown::spawn("skills-watcher", |mut shutdown| async move {
+        use crate::modules::infra::log_bridge::emit_custom_event;
 
         // Ensure directory exists
         let _ = ensure_skills_dir();
 
+        // Initial load is the baseline; no events for it
+        let snapshot = |skills: &[Skill]| -> HashMap<String, String> {
+            skills.iter().map(|s| (s.name.clone(), skill_fingerprint(s))).collect()
+        };
+        let mut last_snapshot = snapshot(&scan_skills());
+
         loop {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(5)) => {}
+                _ = shutdown.cancelled() => break,
+            }
 
             let skills = scan_skills();
-            let current: HashSet<String> = skills
-                .iter()
-                .map(|s| format!("{}:{}:{}", s.name, s.version, s.enabled))
-                .collect();
-
-            if current != last_snapshot {
-                if !last_snapshot.is_empty() {
-                    // Only emit after the first scan (skip initial load)
-                    info!("[skills] Change detected, notifying frontend ({} skills)", skills.len());
-                    let payload = serde_json::json!({ "count": skills.len() });
-                    crate::modules::infra::log_bridge::emit_custom_event("skills-changed", payload);
-                }
-                last_snapshot = current;
+            let current = snapshot(&skills);
+            let changes = diff_skill_snapshots(&last_snapshot, &current);
+            if changes.is_empty() {
+                continue;
             }
+
+            info!("[skills] {} change(s) detected, notifying frontend ({} skills)", changes.len(), skills.len());
+            for change in changes {
+                emit_custom_event("skills://changed", change);
+            }
+            emit_custom_event("skills-changed", serde_json::json!({ "count": skills.len() }));
+            last_snapshot = current;
         }
     });
     info!("[skills] Hot-reload watcher started (scan every 5s)");
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_skill_md_errors() {
+        assert!(parse_skill_md("---\nname: demo\n---\nbody").is_ok());
+        assert!(parse_skill_md("no frontmatter").unwrap_err().contains("missing"));
+        assert!(parse_skill_md("---\nname: demo\n").unwrap_err().contains("unterminated"));
+        assert!(parse_skill_md("---\ndescription: x\n---\n").unwrap_err().contains("invalid"));
+    }
+
+    #[test]
+    fn test_diff_skill_snapshots() {
+        let snap = |items: &[(&str, &str)]| -> HashMap<String, String> {
+            items.iter().map(|(n, f)| (n.to_string(), f.to_string())).collect()
+        };
+        let old = snap(&[("a", "1"), ("b", "1"), ("c", "1")]);
+        let new = snap(&[("a", "1"), ("b", "2"), ("d", "1")]);
+        let changes: Vec<(String, String)> = diff_skill_snapshots(&old, &new)
+            .into_iter()
+            .map(|c| (c.event, c.skill_name))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("updated".to_string(), "b".to_string()),
+                ("removed".to_string(), "c".to_string()),
+                ("added".to_string(), "d".to_string()),
+            ]
+        );
+        assert!(diff_skill_snapshots(&old, &old).is_empty());
+    }
+}
@@ -60,6 +60,8 @@ pub async fn run_subagent(params: SubagentParams) -> Result<SubagentResult, Stri
     let model = Arc::new(crate::modules::agent::core::InterceptingChatModel {
         inner: base_model,
         limit,
+        retry: config.ai_retry,
+        stream: None,
     });
 
     let base_prompt = params.system_prompt.unwrap_or_else(|| {
@@ -10,6 +10,7 @@ use std::process::Stdio;
 use std::sync::{Arc, Mutex};
 
 use agents_sdk::{ToolContext, ToolParameterSchema, ToolResult};
+use crate::modules::environments::WithManagedEnv;
 
 /// Shared HTTP client — reused across all web tools for connection pooling.
 static SHARED_HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
@@ -41,12 +42,101 @@ pub fn take_sent_files_for(session_id: &str) -> Vec<Value> {
     }
 }
 
+/// Results of tools that finished during the current agent run (keyed by account_id),
+/// reported back as partial results when the run is cancelled.
+static COMPLETED_TOOLS: std::sync::LazyLock<Mutex<std::collections::HashMap<String, Vec<(String, String)>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(std::collections::HashMap::new()));
+
+/// Reset completed-tool tracking at the start of an agent run.
+pub fn clear_completed_tools_for(session_id: &str) {
+    if let Ok(mut map) = COMPLETED_TOOLS.lock() {
+        map.remove(session_id);
+    }
+}
+
+/// Get and clear `(tool name, output)` pairs of tools completed in a session's run.
+pub fn take_completed_tools_for(session_id: &str) -> Vec<(String, String)> {
+    COMPLETED_TOOLS
+        .lock()
+        .ok()
+        .and_then(|mut map| map.remove(session_id))
+        .unwrap_or_default()
+}
+
+/// Run a tool body until it finishes or the session's run is cancelled; in
+/// a dry-run replay the body is not run at all, only recorded.
+/// Dropping the future aborts in-flight requests; shell children are killed
+/// by their `ProcessGroupGuard`. Reports `tool_started` / `tool_finished` on
+/// the run's progress stream, and swaps large outputs for artifact references.
+async fn run_cancellable<F>(name: &str, args: &Value, fut: F) -> Result<String, String>
+where
+    F: std::future::Future<Output = Result<String, String>>,
+{
+    if super::core::SESSION_DRY_RUN.try_with(|dry_run| *dry_run).unwrap_or(false) {
+        super::replay::note_tool_call(name, args, true);
+        return Ok(format!("[dry run] `{}` was not executed; assume it succeeded.", name));
+    }
+    let progress = super::core::session_progress();
+    if let Some(p) = &progress {
+        p.emit("tool_started", json!({ "name": name, "args": super::progress::args_preview(args) }));
+    }
+    let start = std::time::Instant::now();
+    let token = super::core::session_cancel_token();
+    let result = tokio::select! {
+        _ = token.cancelled() => Err(format!("{} cancelled by user", name)),
+        r = fut => r,
+    };
+    // Large outputs go to disk; the model gets a preview and an artifact id
+    let result = result.map(|output| super::artifacts::persist_if_large(name, &output).unwrap_or(output));
+    if let Some(p) = &progress {
+        p.emit(
+            "tool_finished",
+            json!({ "name": name, "duration_ms": start.elapsed().as_millis() as u64, "success": result.is_ok() }),
+        );
+    }
+    super::replay::note_tool_call(name, args, result.is_ok());
+    if let Ok(output) = &result {
+        if let Ok(account_id) = super::core::SESSION_ACCOUNT_ID.try_with(|a| a.clone()) {
+            if let Ok(mut map) = COMPLETED_TOOLS.lock() {
+                map.entry(account_id).or_default().push((name.to_string(), output.clone()));
+            }
+        }
+    }
+    result
+}
+
+/// Kills a spawned shell's whole process group (the shell plus anything it
+/// started) when dropped — on cancellation, timeout, or the tool future being
+/// aborted. Disarmed after a normal exit so intentionally backgrounded jobs survive.
+pub(super) struct ProcessGroupGuard {
+    pub(super) pgid: Option<u32>,
+}
+
+impl ProcessGroupGuard {
+    pub(super) fn disarm(&mut self) {
+        self.pgid = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid {
+            // SAFETY: killpg only sends a signal; the group was created by us via process_group(0).
+            unsafe {
+                libc::killpg(pgid as libc::pid_t, libc::SIGKILL);
+            }
+            info!("[shell_exec] Killed process group {}", pgid);
+        }
+    }
+}
+
 /// Sandbox directory for agent file writes — all file_write/file_edit operations
 /// are restricted to this directory to prevent the agent from writing files everywhere.
 const SANDBOX_DIR: &str = "helix_workspace";
 
 /// Get the full sandbox directory path
-fn get_sandbox_path() -> String {
+pub(crate) fn get_sandbox_path() -> String {
     if let Some(home) = dirs::home_dir() {
         format!("{}/{}", home.display(), SANDBOX_DIR)
     } else {
@@ -87,9 +177,9 @@ fn validate_sandbox_path(path: &str) -> Result<String, String> {
     if check_path.starts_with(&canonical_sandbox) {
         Ok(abs_path)
     } else {
-        Err(format!(
-            "❌ 安全限制: 只能在 ~/{} 目录下写入文件。请使用该目录下的路径。\n当前路径: {}",
-            SANDBOX_DIR, abs_path
+        Err(crate::modules::i18n::t(
+            "error.sandbox_write_denied",
+            &[("dir", SANDBOX_DIR), ("path", &abs_path)],
         ))
     }
 }
@@ -136,7 +226,7 @@ fn schema(props: Vec<(String, ToolParameterSchema)>, required: Vec<&str>) -> Too
 // ============================================================================
 
 pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
-    vec![
+    let mut tools = vec![
         agents_sdk::tool(
             "shell_exec",
             "Execute a shell command on the system and return stdout/stderr.",
@@ -150,7 +240,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("$ {}", if cmd.len() > 60 { &cmd[..60] } else { cmd });
                 super::core::emit_agent_progress("tool_call", json!({ "name": "shell_exec", "icon": "terminal", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_shell_exec(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("shell_exec", &args, tool_shell_exec(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "shell_exec", "icon": "terminal", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -168,7 +258,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{}", path);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "file_read", "icon": "file", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_file_read(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("file_read", &args, tool_file_read(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "file_read", "icon": "file", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -187,7 +277,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{}", path);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "file_write", "icon": "edit", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_file_write(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("file_write", &args, tool_file_write(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 let content_len = args["content"].as_str().map(|s| s.len()).unwrap_or(0);
                 super::core::emit_agent_progress("tool_result", json!({ "name": "file_write", "icon": "edit", "chars": content_len, "elapsed_ms": elapsed, "detail": detail }));
@@ -208,7 +298,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{}", path);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "file_edit", "icon": "edit", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_file_edit(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("file_edit", &args, tool_file_edit(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "file_edit", "icon": "edit", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -229,12 +319,35 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{} {}", method, url);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "web_fetch", "icon": "globe", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_web_fetch(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("web_fetch", &args, tool_web_fetch(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "web_fetch", "icon": "globe", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
             },
         ),
+        agents_sdk::tool(
+            "http_test",
+            "Test an API endpoint: send one request and report status, latency, headers and a preview of the (pretty-printed JSON) body. Use it for health checks and API debugging; use web_fetch to read page content.",
+            schema(vec![
+                param("url", "string", Some("Endpoint URL")),
+                param("method", "string", Some("HTTP method (default: GET)")),
+                param("headers", "object", Some("Request headers")),
+                param("body", "string", Some("Request body")),
+                param("expected_status", "integer", Some("Status that counts as ok (default: any 2xx)")),
+                param("timeout_secs", "integer", Some("Request timeout in seconds (default: 10)")),
+            ], vec!["url"]),
+            |args: Value, ctx: ToolContext| async move {
+                let url = args["url"].as_str().unwrap_or("?");
+                let method = args["method"].as_str().unwrap_or("GET");
+                let detail = format!("{} {}", method, url);
+                super::core::emit_agent_progress("tool_call", json!({ "name": "http_test", "icon": "globe", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("http_test", &args, tool_http_test(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "http_test", "icon": "globe", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
         agents_sdk::tool(
             "web_search",
             "Search the web. Also handles weather queries and hot searches.",
@@ -247,7 +360,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{}", query);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "web_search", "icon": "search", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_web_search(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("web_search", &args, tool_web_search(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "web_search", "icon": "search", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -264,7 +377,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let key = args["key"].as_str().unwrap_or("?");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "memory_store", "icon": "brain", "detail": key }));
                 let start = std::time::Instant::now();
-                let r = tool_memory_store(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("memory_store", &args, tool_memory_store(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "memory_store", "icon": "brain", "chars": r.len(), "elapsed_ms": elapsed, "detail": key }));
                 Ok(ToolResult::text(&ctx, r))
@@ -280,7 +393,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let query = args["query"].as_str().unwrap_or("?");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "memory_recall", "icon": "brain", "detail": query }));
                 let start = std::time::Instant::now();
-                let r = tool_memory_recall(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("memory_recall", &args, tool_memory_recall(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "memory_recall", "icon": "brain", "chars": r.len(), "elapsed_ms": elapsed, "detail": query }));
                 Ok(ToolResult::text(&ctx, r))
@@ -298,7 +411,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let path = args["path"].as_str().unwrap_or("?");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "list_dir", "icon": "folder", "detail": path }));
                 let start = std::time::Instant::now();
-                let r = tool_list_dir(&args).map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("list_dir", &args, async { tool_list_dir(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "list_dir", "icon": "folder", "chars": r.len(), "elapsed_ms": elapsed, "detail": path }));
                 Ok(ToolResult::text(&ctx, r))
@@ -320,7 +433,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("'{}' in {}", pattern, path);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "grep_search", "icon": "search", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_grep_search(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("grep_search", &args, tool_grep_search(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "grep_search", "icon": "search", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -341,7 +454,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("'{}' in {}", pattern, path);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "find_files", "icon": "folder", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_find_files(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("find_files", &args, tool_find_files(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "find_files", "icon": "folder", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
@@ -358,7 +471,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let filter = args["filter"].as_str().unwrap_or("all");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "process_list", "icon": "cpu", "detail": filter }));
                 let start = std::time::Instant::now();
-                let r = tool_process_list(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("process_list", &args, tool_process_list(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "process_list", "icon": "cpu", "chars": r.len(), "elapsed_ms": elapsed, "detail": filter }));
                 Ok(ToolResult::text(&ctx, r))
@@ -376,7 +489,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = args["name"].as_str().or(args["pid"].as_str()).unwrap_or("?");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "process_kill", "icon": "cpu", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_process_kill(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("process_kill", &args, tool_process_kill(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "process_kill", "icon": "cpu", "chars": r.len(), "elapsed_ms": elapsed }));
                 Ok(ToolResult::text(&ctx, r))
@@ -389,7 +502,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
             |args: Value, ctx: ToolContext| async move {
                 super::core::emit_agent_progress("tool_call", json!({ "name": "sysinfo", "icon": "cpu", "detail": "系统信息" }));
                 let start = std::time::Instant::now();
-                let r = tool_sysinfo(&args).map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("sysinfo", &args, async { tool_sysinfo(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "sysinfo", "icon": "cpu", "chars": r.len(), "elapsed_ms": elapsed }));
                 Ok(ToolResult::text(&ctx, r))
@@ -405,7 +518,7 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let path = args["path"].as_str().unwrap_or("?");
                 super::core::emit_agent_progress("tool_call", json!({ "name": "chat_send_file", "icon": "file", "detail": path }));
                 let start = std::time::Instant::now();
-                let r = tool_chat_send_file(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("chat_send_file", &args, tool_chat_send_file(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "chat_send_file", "icon": "file", "chars": r.len(), "elapsed_ms": elapsed, "detail": path }));
                 Ok(ToolResult::text(&ctx, r))
@@ -420,16 +533,181 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 Ok(ToolResult::text(&ctx, r))
             },
         ),
+        agents_sdk::tool(
+            "clipboard_read",
+            "Read the current text content of the system clipboard.",
+            schema(vec![], vec![]),
+            |args: Value, ctx: ToolContext| async move {
+                super::core::emit_agent_progress("tool_call", json!({ "name": "clipboard_read", "icon": "clipboard", "detail": "读取剪贴板" }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("clipboard_read", &args, clipboard_get_text()).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "clipboard_read", "icon": "clipboard", "chars": r.len(), "elapsed_ms": elapsed }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "clipboard_write",
+            "Copy text to the system clipboard so the user can paste it elsewhere.",
+            schema(vec![
+                param("text", "string", Some("Text to put on the clipboard")),
+            ], vec!["text"]),
+            |args: Value, ctx: ToolContext| async move {
+                super::core::emit_agent_progress("tool_call", json!({ "name": "clipboard_write", "icon": "clipboard", "detail": "写入剪贴板" }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("clipboard_write", &args, tool_clipboard_write(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "clipboard_write", "icon": "clipboard", "chars": r.len(), "elapsed_ms": elapsed }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "ocr",
+            "Extract text from an image file (screenshot, scanned document, photo). Uses tesseract when installed, otherwise the vision model. Returns text and word bounding boxes when available.",
+            schema(vec![
+                param("path", "string", Some("Absolute path to the image file")),
+                param("lang", "string", Some("Tesseract language codes, e.g. chi_sim+eng (default)")),
+                param("include_boxes", "boolean", Some("Include word bounding boxes in the output")),
+            ], vec!["path"]),
+            |args: Value, ctx: ToolContext| async move {
+                let path = args["path"].as_str().unwrap_or("?");
+                super::core::emit_agent_progress("tool_call", json!({ "name": "ocr", "icon": "file", "detail": path }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("ocr", &args, tool_ocr(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "ocr", "icon": "file", "chars": r.len(), "elapsed_ms": elapsed, "detail": path }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "schedule_task",
+            "Create a recurring scheduled task. Accepts a natural-language schedule (e.g. '每天早上8点', '每周一上午10点', 'every 30 minutes', 'weekdays at 6pm') or a cron expression, plus either a shell command to run or a reminder message.",
+            schema(vec![
+                param("schedule", "string", Some("Natural-language schedule or 5-field cron expression")),
+                param("name", "string", Some("Short task name")),
+                param("command", "string", Some("Shell command to run on schedule")),
+                param("message", "string", Some("Reminder text to deliver on schedule (used when no command is given)")),
+                param("notify_channel", "string", Some("Notification channel for results: feishu | dingtalk | smtp")),
+            ], vec!["schedule", "name"]),
+            |args: Value, ctx: ToolContext| async move {
+                let schedule = args["schedule"].as_str().unwrap_or("?");
+                super::core::emit_agent_progress("tool_call", json!({ "name": "schedule_task", "icon": "clock", "detail": schedule }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("schedule_task", &args, async { tool_schedule_task(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "schedule_task", "icon": "clock", "chars": r.len(), "elapsed_ms": elapsed, "detail": schedule }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "mcp_resource_fetch",
+            "List or read resources (files, database snapshots, documents) exposed by a configured MCP server. Omit 'uri' to list available resources.",
+            schema(vec![
+                param("server", "string", Some("MCP server name as configured in Helix")),
+                param("uri", "string", Some("Resource URI to read; omit to list resources")),
+            ], vec!["server"]),
+            |args: Value, ctx: ToolContext| async move {
+                let server = args["server"].as_str().unwrap_or("?");
+                let detail = format!("{} {}", server, args["uri"].as_str().unwrap_or("(list)"));
+                super::core::emit_agent_progress("tool_call", json!({ "name": "mcp_resource_fetch", "icon": "plug", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("mcp_resource_fetch", &args, tool_mcp_resource_fetch(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "mcp_resource_fetch", "icon": "plug", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "usage_query",
+            "Query LLM token usage and cost. Use for questions like '我今天花了多少 token' or 'which model costs the most'. Returns compact JSON.",
+            schema(vec![
+                param("scope", "string", Some("today | totals | by_model | daily (default: today)")),
+                param("days", "integer", Some("Number of days for scope=daily (default 7)")),
+            ], vec![]),
+            |args: Value, ctx: ToolContext| async move {
+                let scope = args["scope"].as_str().unwrap_or("today").to_string();
+                super::core::emit_agent_progress("tool_call", json!({ "name": "usage_query", "icon": "chart", "detail": scope }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("usage_query", &args, async { tool_usage_query(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "usage_query", "icon": "chart", "chars": r.len(), "elapsed_ms": elapsed, "detail": scope }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "artifact_read",
+            "Read a saved tool output artifact of this session. Long tool outputs are replaced by a preview and 'artifact #id'; use this to see the rest, paging with offset/limit.",
+            schema(vec![
+                param("id", "integer", Some("Artifact id from the tool output")),
+                param("offset", "integer", Some("Character offset to start at (default 0)")),
+                param("limit", "integer", Some("Max characters to return (default 4000)")),
+            ], vec!["id"]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = format!("#{}", args["id"].as_i64().unwrap_or(0));
+                super::core::emit_agent_progress("tool_call", json!({ "name": "artifact_read", "icon": "file", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("artifact_read", &args, async { super::artifacts::tool_artifact_read(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "artifact_read", "icon": "file", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "scratchpad_set",
+            "Save a short working note for this conversation under a key (overwrites). Use it for plans, intermediate results and ids you need later in this task. Values over 2KB are truncated; at most 30 keys, the oldest is evicted. Cleared by /new; not long-term memory (use memory_store for that).",
+            schema(vec![
+                param("key", "string", Some("Short key, e.g. 'plan' or 'pr_url'")),
+                param("value", "string", Some("Value to store (max 2KB)")),
+            ], vec!["key", "value"]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = args["key"].as_str().unwrap_or("").to_string();
+                super::core::emit_agent_progress("tool_call", json!({ "name": "scratchpad_set", "icon": "brain", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("scratchpad_set", &args, async { super::scratchpad::tool_scratchpad_set(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "scratchpad_set", "icon": "brain", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "scratchpad_get",
+            "Read the full value of a scratchpad key of this conversation. The system prompt only shows a shortened summary.",
+            schema(vec![
+                param("key", "string", Some("Key to read")),
+            ], vec!["key"]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = args["key"].as_str().unwrap_or("").to_string();
+                super::core::emit_agent_progress("tool_call", json!({ "name": "scratchpad_get", "icon": "brain", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("scratchpad_get", &args, async { super::scratchpad::tool_scratchpad_get(&args) }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "scratchpad_get", "icon": "brain", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "scratchpad_list",
+            "List the scratchpad keys of this conversation with their sizes, oldest first.",
+            schema(vec![], vec![]),
+            |args: Value, ctx: ToolContext| async move {
+                super::core::emit_agent_progress("tool_call", json!({ "name": "scratchpad_list", "icon": "brain" }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("scratchpad_list", &args, async { super::scratchpad::tool_scratchpad_list() }).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "scratchpad_list", "icon": "brain", "chars": r.len(), "elapsed_ms": elapsed }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
         agents_sdk::tool(
             "desktop_screenshot",
-            "Capture a screenshot of the current desktop screen. Returns the path to the saved screenshot image.",
+            "Capture a screenshot of the current desktop screen. Returns the path to the saved screenshot image. Not available on Linux Wayland sessions; use browser_use screenshot for web pages there.",
             schema(vec![
                 param("display", "integer", Some("Display number to capture (default: main display)")),
             ], vec![]),
             |args: Value, ctx: ToolContext| async move {
                 super::core::emit_agent_progress("tool_call", json!({ "name": "desktop_screenshot", "icon": "camera", "detail": "截图" }));
                 let start = std::time::Instant::now();
-                let r = tool_desktop_screenshot(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("desktop_screenshot", &args, tool_desktop_screenshot(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "desktop_screenshot", "icon": "camera", "chars": r.len(), "elapsed_ms": elapsed }));
                 Ok(ToolResult::text(&ctx, r))
@@ -449,12 +727,172 @@ pub fn build_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
                 let detail = format!("{}", action);
                 super::core::emit_agent_progress("tool_call", json!({ "name": "browser_use", "icon": "globe", "detail": detail }));
                 let start = std::time::Instant::now();
-                let r = tool_browser_use(&args).await.map_err(|e| anyhow::anyhow!(e))?;
+                let r = run_cancellable("browser_use", &args, tool_browser_use(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
                 let elapsed = start.elapsed().as_millis();
                 super::core::emit_agent_progress("tool_result", json!({ "name": "browser_use", "icon": "globe", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
                 Ok(ToolResult::text(&ctx, r))
             },
         ),
+        agents_sdk::tool(
+            "browser_render",
+            "Render a page in a headless browser (runs its JavaScript) and return the readable text. Use when web_fetch returns an empty shell or a JS-heavy site. Optionally waits for a CSS selector and captures a full-page screenshot sent to the chat.",
+            schema(vec![
+                param("url", "string", Some("http(s) URL to render")),
+                param("wait_selector", "string", Some("CSS selector to wait for (default: wait for network idle)")),
+                param("screenshot", "boolean", Some("Also capture a full-page screenshot (default: false)")),
+            ], vec!["url"]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = args["url"].as_str().unwrap_or("?").to_string();
+                super::core::emit_agent_progress("tool_call", json!({ "name": "browser_render", "icon": "globe", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("browser_render", &args, tool_browser_render(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "browser_render", "icon": "globe", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+    ];
+
+    // GitHub tools are only offered once a token is configured (~/.helix/github.json)
+    if crate::modules::github::is_configured() {
+        tools.extend(build_github_tools());
+    }
+    // kubectl and Aliyun are opt-in via tool_policy so clusters/clouds aren't touchable by default
+    let policy = crate::modules::load_app_config().map(|c| c.tool_policy).unwrap_or_default();
+    if policy.kubectl_enabled {
+        tools.push(build_kubectl_tool());
+    }
+    if policy.aliyun_enabled && crate::modules::aliyun::has_credentials() {
+        tools.extend(build_aliyun_tools());
+    }
+    tools
+}
+
+fn build_aliyun_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
+    vec![
+        agents_sdk::tool(
+            "aliyun_ecs_list",
+            "List Aliyun ECS instances (id, name, status, public IP) in a region using the local aliyun CLI credentials.",
+            schema(vec![
+                param("region", "string", Some("Region ID, e.g. cn-hangzhou (default: profile region)")),
+            ], vec![]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = args["region"].as_str().unwrap_or("default").to_string();
+                super::core::emit_agent_progress("tool_call", json!({ "name": "aliyun_ecs_list", "icon": "cpu", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("aliyun_ecs_list", &args, tool_aliyun_ecs_list(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "aliyun_ecs_list", "icon": "cpu", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+        agents_sdk::tool(
+            "aliyun_oss_upload",
+            "Upload a local/workspace file to an Aliyun OSS bucket and return a time-limited signed download URL.",
+            schema(vec![
+                param("path", "string", Some("File to upload (relative paths resolve against the session workspace)")),
+                param("bucket", "string", Some("OSS bucket name")),
+                param("key", "string", Some("Object key, e.g. reports/2024/summary.pdf")),
+                param("region", "string", Some("Bucket region, e.g. cn-hangzhou (default: profile region)")),
+                param("expires_secs", "integer", Some("Signed URL lifetime in seconds (default 3600, max 604800)")),
+            ], vec!["path", "bucket", "key"]),
+            |args: Value, ctx: ToolContext| async move {
+                let detail = format!("{}/{}", args["bucket"].as_str().unwrap_or("?"), args["key"].as_str().unwrap_or("?"));
+                super::core::emit_agent_progress("tool_call", json!({ "name": "aliyun_oss_upload", "icon": "file", "detail": detail }));
+                let start = std::time::Instant::now();
+                let r = run_cancellable("aliyun_oss_upload", &args, tool_aliyun_oss_upload(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+                let elapsed = start.elapsed().as_millis();
+                super::core::emit_agent_progress("tool_result", json!({ "name": "aliyun_oss_upload", "icon": "file", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+                Ok(ToolResult::text(&ctx, r))
+            },
+        ),
+    ]
+}
+
+fn build_kubectl_tool() -> Arc<dyn agents_sdk::Tool> {
+    agents_sdk::tool(
+        "kubectl_exec",
+        "Run kubectl against a kubeconfig context (default: current-context). Read-only verbs (get, describe, logs, top) unless mutations are enabled in settings. Put the subcommand first, e.g. [\"get\", \"pods\", \"-o\", \"wide\"].",
+        schema(vec![
+            param("args", "array", Some("kubectl arguments starting with the subcommand, e.g. [\"logs\", \"api-0\", \"--tail=200\"]")),
+            param("context", "string", Some("kubeconfig context name (default: current-context)")),
+            param("namespace", "string", Some("Namespace (default: the context's namespace)")),
+            param("timeout_secs", "integer", Some("Timeout in seconds (default 30, max 120)")),
+        ], vec!["args"]),
+        |args: Value, ctx: ToolContext| async move {
+            let detail = args["args"].as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(" "))
+                .unwrap_or_default();
+            super::core::emit_agent_progress("tool_call", json!({ "name": "kubectl_exec", "icon": "terminal", "detail": detail }));
+            let start = std::time::Instant::now();
+            let r = run_cancellable("kubectl_exec", &args, tool_kubectl_exec(&args)).await.map_err(|e| anyhow::anyhow!(e))?;
+            let elapsed = start.elapsed().as_millis();
+            super::core::emit_agent_progress("tool_result", json!({ "name": "kubectl_exec", "icon": "terminal", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+            Ok(ToolResult::text(&ctx, r))
+        },
+    )
+}
+
+fn github_tool(
+    name: &'static str,
+    description: &'static str,
+    params: ToolParameterSchema,
+) -> Arc<dyn agents_sdk::Tool> {
+    agents_sdk::tool(name, description, params, move |args: Value, ctx: ToolContext| async move {
+        let detail = args["title"].as_str()
+            .map(String::from)
+            .or_else(|| args["pr_number"].as_u64().map(|n| format!("#{}", n)))
+            .unwrap_or_else(|| args["state"].as_str().unwrap_or("open").to_string());
+        super::core::emit_agent_progress("tool_call", json!({ "name": name, "icon": "github", "detail": detail }));
+        let start = std::time::Instant::now();
+        let r = run_cancellable(name, &args, tool_github(name, &args)).await.map_err(|e| anyhow::anyhow!(e))?;
+        let elapsed = start.elapsed().as_millis();
+        super::core::emit_agent_progress("tool_result", json!({ "name": name, "icon": "github", "chars": r.len(), "elapsed_ms": elapsed, "detail": detail }));
+        Ok(ToolResult::text(&ctx, r))
+    })
+}
+
+fn build_github_tools() -> Vec<Arc<dyn agents_sdk::Tool>> {
+    let repo_param = || param("repo", "string", Some("Repository as owner/repo (default: configured default repo)"));
+    vec![
+        github_tool(
+            "github_create_issue",
+            "Create a GitHub issue. Returns the issue number and URL.",
+            schema(vec![
+                param("title", "string", Some("Issue title")),
+                param("body", "string", Some("Issue body (markdown)")),
+                param("labels", "array", Some("Label names to apply")),
+                repo_param(),
+            ], vec!["title"]),
+        ),
+        github_tool(
+            "github_list_issues",
+            "List GitHub issues (pull requests excluded).",
+            schema(vec![
+                param("state", "string", Some("open | closed | all (default: open)")),
+                param("limit", "integer", Some("Max issues to return (default 20, max 100)")),
+                repo_param(),
+            ], vec![]),
+        ),
+        github_tool(
+            "github_create_pr",
+            "Open a GitHub pull request from branch `head` into `base`.",
+            schema(vec![
+                param("title", "string", Some("Pull request title")),
+                param("body", "string", Some("Pull request description (markdown)")),
+                param("head", "string", Some("Branch with the changes (or owner:branch for forks)")),
+                param("base", "string", Some("Branch to merge into, e.g. main")),
+                repo_param(),
+            ], vec!["title", "head", "base"]),
+        ),
+        github_tool(
+            "github_get_pr_diff",
+            "Fetch the unified diff of a GitHub pull request for review.",
+            schema(vec![
+                param("pr_number", "integer", Some("Pull request number")),
+                repo_param(),
+            ], vec!["pr_number"]),
+        ),
     ]
 }
 
@@ -469,6 +907,7 @@ pub async fn execute_tool(name: &str, args: &Value, _ctx: Option<&str>) -> Resul
         "file_write" => tool_file_write(args).await,
         "file_edit" => tool_file_edit(args).await,
         "web_fetch" => tool_web_fetch(args).await,
+        "http_test" => tool_http_test(args).await,
         "web_search" => tool_web_search(args).await,
         "memory_store" => tool_memory_store(args).await,
         "memory_recall" => tool_memory_recall(args).await,
@@ -480,8 +919,25 @@ pub async fn execute_tool(name: &str, args: &Value, _ctx: Option<&str>) -> Resul
         "sysinfo" => tool_sysinfo(args),
         "chat_send_file" => tool_chat_send_file(args).await,
         "get_current_time" => Ok(tool_get_current_time()),
+        "ocr" => tool_ocr(args).await,
+        "schedule_task" => tool_schedule_task(args),
+        "mcp_resource_fetch" => tool_mcp_resource_fetch(args).await,
+        "usage_query" => tool_usage_query(args),
+        "artifact_read" => super::artifacts::tool_artifact_read(args),
+        "scratchpad_set" => super::scratchpad::tool_scratchpad_set(args),
+        "scratchpad_get" => super::scratchpad::tool_scratchpad_get(args),
+        "scratchpad_list" => super::scratchpad::tool_scratchpad_list(),
+        "github_create_issue" | "github_list_issues" | "github_create_pr" | "github_get_pr_diff" => {
+            tool_github(name, args).await
+        }
+        "kubectl_exec" => tool_kubectl_exec(args).await,
+        "aliyun_ecs_list" => tool_aliyun_ecs_list(args).await,
+        "aliyun_oss_upload" => tool_aliyun_oss_upload(args).await,
+        "clipboard_read" => clipboard_get_text().await,
+        "clipboard_write" => tool_clipboard_write(args).await,
         "desktop_screenshot" => tool_desktop_screenshot(args).await,
         "browser_use" => tool_browser_use(args).await,
+        "browser_render" => tool_browser_render(args).await,
         other => Err(format!("Unknown tool: {}", other)),
     }
 }
@@ -505,52 +961,85 @@ async fn tool_shell_exec(args: &Value) -> Result<String, String> {
     let working_dir = args["working_dir"]
         .as_str()
         .map(|s| expand_path(s))
-        .unwrap_or_else(|| {
-            // Use session workspace if set, otherwise fall back to sandbox
-            let ws_path = super::core::SESSION_WORKSPACE
-                .try_with(|ws| ws.clone())
-                .ok()
-                .flatten()
-                .map(|w| expand_path(&w))
-                .unwrap_or_else(|| {
-                    let sandbox = dirs::home_dir()
-                        .map(|h| h.join(".helix").join("sandbox"))
-                        .unwrap_or_else(|| std::path::PathBuf::from("/tmp/helix-sandbox"));
-                    sandbox.to_string_lossy().to_string()
-                });
-            let _ = std::fs::create_dir_all(&ws_path);
-            ws_path
-        });
+        .unwrap_or_else(default_working_dir);
     let timeout = args["timeout_secs"].as_u64().unwrap_or(30);
 
-    let output = tokio::time::timeout(
-        std::time::Duration::from_secs(timeout),
-        if cfg!(target_os = "macos") {
-            tokio::process::Command::new("zsh")
-                .arg("-l")
-                .arg("-c")
-                .arg(cmd)
-                .current_dir(&working_dir)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output()
-        } else {
-            tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(cmd)
-                .current_dir(&working_dir)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .output()
-        },
-    )
-    .await
-    .map_err(|_| format!("Command timed out after {}s", timeout))?
-    .map_err(|e| format!("Command failed: {}", e))?;
+    let (code, output) = run_shell(cmd, &working_dir, timeout).await?;
+    Ok(format!("Exit code: {}\n{}", code, output))
+}
+
+/// Session workspace if set, otherwise the sandbox directory; created if missing.
+pub(super) fn default_working_dir() -> String {
+    let ws_path = super::core::SESSION_WORKSPACE
+        .try_with(|ws| ws.clone())
+        .ok()
+        .flatten()
+        .map(|w| expand_path(&w))
+        .unwrap_or_else(super::sandbox::sandbox_dir);
+    let _ = std::fs::create_dir_all(&ws_path);
+    ws_path
+}
+
+/// Run `cmd` in a shell the way `shell_exec` does: sandboxed when
+/// HELIX_SANDBOX is on, cancellable and killed as a process group.
+/// Returns the exit code and the formatted output.
+pub(super) async fn run_shell(cmd: &str, working_dir: &str, timeout: u64) -> Result<(i32, String), String> {
+    if super::sandbox::sandbox_enabled() {
+        // Whatever `working_dir` the caller picked, writes stay confined to
+        // the session workspace, without network or the app's environment
+        let config = super::sandbox::SandboxConfig {
+            allowed_paths: vec![default_working_dir()],
+            ..Default::default()
+        };
+        let token = super::core::session_cancel_token();
+        let run = super::sandbox::run_sandboxed(cmd, working_dir, config);
+        let (output, code) = tokio::select! {
+            _ = token.cancelled() => return Err("Command cancelled by user".to_string()),
+            r = tokio::time::timeout(std::time::Duration::from_secs(timeout), run) => r
+                .map_err(|_| format!("Command timed out after {}s", timeout))??,
+        };
+        return Ok((code, output));
+    }
+
+    let mut command = if cfg!(target_os = "macos") {
+        let mut c = tokio::process::Command::new("zsh");
+        c.arg("-l");
+        c
+    } else {
+        tokio::process::Command::new("sh")
+    };
+    command
+        .arg("-c")
+        .arg(cmd)
+        .current_dir(&working_dir)
+        .with_managed_env()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // New process group so cancellation can kill the shell and its children together
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let child = command.spawn().map_err(|e| format!("Command failed: {}", e))?;
+    let mut group = ProcessGroupGuard { pgid: child.id() };
+    let token = super::core::session_cancel_token();
+
+    let output = tokio::select! {
+        _ = token.cancelled() => return Err("Command cancelled by user".to_string()),
+        r = tokio::time::timeout(std::time::Duration::from_secs(timeout), child.wait_with_output()) => r
+            .map_err(|_| format!("Command timed out after {}s", timeout))?
+            .map_err(|e| format!("Command failed: {}", e))?,
+    };
+    group.disarm();
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
     let code = output.status.code().unwrap_or(-1);
+    Ok((code, format_shell_output(&output.stdout, &output.stderr)))
+}
+
+/// stdout and stderr of a shell command, each cut to 8000 bytes.
+pub(super) fn format_shell_output(stdout: &[u8], stderr: &[u8]) -> String {
+    let stdout = String::from_utf8_lossy(stdout);
+    let stderr = String::from_utf8_lossy(stderr);
 
     let max = 8000;
     let stdout_trunc = if stdout.len() > max {
@@ -563,11 +1052,7 @@ async fn tool_shell_exec(args: &Value) -> Result<String, String> {
     } else {
         &stderr
     };
-
-    Ok(format!(
-        "Exit code: {}\n--- stdout ---\n{}\n--- stderr ---\n{}",
-        code, stdout_trunc, stderr_trunc
-    ))
+    format!("--- stdout ---\n{}\n--- stderr ---\n{}", stdout_trunc, stderr_trunc)
 }
 
 // ---- File Read ----
@@ -795,6 +1280,112 @@ async fn tool_web_fetch(args: &Value) -> Result<String, String> {
     ))
 }
 
+// ---- HTTP Test ----
+
+/// Default `http_test` timeout and the largest one accepted.
+const HTTP_TEST_DEFAULT_TIMEOUT_SECS: u64 = 10;
+const HTTP_TEST_MAX_TIMEOUT_SECS: u64 = 120;
+/// Characters of the response body included in the result.
+const HTTP_TEST_BODY_PREVIEW_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq)]
+struct HttpTestResult {
+    status: u16,
+    /// Status matched `expected_status` (or was 2xx when none was given)
+    ok: bool,
+    /// Time until the response headers arrived
+    latency_ms: u64,
+    /// Start of the body, pretty-printed if it is JSON
+    body_preview: String,
+    headers: std::collections::HashMap<String, String>,
+}
+
+/// Pretty-print a JSON body and cut it to `HTTP_TEST_BODY_PREVIEW_CHARS`.
+fn http_test_body_preview(body: &str) -> String {
+    let text = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| body.to_string());
+    let preview = crate::utils::truncate::safe_truncate(&text, HTTP_TEST_BODY_PREVIEW_CHARS);
+    if preview.len() < text.len() {
+        format!("{}\n... (truncated, {} total chars)", preview, text.chars().count())
+    } else {
+        text
+    }
+}
+
+async fn http_test(client: &reqwest::Client, args: &Value) -> Result<HttpTestResult, String> {
+    let url = args["url"].as_str().ok_or("Missing 'url'")?;
+    let method = args["method"].as_str().unwrap_or("GET").to_uppercase();
+    let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|_| format!("Invalid method '{}'", method))?;
+    let expected = args["expected_status"].as_u64();
+    let timeout = args["timeout_secs"]
+        .as_u64()
+        .unwrap_or(HTTP_TEST_DEFAULT_TIMEOUT_SECS)
+        .clamp(1, HTTP_TEST_MAX_TIMEOUT_SECS);
+
+    let mut req = client.request(method, url).timeout(std::time::Duration::from_secs(timeout));
+    if let Some(headers) = args["headers"].as_object() {
+        for (k, v) in headers {
+            if let Some(val) = v.as_str() {
+                req = req.header(k.as_str(), val);
+            }
+        }
+    }
+    if let Some(body) = args["body"].as_str().filter(|b| !b.is_empty()) {
+        req = req.body(body.to_string());
+    }
+
+    let start = std::time::Instant::now();
+    let resp = req.send().await.map_err(|e| {
+        if e.is_timeout() {
+            format!("Request timed out after {}s", timeout)
+        } else {
+            format!("Request failed: {}", e)
+        }
+    })?;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    let status = resp.status().as_u16();
+    let headers = resp
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+        .collect();
+    let body = resp.text().await.map_err(|e| format!("Read body: {}", e))?;
+    Ok(HttpTestResult {
+        status,
+        ok: expected.map_or((200..300).contains(&status), |e| e == status as u64),
+        latency_ms,
+        body_preview: http_test_body_preview(&body),
+        headers,
+    })
+}
+
+fn format_http_test(result: &HttpTestResult, expected: Option<u64>) -> String {
+    let expectation = match expected {
+        Some(e) => format!("expected {}", e),
+        None => "expected 2xx".to_string(),
+    };
+    let mut headers: Vec<_> = result.headers.iter().collect();
+    headers.sort();
+    let header_lines: Vec<String> = headers.iter().map(|(k, v)| format!("  {}: {}", k, v)).collect();
+    format!(
+        "status: {} ({})\nok: {}\nlatency_ms: {}\nheaders:\n{}\nbody_preview:\n{}",
+        result.status,
+        expectation,
+        result.ok,
+        result.latency_ms,
+        header_lines.join("\n"),
+        result.body_preview
+    )
+}
+
+async fn tool_http_test(args: &Value) -> Result<String, String> {
+    let result = http_test(&SHARED_HTTP_CLIENT, args).await?;
+    Ok(format_http_test(&result, args["expected_status"].as_u64()))
+}
+
 // ---- Web Search (multi-engine) ----
 async fn tool_web_search(args: &Value) -> Result<String, String> {
     let query = args["query"].as_str().ok_or("Missing 'query'")?;
@@ -1047,9 +1638,16 @@ async fn tool_memory_store(args: &Value) -> Result<String, String> {
         .try_with(|id| id.clone())
         .unwrap_or_else(|_| "default".to_string());
 
-    // Store with session_id as source for strict isolation
-    super::memory::memory_store_entry(key.to_string(), value.to_string(), Some(session_id), None)
-        .await?;
+    // Owned by this session (private) so other sessions don't see it
+    super::memory::memory_store_entry(
+        key.to_string(),
+        value.to_string(),
+        Some(session_id.clone()),
+        None,
+        Some(session_id),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
     Ok(format!("✅ Stored under key '{}'", key))
 }
 
@@ -1060,20 +1658,16 @@ async fn tool_memory_recall(args: &Value) -> Result<String, String> {
         .try_with(|id| id.clone())
         .unwrap_or_else(|_| "default".to_string());
 
-    let results = super::memory::memory_search(query.to_string(), Some(20)).await?;
-
-    // Strict isolation: only return memory from this exact conversation
-    let session_results: Vec<_> = results
-        .into_iter()
-        .filter(|r| r.entry.source == session_id)
-        .take(10)
-        .collect();
+    // Visibility filtering: this session's private entries plus shared/global ones
+    let session_results = super::memory::memory_search(query.to_string(), Some(10), Some(session_id))
+        .await
+        .map_err(|e| e.to_string())?;
 
     if session_results.is_empty() {
         Ok("No matching memories found for this conversation.".to_string())
     } else {
         let mut output = format!(
-            "Found {} memories in this conversation:\n\n",
+            "Found {} memories for this conversation:\n\n",
             session_results.len()
         );
         for r in &session_results {
@@ -1430,7 +2024,408 @@ fn tool_get_current_time() -> String {
     format!("🕐 Current time: {}", time_str)
 }
 
+// ---- Clipboard ----
+
+/// System clipboard handle. Kept alive for the whole process because on
+/// X11/Wayland the clipboard contents are owned by the writing client and
+/// vanish once it is dropped.
+static CLIPBOARD: std::sync::LazyLock<Mutex<Option<arboard::Clipboard>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+fn with_clipboard<T>(
+    f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, String> {
+    let mut guard = CLIPBOARD
+        .lock()
+        .map_err(|e| format!("Clipboard lock: {}", e))?;
+    if guard.is_none() {
+        let clipboard = arboard::Clipboard::new().map_err(|e| {
+            format!(
+                "Clipboard is not available (headless or no display server?): {}",
+                e
+            )
+        })?;
+        *guard = Some(clipboard);
+    }
+    let clipboard = guard.as_mut().ok_or("Clipboard is not available")?;
+    f(clipboard).map_err(|e| format!("Clipboard: {}", e))
+}
+
+async fn clipboard_get_text() -> Result<String, String> {
+    tokio::task::spawn_blocking(|| {
+        with_clipboard(|cb| match cb.get_text() {
+            Err(arboard::Error::ContentNotAvailable) => Ok(String::new()),
+            other => other,
+        })
+    })
+    .await
+    .map_err(|e| format!("Clipboard task: {}", e))?
+}
+
+async fn clipboard_set_text(text: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || with_clipboard(|cb| cb.set_text(text)))
+        .await
+        .map_err(|e| format!("Clipboard task: {}", e))?
+}
+
+async fn tool_clipboard_write(args: &Value) -> Result<String, String> {
+    let text = args["text"].as_str().ok_or("Missing 'text'")?;
+    clipboard_set_text(text.to_string()).await?;
+    Ok(format!("📋 已复制 {} 个字符到剪贴板", text.chars().count()))
+}
+
+/// Read text from the system clipboard.
+#[tauri::command]
+pub async fn clipboard_read() -> Result<String, String> {
+    clipboard_get_text().await
+}
+
+/// Write text to the system clipboard.
+#[tauri::command]
+pub async fn clipboard_write(text: String) -> Result<(), String> {
+    clipboard_set_text(text).await
+}
+
+// ---- OCR ----
+async fn tool_ocr(args: &Value) -> Result<String, String> {
+    let path = expand_path(args["path"].as_str().ok_or("Missing 'path'")?);
+    let include_boxes = args["include_boxes"].as_bool().unwrap_or(false);
+    let result = crate::modules::media_understanding::ocr_image(&path, args["lang"].as_str()).await?;
+
+    if result.text.trim().is_empty() {
+        return Ok(format!("🔍 未在图片中识别到文字 (engine: {})", result.engine));
+    }
+
+    let mut output = format!("🔍 OCR ({}):\n{}", result.engine, result.text);
+    if include_boxes && !result.boxes.is_empty() {
+        output.push_str("\n\n--- boxes (text @ left,top width×height conf) ---");
+        for b in result.boxes.iter().take(200) {
+            output.push_str(&format!(
+                "\n{} @ {},{} {}×{} {:.0}",
+                b.text, b.left, b.top, b.width, b.height, b.confidence
+            ));
+        }
+    }
+    Ok(output)
+}
+
+// ---- Schedule Task ----
+fn tool_schedule_task(args: &Value) -> Result<String, String> {
+    use crate::modules::cron;
+
+    let schedule_text = args["schedule"].as_str().ok_or("Missing 'schedule'")?;
+    let name = args["name"].as_str().ok_or("Missing 'name'")?;
+    let schedule = cron::parse_natural_schedule(schedule_text).ok_or_else(|| {
+        format!(
+            "Could not understand schedule '{}'. Use a phrase like '每天早上8点' / 'every monday at 9am' or a cron expression like '0 8 * * *'.",
+            schedule_text
+        )
+    })?;
+
+    let script = match (args["command"].as_str(), args["message"].as_str()) {
+        (Some(cmd), _) if !cmd.trim().is_empty() => cmd.to_string(),
+        (_, Some(msg)) if !msg.trim().is_empty() => format!("echo '{}'", msg.replace('\'', "'\\''")),
+        _ => return Err("Provide either 'command' or 'message'".into()),
+    };
+
+    let task = cron::create_task(cron::CreateTaskInput {
+        name: name.to_string(),
+        description: Some(schedule_text.to_string()),
+        task_type: "cron".into(),
+        schedule: Some(schedule.clone()),
+        script: Some(script),
+        notify_channel: args["notify_channel"].as_str().map(|s| s.to_string()),
+        group_name: None,
+        depends_on: None,
+        run_after_success: None,
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "⏰ 已创建定时任务「{}」\n- 计划: {} ({})\n- 下次执行: {}\n- ID: {}",
+        task.name,
+        schedule_text,
+        schedule,
+        task.next_run.as_deref().unwrap_or("—"),
+        task.id
+    ))
+}
+
+// ---- MCP Resources ----
+async fn tool_mcp_resource_fetch(args: &Value) -> Result<String, String> {
+    use crate::modules::mcp_client;
+    use crate::utils::truncate::safe_truncate;
+
+    let server = args["server"].as_str().ok_or("Missing 'server'")?;
+    let Some(uri) = args["uri"].as_str().filter(|u| !u.is_empty()) else {
+        let resources = mcp_client::list_resources(server).await?;
+        if resources.is_empty() {
+            return Ok(format!("MCP server '{}' exposes no resources.", server));
+        }
+        let mut output = format!("📚 Resources on '{}' ({}):\n", server, resources.len());
+        for r in &resources {
+            output.push_str(&format!("- {} — {}", r.uri, r.name));
+            if let Some(ref mime) = r.mime_type {
+                output.push_str(&format!(" [{}]", mime));
+            }
+            if let Some(ref desc) = r.description {
+                output.push_str(&format!(": {}", safe_truncate(desc, 120)));
+            }
+            output.push('\n');
+        }
+        return Ok(output);
+    };
+
+    let contents = mcp_client::read_resource(server, uri).await?;
+    let mut output = String::new();
+    for c in &contents {
+        match (&c.text, &c.blob) {
+            (Some(text), _) => output.push_str(text),
+            (None, Some(blob)) => output.push_str(&format!(
+                "[binary resource {} ({}), {} bytes base64]",
+                c.uri,
+                c.mime_type.as_deref().unwrap_or("application/octet-stream"),
+                blob.len()
+            )),
+            (None, None) => {}
+        }
+        output.push('\n');
+    }
+    if output.trim().is_empty() {
+        return Ok(format!("Resource '{}' is empty.", uri));
+    }
+    let max = 15000;
+    let text = safe_truncate(&output, max);
+    if text.len() < output.len() {
+        return Ok(format!("{}\n... (truncated, {} total bytes)", text, output.len()));
+    }
+    Ok(output)
+}
+
+// ---- Usage Query ----
+fn tool_usage_query(args: &Value) -> Result<String, String> {
+    use crate::modules::usage;
+
+    let round_cost = |c: f64| (c * 10000.0).round() / 10000.0;
+    let totals_json = |t: usage::UsageTotals| {
+        json!({
+            "requests": t.total_requests,
+            "prompt_tokens": t.total_prompt_tokens,
+            "completion_tokens": t.total_completion_tokens,
+            "total_tokens": t.total_tokens,
+            "cost_usd": round_cost(t.total_cost_usd),
+        })
+    };
+
+    let result = match args["scope"].as_str().unwrap_or("today") {
+        "today" => json!({
+            "scope": "today",
+            "date_utc": chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            "usage": totals_json(usage::get_today_totals()?),
+        }),
+        "totals" => json!({ "scope": "lifetime", "usage": totals_json(usage::get_lifetime_totals()?) }),
+        "by_model" => {
+            let models: Vec<Value> = usage::get_model_breakdown()?
+                .into_iter()
+                .map(|m| json!({
+                    "model": m.model,
+                    "provider": m.provider,
+                    "requests": m.request_count,
+                    "total_tokens": m.total_tokens,
+                    "cost_usd": round_cost(m.cost_usd),
+                }))
+                .collect();
+            json!({ "scope": "by_model", "models": models })
+        }
+        "daily" => {
+            let days = args["days"].as_i64().unwrap_or(7).clamp(1, 90);
+            let daily: Vec<Value> = usage::get_daily_usage(days)?
+                .into_iter()
+                .map(|d| json!({
+                    "date": d.date,
+                    "requests": d.request_count,
+                    "total_tokens": d.total_tokens,
+                    "cost_usd": round_cost(d.cost_usd),
+                }))
+                .collect();
+            json!({ "scope": "daily", "days": days, "daily": daily })
+        }
+        other => return Err(format!("Unknown scope '{}'. Use today, totals, by_model or daily.", other)),
+    };
+
+    Ok(result.to_string())
+}
+
+// ---- GitHub ----
+async fn tool_github(name: &str, args: &Value) -> Result<String, String> {
+    use crate::modules::github;
+
+    let repo = args["repo"].as_str();
+    let text = |key: &str| args[key].as_str().unwrap_or("").to_string();
+    let result = match name {
+        "github_create_issue" => {
+            let labels: Vec<String> = args["labels"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|l| l.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            let issue = github::create_issue(repo, &text("title"), &text("body"), &labels).await?;
+            json!({ "number": issue.number, "url": issue.html_url, "state": issue.state })
+        }
+        "github_list_issues" => {
+            let state = args["state"].as_str().unwrap_or("open");
+            let limit = args["limit"].as_u64().unwrap_or(20) as u32;
+            let issues: Vec<Value> = github::list_issues(repo, state, limit)
+                .await?
+                .into_iter()
+                .map(|i| json!({
+                    "number": i.number,
+                    "title": i.title,
+                    "state": i.state,
+                    "labels": i.labels,
+                    "author": i.author,
+                    "url": i.html_url,
+                }))
+                .collect();
+            json!({ "count": issues.len(), "issues": issues })
+        }
+        "github_create_pr" => {
+            let pr = github::create_pr(repo, &text("title"), &text("body"), &text("head"), &text("base")).await?;
+            json!({ "number": pr.number, "url": pr.html_url, "head": pr.head, "base": pr.base })
+        }
+        "github_get_pr_diff" => {
+            let number = args["pr_number"].as_u64().ok_or("Missing pr_number")?;
+            let diff = github::get_pr_diff(repo, number).await?;
+            let max = 30000;
+            if diff.chars().count() > max {
+                return Ok(format!(
+                    "{}\n\n[diff truncated at {} chars]",
+                    crate::utils::truncate::safe_truncate(&diff, max),
+                    max
+                ));
+            }
+            return Ok(diff);
+        }
+        other => return Err(format!("Unknown GitHub tool: {}", other)),
+    };
+    Ok(result.to_string())
+}
+
+// ---- kubectl ----
+async fn tool_kubectl_exec(args: &Value) -> Result<String, String> {
+    let policy = crate::modules::load_app_config()?.tool_policy;
+    if !policy.kubectl_enabled {
+        return Err("kubectl_exec is disabled (enable it in tool policy settings)".to_string());
+    }
+
+    let kubectl_args: Vec<String> = match &args["args"] {
+        Value::Array(items) => items.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        Value::String(s) => s.split_whitespace().map(String::from).collect(),
+        _ => return Err("Missing 'args'".to_string()),
+    };
+    let timeout = args["timeout_secs"].as_u64().unwrap_or(30).clamp(1, 120);
+
+    let output = crate::modules::kubeconfig::run_kubectl(
+        &kubectl_args,
+        args["context"].as_str(),
+        args["namespace"].as_str(),
+        policy.kubectl_allow_mutations,
+        timeout,
+    )
+    .await?;
+
+    let max = 8000;
+    if output.chars().count() > max {
+        return Ok(format!(
+            "{}\n\n[output truncated at {} chars]",
+            crate::utils::truncate::safe_truncate(&output, max),
+            max
+        ));
+    }
+    Ok(output)
+}
+
+// ---- Aliyun ----
+fn aliyun_tools_enabled() -> Result<(), String> {
+    if crate::modules::load_app_config()?.tool_policy.aliyun_enabled {
+        Ok(())
+    } else {
+        Err("Aliyun tools are disabled (enable them in tool policy settings)".to_string())
+    }
+}
+
+async fn tool_aliyun_ecs_list(args: &Value) -> Result<String, String> {
+    aliyun_tools_enabled()?;
+    let instances = crate::modules::aliyun::ecs_list_instances(args["region"].as_str()).await?;
+    let items: Vec<Value> = instances
+        .iter()
+        .map(|i| json!({
+            "instance_id": i.instance_id,
+            "name": i.name,
+            "status": i.status,
+            "public_ip": i.public_ip,
+        }))
+        .collect();
+    let region = instances.first().map(|i| i.region_id.as_str()).unwrap_or("");
+    Ok(json!({ "region": region, "count": items.len(), "instances": items }).to_string())
+}
+
+async fn tool_aliyun_oss_upload(args: &Value) -> Result<String, String> {
+    aliyun_tools_enabled()?;
+    let path = args["path"].as_str().ok_or("Missing 'path'")?;
+    let bucket = args["bucket"].as_str().ok_or("Missing 'bucket'")?;
+    let key = args["key"].as_str().ok_or("Missing 'key'")?;
+    let expires = args["expires_secs"].as_u64().unwrap_or(3600);
+
+    let mut full_path = expand_path(path);
+    if std::path::Path::new(&full_path).is_relative() {
+        if let Some(ws) = super::core::SESSION_WORKSPACE.try_with(|ws| ws.clone()).ok().flatten() {
+            full_path = std::path::Path::new(&expand_path(&ws)).join(&full_path).to_string_lossy().to_string();
+        }
+    }
+
+    let url = crate::modules::aliyun::oss_upload_file(&full_path, bucket, key, args["region"].as_str(), expires).await?;
+    Ok(json!({
+        "bucket": bucket,
+        "key": key.trim_start_matches('/'),
+        "expires_secs": expires.clamp(1, crate::modules::aliyun::OSS_MAX_EXPIRES_SECS),
+        "url": url,
+    })
+    .to_string())
+}
+
 // ---- Desktop Screenshot ----
+
+/// Set to 1 to try native Wayland capture (grim, gnome-screenshot) instead of
+/// failing fast on Wayland sessions.
+const FORCE_WAYLAND_CAPTURE_ENV: &str = "HELIX_FORCE_WAYLAND_CAPTURE";
+
+/// Screenshot programs to try on Linux, in order. X11 tools only see a blank
+/// screen under Wayland, and the native tools depend on the compositor, so on
+/// Wayland capture is refused with an explanation unless forced.
+fn linux_capture_commands(
+    wayland: bool,
+    force_native: bool,
+    path: &str,
+) -> Result<Vec<(&'static str, Vec<String>)>, String> {
+    if !wayland {
+        return Ok(vec![
+            ("gnome-screenshot", vec!["-f".to_string(), path.to_string()]),
+            ("scrot", vec![path.to_string()]),
+        ]);
+    }
+    if !force_native {
+        return Err(format!(
+            "Desktop screenshot is not supported on Wayland sessions. For web pages use browser_use with \
+             action=screenshot, or set {}=1 to try native capture (grim, gnome-screenshot).",
+            FORCE_WAYLAND_CAPTURE_ENV
+        ));
+    }
+    Ok(vec![
+        ("grim", vec![path.to_string()]),
+        ("gnome-screenshot", vec!["-f".to_string(), path.to_string()]),
+    ])
+}
+
 async fn tool_desktop_screenshot(args: &Value) -> Result<String, String> {
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
     let screenshot_dir = dirs::home_dir()
@@ -1466,31 +2461,31 @@ async fn tool_desktop_screenshot(args: &Value) -> Result<String, String> {
             return Err(format!("Screenshot failed: {}", stderr));
         }
     } else if cfg!(target_os = "linux") {
-        // Linux: try gnome-screenshot or scrot
-        let output = tokio::process::Command::new("gnome-screenshot")
-            .arg("-f")
-            .arg(&filepath_str)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await;
-
-        match output {
-            Ok(o) if o.status.success() => {}
-            _ => {
-                // Fallback to scrot
-                let output = tokio::process::Command::new("scrot")
-                    .arg(&filepath_str)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .output()
-                    .await
-                    .map_err(|e| format!("scrot failed: {}", e))?;
-                if !output.status.success() {
-                    return Err("Screenshot failed: no screenshot tool available (tried gnome-screenshot, scrot)".to_string());
-                }
+        let commands = linux_capture_commands(
+            crate::is_wayland_session(),
+            crate::env_flag_enabled(FORCE_WAYLAND_CAPTURE_ENV),
+            &filepath_str,
+        )?;
+        let mut captured = false;
+        for (program, program_args) in &commands {
+            let output = tokio::process::Command::new(program)
+                .args(program_args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output()
+                .await;
+            if matches!(output, Ok(o) if o.status.success()) {
+                captured = true;
+                break;
             }
         }
+        if !captured {
+            let tried: Vec<&str> = commands.iter().map(|(program, _)| *program).collect();
+            return Err(format!(
+                "Screenshot failed: no screenshot tool available (tried {})",
+                tried.join(", ")
+            ));
+        }
     } else {
         return Err("Desktop screenshot is not supported on this OS".to_string());
     }
@@ -1544,8 +2539,21 @@ async fn tool_browser_use(args: &Value) -> Result<String, String> {
             Ok(format!("📋 Page Elements:\n{}", tree))
         }
         "screenshot" => {
-            // Take a screenshot via the desktop_screenshot tool as a fallback
-            tool_desktop_screenshot(args).await
+            if !BrowserSession::is_active().await {
+                // No page to capture: take a screenshot via the desktop_screenshot tool as a fallback
+                return tool_desktop_screenshot(args).await;
+            }
+            let dir = dirs::home_dir()
+                .ok_or("Cannot find home directory")?
+                .join(".helix")
+                .join("screenshots");
+            std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create screenshots dir: {}", e))?;
+            let path = dir.join(format!("browser_{}.png", chrono::Local::now().format("%Y%m%d_%H%M%S")));
+            BrowserSession::screenshot(&path).await?;
+            Ok(format!(
+                "📸 Page screenshot saved: {}\n  Use `chat_send_file` with this path to share it with the user.",
+                path.display()
+            ))
         }
         "stop" | "close" | "quit" => {
             // Browser will be closed when the global session is dropped
@@ -1554,3 +2562,180 @@ async fn tool_browser_use(args: &Value) -> Result<String, String> {
         _ => Err(format!("Unknown browser action: '{}'. Valid: launch, goto, click, fill, snapshot, screenshot, stop", action)),
     }
 }
+
+// ---- Browser Render (headless) ----
+async fn tool_browser_render(args: &Value) -> Result<String, String> {
+    use crate::modules::browser_engine::{render_page, RenderOptions};
+
+    let url = args["url"].as_str().ok_or("Missing 'url'")?;
+    let screenshot_path = if args["screenshot"].as_bool().unwrap_or(false) {
+        let dir = std::path::Path::new(&get_sandbox_path()).join("screenshots");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create screenshots dir: {}", e))?;
+        Some(dir.join(format!("render_{}.png", chrono::Local::now().format("%Y%m%d_%H%M%S_%3f"))))
+    } else {
+        None
+    };
+    let opts = RenderOptions {
+        url: url.to_string(),
+        wait_selector: args["wait_selector"].as_str().map(str::trim).filter(|s| !s.is_empty()).map(String::from),
+        screenshot_path,
+        denylist: crate::modules::load_app_config().map(|c| c.tool_policy.browser_denylist).unwrap_or_default(),
+    };
+
+    // Structured error so the agent can tell a timeout from a blocked host
+    let page = render_page(opts).await.map_err(|e| e.to_json().to_string())?;
+
+    let mut out = format!("🌐 {}\nURL: {}\n", page.title, page.final_url);
+    if let Some(path) = &page.screenshot_path {
+        let sent = tool_chat_send_file(&json!({ "path": path })).await?;
+        out.push_str(&format!("Screenshot: {} ({})\n", path, sent));
+    }
+    out.push_str(&format!("\n{}", page.text));
+    if page.truncated {
+        out.push_str("\n\n... (truncated)");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linux_capture_commands() {
+        let programs = |wayland, force| {
+            linux_capture_commands(wayland, force, "/tmp/shot.png")
+                .map(|cmds| cmds.into_iter().map(|(program, _)| program).collect::<Vec<_>>())
+        };
+        assert_eq!(programs(false, false).unwrap(), vec!["gnome-screenshot", "scrot"]);
+        assert_eq!(programs(false, true).unwrap(), vec!["gnome-screenshot", "scrot"]);
+        assert!(programs(true, false).unwrap_err().contains(FORCE_WAYLAND_CAPTURE_ENV));
+        assert_eq!(programs(true, true).unwrap(), vec!["grim", "gnome-screenshot"]);
+    }
+
+    /// True if `pid` exists and is not a zombie.
+    #[cfg(unix)]
+    fn process_alive(pid: &str) -> bool {
+        std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", pid])
+            .output()
+            .map(|o| {
+                let stat = String::from_utf8_lossy(&o.stdout).trim().to_string();
+                !stat.is_empty() && !stat.starts_with('Z')
+            })
+            .unwrap_or(false)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancel_kills_shell_children() {
+        let pid_file = std::env::temp_dir().join(format!("helix-cancel-{}.pid", std::process::id()));
+        let _ = std::fs::remove_file(&pid_file);
+        let args = json!({
+            "command": format!("sleep 30 & echo $! > {}; wait", pid_file.display()),
+            "working_dir": std::env::temp_dir().to_string_lossy(),
+            "timeout_secs": 60,
+        });
+
+        let token = tokio_util::sync::CancellationToken::new();
+        let canceller = token.clone();
+        let pid_path = pid_file.clone();
+        tokio::spawn(async move {
+            // Cancel once the background sleep has started
+            while !pid_path.exists() {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let start = std::time::Instant::now();
+        let result = super::super::core::SESSION_CANCEL
+            .scope(token, tool_shell_exec(&args))
+            .await;
+        assert!(result.unwrap_err().contains("cancelled"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap().trim().to_string();
+        let _ = std::fs::remove_file(&pid_file);
+        for _ in 0..50 {
+            if !process_alive(&pid) {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("child process {} still running after cancellation", pid);
+    }
+
+    /// Serve one HTTP request on a local port: wait `delay_ms`, then answer
+    /// with `status` and `body`. Returns the base URL.
+    async fn mock_server(status: u16, body: &'static str, delay_ms: u64) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nX-Mock: yes\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_test_status_and_latency() {
+        let client = reqwest::Client::new();
+        let url = mock_server(200, r#"{"healthy":true}"#, 150).await;
+        let result = http_test(&client, &json!({ "url": format!("{}/health", url), "expected_status": 200 }))
+            .await
+            .unwrap();
+        assert_eq!(result.status, 200);
+        assert!(result.ok);
+        assert!(result.latency_ms >= 150, "latency {}ms", result.latency_ms);
+        assert_eq!(result.body_preview, "{\n  \"healthy\": true\n}");
+        assert_eq!(result.headers.get("x-mock").map(String::as_str), Some("yes"));
+        let text = format_http_test(&result, Some(200));
+        assert!(text.starts_with("status: 200 (expected 200)\nok: true\n"));
+
+        let url = mock_server(503, "down", 0).await;
+        let result = http_test(&client, &json!({ "url": url, "method": "post", "body": "{}" })).await.unwrap();
+        assert_eq!((result.status, result.ok), (503, false));
+        assert_eq!(result.body_preview, "down");
+
+        let url = mock_server(201, "", 0).await;
+        let result = http_test(&client, &json!({ "url": url, "expected_status": 200 })).await.unwrap();
+        assert!(!result.ok);
+    }
+
+    #[tokio::test]
+    async fn test_http_test_timeout() {
+        let client = reqwest::Client::new();
+        let url = mock_server(200, "late", 3_000).await;
+        let err = http_test(&client, &json!({ "url": url, "timeout_secs": 1 })).await.unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(http_test(&client, &json!({ "url": url, "method": "BAD METHOD" })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_skips_tool_body() {
+        let ran = std::sync::atomic::AtomicBool::new(false);
+        let body = async {
+            ran.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok("written".to_string())
+        };
+        let args = json!({ "path": "/tmp/x", "content": "y" });
+        let output = super::super::core::SESSION_DRY_RUN
+            .scope(true, run_cancellable("file_write", &args, body))
+            .await
+            .unwrap();
+        assert!(output.starts_with("[dry run] `file_write`"), "{}", output);
+        assert!(!ran.load(std::sync::atomic::Ordering::SeqCst));
+    }
+}
@@ -2,13 +2,19 @@
 //!
 //! Reads config from helix_config.json and provides chat completions.
 
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::{error, info};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
-use crate::models::config::AiModelConfig;
+use crate::error::{codes, HelixError, HelixResult};
+use crate::models::config::{AiModelConfig, AiRetryConfig, KeyRotationStrategy};
 use crate::modules::config::{load_app_config, save_app_config};
+use crate::utils::truncate::safe_truncate;
 
 /// Sanitize a base URL (currently passthrough).
 pub fn sanitize_base_url(url: &str) -> String {
@@ -103,6 +109,202 @@ pub async fn team_chat_fetch(
     serde_json::from_str(&text).map_err(|e| format!("Parse error: {}\nBody: {}", e, text))
 }
 
+// ============================================================================
+// API Key Rotation
+// ============================================================================
+
+/// How long a key is sidelined after a 429 without a Retry-After header.
+const KEY_COOLDOWN_SECS: u64 = 60;
+
+/// Per-key usage/cooldown/error state, keyed by the full key (never logged).
+#[derive(Default)]
+struct KeyRotation {
+    last_used: HashMap<String, Instant>,
+    cooldown_until: HashMap<String, Instant>,
+    errors: HashMap<String, u32>,
+}
+
+impl KeyRotation {
+    /// Key that is not cooling down: the least recently used one, or with
+    /// `Failover` the first listed one. If every key is sidelined, the one
+    /// whose cooldown ends first.
+    fn pick(&mut self, keys: &[String], now: Instant, strategy: KeyRotationStrategy) -> Option<String> {
+        let mut available = keys
+            .iter()
+            .filter(|k| !self.cooldown_until.get(*k).is_some_and(|until| *until > now));
+        let available = match strategy {
+            KeyRotationStrategy::RoundRobin => available.min_by_key(|k| self.last_used.get(*k).copied()),
+            KeyRotationStrategy::Failover => available.next(),
+        };
+        let key = available
+            .or_else(|| keys.iter().min_by_key(|k| self.cooldown_until.get(*k).copied()))?
+            .clone();
+        self.last_used.insert(key.clone(), now);
+        Some(key)
+    }
+
+    fn has_available(&self, keys: &[String], now: Instant) -> bool {
+        keys.iter()
+            .any(|k| !self.cooldown_until.get(k).is_some_and(|until| *until > now))
+    }
+
+    fn sideline(&mut self, key: &str, until: Instant) {
+        self.cooldown_until.insert(key.to_string(), until);
+    }
+
+    fn cooldown_remaining(&self, key: &str, now: Instant) -> u64 {
+        self.cooldown_until
+            .get(key)
+            .map_or(0, |until| until.saturating_duration_since(now).as_secs())
+    }
+
+    fn record_error(&mut self, key: &str) {
+        *self.errors.entry(key.to_string()).or_default() += 1;
+    }
+
+    fn error_count(&self, key: &str) -> u32 {
+        self.errors.get(key).copied().unwrap_or(0)
+    }
+}
+
+static KEY_ROTATION: Lazy<Mutex<KeyRotation>> = Lazy::new(|| Mutex::new(KeyRotation::default()));
+
+/// Models whose last request was rate limited on every key, until the cooldown ends.
+static MODEL_RATE_LIMITS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Seconds until `model` is usable again after running out of quota on every key (0 = usable).
+pub(crate) fn model_rate_limit_remaining(model: &str) -> u64 {
+    MODEL_RATE_LIMITS
+        .lock()
+        .get(model)
+        .map_or(0, |until| until.saturating_duration_since(Instant::now()).as_secs())
+}
+
+/// Pick a key from `keys` with the shared rotation state.
+pub(crate) fn pick_key(keys: &[String], strategy: KeyRotationStrategy) -> Option<String> {
+    KEY_ROTATION.lock().pick(keys, Instant::now(), strategy)
+}
+
+/// Rotation state of one key, masked for the UI.
+pub(crate) fn key_status(key: &str) -> Value {
+    let rotation = KEY_ROTATION.lock();
+    let remaining = rotation.cooldown_remaining(key, Instant::now());
+    json!({
+        "key": mask_key(key),
+        "cooling_down": remaining > 0,
+        "cooldown_remaining_secs": remaining,
+        "error_count": rotation.error_count(key),
+    })
+}
+
+/// Mask an API key for logs/UI: first 4 and last 4 chars only.
+pub fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 12 {
+        return "****".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+// ============================================================================
+// Retry
+// ============================================================================
+
+/// Longest `Retry-After` we are willing to wait inline.
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+/// Whether a response status is worth retrying against the same provider.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parse a `Retry-After` header given in seconds.
+fn retry_after_secs(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+}
+
+/// Backoff before retry number `retry` (1-based): `Retry-After` when given
+/// (capped), otherwise exponential from `base_delay_ms` up to `max_delay_ms`
+/// with equal jitter (`jitter` in [0, 1) picks a point in the upper half).
+fn retry_delay(policy: &AiRetryConfig, retry: u32, retry_after: Option<u64>, jitter: f64) -> Duration {
+    if let Some(secs) = retry_after {
+        return Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS));
+    }
+    let exp = policy
+        .base_delay_ms
+        .saturating_mul(1u64 << retry.saturating_sub(1).min(20))
+        .min(policy.max_delay_ms);
+    Duration::from_millis(exp / 2 + (exp as f64 / 2.0 * jitter.clamp(0.0, 1.0)) as u64)
+}
+
+/// Whether an error from the agent's model call is worth retrying: a
+/// `ProviderError` for a connection error or timeout, or a 429/5xx status.
+/// Streams that already produced text fail with `BadResponse` and are not
+/// retried, so no text is emitted twice.
+pub(crate) fn is_transient_model_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<ProviderError>() {
+        Some(ProviderError::Network { source }) => source.is_connect() || source.is_timeout(),
+        Some(ProviderError::Http { status, .. }) => is_retryable_status(*status),
+        _ => false,
+    }
+}
+
+/// The SDK's model calls fail with a bare `reqwest::Error` or with the text
+/// `OpenAI API error: <status> - <body>`; turn both into `ProviderError` so
+/// callers can match on it. Other errors pass through unchanged.
+pub(crate) fn provider_error_from_sdk(err: anyhow::Error) -> anyhow::Error {
+    let err = match err.downcast::<reqwest::Error>() {
+        Ok(source) => return ProviderError::Network { source }.into(),
+        Err(err) => err,
+    };
+    let http = err.to_string().strip_prefix("OpenAI API error: ").and_then(|rest| {
+        let (status, body) = rest.split_once(" - ").unwrap_or((rest, ""));
+        let code = status.split_whitespace().next()?.parse::<u16>().ok()?;
+        let status = reqwest::StatusCode::from_u16(code).ok()?;
+        Some(ProviderError::Http { status, body: body.to_string() })
+    });
+    http.map(anyhow::Error::from).unwrap_or(err)
+}
+
+/// Backoff before agent model retry number `retry` (1-based). SDK errors
+/// carry no `Retry-After`, so this is always the jittered exponential delay.
+pub(crate) fn model_retry_delay(policy: &AiRetryConfig, retry: u32) -> Duration {
+    retry_delay(policy, retry, None, jitter())
+}
+
+/// Random value in [0, 1) for backoff jitter.
+fn jitter() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The primary `api_key` plus the keys of the named provider (and any legacy
+/// extra keys), deduplicated, with the provider's rotation strategy.
+fn key_pool(config: &AiModelConfig) -> (Vec<String>, KeyRotationStrategy) {
+    let app = load_app_config().ok();
+    let named = app.as_ref().and_then(|c| super::providers::find_provider(c, &config.provider));
+    let strategy = named.map(|p| p.rotation).unwrap_or_default();
+    let extra = named
+        .map(|p| p.keys.clone())
+        .into_iter()
+        .flatten()
+        .chain(app.as_ref().and_then(|c| c.provider_keys.get(&config.provider).cloned()).unwrap_or_default());
+    let mut keys: Vec<String> = Vec::new();
+    for key in std::iter::once(config.api_key.clone()).chain(extra) {
+        let key = key.trim().to_string();
+        if !key.is_empty() && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    (keys, strategy)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiChatResponse {
     pub content: String,
@@ -117,6 +319,53 @@ pub struct AiUsage {
     pub total_tokens: u32,
 }
 
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    #[error("读取配置失败: {0}")]
+    Config(String),
+    #[error("API Key 未设置，请在设置中配置")]
+    NotConfigured,
+    #[error("Invalid API key {0}")]
+    InvalidKey(String),
+    #[error("AI API 请求失败: {source}")]
+    Network { source: reqwest::Error },
+    #[error("AI API 返回错误 ({status}): {body}")]
+    Http { status: reqwest::StatusCode, body: String },
+    #[error("解析 AI 响应失败: {0}")]
+    BadResponse(String),
+}
+
+impl From<ProviderError> for HelixError {
+    fn from(err: ProviderError) -> Self {
+        let message = err.to_string();
+        match err {
+            ProviderError::Config(_) => HelixError::new(codes::CONFIG_LOAD_FAILED, message),
+            ProviderError::NotConfigured => HelixError::new(codes::PROVIDER_NOT_CONFIGURED, message),
+            ProviderError::InvalidKey(_) => HelixError::new(codes::PROVIDER_AUTH_FAILED, message),
+            ProviderError::Network { source } if source.is_timeout() => {
+                HelixError::new(codes::NETWORK_TIMEOUT, message).retriable()
+            }
+            ProviderError::Network { .. } => HelixError::new(codes::NETWORK_UNREACHABLE, message).retriable(),
+            ProviderError::Http { status, body } => {
+                let code = match status.as_u16() {
+                    401 | 403 => codes::PROVIDER_AUTH_FAILED,
+                    429 => codes::PROVIDER_RATE_LIMITED,
+                    500..=599 => codes::PROVIDER_UNAVAILABLE,
+                    _ => codes::PROVIDER_BAD_REQUEST,
+                };
+                let err = HelixError::new(code, message)
+                    .with_detail(json!({ "status": status.as_u16(), "body": body }));
+                if is_retryable_status(status) { err.retriable() } else { err }
+            }
+            ProviderError::BadResponse(_) => HelixError::new(codes::PROVIDER_BAD_RESPONSE, message),
+        }
+    }
+}
+
 // ============================================================================
 // Core AI call
 // ============================================================================
@@ -125,19 +374,12 @@ pub struct AiUsage {
 pub async fn chat_complete(
     config: &AiModelConfig,
     messages: Vec<AiMessage>,
-) -> Result<AiChatResponse, String> {
-    if config.api_key.is_empty() {
-        return Err("API Key 未设置，请在设置中配置".to_string());
+) -> Result<AiChatResponse, ProviderError> {
+    let (keys, strategy) = key_pool(config);
+    if keys.is_empty() {
+        return Err(ProviderError::NotConfigured);
     }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(&format!("Bearer {}", config.api_key))
-            .map_err(|e| format!("Invalid API key: {}", e))?,
-    );
-
     let body = json!({
         "model": config.model,
         "messages": messages,
@@ -162,13 +404,83 @@ pub async fn chat_complete(
         .build()
         .unwrap_or_else(|_| reqwest::Client::new());
 
-    let resp = client
-        .post(&url)
-        .headers(headers)
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| format!("AI API 请求失败: {}", e))?;
+    // A 429 sidelines the key and moves straight on to another available key.
+    // 429s with every key sidelined, 5xx responses and connection errors are
+    // retried against the same provider with jittered backoff.
+    let policy = load_app_config().map(|c| c.ai_retry).unwrap_or_default();
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt: u32 = 0;
+    let mut rotations = 0;
+    let resp = loop {
+        let key = KEY_ROTATION
+            .lock()
+            .pick(&keys, Instant::now(), strategy)
+            .ok_or(ProviderError::NotConfigured)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", key))
+                .map_err(|e| ProviderError::InvalidKey(format!("{}: {}", mask_key(&key), e)))?,
+        );
+
+        let sent = client.post(&url).headers(headers).json(&body).send().await;
+        let (reason, retry_after) = match sent {
+            Ok(resp) if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = retry_after_secs(resp.headers());
+                let cooldown = retry_after.unwrap_or(KEY_COOLDOWN_SECS);
+                let mut rotation = KEY_ROTATION.lock();
+                rotation.sideline(&key, Instant::now() + Duration::from_secs(cooldown));
+                rotation.record_error(&key);
+                warn!(
+                    "AI API rate limited on key {} — cooling down {}s",
+                    mask_key(&key),
+                    cooldown
+                );
+                if rotations < keys.len() && rotation.has_available(&keys, Instant::now()) {
+                    rotations += 1;
+                    continue;
+                }
+                MODEL_RATE_LIMITS
+                    .lock()
+                    .insert(config.model.clone(), Instant::now() + Duration::from_secs(cooldown));
+                if attempt + 1 >= max_attempts {
+                    break resp;
+                }
+                ("HTTP 429".to_string(), retry_after)
+            }
+            Ok(resp) if is_retryable_status(resp.status()) => {
+                KEY_ROTATION.lock().record_error(&key);
+                if attempt + 1 >= max_attempts {
+                    break resp;
+                }
+                (format!("HTTP {}", resp.status()), retry_after_secs(resp.headers()))
+            }
+            Ok(resp) => {
+                if matches!(resp.status().as_u16(), 401 | 403) {
+                    KEY_ROTATION.lock().record_error(&key);
+                }
+                break resp;
+            }
+            Err(e) if (e.is_connect() || e.is_timeout()) && attempt + 1 < max_attempts => {
+                (format!("connection error: {}", e), None)
+            }
+            Err(e) => return Err(ProviderError::Network { source: e }),
+        };
+
+        attempt += 1;
+        let delay = retry_delay(&policy, attempt, retry_after, jitter());
+        debug!(
+            "AI request retry {}/{} for provider={} in {}ms ({})",
+            attempt,
+            max_attempts - 1,
+            config.provider,
+            delay.as_millis(),
+            reason
+        );
+        tokio::time::sleep(delay).await;
+    };
 
     let status = resp.status();
     if !status.is_success() {
@@ -176,19 +488,18 @@ pub async fn chat_complete(
         error!(
             "AI API error: status={}, body={}",
             status,
-            &err_body[..err_body.len().min(500)]
+            crate::utils::truncate::safe_truncate(&err_body, 500)
         );
-        return Err(format!(
-            "AI API 返回错误 ({}): {}",
+        return Err(ProviderError::Http {
             status,
-            &err_body[..err_body.len().min(200)]
-        ));
+            body: crate::utils::truncate::safe_truncate(&err_body, 200).to_string(),
+        });
     }
 
     let data: Value = resp
         .json()
         .await
-        .map_err(|e| format!("解析 AI 响应失败: {}", e))?;
+        .map_err(|e| ProviderError::BadResponse(e.to_string()))?;
 
     let content = data["choices"][0]["message"]["content"]
         .as_str()
@@ -258,7 +569,7 @@ pub async fn process_wechat_message(content: &str) -> Result<String, String> {
         },
     ];
 
-    let resp = chat_complete(ai, messages).await?;
+    let resp = chat_complete(ai, messages).await.map_err(|e| e.to_string())?;
     Ok(resp.content)
 }
 
@@ -266,12 +577,23 @@ pub async fn process_wechat_message(content: &str) -> Result<String, String> {
 // Tauri Commands
 // ============================================================================
 
-/// Send a message to the AI and get a reply (manual test)
+/// Send a message to the AI and get a reply (manual test).
+/// With `template_id`, the user message is the rendered prompt template
+/// (`content` fills its `{{input}}` variable, `variables` the rest).
 #[tauri::command]
-pub async fn ai_chat_send(content: String) -> Result<Value, String> {
-    let config = load_app_config().map_err(|e| format!("读取配置失败: {}", e))?;
+pub async fn ai_chat_send(
+    content: String,
+    template_id: Option<String>,
+    variables: Option<HashMap<String, String>>,
+) -> HelixResult<Value> {
+    let config = load_app_config().map_err(ProviderError::Config)?;
     let ai = &config.ai_config;
 
+    let content = match template_id.as_deref() {
+        Some(id) => crate::modules::prompt_templates::render_for_chat(id, &content, variables.unwrap_or_default())?,
+        None => content,
+    };
+
     let messages = vec![
         AiMessage {
             role: "system".to_string(),
@@ -301,12 +623,19 @@ pub async fn ai_chat_send(content: String) -> Result<Value, String> {
 pub async fn ai_get_config() -> Result<Value, String> {
     let config = load_app_config().map_err(|e| format!("读取配置失败: {}", e))?;
     let ai = &config.ai_config;
+    let extra_keys: Vec<String> = key_pool(ai)
+        .0
+        .iter()
+        .filter(|k| **k != ai.api_key)
+        .map(|k| mask_key(k))
+        .collect();
 
     Ok(json!({
         "provider": ai.provider,
         "base_url": ai.base_url,
         "api_key": if ai.api_key.is_empty() { "".to_string() } else { format!("{}****", &ai.api_key[..ai.api_key.len().min(8)]) },
         "api_key_set": !ai.api_key.is_empty(),
+        "extra_api_keys": extra_keys,
         "model": ai.model,
         "max_tokens": ai.max_tokens,
         "system_prompt": ai.system_prompt,
@@ -324,11 +653,16 @@ pub async fn ai_set_config(
     max_tokens: Option<u32>,
     system_prompt: Option<String>,
     auto_reply: Option<bool>,
+    api_keys: Option<Vec<String>>,
 ) -> Result<Value, String> {
     let mut config = load_app_config().map_err(|e| format!("读取配置失败: {}", e))?;
 
     if let Some(v) = provider {
-        config.ai_config.provider = v;
+        if v != config.ai_config.provider {
+            config.ai_config.provider = v;
+            // Switching to a named provider picks up its URL, key and model
+            super::providers::sync_ai_config(&mut config);
+        }
     }
     if let Some(v) = base_url {
         config.ai_config.base_url = v;
@@ -348,6 +682,8 @@ pub async fn ai_set_config(
     if let Some(v) = auto_reply {
         config.ai_config.auto_reply = v;
     }
+    // Kept as a shim over the named providers: the settings land in the default one
+    super::providers::upsert_default_provider(&mut config, api_keys);
 
     save_app_config(&config).map_err(|e| format!("保存配置失败: {}", e))?;
 
@@ -359,29 +695,196 @@ pub async fn ai_set_config(
     Ok(json!({ "ok": true }))
 }
 
-/// Test AI connection
+/// Rotation state of the current provider's keys (masked).
 #[tauri::command]
-pub async fn ai_test_connection() -> Result<Value, String> {
+pub async fn ai_key_pool_status() -> Result<Value, String> {
     let config = load_app_config().map_err(|e| format!("读取配置失败: {}", e))?;
+    let (keys, strategy) = key_pool(&config.ai_config);
+    let items: Vec<Value> = keys.iter().map(|k| key_status(k)).collect();
+
+    Ok(json!({ "provider": config.ai_config.provider, "rotation": strategy, "keys": items }))
+}
+
+// ============================================================================
+// Connection test
+// ============================================================================
+
+/// Timeout of each `ai_test_connection` probe; probes run concurrently.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(7);
+/// Longest error body excerpt kept per probe.
+const PROBE_ERROR_EXCERPT: usize = 200;
+
+/// Outcome of one request made by `ai_test_connection`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub ok: bool,
+    /// HTTP status, `None` when no response arrived
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    /// Start of the error body, or the transport error
+    pub error: Option<String>,
+}
+
+impl ProbeResult {
+    fn reachable(&self) -> bool {
+        self.status.is_some()
+    }
+
+    fn auth_rejected(&self) -> bool {
+        matches!(self.status, Some(401 | 403))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionReport {
+    /// At least one probe got an HTTP response
+    pub reachable: bool,
+    /// Reachable and no probe was rejected with 401/403
+    pub auth_ok: bool,
+    pub chat_ok: bool,
+    pub embeddings_ok: bool,
+    pub models_ok: bool,
+    /// Provider guessed from the configured model name
+    pub provider: String,
+    pub model: String,
+    pub models: Vec<String>,
+    pub probes: HashMap<String, ProbeResult>,
+}
+
+impl ConnectionReport {
+    fn from_probes(
+        provider: String,
+        model: String,
+        models_probe: ProbeResult,
+        models: Vec<String>,
+        chat: ProbeResult,
+        embeddings: ProbeResult,
+    ) -> Self {
+        let all = [&models_probe, &chat, &embeddings];
+        let reachable = all.iter().any(|p| p.reachable());
+        let auth_ok = reachable && !all.iter().any(|p| p.auth_rejected());
+        ConnectionReport {
+            reachable,
+            auth_ok,
+            chat_ok: chat.ok,
+            embeddings_ok: embeddings.ok,
+            models_ok: models_probe.ok,
+            provider,
+            model,
+            models,
+            probes: HashMap::from([
+                ("models".to_string(), models_probe),
+                ("chat".to_string(), chat),
+                ("embeddings".to_string(), embeddings),
+            ]),
+        }
+    }
+}
+
+/// Send one probe request, returning its outcome and the JSON body on success.
+async fn run_probe(request: reqwest::RequestBuilder) -> (ProbeResult, Option<Value>) {
+    let started = Instant::now();
+    let sent = request.timeout(PROBE_TIMEOUT).send().await;
+    let elapsed = |started: Instant| started.elapsed().as_millis() as u64;
+    match sent {
+        Ok(resp) if resp.status().is_success() => {
+            let status = resp.status().as_u16();
+            let body = resp.json::<Value>().await;
+            let latency_ms = elapsed(started);
+            match body {
+                Ok(data) => (ProbeResult { ok: true, status: Some(status), latency_ms, error: None }, Some(data)),
+                Err(e) => (
+                    ProbeResult {
+                        ok: false,
+                        status: Some(status),
+                        latency_ms,
+                        error: Some(format!("invalid JSON response: {}", e)),
+                    },
+                    None,
+                ),
+            }
+        }
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            let excerpt = safe_truncate(body.trim(), PROBE_ERROR_EXCERPT).to_string();
+            let result = ProbeResult {
+                ok: false,
+                status: Some(status),
+                latency_ms: elapsed(started),
+                error: Some(if excerpt.is_empty() { format!("HTTP {}", status) } else { excerpt }),
+            };
+            (result, None)
+        }
+        Err(e) => {
+            let error = if e.is_timeout() { "request timed out".to_string() } else { e.to_string() };
+            (ProbeResult { ok: false, status: None, latency_ms: elapsed(started), error: Some(error) }, None)
+        }
+    }
+}
+
+/// Model ids from an OpenAI-style (`data[].id`) or Ollama-style (`models[].name`) listing.
+fn parse_model_list(data: &Value) -> Vec<String> {
+    let ids = data["data"].as_array().into_iter().flatten().filter_map(|m| m["id"].as_str());
+    let names = data["models"].as_array().into_iter().flatten().filter_map(|m| m["name"].as_str());
+    ids.chain(names).map(str::to_string).collect()
+}
+
+/// Test AI connection: list models, a 1-token chat completion and an
+/// embeddings call, probed concurrently so a broken capability shows up on its own.
+#[tauri::command]
+pub async fn ai_test_connection() -> HelixResult<ConnectionReport> {
+    let config = load_app_config().map_err(ProviderError::Config)?;
     let ai = &config.ai_config;
 
     if ai.api_key.is_empty() {
-        return Err("请先设置 API Key".to_string());
+        return Err(HelixError::new(codes::PROVIDER_NOT_CONFIGURED, "请先设置 API Key"));
     }
 
-    let messages = vec![AiMessage {
-        role: "user".to_string(),
-        content: "你好，请简短回复一个字以确认连接正常。".to_string(),
-    }];
-
-    let resp = chat_complete(ai, messages).await?;
+    let base = sanitize_base_url(&ai.base_url);
+    let base = base.trim_end_matches('/');
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", ai.api_key))
+            .map_err(|e| ProviderError::InvalidKey(format!("{}: {}", mask_key(&ai.api_key), e)))?,
+    );
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .default_headers(headers)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
 
-    Ok(json!({
-        "ok": true,
-        "reply": resp.content,
-        "model": resp.model,
-        "usage": resp.usage,
-    }))
+    let models_req = client.get(format!("{}/models", base));
+    let chat_req = client.post(format!("{}/chat/completions", base)).json(&json!({
+        "model": ai.model,
+        "messages": [{ "role": "user", "content": "ping" }],
+        "max_tokens": 1,
+        "stream": false,
+    }));
+    let embeddings_req = client.post(format!("{}/embeddings", base)).json(&json!({
+        "model": "text-embedding-3-small",
+        "input": "ping",
+    }));
+
+    let ((models_probe, models_data), (chat, _), (embeddings, _)) =
+        tokio::join!(run_probe(models_req), run_probe(chat_req), run_probe(embeddings_req));
+    let models = models_data.as_ref().map(parse_model_list).unwrap_or_default();
+
+    let report = ConnectionReport::from_probes(
+        super::providers::detect_provider(&ai.model).to_string(),
+        ai.model.clone(),
+        models_probe,
+        models,
+        chat,
+        embeddings,
+    );
+    info!(
+        "AI connection test: reachable={}, auth_ok={}, models_ok={}, chat_ok={}, embeddings_ok={}",
+        report.reachable, report.auth_ok, report.models_ok, report.chat_ok, report.embeddings_ok
+    );
+    Ok(report)
 }
 
 /// List available models from an OpenAI-compatible provider
@@ -524,3 +1027,160 @@ fn get_builtin_models(base_url: &str) -> Vec<String> {
     .map(|s| s.to_string())
     .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("sk-test-key-{:04}", i)).collect()
+    }
+
+    #[test]
+    fn test_key_rotation_round_robin_and_cooldown() {
+        let keys = keys(3);
+        let mut rotation = KeyRotation::default();
+        let t0 = Instant::now();
+        let rr = KeyRotationStrategy::RoundRobin;
+        let picked: Vec<String> = (0..3)
+            .map(|i| rotation.pick(&keys, t0 + Duration::from_secs(i), rr).unwrap())
+            .collect();
+        assert_eq!(picked, keys);
+
+        // Sidelined key is skipped until its cooldown passes
+        rotation.sideline(&keys[0], t0 + Duration::from_secs(100));
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(10), rr).unwrap(), keys[1]);
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(11), rr).unwrap(), keys[2]);
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(101), rr).unwrap(), keys[0]);
+
+        // All sidelined: earliest cooldown wins
+        rotation.sideline(&keys[0], t0 + Duration::from_secs(300));
+        rotation.sideline(&keys[1], t0 + Duration::from_secs(200));
+        rotation.sideline(&keys[2], t0 + Duration::from_secs(400));
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(150), rr).unwrap(), keys[1]);
+        assert_eq!(rotation.cooldown_remaining(&keys[2], t0 + Duration::from_secs(150)), 250);
+        assert!(rotation.pick(&[], t0, rr).is_none());
+    }
+
+    #[test]
+    fn test_key_rotation_failover_and_errors() {
+        let keys = keys(3);
+        let mut rotation = KeyRotation::default();
+        let t0 = Instant::now();
+        let failover = KeyRotationStrategy::Failover;
+        assert_eq!(rotation.pick(&keys, t0, failover).unwrap(), keys[0]);
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(1), failover).unwrap(), keys[0]);
+
+        // Rate limited primary: fall over to the next listed key, then back
+        rotation.sideline(&keys[0], t0 + Duration::from_secs(60));
+        rotation.record_error(&keys[0]);
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(2), failover).unwrap(), keys[1]);
+        assert_eq!(rotation.pick(&keys, t0 + Duration::from_secs(61), failover).unwrap(), keys[0]);
+
+        rotation.record_error(&keys[0]);
+        assert_eq!(rotation.error_count(&keys[0]), 2);
+        assert_eq!(rotation.error_count(&keys[1]), 0);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = AiRetryConfig { max_attempts: 5, base_delay_ms: 500, max_delay_ms: 3000 };
+        assert_eq!(retry_delay(&policy, 1, None, 0.0), Duration::from_millis(250));
+        assert_eq!(retry_delay(&policy, 1, None, 1.0), Duration::from_millis(500));
+        assert_eq!(retry_delay(&policy, 2, None, 1.0), Duration::from_millis(1000));
+        // Capped at max_delay_ms
+        assert_eq!(retry_delay(&policy, 10, None, 1.0), Duration::from_millis(3000));
+        // Retry-After wins, capped
+        assert_eq!(retry_delay(&policy, 1, Some(7), 0.5), Duration::from_secs(7));
+        assert_eq!(retry_delay(&policy, 1, Some(3600), 0.5), Duration::from_secs(MAX_RETRY_AFTER_SECS));
+        let j = jitter();
+        assert!((0.0..1.0).contains(&j));
+    }
+
+    #[test]
+    fn test_is_transient_model_error() {
+        let http = |code: u16| {
+            let status = reqwest::StatusCode::from_u16(code).unwrap();
+            anyhow::Error::from(ProviderError::Http { status, body: String::new() })
+        };
+        assert!(is_transient_model_error(&http(429)));
+        assert!(is_transient_model_error(&http(502)));
+        assert!(!is_transient_model_error(&http(400)));
+        assert!(!is_transient_model_error(&http(401)));
+        let read = ProviderError::BadResponse("Read SSE body: connection reset".into());
+        assert!(!is_transient_model_error(&anyhow::Error::from(read)));
+        // Plain messages are not classified, even if they look like a status
+        assert!(!is_transient_model_error(&anyhow::anyhow!("API error (502 Bad Gateway): upstream")));
+        assert!(!is_transient_model_error(&anyhow::anyhow!("cancelled by user")));
+    }
+
+    #[test]
+    fn test_provider_error_from_sdk() {
+        let sdk = |msg: &str| provider_error_from_sdk(anyhow::anyhow!("{}", msg));
+        match sdk("OpenAI API error: 429 Too Many Requests - slow down").downcast::<ProviderError>() {
+            Ok(ProviderError::Http { status, body }) => {
+                assert_eq!((status.as_u16(), body.as_str()), (429, "slow down"))
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(is_transient_model_error(&sdk("OpenAI API error: 503 Service Unavailable - ")));
+        assert!(!is_transient_model_error(&sdk("OpenAI API error: 400 Bad Request - bad tool schema")));
+        let other = sdk("OpenAI response missing choices");
+        assert!(other.downcast_ref::<ProviderError>().is_none());
+        assert_eq!(other.to_string(), "OpenAI response missing choices");
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key("sk-abcdefghijklmnop"), "sk-a…mnop");
+        assert_eq!(mask_key("short"), "****");
+    }
+
+    fn probe(status: Option<u16>) -> ProbeResult {
+        ProbeResult { ok: status == Some(200), status, ..Default::default() }
+    }
+
+    #[test]
+    fn test_connection_report_from_probes() {
+        // /models 404s but chat works: reachable with a good key
+        let report = ConnectionReport::from_probes(
+            "openai".into(),
+            "gpt-4o".into(),
+            probe(Some(404)),
+            vec![],
+            probe(Some(200)),
+            probe(Some(404)),
+        );
+        assert!(report.reachable && report.auth_ok && report.chat_ok);
+        assert!(!report.models_ok && !report.embeddings_ok);
+
+        let report = ConnectionReport::from_probes(
+            "openai".into(),
+            "gpt-4o".into(),
+            probe(Some(401)),
+            vec![],
+            probe(Some(401)),
+            probe(None),
+        );
+        assert!(report.reachable && !report.auth_ok);
+
+        let report = ConnectionReport::from_probes(
+            "openai".into(),
+            "gpt-4o".into(),
+            probe(None),
+            vec![],
+            probe(None),
+            probe(None),
+        );
+        assert!(!report.reachable && !report.auth_ok);
+    }
+
+    #[test]
+    fn test_parse_model_list() {
+        let openai = json!({ "data": [{ "id": "gpt-4o" }, { "id": "gpt-4o-mini" }] });
+        assert_eq!(parse_model_list(&openai), vec!["gpt-4o", "gpt-4o-mini"]);
+        let ollama = json!({ "models": [{ "name": "llama3:8b" }] });
+        assert_eq!(parse_model_list(&ollama), vec!["llama3:8b"]);
+        assert!(parse_model_list(&json!({})).is_empty());
+    }
+}
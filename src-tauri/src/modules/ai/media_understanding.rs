@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::Path;
 
+use crate::models::config::AiModelConfig;
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -47,6 +49,8 @@ const TEXT_MIMES: &[&str] = &[
     "application/x-python",
 ];
 
+const DOCX_MIME: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
 /// Detect MIME type from file extension.
 pub fn detect_mime(path: &str) -> String {
     let ext = Path::new(path)
@@ -89,6 +93,7 @@ pub fn detect_mime(path: &str) -> String {
         "css" => "text/css".into(),
         "sql" => "application/sql".into(),
         "pdf" => "application/pdf".into(),
+        "docx" => DOCX_MIME.into(),
         _ => "application/octet-stream".into(),
     }
 }
@@ -223,6 +228,100 @@ pub fn extract_file_content(path: &str, max_chars: usize) -> MediaResult {
     }
 }
 
+// ============================================================================
+// Document Text Extraction (pdftotext, docx, vision for images)
+// ============================================================================
+
+/// Extract the text layer of a PDF with poppler's `pdftotext`.
+pub async fn extract_pdf_text(path: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new("pdftotext")
+        .arg("-layout")
+        .arg("-enc")
+        .arg("UTF-8")
+        .arg(path)
+        .arg("-")
+        .output()
+        .await
+        .map_err(|e| format!("pdftotext: {} (install poppler)", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pdftotext failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Extract the body text of a .docx by reading `word/document.xml` with `unzip`.
+pub async fn extract_docx_text(path: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new("unzip")
+        .arg("-p")
+        .arg(path)
+        .arg("word/document.xml")
+        .output()
+        .await
+        .map_err(|e| format!("unzip: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "unzip failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(docx_xml_to_text(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Flatten WordprocessingML to text: paragraphs and breaks become newlines,
+/// tabs stay tabs, all other markup is dropped.
+fn docx_xml_to_text(xml: &str) -> String {
+    let xml = xml
+        .replace("</w:p>", "\n")
+        .replace("<w:br/>", "\n")
+        .replace("<w:tab/>", "\t");
+    let tag = regex::Regex::new(r"<[^>]*>").unwrap();
+    let text = tag
+        .replace_all(&xml, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Extract what an agent should read from a file: text files verbatim, PDF
+/// and DOCX via their text, images via the vision model.
+/// Returns `(kind, text)` where kind is "text", "pdf", "docx" or "image".
+pub async fn extract_text(path: &str) -> Result<(&'static str, String), String> {
+    let mime = detect_mime(path);
+    if mime == "application/pdf" {
+        return Ok(("pdf", extract_pdf_text(path).await?));
+    }
+    if mime == DOCX_MIME {
+        return Ok(("docx", extract_docx_text(path).await?));
+    }
+    if is_image_mime(&mime) {
+        let result = describe_image(path).await;
+        return match result.error {
+            Some(e) => Err(e),
+            None => Ok(("image", result.description)),
+        };
+    }
+
+    let bytes = tokio::fs::read(path).await.map_err(|e| format!("Read error: {}", e))?;
+    if is_text_mime(&mime) {
+        return Ok(("text", String::from_utf8_lossy(&bytes).to_string()));
+    }
+    // Unknown extensions are accepted when the content is clearly text
+    match String::from_utf8(bytes) {
+        Ok(text) if mime == "application/octet-stream" && !text.contains('\0') => Ok(("text", text)),
+        _ => Err(format!("Unsupported file type: {}", mime)),
+    }
+}
+
 // ============================================================================
 // Image Understanding (Vision API)
 // ============================================================================
@@ -359,124 +458,352 @@ pub async fn describe_image(image_path: &str) -> MediaResult {
 // Audio Transcription (Whisper API)
 // ============================================================================
 
-/// Transcribe audio using OpenAI Whisper API.
-pub async fn transcribe_audio(audio_path: &str) -> MediaResult {
-    let path = Path::new(audio_path);
-    if !path.exists() {
-        return MediaResult {
-            media_type: "audio".into(),
-            source: audio_path.into(),
-            description: String::new(),
-            content_length: 0,
-            error: Some("Audio file not found".into()),
-        };
-    }
+/// Largest upload the Whisper API accepts; longer recordings are split.
+const WHISPER_MAX_BYTES: u64 = 25 * 1024 * 1024;
+/// Length of the pieces a long recording is split into.
+const WHISPER_SEGMENT_SECS: u32 = 60;
+/// Segments uploaded at the same time.
+const WHISPER_PARALLEL: usize = 4;
+const WHISPER_EXTENSIONS: &[&str] = &["mp3", "m4a", "wav", "ogg"];
 
-    let audio_data = match std::fs::read(path) {
-        Ok(data) => data,
-        Err(e) => {
-            return MediaResult {
-                media_type: "audio".into(),
-                source: audio_path.into(),
-                description: String::new(),
-                content_length: 0,
-                error: Some(format!("Read audio: {}", e)),
-            };
-        }
-    };
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    /// Spoken language detected by Whisper, or the target of a translation
+    pub language: Option<String>,
+    pub duration_secs: Option<f64>,
+    /// Whitespace-separated words, counting each CJK character as a word
+    pub word_count: usize,
+}
 
-    let config = match crate::modules::config::load_app_config() {
-        Ok(c) => c,
-        Err(e) => {
-            return MediaResult {
-                media_type: "audio".into(),
-                source: audio_path.into(),
-                description: String::new(),
-                content_length: audio_data.len(),
-                error: Some(format!("Config: {}", e)),
-            };
-        }
-    };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WhisperTask {
+    /// Text in the spoken language
+    Transcribe,
+    /// English text
+    Translate,
+}
 
-    let ai = &config.ai_config;
-    if ai.api_key.is_empty() {
-        return MediaResult {
-            media_type: "audio".into(),
-            source: audio_path.into(),
-            description: "[API Key 未配置，无法转录音频]".into(),
-            content_length: audio_data.len(),
-            error: Some("API key not configured".into()),
-        };
+impl WhisperTask {
+    fn endpoint(self) -> &'static str {
+        match self {
+            WhisperTask::Transcribe => "transcriptions",
+            WhisperTask::Translate => "translations",
+        }
     }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}'
+    )
+}
 
-    let url = format!("{}/audio/transcriptions", ai.base_url.trim_end_matches('/'));
-    let filename = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("audio.mp3");
+fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|word| {
+            let cjk = word.chars().filter(|c| is_cjk(*c)).count();
+            let rest = word.split(is_cjk).filter(|part| part.chars().any(char::is_alphanumeric)).count();
+            cjk + rest
+        })
+        .sum()
+}
 
-    let file_part = reqwest::multipart::Part::bytes(audio_data.clone())
-        .file_name(filename.to_string())
-        .mime_str("audio/mpeg")
-        .unwrap_or_else(|_| reqwest::multipart::Part::bytes(audio_data.clone()));
+/// Join the `verbose_json` responses of consecutive segments.
+fn merge_segments(segments: &[Value]) -> TranscriptionResult {
+    let text = segments
+        .iter()
+        .filter_map(|s| s["text"].as_str().map(str::trim))
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let durations: Vec<f64> = segments.iter().filter_map(|s| s["duration"].as_f64()).collect();
+    TranscriptionResult {
+        word_count: count_words(&text),
+        language: segments.iter().find_map(|s| s["language"].as_str().filter(|l| !l.is_empty()).map(String::from)),
+        duration_secs: (!durations.is_empty()).then(|| durations.iter().sum()),
+        text,
+    }
+}
 
+/// Upload one file to `{base_url}/audio/{transcriptions|translations}`.
+async fn whisper_request(ai: &AiModelConfig, path: &Path, task: WhisperTask) -> Result<Value, String> {
+    let audio_data = tokio::fs::read(path).await.map_err(|e| format!("Read audio: {}", e))?;
+    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("audio.mp3").to_string();
+    let file_part = reqwest::multipart::Part::bytes(audio_data)
+        .file_name(filename)
+        .mime_str(&detect_mime(&path.to_string_lossy()))
+        .map_err(|e| format!("MIME: {}", e))?;
     let form = reqwest::multipart::Form::new()
         .part("file", file_part)
         .text("model", "whisper-1")
-        .text("language", "zh");
+        .text("response_format", "verbose_json");
 
     let client = reqwest::Client::builder()
         .danger_accept_invalid_certs(true)
-        .timeout(std::time::Duration::from_secs(60))
+        .timeout(std::time::Duration::from_secs(180))
         .build()
         .unwrap_or_else(|_| reqwest::Client::new());
-
-    match client
+    let url = format!("{}/audio/{}", ai.base_url.trim_end_matches('/'), task.endpoint());
+    let resp = client
         .post(&url)
         .header("Authorization", format!("Bearer {}", ai.api_key))
         .multipart(form)
         .send()
         .await
-    {
-        Ok(resp) => {
-            if !resp.status().is_success() {
-                let err = resp.text().await.unwrap_or_default();
-                return MediaResult {
-                    media_type: "audio".into(),
-                    source: audio_path.into(),
-                    description: "[音频转录失败]".into(),
-                    content_length: audio_data.len(),
-                    error: Some(format!("API error: {}", &err[..err.len().min(200)])),
-                };
+        .map_err(|e| format!("Request: {}", e))?;
+    if !resp.status().is_success() {
+        let err = resp.text().await.unwrap_or_default();
+        return Err(format!("API error: {}", crate::utils::truncate::safe_truncate(&err, 200)));
+    }
+    resp.json::<Value>().await.map_err(|e| format!("Parse: {}", e))
+}
+
+/// Split `path` into `WHISPER_SEGMENT_SECS` pieces inside `out_dir` with ffmpeg.
+async fn split_audio(path: &Path, out_dir: &Path) -> Result<Vec<std::path::PathBuf>, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("Create {}: {}", out_dir.display(), e))?;
+    let output = tokio::process::Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-f", "segment", "-segment_time", &WHISPER_SEGMENT_SECS.to_string(), "-c", "copy"])
+        .arg(out_dir.join(format!("part_%04d.{}", ext)))
+        .output()
+        .await
+        .map_err(|e| format!("ffmpeg is required for audio over 25 MB: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let mut parts: Vec<_> = std::fs::read_dir(out_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    parts.sort();
+    if parts.is_empty() {
+        return Err("ffmpeg produced no segments".into());
+    }
+    Ok(parts)
+}
+
+/// Run a Whisper task on a local recording, splitting it when it is too
+/// large for one upload and sending the segments in parallel.
+async fn run_whisper(audio_path: &str, task: WhisperTask) -> Result<TranscriptionResult, String> {
+    use futures::stream::{self, StreamExt, TryStreamExt};
+
+    let path = Path::new(audio_path);
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if !WHISPER_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(format!("Unsupported audio format '{}' (expected {})", ext, WHISPER_EXTENSIONS.join(", ")));
+    }
+    let size = std::fs::metadata(path).map_err(|_| format!("Audio file not found: {}", audio_path))?.len();
+
+    let config = crate::modules::config::load_app_config()?;
+    let ai = &config.ai_config;
+    if ai.api_key.is_empty() {
+        return Err("API key not configured".into());
+    }
+
+    if size <= WHISPER_MAX_BYTES {
+        return Ok(merge_segments(&[whisper_request(ai, path, task).await?]));
+    }
+
+    let work_dir = std::env::temp_dir().join(format!("helix-whisper-{}", uuid::Uuid::new_v4()));
+    let result = async {
+        let parts = split_audio(path, &work_dir).await?;
+        tracing::info!("[media] Split {} ({} bytes) into {} segments", audio_path, size, parts.len());
+        let requests: Vec<_> = parts.iter().map(|part| whisper_request(ai, part, task)).collect();
+        let responses: Vec<Value> = stream::iter(requests)
+            .buffered(WHISPER_PARALLEL)
+            .try_collect()
+            .await?;
+        Ok(merge_segments(&responses))
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// Transcribe audio (mp3, m4a, wav, ogg) in its spoken language.
+pub async fn transcribe_audio(audio_path: &str) -> Result<TranscriptionResult, String> {
+    run_whisper(audio_path, WhisperTask::Transcribe).await
+}
+
+/// Translate the speech in an audio file into `target_lang`. Whisper's
+/// translations endpoint only produces English; other targets get the
+/// English text translated by the chat model.
+pub async fn translate_audio(audio_path: &str, target_lang: &str) -> Result<TranscriptionResult, String> {
+    let mut result = run_whisper(audio_path, WhisperTask::Translate).await?;
+    let target = target_lang.trim();
+    if target.is_empty() || matches!(target.to_lowercase().as_str(), "en" | "english" | "英语" | "英文") {
+        result.language = Some("en".into());
+        return Ok(result);
+    }
+
+    use crate::modules::ai_chat::{chat_complete, AiMessage};
+    let config = crate::modules::config::load_app_config()?;
+    let messages = vec![
+        AiMessage {
+            role: "system".to_string(),
+            content: format!(
+                "Translate the user's transcript into {}. Reply with the translation only, without notes.",
+                target
+            ),
+        },
+        AiMessage { role: "user".to_string(), content: result.text.clone() },
+    ];
+    let response = chat_complete(&config.ai_config, messages).await.map_err(|e| e.to_string())?;
+    result.text = response.content.trim().to_string();
+    result.word_count = count_words(&result.text);
+    result.language = Some(target.to_string());
+    Ok(result)
+}
+
+// ============================================================================
+// OCR (tesseract, vision model fallback)
+// ============================================================================
+
+/// Prompt used when OCR falls back to the vision model.
+const OCR_VISION_PROMPT: &str = "Extract ALL text visible in this image exactly as written, \
+preserving line breaks and reading order. Do not describe the image, translate, or add \
+commentary. If there is no text, reply with an empty string.";
+
+/// Default tesseract languages (Simplified Chinese + English).
+const OCR_DEFAULT_LANG: &str = "chi_sim+eng";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrBox {
+    pub text: String,
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Word confidence 0-100 as reported by the engine
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrResult {
+    pub source: String,
+    /// "tesseract" or "vision"
+    pub engine: String,
+    pub text: String,
+    /// Word-level bounding boxes (empty when the engine does not provide them)
+    #[serde(default)]
+    pub boxes: Vec<OcrBox>,
+}
+
+/// Check whether a local tesseract binary is available.
+async fn tesseract_available() -> bool {
+    tokio::process::Command::new("tesseract")
+        .arg("--version")
+        .output()
+        .await
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Parse tesseract TSV output into (text, word boxes).
+/// Words are joined with spaces and lines are separated by newlines.
+fn parse_tesseract_tsv(tsv: &str) -> (String, Vec<OcrBox>) {
+    let mut boxes = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut current_line: Option<(i32, i32, i32)> = None;
+
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue; // only word-level rows carry text
+        }
+        let word = cols[11].trim();
+        if word.is_empty() {
+            continue;
+        }
+        let num = |i: usize| cols[i].parse::<i32>().unwrap_or(0);
+        let line_id = (num(2), num(3), num(4)); // block, paragraph, line
+
+        if current_line == Some(line_id) {
+            if let Some(last) = lines.last_mut() {
+                last.push(' ');
+                last.push_str(word);
             }
-            match resp.json::<Value>().await {
-                Ok(data) => {
-                    let text = data["text"].as_str().unwrap_or("").to_string();
-                    MediaResult {
-                        media_type: "audio".into(),
-                        source: audio_path.into(),
-                        description: text,
-                        content_length: audio_data.len(),
-                        error: None,
-                    }
-                }
-                Err(e) => MediaResult {
-                    media_type: "audio".into(),
-                    source: audio_path.into(),
-                    description: String::new(),
-                    content_length: audio_data.len(),
-                    error: Some(format!("Parse: {}", e)),
-                },
+        } else {
+            lines.push(word.to_string());
+            current_line = Some(line_id);
+        }
+
+        boxes.push(OcrBox {
+            text: word.to_string(),
+            left: num(6),
+            top: num(7),
+            width: num(8),
+            height: num(9),
+            confidence: cols[10].parse::<f32>().unwrap_or(-1.0),
+        });
+    }
+
+    (lines.join("\n"), boxes)
+}
+
+async fn run_tesseract(image_path: &str, lang: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new("tesseract")
+        .arg(image_path)
+        .arg("stdout")
+        .arg("-l")
+        .arg(lang)
+        .arg("tsv")
+        .output()
+        .await
+        .map_err(|e| format!("tesseract: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "tesseract failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Extract text from an image. Uses tesseract when installed (with word
+/// bounding boxes), otherwise falls back to the configured vision model.
+pub async fn ocr_image(image_path: &str, lang: Option<&str>) -> Result<OcrResult, String> {
+    if !Path::new(image_path).exists() {
+        return Err(format!("Image file not found: {}", image_path));
+    }
+
+    if tesseract_available().await {
+        let requested = lang.unwrap_or(OCR_DEFAULT_LANG);
+        let tsv = match run_tesseract(image_path, requested).await {
+            Ok(tsv) => Ok(tsv),
+            // Language packs for the default may be missing; English ships with tesseract
+            Err(_) if lang.is_none() => run_tesseract(image_path, "eng").await,
+            Err(e) => Err(e),
+        };
+        match tsv {
+            Ok(tsv) => {
+                let (text, boxes) = parse_tesseract_tsv(&tsv);
+                return Ok(OcrResult {
+                    source: image_path.into(),
+                    engine: "tesseract".into(),
+                    text,
+                    boxes,
+                });
             }
+            Err(e) => tracing::warn!("[ocr] {}, falling back to vision model", e),
         }
-        Err(e) => MediaResult {
-            media_type: "audio".into(),
-            source: audio_path.into(),
-            description: String::new(),
-            content_length: audio_data.len(),
-            error: Some(format!("Request: {}", e)),
-        },
     }
+
+    let text = crate::modules::agent_tools::tool_image_describe(
+        image_path.to_string(),
+        Some(OCR_VISION_PROMPT.to_string()),
+    )
+    .await?;
+    Ok(OcrResult {
+        source: image_path.into(),
+        engine: "vision".into(),
+        text: text.trim().to_string(),
+        boxes: Vec::new(),
+    })
 }
 
 // ============================================================================
@@ -502,6 +829,67 @@ pub async fn media_describe_image(path: String) -> Result<MediaResult, String> {
 }
 
 #[tauri::command]
-pub async fn media_transcribe_audio(path: String) -> Result<MediaResult, String> {
-    Ok(transcribe_audio(&path).await)
+pub async fn media_transcribe_audio(path: String) -> Result<TranscriptionResult, String> {
+    transcribe_audio(&path).await
+}
+
+#[tauri::command]
+pub async fn media_translate_audio(path: String, target_lang: String) -> Result<TranscriptionResult, String> {
+    translate_audio(&path, &target_lang).await
+}
+
+#[tauri::command]
+pub async fn media_ocr_image(path: String, lang: Option<String>) -> Result<OcrResult, String> {
+    ocr_image(&path, lang.as_deref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tesseract_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                   1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t\n\
+                   5\t1\t1\t1\t1\t1\t10\t20\t50\t12\t96.5\tHello\n\
+                   5\t1\t1\t1\t1\t2\t70\t20\t60\t12\t91.0\tWorld\n\
+                   5\t1\t1\t1\t2\t1\t10\t40\t40\t12\t88.0\t你好\n";
+        let (text, boxes) = parse_tesseract_tsv(tsv);
+        assert_eq!(text, "Hello World\n你好");
+        assert_eq!(boxes.len(), 3);
+        assert_eq!(boxes[1].left, 70);
+        assert_eq!(boxes[2].text, "你好");
+    }
+
+    #[test]
+    fn test_docx_xml_to_text() {
+        let xml = concat!(
+            r#"<?xml version="1.0"?><w:document><w:body>"#,
+            r#"<w:p><w:pPr><w:tabs><w:tab w:val="left"/></w:tabs></w:pPr>"#,
+            r#"<w:r><w:t>Q3 &amp; Q4</w:t></w:r><w:r><w:tab/><w:t>报告</w:t></w:r></w:p>"#,
+            r#"<w:p></w:p>"#,
+            r#"<w:p><w:r><w:t xml:space="preserve">a &lt; b</w:t><w:br/><w:t>next</w:t></w:r></w:p>"#,
+            r#"</w:body></w:document>"#,
+        );
+        assert_eq!(docx_xml_to_text(xml), "Q3 & Q4\t报告\na < b\nnext");
+        assert_eq!(detect_mime("Report.DOCX"), DOCX_MIME);
+    }
+
+    #[test]
+    fn test_merge_whisper_segments() {
+        assert_eq!(count_words("Hello, world!  ok"), 3);
+        assert_eq!(count_words("你好世界 GPT-4 测试"), 7);
+        assert_eq!(count_words(" — "), 0);
+
+        let merged = merge_segments(&[
+            json!({"text": " 今天开会。", "language": "chinese", "duration": 60.0}),
+            json!({"text": "", "duration": 0.5}),
+            json!({"text": "Next step is QA ", "language": "english", "duration": 12.25}),
+        ]);
+        assert_eq!(merged.text, "今天开会。 Next step is QA");
+        assert_eq!(merged.language.as_deref(), Some("chinese"));
+        assert_eq!(merged.duration_secs, Some(72.75));
+        assert_eq!(merged.word_count, 8);
+        assert_eq!(merge_segments(&[json!({"text": "hi"})]).duration_secs, None);
+    }
 }
@@ -3,6 +3,7 @@ pub mod context;
 pub mod link_understanding;
 pub mod media_understanding;
 pub mod model_selection;
+pub mod prompt_templates;
 pub mod providers;
 pub mod stream_events;
 pub mod streaming;
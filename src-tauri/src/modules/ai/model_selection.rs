@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::info;
 
+use crate::models::config::{AiModelConfig, AppConfig};
 use crate::modules::config::load_app_config;
 
 // ============================================================================
@@ -155,6 +156,48 @@ pub fn parse_config_aliases(raw: &HashMap<String, String>) -> HashMap<String, Mo
     aliases
 }
 
+// ============================================================================
+// Session Overrides
+// ============================================================================
+
+/// Endpoint, key and model for a per-session model override. A bare model
+/// name runs on the default provider; `provider/model` and the built-in
+/// aliases name a provider, whose entry in `providers` supplies the base URL
+/// and key. An override naming a provider that is not configured is rejected
+/// rather than sent to the default provider's endpoint.
+pub fn override_model_config(raw: &str, config: &AppConfig) -> Result<AiModelConfig, String> {
+    let input = raw.trim();
+    let mut ai = config.ai_config.clone();
+    let is_alias = builtin_aliases().iter().any(|(alias, _, _)| input.eq_ignore_ascii_case(alias));
+    if !input.contains('/') && !is_alias {
+        ai.model = input.to_string();
+        return Ok(ai);
+    }
+
+    // Configured provider names are matched as written, before normalizing
+    let (provider_name, model) = match input.split_once('/') {
+        Some((name, model)) if super::providers::find_provider(config, name.trim()).is_some() => {
+            (name.trim().to_string(), model.to_string())
+        }
+        _ => {
+            let resolved = resolve_model_ref(input, &HashMap::new());
+            (resolved.provider, resolved.model)
+        }
+    };
+    if provider_name == ai.provider {
+        ai.model = model;
+        return Ok(ai);
+    }
+    let provider = super::providers::find_provider(config, &provider_name).ok_or_else(|| {
+        format!("Model '{}' needs provider '{}', which is not configured", input, provider_name)
+    })?;
+    ai.provider = provider.name.clone();
+    ai.base_url = provider.base_url.clone();
+    ai.api_key = provider.keys.first().cloned().unwrap_or_default();
+    ai.model = model;
+    Ok(ai)
+}
+
 // ============================================================================
 // Default Model Resolution
 // ============================================================================
@@ -193,6 +236,165 @@ pub fn build_model_alias_lines() -> String {
     lines.join("\n")
 }
 
+// ============================================================================
+// Model Recommendation
+// ============================================================================
+
+/// Task types understood by `model_recommend`, in capability-score order.
+const TASK_TYPES: [&str; 5] = ["code", "analysis", "creative", "qa", "vision"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecommendContext {
+    /// One of `TASK_TYPES`
+    pub task_type: String,
+    pub estimated_input_tokens: u32,
+    #[serde(default)]
+    pub requires_vision: bool,
+    #[serde(default)]
+    pub requires_tool_use: bool,
+    /// Budget for one request (0 = no limit)
+    #[serde(default)]
+    pub max_cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecommendation {
+    pub model: ModelRef,
+    pub reason: String,
+    /// Estimated USD for one request of this size
+    pub estimated_cost: f64,
+    /// 0–1 fit of the model for the task type
+    pub capabilities_match: f64,
+}
+
+/// What a model can do and how well it does each of `TASK_TYPES`.
+struct ModelCapability {
+    provider: &'static str,
+    model: &'static str,
+    vision: bool,
+    tool_use: bool,
+    context_window: u32,
+    scores: [f64; 5],
+}
+
+/// Built-in capability matrix for the models behind the built-in aliases.
+fn builtin_capabilities() -> Vec<ModelCapability> {
+    let cap = |provider, model, vision, tool_use, context_window, scores| ModelCapability {
+        provider,
+        model,
+        vision,
+        tool_use,
+        context_window,
+        scores,
+    };
+    vec![
+        // Anthropic
+        cap("anthropic", "claude-opus-4-6-20260115", true, true, 200_000, [0.97, 0.97, 0.95, 0.85, 0.88]),
+        cap("anthropic", "claude-sonnet-4-5-20250514", true, true, 200_000, [0.95, 0.90, 0.90, 0.85, 0.85]),
+        cap("anthropic", "claude-haiku-3-5-20250620", false, true, 200_000, [0.70, 0.65, 0.65, 0.80, 0.0]),
+        // OpenAI
+        cap("openai", "gpt-4o", true, true, 128_000, [0.85, 0.85, 0.85, 0.85, 0.90]),
+        cap("openai", "gpt-4o-mini", true, true, 128_000, [0.65, 0.65, 0.70, 0.80, 0.70]),
+        cap("openai", "o1", true, true, 200_000, [0.90, 0.95, 0.60, 0.70, 0.75]),
+        cap("openai", "o3-mini", false, true, 200_000, [0.88, 0.88, 0.50, 0.70, 0.0]),
+        cap("openai", "o4-mini", true, true, 200_000, [0.88, 0.88, 0.55, 0.75, 0.80]),
+        // Google
+        cap("google", "gemini-2.5-pro", true, true, 1_000_000, [0.90, 0.92, 0.85, 0.80, 0.90]),
+        cap("google", "gemini-2.5-flash", true, true, 1_000_000, [0.75, 0.75, 0.75, 0.85, 0.80]),
+        // Ollama local
+        cap("ollama", "llama3.3", false, true, 128_000, [0.60, 0.60, 0.65, 0.70, 0.0]),
+        cap("ollama", "qwen2.5", false, true, 32_000, [0.65, 0.60, 0.60, 0.70, 0.0]),
+        cap("ollama", "deepseek-r1", false, false, 64_000, [0.70, 0.80, 0.55, 0.65, 0.0]),
+    ]
+}
+
+/// Typical completion length per task type, for the cost estimate.
+fn expected_output_tokens(task_index: usize) -> u32 {
+    [2_000, 1_500, 2_000, 500, 500][task_index]
+}
+
+/// Live state of a model from usage tracking.
+#[derive(Debug, Clone, Default)]
+struct ModelStatus {
+    requests_today: i64,
+    /// Seconds until a quota/rate-limit cooldown ends (0 = usable)
+    rate_limited_secs: u64,
+}
+
+fn current_status(models: &[ModelCapability]) -> HashMap<String, ModelStatus> {
+    let mut status: HashMap<String, ModelStatus> = HashMap::new();
+    for usage in super::usage::get_today_model_breakdown().unwrap_or_default() {
+        status.entry(usage.model).or_default().requests_today += usage.request_count;
+    }
+    for cap in models {
+        let remaining = super::chat::model_rate_limit_remaining(cap.model);
+        if remaining > 0 {
+            status.entry(cap.model.to_string()).or_default().rate_limited_secs = remaining;
+        }
+    }
+    status
+}
+
+/// Rank `models` for `context`: unsuitable, over-budget and rate-limited
+/// models are left out; the rest are ordered by task fit, then by cost.
+fn recommend_with(
+    context: &ModelRecommendContext,
+    models: &[ModelCapability],
+    status: &HashMap<String, ModelStatus>,
+    estimate_cost: &dyn Fn(&str, u32, u32) -> f64,
+) -> Result<Vec<ModelRecommendation>, String> {
+    let task = context.task_type.trim().to_lowercase();
+    let task_index = TASK_TYPES
+        .iter()
+        .position(|t| *t == task)
+        .ok_or_else(|| {
+            format!("Unknown task type '{}' (expected one of: {})", context.task_type, TASK_TYPES.join(", "))
+        })?;
+    let needs_vision = context.requires_vision || task == "vision";
+    let output_tokens = expected_output_tokens(task_index);
+
+    let mut ranked = Vec::new();
+    for cap in models {
+        let state = status.get(cap.model).cloned().unwrap_or_default();
+        if (needs_vision && !cap.vision)
+            || (context.requires_tool_use && !cap.tool_use)
+            || context.estimated_input_tokens > cap.context_window
+            || state.rate_limited_secs > 0
+        {
+            continue;
+        }
+        let cost = estimate_cost(cap.model, context.estimated_input_tokens, output_tokens);
+        if context.max_cost_usd > 0.0 && cost > context.max_cost_usd {
+            continue;
+        }
+        let score = cap.scores[task_index];
+        let mut reason = vec![format!("{} fit {:.0}%", task, score * 100.0)];
+        if needs_vision {
+            reason.push("supports images".to_string());
+        }
+        if context.requires_tool_use {
+            reason.push("supports tool use".to_string());
+        }
+        reason.push(format!("{}K context", cap.context_window / 1_000));
+        reason.push(if cost == 0.0 { "free (local)".to_string() } else { format!("~${:.4} per request", cost) });
+        if state.requests_today > 0 {
+            reason.push(format!("{} requests today", state.requests_today));
+        }
+        ranked.push(ModelRecommendation {
+            model: ModelRef { provider: cap.provider.to_string(), model: cap.model.to_string() },
+            reason: reason.join(", "),
+            estimated_cost: cost,
+            capabilities_match: score,
+        });
+    }
+    ranked.sort_by(|a, b| {
+        b.capabilities_match
+            .total_cmp(&a.capabilities_match)
+            .then(a.estimated_cost.total_cmp(&b.estimated_cost))
+    });
+    Ok(ranked)
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -214,3 +416,108 @@ pub async fn model_list_aliases() -> Result<Vec<(String, String, String)>, Strin
 pub async fn model_default() -> Result<ModelRef, String> {
     Ok(resolve_default_model())
 }
+
+/// Models suited to a task, best first, from the capability matrix, today's
+/// usage and rate limits, and the pricing table.
+#[tauri::command]
+pub async fn model_recommend(context: ModelRecommendContext) -> Result<Vec<ModelRecommendation>, String> {
+    let models = builtin_capabilities();
+    let status = current_status(&models);
+    recommend_with(&context, &models, &status, &super::usage::estimate_cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(task_type: &str, tokens: u32) -> ModelRecommendContext {
+        ModelRecommendContext {
+            task_type: task_type.into(),
+            estimated_input_tokens: tokens,
+            requires_vision: false,
+            requires_tool_use: false,
+            max_cost_usd: 0.0,
+        }
+    }
+
+    fn models(ranked: &[ModelRecommendation]) -> Vec<&str> {
+        ranked.iter().map(|r| r.model.model.as_str()).collect()
+    }
+
+    #[test]
+    fn test_override_model_config_uses_provider_endpoint() {
+        let mut config = AppConfig::default();
+        config.ai_config.provider = "ark".into();
+        config.ai_config.base_url = "https://ark.example/v3".into();
+        config.ai_config.api_key = "ark-key".into();
+        config.providers.push(crate::models::config::NamedProvider {
+            name: "anthropic".into(),
+            base_url: "https://anthropic.example/v1".into(),
+            keys: vec!["ant-key".into()],
+            default_model: None,
+            rotation: Default::default(),
+        });
+
+        let bare = override_model_config("doubao-pro", &config).unwrap();
+        assert_eq!((bare.base_url.as_str(), bare.api_key.as_str()), ("https://ark.example/v3", "ark-key"));
+        assert_eq!(bare.model, "doubao-pro");
+
+        let other = override_model_config("sonnet", &config).unwrap();
+        assert_eq!((other.base_url.as_str(), other.api_key.as_str()), ("https://anthropic.example/v1", "ant-key"));
+        assert_eq!(other.model, "claude-sonnet-4-5-20250514");
+        assert_eq!(override_model_config("anthropic/claude-x", &config).unwrap().provider, "anthropic");
+        assert_eq!(override_model_config("ark/doubao-lite", &config).unwrap().base_url, "https://ark.example/v3");
+
+        let err = override_model_config("openai/gpt-4o", &config).unwrap_err();
+        assert!(err.contains("not configured"), "{}", err);
+    }
+
+    #[test]
+    fn test_recommend_filters_and_ranks() {
+        let caps = builtin_capabilities();
+        let status = HashMap::new();
+        let cost = |model: &str, _: u32, _: u32| if model.starts_with("claude-opus") { 0.5 } else { 0.01 };
+
+        let code = recommend_with(&context("code", 10_000), &caps, &status, &cost).unwrap();
+        assert_eq!(models(&code)[..2], ["claude-opus-4-6-20260115", "claude-sonnet-4-5-20250514"]);
+        assert!(code[0].reason.starts_with("code fit 97%"));
+
+        // Budget drops opus; vision drops text-only models
+        let mut budget = context("code", 10_000);
+        budget.max_cost_usd = 0.1;
+        let within_budget = recommend_with(&budget, &caps, &status, &cost).unwrap();
+        assert_eq!(models(&within_budget)[0], "claude-sonnet-4-5-20250514");
+        let vision = recommend_with(&context("vision", 1_000), &caps, &status, &cost).unwrap();
+        assert!(!models(&vision).contains(&"o3-mini") && !models(&vision).contains(&"llama3.3"));
+
+        // Only the 1M-context models fit a huge input
+        let huge = recommend_with(&context("analysis", 500_000), &caps, &status, &cost).unwrap();
+        assert_eq!(models(&huge), vec!["gemini-2.5-pro", "gemini-2.5-flash"]);
+
+        let mut tools = context("analysis", 1_000);
+        tools.requires_tool_use = true;
+        assert!(!models(&recommend_with(&tools, &caps, &status, &cost).unwrap()).contains(&"deepseek-r1"));
+
+        assert!(recommend_with(&context("poetry", 1_000), &caps, &status, &cost).is_err());
+    }
+
+    #[test]
+    fn test_recommend_skips_rate_limited_models() {
+        let caps = builtin_capabilities();
+        let cost = |_: &str, _: u32, _: u32| 0.01;
+        let mut status = HashMap::new();
+        status.insert(
+            "claude-opus-4-6-20260115".to_string(),
+            ModelStatus { requests_today: 40, rate_limited_secs: 30 },
+        );
+        status.insert(
+            "claude-sonnet-4-5-20250514".to_string(),
+            ModelStatus { requests_today: 3, rate_limited_secs: 0 },
+        );
+
+        let ranked = recommend_with(&context("code", 1_000), &caps, &status, &cost).unwrap();
+        assert_eq!(ranked[0].model.model, "claude-sonnet-4-5-20250514");
+        assert!(ranked[0].reason.ends_with("3 requests today"));
+        assert!(!models(&ranked).contains(&"claude-opus-4-6-20260115"));
+    }
+}
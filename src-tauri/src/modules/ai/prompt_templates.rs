@@ -0,0 +1,503 @@
+//! Prompt Templates — reusable prompts with `{{variable}}` placeholders.
+//!
+//! Templates live in the `prompt_templates` table of helix.db with an FTS5
+//! index for search. `prompt_import_defaults` seeds a few built-ins, and
+//! `ai_chat_send(template_id)` renders one into the user message.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::error::{codes, HelixError, HelixResult};
+use crate::modules::config::get_data_dir;
+
+/// Variable filled from the chat message when a template is used in `ai_chat_send`.
+pub const INPUT_VARIABLE: &str = "input";
+
+/// `{{name}}` placeholders; whitespace inside the braces is allowed.
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub variables: Vec<String>,
+    pub category: String,
+    pub created_at: String,
+}
+
+/// (id, name, category, content) of the templates seeded by `prompt_import_defaults`.
+const DEFAULT_TEMPLATES: &[(&str, &str, &str, &str)] = &[
+    (
+        "builtin-code-review",
+        "Code Review",
+        "development",
+        "Review the following code. Point out bugs, security issues and unclear naming, \
+         ordered by severity, and suggest concrete fixes.\n\n{{input}}",
+    ),
+    (
+        "builtin-summarize",
+        "Summarize",
+        "writing",
+        "Summarize the following text in a few bullet points, keeping names, numbers and decisions.\n\n{{input}}",
+    ),
+    (
+        "builtin-translate",
+        "Translate",
+        "writing",
+        "Translate the following text into {{language}}. Keep the formatting and do not add explanations.\n\n{{input}}",
+    ),
+    (
+        "builtin-explain",
+        "Explain",
+        "learning",
+        "Explain the following step by step for someone new to the topic, with a short example.\n\n{{input}}",
+    ),
+];
+
+// ============================================================================
+// Database
+// ============================================================================
+
+static PROMPT_DB: Lazy<Mutex<Connection>> = Lazy::new(|| {
+    let conn = open_prompt_db().expect("Failed to open prompt database");
+    Mutex::new(conn)
+});
+
+fn open_prompt_db() -> Result<Connection, String> {
+    let data_dir = get_data_dir()?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| format!("create dir: {}", e))?;
+    let db_path = data_dir.join("helix.db");
+    let conn = Connection::open(&db_path).map_err(|e| format!("open DB: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA busy_timeout=5000;")
+        .map_err(|e| format!("pragmas: {}", e))?;
+    Ok(conn)
+}
+
+const PROMPT_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS prompt_templates (
+        id          TEXT PRIMARY KEY,
+        name        TEXT NOT NULL,
+        content     TEXT NOT NULL,
+        variables   TEXT NOT NULL DEFAULT '[]',
+        category    TEXT NOT NULL DEFAULT 'general',
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_prompt_category ON prompt_templates(category);
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS prompt_templates_fts USING fts5(
+        name,
+        content,
+        category,
+        content=prompt_templates,
+        tokenize='unicode61'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS prompt_fts_insert AFTER INSERT ON prompt_templates BEGIN
+        INSERT INTO prompt_templates_fts(rowid, name, content, category)
+        VALUES (new.rowid, new.name, new.content, new.category);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS prompt_fts_delete AFTER DELETE ON prompt_templates BEGIN
+        INSERT INTO prompt_templates_fts(prompt_templates_fts, rowid, name, content, category)
+        VALUES ('delete', old.rowid, old.name, old.content, old.category);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS prompt_fts_update AFTER UPDATE ON prompt_templates BEGIN
+        INSERT INTO prompt_templates_fts(prompt_templates_fts, rowid, name, content, category)
+        VALUES ('delete', old.rowid, old.name, old.content, old.category);
+        INSERT INTO prompt_templates_fts(rowid, name, content, category)
+        VALUES (new.rowid, new.name, new.content, new.category);
+    END;
+";
+
+pub fn init_prompt_tables() -> Result<(), String> {
+    let conn = PROMPT_DB.lock();
+    conn.execute_batch(PROMPT_SCHEMA)
+        .map_err(|e| format!("create prompt tables: {}", e))?;
+    info!("Prompt template tables initialized");
+    Ok(())
+}
+
+fn storage_error(e: impl std::fmt::Display) -> HelixError {
+    HelixError::new(codes::STORAGE_DB, e.to_string())
+}
+
+const SELECT_COLUMNS: &str = "id, name, content, variables, category, created_at";
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<PromptTemplate> {
+    let variables: String = row.get(3)?;
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        content: row.get(2)?,
+        variables: serde_json::from_str(&variables).unwrap_or_default(),
+        category: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+// ============================================================================
+// Variables & Rendering
+// ============================================================================
+
+/// Placeholder names in `content`, in order of first appearance.
+pub fn extract_variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for cap in PLACEHOLDER.captures_iter(content) {
+        let name = cap[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Replace every `{{name}}` with its value. Fails listing every variable of
+/// the template that has no value; unknown placeholders are left as written.
+pub fn render(template: &PromptTemplate, values: &HashMap<String, String>) -> HelixResult<String> {
+    let missing: Vec<String> = template
+        .variables
+        .iter()
+        .filter(|v| !values.contains_key(*v))
+        .map(|v| format!("missing variable '{}'", v))
+        .collect();
+    if !missing.is_empty() {
+        return Err(HelixError::validation_error(missing));
+    }
+    let rendered = PLACEHOLDER.replace_all(&template.content, |cap: &regex::Captures| {
+        values.get(&cap[1]).cloned().unwrap_or_else(|| cap[0].to_string())
+    });
+    Ok(rendered.into_owned())
+}
+
+/// Check the editable fields; `variables` default to the placeholders in `content`.
+fn validate(name: &str, content: &str, variables: Option<Vec<String>>) -> HelixResult<Vec<String>> {
+    let mut errors = Vec::new();
+    if name.trim().is_empty() {
+        errors.push("name must not be empty".to_string());
+    }
+    if content.trim().is_empty() {
+        errors.push("content must not be empty".to_string());
+    }
+    let variables = variables.unwrap_or_else(|| extract_variables(content));
+    for v in &variables {
+        let valid = v.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            errors.push(format!("invalid variable name '{}'", v));
+        }
+    }
+    if errors.is_empty() {
+        Ok(variables)
+    } else {
+        Err(HelixError::validation_error(errors))
+    }
+}
+
+// ============================================================================
+// CRUD
+// ============================================================================
+
+fn get_in(conn: &Connection, id: &str) -> HelixResult<PromptTemplate> {
+    conn.query_row(
+        &format!("SELECT {} FROM prompt_templates WHERE id = ?1", SELECT_COLUMNS),
+        params![id],
+        row_to_template,
+    )
+    .optional()
+    .map_err(storage_error)?
+    .ok_or_else(|| HelixError::not_found(format!("Prompt template '{}' not found", id)))
+}
+
+fn create_in(
+    conn: &Connection,
+    name: &str,
+    content: &str,
+    category: Option<&str>,
+    variables: Option<Vec<String>>,
+) -> HelixResult<PromptTemplate> {
+    let variables = validate(name, content, variables)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let template = PromptTemplate {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        content: content.to_string(),
+        variables,
+        category: category.map(str::trim).filter(|c| !c.is_empty()).unwrap_or("general").to_string(),
+        created_at: now.clone(),
+    };
+    conn.execute(
+        "INSERT INTO prompt_templates (id, name, content, variables, category, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+        params![
+            template.id,
+            template.name,
+            template.content,
+            serde_json::to_string(&template.variables).unwrap_or_else(|_| "[]".into()),
+            template.category,
+            now
+        ],
+    )
+    .map_err(storage_error)?;
+    Ok(template)
+}
+
+fn update_in(
+    conn: &Connection,
+    id: &str,
+    name: Option<String>,
+    content: Option<String>,
+    category: Option<String>,
+    variables: Option<Vec<String>>,
+) -> HelixResult<PromptTemplate> {
+    let current = get_in(conn, id)?;
+    let content_changed = content.is_some();
+    let name = name.unwrap_or(current.name);
+    let content = content.unwrap_or(current.content);
+    // New content without an explicit list re-derives the variables from it
+    let variables = match variables {
+        Some(v) => Some(v),
+        None if content_changed => None,
+        None => Some(current.variables),
+    };
+    let variables = validate(&name, &content, variables)?;
+    let category = category
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or(current.category);
+
+    conn.execute(
+        "UPDATE prompt_templates SET name = ?2, content = ?3, variables = ?4, category = ?5, updated_at = ?6
+         WHERE id = ?1",
+        params![
+            id,
+            name.trim(),
+            content,
+            serde_json::to_string(&variables).unwrap_or_else(|_| "[]".into()),
+            category,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(storage_error)?;
+    get_in(conn, id)
+}
+
+fn list_in(conn: &Connection, category: Option<&str>) -> HelixResult<Vec<PromptTemplate>> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM prompt_templates WHERE ?1 IS NULL OR category = ?1 ORDER BY category, name",
+            SELECT_COLUMNS
+        ))
+        .map_err(storage_error)?;
+    let rows = stmt.query_map(params![category], row_to_template).map_err(storage_error)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(storage_error)
+}
+
+fn delete_in(conn: &Connection, id: &str) -> HelixResult<()> {
+    let deleted = conn
+        .execute("DELETE FROM prompt_templates WHERE id = ?1", params![id])
+        .map_err(storage_error)?;
+    if deleted == 0 {
+        return Err(HelixError::not_found(format!("Prompt template '{}' not found", id)));
+    }
+    Ok(())
+}
+
+fn search_in(conn: &Connection, query: &str, limit: i64) -> HelixResult<Vec<PromptTemplate>> {
+    // Quote each word so FTS5 operators in the query are matched literally
+    let fts_query = query
+        .split_whitespace()
+        .map(|w| format!("\"{}\"", w.replace('"', "")))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    if fts_query.is_empty() {
+        return list_in(conn, None);
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.name, p.content, p.variables, p.category, p.created_at
+             FROM prompt_templates_fts f
+             JOIN prompt_templates p ON f.rowid = p.rowid
+             WHERE prompt_templates_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )
+        .map_err(storage_error)?;
+    let rows = stmt.query_map(params![fts_query, limit], row_to_template).map_err(storage_error)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(storage_error)
+}
+
+/// Insert the built-in templates that are not present yet; returns how many were added.
+fn import_defaults_in(conn: &Connection) -> HelixResult<usize> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut added = 0;
+    for (id, name, category, content) in DEFAULT_TEMPLATES {
+        added += conn
+            .execute(
+                "INSERT OR IGNORE INTO prompt_templates (id, name, content, variables, category, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                params![
+                    id,
+                    name,
+                    content,
+                    serde_json::to_string(&extract_variables(content)).unwrap_or_else(|_| "[]".into()),
+                    category,
+                    now
+                ],
+            )
+            .map_err(storage_error)?;
+    }
+    Ok(added)
+}
+
+pub fn get_template(id: &str) -> HelixResult<PromptTemplate> {
+    get_in(&PROMPT_DB.lock(), id)
+}
+
+/// Build the user message for `ai_chat_send`: the rendered template, with the
+/// chat text filling `{{input}}` or, when the template has no such variable,
+/// appended after it.
+pub fn render_for_chat(template_id: &str, content: &str, mut values: HashMap<String, String>) -> HelixResult<String> {
+    let template = get_template(template_id)?;
+    let uses_input = template.variables.iter().any(|v| v == INPUT_VARIABLE);
+    if uses_input {
+        values.entry(INPUT_VARIABLE.to_string()).or_insert_with(|| content.to_string());
+    }
+    let rendered = render(&template, &values)?;
+    if uses_input || content.trim().is_empty() {
+        Ok(rendered)
+    } else {
+        Ok(format!("{}\n\n{}", rendered, content))
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn prompt_create(
+    name: String,
+    content: String,
+    category: Option<String>,
+    variables: Option<Vec<String>>,
+) -> HelixResult<PromptTemplate> {
+    create_in(&PROMPT_DB.lock(), &name, &content, category.as_deref(), variables)
+}
+
+#[tauri::command]
+pub async fn prompt_list(category: Option<String>) -> HelixResult<Vec<PromptTemplate>> {
+    list_in(&PROMPT_DB.lock(), category.as_deref())
+}
+
+#[tauri::command]
+pub async fn prompt_get(id: String) -> HelixResult<PromptTemplate> {
+    get_template(&id)
+}
+
+#[tauri::command]
+pub async fn prompt_update(
+    id: String,
+    name: Option<String>,
+    content: Option<String>,
+    category: Option<String>,
+    variables: Option<Vec<String>>,
+) -> HelixResult<PromptTemplate> {
+    update_in(&PROMPT_DB.lock(), &id, name, content, category, variables)
+}
+
+#[tauri::command]
+pub async fn prompt_delete(id: String) -> HelixResult<()> {
+    delete_in(&PROMPT_DB.lock(), &id)
+}
+
+#[tauri::command]
+pub async fn prompt_render(id: String, variables: HashMap<String, String>) -> HelixResult<String> {
+    render(&get_template(&id)?, &variables)
+}
+
+#[tauri::command]
+pub async fn prompt_import_defaults() -> HelixResult<usize> {
+    let added = import_defaults_in(&PROMPT_DB.lock())?;
+    info!("[prompts] Imported {} built-in templates", added);
+    Ok(added)
+}
+
+#[tauri::command]
+pub async fn prompt_search(query: String, limit: Option<i64>) -> HelixResult<Vec<PromptTemplate>> {
+    search_in(&PROMPT_DB.lock(), &query, limit.unwrap_or(20))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(PROMPT_SCHEMA).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_extract_and_render() {
+        let content = "Translate {{ input }} into {{language}}, then {{input}} again; keep {{literal-braces}}";
+        assert_eq!(extract_variables(content), vec!["input", "language"]);
+
+        let template = PromptTemplate {
+            id: "t".into(),
+            name: "t".into(),
+            content: content.into(),
+            variables: extract_variables(content),
+            category: "general".into(),
+            created_at: String::new(),
+        };
+        let values = HashMap::from([("input".to_string(), "你好".to_string()), ("language".to_string(), "English".to_string())]);
+        assert_eq!(
+            render(&template, &values).unwrap(),
+            "Translate 你好 into English, then 你好 again; keep {{literal-braces}}"
+        );
+
+        let err = render(&template, &HashMap::new()).unwrap_err();
+        assert_eq!(err.code, codes::VALIDATION_ERROR);
+        assert_eq!(err.message, "missing variable 'input'; missing variable 'language'");
+    }
+
+    #[test]
+    fn test_crud_and_search() {
+        let conn = test_conn();
+        assert_eq!(import_defaults_in(&conn).unwrap(), DEFAULT_TEMPLATES.len());
+        assert_eq!(import_defaults_in(&conn).unwrap(), 0);
+
+        let created = create_in(&conn, " Commit message ", "Write a commit message for {{diff}}", Some("development"), None).unwrap();
+        assert_eq!(created.name, "Commit message");
+        assert_eq!(created.variables, vec!["diff"]);
+        assert!(create_in(&conn, "", "x", None, None).is_err());
+
+        let hits = search_in(&conn, "commit", 10).unwrap();
+        assert_eq!(hits.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec![created.id.as_str()]);
+
+        let updated = update_in(&conn, &created.id, None, Some("Describe {{changes}}".into()), None, None).unwrap();
+        assert_eq!(updated.variables, vec!["changes"]);
+        assert_eq!(updated.category, "development");
+        assert!(search_in(&conn, "commit", 10).unwrap().iter().any(|t| t.id == created.id));
+        assert!(search_in(&conn, "Describe", 10).unwrap().iter().any(|t| t.id == created.id));
+
+        assert_eq!(list_in(&conn, Some("development")).unwrap().len(), 2);
+        delete_in(&conn, &created.id).unwrap();
+        assert_eq!(delete_in(&conn, &created.id).unwrap_err().code, codes::NOT_FOUND);
+        assert!(search_in(&conn, "Describe", 10).unwrap().is_empty());
+    }
+}
@@ -8,6 +8,10 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use tracing::info;
 
+use crate::error::{codes, HelixError, HelixResult};
+use crate::models::config::{AppConfig, KeyRotationStrategy, NamedProvider};
+use crate::modules::config::{load_app_config, save_app_config};
+
 // ============================================================================
 // Provider Types
 // ============================================================================
@@ -358,25 +362,295 @@ pub fn auth_headers(config: &ProviderConfig) -> Vec<(String, String)> {
     headers
 }
 
+// ============================================================================
+// Named Providers
+// ============================================================================
+
+pub fn find_provider<'a>(config: &'a AppConfig, name: &str) -> Option<&'a NamedProvider> {
+    config.providers.iter().find(|p| p.name == name)
+}
+
+/// Trimmed, non-empty, deduplicated keys in their original order.
+fn clean_keys(keys: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for key in keys {
+        let key = key.trim().to_string();
+        if !key.is_empty() && !cleaned.contains(&key) {
+            cleaned.push(key);
+        }
+    }
+    cleaned
+}
+
+/// Copy the default provider (named by `ai_config.provider`) into `ai_config`,
+/// which the rest of the app reads. Its first key becomes the primary `api_key`.
+pub fn sync_ai_config(config: &mut AppConfig) {
+    let Some(provider) = find_provider(config, &config.ai_config.provider).cloned() else {
+        return;
+    };
+    config.ai_config.base_url = provider.base_url;
+    config.ai_config.api_key = provider.keys.first().cloned().unwrap_or_default();
+    if let Some(model) = provider.default_model.filter(|m| !m.is_empty()) {
+        config.ai_config.model = model;
+    }
+}
+
+/// Write `ai_config` back into the default named provider, creating it (with
+/// any legacy `provider_keys`) if needed. `extra_keys` replaces the keys after
+/// the primary one; `None` keeps them.
+pub fn upsert_default_provider(config: &mut AppConfig, extra_keys: Option<Vec<String>>) {
+    let ai = config.ai_config.clone();
+    let legacy = config.provider_keys.remove(&ai.provider).unwrap_or_default();
+    let index = match config.providers.iter().position(|p| p.name == ai.provider) {
+        Some(i) => i,
+        None => {
+            config.providers.push(NamedProvider {
+                name: ai.provider.clone(),
+                base_url: String::new(),
+                keys: std::iter::once(String::new()).chain(legacy).collect(),
+                default_model: None,
+                rotation: KeyRotationStrategy::default(),
+            });
+            config.providers.len() - 1
+        }
+    };
+    let provider = &mut config.providers[index];
+    let extra = extra_keys.unwrap_or_else(|| provider.keys.iter().skip(1).cloned().collect());
+    provider.keys = clean_keys(std::iter::once(ai.api_key).chain(extra));
+    provider.base_url = ai.base_url;
+    provider.default_model = Some(ai.model).filter(|m| !m.is_empty());
+}
+
+/// Problems with a provider entry, checked against the other `providers`.
+fn validate_provider(provider: &NamedProvider, others: &[NamedProvider]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if provider.name.trim().is_empty() {
+        problems.push("name is empty".to_string());
+    } else if others.iter().any(|p| p.name == provider.name) {
+        problems.push(format!("provider '{}' already exists", provider.name));
+    }
+    if provider.base_url.trim().is_empty() {
+        problems.push("base_url is empty".to_string());
+    }
+    problems
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderUpdate {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub keys: Option<Vec<String>>,
+    #[serde(default)]
+    pub default_model: Option<String>,
+    #[serde(default)]
+    pub rotation: Option<KeyRotationStrategy>,
+}
+
+/// Provider as shown in the UI: masked keys with their rotation state.
+fn provider_summary(provider: &NamedProvider, default_name: &str) -> Value {
+    json!({
+        "name": provider.name,
+        "base_url": provider.base_url,
+        "default_model": provider.default_model,
+        "rotation": provider.rotation,
+        "is_default": provider.name == default_name,
+        "keys": provider.keys.iter().map(|k| super::chat::key_status(k)).collect::<Vec<_>>(),
+    })
+}
+
+fn load_config() -> HelixResult<AppConfig> {
+    load_app_config().map_err(|e| HelixError::new(codes::CONFIG_LOAD_FAILED, format!("读取配置失败: {}", e)))
+}
+
+fn save_config(config: &AppConfig) -> HelixResult<()> {
+    save_app_config(config).map_err(|e| HelixError::internal(format!("保存配置失败: {}", e)))
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
+#[tauri::command]
+pub async fn providers_list() -> HelixResult<Vec<Value>> {
+    let config = load_config()?;
+    Ok(config
+        .providers
+        .iter()
+        .map(|p| provider_summary(p, &config.ai_config.provider))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn providers_add(provider: NamedProvider) -> HelixResult<Value> {
+    let mut config = load_config()?;
+    let provider = NamedProvider {
+        name: provider.name.trim().to_string(),
+        base_url: provider.base_url.trim().to_string(),
+        keys: clean_keys(provider.keys),
+        default_model: provider.default_model.filter(|m| !m.trim().is_empty()),
+        rotation: provider.rotation,
+    };
+    let problems = validate_provider(&provider, &config.providers);
+    if !problems.is_empty() {
+        return Err(HelixError::validation_error(problems));
+    }
+    config.providers.push(provider.clone());
+    sync_ai_config(&mut config);
+    save_config(&config)?;
+    info!("[providers] Added provider '{}' ({} keys)", provider.name, provider.keys.len());
+    Ok(provider_summary(&provider, &config.ai_config.provider))
+}
+
+#[tauri::command]
+pub async fn providers_update(name: String, update: ProviderUpdate) -> HelixResult<Value> {
+    let mut config = load_config()?;
+    let index = config
+        .providers
+        .iter()
+        .position(|p| p.name == name)
+        .ok_or_else(|| HelixError::not_found(format!("provider '{}' not found", name)))?;
+
+    let mut provider = config.providers.remove(index);
+    if let Some(v) = update.name {
+        provider.name = v.trim().to_string();
+    }
+    if let Some(v) = update.base_url {
+        provider.base_url = v.trim().to_string();
+    }
+    if let Some(v) = update.keys {
+        provider.keys = clean_keys(v);
+    }
+    if let Some(v) = update.default_model {
+        provider.default_model = Some(v).filter(|m| !m.trim().is_empty());
+    }
+    if let Some(v) = update.rotation {
+        provider.rotation = v;
+    }
+    let problems = validate_provider(&provider, &config.providers);
+    if !problems.is_empty() {
+        return Err(HelixError::validation_error(problems));
+    }
+
+    // Renaming the default provider keeps it the default
+    if config.ai_config.provider == name {
+        config.ai_config.provider = provider.name.clone();
+    }
+    config.providers.insert(index, provider.clone());
+    sync_ai_config(&mut config);
+    save_config(&config)?;
+    info!("[providers] Updated provider '{}'", provider.name);
+    Ok(provider_summary(&provider, &config.ai_config.provider))
+}
+
+#[tauri::command]
+pub async fn providers_remove(name: String) -> HelixResult<()> {
+    let mut config = load_config()?;
+    if config.ai_config.provider == name {
+        return Err(HelixError::validation_error(vec![format!(
+            "provider '{}' is the default; switch to another provider first",
+            name
+        )]));
+    }
+    let before = config.providers.len();
+    config.providers.retain(|p| p.name != name);
+    if config.providers.len() == before {
+        return Err(HelixError::not_found(format!("provider '{}' not found", name)));
+    }
+    save_config(&config)?;
+    info!("[providers] Removed provider '{}'", name);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn providers_detect(model: String) -> Result<String, String> {
     Ok(detect_provider(&model).to_string())
 }
 
+/// Resolve a provider config. With `name`, the named provider's base URL is
+/// used with one of its keys picked by its rotation strategy, and its
+/// default model when `model` is empty.
 #[tauri::command]
 pub async fn providers_resolve(
     model: String,
     base_url: Option<String>,
     api_key: Option<String>,
+    name: Option<String>,
 ) -> Result<ProviderConfig, String> {
+    let Some(name) = name else {
+        return Ok(resolve_provider_config(
+            &model,
+            base_url.as_deref(),
+            api_key.as_deref(),
+            None,
+        ));
+    };
+
+    let config = load_app_config().map_err(|e| format!("读取配置失败: {}", e))?;
+    let provider = find_provider(&config, &name).ok_or_else(|| format!("provider '{}' not found", name))?;
+    let key = super::chat::pick_key(&provider.keys, provider.rotation);
+    let model = if model.is_empty() { provider.default_model.clone().unwrap_or_default() } else { model };
     Ok(resolve_provider_config(
         &model,
-        base_url.as_deref(),
-        api_key.as_deref(),
+        Some(&provider.base_url),
+        key.as_deref(),
         None,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str, keys: &[&str]) -> NamedProvider {
+        NamedProvider {
+            name: name.to_string(),
+            base_url: format!("https://{}.example.com/v1", name),
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            default_model: Some(format!("{}-model", name)),
+            rotation: KeyRotationStrategy::Failover,
+        }
+    }
+
+    #[test]
+    fn test_sync_and_upsert_default_provider() {
+        let mut config = AppConfig::new();
+        config.providers = vec![named("personal", &["sk-p1", "sk-p2"]), named("team", &["sk-t1"])];
+        config.ai_config.provider = "team".to_string();
+        sync_ai_config(&mut config);
+        assert_eq!(config.ai_config.api_key, "sk-t1");
+        assert_eq!(config.ai_config.base_url, "https://team.example.com/v1");
+        assert_eq!(config.ai_config.model, "team-model");
+
+        // ai_set_config shim: new primary key, extra keys kept
+        config.ai_config.provider = "personal".to_string();
+        sync_ai_config(&mut config);
+        config.ai_config.api_key = "sk-p0".to_string();
+        upsert_default_provider(&mut config, None);
+        assert_eq!(find_provider(&config, "personal").unwrap().keys, vec!["sk-p0", "sk-p2"]);
+        upsert_default_provider(&mut config, Some(vec![" sk-p3 ".to_string(), "sk-p0".to_string()]));
+        assert_eq!(find_provider(&config, "personal").unwrap().keys, vec!["sk-p0", "sk-p3"]);
+
+        // Unknown provider is created from ai_config plus legacy extra keys
+        config.ai_config.provider = "ark".to_string();
+        config.provider_keys.insert("ark".to_string(), vec!["sk-a2".to_string()]);
+        config.ai_config.api_key = "sk-a1".to_string();
+        upsert_default_provider(&mut config, None);
+        let ark = find_provider(&config, "ark").unwrap();
+        assert_eq!(ark.keys, vec!["sk-a1", "sk-a2"]);
+        assert_eq!(ark.rotation, KeyRotationStrategy::RoundRobin);
+        assert!(config.provider_keys.is_empty());
+    }
+
+    #[test]
+    fn test_validate_provider() {
+        let others = vec![named("team", &[])];
+        assert!(validate_provider(&named("personal", &[]), &others).is_empty());
+        assert_eq!(validate_provider(&named("team", &[]), &others).len(), 1);
+        let mut blank = named("", &[]);
+        blank.base_url = " ".to_string();
+        assert_eq!(validate_provider(&blank, &others).len(), 2);
+    }
+}
@@ -296,6 +296,80 @@ pub fn extract_complete_blocks(text: &str) -> (Vec<String>, String) {
     (blocks, remainder)
 }
 
+// ============================================================================
+// Code Block Extraction
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeBlock {
+    /// First word of the fence's info string (```rust → "rust")
+    pub language: Option<String>,
+    pub content: String,
+    /// Byte offset of the opening fence
+    pub start_offset: usize,
+    /// Byte offset just past the closing fence (end of text if unterminated)
+    pub end_offset: usize,
+}
+
+/// Length of the backtick run opening a fence line (≥3, up to 3 spaces of indent).
+/// Info strings containing backticks are inline code, not fences.
+fn fence_len(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let len = trimmed.len() - trimmed.trim_start_matches('`').len();
+    (len >= 3 && !trimmed[len..].contains('`')).then_some(len)
+}
+
+/// Extract fenced (```) code blocks. A block closes on a line of at least as
+/// many backticks as opened it, so ```` fences can contain ``` lines.
+/// An unterminated block runs to the end of the text.
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    // (fence length, language, start offset, content start offset)
+    let mut open: Option<(usize, Option<String>, usize, usize)> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let body = line.trim_end_matches(['\n', '\r']);
+
+        match &open {
+            None => {
+                if let Some(len) = fence_len(body) {
+                    let info = body.trim_start_matches(' ')[len..].trim();
+                    let language = info.split_whitespace().next().map(str::to_string);
+                    open = Some((len, language, line_start, offset));
+                }
+            }
+            Some((len, _, _, _)) => {
+                let closing = body.trim();
+                if closing.len() >= *len && closing.chars().all(|c| c == '`') && fence_len(body).is_some() {
+                    let (_, language, start, content_start) = open.take().unwrap();
+                    blocks.push(CodeBlock {
+                        language,
+                        content: text[content_start..line_start].trim_end_matches(['\n', '\r']).to_string(),
+                        start_offset: start,
+                        end_offset: line_start + body.len(),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some((_, language, start, content_start)) = open {
+        blocks.push(CodeBlock {
+            language,
+            content: text[content_start..].trim_end_matches(['\n', '\r']).to_string(),
+            start_offset: start,
+            end_offset: text.len(),
+        });
+    }
+    blocks
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -309,3 +383,38 @@ pub async fn stream_clean_text(text: String) -> Result<String, String> {
 pub async fn stream_strip_thinking(text: String) -> Result<String, String> {
     Ok(strip_thinking_blocks(&text))
 }
+
+#[tauri::command]
+pub async fn stream_extract_code_blocks(text: String) -> Result<Vec<CodeBlock>, String> {
+    Ok(extract_code_blocks(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let text = "Run this:\n```bash\necho `date`\n```\nthen\n```\nplain\n```";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language.as_deref(), Some("bash"));
+        assert_eq!(blocks[0].content, "echo `date`");
+        assert_eq!(&text[blocks[0].start_offset..blocks[0].end_offset], "```bash\necho `date`\n```");
+        assert_eq!(blocks[1].language, None);
+        assert_eq!(blocks[1].content, "plain");
+        assert_eq!(blocks[1].end_offset, text.len());
+    }
+
+    #[test]
+    fn test_extract_code_blocks_nested_and_unterminated() {
+        let text = "````markdown\n```rust\nfn main() {}\n```\n````\ninline ```not a fence``` here\n```py\nprint(1)\n";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language.as_deref(), Some("markdown"));
+        assert_eq!(blocks[0].content, "```rust\nfn main() {}\n```");
+        assert_eq!(blocks[1].language.as_deref(), Some("py"));
+        assert_eq!(blocks[1].content, "print(1)");
+        assert_eq!(blocks[1].end_offset, text.len());
+    }
+}
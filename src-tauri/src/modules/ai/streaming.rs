@@ -8,6 +8,7 @@ use serde_json::{json, Value};
 use std::time::Duration;
 use tracing::info;
 
+use super::chat::ProviderError;
 use super::providers::{ProviderConfig, ProviderKind, auth_headers, chat_completion_url};
 
 // ============================================================================
@@ -80,7 +81,7 @@ pub async fn stream_chat_completion(
     provider: &ProviderConfig,
     body: &Value,
     on_event: impl Fn(StreamEvent),
-) -> Result<StreamResult, String> {
+) -> Result<StreamResult, ProviderError> {
     match provider.kind {
         ProviderKind::Ollama => stream_ollama(provider, body, on_event).await,
         ProviderKind::Anthropic => stream_anthropic_sse(provider, body, on_event).await,
@@ -126,6 +127,16 @@ pub async fn complete_simple(
 // HTTP Client
 // ============================================================================
 
+/// Pass a successful response through; any other status becomes `ProviderError::Http`.
+async fn check_status(resp: reqwest::Response) -> Result<reqwest::Response, ProviderError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let err = resp.text().await.unwrap_or_default();
+    Err(ProviderError::Http { status, body: crate::utils::truncate::safe_truncate(&err, 300).to_string() })
+}
+
 fn build_client() -> Result<reqwest::Client, String> {
     reqwest::Client::builder()
         .danger_accept_invalid_certs(true)
@@ -142,9 +153,9 @@ async fn stream_openai_sse(
     provider: &ProviderConfig,
     body: &Value,
     on_event: impl Fn(StreamEvent),
-) -> Result<StreamResult, String> {
+) -> Result<StreamResult, ProviderError> {
     let url = chat_completion_url(provider);
-    let client = build_client()?;
+    let client = build_client().map_err(ProviderError::Config)?;
     let mut request = client.post(&url).timeout(Duration::from_secs(180));
 
     for (key, val) in auth_headers(provider) {
@@ -155,95 +166,21 @@ async fn stream_openai_sse(
         .json(body)
         .send()
         .await
-        .map_err(|e| format!("SSE request failed: {}", e))?;
-
-    let status = resp.status();
-    if !status.is_success() {
-        let err = resp.text().await.unwrap_or_default();
-        return Err(format!("API error ({}): {}", status, &err[..err.len().min(300)]));
-    }
-
-    let full_text = resp.text().await.map_err(|e| format!("Read SSE body: {}", e))?;
-
-    let mut content = String::new();
-    let mut tool_calls: Vec<(String, String, String)> = Vec::new(); // (id, name, args)
-    let mut usage = StreamUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 };
-    let mut stop_reason = "stop".to_string();
-
-    for line in full_text.lines() {
-        let line = line.trim();
-        if line.is_empty() || line == "data: [DONE]" {
-            continue;
-        }
-        if !line.starts_with("data: ") {
-            continue;
-        }
-
-        let json_str = &line[6..];
-        let chunk: Value = match serde_json::from_str(json_str) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
-
-        // Delta text
-        if let Some(delta_content) = chunk["choices"][0]["delta"]["content"].as_str() {
-            content.push_str(delta_content);
-            on_event(StreamEvent::Delta {
-                text: delta_content.to_string(),
-            });
-        }
-
-        // Tool calls
-        if let Some(tc_array) = chunk["choices"][0]["delta"]["tool_calls"].as_array() {
-            for tc in tc_array {
-                let index = tc["index"].as_u64().unwrap_or(0) as usize;
-                let id = tc["id"].as_str().map(|s| s.to_string());
-                let name = tc["function"]["name"].as_str().map(|s| s.to_string());
-                let args_delta = tc["function"]["arguments"].as_str().unwrap_or("");
-
-                // Grow tool_calls vector as needed
-                while tool_calls.len() <= index {
-                    tool_calls.push((String::new(), String::new(), String::new()));
-                }
-                // Only overwrite id/name when non-empty — Qwen sends "" in follow-up chunks
-                if let Some(ref id) = id {
-                    if !id.is_empty() {
-                        tool_calls[index].0 = id.clone();
-                    }
-                }
-                if let Some(ref name) = name {
-                    if !name.is_empty() {
-                        tool_calls[index].1 = name.clone();
-                    }
-                }
-                tool_calls[index].2.push_str(args_delta);
-
-                on_event(StreamEvent::ToolCallDelta {
-                    index,
-                    id,
-                    name,
-                    arguments_delta: args_delta.to_string(),
-                });
-            }
-        }
-
-        // Usage
-        if let Some(u) = chunk.get("usage") {
-            usage.prompt_tokens = u["prompt_tokens"].as_u64().unwrap_or(0) as u32;
-            usage.completion_tokens = u["completion_tokens"].as_u64().unwrap_or(0) as u32;
-            usage.total_tokens = u["total_tokens"].as_u64().unwrap_or(0) as u32;
-            on_event(StreamEvent::Usage {
-                prompt_tokens: usage.prompt_tokens,
-                completion_tokens: usage.completion_tokens,
-                total_tokens: usage.total_tokens,
-            });
-        }
-
-        // Stop reason
-        if let Some(fr) = chunk["choices"][0]["finish_reason"].as_str() {
-            stop_reason = fr.to_string();
+        .map_err(|source| ProviderError::Network { source })?;
+    let mut resp = check_status(resp).await?;
+
+    // Read chunk by chunk so deltas reach `on_event` as they arrive
+    let mut state = SseState::default();
+    let mut pending: Vec<u8> = Vec::new();
+    let read_error = |e: reqwest::Error| ProviderError::BadResponse(format!("Read SSE body: {}", e));
+    while let Some(chunk) = resp.chunk().await.map_err(read_error)? {
+        pending.extend_from_slice(&chunk);
+        while let Some(line) = take_line(&mut pending) {
+            handle_openai_sse_line(&line, &mut state, &on_event);
         }
     }
+    handle_openai_sse_line(&String::from_utf8_lossy(&pending), &mut state, &on_event);
+    let SseState { content, tool_calls, usage, stop_reason } = state;
 
     on_event(StreamEvent::Done {
         stop_reason: stop_reason.clone(),
@@ -270,6 +207,108 @@ async fn stream_openai_sse(
     })
 }
 
+/// Accumulated state of an OpenAI SSE stream.
+struct SseState {
+    content: String,
+    tool_calls: Vec<(String, String, String)>, // (id, name, args)
+    usage: StreamUsage,
+    stop_reason: String,
+}
+
+impl Default for SseState {
+    fn default() -> Self {
+        Self {
+            content: String::new(),
+            tool_calls: Vec::new(),
+            usage: StreamUsage { prompt_tokens: 0, completion_tokens: 0, total_tokens: 0 },
+            stop_reason: "stop".to_string(),
+        }
+    }
+}
+
+/// Split the next complete line off `pending`; partial lines (and split
+/// UTF-8 sequences) stay buffered until the rest arrives.
+fn take_line(pending: &mut Vec<u8>) -> Option<String> {
+    let pos = pending.iter().position(|b| *b == b'\n')?;
+    let line: Vec<u8> = pending.drain(..=pos).collect();
+    Some(String::from_utf8_lossy(&line).into_owned())
+}
+
+fn handle_openai_sse_line(line: &str, state: &mut SseState, on_event: &impl Fn(StreamEvent)) {
+    let line = line.trim();
+    if line.is_empty() || line == "data: [DONE]" {
+        return;
+    }
+    if !line.starts_with("data: ") {
+        return;
+    }
+
+    let json_str = &line[6..];
+    let chunk: Value = match serde_json::from_str(json_str) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+
+    // Delta text
+    if let Some(delta_content) = chunk["choices"][0]["delta"]["content"].as_str() {
+        state.content.push_str(delta_content);
+        on_event(StreamEvent::Delta {
+            text: delta_content.to_string(),
+        });
+    }
+
+    // Tool calls
+    if let Some(tc_array) = chunk["choices"][0]["delta"]["tool_calls"].as_array() {
+        for tc in tc_array {
+            let index = tc["index"].as_u64().unwrap_or(0) as usize;
+            let id = tc["id"].as_str().map(|s| s.to_string());
+            let name = tc["function"]["name"].as_str().map(|s| s.to_string());
+            let args_delta = tc["function"]["arguments"].as_str().unwrap_or("");
+
+            // Grow tool_calls vector as needed
+            while state.tool_calls.len() <= index {
+                state.tool_calls.push((String::new(), String::new(), String::new()));
+            }
+            // Only overwrite id/name when non-empty — Qwen sends "" in follow-up chunks
+            if let Some(ref id) = id {
+                if !id.is_empty() {
+                    state.tool_calls[index].0 = id.clone();
+                }
+            }
+            if let Some(ref name) = name {
+                if !name.is_empty() {
+                    state.tool_calls[index].1 = name.clone();
+                }
+            }
+            state.tool_calls[index].2.push_str(args_delta);
+
+            on_event(StreamEvent::ToolCallDelta {
+                index,
+                id,
+                name,
+                arguments_delta: args_delta.to_string(),
+            });
+        }
+    }
+
+    // Usage
+    if let Some(u) = chunk.get("usage") {
+        state.usage.prompt_tokens = u["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+        state.usage.completion_tokens = u["completion_tokens"].as_u64().unwrap_or(0) as u32;
+        state.usage.total_tokens = u["total_tokens"].as_u64().unwrap_or(0) as u32;
+        on_event(StreamEvent::Usage {
+            prompt_tokens: state.usage.prompt_tokens,
+            completion_tokens: state.usage.completion_tokens,
+            total_tokens: state.usage.total_tokens,
+        });
+    }
+
+    // Stop reason
+    if let Some(fr) = chunk["choices"][0]["finish_reason"].as_str() {
+        state.stop_reason = fr.to_string();
+    }
+}
+
 // ============================================================================
 // Anthropic SSE Streaming
 // ============================================================================
@@ -278,9 +317,9 @@ async fn stream_anthropic_sse(
     provider: &ProviderConfig,
     body: &Value,
     on_event: impl Fn(StreamEvent),
-) -> Result<StreamResult, String> {
+) -> Result<StreamResult, ProviderError> {
     let url = chat_completion_url(provider);
-    let client = build_client()?;
+    let client = build_client().map_err(ProviderError::Config)?;
     let mut request = client.post(&url).timeout(Duration::from_secs(180));
 
     for (key, val) in auth_headers(provider) {
@@ -291,15 +330,11 @@ async fn stream_anthropic_sse(
         .json(body)
         .send()
         .await
-        .map_err(|e| format!("Anthropic SSE request failed: {}", e))?;
+        .map_err(|source| ProviderError::Network { source })?;
+    let resp = check_status(resp).await?;
 
-    let status = resp.status();
-    if !status.is_success() {
-        let err = resp.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error ({}): {}", status, &err[..err.len().min(300)]));
-    }
-
-    let full_text = resp.text().await.map_err(|e| format!("Read Anthropic SSE: {}", e))?;
+    let full_text =
+        resp.text().await.map_err(|e| ProviderError::BadResponse(format!("Read Anthropic SSE: {}", e)))?;
 
     let mut content = String::new();
     let mut tool_calls: Vec<AccumulatedToolCall> = Vec::new();
@@ -416,9 +451,9 @@ async fn stream_ollama(
     provider: &ProviderConfig,
     body: &Value,
     on_event: impl Fn(StreamEvent),
-) -> Result<StreamResult, String> {
+) -> Result<StreamResult, ProviderError> {
     let url = chat_completion_url(provider);
-    let client = build_client()?;
+    let client = build_client().map_err(ProviderError::Config)?;
 
     let resp = client
         .post(&url)
@@ -427,15 +462,11 @@ async fn stream_ollama(
         .timeout(Duration::from_secs(300))
         .send()
         .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
+        .map_err(|source| ProviderError::Network { source })?;
+    let resp = check_status(resp).await?;
 
-    let status = resp.status();
-    if !status.is_success() {
-        let err = resp.text().await.unwrap_or_default();
-        return Err(format!("Ollama error ({}): {}", status, &err[..err.len().min(300)]));
-    }
-
-    let full_text = resp.text().await.map_err(|e| format!("Read Ollama NDJSON: {}", e))?;
+    let full_text =
+        resp.text().await.map_err(|e| ProviderError::BadResponse(format!("Read Ollama NDJSON: {}", e)))?;
 
     let mut content = String::new();
     let mut tool_calls = Vec::new();
@@ -608,6 +639,226 @@ fn parse_ollama_response(data: &Value) -> Result<StreamResult, String> {
     Ok(StreamResult { content, tool_calls, usage, stop_reason })
 }
 
+// ============================================================================
+// Provider Benchmark
+// ============================================================================
+
+/// Fixed prompt used for latency benchmarks (short answer keeps timing comparable).
+const BENCHMARK_PROMPT: &str = "Reply with exactly one word: pong";
+const BENCHMARK_MAX_TOKENS: u32 = 16;
+const BENCHMARK_DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// A provider/model pair to benchmark. Missing base_url / api_key fall back
+/// to provider defaults and environment variables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkTarget {
+    pub model: String,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Display label (defaults to "provider/model")
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderBenchmarkResult {
+    pub label: String,
+    pub provider: String,
+    pub model: String,
+    pub ok: bool,
+    /// Time to first streamed text token
+    pub ttft_ms: Option<u64>,
+    /// Time until the stream finished
+    pub total_ms: Option<u64>,
+    pub output_chars: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvidersBenchmark {
+    /// Sorted fastest first (by time-to-first-token); failures last
+    pub results: Vec<ProviderBenchmarkResult>,
+    /// Markdown comparison table
+    pub table: String,
+}
+
+/// Default targets: the configured AI model plus any provider with an API key in the environment.
+fn default_benchmark_targets() -> Vec<BenchmarkTarget> {
+    let mut targets = Vec::new();
+
+    if let Ok(config) = crate::modules::config::load_app_config() {
+        let ai = config.ai_config;
+        if !ai.model.is_empty() {
+            targets.push(BenchmarkTarget {
+                label: Some(format!("{} (configured)", ai.provider)),
+                model: ai.model,
+                base_url: Some(ai.base_url).filter(|u| !u.is_empty()),
+                api_key: Some(ai.api_key).filter(|k| !k.is_empty()),
+            });
+        }
+    }
+
+    let env_defaults = [
+        (ProviderKind::OpenAI, "gpt-4o-mini"),
+        (ProviderKind::Anthropic, "claude-haiku-3-5-20250620"),
+        (ProviderKind::Google, "gemini-2.5-flash"),
+    ];
+    for (kind, model) in env_defaults {
+        let auth = super::providers::resolve_api_key(&kind, None);
+        if auth.source.starts_with("env:") && !targets.iter().any(|t| t.model == model) {
+            targets.push(BenchmarkTarget {
+                label: Some(format!("{} ({})", kind, auth.source)),
+                model: model.to_string(),
+                base_url: None,
+                api_key: None,
+            });
+        }
+    }
+
+    targets
+}
+
+/// Build a minimal streaming request body for the provider's wire format.
+fn benchmark_request_body(kind: &ProviderKind, model: &str) -> Value {
+    let messages = [json!({"role": "user", "content": BENCHMARK_PROMPT})];
+    match kind {
+        ProviderKind::Anthropic => super::providers::build_anthropic_request(
+            model, &messages, None, None, BENCHMARK_MAX_TOKENS, true,
+        ),
+        ProviderKind::Ollama => super::providers::build_ollama_request(model, &messages, None, true),
+        ProviderKind::Google => json!({
+            "contents": [{"role": "user", "parts": [{"text": BENCHMARK_PROMPT}]}],
+            "generationConfig": {"maxOutputTokens": BENCHMARK_MAX_TOKENS},
+        }),
+        _ => super::providers::build_openai_request(model, &messages, None, BENCHMARK_MAX_TOKENS, true),
+    }
+}
+
+/// Extract streamed text from one SSE `data:` line or Ollama NDJSON line.
+fn stream_line_text(kind: &ProviderKind, line: &str) -> Option<String> {
+    let payload = line.trim();
+    let payload = payload.strip_prefix("data:").map(str::trim).unwrap_or(payload);
+    if payload.is_empty() || payload == "[DONE]" {
+        return None;
+    }
+    let data: Value = serde_json::from_str(payload).ok()?;
+    let text = match kind {
+        ProviderKind::Anthropic => {
+            if data["type"] != "content_block_delta" {
+                return None;
+            }
+            data["delta"]["text"].as_str()
+        }
+        ProviderKind::Ollama => data["message"]["content"].as_str(),
+        ProviderKind::Google => data["candidates"][0]["content"]["parts"][0]["text"].as_str(),
+        _ => data["choices"][0]["delta"]["content"].as_str(),
+    }?;
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Stream one completion chunk-by-chunk, timing the first text token and the end of stream.
+async fn measure_stream(provider: &ProviderConfig, body: &Value) -> Result<(Option<u64>, u64, usize), String> {
+    let client = build_client()?;
+    let mut request = client.post(chat_completion_url(provider));
+    for (key, val) in auth_headers(provider) {
+        request = request.header(&key, &val);
+    }
+
+    let start = std::time::Instant::now();
+    let mut resp = request
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let err = resp.text().await.unwrap_or_default();
+        return Err(format!("API error ({}): {}", status, crate::utils::truncate::safe_truncate(&err, 200)));
+    }
+
+    let mut ttft_ms = None;
+    let mut output_chars = 0;
+    let mut pending = String::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("Read stream: {}", e))? {
+        pending.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(pos) = pending.find('\n') {
+            let line: String = pending.drain(..=pos).collect();
+            if let Some(text) = stream_line_text(&provider.kind, &line) {
+                ttft_ms.get_or_insert(start.elapsed().as_millis() as u64);
+                output_chars += text.chars().count();
+            }
+        }
+    }
+    if let Some(text) = stream_line_text(&provider.kind, &pending) {
+        ttft_ms.get_or_insert(start.elapsed().as_millis() as u64);
+        output_chars += text.chars().count();
+    }
+
+    Ok((ttft_ms, start.elapsed().as_millis() as u64, output_chars))
+}
+
+async fn benchmark_target(target: BenchmarkTarget, timeout: Duration) -> ProviderBenchmarkResult {
+    let provider = super::providers::resolve_provider_config(
+        &target.model,
+        target.base_url.as_deref(),
+        target.api_key.as_deref(),
+        None,
+    );
+    let mut result = ProviderBenchmarkResult {
+        label: target.label.clone().unwrap_or_else(|| format!("{}/{}", provider.kind, target.model)),
+        provider: provider.kind.to_string(),
+        model: target.model.clone(),
+        ok: false,
+        ttft_ms: None,
+        total_ms: None,
+        output_chars: 0,
+        error: None,
+    };
+
+    if provider.api_key.is_empty() && provider.kind != ProviderKind::Ollama {
+        result.error = Some("No API key configured".to_string());
+        return result;
+    }
+
+    let body = benchmark_request_body(&provider.kind, &target.model);
+    match tokio::time::timeout(timeout, measure_stream(&provider, &body)).await {
+        Ok(Ok((ttft_ms, total_ms, output_chars))) => {
+            result.ok = true;
+            result.ttft_ms = ttft_ms;
+            result.total_ms = Some(total_ms);
+            result.output_chars = output_chars;
+            if ttft_ms.is_none() {
+                result.error = Some("Stream finished without any text".to_string());
+            }
+        }
+        Ok(Err(e)) => result.error = Some(e),
+        Err(_) => result.error = Some(format!("Timed out after {}s", timeout.as_secs())),
+    }
+    result
+}
+
+fn format_benchmark_table(results: &[ProviderBenchmarkResult]) -> String {
+    let ms = |v: Option<u64>| v.map(|v| format!("{} ms", v)).unwrap_or_else(|| "—".to_string());
+    let mut table = String::from("| # | Provider | Model | TTFT | Total | Status |\n|---|---|---|---|---|---|\n");
+    for (i, r) in results.iter().enumerate() {
+        let status = match (&r.error, r.ok) {
+            (None, true) => "✅".to_string(),
+            (Some(e), _) => format!("❌ {}", crate::utils::truncate::safe_truncate(e, 60)),
+            (None, false) => "❌".to_string(),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            i + 1,
+            r.label,
+            r.model,
+            ms(r.ttft_ms),
+            ms(r.total_ms),
+            status
+        ));
+    }
+    table
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -637,3 +888,70 @@ pub async fn streaming_test(
     let result = complete_simple(&provider, &body).await?;
     Ok(result.content)
 }
+
+/// Benchmark time-to-first-token and total latency across providers concurrently.
+/// Each provider is measured independently; failures are reported per row.
+#[tauri::command]
+pub async fn providers_benchmark(
+    targets: Option<Vec<BenchmarkTarget>>,
+    timeout_secs: Option<u64>,
+) -> Result<ProvidersBenchmark, String> {
+    let targets = targets
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(default_benchmark_targets);
+    if targets.is_empty() {
+        return Err("No providers configured to benchmark".to_string());
+    }
+
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(BENCHMARK_DEFAULT_TIMEOUT_SECS));
+    info!("[benchmark] Measuring {} providers (timeout {}s)", targets.len(), timeout.as_secs());
+
+    let mut results = futures::future::join_all(
+        targets.into_iter().map(|t| benchmark_target(t, timeout)),
+    )
+    .await;
+    results.sort_by_key(|r| (r.ttft_ms.is_none(), r.ttft_ms.unwrap_or(u64::MAX)));
+
+    let table = format_benchmark_table(&results);
+    Ok(ProvidersBenchmark { results, table })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_line_text() {
+        let openai = r#"data: {"choices":[{"delta":{"content":"po"}}]}"#;
+        assert_eq!(stream_line_text(&ProviderKind::OpenAI, openai).as_deref(), Some("po"));
+        assert_eq!(stream_line_text(&ProviderKind::OpenAI, "data: [DONE]"), None);
+
+        let anthropic = r#"data: {"type":"content_block_delta","delta":{"type":"text_delta","text":"ng"}}"#;
+        assert_eq!(stream_line_text(&ProviderKind::Anthropic, anthropic).as_deref(), Some("ng"));
+        assert_eq!(stream_line_text(&ProviderKind::Anthropic, r#"data: {"type":"ping"}"#), None);
+
+        let google = r#"data: {"candidates":[{"content":{"parts":[{"text":"pong"}]}}]}"#;
+        assert_eq!(stream_line_text(&ProviderKind::Google, google).as_deref(), Some("pong"));
+
+        let ollama = r#"{"message":{"role":"assistant","content":"pong"},"done":false}"#;
+        assert_eq!(stream_line_text(&ProviderKind::Ollama, ollama).as_deref(), Some("pong"));
+    }
+    #[test]
+    fn test_openai_sse_across_chunks() {
+        let events = std::cell::RefCell::new(Vec::new());
+        let on_event = |e: StreamEvent| events.borrow_mut().push(e);
+        let mut state = SseState::default();
+        let mut pending = Vec::new();
+        // "你好" split mid-character, and a line split across chunks
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"你好\"}}]}\n\ndata: [DONE]\n".as_bytes();
+        for chunk in [&stream[..40], &stream[40..52], &stream[52..]] {
+            pending.extend_from_slice(chunk);
+            while let Some(line) = take_line(&mut pending) {
+                handle_openai_sse_line(&line, &mut state, &on_event);
+            }
+        }
+        assert!(pending.is_empty());
+        assert_eq!(state.content, "你好");
+        assert!(matches!(&events.borrow()[..], [StreamEvent::Delta { text }] if text == "你好"));
+    }
+}
@@ -7,8 +7,11 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
 use tracing::info;
 
+use crate::models::config::ModelPrice;
 use crate::modules::config::get_data_dir;
 
 // ============================================================================
@@ -51,6 +54,15 @@ pub struct ModelUsage {
     pub cost_usd: f64,
 }
 
+/// Per-session breakdown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionUsage {
+    pub session_key: String,
+    pub request_count: i64,
+    pub total_tokens: i64,
+    pub cost_usd: f64,
+}
+
 /// Per-day breakdown.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyUsage {
@@ -62,6 +74,45 @@ pub struct DailyUsage {
     pub cost_usd: f64,
 }
 
+/// Usage aggregated by day of week (local time, 0 = Sunday).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DayUsage {
+    pub day: u32,
+    pub total_tokens: i64,
+    pub total_cost_usd: f64,
+    pub request_count: i64,
+}
+
+/// Usage aggregated by hour of day (local time, 0–23).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HourUsage {
+    pub hour: u32,
+    pub total_tokens: i64,
+    pub request_count: i64,
+}
+
+/// Result of a CSV export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageCsvExport {
+    pub path: String,
+    /// Data rows written (excluding header and total)
+    pub rows: usize,
+    pub estimated_cost_usd: f64,
+}
+
+/// End-of-day summary sent by the daily usage report job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDailyReport {
+    /// Local date covered (YYYY-MM-DD)
+    pub date: String,
+    pub totals: UsageTotals,
+    pub top_models: Vec<ModelUsage>,
+    pub top_sessions: Vec<SessionUsage>,
+    /// Rendered notification title and markdown body
+    pub title: String,
+    pub body: String,
+}
+
 /// Complete usage dashboard data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageDashboard {
@@ -81,8 +132,29 @@ pub struct UsageDashboard {
 // Cost Estimation
 // ============================================================================
 
+/// Price overrides from config: the longest matching model-name prefix wins.
+fn configured_pricing(model: &str, overrides: &HashMap<String, ModelPrice>) -> Option<(f64, f64)> {
+    let m = model.to_lowercase();
+    overrides
+        .iter()
+        .filter(|(prefix, _)| m.starts_with(&prefix.to_lowercase()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, p)| (p.input_per_m, p.output_per_m))
+}
+
+fn load_pricing_overrides() -> HashMap<String, ModelPrice> {
+    crate::modules::config::load_app_config()
+        .map(|c| c.model_pricing)
+        .unwrap_or_default()
+}
+
+/// Cost per 1M tokens (input, output): configured overrides, then built-in defaults.
+fn model_pricing_with(model: &str, overrides: &HashMap<String, ModelPrice>) -> (f64, f64) {
+    configured_pricing(model, overrides).unwrap_or_else(|| default_model_pricing(model))
+}
+
 /// Estimated cost per 1M tokens (input, output) for common models.
-fn model_pricing(model: &str) -> (f64, f64) {
+fn default_model_pricing(model: &str) -> (f64, f64) {
     let m = model.to_lowercase();
 
     // OpenAI
@@ -125,7 +197,16 @@ fn model_pricing(model: &str) -> (f64, f64) {
 
 /// Calculate estimated cost in USD.
 pub fn estimate_cost(model: &str, prompt_tokens: u32, completion_tokens: u32) -> f64 {
-    let (input_per_m, output_per_m) = model_pricing(model);
+    estimate_cost_with(model, prompt_tokens as i64, completion_tokens as i64, &load_pricing_overrides())
+}
+
+fn estimate_cost_with(
+    model: &str,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    overrides: &HashMap<String, ModelPrice>,
+) -> f64 {
+    let (input_per_m, output_per_m) = model_pricing_with(model, overrides);
     let input_cost = (prompt_tokens as f64 / 1_000_000.0) * input_per_m;
     let output_cost = (completion_tokens as f64 / 1_000_000.0) * output_per_m;
     input_cost + output_cost
@@ -212,7 +293,7 @@ pub fn record_usage(
 // ============================================================================
 
 /// Get lifetime totals.
-fn query_totals(where_clause: &str) -> Result<UsageTotals, String> {
+fn query_totals<P: rusqlite::Params>(where_clause: &str, params: P) -> Result<UsageTotals, String> {
     let conn = USAGE_DB.lock();
     let sql = format!(
         "SELECT COUNT(*), COALESCE(SUM(prompt_tokens),0), COALESCE(SUM(completion_tokens),0),
@@ -220,7 +301,7 @@ fn query_totals(where_clause: &str) -> Result<UsageTotals, String> {
          FROM usage_log {}",
         where_clause
     );
-    conn.query_row(&sql, [], |r| {
+    conn.query_row(&sql, params, |r| {
         Ok(UsageTotals {
             total_requests: r.get(0)?,
             total_prompt_tokens: r.get(1)?,
@@ -234,12 +315,12 @@ fn query_totals(where_clause: &str) -> Result<UsageTotals, String> {
 
 /// Get lifetime totals (all time).
 pub fn get_lifetime_totals() -> Result<UsageTotals, String> {
-    query_totals("")
+    query_totals("", [])
 }
 
 /// Get today's totals.
 pub fn get_today_totals() -> Result<UsageTotals, String> {
-    query_totals("WHERE date(created_at) = date('now')")
+    query_totals("WHERE date(created_at) = date('now')", [])
 }
 
 /// Get totals for a specific session.
@@ -265,18 +346,27 @@ pub fn get_session_totals(session_key: &str) -> Result<UsageTotals, String> {
 
 /// Get per-model breakdown.
 pub fn get_model_breakdown() -> Result<Vec<ModelUsage>, String> {
+    query_model_breakdown("", [], -1)
+}
+
+/// Per-model breakdown of rows matching `where_clause`, heaviest first (`limit` -1 = all).
+fn query_model_breakdown<P: rusqlite::Params>(
+    where_clause: &str,
+    params: P,
+    limit: i64,
+) -> Result<Vec<ModelUsage>, String> {
     let conn = USAGE_DB.lock();
-    let mut stmt = conn
-        .prepare(
-            "SELECT model, provider, COUNT(*), COALESCE(SUM(prompt_tokens),0),
-             COALESCE(SUM(completion_tokens),0), COALESCE(SUM(total_tokens),0),
-             COALESCE(SUM(cost_usd),0.0)
-             FROM usage_log GROUP BY model, provider ORDER BY SUM(total_tokens) DESC",
-        )
-        .map_err(|e| format!("prepare: {}", e))?;
+    let sql = format!(
+        "SELECT model, provider, COUNT(*), COALESCE(SUM(prompt_tokens),0),
+         COALESCE(SUM(completion_tokens),0), COALESCE(SUM(total_tokens),0),
+         COALESCE(SUM(cost_usd),0.0)
+         FROM usage_log {} GROUP BY model, provider ORDER BY SUM(total_tokens) DESC LIMIT {}",
+        where_clause, limit
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("prepare: {}", e))?;
 
     let rows = stmt
-        .query_map([], |r| {
+        .query_map(params, |r| {
             Ok(ModelUsage {
                 model: r.get(0)?,
                 provider: r.get(1)?,
@@ -294,6 +384,11 @@ pub fn get_model_breakdown() -> Result<Vec<ModelUsage>, String> {
     Ok(rows)
 }
 
+/// Per-model breakdown of today's usage (UTC day, like `get_today_totals`).
+pub fn get_today_model_breakdown() -> Result<Vec<ModelUsage>, String> {
+    query_model_breakdown("WHERE date(created_at) = date('now')", [], -1)
+}
+
 /// Get daily usage for the last N days.
 pub fn get_daily_usage(days: i64) -> Result<Vec<DailyUsage>, String> {
     let conn = USAGE_DB.lock();
@@ -360,6 +455,195 @@ pub fn get_recent_usage(limit: i64) -> Result<Vec<UsageEntry>, String> {
     Ok(entries)
 }
 
+// ============================================================================
+// Usage Patterns
+// ============================================================================
+
+/// (bucket, requests, tokens, cost) for each `strftime` bucket of local time,
+/// optionally limited to local dates `start_date..=end_date` (YYYY-MM-DD).
+fn query_time_buckets(
+    bucket_format: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+) -> Result<Vec<(u32, i64, i64, f64)>, String> {
+    let start = start_date.map(|d| parse_export_date(d, "start_date")).transpose()?;
+    let end = end_date.map(|d| parse_export_date(d, "end_date")).transpose()?;
+
+    let conn = USAGE_DB.lock();
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(strftime(?1, created_at, 'localtime') AS INTEGER), COUNT(*),
+             COALESCE(SUM(total_tokens),0), COALESCE(SUM(cost_usd),0.0)
+             FROM usage_log
+             WHERE (?2 IS NULL OR date(created_at, 'localtime') >= ?2)
+               AND (?3 IS NULL OR date(created_at, 'localtime') <= ?3)
+             GROUP BY 1",
+        )
+        .map_err(|e| format!("prepare: {}", e))?;
+
+    let rows = stmt
+        .query_map(
+            params![bucket_format, start.map(|d| d.to_string()), end.map(|d| d.to_string())],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .map_err(|e| format!("query: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("collect: {}", e))?;
+
+    Ok(rows)
+}
+
+/// Spread bucket rows over `0..count`, filling buckets with no usage with zeros.
+fn fill_buckets(rows: &[(u32, i64, i64, f64)], count: u32) -> Vec<(u32, i64, i64, f64)> {
+    (0..count)
+        .map(|b| {
+            rows.iter()
+                .find(|r| r.0 == b)
+                .copied()
+                .unwrap_or((b, 0, 0, 0.0))
+        })
+        .collect()
+}
+
+/// Usage per day of week (0 = Sunday … 6 = Saturday), all seven days.
+pub fn get_usage_by_day_of_week(start_date: Option<&str>, end_date: Option<&str>) -> Result<Vec<DayUsage>, String> {
+    let rows = query_time_buckets("%w", start_date, end_date)?;
+    Ok(fill_buckets(&rows, 7)
+        .into_iter()
+        .map(|(day, requests, tokens, cost)| DayUsage {
+            day,
+            total_tokens: tokens,
+            total_cost_usd: cost,
+            request_count: requests,
+        })
+        .collect())
+}
+
+/// Usage per hour of day (0–23), all 24 hours.
+pub fn get_usage_by_hour_of_day(start_date: Option<&str>, end_date: Option<&str>) -> Result<Vec<HourUsage>, String> {
+    let rows = query_time_buckets("%H", start_date, end_date)?;
+    Ok(fill_buckets(&rows, 24)
+        .into_iter()
+        .map(|(hour, requests, tokens, _)| HourUsage {
+            hour,
+            total_tokens: tokens,
+            request_count: requests,
+        })
+        .collect())
+}
+
+// ============================================================================
+// CSV Export
+// ============================================================================
+
+/// Quote a CSV field when it contains a delimiter, quote or newline (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_export_date(value: &str, name: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid '{}' date '{}': expected YYYY-MM-DD", name, value))
+}
+
+/// Write per-day, per-model usage between `from` and `to` (inclusive, UTC dates)
+/// to a CSV file. Rows are streamed from SQLite straight to disk; the estimated
+/// cost is recomputed with the current (configurable) pricing.
+pub fn export_usage_csv(from: &str, to: &str, path: &str) -> Result<UsageCsvExport, String> {
+    let from = parse_export_date(from, "from")?;
+    let to = parse_export_date(to, "to")?;
+    if from > to {
+        return Err(format!("'from' ({}) is after 'to' ({})", from, to));
+    }
+
+    let target = std::path::PathBuf::from(crate::modules::agent_tools::expand_path(path));
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("create dir: {}", e))?;
+    }
+    let file = std::fs::File::create(&target).map_err(|e| format!("create csv: {}", e))?;
+    let mut out = std::io::BufWriter::new(file);
+    let write_err = |e: std::io::Error| format!("write csv: {}", e);
+
+    writeln!(
+        out,
+        "date,model,provider,requests,prompt_tokens,completion_tokens,total_tokens,recorded_cost_usd,estimated_cost_usd"
+    )
+    .map_err(write_err)?;
+
+    let overrides = load_pricing_overrides();
+    let conn = USAGE_DB.lock();
+    let mut stmt = conn
+        .prepare(
+            "SELECT date(created_at), model, provider, COUNT(*), COALESCE(SUM(prompt_tokens),0),
+             COALESCE(SUM(completion_tokens),0), COALESCE(SUM(total_tokens),0),
+             COALESCE(SUM(cost_usd),0.0)
+             FROM usage_log
+             WHERE date(created_at) BETWEEN ?1 AND ?2
+             GROUP BY date(created_at), model, provider
+             ORDER BY date(created_at), model, provider",
+        )
+        .map_err(|e| format!("prepare: {}", e))?;
+    let mut rows = stmt
+        .query(params![from.to_string(), to.to_string()])
+        .map_err(|e| format!("query: {}", e))?;
+
+    let mut count = 0usize;
+    let mut total = (0i64, 0i64, 0i64, 0i64, 0.0f64, 0.0f64);
+    while let Some(r) = rows.next().map_err(|e| format!("query: {}", e))? {
+        let date: String = r.get(0).map_err(|e| format!("row: {}", e))?;
+        let model: String = r.get(1).map_err(|e| format!("row: {}", e))?;
+        let provider: String = r.get(2).map_err(|e| format!("row: {}", e))?;
+        let requests: i64 = r.get(3).map_err(|e| format!("row: {}", e))?;
+        let prompt: i64 = r.get(4).map_err(|e| format!("row: {}", e))?;
+        let completion: i64 = r.get(5).map_err(|e| format!("row: {}", e))?;
+        let tokens: i64 = r.get(6).map_err(|e| format!("row: {}", e))?;
+        let recorded: f64 = r.get(7).map_err(|e| format!("row: {}", e))?;
+        let estimated = estimate_cost_with(&model, prompt, completion, &overrides);
+
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{:.6},{:.6}",
+            date,
+            csv_field(&model),
+            csv_field(&provider),
+            requests,
+            prompt,
+            completion,
+            tokens,
+            recorded,
+            estimated
+        )
+        .map_err(write_err)?;
+
+        count += 1;
+        total.0 += requests;
+        total.1 += prompt;
+        total.2 += completion;
+        total.3 += tokens;
+        total.4 += recorded;
+        total.5 += estimated;
+    }
+
+    writeln!(
+        out,
+        "TOTAL,,,{},{},{},{},{:.6},{:.6}",
+        total.0, total.1, total.2, total.3, total.4, total.5
+    )
+    .map_err(write_err)?;
+    out.flush().map_err(write_err)?;
+
+    info!("[usage] Exported {} rows ({} – {}) to {}", count, from, to, target.display());
+    Ok(UsageCsvExport {
+        path: target.display().to_string(),
+        rows: count,
+        estimated_cost_usd: total.5,
+    })
+}
+
 /// Build the complete dashboard data.
 pub fn get_dashboard(recent_limit: i64, daily_days: i64) -> Result<UsageDashboard, String> {
     Ok(UsageDashboard {
@@ -371,6 +655,136 @@ pub fn get_dashboard(recent_limit: i64, daily_days: i64) -> Result<UsageDashboar
     })
 }
 
+// ============================================================================
+// Daily Report
+// ============================================================================
+
+/// Models and sessions listed in the daily report.
+const DAILY_REPORT_TOP_N: i64 = 3;
+
+const DAILY_REPORT_TITLE: &str = "📊 Helix 用量日报 {{ReportDate}}";
+const DAILY_REPORT_TEMPLATE: &str = "**Tokens**: {{TotalTokens}}（{{Requests}} 次请求）\n\
+**费用**: ${{Cost}}\n\n\
+**Top 模型**\n{{TopModels}}\n\n\
+**Top 会话**\n{{TopSessions}}";
+
+/// UTC `created_at` bounds (inclusive start, exclusive end) of a local calendar day.
+fn local_day_bounds(date: chrono::NaiveDate) -> (String, String) {
+    use chrono::TimeZone;
+    let to_utc = |d: chrono::NaiveDate| {
+        let midnight = d.and_hms_opt(0, 0, 0).unwrap_or_default();
+        chrono::Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|t| t.naive_utc())
+            .unwrap_or(midnight)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+    (to_utc(date), to_utc(date.succ_opt().unwrap_or(date)))
+}
+
+fn query_top_sessions(from: &str, to: &str, limit: i64) -> Result<Vec<SessionUsage>, String> {
+    let conn = USAGE_DB.lock();
+    let mut stmt = conn
+        .prepare(
+            "SELECT session_key, COUNT(*), COALESCE(SUM(total_tokens),0), COALESCE(SUM(cost_usd),0.0)
+             FROM usage_log
+             WHERE created_at >= ?1 AND created_at < ?2
+             GROUP BY session_key ORDER BY SUM(total_tokens) DESC LIMIT ?3",
+        )
+        .map_err(|e| format!("prepare: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![from, to, limit], |r| {
+            Ok(SessionUsage {
+                session_key: r.get(0)?,
+                request_count: r.get(1)?,
+                total_tokens: r.get(2)?,
+                cost_usd: r.get(3)?,
+            })
+        })
+        .map_err(|e| format!("query: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("collect: {}", e))?;
+
+    Ok(rows)
+}
+
+/// Render the report title and body through the messaging template engine.
+fn render_daily_report(
+    date: &str,
+    totals: &UsageTotals,
+    top_models: &[ModelUsage],
+    top_sessions: &[SessionUsage],
+) -> (String, String) {
+    use crate::modules::messaging::{apply_template, TemplateContext};
+
+    let ranked = |lines: Vec<String>| {
+        if lines.is_empty() {
+            "-".to_string()
+        } else {
+            lines
+                .iter()
+                .enumerate()
+                .map(|(i, l)| format!("{}. {}", i + 1, l))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    };
+    let models = ranked(
+        top_models
+            .iter()
+            .map(|m| format!("{} — {} tokens · ${:.4}", m.model, m.total_tokens, m.cost_usd))
+            .collect(),
+    );
+    let sessions = ranked(
+        top_sessions
+            .iter()
+            .map(|s| format!("`{}` — {} tokens · ${:.4}", s.session_key, s.total_tokens, s.cost_usd))
+            .collect(),
+    );
+
+    let mut ctx = TemplateContext::default();
+    ctx.custom.insert("ReportDate".to_string(), date.to_string());
+    ctx.custom.insert("TotalTokens".to_string(), totals.total_tokens.to_string());
+    ctx.custom.insert("Requests".to_string(), totals.total_requests.to_string());
+    ctx.custom.insert("Cost".to_string(), format!("{:.4}", totals.total_cost_usd));
+    ctx.custom.insert("TopModels".to_string(), models);
+    ctx.custom.insert("TopSessions".to_string(), sessions);
+
+    (apply_template(DAILY_REPORT_TITLE, &ctx), apply_template(DAILY_REPORT_TEMPLATE, &ctx))
+}
+
+/// Assemble the usage summary for a local calendar day. None when nothing
+/// was used that day (the report is skipped).
+pub fn usage_daily_report(date: chrono::NaiveDate) -> Result<Option<UsageDailyReport>, String> {
+    let (from, to) = local_day_bounds(date);
+    let range = "WHERE created_at >= ?1 AND created_at < ?2";
+    let totals = query_totals(range, params![from, to])?;
+    if totals.total_requests == 0 {
+        return Ok(None);
+    }
+    let top_models = query_model_breakdown(range, params![from, to], DAILY_REPORT_TOP_N)?;
+    let top_sessions = query_top_sessions(&from, &to, DAILY_REPORT_TOP_N)?;
+
+    let date = date.to_string();
+    let (title, body) = render_daily_report(&date, &totals, &top_models, &top_sessions);
+    Ok(Some(UsageDailyReport { date, totals, top_models, top_sessions, title, body }))
+}
+
+/// Send today's report to the configured channel. Returns a line for the cron run log.
+pub async fn send_daily_report() -> Result<String, String> {
+    let config = crate::modules::config::load_app_config()?.usage_report;
+    let today = chrono::Local::now().date_naive();
+    let Some(report) = usage_daily_report(today)? else {
+        return Ok(format!("No usage on {}, report skipped", today));
+    };
+    crate::modules::notifications::send_notification(&config.channel, &report.title, &report.body).await?;
+    info!("[usage] Sent daily report for {} to {}", report.date, config.channel);
+    Ok(format!("Sent usage report for {} to {}", report.date, config.channel))
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -420,8 +834,100 @@ pub async fn usage_log(limit: Option<i64>) -> Result<Vec<UsageEntry>, String> {
     get_recent_usage(limit.unwrap_or(50))
 }
 
+/// Usage by day of week (local time), optionally within `start_date..=end_date`
+#[tauri::command]
+pub async fn usage_by_day_of_week(
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<DayUsage>, String> {
+    get_usage_by_day_of_week(start_date.as_deref(), end_date.as_deref())
+}
+
+/// Usage by hour of day (local time), optionally within `start_date..=end_date`
+#[tauri::command]
+pub async fn usage_by_hour_of_day(
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<HourUsage>, String> {
+    get_usage_by_hour_of_day(start_date.as_deref(), end_date.as_deref())
+}
+
+/// Export per-day, per-model usage for `from`..=`to` (YYYY-MM-DD) to a CSV file
+#[tauri::command]
+pub async fn usage_export_csv(from: String, to: String, path: String) -> Result<UsageCsvExport, String> {
+    export_usage_csv(&from, &to, &path)
+}
+
+/// Today's daily report as it would be sent (null when there was no usage)
+#[tauri::command]
+pub async fn usage_daily_report_preview() -> Result<Option<UsageDailyReport>, String> {
+    usage_daily_report(chrono::Local::now().date_naive())
+}
+
 /// Estimate cost for given tokens
 #[tauri::command]
 pub async fn usage_estimate_cost(model: String, prompt_tokens: u32, completion_tokens: u32) -> Result<f64, String> {
     Ok(estimate_cost(&model, prompt_tokens, completion_tokens))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("gpt-4o"), "gpt-4o");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn test_configured_pricing_overrides_defaults() {
+        let mut overrides = HashMap::new();
+        overrides.insert("gpt-4o".to_string(), ModelPrice { input_per_m: 1.0, output_per_m: 2.0 });
+        overrides.insert("GPT-4o-mini".to_string(), ModelPrice { input_per_m: 0.1, output_per_m: 0.2 });
+
+        // Longest prefix wins, case-insensitively
+        assert_eq!(model_pricing_with("gpt-4o-mini-2024", &overrides), (0.1, 0.2));
+        assert_eq!(model_pricing_with("gpt-4o", &overrides), (1.0, 2.0));
+        // Unconfigured models fall back to the built-in table
+        assert_eq!(model_pricing_with("deepseek-chat", &overrides), default_model_pricing("deepseek-chat"));
+
+        let cost = estimate_cost_with("gpt-4o", 1_000_000, 500_000, &overrides);
+        assert!((cost - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_buckets() {
+        let rows = vec![(2, 3, 300, 0.5), (0, 1, 100, 0.1)];
+        let filled = fill_buckets(&rows, 4);
+        assert_eq!(filled, vec![(0, 1, 100, 0.1), (1, 0, 0, 0.0), (2, 3, 300, 0.5), (3, 0, 0, 0.0)]);
+    }
+
+    #[test]
+    fn test_render_daily_report() {
+        let totals = UsageTotals {
+            total_requests: 12,
+            total_prompt_tokens: 9000,
+            total_completion_tokens: 3000,
+            total_tokens: 12000,
+            total_cost_usd: 0.0425,
+        };
+        let models = vec![ModelUsage {
+            model: "gpt-4o".to_string(),
+            provider: "openai".to_string(),
+            request_count: 12,
+            prompt_tokens: 9000,
+            completion_tokens: 3000,
+            total_tokens: 12000,
+            cost_usd: 0.0425,
+        }];
+        let (title, body) = render_daily_report("2024-05-01", &totals, &models, &[]);
+        assert_eq!(title, "📊 Helix 用量日报 2024-05-01");
+        assert!(body.starts_with("**Tokens**: 12000（12 次请求）\n**费用**: $0.0425\n"));
+        assert!(body.contains("**Top 模型**\n1. gpt-4o — 12000 tokens · $0.0425\n"));
+        assert!(body.ends_with("**Top 会话**\n-"));
+        assert!(!body.contains("{{"));
+    }
+}
@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio::sync::{oneshot, RwLock};
+use tracing::{debug, info, warn};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -17,6 +19,15 @@ const DETACHED_PROCESS: u32 = 0x00000008;
 #[cfg(target_os = "windows")]
 const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
 
+/// 快速隧道 / Token 隧道（`CloudflaredConfig` 顶层配置）使用的隧道 ID
+pub const DEFAULT_TUNNEL_ID: &str = "default";
+/// 崩溃重启的初始退避时间
+const RESTART_BACKOFF_MIN: Duration = Duration::from_secs(2);
+/// 崩溃重启的最大退避时间
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(120);
+/// 运行超过该时长后视为稳定，退避时间重置
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
 /// Cloudflared隧道模式
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -48,6 +59,9 @@ pub struct CloudflaredConfig {
     /// 使用http2协议(更兼容)
     #[serde(default)]
     pub use_http2: bool,
+    /// 命名隧道（可同时运行多个）
+    #[serde(default)]
+    pub tunnels: Vec<NamedTunnelConfig>,
 }
 
 impl Default for CloudflaredConfig {
@@ -58,39 +72,137 @@ impl Default for CloudflaredConfig {
             port: 8045,
             token: None,
             use_http2: true, // 默认启用http2，更稳定
+            tunnels: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 命名隧道的 hostname → 本地端口映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngressRule {
+    pub hostname: String,
+    pub local_port: u16,
+}
+
+/// 命名隧道配置
+///
+/// 两种认证方式二选一：
+/// - `credentials_json`：`cloudflared tunnel create` 生成的凭证文件内容，ingress 由本地配置生成
+/// - `token`：Dashboard 远程管理的隧道 Token，ingress 在 Dashboard 配置，这里的映射仅用于展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedTunnelConfig {
+    /// 本地唯一标识
+    pub id: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub credentials_json: Option<String>,
+    #[serde(default)]
+    pub ingress: Vec<IngressRule>,
+    #[serde(default = "default_true")]
+    pub use_http2: bool,
+    /// 进程崩溃后自动重启（指数退避）
+    #[serde(default = "default_true")]
+    pub auto_restart: bool,
+}
+
+impl NamedTunnelConfig {
+    /// 校验配置
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Tunnel id is required".to_string());
+        }
+        if self.id == DEFAULT_TUNNEL_ID {
+            return Err(format!("Tunnel id '{}' is reserved", DEFAULT_TUNNEL_ID));
+        }
+        if !self.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err("Tunnel id may only contain letters, digits, '-' and '_'".to_string());
         }
+        let has_token = self.token.as_deref().is_some_and(|t| !t.trim().is_empty());
+        let has_credentials = self.credentials_json.as_deref().is_some_and(|c| !c.trim().is_empty());
+        if !has_token && !has_credentials {
+            return Err("Named tunnel requires a token or credentials JSON".to_string());
+        }
+        if has_credentials {
+            parse_tunnel_uuid(self.credentials_json.as_deref().unwrap_or_default())?;
+            if self.ingress.is_empty() {
+                return Err("Credentials-based tunnel requires at least one hostname mapping".to_string());
+            }
+        }
+        for rule in &self.ingress {
+            if rule.hostname.trim().is_empty() || rule.local_port == 0 {
+                return Err(format!("Invalid mapping: {} → {}", rule.hostname, rule.local_port));
+            }
+        }
+        Ok(())
     }
 }
 
-/// Cloudflared状态
+/// Cloudflared状态（每个隧道一份）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CloudflaredStatus {
+    #[serde(default)]
+    pub tunnel_id: String,
     pub installed: bool,
     pub version: Option<String>,
     pub running: bool,
     pub url: Option<String>,
     pub error: Option<String>,
+    /// 映射的 hostname（命名隧道）
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    /// 已注册到 Cloudflare 边缘的连接数（> 0 表示健康）
+    #[serde(default)]
+    pub connections: u32,
+    /// 自动重启次数
+    #[serde(default)]
+    pub restarts: u32,
 }
 
 impl Default for CloudflaredStatus {
     fn default() -> Self {
         Self {
+            tunnel_id: DEFAULT_TUNNEL_ID.to_string(),
             installed: false,
             version: None,
             running: false,
             url: None,
             error: None,
+            hostnames: Vec::new(),
+            connections: 0,
+            restarts: 0,
         }
     }
 }
 
+/// 单个隧道的运行句柄
+struct TunnelHandle {
+    status: Arc<RwLock<CloudflaredStatus>>,
+    /// 通知监控任务停止（发送或丢弃都会让进程被终止）
+    shutdown_tx: Option<oneshot::Sender<()>>,
+}
+
+/// 启动一个隧道进程所需的参数
+#[derive(Clone)]
+struct TunnelLaunch {
+    tunnel_id: String,
+    args: Vec<String>,
+    hostnames: Vec<String>,
+    /// 快速隧道每次重启 URL 都会变化
+    ephemeral_url: bool,
+    auto_restart: bool,
+}
+
 /// Cloudflared管理器状态
 pub struct CloudflaredManager {
-    process: Arc<RwLock<Option<Child>>>,
-    status: Arc<RwLock<CloudflaredStatus>>,
     bin_path: PathBuf,
-    /// 用于通知进程监控任务停止
-    shutdown_tx: RwLock<Option<tokio::sync::oneshot::Sender<()>>>,
+    /// 命名隧道的凭证 / 配置文件目录
+    tunnels_dir: PathBuf,
+    tunnels: Arc<RwLock<HashMap<String, TunnelHandle>>>,
 }
 
 impl CloudflaredManager {
@@ -103,10 +215,9 @@ impl CloudflaredManager {
         let bin_path = data_dir.join("bin").join(bin_name);
 
         Self {
-            process: Arc::new(RwLock::new(None)),
-            status: Arc::new(RwLock::new(CloudflaredStatus::default())),
             bin_path,
-            shutdown_tx: RwLock::new(None),
+            tunnels_dir: data_dir.join("cloudflared"),
+            tunnels: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -137,15 +248,46 @@ impl CloudflaredManager {
         }
     }
 
-    /// 获取当前状态
-    pub async fn get_status(&self) -> CloudflaredStatus {
-        self.status.read().await.clone()
+    /// 获取指定隧道的状态
+    pub async fn get_status(&self, tunnel_id: &str) -> CloudflaredStatus {
+        let (installed, version) = self.check_installed().await;
+        let mut status = match self.tunnels.read().await.get(tunnel_id) {
+            Some(handle) => handle.status.read().await.clone(),
+            None => CloudflaredStatus {
+                tunnel_id: tunnel_id.to_string(),
+                ..CloudflaredStatus::default()
+            },
+        };
+        status.installed = installed;
+        status.version = version;
+        if !installed {
+            status.running = false;
+            status.url = None;
+        }
+        status
     }
 
-    /// 更新状态
-    async fn update_status(&self, f: impl FnOnce(&mut CloudflaredStatus)) {
-        let mut status = self.status.write().await;
-        f(&mut status);
+    /// 列出默认隧道、所有已配置的命名隧道及其运行状态
+    pub async fn list(&self, configured: &[NamedTunnelConfig]) -> Vec<CloudflaredStatus> {
+        let mut ids = vec![DEFAULT_TUNNEL_ID.to_string()];
+        ids.extend(configured.iter().map(|t| t.id.clone()));
+        for id in self.tunnels.read().await.keys() {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+
+        let mut result = Vec::with_capacity(ids.len());
+        for id in ids {
+            let mut status = self.get_status(&id).await;
+            if status.hostnames.is_empty() {
+                if let Some(cfg) = configured.iter().find(|t| t.id == id) {
+                    status.hostnames = cfg.ingress.iter().map(|r| r.hostname.clone()).collect();
+                }
+            }
+            result.push(status);
+        }
+        result
     }
 
     /// 安装cloudflared
@@ -204,197 +346,361 @@ impl CloudflaredManager {
                 .map_err(|e| format!("Failed to set permissions: {}", e))?;
         }
 
-        let (installed, version) = self.check_installed().await;
-        self.update_status(|s| {
-            s.installed = installed;
-            s.version = version.clone();
-        }).await;
-
-        info!("[cloudflared] Installed successfully, version: {:?}", version);
-        Ok(self.get_status().await)
+        info!("[cloudflared] Installed successfully");
+        Ok(self.get_status(DEFAULT_TUNNEL_ID).await)
     }
 
-    /// 启动隧道
+    /// 启动默认隧道（快速隧道或 Token 隧道）
     pub async fn start(&self, config: CloudflaredConfig) -> Result<CloudflaredStatus, String> {
-        // 检查是否已在运行
-        {
-            let proc = self.process.read().await;
-            if proc.is_some() {
-                return Ok(self.get_status().await);
+        let local_url = format!("http://localhost:{}", config.port);
+        let mut args: Vec<String> = vec!["tunnel".into()];
+
+        match config.mode {
+            TunnelMode::Quick => {
+                info!("[cloudflared] Starting quick tunnel to: {}", local_url);
+                args.push("--url".into());
+                args.push(local_url);
+                // 注意：--no-autoupdate 和 --loglevel 参数在较新版本的 cloudflared 中会导致进程立即退出，故不传
+            }
+            TunnelMode::Auth => {
+                let token = config
+                    .token
+                    .filter(|t| !t.is_empty())
+                    .ok_or_else(|| "Token required for auth mode".to_string())?;
+                info!("[cloudflared] Starting token tunnel (tunnel run --token [HIDDEN])");
+                args.push("run".into());
+                args.push("--token".into());
+                args.push(token);
             }
         }
+        if config.use_http2 {
+            args.push("--protocol".into());
+            args.push("http2".into());
+        }
+
+        self.start_tunnel(TunnelLaunch {
+            tunnel_id: DEFAULT_TUNNEL_ID.to_string(),
+            args,
+            hostnames: Vec::new(),
+            ephemeral_url: config.mode == TunnelMode::Quick,
+            auto_restart: true,
+        })
+        .await
+    }
+
+    /// 启动命名隧道
+    pub async fn start_named(&self, tunnel: &NamedTunnelConfig) -> Result<CloudflaredStatus, String> {
+        tunnel.validate()?;
+        let hostnames: Vec<String> = tunnel.ingress.iter().map(|r| r.hostname.clone()).collect();
+        let mut args: Vec<String> = vec!["tunnel".into()];
+        if tunnel.use_http2 {
+            args.push("--protocol".into());
+            args.push("http2".into());
+        }
 
-        // 停止之前的监控任务
-        if let Some(tx) = self.shutdown_tx.write().await.take() {
-            let _ = tx.send(());
+        match tunnel.credentials_json.as_deref().filter(|c| !c.trim().is_empty()) {
+            Some(credentials) => {
+                let config_path = self.write_tunnel_files(tunnel, credentials)?;
+                info!("[cloudflared:{}] Starting named tunnel with credentials", tunnel.id);
+                args.push("--config".into());
+                args.push(config_path.display().to_string());
+                args.push("run".into());
+            }
+            None => {
+                info!("[cloudflared:{}] Starting named tunnel (run --token [HIDDEN])", tunnel.id);
+                args.push("run".into());
+                args.push("--token".into());
+                args.push(tunnel.token.clone().unwrap_or_default());
+            }
         }
 
+        self.start_tunnel(TunnelLaunch {
+            tunnel_id: tunnel.id.clone(),
+            args,
+            hostnames,
+            ephemeral_url: false,
+            auto_restart: tunnel.auto_restart,
+        })
+        .await
+    }
+
+    /// 写入凭证文件和 ingress 配置，返回配置文件路径
+    fn write_tunnel_files(&self, tunnel: &NamedTunnelConfig, credentials: &str) -> Result<PathBuf, String> {
+        let tunnel_uuid = parse_tunnel_uuid(credentials)?;
+        std::fs::create_dir_all(&self.tunnels_dir)
+            .map_err(|e| format!("Failed to create tunnels directory: {}", e))?;
+
+        let credentials_path = self.tunnels_dir.join(format!("{}.json", tunnel.id));
+        std::fs::write(&credentials_path, credentials)
+            .map_err(|e| format!("Failed to write tunnel credentials: {}", e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&credentials_path, std::fs::Permissions::from_mode(0o600));
+        }
+
+        let config_path = self.tunnels_dir.join(format!("{}.yml", tunnel.id));
+        let yaml = build_ingress_config(&tunnel_uuid, &credentials_path, &tunnel.ingress)?;
+        std::fs::write(&config_path, yaml)
+            .map_err(|e| format!("Failed to write tunnel config: {}", e))?;
+        Ok(config_path)
+    }
+
+    /// 启动隧道进程并交给监控任务管理
+    async fn start_tunnel(&self, launch: TunnelLaunch) -> Result<CloudflaredStatus, String> {
         let (installed, version) = self.check_installed().await;
         if !installed {
             return Err("Cloudflared not installed".to_string());
         }
 
-        let local_url = format!("http://localhost:{}", config.port);
-        info!("[cloudflared] Starting tunnel to: {}", local_url);
+        let mut tunnels = self.tunnels.write().await;
 
-        let mut cmd = Command::new(&self.bin_path);
-        
-        // 设置工作目录
-        // 设置工作目录
-        if let Some(bin_dir) = self.bin_path.parent() {
-            cmd.current_dir(bin_dir);
-            debug!("[cloudflared] Working directory: {:?}", bin_dir);
+        // 检查是否已在运行
+        if let Some(handle) = tunnels.get(&launch.tunnel_id) {
+            if handle.shutdown_tx.is_some() {
+                return Ok(handle.status.read().await.clone());
+            }
         }
 
-        match config.mode {
-            TunnelMode::Quick => {
-                cmd.arg("tunnel")
-                    .arg("--url")
-                    .arg(&local_url);
-                
-                // 注意：--no-autoupdate 参数在较新版本的 cloudflared 中已不被支持，会导致进程立即退出
-                // cmd.arg("--no-autoupdate");
-
-                if config.use_http2 {
-                    cmd.arg("--protocol").arg("http2");
-                }
-                
-                // 注意：--loglevel 参数在此上下文中也会导致 Incorrect Usage 错误，故移除以使用默认值
-                // cmd.arg("--loglevel").arg("info");
-                
-                info!("[cloudflared] Command args: tunnel --url {} ...", local_url);
-            }
-            TunnelMode::Auth => {
-                if let Some(token) = &config.token {
-                    cmd.arg("tunnel")
-                        .arg("run")
-                        .arg("--token")
-                        .arg(token);
-                    
-                    // 注意：--no-autoupdate 参数不被支持
-                    // cmd.arg("--no-autoupdate");
-                    
-                    if config.use_http2 {
-                        cmd.arg("--protocol").arg("http2");
-                    }
-                    
-                    // 注意：--loglevel 参数不被支持
-                    // cmd.arg("--loglevel").arg("info");
-                    
-                    info!("[cloudflared] Command args: tunnel run --token [HIDDEN] ...");
-                } else {
-                    return Err("Token required for auth mode".to_string());
+        let status = Arc::new(RwLock::new(CloudflaredStatus {
+            tunnel_id: launch.tunnel_id.clone(),
+            installed,
+            version,
+            url: launch.hostnames.first().map(|h| format!("https://{}", h)),
+            hostnames: launch.hostnames.clone(),
+            ..CloudflaredStatus::default()
+        }));
+
+        // 首次启动同步进行，让启动错误直接返回给调用方
+        let child = spawn_cloudflared(&self.bin_path, &launch, &status)?;
+        status.write().await.running = true;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        tunnels.insert(
+            launch.tunnel_id.clone(),
+            TunnelHandle {
+                status: status.clone(),
+                shutdown_tx: Some(shutdown_tx),
+            },
+        );
+        drop(tunnels);
+
+        emit_status(&status).await;
+        let tunnels = self.tunnels.clone();
+        let supervised = status.clone();
+        let bin_path = self.bin_path.clone();
+        tokio::spawn(async move {
+            let id = launch.tunnel_id.clone();
+            supervise_tunnel(bin_path, launch, supervised.clone(), child, shutdown_rx).await;
+            release_handle(&tunnels, &id, &supervised).await;
+        });
+
+        let current = status.read().await.clone();
+        Ok(current)
+    }
+
+    /// 停止隧道
+    pub async fn stop(&self, tunnel_id: &str) -> Result<CloudflaredStatus, String> {
+        let handle_status = {
+            let mut tunnels = self.tunnels.write().await;
+            tunnels.get_mut(tunnel_id).map(|handle| {
+                if let Some(tx) = handle.shutdown_tx.take() {
+                    let _ = tx.send(());
+                    info!("[cloudflared:{}] Tunnel stopped", tunnel_id);
                 }
+                handle.status.clone()
+            })
+        };
+
+        if let Some(status) = handle_status {
+            {
+                let mut s = status.write().await;
+                s.running = false;
+                s.url = None;
+                s.error = None;
+                s.connections = 0;
             }
+            emit_status(&status).await;
         }
 
-        // 恢复管道
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-        
-        // 使用 DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP 隐藏窗口
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        Ok(self.get_status(tunnel_id).await)
+    }
+
+    /// 停止并移除隧道（删除命名隧道配置时调用）
+    pub async fn remove(&self, tunnel_id: &str) {
+        let _ = self.stop(tunnel_id).await;
+        self.tunnels.write().await.remove(tunnel_id);
+        let _ = std::fs::remove_file(self.tunnels_dir.join(format!("{}.json", tunnel_id)));
+        let _ = std::fs::remove_file(self.tunnels_dir.join(format!("{}.yml", tunnel_id)));
+    }
+}
 
-        let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
+/// 启动 cloudflared 子进程并挂接日志解析
+fn spawn_cloudflared(
+    bin_path: &Path,
+    launch: &TunnelLaunch,
+    status: &Arc<RwLock<CloudflaredStatus>>,
+) -> Result<Child, String> {
+    let mut cmd = Command::new(bin_path);
+
+    // 设置工作目录
+    if let Some(bin_dir) = bin_path.parent() {
+        cmd.current_dir(bin_dir);
+        debug!("[cloudflared] Working directory: {:?}", bin_dir);
+    }
 
-        let stdout = child.stdout.take();
-        let stderr = child.stderr.take();
+    cmd.args(&launch.args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
 
-        let status_clone = self.status.clone();
-        if let Some(stdout) = stdout {
-            spawn_log_reader(stdout, status_clone.clone());
-        }
+    // 使用 DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP 隐藏窗口
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
 
-        if let Some(stderr) = stderr {
-            spawn_log_reader(stderr, status_clone.clone());
-        }
+    let mut child = cmd.spawn().map_err(|e| format!("Failed to spawn: {}", e))?;
 
-        *self.process.write().await = Some(child);
-        self.update_status(|s| {
-            s.installed = installed.clone();
-            s.version = version.clone();
-            s.running = true;
-            s.error = None;
-        }).await;
+    if let Some(stdout) = child.stdout.take() {
+        spawn_log_reader(stdout, launch.tunnel_id.clone(), status.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_log_reader(stderr, launch.tunnel_id.clone(), status.clone());
+    }
 
-        // 启动进程监控任务
-        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
-        *self.shutdown_tx.write().await = Some(shutdown_tx);
+    Ok(child)
+}
 
-        let process_ref = self.process.clone();
-        let status_ref = self.status.clone();
+/// 监控隧道进程：收到停止信号时终止进程；进程崩溃时按指数退避自动重启
+async fn supervise_tunnel(
+    bin_path: PathBuf,
+    launch: TunnelLaunch,
+    status: Arc<RwLock<CloudflaredStatus>>,
+    mut child: Child,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) {
+    let id = launch.tunnel_id.clone();
+    let mut backoff = RESTART_BACKOFF_MIN;
+
+    loop {
+        let started = std::time::Instant::now();
+
+        let exit = tokio::select! {
+            _ = &mut shutdown_rx => {
+                let _ = child.kill().await;
+                debug!("[cloudflared:{}] Process monitor shutdown", id);
+                return;
+            }
+            exit = child.wait() => exit,
+        };
 
-        tokio::spawn(async move {
+        let reason = match exit {
+            Ok(exit_status) => format!("Tunnel process exited (status: {:?})", exit_status),
+            Err(e) => format!("Error checking tunnel: {}", e),
+        };
+        info!("[cloudflared:{}] {}", id, reason);
+        {
+            let mut s = status.write().await;
+            s.running = false;
+            s.connections = 0;
+            s.error = Some(reason);
+        }
+        emit_status(&status).await;
+
+        if !launch.auto_restart {
+            return;
+        }
+        if started.elapsed() >= STABLE_RUN {
+            backoff = RESTART_BACKOFF_MIN;
+        }
+
+        // 按退避时间重试，直到进程启动或收到停止信号
+        child = loop {
+            info!("[cloudflared:{}] Restarting in {}s", id, backoff.as_secs());
             tokio::select! {
-                _ = shutdown_rx => {
-                    debug!("[cloudflared] Process monitor shutdown");
+                _ = &mut shutdown_rx => return,
+                _ = tokio::time::sleep(backoff) => {}
+            }
+            backoff = (backoff * 2).min(RESTART_BACKOFF_MAX);
+
+            match spawn_cloudflared(&bin_path, &launch, &status) {
+                Ok(child) => break child,
+                Err(e) => {
+                    warn!("[cloudflared:{}] Restart failed: {}", id, e);
+                    status.write().await.error = Some(e);
+                    emit_status(&status).await;
                 }
-                _ = async {
-                    loop {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-
-                        let mut proc_lock = process_ref.write().await;
-                        if let Some(ref mut child) = *proc_lock {
-                            match child.try_wait() {
-                                Ok(Some(exit_status)) => {
-                                    // 进程已退出
-                                    info!("[cloudflared] Process exited with status: {:?}", exit_status);
-                                    *proc_lock = None;
-                                    drop(proc_lock);
-
-                                    let mut s = status_ref.write().await;
-                                    s.running = false;
-                                    s.error = Some(format!("Tunnel process exited (status: {:?})", exit_status));
-                                    break;
-                                }
-                                Ok(None) => {
-                                    // 进程仍在运行
-                                }
-                                Err(e) => {
-                                    info!("[cloudflared] Error checking process: {}", e);
-                                    *proc_lock = None;
-                                    drop(proc_lock);
-
-                                    let mut s = status_ref.write().await;
-                                    s.running = false;
-                                    s.error = Some(format!("Error checking tunnel: {}", e));
-                                    break;
-                                }
-                            }
-                        } else {
-                            // 进程不存在
-                            drop(proc_lock);
-                            let mut s = status_ref.write().await;
-                            if s.running {
-                                s.running = false;
-                                s.error = Some("Tunnel process not found".to_string());
-                            }
-                            break;
-                        }
-                    }
-                } => {}
             }
-        });
+        };
 
-        Ok(self.get_status().await)
+        {
+            let mut s = status.write().await;
+            s.running = true;
+            s.restarts += 1;
+            if launch.ephemeral_url {
+                s.url = None;
+            }
+        }
+        emit_status(&status).await;
     }
+}
 
-    /// 停止隧道
-    pub async fn stop(&self) -> Result<CloudflaredStatus, String> {
-        let mut proc_lock = self.process.write().await;
-        if let Some(mut child) = proc_lock.take() {
-            let _ = child.kill().await;
-            info!("[cloudflared] Tunnel stopped");
+/// 监控任务退出后（停止、进程退出且不自动重启）清除运行标记，隧道可以再次启动。
+/// 只处理本次运行的句柄，不影响之后重新启动的同名隧道
+async fn release_handle(
+    tunnels: &RwLock<HashMap<String, TunnelHandle>>,
+    tunnel_id: &str,
+    status: &Arc<RwLock<CloudflaredStatus>>,
+) {
+    if let Some(handle) = tunnels.write().await.get_mut(tunnel_id) {
+        if Arc::ptr_eq(&handle.status, status) {
+            handle.shutdown_tx = None;
         }
+    }
+    let mut s = status.write().await;
+    s.running = false;
+    s.connections = 0;
+}
 
-        self.update_status(|s| {
-            s.running = false;
-            s.url = None;
-            s.error = None;
-        }).await;
+/// 推送隧道状态变化事件
+async fn emit_status(status: &Arc<RwLock<CloudflaredStatus>>) {
+    let snapshot = status.read().await.clone();
+    crate::modules::infra::log_bridge::emit_custom_event("cloudflared://status-changed", snapshot);
+}
 
-        Ok(self.get_status().await)
-    }
+/// 从 `cloudflared tunnel create` 生成的凭证 JSON 中读取隧道 UUID
+fn parse_tunnel_uuid(credentials: &str) -> Result<String, String> {
+    let value: serde_json::Value = serde_json::from_str(credentials)
+        .map_err(|e| format!("Invalid tunnel credentials JSON: {}", e))?;
+    value["TunnelID"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .ok_or_else(|| "Tunnel credentials JSON is missing TunnelID".to_string())
+}
+
+/// 生成 cloudflared 配置文件（ingress 末尾必须有兜底规则）
+fn build_ingress_config(
+    tunnel_uuid: &str,
+    credentials_path: &Path,
+    ingress: &[IngressRule],
+) -> Result<String, String> {
+    let mut rules: Vec<serde_json::Value> = ingress
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "hostname": r.hostname.trim(),
+                "service": format!("http://localhost:{}", r.local_port),
+            })
+        })
+        .collect();
+    rules.push(serde_json::json!({ "service": "http_status:404" }));
+
+    serde_yaml::to_string(&serde_json::json!({
+        "tunnel": tunnel_uuid,
+        "credentials-file": credentials_path.display().to_string(),
+        "ingress": rules,
+    }))
+    .map_err(|e| format!("Failed to build tunnel config: {}", e))
 }
 
 /// 获取下载URL
@@ -417,7 +723,7 @@ fn get_download_url() -> Result<String, String> {
     ))
 }
 
-fn spawn_log_reader<R>(stream: R, status_ref: Arc<RwLock<CloudflaredStatus>>)
+fn spawn_log_reader<R>(stream: R, tunnel_id: String, status_ref: Arc<RwLock<CloudflaredStatus>>)
 where
     R: AsyncRead + Unpin + Send + 'static,
 {
@@ -426,16 +732,49 @@ where
         let mut lines = reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
             // 恢复日志级别为 debug，避免污染生产环境日志
-            debug!("[cloudflared output] {}", line);
-            if let Some(url) = extract_tunnel_url(&line) {
-                info!("[cloudflared] Tunnel URL: {}", url);
+            debug!("[cloudflared:{} output] {}", tunnel_id, line);
+
+            let mut changed = false;
+            {
                 let mut s = status_ref.write().await;
-                s.url = Some(url);
+                if let Some(url) = extract_tunnel_url(&line) {
+                    if s.url.as_deref() != Some(url.as_str()) {
+                        info!("[cloudflared:{}] Tunnel URL: {}", tunnel_id, url);
+                        s.url = Some(url);
+                        changed = true;
+                    }
+                }
+                match parse_connection_event(&line) {
+                    Some(true) => {
+                        s.connections += 1;
+                        s.error = None;
+                        changed = true;
+                    }
+                    Some(false) => {
+                        s.connections = s.connections.saturating_sub(1);
+                        changed = true;
+                    }
+                    None => {}
+                }
+            }
+            if changed {
+                emit_status(&status_ref).await;
             }
         }
     });
 }
 
+/// 解析边缘连接注册 / 断开日志：Some(true) 注册，Some(false) 断开
+fn parse_connection_event(line: &str) -> Option<bool> {
+    if line.contains("Registered tunnel connection") {
+        Some(true)
+    } else if line.contains("Unregistered tunnel connection") || line.contains("Connection terminated") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
 /// 从日志行提取隧道URL
 /// 支持两种模式：
 /// 1. 快速隧道：直接提取 .trycloudflare.com URL
@@ -466,3 +805,105 @@ fn extract_tunnel_url(line: &str) -> Option<String> {
     None
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_tunnel_validation() {
+        let mut tunnel = NamedTunnelConfig {
+            id: "bot-api".into(),
+            token: None,
+            credentials_json: Some(r#"{"AccountTag":"a","TunnelSecret":"s","TunnelID":"6ff42ae2-765d-4adf-8112-31c55c1551ef"}"#.into()),
+            ingress: vec![IngressRule { hostname: "bot.example.com".into(), local_port: 9520 }],
+            use_http2: true,
+            auto_restart: true,
+        };
+        assert!(tunnel.validate().is_ok());
+
+        tunnel.ingress.clear();
+        assert!(tunnel.validate().is_err());
+
+        tunnel.credentials_json = None;
+        tunnel.token = Some("eyJh...".into());
+        assert!(tunnel.validate().is_ok());
+
+        tunnel.id = DEFAULT_TUNNEL_ID.into();
+        assert!(tunnel.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_ingress_config() {
+        let yaml = build_ingress_config(
+            "6ff42ae2-765d-4adf-8112-31c55c1551ef",
+            Path::new("/tmp/bot-api.json"),
+            &[
+                IngressRule { hostname: "bot.example.com".into(), local_port: 9520 },
+                IngressRule { hostname: "dev.example.com".into(), local_port: 5173 },
+            ],
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed["tunnel"], "6ff42ae2-765d-4adf-8112-31c55c1551ef");
+        assert_eq!(parsed["ingress"][1]["service"], "http://localhost:5173");
+        assert_eq!(parsed["ingress"][2]["service"], "http_status:404");
+    }
+
+    #[test]
+    fn test_parse_connection_event() {
+        assert_eq!(
+            parse_connection_event("2024-01-01T00:00:00Z INF Registered tunnel connection connIndex=0 protocol=http2"),
+            Some(true)
+        );
+        assert_eq!(
+            parse_connection_event("2024-01-01T00:00:00Z INF Unregistered tunnel connection connIndex=0"),
+            Some(false)
+        );
+        assert_eq!(parse_connection_event("INF Starting tunnel"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_tunnel_restartable_after_exit() {
+        use std::os::unix::fs::PermissionsExt;
+        let data_dir = std::env::temp_dir().join(format!("helix-cloudflared-test-{}", std::process::id()));
+        let runs = data_dir.join("runs");
+        std::fs::create_dir_all(data_dir.join("bin")).unwrap();
+        let bin = data_dir.join("bin").join("cloudflared");
+        let script = format!(
+            "#!/bin/sh\n[ \"$1\" = --version ] && echo 'cloudflared test' && exit 0\necho run >> '{}'\nexit 1\n",
+            runs.display()
+        );
+        std::fs::write(&bin, script).unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let manager = CloudflaredManager::new(&data_dir);
+        let launch = TunnelLaunch {
+            tunnel_id: "test".into(),
+            args: Vec::new(),
+            hostnames: Vec::new(),
+            ephemeral_url: true,
+            auto_restart: false,
+        };
+        let exited = || async {
+            for _ in 0..100 {
+                let released = manager.tunnels.read().await.get("test").is_some_and(|h| h.shutdown_tx.is_none());
+                if released {
+                    return true;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            false
+        };
+
+        manager.start_tunnel(launch.clone()).await.unwrap();
+        assert!(exited().await, "handle not released after the process exited");
+        assert!(!manager.get_status("test").await.running);
+
+        // Not stuck as "running": a second start launches the process again
+        manager.start_tunnel(launch).await.unwrap();
+        assert!(exited().await);
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 2);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -8,14 +8,18 @@ use chrono::{DateTime, Timelike, Utc};
 use cron::Schedule;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
-use rusqlite::{params, Connection};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{info, error, warn};
 
+use crate::error::{codes, HelixError, HelixResult};
 use crate::modules::config::get_data_dir;
+use crate::modules::environments::WithManagedEnv;
 
 // ============================================================================
 // Types
@@ -31,12 +35,105 @@ pub struct CronTask {
     pub schedule: Option<String>, // cron expression
     pub script: Option<String>,   // shell command or AI prompt
     pub status: String,           // "active" | "paused" | "error"
-    pub notify_channel: Option<String>, // "feishu" | "dingtalk" | null
+    pub notify_channel: Option<String>, // "feishu" | "dingtalk" | "smtp" | "wecom" | null
     pub created_at: String,
     pub updated_at: String,
     pub last_run: Option<String>,
     pub last_result: Option<String>, // "success" | "error"
     pub next_run: Option<String>,
+    #[serde(default = "default_group")]
+    pub group_name: String,
+    /// Prerequisite task id
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    /// Run this task whenever `depends_on` finishes successfully
+    #[serde(default)]
+    pub run_after_success: bool,
+}
+
+pub const DEFAULT_GROUP: &str = "default";
+
+/// Task type for built-in jobs handled in-process instead of by a shell script.
+pub const INTERNAL_TASK_TYPE: &str = "internal";
+/// Group that internal tasks are filed under.
+pub const INTERNAL_GROUP: &str = "system";
+/// Reserved id of the daily usage report job.
+pub const USAGE_REPORT_TASK_ID: &str = "internal:usage-daily-report";
+/// Runs kept per task when the app config cannot be read.
+const DEFAULT_MAX_RUNS: usize = 100;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CronError {
+    #[error("Task not found: {0}")]
+    NotFound(String),
+    #[error("{}", crate::modules::i18n::t("error.cron_no_script", &[]))]
+    NoScript,
+    #[error("任务「{task}」正在 {holder} 上执行，已跳过")]
+    AlreadyRunning { task: String, holder: String },
+    #[error("{}", crate::modules::i18n::t("error.cron_invalid_expr", &[("error", .0.as_str())]))]
+    InvalidExpr(String),
+    #[error("{0}")]
+    InvalidGroup(String),
+    #[error("Group '{0}' already exists")]
+    GroupExists(String),
+    #[error("Task {0} is not locked")]
+    NotLocked(String),
+    #[error("Template not found: {0}")]
+    TemplateNotFound(String),
+    #[error("Missing template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    #[error("Invalid template variable '{name}': {reason}")]
+    InvalidVariable { name: String, reason: String },
+    #[error("Invalid import file: {0}")]
+    InvalidImport(String),
+    #[error("Invalid dependency: {0}")]
+    InvalidDependency(String),
+    #[error("{0}")]
+    Storage(String),
+}
+
+impl From<String> for CronError {
+    fn from(message: String) -> Self {
+        CronError::Storage(message)
+    }
+}
+
+impl From<CronError> for HelixError {
+    fn from(err: CronError) -> Self {
+        let message = err.to_string();
+        match err {
+            CronError::NotFound(id) => {
+                HelixError::new(codes::CRON_NOT_FOUND, message).with_detail(serde_json::json!({ "task_id": id }))
+            }
+            CronError::NoScript => HelixError::new(codes::CRON_NO_SCRIPT, message),
+            CronError::AlreadyRunning { holder, .. } => HelixError::new(codes::CRON_ALREADY_RUNNING, message)
+                .with_detail(serde_json::json!({ "locked_by": holder }))
+                .retriable(),
+            CronError::InvalidExpr(_) => HelixError::new(codes::CRON_INVALID_EXPR, message),
+            CronError::InvalidGroup(_) => HelixError::new(codes::CRON_INVALID_GROUP, message),
+            CronError::GroupExists(_) => HelixError::new(codes::CRON_GROUP_EXISTS, message),
+            CronError::NotLocked(_) => HelixError::new(codes::CRON_NOT_LOCKED, message),
+            CronError::TemplateNotFound(_) => HelixError::not_found(message),
+            CronError::MissingVariables(names) => {
+                HelixError::validation_error(names.iter().map(|n| format!("missing variable '{}'", n)).collect())
+            }
+            CronError::InvalidVariable { .. } => HelixError::validation_error(vec![message]),
+            CronError::InvalidImport(_) => HelixError::new(codes::VALIDATION_ERROR, message),
+            CronError::InvalidDependency(_) => HelixError::new(codes::VALIDATION_ERROR, message),
+            CronError::Storage(_) => HelixError::new(codes::STORAGE_DB, message),
+        }
+    }
+}
+
+fn default_group() -> String {
+    DEFAULT_GROUP.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronGroup {
+    pub name: String,
+    pub task_count: i64,
+    pub active_count: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,10 +142,28 @@ pub struct CronRun {
     pub task_id: String,
     pub started_at: String,
     pub finished_at: Option<String>,
-    pub result: String, // "success" | "error" | "running"
+    pub result: String, // "success" | "error" | "running" | "aborted"
     pub output: String,
 }
 
+/// A task currently being executed by some Helix instance sharing the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronLock {
+    pub task_id: String,
+    pub locked_at: String,
+    /// "<hostname>:<pid>" of the holder
+    pub locked_by: String,
+}
+
+/// Upcoming execution of a scheduled task.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledRun {
+    pub task_id: String,
+    pub task_name: String,
+    /// RFC 3339, UTC
+    pub next_run_at: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskInput {
     pub name: String,
@@ -58,6 +173,14 @@ pub struct CreateTaskInput {
     pub schedule: Option<String>,
     pub script: Option<String>,
     pub notify_channel: Option<String>,
+    /// Group to file the task under (default: "default")
+    #[serde(default)]
+    pub group_name: Option<String>,
+    /// Prerequisite task id
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    #[serde(default)]
+    pub run_after_success: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +191,12 @@ pub struct UpdateTaskInput {
     pub script: Option<String>,
     pub status: Option<String>,
     pub notify_channel: Option<Value>, // can be string or null
+    #[serde(default)]
+    pub group_name: Option<String>,
+    #[serde(default)]
+    pub depends_on: Option<Value>, // task id, or null to remove the dependency
+    #[serde(default)]
+    pub run_after_success: Option<bool>,
 }
 
 // ============================================================================
@@ -119,10 +248,36 @@ pub fn init_cron_tables() -> Result<(), String> {
             output      TEXT NOT NULL DEFAULT '',
             FOREIGN KEY (task_id) REFERENCES cron_tasks(id) ON DELETE CASCADE
         );
+
+        CREATE INDEX IF NOT EXISTS idx_cron_runs_task ON cron_runs(task_id, started_at);
+
+        CREATE TABLE IF NOT EXISTS cron_groups (
+            name       TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS cron_locks (
+            task_id   TEXT PRIMARY KEY,
+            locked_at TEXT NOT NULL,
+            locked_by TEXT NOT NULL
+        );
         ",
     )
     .map_err(|e| format!("Failed to create cron tables: {}", e))?;
 
+    // Migration: task groups
+    let _ = conn.execute(
+        "ALTER TABLE cron_tasks ADD COLUMN group_name TEXT NOT NULL DEFAULT 'default'",
+        [],
+    );
+
+    // Migration: task dependencies
+    let _ = conn.execute("ALTER TABLE cron_tasks ADD COLUMN depends_on TEXT", []);
+    let _ = conn.execute(
+        "ALTER TABLE cron_tasks ADD COLUMN run_after_success INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+
     info!("Cron tables initialized");
     Ok(())
 }
@@ -133,13 +288,15 @@ pub fn init_cron_tables() -> Result<(), String> {
 
 /// Parse a cron expression and compute the next run time.
 pub fn compute_next_run(cron_expr: &str) -> Option<String> {
+    compute_next_run_after(cron_expr, Utc::now()).map(|at| at.to_rfc3339())
+}
+
+/// First trigger of `cron_expr` strictly after `now`.
+fn compute_next_run_after(cron_expr: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
     // Support 5/6/7 field expressions
     let expr = normalize_cron_expr(cron_expr);
     match Schedule::from_str(&expr) {
-        Ok(schedule) => {
-            let upcoming = schedule.upcoming(Utc).next()?;
-            Some(upcoming.to_rfc3339())
-        }
+        Ok(schedule) => schedule.after(&now).next(),
         Err(e) => {
             warn!("Invalid cron expression '{}': {}", cron_expr, e);
             None
@@ -147,6 +304,36 @@ pub fn compute_next_run(cron_expr: &str) -> Option<String> {
     }
 }
 
+/// Scheduled tasks whose next trigger after `now` falls within `hours`,
+/// soonest first. Computed from the expression, not the stored `next_run`,
+/// which lags behind while the scheduler is paused or the machine sleeps.
+fn upcoming_runs(tasks: &[CronTask], now: DateTime<Utc>, hours: u32) -> Vec<ScheduledRun> {
+    let horizon = now + chrono::Duration::hours(hours as i64);
+    let mut runs: Vec<(DateTime<Utc>, ScheduledRun)> = tasks
+        .iter()
+        .filter(|t| t.status == "active" && (t.task_type == "cron" || t.task_type == INTERNAL_TASK_TYPE))
+        .filter_map(|t| {
+            let schedule = t.schedule.as_deref().filter(|s| !s.is_empty())?;
+            let at = compute_next_run_after(schedule, now)?;
+            (at <= horizon).then(|| {
+                let run = ScheduledRun {
+                    task_id: t.id.clone(),
+                    task_name: t.name.clone(),
+                    next_run_at: at.to_rfc3339(),
+                };
+                (at, run)
+            })
+        })
+        .collect();
+    runs.sort_by_key(|(at, _)| *at);
+    runs.into_iter().map(|(_, run)| run).collect()
+}
+
+/// Active tasks scheduled to run within the next `hours`, soonest first.
+pub fn next_runs(hours: u32) -> Result<Vec<ScheduledRun>, CronError> {
+    Ok(upcoming_runs(&list_tasks()?, Utc::now(), hours))
+}
+
 /// Normalize a 5-field cron (min hour dom mon dow) to 7-field (sec min hour dom mon dow year)
 /// by prepending "0" for seconds and appending "*" for year if needed.
 fn normalize_cron_expr(expr: &str) -> String {
@@ -160,17 +347,206 @@ fn normalize_cron_expr(expr: &str) -> String {
 }
 
 /// Check if a cron expression is valid.
-pub fn validate_cron_expr(expr: &str) -> Result<(), String> {
+pub fn validate_cron_expr(expr: &str) -> Result<(), CronError> {
     let normalized = normalize_cron_expr(expr);
-    Schedule::from_str(&normalized).map_err(|e| format!("Invalid cron expression: {}", e))?;
+    Schedule::from_str(&normalized).map_err(|e| CronError::InvalidExpr(e.to_string()))?;
     Ok(())
 }
 
+// ============================================================================
+// Natural-Language Schedules
+// ============================================================================
+
+/// Default time of day (hour) for recurring schedules with no explicit time.
+const NL_DEFAULT_HOUR: u32 = 9;
+
+static NL_INTERVAL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:每|every)\s*(?:隔)?\s*(\d+)?\s*个?\s*(分钟|分|小时|钟头|minutes?|mins?|hours?|hrs?)").unwrap()
+});
+
+/// Time of day: "早上8点半", "下午3点15分", "20:30", "5:15 pm", "8am", "at 9".
+static NL_TIME_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(凌晨|早上|早晨|上午|中午|下午|傍晚|晚上|at)?\s*(\d{1,2})\s*(?:([点點时])\s*(?:(半)|(\d{1,2})\s*分?)?|[:：]\s*(\d{2}))?\s*(am|pm)?").unwrap()
+});
+
+static NL_ZH_WEEKDAY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:每|每个)?(?:周|星期|礼拜)([1-7日天])").unwrap()
+});
+
+static NL_MONTH_DAY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"每个?月\s*(\d{1,2})\s*[号日]|every month on the (\d{1,2})(?:st|nd|rd|th)?|monthly on the (\d{1,2})(?:st|nd|rd|th)?").unwrap()
+});
+
+const EN_WEEKDAYS: [(&str, &str); 7] = [
+    ("monday", "MON"),
+    ("tuesday", "TUE"),
+    ("wednesday", "WED"),
+    ("thursday", "THU"),
+    ("friday", "FRI"),
+    ("saturday", "SAT"),
+    ("sunday", "SUN"),
+];
+
+/// Replace Chinese numerals (一 … 九十九, 两, 〇) with Arabic digits so
+/// "每天早上八点半" and "每天早上8点半" parse the same way.
+fn zh_numerals_to_digits(text: &str) -> String {
+    fn digit(c: char) -> Option<u32> {
+        match c {
+            '零' | '〇' => Some(0),
+            '一' => Some(1),
+            '二' | '两' => Some(2),
+            '三' => Some(3),
+            '四' => Some(4),
+            '五' => Some(5),
+            '六' => Some(6),
+            '七' => Some(7),
+            '八' => Some(8),
+            '九' => Some(9),
+            _ => None,
+        }
+    }
+    fn convert(run: &str) -> String {
+        match run.split_once('十') {
+            Some((tens, ones)) => {
+                let tens = tens.chars().next().and_then(digit).unwrap_or(1);
+                let ones = ones.chars().next().and_then(digit).unwrap_or(0);
+                (tens * 10 + ones).to_string()
+            }
+            None => run.chars().filter_map(digit).map(|d| d.to_string()).collect(),
+        }
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut run = String::new();
+    for c in text.chars() {
+        if c == '十' || digit(c).is_some() {
+            run.push(c);
+        } else {
+            if !run.is_empty() {
+                out.push_str(&convert(&run));
+                run.clear();
+            }
+            out.push(c);
+        }
+    }
+    if !run.is_empty() {
+        out.push_str(&convert(&run));
+    }
+    out
+}
+
+/// Extract (hour, minute) in 24h form from Chinese or English time phrasings.
+fn parse_time_of_day(text: &str) -> Option<(u32, u32)> {
+    for c in NL_TIME_RE.captures_iter(text) {
+        let prefix = c.get(1).map(|m| m.as_str());
+        let meridiem = c.get(7).map(|m| m.as_str());
+        // Bare numbers ("每月15号") are not times
+        if c.get(3).is_none() && c.get(6).is_none() && meridiem.is_none() && prefix != Some("at") {
+            continue;
+        }
+
+        let mut hour: u32 = c[2].parse().ok()?;
+        let minute: u32 = if c.get(4).is_some() {
+            30
+        } else {
+            match c.get(5).or_else(|| c.get(6)) {
+                Some(m) => m.as_str().parse().ok()?,
+                None => 0,
+            }
+        };
+        match (prefix, meridiem) {
+            (Some("下午") | Some("傍晚") | Some("晚上"), _) | (_, Some("pm")) if hour < 12 => hour += 12,
+            (Some("中午"), _) if hour < 11 => hour += 12,
+            (Some("凌晨"), _) | (_, Some("am")) if hour == 12 => hour = 0,
+            _ => {}
+        }
+        return (hour < 24 && minute < 60).then_some((hour, minute));
+    }
+
+    if text.contains("noon") || text.contains("中午") {
+        return Some((12, 0));
+    }
+    if text.contains("midnight") || text.contains("午夜") {
+        return Some((0, 0));
+    }
+    None
+}
+
+/// Translate a natural-language schedule ("每天早上8点", "every monday at 9am",
+/// "每30分钟") into a 5-field cron expression. Valid cron expressions are
+/// returned unchanged. Returns `None` if the phrase is not recognized.
+pub fn parse_natural_schedule(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    let field_count = trimmed.split_whitespace().count();
+    if (5..=7).contains(&field_count)
+        && trimmed.chars().all(|c| c.is_ascii_alphanumeric() || " */,-?#LW".contains(c))
+        && validate_cron_expr(trimmed).is_ok()
+    {
+        return Some(trimmed.to_string());
+    }
+
+    let text = zh_numerals_to_digits(&trimmed.to_lowercase());
+
+    // Fixed intervals: 每30分钟 / every 2 hours / hourly
+    if let Some(c) = NL_INTERVAL_RE.captures(&text) {
+        let n: u32 = c.get(1).map_or(Some(1), |m| m.as_str().parse().ok())?;
+        let unit = &c[2];
+        if unit.starts_with('分') || unit.starts_with("min") {
+            return match n {
+                1 => Some("* * * * *".into()),
+                2..=59 => Some(format!("*/{} * * * *", n)),
+                _ => None,
+            };
+        }
+        return match n {
+            1 => Some("0 * * * *".into()),
+            2..=23 => Some(format!("0 */{} * * *", n)),
+            _ => None,
+        };
+    }
+    if text.contains("hourly") {
+        return Some("0 * * * *".into());
+    }
+
+    // Day-of-month / day-of-week, then time of day
+    let mut dom = "*".to_string();
+    let mut dow = "*".to_string();
+
+    if let Some(c) = NL_MONTH_DAY_RE.captures(&text) {
+        let day: u32 = (1..=3).find_map(|i| c.get(i))?.as_str().parse().ok()?;
+        if !(1..=31).contains(&day) {
+            return None;
+        }
+        dom = day.to_string();
+    } else if text.contains("每月") || text.contains("monthly") || text.contains("every month") {
+        dom = "1".into();
+    } else if text.contains("工作日") || text.contains("weekday") {
+        dow = "MON-FRI".into();
+    } else if text.contains("周末") || text.contains("weekend") {
+        dow = "SAT,SUN".into();
+    } else if let Some(c) = NL_ZH_WEEKDAY_RE.captures(&text) {
+        dow = match &c[1] {
+            "日" | "天" | "7" => "SUN".into(),
+            d => EN_WEEKDAYS[d.parse::<usize>().ok()? - 1].1.into(),
+        };
+    } else if let Some((_, abbr)) = EN_WEEKDAYS.iter().find(|(name, _)| text.contains(name)) {
+        dow = abbr.to_string();
+    } else if !["每天", "每日", "每晚", "每早", "天天", "daily", "every day", "everyday", "every night", "every morning"]
+        .iter()
+        .any(|k| text.contains(k))
+    {
+        return None;
+    }
+
+    let (hour, minute) = parse_time_of_day(&text).unwrap_or((NL_DEFAULT_HOUR, 0));
+    Some(format!("{} {} {} * {}", minute, hour, dom, dow))
+}
+
 // ============================================================================
 // CRUD Operations
 // ============================================================================
 
-pub fn create_task(input: CreateTaskInput) -> Result<CronTask, String> {
+pub fn create_task(input: CreateTaskInput) -> Result<CronTask, CronError> {
     // Validate cron expression if provided
     if input.task_type == "cron" {
         if let Some(ref schedule) = input.schedule {
@@ -194,11 +570,17 @@ pub fn create_task(input: CreateTaskInput) -> Result<CronTask, String> {
     let schedule = input.schedule.clone();
     let script = input.script.clone();
     let notify_channel = input.notify_channel.clone();
+    let group_name = normalize_group_name(input.group_name.as_deref().unwrap_or(DEFAULT_GROUP))?;
+    let depends_on = input.depends_on.clone().filter(|d| !d.is_empty());
+    if let Some(ref prerequisite) = depends_on {
+        check_dependency(&id, prerequisite)?;
+    }
+    let run_after_success = input.run_after_success.unwrap_or(false);
 
     let conn = CRON_DB.lock();
     conn.execute(
-        "INSERT INTO cron_tasks (id, name, description, task_type, schedule, script, status, notify_channel, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, ?8, ?9)",
+        "INSERT INTO cron_tasks (id, name, description, task_type, schedule, script, status, notify_channel, created_at, updated_at, group_name, depends_on, run_after_success)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, ?8, ?9, ?10, ?11, ?12)",
         params![
             id,
             input.name,
@@ -209,6 +591,9 @@ pub fn create_task(input: CreateTaskInput) -> Result<CronTask, String> {
             input.notify_channel,
             now,
             now,
+            group_name,
+            depends_on,
+            run_after_success,
         ],
     )
     .map_err(|e| format!("Failed to create task: {}", e))?;
@@ -229,39 +614,54 @@ pub fn create_task(input: CreateTaskInput) -> Result<CronTask, String> {
         last_run: None,
         last_result: None,
         next_run,
+        group_name,
+        depends_on,
+        run_after_success,
     })
 }
 
-pub fn list_tasks() -> Result<Vec<CronTask>, String> {
+pub fn list_tasks() -> Result<Vec<CronTask>, CronError> {
+    list_tasks_in_group(None)
+}
+
+const TASK_COLUMNS: &str = "id, name, description, task_type, schedule, script, status, notify_channel,
+    created_at, updated_at, last_run, last_result, group_name, depends_on, run_after_success";
+
+fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<CronTask> {
+    let schedule: Option<String> = row.get(4)?;
+    let next_run = schedule.as_ref().and_then(|s| compute_next_run(s));
+    Ok(CronTask {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        task_type: row.get(3)?,
+        schedule,
+        script: row.get(5)?,
+        status: row.get(6)?,
+        notify_channel: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+        last_run: row.get(10)?,
+        last_result: row.get(11)?,
+        next_run,
+        group_name: row.get(12)?,
+        depends_on: row.get(13)?,
+        run_after_success: row.get(14)?,
+    })
+}
+
+/// List tasks, optionally only those in `group`.
+pub fn list_tasks_in_group(group: Option<&str>) -> Result<Vec<CronTask>, CronError> {
     let conn = CRON_DB.lock();
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, description, task_type, schedule, script, status, notify_channel,
-                    created_at, updated_at, last_run, last_result
-             FROM cron_tasks ORDER BY created_at DESC",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM cron_tasks WHERE ?1 IS NULL OR group_name = ?1 ORDER BY created_at DESC",
+            TASK_COLUMNS
+        ))
         .map_err(|e| format!("Failed to query tasks: {}", e))?;
 
     let tasks = stmt
-        .query_map([], |row| {
-            let schedule: Option<String> = row.get(4)?;
-            let next_run = schedule.as_ref().and_then(|s| compute_next_run(s));
-            Ok(CronTask {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                task_type: row.get(3)?,
-                schedule,
-                script: row.get(5)?,
-                status: row.get(6)?,
-                notify_channel: row.get(7)?,
-                created_at: row.get(8)?,
-                updated_at: row.get(9)?,
-                last_run: row.get(10)?,
-                last_result: row.get(11)?,
-                next_run,
-            })
-        })
+        .query_map(params![group], row_to_task)
         .map_err(|e| format!("Failed to map tasks: {}", e))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| format!("Failed to collect tasks: {}", e))?;
@@ -269,39 +669,19 @@ pub fn list_tasks() -> Result<Vec<CronTask>, String> {
     Ok(tasks)
 }
 
-pub fn get_task(id: &str) -> Result<CronTask, String> {
+pub fn get_task(id: &str) -> Result<CronTask, CronError> {
     let conn = CRON_DB.lock();
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, description, task_type, schedule, script, status, notify_channel,
-                    created_at, updated_at, last_run, last_result
-             FROM cron_tasks WHERE id = ?1",
-        )
+        .prepare(&format!("SELECT {} FROM cron_tasks WHERE id = ?1", TASK_COLUMNS))
         .map_err(|e| format!("Query error: {}", e))?;
 
-    stmt.query_row(params![id], |row| {
-        let schedule: Option<String> = row.get(4)?;
-        let next_run = schedule.as_ref().and_then(|s| compute_next_run(s));
-        Ok(CronTask {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            task_type: row.get(3)?,
-            schedule,
-            script: row.get(5)?,
-            status: row.get(6)?,
-            notify_channel: row.get(7)?,
-            created_at: row.get(8)?,
-            updated_at: row.get(9)?,
-            last_run: row.get(10)?,
-            last_result: row.get(11)?,
-            next_run,
-        })
+    stmt.query_row(params![id], row_to_task).map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => CronError::NotFound(id.to_string()),
+        e => CronError::Storage(format!("Query error: {}", e)),
     })
-    .map_err(|e| format!("Task not found: {}", e))
 }
 
-pub fn update_task(id: &str, input: UpdateTaskInput) -> Result<CronTask, String> {
+pub fn update_task(id: &str, input: UpdateTaskInput) -> Result<CronTask, CronError> {
     // Validate cron expression if being updated
     if let Some(ref schedule) = input.schedule {
         if !schedule.is_empty() {
@@ -309,6 +689,12 @@ pub fn update_task(id: &str, input: UpdateTaskInput) -> Result<CronTask, String>
         }
     }
 
+    let group_name = input.group_name.as_deref().map(normalize_group_name).transpose()?;
+    let depends_on = input.depends_on.as_ref().map(|d| d.as_str().filter(|d| !d.is_empty()).map(str::to_string));
+    if let Some(Some(ref prerequisite)) = depends_on {
+        check_dependency(id, prerequisite)?;
+    }
+
     let now = Utc::now().to_rfc3339();
     let conn = CRON_DB.lock();
 
@@ -352,6 +738,21 @@ pub fn update_task(id: &str, input: UpdateTaskInput) -> Result<CronTask, String>
         param_values.push(Box::new(val));
         param_idx += 1;
     }
+    if let Some(group) = group_name {
+        sets.push(format!("group_name = ?{}", param_idx));
+        param_values.push(Box::new(group));
+        param_idx += 1;
+    }
+    if let Some(prerequisite) = depends_on {
+        sets.push(format!("depends_on = ?{}", param_idx));
+        param_values.push(Box::new(prerequisite));
+        param_idx += 1;
+    }
+    if let Some(after_success) = input.run_after_success {
+        sets.push(format!("run_after_success = ?{}", param_idx));
+        param_values.push(Box::new(after_success));
+        param_idx += 1;
+    }
 
     let _ = param_idx; // suppress unused warning
 
@@ -366,16 +767,355 @@ pub fn update_task(id: &str, input: UpdateTaskInput) -> Result<CronTask, String>
     get_task(id)
 }
 
-pub fn delete_task(id: &str) -> Result<(), String> {
+pub fn delete_task(id: &str) -> Result<(), CronError> {
     let conn = CRON_DB.lock();
     conn.execute("DELETE FROM cron_runs WHERE task_id = ?1", params![id])
         .map_err(|e| format!("Failed to delete task runs: {}", e))?;
     conn.execute("DELETE FROM cron_tasks WHERE id = ?1", params![id])
         .map_err(|e| format!("Failed to delete task: {}", e))?;
+    conn.execute("UPDATE cron_tasks SET depends_on = NULL WHERE depends_on = ?1", params![id])
+        .map_err(|e| format!("Failed to clear task dependencies: {}", e))?;
     info!("Deleted cron task: {}", id);
     Ok(())
 }
 
+// ============================================================================
+// Task Dependencies
+// ============================================================================
+
+/// Longest prerequisite chain followed before giving up.
+const MAX_DEPENDENCY_DEPTH: usize = 64;
+
+/// Whether making `task_id` depend on `prerequisite` closes a loop, given
+/// each task's current prerequisite from `depends_on_of`.
+fn creates_cycle(task_id: &str, prerequisite: &str, depends_on_of: impl Fn(&str) -> Option<String>) -> bool {
+    let mut current = prerequisite.to_string();
+    for _ in 0..MAX_DEPENDENCY_DEPTH {
+        if current == task_id {
+            return true;
+        }
+        match depends_on_of(&current) {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The prerequisite must exist and must not (transitively) depend on `task_id`.
+fn check_dependency(task_id: &str, prerequisite: &str) -> Result<(), CronError> {
+    get_task(prerequisite)
+        .map_err(|_| CronError::InvalidDependency(format!("prerequisite task {} does not exist", prerequisite)))?;
+    let conn = CRON_DB.lock();
+    let depends_on_of = |id: &str| {
+        conn.query_row("SELECT depends_on FROM cron_tasks WHERE id = ?1", params![id], |row| {
+            row.get::<_, Option<String>>(0)
+        })
+        .ok()
+        .flatten()
+    };
+    if creates_cycle(task_id, prerequisite, depends_on_of) {
+        return Err(CronError::InvalidDependency(format!(
+            "task {} would (indirectly) depend on itself",
+            task_id
+        )));
+    }
+    Ok(())
+}
+
+/// Active tasks set to run after `task_id` succeeds.
+fn dependent_tasks(task_id: &str) -> Result<Vec<String>, String> {
+    let conn = CRON_DB.lock();
+    let mut stmt = conn
+        .prepare("SELECT id FROM cron_tasks WHERE depends_on = ?1 AND run_after_success = 1 AND status = 'active'")
+        .map_err(|e| format!("Query error: {}", e))?;
+    let ids = stmt
+        .query_map(params![task_id], |row| row.get(0))
+        .map_err(|e| format!("Map error: {}", e))?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| format!("Collect error: {}", e));
+    ids
+}
+
+/// Boxed so `execute_task` can start dependents without a recursive future type.
+fn execute_task_boxed(
+    task_id: String,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<CronRun, CronError>> + Send>> {
+    Box::pin(async move { execute_task(&task_id).await })
+}
+
+/// Start the tasks chained after `task_id`, each in the background.
+fn trigger_dependents(task_id: &str) {
+    let dependents = match dependent_tasks(task_id) {
+        Ok(ids) => ids,
+        Err(e) => {
+            warn!("Failed to look up tasks depending on {}: {}", task_id, e);
+            return;
+        }
+    };
+    for dependent in dependents {
+        info!("Cron task {} succeeded, starting dependent task {}", task_id, dependent);
+        tokio::spawn(async move {
+            if let Err(e) = execute_task_boxed(dependent.clone()).await {
+                error!("Dependent cron task {} failed: {}", dependent, e);
+            }
+        });
+    }
+}
+
+// ============================================================================
+// Task Groups
+// ============================================================================
+
+/// Trim and validate a group name.
+fn normalize_group_name(name: &str) -> Result<String, CronError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CronError::InvalidGroup("Group name cannot be empty".to_string()));
+    }
+    if name.chars().count() > 64 {
+        return Err(CronError::InvalidGroup("Group name is too long (max 64 characters)".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+pub fn create_group(name: &str) -> Result<(), CronError> {
+    let name = normalize_group_name(name)?;
+    let conn = CRON_DB.lock();
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM cron_groups WHERE name = ?1)
+                 OR EXISTS(SELECT 1 FROM cron_tasks WHERE group_name = ?1)",
+            params![name],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Query error: {}", e))?;
+    if exists || name == DEFAULT_GROUP {
+        return Err(CronError::GroupExists(name));
+    }
+    conn.execute(
+        "INSERT INTO cron_groups (name, created_at) VALUES (?1, ?2)",
+        params![name, Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to create group: {}", e))?;
+    info!("Created cron group: {}", name);
+    Ok(())
+}
+
+/// All groups: the default group, explicitly created ones, and any named by tasks.
+pub fn list_groups() -> Result<Vec<CronGroup>, CronError> {
+    let conn = CRON_DB.lock();
+    let mut stmt = conn
+        .prepare(
+            "SELECT g.name,
+                    (SELECT COUNT(*) FROM cron_tasks t WHERE t.group_name = g.name),
+                    (SELECT COUNT(*) FROM cron_tasks t WHERE t.group_name = g.name AND t.status = 'active')
+             FROM (SELECT ?1 AS name
+                   UNION SELECT name FROM cron_groups
+                   UNION SELECT group_name FROM cron_tasks) g
+             ORDER BY g.name = ?1 DESC, g.name",
+        )
+        .map_err(|e| format!("Query error: {}", e))?;
+    let groups = stmt
+        .query_map(params![DEFAULT_GROUP], |row| {
+            Ok(CronGroup {
+                name: row.get(0)?,
+                task_count: row.get(1)?,
+                active_count: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Map error: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Collect error: {}", e))?;
+    Ok(groups)
+}
+
+/// Set the status of every task in a group; returns how many changed.
+fn set_group_status(name: &str, status: &str) -> Result<usize, CronError> {
+    let conn = CRON_DB.lock();
+    let changed = conn
+        .execute(
+            "UPDATE cron_tasks SET status = ?1, updated_at = ?2 WHERE group_name = ?3 AND status != ?1",
+            params![status, Utc::now().to_rfc3339(), name.trim()],
+        )
+        .map_err(|e| format!("Failed to update group: {}", e))?;
+    info!("Cron group '{}': {} task(s) set to {}", name.trim(), changed, status);
+    Ok(changed)
+}
+
+pub fn pause_group(name: &str) -> Result<usize, CronError> {
+    set_group_status(name, "paused")
+}
+
+pub fn resume_group(name: &str) -> Result<usize, CronError> {
+    set_group_status(name, "active")
+}
+
+/// Delete a group. Its tasks are deleted too when `delete_tasks`, otherwise
+/// they move to the default group.
+pub fn delete_group(name: &str, delete_tasks: bool) -> Result<(), CronError> {
+    let name = normalize_group_name(name)?;
+    if name == DEFAULT_GROUP {
+        return Err(CronError::InvalidGroup("The default group cannot be deleted".to_string()));
+    }
+    let mut conn = CRON_DB.lock();
+    let tx = conn.transaction().map_err(|e| format!("Failed to delete group: {}", e))?;
+    if delete_tasks {
+        tx.execute(
+            "DELETE FROM cron_runs WHERE task_id IN (SELECT id FROM cron_tasks WHERE group_name = ?1)",
+            params![name],
+        )
+        .map_err(|e| format!("Failed to delete task runs: {}", e))?;
+        tx.execute("DELETE FROM cron_tasks WHERE group_name = ?1", params![name])
+            .map_err(|e| format!("Failed to delete tasks: {}", e))?;
+    } else {
+        tx.execute(
+            "UPDATE cron_tasks SET group_name = ?1, updated_at = ?2 WHERE group_name = ?3",
+            params![DEFAULT_GROUP, Utc::now().to_rfc3339(), name],
+        )
+        .map_err(|e| format!("Failed to move tasks: {}", e))?;
+    }
+    tx.execute("DELETE FROM cron_groups WHERE name = ?1", params![name])
+        .map_err(|e| format!("Failed to delete group: {}", e))?;
+    tx.commit().map_err(|e| format!("Failed to delete group: {}", e))?;
+    info!("Deleted cron group '{}' (tasks {})", name, if delete_tasks { "deleted" } else { "moved to default" });
+    Ok(())
+}
+
+// ============================================================================
+// Execution Locks (multi-instance)
+// ============================================================================
+
+/// Tasks are killed after this long; locks older than this + 60s are stale.
+const TASK_TIMEOUT_SECS: u64 = 3600;
+/// How long to wait for another instance's exclusive transaction when locking.
+const LOCK_BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Identifies this process among instances sharing helix.db.
+static INSTANCE_ID: Lazy<String> = Lazy::new(|| {
+    let host = hostname::get()
+        .map(|h| h.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "unknown".to_string());
+    format!("{}:{}", host, std::process::id())
+});
+
+fn is_lock_stale(locked_at: &str, now: DateTime<Utc>) -> bool {
+    match DateTime::parse_from_rfc3339(locked_at) {
+        Ok(ts) => (now - ts.with_timezone(&Utc)).num_seconds() > TASK_TIMEOUT_SECS as i64 + 60,
+        Err(_) => true,
+    }
+}
+
+/// Take the execution lock for a task inside an exclusive transaction.
+/// Returns the current holder instead if another run holds it.
+fn try_acquire_lock(task_id: &str) -> Result<Result<(), CronLock>, String> {
+    let mut conn = CRON_DB.lock();
+    let _ = conn.busy_timeout(LOCK_BUSY_TIMEOUT);
+    let result = (|| -> Result<Result<(), CronLock>, String> {
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Exclusive)
+            .map_err(|e| format!("Failed to lock task: {}", e))?;
+        let holder = tx
+            .query_row(
+                "SELECT task_id, locked_at, locked_by FROM cron_locks WHERE task_id = ?1",
+                params![task_id],
+                |row| Ok(CronLock { task_id: row.get(0)?, locked_at: row.get(1)?, locked_by: row.get(2)? }),
+            )
+            .ok();
+        if let Some(lock) = holder {
+            return Ok(Err(lock));
+        }
+        tx.execute(
+            "INSERT INTO cron_locks (task_id, locked_at, locked_by) VALUES (?1, ?2, ?3)",
+            params![task_id, Utc::now().to_rfc3339(), INSTANCE_ID.as_str()],
+        )
+        .map_err(|e| format!("Failed to lock task: {}", e))?;
+        tx.commit().map_err(|e| format!("Failed to lock task: {}", e))?;
+        Ok(Ok(()))
+    })();
+    let _ = conn.busy_timeout(std::time::Duration::from_millis(5000));
+    result
+}
+
+fn release_lock(conn: &Connection, task_id: &str) {
+    if let Err(e) = conn.execute(
+        "DELETE FROM cron_locks WHERE task_id = ?1 AND locked_by = ?2",
+        params![task_id, INSTANCE_ID.as_str()],
+    ) {
+        warn!("Failed to release cron lock for {}: {}", task_id, e);
+    }
+}
+
+pub fn list_locks() -> Result<Vec<CronLock>, CronError> {
+    let conn = CRON_DB.lock();
+    let mut stmt = conn
+        .prepare("SELECT task_id, locked_at, locked_by FROM cron_locks ORDER BY locked_at")
+        .map_err(|e| format!("Query error: {}", e))?;
+    let locks = stmt
+        .query_map([], |row| {
+            Ok(CronLock {
+                task_id: row.get(0)?,
+                locked_at: row.get(1)?,
+                locked_by: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Map error: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Collect error: {}", e))?;
+    Ok(locks)
+}
+
+/// Drop a task's lock regardless of holder (admin escape hatch).
+pub fn force_unlock(task_id: &str) -> Result<(), CronError> {
+    let conn = CRON_DB.lock();
+    let removed = conn
+        .execute("DELETE FROM cron_locks WHERE task_id = ?1", params![task_id])
+        .map_err(|e| format!("Failed to unlock task: {}", e))?;
+    if removed == 0 {
+        return Err(CronError::NotLocked(task_id.to_string()));
+    }
+    warn!("Cron lock for {} force-released", task_id);
+    Ok(())
+}
+
+/// Mark this instance's in-flight runs as `aborted` and drop its locks.
+/// Called on app exit so no run is left looking like it is still going.
+pub fn abort_running_runs() -> Result<usize, String> {
+    let conn = CRON_DB.lock();
+    let now = Utc::now().to_rfc3339();
+    let aborted = conn
+        .execute(
+            "UPDATE cron_runs SET finished_at = ?1, result = 'aborted', output = 'Interrupted by app shutdown'
+             WHERE result = 'running' AND task_id IN (SELECT task_id FROM cron_locks WHERE locked_by = ?2)",
+            params![now, INSTANCE_ID.as_str()],
+        )
+        .map_err(|e| format!("Failed to abort runs: {}", e))?;
+    conn.execute("DELETE FROM cron_locks WHERE locked_by = ?1", params![INSTANCE_ID.as_str()])
+        .map_err(|e| format!("Failed to release locks: {}", e))?;
+    Ok(aborted)
+}
+
+/// Remove locks whose holder must have died (older than the task timeout + 60s).
+fn clear_stale_locks() {
+    let now = Utc::now();
+    let stale: Vec<CronLock> = list_locks()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|l| is_lock_stale(&l.locked_at, now))
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    let conn = CRON_DB.lock();
+    for lock in stale {
+        warn!("Clearing stale cron lock for {} (held by {} since {})", lock.task_id, lock.locked_by, lock.locked_at);
+        let _ = conn.execute(
+            "DELETE FROM cron_locks WHERE task_id = ?1 AND locked_at = ?2",
+            params![lock.task_id, lock.locked_at],
+        );
+    }
+}
+
 // ============================================================================
 // Task Execution
 // ============================================================================
@@ -392,16 +1132,46 @@ fn start_run(task_id: &str) -> Result<i64, String> {
     Ok(conn.last_insert_rowid())
 }
 
-/// Finish a run.
-fn finish_run(run_id: i64, result: &str, output: &str) -> Result<(), String> {
+/// Finish a run, release the task's execution lock and prune its run history
+/// to `cron_max_runs` from the app config.
+fn finish_run(task_id: &str, run_id: i64, result: &str, output: &str) -> Result<(), String> {
+    let keep = crate::modules::config::load_app_config()
+        .map(|c| c.cron_max_runs)
+        .unwrap_or(DEFAULT_MAX_RUNS);
     let conn = CRON_DB.lock();
     let now = Utc::now().to_rfc3339();
+    let updated = conn
+        .execute(
+            "UPDATE cron_runs SET finished_at = ?1, result = ?2, output = ?3 WHERE id = ?4",
+            params![now, result, output, run_id],
+        )
+        .map_err(|e| format!("Failed to finish run: {}", e));
+    release_lock(&conn, task_id);
+    if keep > 0 {
+        match prune_runs_in(&conn, task_id, keep) {
+            Ok(0) => {}
+            Ok(n) => info!("Pruned {} old runs of cron task {}", n, task_id),
+            Err(e) => warn!("Failed to prune runs of cron task {}: {}", task_id, e),
+        }
+    }
+    updated.map(|_| ())
+}
+
+/// Delete all but the newest `keep` runs of a task. Returns how many were deleted.
+fn prune_runs_in(conn: &Connection, task_id: &str, keep: usize) -> Result<usize, String> {
     conn.execute(
-        "UPDATE cron_runs SET finished_at = ?1, result = ?2, output = ?3 WHERE id = ?4",
-        params![now, result, output, run_id],
+        "DELETE FROM cron_runs WHERE task_id = ?1 AND id NOT IN (
+             SELECT id FROM cron_runs WHERE task_id = ?1 ORDER BY started_at DESC, id DESC LIMIT ?2
+         )",
+        params![task_id, keep as i64],
     )
-    .map_err(|e| format!("Failed to finish run: {}", e))?;
-    Ok(())
+    .map_err(|e| format!("Failed to prune runs: {}", e))
+}
+
+/// Keep only the newest `keep` runs of a task. Returns how many were deleted.
+pub fn prune_runs(task_id: &str, keep: usize) -> Result<usize, CronError> {
+    get_task(task_id)?;
+    Ok(prune_runs_in(&CRON_DB.lock(), task_id, keep)?)
 }
 
 /// Update task last_run and last_result.
@@ -417,7 +1187,7 @@ fn update_task_run_status(task_id: &str, result: &str) -> Result<(), String> {
 }
 
 /// Get run history for a task.
-pub fn get_runs(task_id: &str, limit: i64) -> Result<Vec<CronRun>, String> {
+pub fn get_runs(task_id: &str, limit: i64) -> Result<Vec<CronRun>, CronError> {
     let conn = CRON_DB.lock();
     let mut stmt = conn
         .prepare(
@@ -444,42 +1214,96 @@ pub fn get_runs(task_id: &str, limit: i64) -> Result<Vec<CronRun>, String> {
     Ok(runs)
 }
 
-/// Execute a task (shell command).
-pub async fn execute_task(task_id: &str) -> Result<CronRun, String> {
-    let task = get_task(task_id)?;
-    let script = task.script.unwrap_or_default();
-
-    if script.is_empty() {
-        return Err("Task has no script to execute".to_string());
+/// Run a shell script (killed after TASK_TIMEOUT_SECS), sandboxed with
+/// `HELIX_SANDBOX=1`; returns (result, combined output).
+async fn run_script(script: &str) -> (&'static str, String) {
+    if crate::modules::sandbox::sandbox_enabled() {
+        let sandbox_dir = crate::modules::sandbox::sandbox_dir();
+        let config = crate::modules::sandbox::SandboxConfig {
+            allowed_paths: vec![sandbox_dir.clone()],
+            ..Default::default()
+        };
+        let run = crate::modules::sandbox::run_sandboxed(script, &sandbox_dir, config);
+        return match tokio::time::timeout(std::time::Duration::from_secs(TASK_TIMEOUT_SECS), run).await {
+            Ok(Ok((output, code))) => (if code == 0 { "success" } else { "error" }, output),
+            Ok(Err(e)) => ("error", format!("Failed to execute: {}", e)),
+            Err(_) => ("error", format!("Timed out after {}s", TASK_TIMEOUT_SECS)),
+        };
     }
 
-    let run_id = start_run(task_id)?;
-    info!("Executing cron task '{}' (run {})", task.name, run_id);
-
-    // Execute as shell command
-    let output = tokio::process::Command::new("sh")
+    let child = tokio::process::Command::new("sh")
         .arg("-c")
-        .arg(&script)
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute: {}", e))?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let combined = if stderr.is_empty() {
-        stdout.clone()
-    } else {
-        format!("{}\n[stderr]\n{}", stdout, stderr)
+        .arg(script)
+        .with_managed_env()
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(std::time::Duration::from_secs(TASK_TIMEOUT_SECS), child).await {
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            let combined = if stderr.is_empty() {
+                stdout
+            } else {
+                format!("{}\n[stderr]\n{}", stdout, stderr)
+            };
+            let result = if output.status.success() { "success" } else { "error" };
+            (result, combined)
+        }
+        Ok(Err(e)) => ("error", format!("Failed to execute: {}", e)),
+        Err(_) => ("error", format!("Timed out after {}s", TASK_TIMEOUT_SECS)),
+    }
+}
+
+/// Dispatch a built-in job by its reserved id.
+async fn run_internal_task(task_id: &str) -> Result<String, String> {
+    match task_id {
+        USAGE_REPORT_TASK_ID => crate::modules::usage::send_daily_report().await,
+        _ => Err(format!("Unknown internal task '{}'", task_id)),
+    }
+}
+
+/// Execute a task (shell command, or in-process for internal tasks).
+pub async fn execute_task(task_id: &str) -> Result<CronRun, CronError> {
+    let task = get_task(task_id)?;
+    let script = task.script.unwrap_or_default();
+    let internal = task.task_type == INTERNAL_TASK_TYPE;
+
+    if script.is_empty() && !internal {
+        return Err(CronError::NoScript);
+    }
+
+    // Another instance (or an overlapping run here) may already be executing it
+    if let Err(holder) = try_acquire_lock(task_id)? {
+        warn!(
+            "Skipping cron task '{}': already running on {} since {}",
+            task.name, holder.locked_by, holder.locked_at
+        );
+        return Err(CronError::AlreadyRunning { task: task.name, holder: holder.locked_by });
+    }
+
+    let run_id = match start_run(task_id) {
+        Ok(id) => id,
+        Err(e) => {
+            release_lock(&CRON_DB.lock(), task_id);
+            return Err(e.into());
+        }
     };
+    info!("Executing cron task '{}' (run {})", task.name, run_id);
 
-    let result = if output.status.success() {
-        "success"
+    let (result, combined) = if internal {
+        match run_internal_task(task_id).await {
+            Ok(output) => ("success", output),
+            Err(e) => ("error", e),
+        }
     } else {
-        "error"
+        run_script(&script).await
     };
 
-    finish_run(run_id, result, &combined)?;
+    finish_run(task_id, run_id, result, &combined)?;
     update_task_run_status(task_id, result)?;
+    if result == "success" {
+        trigger_dependents(task_id);
+    }
 
     // Send notification if configured
     if let Some(ref channel) = task.notify_channel {
@@ -509,6 +1333,489 @@ pub async fn execute_task(task_id: &str) -> Result<CronRun, String> {
     })
 }
 
+// ============================================================================
+// Internal Tasks
+// ============================================================================
+
+/// Convert a local "HH:MM" time of day into a daily cron expression in UTC
+/// (the scheduler evaluates schedules in UTC) for the given local offset.
+fn daily_utc_cron(time: &str, offset: chrono::FixedOffset) -> Result<String, CronError> {
+    let local = chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| CronError::InvalidExpr(format!("Invalid time '{}': expected HH:MM", time)))?;
+    let utc = local - chrono::Duration::seconds(offset.local_minus_utc() as i64);
+    Ok(format!("{} {} * * *", utc.minute(), utc.hour()))
+}
+
+/// Create or update the reserved daily usage report task from config.
+/// Called at startup and whenever the config is saved.
+pub fn sync_usage_report_task(config: &crate::models::config::UsageReportConfig) -> Result<(), CronError> {
+    let schedule = daily_utc_cron(&config.time, *chrono::Local::now().offset())?;
+    let status = if config.enabled { "active" } else { "paused" };
+    let now = Utc::now().to_rfc3339();
+
+    let conn = CRON_DB.lock();
+    conn.execute(
+        "INSERT INTO cron_tasks (id, name, description, task_type, schedule, status, created_at, updated_at, group_name)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7, ?8)
+         ON CONFLICT(id) DO UPDATE SET schedule = ?5, status = ?6, updated_at = ?7
+         WHERE schedule IS NOT ?5 OR status != ?6",
+        params![
+            USAGE_REPORT_TASK_ID,
+            "每日用量报告",
+            format!("Daily usage summary at {} (local) via {}", config.time.trim(), config.channel),
+            INTERNAL_TASK_TYPE,
+            schedule,
+            status,
+            now,
+            INTERNAL_GROUP,
+        ],
+    )
+    .map_err(|e| format!("Failed to sync usage report task: {}", e))?;
+    Ok(())
+}
+
+// ============================================================================
+// Import / Export
+// ============================================================================
+
+/// Version of the export file format.
+const EXPORT_VERSION: u32 = 1;
+
+/// Portable task definition; tasks are matched by name on import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTask {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type")]
+    pub task_type: String,
+    pub schedule: Option<String>,
+    pub script: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: String,
+    pub notify_channel: Option<String>,
+    #[serde(default = "default_group")]
+    pub group_name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<ExportedRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedRun {
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub result: String,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub tasks: Vec<ExportedTask>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CronImportReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub runs_imported: usize,
+    /// "<task name>: <reason>" for each task, or task's run history, that was not imported
+    pub errors: Vec<String>,
+}
+
+fn default_status() -> String {
+    "active".to_string()
+}
+
+/// Run history kept per task in an export.
+const EXPORT_RUNS_PER_TASK: i64 = 100;
+
+/// Serialize all user tasks (internal tasks are recreated from config) and,
+/// optionally, their recent runs to `path`. Returns the number of tasks written.
+pub fn export_tasks(path: &str, include_runs: bool) -> Result<usize, CronError> {
+    let mut tasks = Vec::new();
+    for task in list_tasks()?.into_iter().filter(|t| t.task_type != INTERNAL_TASK_TYPE) {
+        let runs = if include_runs {
+            get_runs(&task.id, EXPORT_RUNS_PER_TASK)?
+                .into_iter()
+                .filter(|r| r.result != "running")
+                .map(|r| ExportedRun { started_at: r.started_at, finished_at: r.finished_at, result: r.result, output: r.output })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        tasks.push(ExportedTask {
+            name: task.name,
+            description: task.description,
+            task_type: task.task_type,
+            schedule: task.schedule,
+            script: task.script,
+            status: task.status,
+            notify_channel: task.notify_channel,
+            group_name: task.group_name,
+            runs,
+        });
+    }
+    // Oldest first, so an import recreates tasks in their original order
+    tasks.reverse();
+
+    let export = CronExport { version: EXPORT_VERSION, exported_at: Utc::now().to_rfc3339(), tasks };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| format!("Serialize tasks: {}", e))?;
+    let path = crate::modules::agent_tools::expand_path(path);
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    info!("Exported {} cron tasks to {}", export.tasks.len(), path);
+    Ok(export.tasks.len())
+}
+
+/// Parse and check an export file before anything is written.
+fn parse_export(json: &str) -> Result<CronExport, CronError> {
+    let export: CronExport = serde_json::from_str(json).map_err(|e| CronError::InvalidImport(e.to_string()))?;
+    if export.version > EXPORT_VERSION {
+        return Err(CronError::InvalidImport(format!(
+            "version {} is newer than supported version {}",
+            export.version, EXPORT_VERSION
+        )));
+    }
+    Ok(export)
+}
+
+/// Create or update one exported task, matched by name; returns (task id, created).
+fn upsert_exported_task(task: &ExportedTask) -> Result<(String, bool), CronError> {
+    if task.name.trim().is_empty() {
+        return Err(CronError::InvalidImport("task name is empty".into()));
+    }
+    if task.task_type == INTERNAL_TASK_TYPE {
+        return Err(CronError::InvalidImport("internal tasks cannot be imported".into()));
+    }
+    // create_task/update_task only check schedules of cron tasks; check them all
+    if let Some(schedule) = task.schedule.as_deref().filter(|s| !s.trim().is_empty()) {
+        validate_cron_expr(schedule)?;
+    }
+    let status = match task.status.as_str() {
+        "active" | "paused" => task.status.clone(),
+        _ => default_status(),
+    };
+
+    let existing: Option<String> = CRON_DB
+        .lock()
+        .query_row(
+            "SELECT id FROM cron_tasks WHERE name = ?1 AND task_type != ?2 ORDER BY created_at LIMIT 1",
+            params![task.name, INTERNAL_TASK_TYPE],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up task: {}", e))?;
+
+    match existing {
+        Some(id) => {
+            // update_task validates before writing, so change the type only once it succeeded
+            update_task(
+                &id,
+                UpdateTaskInput {
+                    name: None,
+                    description: Some(task.description.clone()),
+                    schedule: task.schedule.clone(),
+                    script: task.script.clone(),
+                    status: Some(status),
+                    notify_channel: Some(task.notify_channel.clone().map(Value::String).unwrap_or(Value::Null)),
+                    group_name: Some(task.group_name.clone()),
+                    depends_on: None,
+                    run_after_success: None,
+                },
+            )?;
+            CRON_DB
+                .lock()
+                .execute("UPDATE cron_tasks SET task_type = ?2 WHERE id = ?1", params![id, task.task_type])
+                .map_err(|e| format!("Failed to update task: {}", e))?;
+            Ok((id, false))
+        }
+        None => {
+            let created = create_task(CreateTaskInput {
+                name: task.name.clone(),
+                description: Some(task.description.clone()),
+                task_type: task.task_type.clone(),
+                schedule: task.schedule.clone(),
+                script: task.script.clone(),
+                notify_channel: task.notify_channel.clone(),
+                group_name: Some(task.group_name.clone()),
+                depends_on: None,
+                run_after_success: None,
+            })?;
+            if status != created.status {
+                update_task(
+                    &created.id,
+                    UpdateTaskInput {
+                        name: None,
+                        description: None,
+                        schedule: None,
+                        script: None,
+                        status: Some(status),
+                        notify_channel: None,
+                        group_name: None,
+                        depends_on: None,
+                        run_after_success: None,
+                    },
+                )?;
+            }
+            Ok((created.id, true))
+        }
+    }
+}
+
+/// Add exported runs to `task_id`, skipping runs already recorded. Returns how many were added.
+fn import_runs(task_id: &str, runs: &[ExportedRun]) -> Result<usize, CronError> {
+    let conn = CRON_DB.lock();
+    let mut added = 0;
+    for run in runs {
+        added += conn
+            .execute(
+                "INSERT INTO cron_runs (task_id, started_at, finished_at, result, output)
+                 SELECT ?1, ?2, ?3, ?4, ?5
+                 WHERE NOT EXISTS (SELECT 1 FROM cron_runs WHERE task_id = ?1 AND started_at = ?2)",
+                params![task_id, run.started_at, run.finished_at, run.result, run.output],
+            )
+            .map_err(|e| format!("Failed to import run: {}", e))?;
+    }
+    Ok(added)
+}
+
+/// Import tasks from an export file, upserting by name. Tasks that fail
+/// validation, and run histories that fail to insert, are reported and
+/// skipped; the others are still imported.
+pub fn import_tasks(path: &str) -> Result<CronImportReport, CronError> {
+    let path = crate::modules::agent_tools::expand_path(path);
+    let json = std::fs::read_to_string(&path).map_err(|e| CronError::InvalidImport(format!("{}: {}", path, e)))?;
+    let export = parse_export(&json)?;
+
+    let mut report = CronImportReport::default();
+    for task in &export.tasks {
+        match upsert_exported_task(task) {
+            Ok((id, created)) => {
+                match import_runs(&id, &task.runs) {
+                    Ok(added) => report.runs_imported += added,
+                    Err(e) => report.errors.push(format!("{}: runs not imported: {}", task.name, e)),
+                }
+                if created {
+                    report.created.push(task.name.clone());
+                } else {
+                    report.updated.push(task.name.clone());
+                }
+            }
+            Err(e) => report.errors.push(format!("{}: {}", task.name, e)),
+        }
+    }
+    info!(
+        "Imported cron tasks from {}: {} created, {} updated, {} failed",
+        path,
+        report.created.len(),
+        report.updated.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
+
+// ============================================================================
+// Task Templates
+// ============================================================================
+
+/// `{{name}}` placeholders in template scripts.
+static TEMPLATE_VAR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+/// What a template variable holds; values are checked against it before rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateVarKind {
+    /// Local time of day, HH:MM
+    Time,
+    /// Absolute path (`~/` is expanded)
+    Path,
+    /// Whole number of days
+    Days,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateVariable {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: TemplateVarKind,
+    /// None means the variable is required
+    pub default: Option<&'static str>,
+}
+
+/// A built-in task. Every template runs daily at its `time` variable (local
+/// HH:MM); the other values are validated by kind and shell-quoted into the script.
+#[derive(Debug, Clone, Serialize)]
+pub struct CronTemplate {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub script: &'static str,
+    pub variables: &'static [TemplateVariable],
+}
+
+const TIME_VARIABLE: &str = "time";
+
+pub const CRON_TEMPLATES: &[CronTemplate] = &[
+    CronTemplate {
+        id: "daily-digest",
+        name: "每日摘要",
+        description: "Host uptime, load, disk and memory usage; pair with a notify channel",
+        script: "echo \"== $(hostname) $(date '+%Y-%m-%d %H:%M') ==\"; uptime; df -h {{path}}; \
+                 (free -h 2>/dev/null || vm_stat 2>/dev/null | head -n 5)",
+        variables: &[
+            TemplateVariable {
+                name: TIME_VARIABLE,
+                description: "Local time of day (HH:MM)",
+                kind: TemplateVarKind::Time,
+                default: Some("09:00"),
+            },
+            TemplateVariable {
+                name: "path",
+                description: "Filesystem to report",
+                kind: TemplateVarKind::Path,
+                default: Some("/"),
+            },
+        ],
+    },
+    CronTemplate {
+        id: "disk-cleanup",
+        name: "磁盘清理",
+        description: "Delete files older than N days from a directory",
+        script: "find {{dir}} -type f -mtime +{{days}} -print -delete",
+        variables: &[
+            TemplateVariable {
+                name: TIME_VARIABLE,
+                description: "Local time of day (HH:MM)",
+                kind: TemplateVarKind::Time,
+                default: Some("03:00"),
+            },
+            TemplateVariable {
+                name: "dir",
+                description: "Directory to clean",
+                kind: TemplateVarKind::Path,
+                default: None,
+            },
+            TemplateVariable {
+                name: "days",
+                description: "Minimum age in days",
+                kind: TemplateVarKind::Days,
+                default: Some("30"),
+            },
+        ],
+    },
+    CronTemplate {
+        id: "backup",
+        name: "目录备份",
+        description: "Archive a directory into a timestamped .tar.gz and prune old archives",
+        script: "mkdir -p {{dest}} && tar -czf {{dest}}/backup-$(date +%Y%m%d-%H%M%S).tar.gz -C {{source}} . \
+                 && find {{dest}} -name 'backup-*.tar.gz' -mtime +{{keep_days}} -print -delete",
+        variables: &[
+            TemplateVariable {
+                name: TIME_VARIABLE,
+                description: "Local time of day (HH:MM)",
+                kind: TemplateVarKind::Time,
+                default: Some("02:00"),
+            },
+            TemplateVariable {
+                name: "source",
+                description: "Directory to back up",
+                kind: TemplateVarKind::Path,
+                default: None,
+            },
+            TemplateVariable {
+                name: "dest",
+                description: "Directory for the archives",
+                kind: TemplateVarKind::Path,
+                default: None,
+            },
+            TemplateVariable {
+                name: "keep_days",
+                description: "Days to keep old archives",
+                kind: TemplateVarKind::Days,
+                default: Some("14"),
+            },
+        ],
+    },
+];
+
+pub fn find_template(id: &str) -> Result<&'static CronTemplate, CronError> {
+    CRON_TEMPLATES
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| CronError::TemplateNotFound(id.to_string()))
+}
+
+/// Check `value` against the variable's kind; returns the value to substitute.
+fn check_template_value(var: &TemplateVariable, value: &str) -> Result<String, CronError> {
+    let invalid = |reason: &str| CronError::InvalidVariable { name: var.name.to_string(), reason: reason.to_string() };
+    match var.kind {
+        TemplateVarKind::Time => {
+            chrono::NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| invalid("expected HH:MM"))?;
+            Ok(value.to_string())
+        }
+        TemplateVarKind::Path => {
+            let path = crate::modules::agent_tools::expand_path(value);
+            if !std::path::Path::new(&path).is_absolute() {
+                return Err(invalid("expected an absolute path"));
+            }
+            Ok(path)
+        }
+        TemplateVarKind::Days => {
+            value.parse::<u32>().map(|days| days.to_string()).map_err(|_| invalid("expected a whole number"))
+        }
+    }
+}
+
+/// Fill a template's variables from `vars` and defaults; returns (schedule, script).
+fn render_template(
+    template: &CronTemplate,
+    vars: &HashMap<String, String>,
+    offset: chrono::FixedOffset,
+) -> Result<(String, String), CronError> {
+    let mut values: HashMap<&str, String> = HashMap::new();
+    let mut missing = Vec::new();
+    for var in template.variables {
+        match vars.get(var.name).map(|v| v.trim()).filter(|v| !v.is_empty()).or(var.default) {
+            Some(value) => {
+                values.insert(var.name, check_template_value(var, value)?);
+            }
+            None => missing.push(var.name.to_string()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(CronError::MissingVariables(missing));
+    }
+
+    let schedule = daily_utc_cron(values.get(TIME_VARIABLE).map(String::as_str).unwrap_or_default(), offset)?;
+    let script = TEMPLATE_VAR_RE
+        .replace_all(template.script, |cap: &regex::Captures| match values.get(&cap[1]) {
+            Some(value) => crate::modules::commands::shell_quote(value),
+            None => cap[0].to_string(),
+        })
+        .into_owned();
+    Ok((schedule, script))
+}
+
+/// Create a task from a built-in template. Besides the template variables,
+/// `vars` may set `name`, `group` and `notify_channel` of the new task.
+pub fn create_task_from_template(template_id: &str, vars: &HashMap<String, String>) -> Result<CronTask, CronError> {
+    let template = find_template(template_id)?;
+    let (schedule, script) = render_template(template, vars, *chrono::Local::now().offset())?;
+    let option = |key: &str| vars.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    create_task(CreateTaskInput {
+        name: option("name").unwrap_or_else(|| template.name.to_string()),
+        description: Some(template.description.to_string()),
+        task_type: "cron".to_string(),
+        schedule: Some(schedule),
+        script: Some(script),
+        notify_channel: option("notify_channel"),
+        group_name: option("group"),
+        depends_on: None,
+        run_after_success: None,
+    })
+}
+
 // ============================================================================
 // Background Cron Scheduler
 // ============================================================================
@@ -517,16 +1824,99 @@ pub async fn execute_task(task_id: &str) -> Result<CronRun, String> {
 static LAST_FIRE: Lazy<Mutex<HashMap<String, DateTime<Utc>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// Set by the tray "pause cron scheduler" toggle; the loop skips ticks while set.
+static SCHEDULER_PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_scheduler_paused() -> bool {
+    SCHEDULER_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Pause or resume automatic task firing (manual `cron_run_task` still works).
+pub fn set_scheduler_paused(paused: bool) {
+    if SCHEDULER_PAUSED.swap(paused, Ordering::Relaxed) != paused {
+        info!("Cron scheduler {}", if paused { "paused" } else { "resumed" });
+        crate::modules::tray::request_refresh();
+    }
+}
+
+/// How often the cron scheduler checks for due tasks
+const CRON_TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Schedule of a task the scheduler fires automatically, if it has one.
+fn auto_schedule(task: &CronTask) -> Option<Schedule> {
+    if task.status != "active" || (task.task_type != "cron" && task.task_type != INTERNAL_TASK_TYPE) {
+        return None;
+    }
+    let schedule_str = task.schedule.as_deref().filter(|s| !s.is_empty())?;
+    Schedule::from_str(&normalize_cron_expr(schedule_str)).ok()
+}
+
+/// Tasks with a trigger time in `[from, until]`, i.e. missed while the machine slept.
+fn missed_tasks(tasks: &[CronTask], from: DateTime<Utc>, until: DateTime<Utc>) -> Vec<&CronTask> {
+    tasks
+        .iter()
+        .filter(|task| {
+            auto_schedule(task)
+                .and_then(|schedule| schedule.after(&(from - chrono::Duration::seconds(1))).next())
+                .is_some_and(|trigger| trigger <= until)
+        })
+        .collect()
+}
+
+/// Run every task missed during `window` once, `stagger` apart, instead of
+/// all at the same moment.
+fn fire_missed_tasks(
+    tasks: &[CronTask],
+    window: crate::modules::scheduler::SleepWindow,
+    stagger: std::time::Duration,
+) {
+    // Triggers in the last minute are left to the regular check
+    let missed = missed_tasks(tasks, window.from, window.to - chrono::Duration::seconds(60));
+    if missed.is_empty() {
+        return;
+    }
+    info!(
+        "Cron scheduler resumed after sleep: running {} missed task(s), {}s apart",
+        missed.len(),
+        stagger.as_secs()
+    );
+    let mut last_fires = LAST_FIRE.lock();
+    for (i, task) in missed.into_iter().enumerate() {
+        last_fires.insert(task.id.clone(), window.to);
+        let task_id = task.id.clone();
+        let task_name = task.name.clone();
+        let delay = stagger * i as u32;
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            info!("Cron scheduler catching up task: {}", task_name);
+            if let Err(e) = execute_task(&task_id).await {
+                error!("Cron task '{}' failed: {}", task_name, e);
+            }
+        });
+    }
+}
+
 /// Start the background scheduler loop. Call once at app setup.
 pub fn start_cron_scheduler() {
-    tauri::async_runtime::spawn(async move {
+    crate::modules::infra::shutdown::spawn("cron-scheduler", |mut shutdown| async move {
         info!("Cron scheduler started");
 
         // Check every 30 seconds for due tasks
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+        let mut interval = tokio::time::interval(CRON_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            let slept = crate::modules::scheduler::record_wake("cron", CRON_TICK);
+
+            if is_scheduler_paused() {
+                continue;
+            }
+
+            clear_stale_locks();
 
             let tasks = match list_tasks() {
                 Ok(t) => t,
@@ -537,22 +1927,22 @@ pub fn start_cron_scheduler() {
             };
 
             let now = Utc::now();
+            let next_due = upcoming_runs(&tasks, now, 24 * 365)
+                .first()
+                .and_then(|run| DateTime::parse_from_rfc3339(&run.next_run_at).ok())
+                .map(|at| at.with_timezone(&Utc));
+            crate::modules::scheduler::set_next_due("cron", next_due);
+
+            if let Some(window) = slept {
+                let stagger = crate::modules::config::load_app_config()
+                    .map(|c| c.scheduler.resume_stagger_secs)
+                    .unwrap_or(10);
+                fire_missed_tasks(&tasks, window, std::time::Duration::from_secs(stagger));
+            }
 
-            for task in tasks {
-                if task.status != "active" || task.task_type != "cron" {
+            for task in &tasks {
+                let Some(schedule) = auto_schedule(task) else {
                     continue;
-                }
-
-                let schedule_str = match task.schedule.as_ref() {
-                    Some(s) if !s.is_empty() => s.clone(),
-                    _ => continue,
-                };
-
-                // Check if task is due
-                let normalized = normalize_cron_expr(&schedule_str);
-                let schedule = match Schedule::from_str(&normalized) {
-                    Ok(s) => s,
-                    Err(_) => continue,
                 };
 
                 // Find the most recent past trigger time
@@ -592,37 +1982,109 @@ pub fn start_cron_scheduler() {
 // ============================================================================
 
 #[tauri::command]
-pub async fn cron_list_tasks() -> Result<Vec<CronTask>, String> {
-    list_tasks()
+pub async fn cron_list_tasks(group: Option<String>) -> HelixResult<Vec<CronTask>> {
+    Ok(list_tasks_in_group(group.as_deref().map(str::trim).filter(|g| !g.is_empty()))?)
+}
+
+#[tauri::command]
+pub async fn cron_create_group(name: String) -> HelixResult<()> {
+    Ok(create_group(&name)?)
+}
+
+#[tauri::command]
+pub async fn cron_list_groups() -> HelixResult<Vec<CronGroup>> {
+    Ok(list_groups()?)
+}
+
+#[tauri::command]
+pub async fn cron_pause_group(name: String) -> HelixResult<usize> {
+    Ok(pause_group(&name)?)
+}
+
+#[tauri::command]
+pub async fn cron_resume_group(name: String) -> HelixResult<usize> {
+    Ok(resume_group(&name)?)
+}
+
+#[tauri::command]
+pub async fn cron_delete_group(name: String, delete_tasks: bool) -> HelixResult<()> {
+    Ok(delete_group(&name, delete_tasks)?)
+}
+
+#[tauri::command]
+pub async fn cron_create_task(input: CreateTaskInput) -> HelixResult<CronTask> {
+    Ok(create_task(input)?)
+}
+
+#[tauri::command]
+pub async fn cron_update_task(id: String, input: UpdateTaskInput) -> HelixResult<CronTask> {
+    Ok(update_task(&id, input)?)
 }
 
 #[tauri::command]
-pub async fn cron_create_task(input: CreateTaskInput) -> Result<CronTask, String> {
-    create_task(input)
+pub async fn cron_delete_task(id: String) -> HelixResult<()> {
+    Ok(delete_task(&id)?)
 }
 
 #[tauri::command]
-pub async fn cron_update_task(id: String, input: UpdateTaskInput) -> Result<CronTask, String> {
-    update_task(&id, input)
+pub async fn cron_run_task(id: String) -> HelixResult<CronRun> {
+    Ok(execute_task(&id).await?)
 }
 
+/// Upcoming runs within `hours` (default 24), for a timeline of automations.
 #[tauri::command]
-pub async fn cron_delete_task(id: String) -> Result<(), String> {
-    delete_task(&id)
+pub async fn cron_next_runs(hours: Option<u32>) -> HelixResult<Vec<ScheduledRun>> {
+    Ok(next_runs(hours.unwrap_or(24))?)
 }
 
 #[tauri::command]
-pub async fn cron_run_task(id: String) -> Result<CronRun, String> {
-    execute_task(&id).await
+pub async fn cron_list_locks() -> HelixResult<Vec<CronLock>> {
+    Ok(list_locks()?)
 }
 
 #[tauri::command]
-pub async fn cron_get_runs(task_id: String, limit: Option<i64>) -> Result<Vec<CronRun>, String> {
-    get_runs(&task_id, limit.unwrap_or(20))
+pub async fn cron_force_unlock(task_id: String) -> HelixResult<()> {
+    Ok(force_unlock(&task_id)?)
 }
 
 #[tauri::command]
-pub async fn cron_validate_expr(expr: String) -> Result<Value, String> {
+pub async fn cron_get_runs(task_id: String, limit: Option<i64>) -> HelixResult<Vec<CronRun>> {
+    Ok(get_runs(&task_id, limit.unwrap_or(20))?)
+}
+
+/// Delete all but the newest `keep` runs of a task; returns how many were deleted.
+#[tauri::command]
+pub async fn cron_prune_runs(task_id: String, keep: usize) -> HelixResult<usize> {
+    Ok(prune_runs(&task_id, keep)?)
+}
+
+/// Export all tasks (and their recent runs with `include_runs`) to a JSON file.
+#[tauri::command]
+pub async fn cron_export(path: String, include_runs: Option<bool>) -> HelixResult<usize> {
+    Ok(export_tasks(&path, include_runs.unwrap_or(false))?)
+}
+
+/// Import tasks from a `cron_export` file, updating tasks with the same name.
+#[tauri::command]
+pub async fn cron_import(path: String) -> HelixResult<CronImportReport> {
+    Ok(import_tasks(&path)?)
+}
+
+#[tauri::command]
+pub async fn cron_list_templates() -> HelixResult<Vec<CronTemplate>> {
+    Ok(CRON_TEMPLATES.to_vec())
+}
+
+#[tauri::command]
+pub async fn cron_create_from_template(
+    template_id: String,
+    vars: Option<HashMap<String, String>>,
+) -> HelixResult<CronTask> {
+    Ok(create_task_from_template(&template_id, &vars.unwrap_or_default())?)
+}
+
+#[tauri::command]
+pub async fn cron_validate_expr(expr: String) -> HelixResult<Value> {
     match validate_cron_expr(&expr) {
         Ok(()) => {
             let next = compute_next_run(&expr);
@@ -633,7 +2095,7 @@ pub async fn cron_validate_expr(expr: String) -> Result<Value, String> {
         }
         Err(e) => Ok(serde_json::json!({
             "valid": false,
-            "error": e,
+            "error": e.to_string(),
         })),
     }
 }
@@ -642,73 +2104,530 @@ pub async fn cron_validate_expr(expr: String) -> Result<Value, String> {
 // Heartbeat System (Inspired by CoPaw's HEARTBEAT.md)
 // ============================================================================
 
-/// Default heartbeat interval in seconds (30 minutes)
-const HEARTBEAT_INTERVAL_SECS: u64 = 30 * 60;
+/// How often the heartbeat loop re-reads its config, so changes apply live
+const HEARTBEAT_POLL: std::time::Duration = std::time::Duration::from_secs(60);
+/// Longest heartbeat interval accepted (one day)
+const HEARTBEAT_MAX_INTERVAL_MINUTES: u32 = 24 * 60;
+
+/// Largest HEARTBEAT.md accepted, since it is sent as a prompt every interval
+const HEARTBEAT_MAX_BYTES: usize = 16 * 1024;
+
+/// ~/.helix/HEARTBEAT.md
+fn heartbeat_path() -> Option<std::path::PathBuf> {
+    Some(dirs::home_dir()?.join(".helix").join("HEARTBEAT.md"))
+}
 
 /// Check if heartbeat is configured (HEARTBEAT.md exists in ~/.helix/)
 fn load_heartbeat_config() -> Option<String> {
-    let helix_dir = dirs::home_dir()?.join(".helix");
-    let heartbeat_path = helix_dir.join("HEARTBEAT.md");
-    std::fs::read_to_string(&heartbeat_path).ok()
+    std::fs::read_to_string(heartbeat_path()?).ok()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatFile {
+    pub path: String,
+    pub exists: bool,
+    pub content: String,
 }
 
-/// Start the heartbeat loop. Reads ~/.helix/HEARTBEAT.md periodically
-/// and sends its content as a prompt to the agent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeartbeatValidation {
+    pub valid: bool,
+    /// Problems that keep the heartbeat from running properly; saving is refused
+    pub errors: Vec<String>,
+    /// Likely mistakes that do not block saving
+    pub warnings: Vec<String>,
+}
+
+/// Check HEARTBEAT.md content before it is saved. The heartbeat loop skips an
+/// empty file and sends the rest verbatim as a prompt, so it must be non-empty
+/// readable text of a reasonable size; an unclosed code fence would swallow
+/// the HEARTBEAT_OK instruction appended after it.
+pub fn validate_heartbeat(content: &str) -> HeartbeatValidation {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if content.trim().is_empty() {
+        errors.push("content is empty; the heartbeat would be skipped".to_string());
+    }
+    if content.len() > HEARTBEAT_MAX_BYTES {
+        errors.push(format!(
+            "content is {} bytes, more than the {} byte limit",
+            content.len(),
+            HEARTBEAT_MAX_BYTES
+        ));
+    }
+
+    let mut open_fence: Option<usize> = None;
+    let mut has_items = false;
+    for (i, line) in content.lines().enumerate() {
+        let line_no = i + 1;
+        if line.chars().any(|c| c.is_control() && c != '\t' && c != '\r') {
+            errors.push(format!("line {}: contains control characters", line_no));
+        }
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            open_fence = match open_fence {
+                Some(_) => None,
+                None => Some(line_no),
+            };
+            continue;
+        }
+        if open_fence.is_none() {
+            let numbered = trimmed.split_once(['.', ')']).is_some_and(|(n, rest)| {
+                !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) && rest.starts_with(' ')
+            });
+            if numbered || ["- ", "* ", "+ "].iter().any(|m| trimmed.starts_with(m)) {
+                has_items = true;
+            }
+        }
+    }
+    if let Some(line_no) = open_fence {
+        errors.push(format!("line {}: code block is never closed", line_no));
+    }
+    if errors.is_empty() && !has_items {
+        warnings.push("no list items found; list the things to check as \"- item\" or \"1. item\"".to_string());
+    }
+
+    HeartbeatValidation { valid: errors.is_empty(), errors, warnings }
+}
+
+fn load_heartbeat_settings() -> crate::models::config::HeartbeatConfig {
+    crate::modules::config::load_app_config()
+        .map(|c| c.heartbeat)
+        .unwrap_or_default()
+}
+
+/// Start the heartbeat loop. Reads ~/.helix/HEARTBEAT.md every
+/// `heartbeat.interval_minutes` and sends its content as a prompt to the agent.
+/// The config is re-read every minute, so disabling or retiming applies live.
 pub fn start_heartbeat() {
-    tauri::async_runtime::spawn(async move {
+    crate::modules::infra::shutdown::spawn("heartbeat", |mut shutdown| async move {
         // Wait 60 seconds after startup before first heartbeat
-        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+        tokio::select! {
+            _ = tokio::time::sleep(tokio::time::Duration::from_secs(60)) => {}
+            _ = shutdown.cancelled() => return,
+        }
 
-        let mut interval = tokio::time::interval(
-            tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS)
+        let settings = load_heartbeat_settings();
+        info!(
+            "Heartbeat system started (enabled: {}, interval: {}m)",
+            settings.enabled, settings.interval_minutes
         );
-
-        info!("Heartbeat system started (interval: {}s)", HEARTBEAT_INTERVAL_SECS);
+        let mut last_run: Option<DateTime<Utc>> = None;
 
         loop {
-            interval.tick().await;
+            let slept = crate::modules::scheduler::record_wake("heartbeat", HEARTBEAT_POLL);
+            let settings = load_heartbeat_settings();
+            let interval = chrono::Duration::minutes(settings.interval_minutes.max(1) as i64);
+            let now = Utc::now();
+            let due_at = last_run.map_or(now, |at| at + interval);
+
+            if settings.enabled && due_at <= now {
+                last_run = Some(now);
+                // A heartbeat that fell due while the machine slept is stale
+                if slept.is_some_and(|w| w.contains(due_at)) && !settings.catch_up {
+                    info!("[heartbeat] Skipping heartbeat due at {} during sleep", due_at.to_rfc3339());
+                } else {
+                    heartbeat_tick().await;
+                }
+            }
+            let next_due = last_run.map_or(now, |at| at + interval);
+            crate::modules::scheduler::set_next_due("heartbeat", settings.enabled.then_some(next_due));
+            // A long heartbeat run must not count as a suspend on the next wake
+            crate::modules::scheduler::record_idle("heartbeat");
+
+            tokio::select! {
+                _ = tokio::time::sleep(HEARTBEAT_POLL) => {}
+                _ = shutdown.cancelled() => break,
+            }
+        }
+    });
+}
 
-            // Check if HEARTBEAT.md exists
-            let heartbeat_content = match load_heartbeat_config() {
-                Some(content) if !content.trim().is_empty() => content,
-                _ => continue, // No heartbeat config, skip
-            };
+/// One scheduled heartbeat: skipped without HEARTBEAT.md or outside active hours.
+async fn heartbeat_tick() {
+    // Check if HEARTBEAT.md exists
+    let heartbeat_content = match load_heartbeat_config() {
+        Some(content) if !content.trim().is_empty() => content,
+        _ => return, // No heartbeat config, skip
+    };
 
-            // Check active hours (default: 8:00 - 23:00)
-            let hour = chrono::Local::now().hour();
-            if !(8..=23).contains(&hour) {
-                continue; // Outside active hours
+    // Check active hours (default: 8:00 - 23:00)
+    let hour = chrono::Local::now().hour();
+    if !(8..=23).contains(&hour) {
+        return; // Outside active hours
+    }
+
+    if let Err(e) = run_heartbeat(&heartbeat_content).await {
+        warn!("[heartbeat] Agent error: {}", e);
+    }
+}
+
+/// Send HEARTBEAT.md through the agent once and return its response.
+async fn run_heartbeat(heartbeat_content: &str) -> Result<String, String> {
+    info!("[heartbeat] Executing heartbeat check");
+
+    // Build heartbeat prompt
+    let prompt = format!(
+        "[HEARTBEAT] {}\n\n{}\n\nIf nothing needs attention, respond with HEARTBEAT_OK.",
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        heartbeat_content.trim()
+    );
+
+    // Run through the agent
+    let response = crate::modules::agent::agent_process_message("heartbeat", &prompt, None).await?;
+    if response.trim() != "HEARTBEAT_OK" && !response.is_empty() {
+        info!("[heartbeat] Agent response: {}", &response[..response.len().min(200)]);
+        // Emit heartbeat result to frontend
+        crate::modules::agent::emit_agent_progress(
+            "heartbeat",
+            serde_json::json!({ "response": response }),
+        );
+    }
+    Ok(response)
+}
+
+/// Run a heartbeat immediately (tray "run heartbeat now"), ignoring active hours.
+pub async fn run_heartbeat_now() -> Result<String, String> {
+    let content = load_heartbeat_config()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| "~/.helix/HEARTBEAT.md 不存在或为空".to_string())?;
+    run_heartbeat(&content).await
+}
+
+#[tauri::command]
+pub async fn heartbeat_get() -> HelixResult<HeartbeatFile> {
+    let path = heartbeat_path().ok_or_else(|| HelixError::internal("home directory not found"))?;
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(HelixError::internal(format!("read {}: {}", path.display(), e))),
+    };
+    Ok(HeartbeatFile {
+        path: path.display().to_string(),
+        exists: content.is_some(),
+        content: content.unwrap_or_default(),
+    })
+}
+
+#[tauri::command]
+pub async fn heartbeat_validate(content: String) -> HelixResult<HeartbeatValidation> {
+    Ok(validate_heartbeat(&content))
+}
+
+/// Update the heartbeat on/off switch and interval; the loop picks them up within a minute.
+#[tauri::command]
+pub async fn heartbeat_set_config(
+    enabled: Option<bool>,
+    interval_minutes: Option<u32>,
+) -> HelixResult<crate::models::config::HeartbeatConfig> {
+    if let Some(minutes) = interval_minutes {
+        if !(1..=HEARTBEAT_MAX_INTERVAL_MINUTES).contains(&minutes) {
+            return Err(HelixError::validation_error(vec![format!(
+                "interval_minutes must be between 1 and {}",
+                HEARTBEAT_MAX_INTERVAL_MINUTES
+            )]));
+        }
+    }
+    let mut config = crate::modules::config::load_app_config()
+        .map_err(|e| HelixError::new(codes::CONFIG_LOAD_FAILED, e))?;
+    if let Some(v) = enabled {
+        config.heartbeat.enabled = v;
+    }
+    if let Some(v) = interval_minutes {
+        config.heartbeat.interval_minutes = v;
+    }
+    crate::modules::config::save_app_config(&config).map_err(HelixError::internal)?;
+    info!(
+        "[heartbeat] Config updated: enabled={}, interval={}m",
+        config.heartbeat.enabled, config.heartbeat.interval_minutes
+    );
+    Ok(config.heartbeat)
+}
+
+/// Validate and save HEARTBEAT.md; refuses malformed content.
+#[tauri::command]
+pub async fn heartbeat_set(content: String) -> HelixResult<HeartbeatValidation> {
+    let validation = validate_heartbeat(&content);
+    if !validation.valid {
+        return Err(HelixError::validation_error(validation.errors));
+    }
+    let path = heartbeat_path().ok_or_else(|| HelixError::internal("home directory not found"))?;
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("md.tmp");
+        std::fs::write(&tmp, &content)?;
+        std::fs::rename(&tmp, &path)
+    };
+    write().map_err(|e| HelixError::internal(format!("write {}: {}", path.display(), e)))?;
+    info!("[heartbeat] Saved {} ({} bytes)", path.display(), content.len());
+    Ok(validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zh_numerals_to_digits() {
+        assert_eq!(zh_numerals_to_digits("每天早上八点半"), "每天早上8点半");
+        assert_eq!(zh_numerals_to_digits("十二点二十五分"), "12点25分");
+        assert_eq!(zh_numerals_to_digits("每两小时"), "每2小时");
+    }
+
+    #[test]
+    fn test_parse_natural_schedule_zh() {
+        let p = |s: &str| parse_natural_schedule(s);
+        assert_eq!(p("每天早上8点提醒我喝水").as_deref(), Some("0 8 * * *"));
+        assert_eq!(p("每天晚上九点半").as_deref(), Some("30 21 * * *"));
+        assert_eq!(p("每周一上午10点").as_deref(), Some("0 10 * * MON"));
+        assert_eq!(p("每星期日").as_deref(), Some("0 9 * * SUN"));
+        assert_eq!(p("工作日下午6点").as_deref(), Some("0 18 * * MON-FRI"));
+        assert_eq!(p("每月15号中午12点").as_deref(), Some("0 12 15 * *"));
+        assert_eq!(p("每30分钟").as_deref(), Some("*/30 * * * *"));
+        assert_eq!(p("每隔两个小时").as_deref(), Some("0 */2 * * *"));
+    }
+
+    #[test]
+    fn test_parse_natural_schedule_en() {
+        let p = |s: &str| parse_natural_schedule(s);
+        assert_eq!(p("every day at 8am").as_deref(), Some("0 8 * * *"));
+        assert_eq!(p("Daily at 20:30").as_deref(), Some("30 20 * * *"));
+        assert_eq!(p("every Friday at 5:15 pm").as_deref(), Some("15 17 * * FRI"));
+        assert_eq!(p("weekdays at 9").as_deref(), Some("0 9 * * MON-FRI"));
+        assert_eq!(p("every 5 minutes").as_deref(), Some("*/5 * * * *"));
+        assert_eq!(p("hourly").as_deref(), Some("0 * * * *"));
+    }
+
+    #[test]
+    fn test_normalize_group_name() {
+        assert_eq!(normalize_group_name("  data pipeline ").unwrap(), "data pipeline");
+        assert!(normalize_group_name("   ").is_err());
+        assert!(normalize_group_name(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_daily_utc_cron() {
+        let east8 = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+        assert_eq!(daily_utc_cron("21:00", east8).unwrap(), "0 13 * * *");
+        assert_eq!(daily_utc_cron("07:30", east8).unwrap(), "30 23 * * *");
+        let west5 = chrono::FixedOffset::west_opt(5 * 3600).unwrap();
+        assert_eq!(daily_utc_cron(" 22:15 ", west5).unwrap(), "15 3 * * *");
+        assert!(daily_utc_cron("9pm", east8).is_err());
+    }
+
+    #[test]
+    fn test_is_lock_stale() {
+        let now = Utc::now();
+        let fresh = (now - chrono::Duration::seconds(30)).to_rfc3339();
+        let edge = (now - chrono::Duration::seconds(TASK_TIMEOUT_SECS as i64 + 30)).to_rfc3339();
+        let old = (now - chrono::Duration::seconds(TASK_TIMEOUT_SECS as i64 + 61)).to_rfc3339();
+        assert!(!is_lock_stale(&fresh, now));
+        assert!(!is_lock_stale(&edge, now));
+        assert!(is_lock_stale(&old, now));
+        assert!(is_lock_stale("garbage", now));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let east8 = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+        let backup = find_template("backup").unwrap();
+        let vars = HashMap::from([
+            ("source".to_string(), "/home/me/My Notes".to_string()),
+            ("dest".to_string(), " /mnt/bak ".to_string()),
+            ("time".to_string(), "23:30".to_string()),
+        ]);
+        let (schedule, script) = render_template(backup, &vars, east8).unwrap();
+        assert_eq!(schedule, "30 15 * * *");
+        assert!(script.starts_with("mkdir -p '/mnt/bak' && tar -czf '/mnt/bak'/backup-"));
+        assert!(script.contains("-C '/home/me/My Notes' ."));
+        assert!(script.ends_with("-mtime +'14' -print -delete"));
+
+        let cleanup = find_template("disk-cleanup").unwrap();
+        let injected = HashMap::from([("dir".to_string(), "/tmp/x'; rm -rf / #".to_string())]);
+        let (_, script) = render_template(cleanup, &injected, east8).unwrap();
+        assert_eq!(script, "find '/tmp/x'\\''; rm -rf / #' -type f -mtime +'30' -print -delete");
+
+        for (name, value) in [("days", "30; rm -rf /"), ("days", "-1"), ("time", "25:00"), ("dir", "relative/dir")] {
+            let mut vars = HashMap::from([("dir".to_string(), "/var/tmp".to_string())]);
+            vars.insert(name.to_string(), value.to_string());
+            match render_template(cleanup, &vars, east8) {
+                Err(CronError::InvalidVariable { name: bad, .. }) => assert_eq!(bad, name),
+                other => panic!("{}={} accepted: {:?}", name, value, other),
             }
+        }
 
-            info!("[heartbeat] Executing heartbeat check");
-
-            // Build heartbeat prompt
-            let prompt = format!(
-                "[HEARTBEAT] {}\n\n{}\n\nIf nothing needs attention, respond with HEARTBEAT_OK.",
-                chrono::Local::now().format("%Y-%m-%d %H:%M"),
-                heartbeat_content.trim()
-            );
-
-            // Run through the agent
-            match crate::modules::agent::agent_process_message(
-                "heartbeat",
-                &prompt,
-                None,
-            ).await {
-                Ok(response) => {
-                    if response.trim() != "HEARTBEAT_OK" && !response.is_empty() {
-                        info!("[heartbeat] Agent response: {}", &response[..response.len().min(200)]);
-                        // Emit heartbeat result to frontend
-                        crate::modules::agent::emit_agent_progress(
-                            "heartbeat",
-                            serde_json::json!({ "response": response }),
-                        );
-                    }
-                }
-                Err(e) => {
-                    warn!("[heartbeat] Agent error: {}", e);
-                }
+        match render_template(backup, &HashMap::new(), east8) {
+            Err(CronError::MissingVariables(names)) => assert_eq!(names, vec!["source", "dest"]),
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(find_template("nope"), Err(CronError::TemplateNotFound(_))));
+        for template in CRON_TEMPLATES {
+            let script_vars: Vec<&str> = TEMPLATE_VAR_RE.captures_iter(template.script).map(|c| c.get(1).unwrap().as_str()).collect();
+            assert!(script_vars.iter().all(|v| template.variables.iter().any(|t| t.name == *v)), "{}", template.id);
+        }
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let json = r#"{ "version": 1, "exported_at": "2026-01-01T00:00:00Z", "tasks": [
+            { "name": "ping", "type": "cron", "schedule": "*/5 * * * *", "script": "ping -c1 example.com",
+              "notify_channel": null, "runs": [{ "started_at": "t", "finished_at": null, "result": "error", "output": "" }] }
+        ]}"#;
+        let export = parse_export(json).unwrap();
+        let task = &export.tasks[0];
+        assert_eq!((task.status.as_str(), task.group_name.as_str(), task.runs.len()), ("active", DEFAULT_GROUP, 1));
+
+        // Without runs the field is omitted and round-trips
+        let plain = ExportedTask { runs: Vec::new(), ..task.clone() };
+        let value = serde_json::to_value(&plain).unwrap();
+        assert!(value.get("runs").is_none());
+        assert_eq!(serde_json::from_value::<ExportedTask>(value).unwrap(), plain);
+
+        assert!(matches!(parse_export(&json.replace("\"version\": 1", "\"version\": 9")), Err(CronError::InvalidImport(_))));
+        assert!(matches!(parse_export("[]"), Err(CronError::InvalidImport(_))));
+    }
+
+    fn task(id: &str, status: &str, next_run: Option<&str>) -> CronTask {
+        CronTask {
+            id: id.to_string(),
+            name: format!("task {}", id),
+            description: String::new(),
+            task_type: "cron".to_string(),
+            schedule: Some("0 * * * *".to_string()),
+            script: None,
+            status: status.to_string(),
+            notify_channel: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            last_run: None,
+            last_result: None,
+            next_run: next_run.map(str::to_string),
+            group_name: DEFAULT_GROUP.to_string(),
+            depends_on: None,
+            run_after_success: false,
+        }
+    }
+
+    fn utc(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_upcoming_runs() {
+        let now = utc("2026-10-17T08:10:00Z");
+        let with_schedule = |id: &str, schedule: &str| {
+            let mut t = task(id, "active", None);
+            t.schedule = Some(schedule.to_string());
+            t
+        };
+        let mut manual = task("manual", "active", None);
+        manual.task_type = "manual".to_string();
+        // A stale stored next_run is ignored in favour of the expression
+        let hourly = task("hourly", "active", Some("2026-10-01T00:00:00+00:00"));
+        let tasks = vec![
+            with_schedule("late", "0 20 * * *"),
+            hourly,
+            task("paused", "paused", None),
+            manual,
+            with_schedule("weekly", "0 9 * * 1"),
+        ];
+        let ids: Vec<String> = upcoming_runs(&tasks, now, 24).into_iter().map(|r| r.task_id).collect();
+        assert_eq!(ids, vec!["hourly", "late"]);
+        let runs = upcoming_runs(&tasks, now, 2);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].next_run_at, "2026-10-17T09:00:00+00:00");
+    }
+
+    #[test]
+    fn test_missed_tasks() {
+        let mut daily = task("daily", "active", None);
+        daily.schedule = Some("30 2 * * *".to_string());
+        let mut manual = task("manual", "active", None);
+        manual.task_type = "manual".to_string();
+        let tasks = vec![task("hourly", "active", None), daily, task("paused", "paused", None), manual];
+        let ids = |from: &str, until: &str| -> Vec<String> {
+            missed_tasks(&tasks, utc(from), utc(until)).into_iter().map(|t| t.id.clone()).collect()
+        };
+
+        // Overnight sleep: each missed task once, however many triggers it missed
+        assert_eq!(ids("2026-10-17T00:10:00Z", "2026-10-17T07:50:00Z"), vec!["hourly", "daily"]);
+        assert_eq!(ids("2026-10-17T03:10:00Z", "2026-10-17T03:50:00Z"), Vec::<String>::new());
+        assert_eq!(ids("2026-10-17T03:00:00Z", "2026-10-17T03:50:00Z"), vec!["hourly"]);
+    }
+
+    #[test]
+    fn test_creates_cycle() {
+        let links: HashMap<&str, &str> = [("b", "a"), ("c", "b"), ("x", "x")].into();
+        let depends_on_of = |id: &str| links.get(id).map(|d| d.to_string());
+        assert!(!creates_cycle("c", "b", depends_on_of));
+        assert!(!creates_cycle("d", "c", depends_on_of));
+        assert!(creates_cycle("a", "c", depends_on_of));
+        assert!(creates_cycle("a", "a", depends_on_of));
+        // A pre-existing loop elsewhere stops at the depth limit
+        assert!(creates_cycle("a", "x", depends_on_of));
+    }
+
+    #[test]
+    fn test_prune_runs_keeps_newest() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE cron_runs (id INTEGER PRIMARY KEY AUTOINCREMENT, task_id TEXT NOT NULL,
+                                     started_at TEXT NOT NULL, finished_at TEXT,
+                                     result TEXT NOT NULL DEFAULT 'running', output TEXT NOT NULL DEFAULT '')",
+        )
+        .unwrap();
+        for i in 0..5 {
+            for task in ["a", "b"] {
+                conn.execute(
+                    "INSERT INTO cron_runs (task_id, started_at) VALUES (?1, ?2)",
+                    params![task, format!("2024-01-01T00:0{}:00+00:00", i)],
+                )
+                .unwrap();
             }
         }
-    });
+
+        assert_eq!(prune_runs_in(&conn, "a", 2).unwrap(), 3);
+        assert_eq!(prune_runs_in(&conn, "a", 2).unwrap(), 0);
+        let kept: Vec<String> = conn
+            .prepare("SELECT started_at FROM cron_runs WHERE task_id = 'a' ORDER BY started_at")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(kept, vec!["2024-01-01T00:03:00+00:00", "2024-01-01T00:04:00+00:00"]);
+        let others: i64 = conn
+            .query_row("SELECT COUNT(*) FROM cron_runs WHERE task_id = 'b'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(others, 5);
+    }
+
+    #[test]
+    fn test_parse_natural_schedule_passthrough_and_unknown() {
+        assert_eq!(parse_natural_schedule("0 8 * * *").as_deref(), Some("0 8 * * *"));
+        assert_eq!(parse_natural_schedule("明天下午3点"), None);
+        assert_eq!(parse_natural_schedule("每90分钟"), None);
+    }
+
+    #[test]
+    fn test_validate_heartbeat() {
+        let ok = validate_heartbeat("检查：\n\n1. 磁盘空间\n- 待办事项\n");
+        assert!(ok.valid && ok.errors.is_empty() && ok.warnings.is_empty());
+
+        let empty = validate_heartbeat("  \n");
+        assert!(!empty.valid);
+
+        let fence = validate_heartbeat("- check\n```bash\ndf -h\n");
+        assert_eq!(fence.errors, vec!["line 2: code block is never closed"]);
+        // Items inside a code block do not count
+        let closed = validate_heartbeat("```\n- not an item\n```\n");
+        assert!(closed.valid);
+        assert_eq!(closed.warnings.len(), 1);
+
+        assert!(!validate_heartbeat("- a\u{0}b").valid);
+        assert!(!validate_heartbeat(&"- x\n".repeat(HEARTBEAT_MAX_BYTES)).valid);
+    }
 }
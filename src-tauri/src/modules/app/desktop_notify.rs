@@ -0,0 +1,227 @@
+//! Desktop notifications for inbound channel messages.
+//!
+//! Close-to-tray hides the window, so the receive paths call `notify_inbound`
+//! for every message they take in: the HTTP API server's `/api/agent/chat`
+//! (channel `api`) and the channel router `channels::route_inbound_message`.
+//! A native notification is shown unless the main window is already in
+//! front, the message was sent by the bot itself, the channel is disabled,
+//! it is quiet hours, or the same session notified within the last 30 seconds.
+//!
+//! Clicking a notification shows the main window and emits
+//! `notification://open-session` with `{ channel, session_key }` so the
+//! frontend can open the conversation. Click actions are only reported on
+//! Linux (freedesktop); on macOS a click reopens the app, and the session of
+//! the last notification is opened from the reopen handler instead.
+
+use chrono::NaiveTime;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+use tracing::warn;
+
+use crate::models::config::DesktopNotificationsConfig;
+use crate::modules::config;
+use crate::utils::truncate::safe_truncate;
+
+/// At most one notification per session in this window.
+const SESSION_THROTTLE: Duration = Duration::from_secs(30);
+/// How long after a notification an app reopen still navigates to its session.
+const PENDING_OPEN_TTL: Duration = Duration::from_secs(300);
+const PREVIEW_CHARS: usize = 120;
+
+pub const OPEN_SESSION_EVENT: &str = "notification://open-session";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenSession {
+    pub channel: String,
+    pub session_key: String,
+}
+
+static APP: OnceCell<tauri::AppHandle> = OnceCell::new();
+static LAST_SHOWN: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Session of the most recent notification, opened on the next reopen where
+/// notification clicks are not reported.
+static PENDING_OPEN: Lazy<Mutex<Option<(OpenSession, Instant)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Keep the app handle for showing the window from notification clicks.
+pub fn init(app: &tauri::AppHandle) {
+    let _ = APP.set(app.clone());
+}
+
+fn parse_hhmm(value: Option<&str>) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value?.trim(), "%H:%M").ok()
+}
+
+/// Whether `now` falls in the quiet period. `start > end` wraps past midnight;
+/// an unset or unparsable bound disables quiet hours.
+fn in_quiet_hours(config: &DesktopNotificationsConfig, now: NaiveTime) -> bool {
+    let (Some(start), Some(end)) =
+        (parse_hhmm(config.quiet_hours_start.as_deref()), parse_hhmm(config.quiet_hours_end.as_deref()))
+    else {
+        return false;
+    };
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn channel_enabled(config: &DesktopNotificationsConfig, channel: &str) -> bool {
+    config.enabled && config.channels.get(channel).copied().unwrap_or(true)
+}
+
+/// Record a notification for `session` unless one was shown within `SESSION_THROTTLE`.
+fn take_throttle_slot(last_shown: &mut HashMap<String, Instant>, session: &str, now: Instant) -> bool {
+    last_shown.retain(|_, at| now.duration_since(*at) < SESSION_THROTTLE);
+    if last_shown.contains_key(session) {
+        return false;
+    }
+    last_shown.insert(session.to_string(), now);
+    true
+}
+
+fn main_window_in_front(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window("main")
+        .is_some_and(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+}
+
+/// Show the main window and ask the frontend to open the session.
+fn open_session(app: &tauri::AppHandle, target: OpenSession) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
+    }
+    let _ = app.emit(OPEN_SESSION_EVENT, target);
+}
+
+/// Called when the app is reopened (dock click, notification click on macOS):
+/// open the session of a recent notification, if any.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn on_app_reopened(app: &tauri::AppHandle) {
+    let pending = PENDING_OPEN.lock().take();
+    if let Some((target, at)) = pending {
+        if at.elapsed() < PENDING_OPEN_TTL {
+            open_session(app, target);
+        }
+    }
+}
+
+fn show_notification(app: tauri::AppHandle, title: String, body: String, target: OpenSession) {
+    *PENDING_OPEN.lock() = Some((target.clone(), Instant::now()));
+
+    // Waiting for the click is a D-Bus signal subscription on an async task,
+    // so no thread is held while the notification stays on screen
+    #[cfg(all(unix, not(target_os = "macos")))]
+    tauri::async_runtime::spawn(async move {
+        let mut notification = notify_rust::Notification::new();
+        notification.appname("Helix").summary(&title).body(&body).action("default", "Open");
+        let handle = match notification.show_async().await {
+            Ok(handle) => handle,
+            Err(e) => return warn!("[notify] Failed to show notification: {}", e),
+        };
+        let mut clicked = false;
+        let wait = handle.wait_for_action_async(|response| {
+            clicked = matches!(response, notify_rust::NotificationResponse::Default)
+                || matches!(response, notify_rust::NotificationResponse::Action(a) if a == "default");
+        });
+        if tokio::time::timeout(PENDING_OPEN_TTL, wait).await.is_ok() && clicked {
+            PENDING_OPEN.lock().take();
+            open_session(&app, target);
+        }
+    });
+
+    // Showing blocks on the platform notification center
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    tauri::async_runtime::spawn_blocking(move || {
+        let _ = (&app, &target);
+        let mut notification = notify_rust::Notification::new();
+        notification.appname("Helix").summary(&title).body(&body);
+        if let Err(e) = notification.show() {
+            warn!("[notify] Failed to show notification: {}", e);
+        }
+    });
+}
+
+/// Notify about a message received on `channel`. `from_bot` marks messages
+/// the agent sent itself (echoed back by the poller), which never notify.
+pub fn notify_inbound(channel: &str, session_key: &str, sender: &str, content: &str, from_bot: bool) {
+    if from_bot {
+        return;
+    }
+    let Some(app) = APP.get() else {
+        return;
+    };
+    let settings = config::load_app_config().map(|c| c.desktop_notifications).unwrap_or_default();
+    if !channel_enabled(&settings, channel)
+        || in_quiet_hours(&settings, chrono::Local::now().time())
+        || main_window_in_front(app)
+    {
+        return;
+    }
+    if !take_throttle_slot(&mut LAST_SHOWN.lock(), &format!("{}:{}", channel, session_key), Instant::now()) {
+        return;
+    }
+
+    let title = if sender.trim().is_empty() { session_key.to_string() } else { sender.trim().to_string() };
+    let preview = safe_truncate(content.trim(), PREVIEW_CHARS);
+    let body = if preview.len() < content.trim().len() { format!("{}…", preview) } else { preview.to_string() };
+    let target = OpenSession { channel: channel.to_string(), session_key: session_key.to_string() };
+    show_notification(app.clone(), title, body, target);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(start: Option<&str>, end: Option<&str>) -> DesktopNotificationsConfig {
+        DesktopNotificationsConfig {
+            quiet_hours_start: start.map(String::from),
+            quiet_hours_end: end.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let at = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").unwrap();
+        let overnight = settings(Some("22:00"), Some("08:00"));
+        assert!(in_quiet_hours(&overnight, at("23:30")));
+        assert!(in_quiet_hours(&overnight, at("07:59")));
+        assert!(!in_quiet_hours(&overnight, at("08:00")));
+        assert!(!in_quiet_hours(&overnight, at("12:00")));
+
+        let lunch = settings(Some("12:00"), Some("13:30"));
+        assert!(in_quiet_hours(&lunch, at("12:45")));
+        assert!(!in_quiet_hours(&lunch, at("13:30")));
+
+        assert!(!in_quiet_hours(&settings(None, Some("08:00")), at("03:00")));
+        assert!(!in_quiet_hours(&settings(Some("late"), Some("08:00")), at("03:00")));
+    }
+
+    #[test]
+    fn test_channel_enabled() {
+        let mut config = DesktopNotificationsConfig::default();
+        config.channels.insert("feishu".into(), false);
+        assert!(channel_enabled(&config, "wechat"));
+        assert!(!channel_enabled(&config, "feishu"));
+        config.enabled = false;
+        assert!(!channel_enabled(&config, "wechat"));
+    }
+
+    #[test]
+    fn test_throttle_per_session() {
+        let mut last_shown = HashMap::new();
+        let t0 = Instant::now();
+        assert!(take_throttle_slot(&mut last_shown, "wechat:a", t0));
+        assert!(!take_throttle_slot(&mut last_shown, "wechat:a", t0 + Duration::from_secs(29)));
+        assert!(take_throttle_slot(&mut last_shown, "wechat:b", t0 + Duration::from_secs(29)));
+        assert!(take_throttle_slot(&mut last_shown, "wechat:a", t0 + Duration::from_secs(30)));
+    }
+}
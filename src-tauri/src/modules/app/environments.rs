@@ -2,7 +2,19 @@
 //!
 //! Provides Tauri commands for managing user-defined environment variables
 //! that are loaded into the agent's process environment at startup.
+//! An optional schema in ~/.helix/env_schema.json lists required keys,
+//! defaults and value patterns; it is checked at startup and by `envs_validate`.
+//!
+//! Precedence: values from envs.json override variables inherited from the
+//! parent environment (the shell or launcher that started Helix). They are
+//! applied to the process once at startup, before the Tauri runtime starts.
+//! Later edits never touch the process environment (`set_var` is unsound once
+//! worker threads run); instead every child process the agent, cron, hooks,
+//! plugins and MCP servers spawn is built with `with_managed_env`, which reads
+//! envs.json at spawn time. Deleting a key removes it from those children,
+//! including a value inherited from the parent environment.
 
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{info, warn};
@@ -17,18 +29,46 @@ pub struct EnvVar {
     pub secret: bool,
 }
 
-/// Path to the envs config file
-fn get_envs_path() -> Result<std::path::PathBuf, String> {
+/// Expected environment variable, declared in env_schema.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvVarSchema {
+    pub key: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub description: String,
+    /// Value used when the variable is not set
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Regex the whole value must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+/// A schema check that failed for one key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvValidationError {
+    pub key: String,
+    pub error: String,
+}
+
+/// Path to a file in ~/.helix, creating the directory if needed
+fn get_helix_file(name: &str) -> Result<std::path::PathBuf, String> {
     let helix_dir = dirs::home_dir()
         .ok_or_else(|| "Cannot determine home directory".to_string())?
         .join(".helix");
     std::fs::create_dir_all(&helix_dir)
         .map_err(|e| format!("Failed to create dir: {}", e))?;
-    Ok(helix_dir.join("envs.json"))
+    Ok(helix_dir.join(name))
+}
+
+/// Path to the envs config file
+pub(crate) fn get_envs_path() -> Result<std::path::PathBuf, String> {
+    get_helix_file("envs.json")
 }
 
 /// Load env vars from file
-fn load_envs() -> Result<Vec<EnvVar>, String> {
+pub(crate) fn load_envs() -> Result<Vec<EnvVar>, String> {
     let path = get_envs_path()?;
     if !path.exists() {
         return Ok(Vec::new());
@@ -49,7 +89,12 @@ fn save_envs(envs: &[EnvVar]) -> Result<(), String> {
     Ok(())
 }
 
-/// Apply env vars to the current process
+/// Keys applied to the process at startup. A child must not inherit one of
+/// them once it has been deleted from envs.json.
+static STARTUP_KEYS: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Apply env vars to the current process. Must only be called from `run()`
+/// before the Tauri runtime starts its threads.
 pub fn apply_envs_to_process() {
     match load_envs() {
         Ok(envs) => {
@@ -59,6 +104,7 @@ pub fn apply_envs_to_process() {
             if !envs.is_empty() {
                 info!("Applied {} environment variables", envs.len());
             }
+            let _ = STARTUP_KEYS.set(envs.into_iter().map(|e| e.key).collect());
         }
         Err(e) => {
             warn!("Failed to load env vars: {}", e);
@@ -66,10 +112,406 @@ pub fn apply_envs_to_process() {
     }
 }
 
-/// List all environment variables
+/// Environment changes a child process needs on top of the inherited one:
+/// current envs.json values and schema defaults to set, and keys deleted
+/// since startup to remove.
+fn managed_env_changes() -> (Vec<(String, String)>, Vec<String>) {
+    let envs = load_envs().unwrap_or_default();
+    let schema = load_schema().unwrap_or_default();
+    let startup_keys = STARTUP_KEYS.get().map(Vec::as_slice).unwrap_or_default();
+    env_changes(&envs, &schema, startup_keys, |key| std::env::var(key).ok())
+}
+
+fn env_changes(
+    envs: &[EnvVar],
+    schema: &[EnvVarSchema],
+    startup_keys: &[String],
+    inherited: impl Fn(&str) -> Option<String>,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let mut set: Vec<(String, String)> = envs.iter().map(|e| (e.key.clone(), e.value.clone())).collect();
+    let removed: Vec<String> = startup_keys
+        .iter()
+        .filter(|key| !envs.iter().any(|e| &e.key == *key))
+        .cloned()
+        .collect();
+    for entry in schema {
+        let Some(default) = &entry.default else { continue };
+        let unset = removed.contains(&entry.key) || inherited(&entry.key).is_none_or(|v| v.is_empty());
+        if unset && !set.iter().any(|(key, _)| *key == entry.key) {
+            set.push((entry.key.clone(), default.clone()));
+        }
+    }
+    (set, removed)
+}
+
+/// A child process command that can take the managed environment.
+pub trait WithManagedEnv {
+    /// Apply the current envs.json (read now, not at startup) to this command.
+    fn with_managed_env(&mut self) -> &mut Self;
+}
+
+macro_rules! impl_with_managed_env {
+    ($($command:ty),*) => {$(
+        impl WithManagedEnv for $command {
+            fn with_managed_env(&mut self) -> &mut Self {
+                let (set, removed) = managed_env_changes();
+                for key in removed {
+                    self.env_remove(key);
+                }
+                self.envs(set)
+            }
+        }
+    )*};
+}
+
+impl_with_managed_env!(std::process::Command, tokio::process::Command);
+
+// ============================================================================
+// Schema validation
+// ============================================================================
+
+/// Load the env schema; a missing file means no schema
+fn load_schema() -> Result<Vec<EnvVarSchema>, String> {
+    let path = get_helix_file("env_schema.json")?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read env schema: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse env schema: {}", e))
+}
+
+/// Compile a schema pattern so it has to match the whole value.
+fn compile_pattern(pattern: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(&format!("^(?:{})$", pattern))
+        .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))
+}
+
+/// Reject empty or duplicate keys and patterns that do not compile.
+fn check_schema(schema: &[EnvVarSchema]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for entry in schema {
+        if entry.key.trim().is_empty() {
+            return Err("Schema entry with an empty key".to_string());
+        }
+        if !seen.insert(entry.key.as_str()) {
+            return Err(format!("Duplicate schema key: {}", entry.key));
+        }
+        if let Some(pattern) = &entry.pattern {
+            compile_pattern(pattern).map_err(|e| format!("{}: {}", entry.key, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Check each schema entry against the value `lookup` returns for its key,
+/// falling back to the entry's default. Empty values count as unset.
+fn validate_against(
+    schema: &[EnvVarSchema],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<EnvValidationError> {
+    let mut errors = Vec::new();
+    for entry in schema {
+        let value = lookup(&entry.key)
+            .filter(|v| !v.is_empty())
+            .or_else(|| entry.default.clone());
+        let Some(value) = value else {
+            if entry.required {
+                errors.push(EnvValidationError {
+                    key: entry.key.clone(),
+                    error: "Required variable is not set".to_string(),
+                });
+            }
+            continue;
+        };
+        let Some(pattern) = &entry.pattern else {
+            continue;
+        };
+        match compile_pattern(pattern) {
+            Ok(re) if re.is_match(&value) => {}
+            Ok(_) => errors.push(EnvValidationError {
+                key: entry.key.clone(),
+                error: format!("Value does not match pattern '{}'", pattern),
+            }),
+            Err(e) => errors.push(EnvValidationError { key: entry.key.clone(), error: e }),
+        }
+    }
+    errors
+}
+
+/// Validate envs.json plus the inherited process environment against the schema.
+pub fn validate_envs() -> Result<Vec<EnvValidationError>, String> {
+    let schema = load_schema()?;
+    if schema.is_empty() {
+        return Ok(Vec::new());
+    }
+    let envs: HashMap<String, String> = load_envs()?.into_iter().map(|e| (e.key, e.value)).collect();
+    Ok(validate_against(&schema, |key| {
+        envs.get(key).cloned().or_else(|| std::env::var(key).ok())
+    }))
+}
+
+/// Apply schema defaults for unset variables and log validation failures.
+/// Called once from `run()`, right after `apply_envs_to_process`.
+pub fn check_envs_at_startup() {
+    let schema = match load_schema() {
+        Ok(schema) => schema,
+        Err(e) => {
+            warn!("Failed to load env schema: {}", e);
+            return;
+        }
+    };
+    for entry in &schema {
+        if let Some(default) = &entry.default {
+            if std::env::var(&entry.key).map(|v| v.is_empty()).unwrap_or(true) {
+                std::env::set_var(&entry.key, default);
+            }
+        }
+    }
+    match validate_envs() {
+        Ok(errors) => {
+            for e in &errors {
+                warn!("Environment variable {}: {}", e.key, e.error);
+            }
+        }
+        Err(e) => warn!("Failed to validate env vars: {}", e),
+    }
+}
+
+/// Mask a value, keeping only the first and last 2 characters visible.
+fn mask_value(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..2].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(chars.len() - 4), tail)
+}
+
+// ============================================================================
+// .env parsing
+// ============================================================================
+
+/// Parse standard .env syntax: `KEY=value`, optional `export` prefix,
+/// `#` comments, single quotes (literal) and double quotes (with escapes).
+/// Returns parsed pairs plus the 1-based line numbers that could not be parsed.
+fn parse_dotenv(text: &str) -> (Vec<(String, String)>, Vec<usize>) {
+    let mut pairs = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (idx, raw_line) in text.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map(|l| l.trim_start()).unwrap_or(line);
+
+        let (key, raw_value) = match line.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => {
+                invalid.push(idx + 1);
+                continue;
+            }
+        };
+        let valid_key = !key.is_empty()
+            && !key.starts_with(|c: char| c.is_ascii_digit())
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_key {
+            invalid.push(idx + 1);
+            continue;
+        }
+
+        let value = if let Some(inner) = raw_value.strip_prefix('"') {
+            match inner.rfind('"') {
+                Some(end) => unescape_double_quoted(&inner[..end]),
+                None => {
+                    invalid.push(idx + 1);
+                    continue;
+                }
+            }
+        } else if let Some(inner) = raw_value.strip_prefix('\'') {
+            match inner.rfind('\'') {
+                Some(end) => inner[..end].to_string(),
+                None => {
+                    invalid.push(idx + 1);
+                    continue;
+                }
+            }
+        } else {
+            // Unquoted: an inline comment starts at whitespace followed by '#'
+            let end = raw_value.find(" #").unwrap_or(raw_value.len());
+            raw_value[..end].trim().to_string()
+        };
+
+        pairs.push((key.to_string(), value));
+    }
+
+    (pairs, invalid)
+}
+
+fn unescape_double_quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Format a value for a .env file, quoting when needed.
+fn format_dotenv_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '\\' | '='));
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// A key present both in envs.json and the imported file with a different value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvConflict {
+    pub key: String,
+    pub existing: String,
+    pub incoming: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DotenvImportReport {
+    /// Keys that were added or updated
+    pub imported: Vec<String>,
+    /// Keys whose value differed from the existing one (values masked)
+    pub conflicts: Vec<EnvConflict>,
+    /// Whether conflicting keys were overwritten
+    pub overwritten: bool,
+    /// 1-based line numbers that could not be parsed
+    pub invalid_lines: Vec<usize>,
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List all environment variables. Values are masked beyond the first and
+/// last 2 characters; use `envs_reveal` to read a full value.
 #[tauri::command]
 pub async fn envs_list() -> Result<Vec<EnvVar>, String> {
-    load_envs()
+    let envs = load_envs()?;
+    Ok(envs
+        .into_iter()
+        .map(|e| EnvVar {
+            value: mask_value(&e.value),
+            ..e
+        })
+        .collect())
+}
+
+/// Reveal the full value of a single environment variable
+#[tauri::command]
+pub async fn envs_reveal(key: String) -> Result<String, String> {
+    load_envs()?
+        .into_iter()
+        .find(|e| e.key == key)
+        .map(|e| e.value)
+        .ok_or_else(|| format!("Environment variable not found: {}", key))
+}
+
+/// Import variables from a .env file path or raw .env text.
+/// Existing keys with different values are reported as conflicts and only
+/// replaced when `overwrite` is true.
+#[tauri::command]
+pub async fn envs_import_dotenv(
+    path_or_text: String,
+    overwrite: Option<bool>,
+) -> Result<DotenvImportReport, String> {
+    let overwrite = overwrite.unwrap_or(false);
+    let candidate = crate::modules::agent_tools::expand_path(path_or_text.trim());
+    let text = if !path_or_text.contains('\n') && std::path::Path::new(&candidate).is_file() {
+        std::fs::read_to_string(&candidate)
+            .map_err(|e| format!("Failed to read {}: {}", candidate, e))?
+    } else {
+        path_or_text
+    };
+
+    let (pairs, invalid_lines) = parse_dotenv(&text);
+    let mut envs = load_envs()?;
+    let mut imported = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (key, value) in pairs {
+        if let Some(existing) = envs.iter_mut().find(|e| e.key == key) {
+            if existing.value == value {
+                continue;
+            }
+            conflicts.push(EnvConflict {
+                key: key.clone(),
+                existing: mask_value(&existing.value),
+                incoming: mask_value(&value),
+            });
+            if !overwrite {
+                continue;
+            }
+            existing.value = value.clone();
+        } else {
+            envs.push(EnvVar {
+                key: key.clone(),
+                value: value.clone(),
+                secret: false,
+            });
+        }
+        imported.push(key);
+    }
+
+    save_envs(&envs)?;
+    info!(
+        "Imported {} environment variables ({} conflicts)",
+        imported.len(),
+        conflicts.len()
+    );
+
+    Ok(DotenvImportReport {
+        imported,
+        conflicts,
+        overwritten: overwrite,
+        invalid_lines,
+    })
+}
+
+/// Export all environment variables to a .env file. Returns the number written.
+#[tauri::command]
+pub async fn envs_export(path: String) -> Result<usize, String> {
+    let envs = load_envs()?;
+    let mut content = format!(
+        "# Exported by Helix on {}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    for env in &envs {
+        content.push_str(&format!("{}={}\n", env.key, format_dotenv_value(&env.value)));
+    }
+    let path = crate::modules::agent_tools::expand_path(&path);
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    info!("Exported {} environment variables to {}", envs.len(), path);
+    Ok(envs.len())
 }
 
 /// Set an environment variable
@@ -90,21 +532,144 @@ pub async fn envs_set(key: String, value: String, secret: Option<bool>) -> Resul
     }
 
     save_envs(&envs)?;
-
-    // Also apply to current process
-    std::env::set_var(&key, &value);
     info!("Environment variable set: {}", key);
     Ok(())
 }
 
+/// Replace the env schema (~/.helix/env_schema.json)
+#[tauri::command]
+pub async fn envs_set_schema(schema: Vec<EnvVarSchema>) -> Result<(), String> {
+    check_schema(&schema)?;
+    let content = serde_json::to_string_pretty(&schema)
+        .map_err(|e| format!("Failed to serialize env schema: {}", e))?;
+    let path = get_helix_file("env_schema.json")?;
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write env schema: {}", e))?;
+    info!("Env schema saved ({} keys)", schema.len());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn envs_get_schema() -> Result<Vec<EnvVarSchema>, String> {
+    load_schema()
+}
+
+/// Check required keys and value patterns; returns one entry per failure
+#[tauri::command]
+pub async fn envs_validate() -> Result<Vec<EnvValidationError>, String> {
+    validate_envs()
+}
+
 /// Delete an environment variable
 #[tauri::command]
 pub async fn envs_delete(key: String) -> Result<(), String> {
     let mut envs = load_envs()?;
     envs.retain(|e| e.key != key);
     save_envs(&envs)?;
-
-    std::env::remove_var(&key);
     info!("Environment variable deleted: {}", key);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let text = "# comment\n\
+                    export API_KEY=abc123\n\
+                    NAME=\"hello world\"\n\
+                    LITERAL='a\\nb'\n\
+                    ESCAPED=\"line1\\nline2\"\n\
+                    PLAIN=value # trailing comment\n\
+                    \n\
+                    not a pair\n\
+                    1BAD=x\n";
+        let (pairs, invalid) = parse_dotenv(text);
+        assert_eq!(
+            pairs,
+            vec![
+                ("API_KEY".to_string(), "abc123".to_string()),
+                ("NAME".to_string(), "hello world".to_string()),
+                ("LITERAL".to_string(), "a\\nb".to_string()),
+                ("ESCAPED".to_string(), "line1\nline2".to_string()),
+                ("PLAIN".to_string(), "value".to_string()),
+            ]
+        );
+        assert_eq!(invalid, vec![8, 9]);
+    }
+
+    #[test]
+    fn test_dotenv_round_trip() {
+        for value in ["simple", "with space", "quote\"s", "multi\nline", ""] {
+            let line = format!("K={}", format_dotenv_value(value));
+            let (pairs, _) = parse_dotenv(&line);
+            assert_eq!(pairs[0].1, value);
+        }
+    }
+
+    #[test]
+    fn test_validate_against_schema() {
+        let entry = |key: &str, required: bool, default: Option<&str>, pattern: Option<&str>| EnvVarSchema {
+            key: key.to_string(),
+            required,
+            description: String::new(),
+            default: default.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+        };
+        let schema = vec![
+            entry("API_KEY", true, None, Some("sk-[a-z0-9]+")),
+            entry("REGION", true, Some("cn-hangzhou"), Some("cn-[a-z]+")),
+            entry("TOKEN", true, None, None),
+            entry("PORT", false, None, Some("[0-9]+")),
+            entry("OPTIONAL", false, None, None),
+        ];
+        let values: HashMap<&str, &str> = [("API_KEY", "sk-abc1"), ("TOKEN", ""), ("PORT", "80a")].into();
+        let errors = validate_against(&schema, |k| values.get(k).map(|v| v.to_string()));
+        let failed: Vec<&str> = errors.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(failed, vec!["TOKEN", "PORT"]);
+        assert!(errors[1].error.contains("does not match"));
+
+        // Patterns match the whole value
+        let errors = validate_against(&schema[..1], |_| Some("xsk-abc".to_string()));
+        assert_eq!(errors.len(), 1);
+
+        assert!(check_schema(&schema).is_ok());
+        assert!(check_schema(&[entry("A", false, None, Some("("))]).is_err());
+        assert!(check_schema(&[entry("A", false, None, None), entry("A", true, None, None)]).is_err());
+    }
+
+    #[test]
+    fn test_mask_value() {
+        assert_eq!(mask_value("sk-abcdef12"), "sk*******12");
+        assert_eq!(mask_value("abcd"), "****");
+        assert_eq!(mask_value(""), "");
+    }
+
+    #[test]
+    fn test_env_changes_for_children() {
+        let envs = vec![EnvVar { key: "API_KEY".into(), value: "new".into(), secret: true }];
+        let schema = vec![
+            EnvVarSchema {
+                key: "REGION".into(),
+                required: false,
+                description: String::new(),
+                default: Some("us".into()),
+                pattern: None,
+            },
+            EnvVarSchema {
+                key: "HOME_DIR".into(),
+                required: false,
+                description: String::new(),
+                default: Some("/tmp".into()),
+                pattern: None,
+            },
+        ];
+        let startup = vec!["API_KEY".to_string(), "OLD_TOKEN".to_string()];
+        let (set, removed) = env_changes(&envs, &schema, &startup, |key| {
+            (key == "HOME_DIR").then(|| "/home/me".to_string())
+        });
+        assert_eq!(set, vec![("API_KEY".to_string(), "new".to_string()), ("REGION".to_string(), "us".to_string())]);
+        assert_eq!(removed, vec!["OLD_TOKEN".to_string()]);
+    }
+}
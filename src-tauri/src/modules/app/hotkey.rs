@@ -0,0 +1,242 @@
+//! Global hotkeys — toggle the main window or the quick-ask window from anywhere.
+//!
+//! The shortcuts live in `AppConfig.global_hotkey` and `AppConfig.quick_ask_hotkey`
+//! (None/empty = disabled) and are re-applied whenever the config is saved.
+//! Registration failures (usually another app owning the combination) are kept
+//! for `get_global_hotkey_status`. Everything is unregistered on exit.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{Listener, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::modules;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyTarget {
+    MainWindow,
+    QuickAsk,
+}
+
+#[derive(Default)]
+struct HotkeySlot {
+    /// Currently registered shortcut and the text it was parsed from
+    current: Option<(Shortcut, String)>,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct HotkeyState {
+    main: HotkeySlot,
+    quick_ask: HotkeySlot,
+}
+
+impl HotkeyState {
+    /// The slot for `target` and the other one.
+    fn slots(&mut self, target: HotkeyTarget) -> (&mut HotkeySlot, &HotkeySlot) {
+        match target {
+            HotkeyTarget::MainWindow => (&mut self.main, &self.quick_ask),
+            HotkeyTarget::QuickAsk => (&mut self.quick_ask, &self.main),
+        }
+    }
+
+    fn target_of(&self, shortcut: &Shortcut) -> Option<HotkeyTarget> {
+        let matches = |slot: &HotkeySlot| slot.current.as_ref().is_some_and(|(s, _)| s == shortcut);
+        if matches(&self.main) {
+            Some(HotkeyTarget::MainWindow)
+        } else if matches(&self.quick_ask) {
+            Some(HotkeyTarget::QuickAsk)
+        } else {
+            None
+        }
+    }
+}
+
+static STATE: Lazy<Mutex<HotkeyState>> = Lazy::new(|| Mutex::new(HotkeyState::default()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyStatus {
+    /// Configured hotkey (None = disabled)
+    pub hotkey: Option<String>,
+    pub registered: bool,
+    /// Why the last registration failed, if it did
+    pub error: Option<String>,
+    pub quick_ask_hotkey: Option<String>,
+    pub quick_ask_registered: bool,
+    pub quick_ask_error: Option<String>,
+}
+
+/// Plugin handler: act on key press for our shortcuts only.
+pub fn handle_shortcut(app: &tauri::AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let target = STATE.lock().target_of(shortcut);
+    match target {
+        Some(HotkeyTarget::MainWindow) => toggle_main_window(app),
+        Some(HotkeyTarget::QuickAsk) => modules::quick_ask::toggle(app),
+        None => {}
+    }
+}
+
+/// Show + focus the main window, or hide it if it's already in front.
+fn toggle_main_window(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false);
+    let focused = window.is_focused().unwrap_or(false);
+    if visible && focused {
+        let _ = window.hide();
+    } else {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
+    }
+}
+
+fn normalize(hotkey: Option<&str>) -> Option<String> {
+    hotkey.map(str::trim).filter(|h| !h.is_empty()).map(String::from)
+}
+
+fn describe(target: HotkeyTarget) -> &'static str {
+    match target {
+        HotkeyTarget::MainWindow => "显示/隐藏主窗口",
+        HotkeyTarget::QuickAsk => "快速提问",
+    }
+}
+
+/// Replace the registered shortcut of `target`. On failure the previous one
+/// is restored and the error is returned (and remembered for the status command).
+pub fn apply_hotkey(app: &tauri::AppHandle, target: HotkeyTarget, hotkey: Option<&str>) -> Result<(), String> {
+    let wanted = normalize(hotkey);
+    let mut state = STATE.lock();
+    let (slot, other) = state.slots(target);
+    if slot.current.as_ref().map(|(_, text)| text) == wanted.as_ref() && slot.last_error.is_none() {
+        return Ok(());
+    }
+
+    let parsed = match wanted.as_deref().map(|h| h.parse::<Shortcut>()) {
+        Some(Err(e)) => {
+            let msg = format!("无效的快捷键 '{}': {}", wanted.unwrap_or_default(), e);
+            slot.last_error = Some(msg.clone());
+            return Err(msg);
+        }
+        Some(Ok(s)) => Some(s),
+        None => None,
+    };
+    if let Some(shortcut) = parsed {
+        if other.current.as_ref().is_some_and(|(s, _)| *s == shortcut) {
+            let other_target =
+                if target == HotkeyTarget::MainWindow { HotkeyTarget::QuickAsk } else { HotkeyTarget::MainWindow };
+            let msg = format!("快捷键 {} 已用于{}", wanted.unwrap_or_default(), describe(other_target));
+            slot.last_error = Some(msg.clone());
+            return Err(msg);
+        }
+    }
+
+    let shortcuts = app.global_shortcut();
+    let previous = slot.current.take();
+    if let Some((old, _)) = &previous {
+        let _ = shortcuts.unregister(*old);
+    }
+
+    let Some(shortcut) = parsed else {
+        slot.last_error = None;
+        modules::logger::log_info(&format!("Global hotkey disabled: {}", describe(target)));
+        return Ok(());
+    };
+    let text = wanted.unwrap_or_default();
+    match shortcuts.register(shortcut) {
+        Ok(()) => {
+            modules::logger::log_info(&format!("Global hotkey registered: {} ({})", text, describe(target)));
+            slot.current = Some((shortcut, text));
+            slot.last_error = None;
+            Ok(())
+        }
+        Err(e) => {
+            let msg = format!("快捷键 {} 注册失败（可能已被其他应用占用）: {}", text, e);
+            modules::logger::log_warn(&msg);
+            if let Some((old, old_text)) = previous {
+                if shortcuts.register(old).is_ok() {
+                    slot.current = Some((old, old_text));
+                }
+            }
+            slot.last_error = Some(msg.clone());
+            Err(msg)
+        }
+    }
+}
+
+fn apply_config(app: &tauri::AppHandle) {
+    let config = modules::load_app_config().unwrap_or_default();
+    let _ = apply_hotkey(app, HotkeyTarget::MainWindow, config.global_hotkey.as_deref());
+    let _ = apply_hotkey(app, HotkeyTarget::QuickAsk, config.quick_ask_hotkey.as_deref());
+}
+
+/// Register the configured hotkeys at startup and follow config changes.
+pub fn init(app: &tauri::AppHandle) {
+    apply_config(app);
+
+    let handle = app.clone();
+    app.listen("config://updated", move |_event| apply_config(&handle));
+}
+
+/// Release every shortcut so other apps can use them after we exit.
+pub fn unregister_all(app: &tauri::AppHandle) {
+    let mut state = STATE.lock();
+    state.main.current = None;
+    state.quick_ask.current = None;
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        modules::logger::log_warn(&format!("Failed to unregister global hotkeys: {}", e));
+    }
+}
+
+pub fn status() -> HotkeyStatus {
+    let config = modules::load_app_config().unwrap_or_default();
+    let state = STATE.lock();
+    HotkeyStatus {
+        hotkey: normalize(config.global_hotkey.as_deref()),
+        registered: state.main.current.is_some(),
+        error: state.main.last_error.clone(),
+        quick_ask_hotkey: normalize(config.quick_ask_hotkey.as_deref()),
+        quick_ask_registered: state.quick_ask.current.is_some(),
+        quick_ask_error: state.quick_ask.last_error.clone(),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Set (or disable with None/"") the global hotkey. Only persisted if it registers.
+#[tauri::command]
+pub async fn set_global_hotkey(app: tauri::AppHandle, hotkey: Option<String>) -> Result<HotkeyStatus, String> {
+    let hotkey = normalize(hotkey.as_deref());
+    apply_hotkey(&app, HotkeyTarget::MainWindow, hotkey.as_deref())?;
+
+    let mut config = modules::load_app_config()?;
+    config.global_hotkey = hotkey;
+    modules::config::save_app_config(&config)?;
+    Ok(status())
+}
+
+/// Set (or disable with None/"") the quick-ask hotkey. Only persisted if it registers.
+#[tauri::command]
+pub async fn set_quick_ask_hotkey(app: tauri::AppHandle, hotkey: Option<String>) -> Result<HotkeyStatus, String> {
+    let hotkey = normalize(hotkey.as_deref());
+    apply_hotkey(&app, HotkeyTarget::QuickAsk, hotkey.as_deref())?;
+
+    let mut config = modules::load_app_config()?;
+    config.quick_ask_hotkey = hotkey;
+    modules::config::save_app_config(&config)?;
+    Ok(status())
+}
+
+#[tauri::command]
+pub async fn get_global_hotkey_status() -> Result<HotkeyStatus, String> {
+    Ok(status())
+}
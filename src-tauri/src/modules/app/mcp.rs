@@ -4,8 +4,11 @@
 //! Supports stdio and SSE transport types.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::{info, warn};
 
+use super::mcp_client::{self, McpPrompt, McpPromptMessage, McpServerStatus};
+
 /// MCP client configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPClient {
@@ -67,11 +70,31 @@ fn save_mcp_config(config: &MCPConfig) -> Result<(), String> {
     Ok(())
 }
 
-/// List all MCP clients
+/// Load all configured MCP clients
+pub(crate) fn load_clients() -> Result<Vec<MCPClient>, String> {
+    Ok(load_mcp_config()?.clients)
+}
+
+/// MCP client config plus its live connection status
+#[derive(Debug, Clone, Serialize)]
+pub struct MCPClientInfo {
+    #[serde(flatten)]
+    pub client: MCPClient,
+    pub status: McpServerStatus,
+}
+
+/// List all MCP clients with their connection status
 #[tauri::command]
-pub async fn mcp_list() -> Result<Vec<MCPClient>, String> {
+pub async fn mcp_list() -> Result<Vec<MCPClientInfo>, String> {
     let config = load_mcp_config()?;
-    Ok(config.clients)
+    Ok(config
+        .clients
+        .into_iter()
+        .map(|client| MCPClientInfo {
+            status: mcp_client::get_status(&client.name),
+            client,
+        })
+        .collect())
 }
 
 /// Create a new MCP client
@@ -120,6 +143,9 @@ pub async fn mcp_toggle(name: String) -> Result<MCPClient, String> {
     info!("MCP client '{}' {}", name, if result.enabled { "enabled" } else { "disabled" });
 
     save_mcp_config(&config)?;
+    if !result.enabled {
+        mcp_client::disconnect(&name).await;
+    }
     Ok(result)
 }
 
@@ -135,6 +161,7 @@ pub async fn mcp_delete(name: String) -> Result<(), String> {
     }
 
     save_mcp_config(&config)?;
+    mcp_client::disconnect(&name).await;
     info!("Deleted MCP client: {}", name);
     Ok(())
 }
@@ -150,10 +177,28 @@ pub async fn mcp_update(name: String, client: MCPClient) -> Result<MCPClient, St
 
     *existing = client.clone();
     save_mcp_config(&config)?;
+    // Reconnect with the new settings on next use
+    mcp_client::disconnect(&name).await;
     info!("Updated MCP client: {}", name);
     Ok(client)
 }
 
+/// List prompt templates offered by an MCP server
+#[tauri::command]
+pub async fn mcp_prompts_list(name: String) -> Result<Vec<McpPrompt>, String> {
+    mcp_client::list_prompts(&name).await
+}
+
+/// Render an MCP prompt template with arguments, for insertion into a session
+#[tauri::command]
+pub async fn mcp_prompt_get(
+    name: String,
+    prompt: String,
+    arguments: Option<HashMap<String, String>>,
+) -> Result<Vec<McpPromptMessage>, String> {
+    mcp_client::get_prompt(&name, &prompt, arguments.unwrap_or_default()).await
+}
+
 /// Get list of tools from enabled MCP clients (for agent tool injection)
 pub fn get_enabled_mcp_tool_descriptions() -> String {
     let config = match load_mcp_config() {
@@ -178,8 +223,13 @@ pub fn get_enabled_mcp_tool_descriptions() -> String {
         if let Some(ref cmd) = client.command {
             desc.push_str(&format!(" — `{}`", cmd));
         }
+        let status = mcp_client::get_status(&client.name);
+        if status.state != "connected" {
+            desc.push_str(&format!(" [{}]", status.state));
+        }
         desc.push('\n');
     }
+    desc.push_str("\nUse `mcp_resource_fetch` to list or read resources (files, DB snapshots) these servers expose.\n");
 
     desc
 }
@@ -0,0 +1,608 @@
+//! MCP protocol client — JSON-RPC sessions with the servers configured in
+//! `mcp.json`.
+//!
+//! Sessions are opened lazily on first use and kept in `MCP_SESSIONS`.
+//! stdio servers speak newline-delimited JSON-RPC over the child's
+//! stdin/stdout; "sse" servers are reached over streamable HTTP (JSON-RPC
+//! POSTs answered with JSON or a short event stream). A background monitor
+//! pings every enabled server and emits `mcp://status-changed` whenever a
+//! server's state changes.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tracing::{info, warn};
+
+use super::mcp::MCPClient;
+use crate::modules::environments::WithManagedEnv;
+use crate::utils::truncate::safe_truncate;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+const HEALTH_CHECK_INTERVAL_SECS: u64 = 60;
+/// Max characters of server stderr kept for status reporting.
+const STDERR_EXCERPT_CHARS: usize = 2000;
+/// Max pages followed for paginated list requests.
+const MAX_LIST_PAGES: usize = 10;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerStatus {
+    /// "connected" | "disconnected" | "error"
+    pub state: String,
+    pub last_error: Option<String>,
+    /// Tail of the server's stderr (stdio transport), captured on failure
+    pub stderr_excerpt: Option<String>,
+    pub last_checked: Option<String>,
+}
+
+impl McpServerStatus {
+    fn new(state: &str) -> Self {
+        Self {
+            state: state.to_string(),
+            last_error: None,
+            stderr_excerpt: None,
+            last_checked: Some(chrono::Utc::now().to_rfc3339()),
+        }
+    }
+
+    fn error(error: &str, stderr_excerpt: Option<String>) -> Self {
+        Self {
+            last_error: Some(error.to_string()),
+            stderr_excerpt,
+            ..Self::new("error")
+        }
+    }
+}
+
+impl Default for McpServerStatus {
+    fn default() -> Self {
+        Self {
+            state: "disconnected".to_string(),
+            last_error: None,
+            stderr_excerpt: None,
+            last_checked: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResource {
+    pub uri: String,
+    #[serde(default)]
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "mimeType")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResourceContent {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: Option<String>,
+    pub text: Option<String>,
+    /// Base64-encoded binary content
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPrompt {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpPromptMessage {
+    pub role: String,
+    pub text: String,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+static MCP_SESSIONS: Lazy<tokio::sync::Mutex<HashMap<String, Arc<McpSession>>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+static MCP_STATUS: Lazy<RwLock<HashMap<String, McpServerStatus>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Current status of a server (disconnected if never contacted).
+pub fn get_status(name: &str) -> McpServerStatus {
+    MCP_STATUS.read().get(name).cloned().unwrap_or_default()
+}
+
+/// Record a server's status, emitting `mcp://status-changed` if the state
+/// or error changed.
+fn set_status(name: &str, status: McpServerStatus) {
+    let changed = {
+        let mut map = MCP_STATUS.write();
+        let changed = map
+            .get(name)
+            .map(|prev| prev.state != status.state || prev.last_error != status.last_error)
+            .unwrap_or(true);
+        map.insert(name.to_string(), status.clone());
+        changed
+    };
+    if changed {
+        info!("MCP server '{}' is now {}", name, status.state);
+        crate::modules::infra::log_bridge::emit_custom_event(
+            "mcp://status-changed",
+            json!({ "name": name, "status": status }),
+        );
+    }
+}
+
+// ============================================================================
+// Session
+// ============================================================================
+
+enum RequestError {
+    /// The connection is unusable (I/O failure, timeout, HTTP error)
+    Transport(String),
+    /// The server answered with a JSON-RPC error
+    Rpc(String),
+}
+
+impl From<RequestError> for String {
+    fn from(e: RequestError) -> String {
+        match e {
+            RequestError::Transport(msg) | RequestError::Rpc(msg) => msg,
+        }
+    }
+}
+
+enum Transport {
+    Stdio {
+        // Held so the process lives (and is killed) with the session
+        _child: Box<Child>,
+        stdin: ChildStdin,
+        stdout: BufReader<ChildStdout>,
+    },
+    Http {
+        client: reqwest::Client,
+        url: String,
+        session_id: Option<String>,
+    },
+}
+
+struct McpSession {
+    transport: tokio::sync::Mutex<Transport>,
+    stderr: Arc<parking_lot::Mutex<String>>,
+    next_id: AtomicU64,
+}
+
+impl McpSession {
+    /// Start the server process (stdio) or prepare the HTTP client (sse).
+    fn open(client: &MCPClient) -> Result<Self, String> {
+        let stderr = Arc::new(parking_lot::Mutex::new(String::new()));
+        let transport = match client.transport.as_str() {
+            "stdio" => {
+                let command = client
+                    .command
+                    .as_deref()
+                    .filter(|c| !c.is_empty())
+                    .ok_or("stdio transport requires a command")?;
+                let mut child = tokio::process::Command::new(command)
+                    .args(client.args.clone().unwrap_or_default())
+                    .with_managed_env()
+                    .envs(&client.env)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true)
+                    .spawn()
+                    .map_err(|e| format!("Failed to start '{}': {}", command, e))?;
+
+                let stdin = child.stdin.take().ok_or("Failed to open MCP server stdin")?;
+                let stdout = child.stdout.take().ok_or("Failed to open MCP server stdout")?;
+                if let Some(err) = child.stderr.take() {
+                    let buf = stderr.clone();
+                    tokio::spawn(async move {
+                        let mut lines = BufReader::new(err).lines();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            let mut buf = buf.lock();
+                            buf.push_str(&line);
+                            buf.push('\n');
+                            let excess = buf.chars().count().saturating_sub(STDERR_EXCERPT_CHARS);
+                            if excess > 0 {
+                                let cut = buf.char_indices().nth(excess).map(|(i, _)| i).unwrap_or(0);
+                                buf.drain(..cut);
+                            }
+                        }
+                    });
+                }
+
+                Transport::Stdio {
+                    _child: Box::new(child),
+                    stdin,
+                    stdout: BufReader::new(stdout),
+                }
+            }
+            "sse" => Transport::Http {
+                client: reqwest::Client::new(),
+                url: client
+                    .url
+                    .clone()
+                    .filter(|u| !u.is_empty())
+                    .ok_or("sse transport requires a URL")?,
+                session_id: None,
+            },
+            other => return Err(format!("Unknown transport type: {}", other)),
+        };
+
+        Ok(Self {
+            transport: tokio::sync::Mutex::new(transport),
+            stderr,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    async fn initialize(&self) -> Result<(), RequestError> {
+        self.request(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "helix", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            REQUEST_TIMEOUT,
+        )
+        .await?;
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        self.transport.lock().await.send(&notification, None).await?;
+        Ok(())
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, RequestError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let mut transport = self.transport.lock().await;
+        let response = tokio::time::timeout(timeout, transport.send(&message, Some(id)))
+            .await
+            .map_err(|_| RequestError::Transport(format!("{} timed out after {}s", method, timeout.as_secs())))??;
+
+        if let Some(err) = response.get("error") {
+            let msg = err["message"].as_str().map(|s| s.to_string()).unwrap_or_else(|| err.to_string());
+            return Err(RequestError::Rpc(format!("{} failed: {}", method, msg)));
+        }
+        Ok(response["result"].clone())
+    }
+
+    fn stderr_excerpt(&self) -> Option<String> {
+        let buf = self.stderr.lock();
+        let trimmed = buf.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
+    }
+}
+
+impl Transport {
+    /// Send a message; for requests (`id` set), wait for the matching response.
+    async fn send(&mut self, message: &Value, id: Option<u64>) -> Result<Value, RequestError> {
+        let io_err = |e: std::io::Error| RequestError::Transport(format!("MCP server I/O error: {}", e));
+
+        match self {
+            Transport::Stdio { stdin, stdout, .. } => {
+                let mut line = message.to_string();
+                line.push('\n');
+                stdin.write_all(line.as_bytes()).await.map_err(io_err)?;
+                stdin.flush().await.map_err(io_err)?;
+
+                let Some(id) = id else { return Ok(Value::Null) };
+                let mut buf = String::new();
+                loop {
+                    buf.clear();
+                    if stdout.read_line(&mut buf).await.map_err(io_err)? == 0 {
+                        return Err(RequestError::Transport("MCP server closed its stdout".into()));
+                    }
+                    // Servers sometimes log to stdout; ignore anything that isn't JSON-RPC
+                    let Ok(incoming) = serde_json::from_str::<Value>(buf.trim()) else { continue };
+
+                    if let Some(method) = incoming.get("method").and_then(|m| m.as_str()) {
+                        // Server-initiated request: answer pings, reject everything else
+                        if let Some(req_id) = incoming.get("id") {
+                            let reply = if method == "ping" {
+                                json!({ "jsonrpc": "2.0", "id": req_id, "result": {} })
+                            } else {
+                                json!({ "jsonrpc": "2.0", "id": req_id, "error": { "code": -32601, "message": "Method not found" } })
+                            };
+                            let mut line = reply.to_string();
+                            line.push('\n');
+                            stdin.write_all(line.as_bytes()).await.map_err(io_err)?;
+                        }
+                        continue;
+                    }
+                    if incoming["id"].as_u64() == Some(id) {
+                        return Ok(incoming);
+                    }
+                }
+            }
+            Transport::Http { client, url, session_id } => {
+                let mut req = client
+                    .post(url.as_str())
+                    .header("Accept", "application/json, text/event-stream")
+                    .json(message);
+                if let Some(sid) = session_id.as_deref() {
+                    req = req.header("Mcp-Session-Id", sid);
+                }
+                let resp = req
+                    .send()
+                    .await
+                    .map_err(|e| RequestError::Transport(format!("MCP request failed: {}", e)))?;
+
+                if let Some(sid) = resp.headers().get("mcp-session-id").and_then(|v| v.to_str().ok()) {
+                    *session_id = Some(sid.to_string());
+                }
+                let status = resp.status();
+                let is_stream = resp
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|ct| ct.contains("text/event-stream"))
+                    .unwrap_or(false);
+                let body = resp
+                    .text()
+                    .await
+                    .map_err(|e| RequestError::Transport(format!("Failed to read MCP response: {}", e)))?;
+
+                if !status.is_success() {
+                    return Err(RequestError::Transport(format!(
+                        "MCP server returned HTTP {}: {}",
+                        status,
+                        safe_truncate(&body, 200)
+                    )));
+                }
+                let Some(id) = id else { return Ok(Value::Null) };
+
+                if is_stream {
+                    parse_sse_response(&body, id).ok_or_else(|| {
+                        RequestError::Transport("No response found in MCP event stream".into())
+                    })
+                } else {
+                    serde_json::from_str(&body)
+                        .map_err(|e| RequestError::Transport(format!("Invalid MCP response: {}", e)))
+                }
+            }
+        }
+    }
+}
+
+/// Find the JSON-RPC response with the given id in a `text/event-stream` body.
+fn parse_sse_response(body: &str, id: u64) -> Option<Value> {
+    body.replace("\r\n", "\n").split("\n\n").find_map(|event| {
+        let data: Vec<&str> = event
+            .lines()
+            .filter_map(|l| l.strip_prefix("data:"))
+            .map(|l| l.strip_prefix(' ').unwrap_or(l))
+            .collect();
+        if data.is_empty() {
+            return None;
+        }
+        let value: Value = serde_json::from_str(&data.join("\n")).ok()?;
+        (value["id"].as_u64() == Some(id)).then_some(value)
+    })
+}
+
+// ============================================================================
+// Session Management
+// ============================================================================
+
+/// Get the live session for a server, connecting if needed.
+async fn get_session(name: &str) -> Result<Arc<McpSession>, String> {
+    if let Some(session) = MCP_SESSIONS.lock().await.get(name) {
+        return Ok(session.clone());
+    }
+
+    let client = super::mcp::load_clients()?
+        .into_iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| format!("MCP client '{}' not found", name))?;
+    if !client.enabled {
+        return Err(format!("MCP client '{}' is disabled", name));
+    }
+
+    let session = McpSession::open(&client)
+        .inspect_err(|e| set_status(name, McpServerStatus::error(e, None)))?;
+    if let Err(e) = session.initialize().await {
+        let e = String::from(e);
+        // Give the stderr reader a moment to catch the server's last words
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let excerpt = session.stderr_excerpt();
+        warn!("MCP server '{}' failed to initialize: {}", name, e);
+        set_status(name, McpServerStatus::error(&e, excerpt.clone()));
+        return Err(match excerpt {
+            Some(stderr) => format!("{}\n[stderr]\n{}", e, stderr),
+            None => e,
+        });
+    }
+
+    let session = Arc::new(session);
+    MCP_SESSIONS.lock().await.insert(name.to_string(), session.clone());
+    set_status(name, McpServerStatus::new("connected"));
+    Ok(session)
+}
+
+/// Call a method on a server. Transport failures drop the session and mark
+/// the server as errored so the next call reconnects.
+async fn call(name: &str, method: &str, params: Value) -> Result<Value, String> {
+    let session = get_session(name).await?;
+    match session.request(method, params, REQUEST_TIMEOUT).await {
+        Ok(result) => Ok(result),
+        Err(RequestError::Rpc(e)) => Err(e),
+        Err(RequestError::Transport(e)) => {
+            MCP_SESSIONS.lock().await.remove(name);
+            set_status(name, McpServerStatus::error(&e, session.stderr_excerpt()));
+            Err(e)
+        }
+    }
+}
+
+/// Call a paginated `*/list` method and collect `key` items from all pages.
+async fn list_all(name: &str, method: &str, key: &str) -> Result<Vec<Value>, String> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_LIST_PAGES {
+        let params = match &cursor {
+            Some(c) => json!({ "cursor": c }),
+            None => json!({}),
+        };
+        let result = call(name, method, params).await?;
+        if let Some(page) = result[key].as_array() {
+            items.extend(page.iter().cloned());
+        }
+        cursor = result["nextCursor"].as_str().map(|s| s.to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// Close a server's session (e.g. after it is disabled, edited or deleted).
+pub async fn disconnect(name: &str) {
+    MCP_SESSIONS.lock().await.remove(name);
+    set_status(name, McpServerStatus::new("disconnected"));
+}
+
+// ============================================================================
+// Resources & Prompts
+// ============================================================================
+
+pub async fn list_resources(name: &str) -> Result<Vec<McpResource>, String> {
+    list_all(name, "resources/list", "resources")
+        .await?
+        .into_iter()
+        .map(|v| serde_json::from_value(v).map_err(|e| format!("Invalid resource from '{}': {}", name, e)))
+        .collect()
+}
+
+pub async fn read_resource(name: &str, uri: &str) -> Result<Vec<McpResourceContent>, String> {
+    let result = call(name, "resources/read", json!({ "uri": uri })).await?;
+    serde_json::from_value(result["contents"].clone())
+        .map_err(|e| format!("Invalid resource contents from '{}': {}", name, e))
+}
+
+pub async fn list_prompts(name: &str) -> Result<Vec<McpPrompt>, String> {
+    list_all(name, "prompts/list", "prompts")
+        .await?
+        .into_iter()
+        .map(|v| serde_json::from_value(v).map_err(|e| format!("Invalid prompt from '{}': {}", name, e)))
+        .collect()
+}
+
+/// Render a server prompt template into plain-text messages.
+pub async fn get_prompt(
+    name: &str,
+    prompt: &str,
+    arguments: HashMap<String, String>,
+) -> Result<Vec<McpPromptMessage>, String> {
+    let result = call(name, "prompts/get", json!({ "name": prompt, "arguments": arguments })).await?;
+    let messages = result["messages"].as_array().cloned().unwrap_or_default();
+    Ok(messages
+        .iter()
+        .map(|m| {
+            let content = &m["content"];
+            let text = match content["type"].as_str() {
+                Some("text") => content["text"].as_str().unwrap_or_default().to_string(),
+                Some("resource") => content["resource"]["text"].as_str().unwrap_or_default().to_string(),
+                _ => String::new(),
+            };
+            McpPromptMessage {
+                role: m["role"].as_str().unwrap_or("user").to_string(),
+                text,
+            }
+        })
+        .collect())
+}
+
+// ============================================================================
+// Health Monitor
+// ============================================================================
+
+async fn check_health(name: &str) {
+    // Connection failures are recorded by get_session
+    let Ok(session) = get_session(name).await else { return };
+    match session.request("ping", json!({}), PING_TIMEOUT).await {
+        // A JSON-RPC error still means the server is alive
+        Ok(_) | Err(RequestError::Rpc(_)) => set_status(name, McpServerStatus::new("connected")),
+        Err(RequestError::Transport(e)) => {
+            MCP_SESSIONS.lock().await.remove(name);
+            set_status(name, McpServerStatus::error(&e, session.stderr_excerpt()));
+        }
+    }
+}
+
+/// Start the background health monitor. Call once at app setup.
+pub fn start_mcp_health_monitor() {
+    crate::modules::infra::shutdown::spawn("mcp-health-monitor", |mut shutdown| async move {
+        info!("MCP health monitor started");
+        let mut interval = tokio::time::interval(Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+
+            let clients = match super::mcp::load_clients() {
+                Ok(c) => c,
+                Err(e) => {
+                    warn!("MCP health monitor: {}", e);
+                    continue;
+                }
+            };
+
+            // Forget servers that were removed from the config
+            let names: HashSet<String> = clients.iter().map(|c| c.name.clone()).collect();
+            MCP_SESSIONS.lock().await.retain(|name, _| names.contains(name));
+            MCP_STATUS.write().retain(|name, _| names.contains(name));
+
+            for client in clients {
+                if client.enabled {
+                    check_health(&client.name).await;
+                } else {
+                    MCP_SESSIONS.lock().await.remove(&client.name);
+                    set_status(&client.name, McpServerStatus::new("disconnected"));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_response() {
+        let body = "event: message\r\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\r\n\r\n\
+                    event: message\r\ndata: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"ok\":true}}\r\n\r\n";
+        let resp = parse_sse_response(body, 7).unwrap();
+        assert_eq!(resp["result"]["ok"], true);
+        assert!(parse_sse_response(body, 8).is_none());
+    }
+}
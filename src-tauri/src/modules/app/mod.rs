@@ -1,4 +1,8 @@
 pub mod tray;
+pub mod hotkey;
+pub mod quick_ask;
+pub mod desktop_notify;
+pub mod quick_actions;
 pub mod scheduler;
 pub mod cron;
 pub mod update_checker;
@@ -6,3 +10,5 @@ pub mod cloudflared;
 pub mod workspace;
 pub mod environments;
 pub mod mcp;
+pub mod mcp_client;
+pub mod search;
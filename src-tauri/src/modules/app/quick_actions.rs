@@ -0,0 +1,182 @@
+//! Quick Actions — the runnable items behind the launcher-style command
+//! palette (opened with the global hotkey).
+//!
+//! Aggregates chat commands (built-in and custom), cron tasks and skills into one flat list. Each
+//! item carries a stable id (`<category>:<key>`) and the Tauri command plus
+//! arguments the frontend should `invoke` to run it.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::modules::{commands, cron, skills};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickAction {
+    /// Stable id: "command:<key>", "cron:<task id>", "skill:<name>"
+    pub id: String,
+    /// "command" | "cron" | "skill"
+    pub category: String,
+    pub title: String,
+    pub subtitle: String,
+    pub icon: String,
+    /// Extra search terms (aliases, tags, schedule)
+    pub keywords: Vec<String>,
+    /// Tauri command that runs the item
+    pub invoke: String,
+    /// Arguments for `invoke`. `commands_execute` also needs the caller's `accountId`.
+    pub args: Value,
+    /// Fuzzy match score for the query (0 when listing without a query)
+    pub score: i64,
+}
+
+fn command_actions() -> Vec<QuickAction> {
+    commands::list_all_commands()
+        .into_iter()
+        .filter(|c| c.enabled)
+        .map(|c| {
+            let mut keywords = vec![format!("/{}", c.key), c.category.clone()];
+            keywords.extend(c.aliases.iter().map(|a| format!("/{}", a)));
+            QuickAction {
+                id: format!("command:{}", c.key),
+                category: "command".to_string(),
+                title: c.name,
+                subtitle: c.description,
+                icon: "⌘".to_string(),
+                keywords,
+                invoke: "commands_execute".to_string(),
+                args: json!({ "command": c.key }),
+                score: 0,
+            }
+        })
+        .collect()
+}
+
+fn cron_actions() -> Vec<QuickAction> {
+    cron::list_tasks()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| {
+            let subtitle = if t.description.is_empty() {
+                t.schedule.clone().unwrap_or_else(|| "手动运行".to_string())
+            } else {
+                t.description.clone()
+            };
+            QuickAction {
+                id: format!("cron:{}", t.id),
+                category: "cron".to_string(),
+                title: format!("运行任务: {}", t.name),
+                subtitle,
+                icon: "⏰".to_string(),
+                keywords: [Some(t.status), t.schedule].into_iter().flatten().collect(),
+                invoke: "cron_run_task".to_string(),
+                args: json!({ "id": t.id }),
+                score: 0,
+            }
+        })
+        .collect()
+}
+
+fn skill_actions() -> Vec<QuickAction> {
+    skills::list_all_skills()
+        .into_iter()
+        .map(|s| {
+            let verb = if s.enabled { "停用技能" } else { "启用技能" };
+            QuickAction {
+                id: format!("skill:{}", s.name),
+                category: "skill".to_string(),
+                title: format!("{}: {}", verb, s.name),
+                subtitle: s.description,
+                icon: if s.icon.is_empty() { "📦".to_string() } else { s.icon },
+                keywords: s.tags,
+                invoke: "skills_toggle".to_string(),
+                args: json!({ "name": s.name, "enabled": !s.enabled }),
+                score: 0,
+            }
+        })
+        .collect()
+}
+
+/// Subsequence fuzzy match (case-insensitive). Consecutive and word-start
+/// matches score higher; None if not every query char is found in order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut qi = 0;
+    let mut prev_match: Option<usize> = None;
+    for (ti, c) in text.iter().enumerate() {
+        if qi == query.len() {
+            break;
+        }
+        if *c != query[qi] {
+            continue;
+        }
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == ti) {
+            score += 5;
+        }
+        if ti == 0 || !text[ti - 1].is_alphanumeric() {
+            score += 3;
+        }
+        prev_match = Some(ti);
+        qi += 1;
+    }
+    (qi == query.len()).then_some(score)
+}
+
+fn match_action(query: &str, action: &QuickAction) -> Option<i64> {
+    std::iter::once(&action.title)
+        .chain(std::iter::once(&action.subtitle))
+        .chain(action.keywords.iter())
+        .filter_map(|text| fuzzy_score(query, text))
+        .max()
+}
+
+/// All quick actions, optionally fuzzy-filtered and ranked by `query`.
+pub fn list_quick_actions(query: Option<&str>) -> Vec<QuickAction> {
+    let mut actions = command_actions();
+    actions.extend(cron_actions());
+    actions.extend(skill_actions());
+
+    let Some(query) = query.map(str::trim).filter(|q| !q.is_empty()) else {
+        return actions;
+    };
+    let mut matched: Vec<QuickAction> = actions
+        .into_iter()
+        .filter_map(|mut a| {
+            a.score = match_action(query, &a)?;
+            Some(a)
+        })
+        .collect();
+    matched.sort_by(|a, b| b.score.cmp(&a.score));
+    matched
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn quick_actions(query: Option<String>) -> Result<Vec<QuickAction>, String> {
+    Ok(list_quick_actions(query.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("rst", "reset").is_some());
+        assert!(fuzzy_score("tsr", "reset").is_none());
+        assert!(fuzzy_score("RES", "/reset").is_some());
+        // Consecutive, word-start matches rank above scattered ones
+        assert!(fuzzy_score("back", "backup db").unwrap() > fuzzy_score("back", "big black rock").unwrap());
+        assert!(fuzzy_score("备份", "运行任务: 数据库备份").is_some());
+    }
+}
@@ -0,0 +1,84 @@
+//! Quick ask — a small always-on-top window for one-off agent questions.
+//!
+//! The window is created on first use (from the quick-ask hotkey or
+//! `quickask_show`) and hidden, not closed, afterwards so the next summon is
+//! instant. It loads the `/quick-ask` route, which sends questions through
+//! `agent_chat` under the dedicated `quick` session and renders the
+//! `agent://progress/{request_id}` events while the agent works. Losing focus
+//! hides it, like a launcher.
+
+use tauri::{Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use tracing::warn;
+
+pub const QUICK_ASK_LABEL: &str = "quick-ask";
+/// Agent session (`account_id`) that quick-ask questions are routed to.
+pub const QUICK_ASK_SESSION: &str = "quick";
+
+const WIDTH: f64 = 680.0;
+const HEIGHT: f64 = 420.0;
+
+/// The quick-ask window, created hidden if it does not exist yet.
+fn ensure_window(app: &tauri::AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(QUICK_ASK_LABEL) {
+        return Ok(window);
+    }
+    let window = WebviewWindowBuilder::new(app, QUICK_ASK_LABEL, WebviewUrl::App("quick-ask".into()))
+        .title("Helix Quick Ask")
+        .inner_size(WIDTH, HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .center()
+        .build()
+        .map_err(|e| format!("Failed to create quick ask window: {}", e))?;
+
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            let _ = handle.hide();
+        }
+    });
+    Ok(window)
+}
+
+fn show(app: &tauri::AppHandle) -> Result<(), String> {
+    let window = ensure_window(app)?;
+    window.center().map_err(|e| e.to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+fn hide(app: &tauri::AppHandle) -> Result<(), String> {
+    match app.get_webview_window(QUICK_ASK_LABEL) {
+        Some(window) => window.hide().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Hotkey handler: show the window, or hide it if it's already in front.
+pub fn toggle(app: &tauri::AppHandle) {
+    let in_front = app
+        .get_webview_window(QUICK_ASK_LABEL)
+        .is_some_and(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false));
+    let result = if in_front { hide(app) } else { show(app) };
+    if let Err(e) = result {
+        warn!("[quick-ask] {}", e);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn quickask_show(app: tauri::AppHandle) -> Result<(), String> {
+    show(&app)
+}
+
+#[tauri::command]
+pub async fn quickask_hide(app: tauri::AppHandle) -> Result<(), String> {
+    hide(&app)
+}
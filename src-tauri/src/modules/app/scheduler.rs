@@ -1,17 +1,37 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
 use crate::modules::{config, logger};
 
+/// Maintenance loop cadence (5 minutes)
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(300);
+
 /// Start the background scheduler for periodic tasks
 pub fn start_scheduler(app_handle: Option<tauri::AppHandle>) {
     let _app = app_handle;
-    
-    tauri::async_runtime::spawn(async move {
+
+    crate::modules::infra::shutdown::spawn("scheduler", |mut shutdown| async move {
         logger::log_info("Scheduler started");
-        
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300)); // 5 minutes
-        
+
+        let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
         loop {
-            interval.tick().await;
-            
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
+            record_wake("scheduler", SCHEDULER_INTERVAL);
+
+            // Drop expired tool output artifacts
+            if let Err(e) = crate::modules::agent::artifacts::cleanup_old_artifacts() {
+                logger::log_warn(&format!("Artifact cleanup failed: {}", e));
+            }
+
             // Periodic config reload check
             if let Ok(_config) = config::load_app_config() {
                 // Future: add periodic ops tasks here (kubeconfig refresh, aliyun config check, etc.)
@@ -20,3 +40,170 @@ pub fn start_scheduler(app_handle: Option<tauri::AppHandle>) {
     });
 }
 
+// ============================================================================
+// Wake Tracking & Suspend Detection
+// ============================================================================
+
+/// A stretch of wall-clock time during which a loop did not run, most likely
+/// because the machine was asleep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SleepWindow {
+    /// When the missed tick was expected
+    pub from: DateTime<Utc>,
+    /// When the loop woke up again
+    pub to: DateTime<Utc>,
+}
+
+impl SleepWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.from <= at && at < self.to
+    }
+}
+
+/// Wake bookkeeping of one background loop, shown by `scheduler_status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopStatus {
+    pub name: String,
+    pub interval_secs: u64,
+    pub last_wake: Option<String>,
+    /// When the loop plans to wake next
+    pub next_wake: Option<String>,
+    /// Next time the loop has actual work due (a heartbeat, the earliest cron task)
+    pub next_due: Option<String>,
+    /// Detected suspend/resume cycles since startup
+    pub resumes: u32,
+    pub last_resume: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct LoopClock {
+    interval: Duration,
+    last_wake: Option<DateTime<Utc>>,
+    /// When the loop went back to sleep after the work of `last_wake`
+    idle_since: Option<DateTime<Utc>>,
+    next_due: Option<DateTime<Utc>>,
+    resumes: u32,
+    last_resume: Option<DateTime<Utc>>,
+}
+
+static LOOPS: Lazy<Mutex<BTreeMap<&'static str, LoopClock>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// A wake at `now` after the previous one at `prev` means the machine slept
+/// when the gap is more than twice the loop interval.
+fn detect_sleep(prev: DateTime<Utc>, now: DateTime<Utc>, interval: Duration) -> Option<SleepWindow> {
+    let interval = chrono::Duration::from_std(interval).ok()?;
+    (now - prev > interval * 2).then(|| SleepWindow { from: prev + interval, to: now })
+}
+
+/// Record that loop `name` woke up now. Returns the sleep window when the
+/// wake came too late, so the loop can handle the resume.
+pub fn record_wake(name: &'static str, interval: Duration) -> Option<SleepWindow> {
+    let now = Utc::now();
+    let mut loops = LOOPS.lock();
+    let clock = loops.entry(name).or_insert_with(|| LoopClock {
+        interval,
+        last_wake: None,
+        idle_since: None,
+        next_due: None,
+        resumes: 0,
+        last_resume: None,
+    });
+    let window = clock
+        .idle_since
+        .or(clock.last_wake)
+        .and_then(|prev| detect_sleep(prev, now, clock.interval));
+    clock.interval = interval;
+    clock.last_wake = Some(now);
+    clock.idle_since = None;
+    if let Some(window) = window {
+        clock.resumes += 1;
+        clock.last_resume = Some(now);
+        logger::log_info(&format!(
+            "[{}] Resumed after sleep: no wake between {} and {}",
+            name,
+            window.from.to_rfc3339(),
+            window.to.to_rfc3339()
+        ));
+    }
+    window
+}
+
+/// Record that loop `name` finished its work and is going back to sleep, so
+/// the time spent working is not taken for a suspend by the next wake.
+pub fn record_idle(name: &'static str) {
+    if let Some(clock) = LOOPS.lock().get_mut(name) {
+        clock.idle_since = Some(Utc::now());
+    }
+}
+
+/// Publish when loop `name` next has work due.
+pub fn set_next_due(name: &'static str, at: Option<DateTime<Utc>>) {
+    if let Some(clock) = LOOPS.lock().get_mut(name) {
+        clock.next_due = at;
+    }
+}
+
+/// Next planned wake and due work of every background loop, for the debug page.
+#[tauri::command]
+pub async fn scheduler_status() -> Result<Vec<LoopStatus>, String> {
+    let loops = LOOPS.lock();
+    Ok(loops
+        .iter()
+        .map(|(name, clock)| {
+            let next_wake = clock
+                .idle_since
+                .or(clock.last_wake)
+                .zip(chrono::Duration::from_std(clock.interval).ok())
+                .map(|(last, interval)| last + interval);
+            LoopStatus {
+                name: name.to_string(),
+                interval_secs: clock.interval.as_secs(),
+                last_wake: clock.last_wake.map(|t| t.to_rfc3339()),
+                next_wake: next_wake.map(|t| t.to_rfc3339()),
+                next_due: clock.next_due.map(|t| t.to_rfc3339()),
+                resumes: clock.resumes,
+                last_resume: clock.last_resume.map(|t| t.to_rfc3339()),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_sleep() {
+        let t0 = DateTime::parse_from_rfc3339("2026-10-17T08:00:00Z").unwrap().with_timezone(&Utc);
+        let interval = Duration::from_secs(30);
+        let secs = chrono::Duration::seconds;
+
+        // On time or a bit late: no sleep
+        assert_eq!(detect_sleep(t0, t0 + secs(30), interval), None);
+        assert_eq!(detect_sleep(t0, t0 + secs(60), interval), None);
+
+        let window = detect_sleep(t0, t0 + secs(3600), interval).unwrap();
+        assert_eq!(window.from, t0 + secs(30));
+        assert_eq!(window.to, t0 + secs(3600));
+        assert!(window.contains(t0 + secs(600)));
+        assert!(!window.contains(t0 + secs(3600)));
+        assert!(!window.contains(t0));
+    }
+    #[test]
+    fn test_work_time_is_not_sleep() {
+        let interval = Duration::from_secs(30);
+        assert_eq!(record_wake("test_loop", interval), None);
+        // The previous wake ran for an hour and only just went idle
+        if let Some(clock) = LOOPS.lock().get_mut("test_loop") {
+            clock.last_wake = Some(Utc::now() - chrono::Duration::hours(1));
+        }
+        record_idle("test_loop");
+        assert_eq!(record_wake("test_loop", interval), None);
+
+        // Without the idle mark the same gap reads as a suspend
+        if let Some(clock) = LOOPS.lock().get_mut("test_loop") {
+            clock.last_wake = Some(Utc::now() - chrono::Duration::hours(1));
+        }
+        assert!(record_wake("test_loop", interval).is_some());
+    }
+}
@@ -0,0 +1,230 @@
+//! Global search across memories, chat messages and received files.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{codes, HelixError, HelixResult};
+use crate::modules::database::{DbFile, MessageSearchResult};
+use crate::modules::memory::MemorySearchResult;
+use crate::utils::truncate::safe_truncate;
+
+/// Subsystems `helix_search` can query, in display order.
+pub const SEARCH_MODULES: &[&str] = &["memory", "messages", "files"];
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 200;
+
+/// One result in the merged ranking; `id` points into the per-module list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchHit {
+    pub module: String,
+    pub id: String,
+    pub title: String,
+    /// Relevance normalized to 0..=1 within its module
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HelixSearchResult {
+    pub memories: Vec<MemorySearchResult>,
+    pub messages: Vec<MessageSearchResult>,
+    pub files: Vec<DbFile>,
+    pub total_count: usize,
+    /// All results merged and ranked best first, for a single result list
+    pub hits: Vec<SearchHit>,
+}
+
+/// Requested modules, defaulting to all. Unknown names are a validation error.
+fn resolve_modules(modules: Option<Vec<String>>) -> HelixResult<Vec<&'static str>> {
+    let Some(requested) = modules.filter(|m| !m.is_empty()) else {
+        return Ok(SEARCH_MODULES.to_vec());
+    };
+    let mut resolved = Vec::new();
+    let mut unknown = Vec::new();
+    for name in &requested {
+        let name = name.trim().to_lowercase();
+        match SEARCH_MODULES.iter().find(|m| **m == name) {
+            Some(module) if !resolved.contains(module) => resolved.push(*module),
+            Some(_) => {}
+            None => unknown.push(format!("unknown module '{}' (expected one of: {})", name, SEARCH_MODULES.join(", "))),
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(HelixError::validation_error(unknown));
+    }
+    Ok(resolved)
+}
+
+/// File names have no FTS rank: exact > prefix > word start > anywhere.
+fn file_name_score(file_name: &str, query: &str) -> f64 {
+    let name = file_name.to_lowercase();
+    let query = query.trim().to_lowercase();
+    let stem = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem);
+    if name == query || stem == query {
+        1.0
+    } else if name.starts_with(&query) {
+        0.8
+    } else if name.split(|c: char| !c.is_alphanumeric()).any(|word| word.starts_with(&query)) {
+        0.6
+    } else {
+        0.4
+    }
+}
+
+/// Scale scores so the best hit of a module is 1.0, making modules comparable.
+fn normalize(scores: Vec<(String, String, f64)>, module: &str) -> Vec<SearchHit> {
+    let max = scores.iter().map(|(_, _, s)| *s).fold(0.0_f64, f64::max);
+    scores
+        .into_iter()
+        .map(|(id, title, score)| SearchHit {
+            module: module.to_string(),
+            id,
+            title,
+            score: if max > 0.0 { score / max } else { 0.0 },
+        })
+        .collect()
+}
+
+/// Merge the per-module results into one list, best first, at most `limit` long.
+fn rank_hits(result: &HelixSearchResult, query: &str, limit: usize) -> Vec<SearchHit> {
+    let title = |text: &str| safe_truncate(text.trim(), 80).to_string();
+    let mut hits = normalize(
+        result
+            .memories
+            .iter()
+            .map(|m| (m.entry.id.to_string(), m.entry.key.clone(), m.score))
+            .collect(),
+        "memory",
+    );
+    hits.extend(normalize(
+        result
+            .messages
+            .iter()
+            .map(|m| (m.message.id.to_string(), title(&m.message.content), m.score))
+            .collect(),
+        "messages",
+    ));
+    hits.extend(normalize(
+        result
+            .files
+            .iter()
+            .map(|f| (f.id.to_string(), f.file_name.clone(), file_name_score(&f.file_name, query)))
+            .collect(),
+        "files",
+    ));
+    // Stable sort keeps each module's own order among equal scores
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    hits.truncate(limit);
+    hits
+}
+
+fn storage_error(message: String) -> HelixError {
+    HelixError::new(codes::STORAGE_DB, message)
+}
+
+/// Search memories, messages and file names at once, for the global search bar.
+/// `modules` restricts the search (default: all); `limit` caps each module and the merged `hits`.
+#[tauri::command]
+pub async fn helix_search(
+    query: String,
+    modules: Option<Vec<String>>,
+    limit: Option<i64>,
+) -> HelixResult<HelixSearchResult> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err(HelixError::validation_error(vec!["query is empty".to_string()]));
+    }
+    let modules = resolve_modules(modules)?;
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let memories = async {
+        if !modules.contains(&"memory") {
+            return Ok(vec![]);
+        }
+        crate::modules::memory::search_hybrid(&query, limit, None).await
+    };
+    let messages = async {
+        if !modules.contains(&"messages") {
+            return Ok(vec![]);
+        }
+        let q = query.clone();
+        tokio::task::spawn_blocking(move || crate::modules::database::search_messages(&q, limit))
+            .await
+            .map_err(|e| format!("Message search task failed: {}", e))?
+    };
+    let files = async {
+        if !modules.contains(&"files") {
+            return Ok(vec![]);
+        }
+        let q = query.clone();
+        tokio::task::spawn_blocking(move || crate::modules::database::search_files(&q, None, None, limit))
+            .await
+            .map_err(|e| format!("File search task failed: {}", e))?
+    };
+    let (memories, messages, files) = tokio::join!(memories, messages, files);
+
+    let mut result = HelixSearchResult {
+        memories: memories.map_err(storage_error)?,
+        messages: messages.map_err(storage_error)?,
+        files: files.map_err(storage_error)?,
+        ..Default::default()
+    };
+    result.total_count = result.memories.len() + result.messages.len() + result.files.len();
+    result.hits = rank_hits(&result, &query, limit as usize);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_modules() {
+        assert_eq!(resolve_modules(None).unwrap(), vec!["memory", "messages", "files"]);
+        assert_eq!(resolve_modules(Some(vec![])).unwrap(), vec!["memory", "messages", "files"]);
+        let picked = resolve_modules(Some(vec!["Files".into(), " memory ".into(), "files".into()])).unwrap();
+        assert_eq!(picked, vec!["files", "memory"]);
+        let err = resolve_modules(Some(vec!["mail".into()])).unwrap_err();
+        assert_eq!(err.code, codes::VALIDATION_ERROR);
+    }
+
+    #[test]
+    fn test_rank_hits_merges_modules() {
+        let message = |id: i64, content: &str, score: f64| MessageSearchResult {
+            message: crate::modules::database::DbMessage {
+                id,
+                account_id: "chat:a".into(),
+                content: content.into(),
+                from_me: false,
+                msg_type: 1,
+                ai_reply: false,
+                created_at: String::new(),
+                annotations: None,
+            },
+            score,
+            snippet: None,
+        };
+        let file = |id: i64, name: &str| DbFile {
+            id,
+            account_id: "chat:a".into(),
+            msg_id: None,
+            file_name: name.into(),
+            file_path: format!("/tmp/{}", name),
+            file_size: 0,
+            mime_type: None,
+            md5: None,
+            created_at: String::new(),
+        };
+        let result = HelixSearchResult {
+            messages: vec![message(1, "the report is late", 4.0), message(2, "report?", 2.0)],
+            files: vec![file(7, "q3_report.pdf"), file(8, "report.pdf")],
+            ..Default::default()
+        };
+
+        let hits = rank_hits(&result, "report", 10);
+        let order: Vec<(&str, &str)> = hits.iter().map(|h| (h.module.as_str(), h.id.as_str())).collect();
+        assert_eq!(order, vec![("messages", "1"), ("files", "8"), ("files", "7"), ("messages", "2")]);
+        assert_eq!(hits[0].score, 1.0);
+        assert_eq!(hits[2].score, 0.6);
+        assert_eq!(rank_hits(&result, "report", 2).len(), 2);
+    }
+}
@@ -1,78 +1,134 @@
+use once_cell::sync::OnceCell;
+use std::time::Duration;
 use tauri::{
-    image::Image,
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     Manager, Listener,
 };
+use tokio::sync::mpsc;
 use crate::modules;
 
-pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
-    // 1. Load config to get language settings
+/// Minimum gap between tray menu rebuilds
+const MENU_REBUILD_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Set once the tray exists. When the tray is disabled (e.g. Linux Wayland)
+/// it is never set and every refresh request is dropped.
+static REFRESH_TX: OnceCell<mpsc::UnboundedSender<()>> = OnceCell::new();
+
+/// Ask for a (debounced) tray menu rebuild. No-op when the tray is disabled.
+pub fn request_refresh() {
+    if let Some(tx) = REFRESH_TX.get() {
+        let _ = tx.send(());
+    }
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        #[cfg(target_os = "macos")]
+        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
+    }
+}
+
+/// Build the full tray menu from config + live scheduler state.
+fn build_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
     let config = modules::load_app_config().unwrap_or_default();
     let texts = modules::i18n::get_tray_texts(&config.language);
-    
-    // 2. Load icon
+    let menu = Menu::new(app)?;
+
+    // Quick actions
+    let auto_reply_paused = !config.ai_config.auto_reply;
+    menu.append(&CheckMenuItem::with_id(app, "pause_auto_reply", &texts.pause_auto_reply, true, auto_reply_paused, None::<&str>)?)?;
+    let cron_paused = modules::cron::is_scheduler_paused();
+    menu.append(&CheckMenuItem::with_id(app, "pause_cron", &texts.pause_cron, true, cron_paused, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "run_heartbeat", &texts.run_heartbeat, true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    menu.append(&MenuItem::with_id(app, "show", &texts.show_window, true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", &texts.quit, true, None::<&str>)?)?;
+
+    Ok(menu)
+}
+
+fn handle_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        "show" => show_main_window(app),
+        "quit" => {
+            app.exit(0);
+        }
+        "pause_auto_reply" => {
+            let mut config = modules::load_app_config().unwrap_or_default();
+            config.ai_config.auto_reply = !config.ai_config.auto_reply;
+            match modules::config::save_app_config(&config) {
+                Ok(()) => modules::logger::log_info(&format!(
+                    "Auto-reply {} from tray",
+                    if config.ai_config.auto_reply { "resumed" } else { "paused" }
+                )),
+                Err(e) => modules::logger::log_error(&format!("Failed to toggle auto-reply: {}", e)),
+            }
+            request_refresh();
+        }
+        "pause_cron" => {
+            modules::cron::set_scheduler_paused(!modules::cron::is_scheduler_paused());
+        }
+        "run_heartbeat" => {
+            tauri::async_runtime::spawn(async {
+                match modules::cron::run_heartbeat_now().await {
+                    Ok(_) => modules::logger::log_info("Heartbeat triggered from tray"),
+                    Err(e) => modules::logger::log_warn(&format!("Tray heartbeat failed: {}", e)),
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
+    // 1. Load icon
     let icon = app.default_window_icon().cloned().ok_or_else(|| {
         tauri::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "No default window icon found"))
     })?;
 
-    // 3. Define menu items
-    let show_i = MenuItem::with_id(app, "show", &texts.show_window, true, None::<&str>)?;
-    let quit_i = MenuItem::with_id(app, "quit", &texts.quit, true, None::<&str>)?;
-    
-    let sep = PredefinedMenuItem::separator(app)?;
-
-    // 4. Build menu
-    let menu = Menu::with_items(app, &[
-        &show_i,
-        &sep,
-        &quit_i,
-    ])?;
+    // 2. Build menu from current state
+    let menu = build_menu(app)?;
 
-    // 5. Build tray icon
+    // 3. Build tray icon
     let _ = TrayIconBuilder::with_id("main")
         .menu(&menu)
         .show_menu_on_left_click(false)
         .icon(icon)
         .icon_as_template(true)
-        .on_menu_event(move |app, event| {
-            match event.id().as_ref() {
-                "show" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        #[cfg(target_os = "macos")]
-                        app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-                    }
-                }
-                "quit" => {
-                    app.exit(0);
-                }
-                _ => {}
-            }
-        })
+        .on_menu_event(move |app, event| handle_menu_event(app, event.id().as_ref()))
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click {
                 button: MouseButton::Left,
                 ..
             } = event
             {
-               let app = tray.app_handle();
-               if let Some(window) = app.get_webview_window("main") {
-                   let _ = window.show();
-                   let _ = window.set_focus();
-                   #[cfg(target_os = "macos")]
-                   app.set_activation_policy(tauri::ActivationPolicy::Regular).unwrap_or(());
-               }
+               show_main_window(tray.app_handle());
             }
         })
         .build(app)?;
 
+    // 4. Debounced rebuilds: coalesce bursts of state changes into one set_menu per second
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if REFRESH_TX.set(tx).is_ok() {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(MENU_REBUILD_DEBOUNCE).await;
+                while rx.try_recv().is_ok() {}
+                update_tray_menus(&handle);
+            }
+        });
+    }
+
     // Listen for config update events
-    let handle = app.clone();
     app.listen("config://updated", move |_event| {
         modules::logger::log_info("Configuration updated, refreshing tray menu");
-        update_tray_menus(&handle);
+        request_refresh();
     });
 
     Ok(())
@@ -82,24 +138,14 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
 pub fn update_tray_menus(app: &tauri::AppHandle) {
     let app_clone = app.clone();
     tauri::async_runtime::spawn(async move {
-         let config = modules::load_app_config().unwrap_or_default();
-         let texts = modules::i18n::get_tray_texts(&config.language);
-         
-         let show_i = MenuItem::with_id(&app_clone, "show", &texts.show_window, true, None::<&str>);
-         let quit_i = MenuItem::with_id(&app_clone, "quit", &texts.quit, true, None::<&str>);
-         
-         if let (Ok(s), Ok(q)) = (show_i, quit_i) {
-             let sep = PredefinedMenuItem::separator(&app_clone).ok();
-             
-             let mut items: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = vec![&s];
-             if let Some(ref sep) = sep { items.push(sep); }
-             items.push(&q);
-             
-             if let Ok(menu) = Menu::with_items(&app_clone, &items) {
-                 if let Some(tray) = app_clone.tray_by_id("main") {
-                     let _ = tray.set_menu(Some(menu));
-                 }
-             }
-         }
+        let Some(tray) = app_clone.tray_by_id("main") else {
+            return;
+        };
+        match build_menu(&app_clone) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => modules::logger::log_warn(&format!("Failed to rebuild tray menu: {}", e)),
+        }
     });
 }
@@ -1,24 +1,54 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use crate::modules::logger;
 use chrono::Utc;
 
+const GITHUB_REPO: &str = "lbjlaq/Helix-Manager";
 const GITHUB_API_URL: &str = "https://api.github.com/repos/lbjlaq/Helix-Manager/releases/latest";
+const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/lbjlaq/Helix-Manager/releases?per_page=20";
 const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com/lbjlaq/Helix-Manager/main/package.json";
 const JSDELIVR_URL: &str = "https://cdn.jsdelivr.net/gh/lbjlaq/Helix-Manager@main/package.json";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 24;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which releases are considered when checking for updates
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    /// Only full releases
+    #[default]
+    Stable,
+    /// Full releases and pre-releases
+    Beta,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateInfo {
     pub current_version: String,
     pub latest_version: String,
     pub has_update: bool,
     pub download_url: String, // previously release_url
-    pub release_notes: String,
+    pub release_notes: String, // markdown
     pub published_at: String,
     #[serde(default)]
     pub source: Option<String>,
+    #[serde(default)]
+    pub channel: ReleaseChannel,
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Updater artifact for this platform (from the release assets)
+    #[serde(default)]
+    pub asset_name: Option<String>,
+    #[serde(default)]
+    pub asset_size: Option<u64>,
+    /// Expected SHA-256 of the artifact, from the release checksums file
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// True once the downloaded artifact matched `sha256`
+    #[serde(default)]
+    pub verified: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +57,8 @@ pub struct UpdateSettings {
     pub last_check_time: u64,
     #[serde(default = "default_check_interval")]
     pub check_interval_hours: u64,
+    #[serde(default)]
+    pub channel: ReleaseChannel,
 }
 
 fn default_check_interval() -> u64 {
@@ -39,6 +71,7 @@ impl Default for UpdateSettings {
             auto_check: true,
             last_check_time: 0,
             check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            channel: ReleaseChannel::Stable,
         }
     }
 }
@@ -47,19 +80,52 @@ impl Default for UpdateSettings {
 struct GitHubRelease {
     tag_name: String,
     html_url: String,
-    body: String,
-    published_at: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    name: String,
+    size: u64,
+    browser_download_url: String,
 }
 
 const UPDATER_JSON_URL: &str = "https://github.com/lbjlaq/Helix-Manager/releases/latest/download/updater.json";
 
 /// Check for updates with improved strategy:
+/// 0. Beta channel: newest release including pre-releases (GitHub API)
 /// 1. Check updater.json (Source of Truth for Auto-Update)
 /// 2. Fallback to GitHub API (Informational)
 pub async fn check_for_updates() -> Result<UpdateInfo, String> {
+    let channel = load_update_settings().map(|s| s.channel).unwrap_or_default();
+
+    // 0. Pre-releases never show up as "latest", so the beta channel lists releases instead
+    if channel == ReleaseChannel::Beta {
+        match check_github_releases().await {
+            Ok(info) => return Ok(info),
+            Err(e) => {
+                logger::log_warn(&format!("GitHub releases check failed: {}. Falling back to stable sources...", e));
+            }
+        }
+    }
+
     // 1. Try updater.json first (Critical for functional Auto-Update)
     match check_updater_json().await {
-        Ok(info) => return Ok(info),
+        Ok(mut info) => {
+            if info.has_update {
+                attach_release_assets_by_tag(&mut info).await;
+            }
+            return Ok(info);
+        }
         Err(e) => {
             logger::log_warn(&format!("updater.json check failed: {}. This might mean artifacts are not ready yet.", e));
             // Don't return error immediately, try fallbacks for at least informational update
@@ -112,14 +178,19 @@ struct UpdaterJson {
     version: String,
     notes: Option<String>,
     pub_date: Option<String>,
+    #[serde(default)]
+    platforms: HashMap<String, UpdaterPlatform>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdaterPlatform {
+    url: String,
 }
 
 async fn check_updater_json() -> Result<UpdateInfo, String> {
-    let client = create_client().await?;
     logger::log_info("Checking for updates via updater.json...");
 
-    let response = client
-        .get(UPDATER_JSON_URL)
+    let response = github_get(UPDATER_JSON_URL)
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
@@ -153,56 +224,306 @@ async fn check_updater_json() -> Result<UpdateInfo, String> {
         release_notes: updater_info.notes.unwrap_or_else(|| "Release notes available on GitHub.".to_string()),
         published_at: updater_info.pub_date.unwrap_or_else(|| Utc::now().to_rfc3339()),
         source: Some("updater.json".to_string()),
+        asset_name: updater_info
+            .platforms
+            .get(&platform_key())
+            .and_then(|p| url_file_name(&p.url)),
+        ..Default::default()
     })
 }
 
-async fn create_client() -> Result<reqwest::Client, String> {
-    let builder = reqwest::Client::builder()
-        .user_agent("Helix-Manager")
-        .timeout(std::time::Duration::from_secs(10));
-
-    builder.build().map_err(|e| format!("Failed to create HTTP client: {}", e))
+/// GitHub token from config (`github_token`) or GITHUB_TOKEN / GH_TOKEN, to avoid API rate limits.
+fn github_token() -> Option<String> {
+    crate::modules::config::load_app_config()
+        .ok()
+        .and_then(|c| c.github_token)
+        .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+        .or_else(|| std::env::var("GH_TOKEN").ok())
+        .filter(|t| !t.trim().is_empty())
 }
 
-async fn check_github_api() -> Result<UpdateInfo, String> {
-    let client = create_client().await?;
-
-    logger::log_info("Checking for updates via GitHub API...");
+/// GET through the shared HTTP client; API requests carry the GitHub token when available.
+fn github_get(url: &str) -> reqwest::RequestBuilder {
+    let mut request = crate::utils::http::get_client()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "Helix-Manager");
+    if url.starts_with("https://api.github.com/") {
+        request = request.header(reqwest::header::ACCEPT, "application/vnd.github+json");
+        if let Some(token) = github_token() {
+            request = request.bearer_auth(token);
+        }
+    }
+    request
+}
 
-    let response = client
-        .get(GITHUB_API_URL)
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str, what: &str) -> Result<T, String> {
+    let response = github_get(url)
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
 
     if !response.status().is_success() {
-        return Err(format!("GitHub API returned status: {}", response.status()));
+        return Err(format!("{} returned status: {}", what, response.status()));
     }
 
-    let release: GitHubRelease = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse release info: {}", e))?;
+        .map_err(|e| format!("Failed to parse {}: {}", what, e))
+}
+
+async fn check_github_api() -> Result<UpdateInfo, String> {
+    logger::log_info("Checking for updates via GitHub API...");
+
+    let release: GitHubRelease = fetch_json(GITHUB_API_URL, "GitHub API").await?;
+    let mut info = release_to_info(&release, "GitHub API", ReleaseChannel::Stable);
+    if info.has_update {
+        attach_release_assets(&mut info, &release).await;
+    }
+    Ok(info)
+}
+
+/// Beta channel: newest non-draft release, pre-releases included.
+async fn check_github_releases() -> Result<UpdateInfo, String> {
+    logger::log_info("Checking for updates via GitHub releases (beta channel)...");
 
+    let releases: Vec<GitHubRelease> = fetch_json(GITHUB_RELEASES_URL, "GitHub releases").await?;
+    let release = select_release(&releases, true)
+        .ok_or_else(|| "No published releases found".to_string())?;
+    let mut info = release_to_info(release, "GitHub releases", ReleaseChannel::Beta);
+    if info.has_update {
+        attach_release_assets(&mut info, release).await;
+    }
+    Ok(info)
+}
+
+/// Pick the highest-versioned non-draft release, optionally skipping pre-releases.
+fn select_release(releases: &[GitHubRelease], include_prerelease: bool) -> Option<&GitHubRelease> {
+    releases
+        .iter()
+        .filter(|r| !r.draft && (include_prerelease || !r.prerelease))
+        .fold(None, |best: Option<&GitHubRelease>, r| match best {
+            Some(b) if !compare_versions(r.tag_name.trim_start_matches('v'), b.tag_name.trim_start_matches('v')) => Some(b),
+            _ => Some(r),
+        })
+}
+
+fn release_to_info(release: &GitHubRelease, source: &str, channel: ReleaseChannel) -> UpdateInfo {
     let latest_version = release.tag_name.trim_start_matches('v').to_string();
     let current_version = CURRENT_VERSION.to_string();
     let has_update = compare_versions(&latest_version, &current_version);
 
     if has_update {
-        logger::log_info(&format!("New version found (API): {} (Current: {})", latest_version, current_version));
+        logger::log_info(&format!("New version found ({}): {} (Current: {})", source, latest_version, current_version));
     } else {
-        logger::log_info(&format!("Up to date (API): {} (Matches {})", current_version, latest_version));
+        logger::log_info(&format!("Up to date ({}): {} (Matches {})", source, current_version, latest_version));
     }
 
-    Ok(UpdateInfo {
+    UpdateInfo {
         current_version,
         latest_version,
         has_update,
-        download_url: release.html_url,
-        release_notes: release.body,
-        published_at: release.published_at,
-        source: Some("GitHub API".to_string()),
-    })
+        download_url: release.html_url.clone(),
+        release_notes: release.body.clone().unwrap_or_default(),
+        published_at: release.published_at.clone().unwrap_or_else(|| Utc::now().to_rfc3339()),
+        source: Some(source.to_string()),
+        channel,
+        prerelease: release.prerelease,
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Release assets & checksums
+// ============================================================================
+
+/// Platform key used in updater.json, e.g. "darwin-aarch64", "windows-x86_64".
+fn platform_key() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        other => other,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+fn url_file_name(url: &str) -> Option<String> {
+    url.split(['?', '#'])
+        .next()?
+        .rsplit('/')
+        .next()
+        .filter(|n| !n.is_empty())
+        .map(|n| urlencoding::decode(n).map(|d| d.into_owned()).unwrap_or_else(|_| n.to_string()))
+}
+
+fn is_checksums_asset(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    (lower.contains("sha256") || lower.contains("checksum"))
+        && !lower.ends_with(".sig")
+        && !lower.ends_with(".asc")
+}
+
+/// Parse `sha256sum` output: `<hex>  <file>` or `<hex> *<file>` per line.
+fn parse_checksums(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let hash = parts.next()?;
+            let file = parts.next()?.trim_start_matches('*');
+            let file = file.rsplit('/').next().unwrap_or(file);
+            (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+                .then(|| (file.to_string(), hash.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Guess this platform's updater artifact when updater.json didn't name it.
+fn guess_platform_asset(assets: &[GitHubAsset]) -> Option<&GitHubAsset> {
+    let suffixes: &[&str] = match std::env::consts::OS {
+        "macos" => &[".app.tar.gz"],
+        "windows" => &["-setup.exe", ".msi"],
+        _ => &[".AppImage", ".AppImage.tar.gz"],
+    };
+    let arch_hints: &[&str] = match std::env::consts::ARCH {
+        "aarch64" => &["aarch64", "arm64", "universal"],
+        _ => &["x86_64", "x64", "amd64", "universal"],
+    };
+    let candidates: Vec<&GitHubAsset> = assets
+        .iter()
+        .filter(|a| suffixes.iter().any(|s| a.name.ends_with(s)))
+        .collect();
+    candidates
+        .iter()
+        .find(|a| arch_hints.iter().any(|h| a.name.contains(h)))
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+async fn fetch_checksums(release: &GitHubRelease) -> Option<HashMap<String, String>> {
+    let asset = release.assets.iter().find(|a| is_checksums_asset(&a.name))?;
+    let response = github_get(&asset.browser_download_url).send().await.ok()?;
+    if !response.status().is_success() {
+        logger::log_warn(&format!("Checksums file {} returned status: {}", asset.name, response.status()));
+        return None;
+    }
+    Some(parse_checksums(&response.text().await.ok()?))
+}
+
+/// Fill in platform asset name/size and expected SHA-256 from the release assets.
+async fn attach_release_assets(info: &mut UpdateInfo, release: &GitHubRelease) {
+    let updater_json_url = release
+        .assets
+        .iter()
+        .find(|a| a.name == "updater.json")
+        .map(|a| a.browser_download_url.clone());
+    if info.asset_name.is_none() {
+        if let Some(url) = updater_json_url {
+            if let Ok(updater) = fetch_json::<UpdaterJson>(&url, "updater.json").await {
+                info.asset_name = updater.platforms.get(&platform_key()).and_then(|p| url_file_name(&p.url));
+            }
+        }
+    }
+
+    let asset = match &info.asset_name {
+        Some(name) => release.assets.iter().find(|a| &a.name == name),
+        None => guess_platform_asset(&release.assets),
+    };
+    if let Some(asset) = asset {
+        info.asset_name = Some(asset.name.clone());
+        info.asset_size = Some(asset.size);
+    }
+
+    if let (Some(name), Some(checksums)) = (&info.asset_name, fetch_checksums(release).await) {
+        info.sha256 = checksums.get(name).cloned();
+    }
+    if info.sha256.is_none() {
+        logger::log_warn(&format!("No SHA-256 checksum published for v{} ({})", info.latest_version, platform_key()));
+    }
+}
+
+async fn fetch_release_by_tag(version: &str) -> Result<GitHubRelease, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/tags/v{}", GITHUB_REPO, version);
+    fetch_json(&url, "GitHub release").await
+}
+
+async fn attach_release_assets_by_tag(info: &mut UpdateInfo) {
+    match fetch_release_by_tag(&info.latest_version).await {
+        Ok(release) => {
+            info.prerelease = release.prerelease;
+            if let Some(body) = release.body.as_ref().filter(|b| !b.trim().is_empty()) {
+                info.release_notes = body.clone();
+            }
+            attach_release_assets(info, &release).await;
+        }
+        Err(e) => logger::log_warn(&format!("Could not load release assets for v{}: {}", info.latest_version, e)),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Download the update through the updater plugin (which checks its minisign signature),
+/// verify the artifact's SHA-256 against the release checksums file, then install it.
+pub async fn install_verified_update<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<UpdateInfo, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let mut info = check_for_updates().await?;
+    if !info.has_update {
+        return Err("already_latest".to_string());
+    }
+
+    // Point the updater at the exact release we checked (pre-releases aren't "latest")
+    let endpoint = format!(
+        "https://github.com/{}/releases/download/v{}/updater.json",
+        GITHUB_REPO, info.latest_version
+    );
+    let endpoint = endpoint
+        .parse()
+        .map_err(|e| format!("Invalid updater endpoint: {}", e))?;
+    let update = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .and_then(|b| b.build())
+        .map_err(|e| format!("Failed to create updater: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Updater check failed: {}", e))?
+        .ok_or_else(|| "already_latest".to_string())?;
+
+    let file_name = url_file_name(update.download_url.as_str())
+        .ok_or_else(|| "Invalid update download URL".to_string())?;
+    if info.asset_name.as_deref() != Some(file_name.as_str()) {
+        info.asset_name = Some(file_name.clone());
+        info.sha256 = None;
+        if let Ok(release) = fetch_release_by_tag(&info.latest_version).await {
+            info.asset_size = release.assets.iter().find(|a| a.name == file_name).map(|a| a.size);
+            info.sha256 = fetch_checksums(&release).await.and_then(|c| c.get(&file_name).cloned());
+        }
+    }
+    let expected = info.sha256.clone().ok_or_else(|| {
+        logger::log_error(&format!("Refusing to install {}: no published SHA-256", file_name));
+        "checksum_missing".to_string()
+    })?;
+
+    logger::log_info(&format!("Downloading update {} ...", file_name));
+    let bytes = update
+        .download(|_, _| {}, || {})
+        .await
+        .map_err(|e| format!("Update download failed: {}", e))?;
+
+    let actual = sha256_hex(&bytes);
+    if actual != expected {
+        logger::log_error(&format!("SHA-256 mismatch for {}: expected {}, got {}", file_name, expected, actual));
+        return Err("checksum_mismatch".to_string());
+    }
+    info.asset_size = Some(bytes.len() as u64);
+    info.verified = true;
+    logger::log_info(&format!("SHA-256 verified for {}, installing v{}", file_name, info.latest_version));
+
+    update
+        .install(bytes)
+        .map_err(|e| format!("Update install failed: {}", e))?;
+    Ok(info)
 }
 
 #[derive(Deserialize)]
@@ -211,12 +532,9 @@ struct PackageJson {
 }
 
 async fn check_static_url(url: &str, source_name: &str) -> Result<UpdateInfo, String> {
-    let client = create_client().await?;
-
     logger::log_info(&format!("Checking for updates via {}...", source_name));
 
-    let response = client
-        .get(url)
+    let response = github_get(url)
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
@@ -252,32 +570,31 @@ async fn check_static_url(url: &str, source_name: &str) -> Result<UpdateInfo, St
         release_notes,
         published_at: Utc::now().to_rfc3339(), // Approximate time
         source: Some(source_name.to_string()),
+        ..Default::default()
     })
 }
 
-/// Compare two semantic versions (e.g., "3.3.30" vs "3.3.29")
-fn compare_versions(latest: &str, current: &str) -> bool {
-    let parse_version = |v: &str| -> Vec<u32> {
-        v.split('.')
-            .filter_map(|s| s.parse::<u32>().ok())
-            .collect()
-    };
-
-    let latest_parts = parse_version(latest);
-    let current_parts = parse_version(current);
-
-    for i in 0..latest_parts.len().max(current_parts.len()) {
-        let latest_part = latest_parts.get(i).unwrap_or(&0);
-        let current_part = current_parts.get(i).unwrap_or(&0);
-
-        if latest_part > current_part {
-            return true;
-        } else if latest_part < current_part {
-            return false; // e.g. local: 3.3.30, remote: 3.3.30 => false
-        }
+/// Parse a version tag leniently: a leading `v` is dropped and a missing
+/// minor/patch counts as 0 ("3.4" is 3.4.0, "3.4-beta.1" is 3.4.0-beta.1).
+fn parse_version(v: &str) -> Option<semver::Version> {
+    let v = v.trim().trim_start_matches('v');
+    if let Ok(version) = semver::Version::parse(v) {
+        return Some(version);
     }
+    let split = v.find(['-', '+']).unwrap_or(v.len());
+    let (core, suffix) = v.split_at(split);
+    let padding = ".0".repeat(2usize.saturating_sub(core.matches('.').count()));
+    semver::Version::parse(&format!("{}{}{}", core, padding, suffix)).ok()
+}
 
-    false
+/// Whether `latest` is newer than `current` by semver precedence, so a
+/// prerelease ranks below its release ("1.2.0-beta.1" < "1.2.0").
+/// Unparsable versions are never offered.
+fn compare_versions(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
 }
 
 /// Check if enough time has passed since last check
@@ -427,6 +744,14 @@ mod tests {
         assert!(compare_versions("4.0.3", "3.3.35"));
         assert!(!compare_versions("3.3.34", "3.3.35"));
         assert!(!compare_versions("3.3.35", "3.3.35"));
+        // Prereleases rank below their release
+        assert!(!compare_versions("1.2.0-beta.1", "1.2.0"));
+        assert!(compare_versions("1.2.0", "1.2.0-beta.1"));
+        assert!(compare_versions("1.2.0-beta.2", "1.2.0-beta.1"));
+        assert!(compare_versions("1.2.0-rc.1", "1.2.0-beta.3"));
+        assert!(compare_versions("v3.4", "3.3.35"));
+        assert!(!compare_versions("3.3", "3.3.0"));
+        assert!(!compare_versions("nightly", "3.3.35"));
     }
 
     #[test]
@@ -443,4 +768,37 @@ mod tests {
         settings.auto_check = false;
         assert!(!should_check_for_updates(&settings));
     }
+
+    #[test]
+    fn test_parse_checksums() {
+        let hash = "a".repeat(64);
+        let text = format!("{h}  Helix_3.4.0_x64-setup.exe\n{H} *dist/Helix.app.tar.gz\nnot a checksum line\n", h = hash, H = hash.to_uppercase());
+        let sums = parse_checksums(&text);
+        assert_eq!(sums.len(), 2);
+        assert_eq!(sums["Helix_3.4.0_x64-setup.exe"], hash);
+        assert_eq!(sums["Helix.app.tar.gz"], hash);
+        assert!(is_checksums_asset("SHA256SUMS.txt"));
+        assert!(!is_checksums_asset("SHA256SUMS.txt.sig"));
+    }
+
+    #[test]
+    fn test_select_release_channel() {
+        let release = |tag: &str, prerelease: bool, draft: bool| GitHubRelease {
+            tag_name: tag.to_string(),
+            html_url: String::new(),
+            body: None,
+            published_at: None,
+            prerelease,
+            draft,
+            assets: Vec::new(),
+        };
+        let releases = vec![
+            release("v3.5.0", false, true),
+            release("v3.4.0-beta.1", true, false),
+            release("v3.3.36", false, false),
+        ];
+        assert_eq!(select_release(&releases, false).unwrap().tag_name, "v3.3.36");
+        assert_eq!(select_release(&releases, true).unwrap().tag_name, "v3.4.0-beta.1");
+        assert_eq!(url_file_name("https://x/download/v1/Helix%20App.app.tar.gz?raw=1").as_deref(), Some("Helix App.app.tar.gz"));
+    }
 }
@@ -1,11 +1,29 @@
 //! Workspace file manager — manages ~/.helix/ prompt configuration files.
 //!
 //! Provides Tauri commands for listing, reading, writing, uploading, and
-//! downloading files in the user's ~/.helix/ workspace directory.
+//! downloading files in the user's ~/.helix/ workspace directory, plus
+//! full-text search and linting across the agent's sandbox (~/helix_workspace/).
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+/// Default cap on `workspace_search` matches.
+const SEARCH_DEFAULT_MAX_RESULTS: usize = 200;
+/// Matched lines longer than this are truncated.
+const SEARCH_LINE_MAX_CHARS: usize = 200;
+/// Files larger than this are skipped.
+const SEARCH_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Linter path/args overrides, in ~/.helix/.
+const LINTER_CONFIG_FILE: &str = "linter_config.json";
+/// A single linter run is killed after this long.
+const LINT_TIMEOUT_SECS: u64 = 120;
+/// Most files collected when linting a directory.
+const LINT_MAX_FILES: usize = 1000;
+/// Directories never descended into when collecting files to lint.
+const LINT_SKIP_DIRS: &[&str] = &["target", "node_modules", "__pycache__", "dist", "build"];
+
 /// File metadata for workspace listing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceFile {
@@ -14,6 +32,16 @@ pub struct WorkspaceFile {
     pub modified: String,
 }
 
+/// A line matched by `workspace_search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    /// Path relative to the sandbox root
+    pub file: String,
+    /// 1-based line number
+    pub line_number: u32,
+    pub content: String,
+}
+
 /// Get the workspace directory path (~/.helix/)
 fn get_workspace_dir() -> Result<std::path::PathBuf, String> {
     let helix_dir = dirs::home_dir()
@@ -257,3 +285,754 @@ pub async fn workspace_read_session_file(dir_path: String, name: String) -> Resu
 
     std::fs::read_to_string(&target).map_err(|e| format!("Failed to read file '{}': {}", name, e))
 }
+
+// ============================================================================
+// Full-text search
+// ============================================================================
+
+/// Translate a file-name glob (`*.rs`, `notes-?.md`) into an anchored regex.
+fn glob_to_regex(glob: &str) -> Result<regex::Regex, String> {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    regex::Regex::new(&pattern).map_err(|e| format!("Invalid include pattern '{}': {}", glob, e))
+}
+
+/// Recursive walk of `dir` (hidden entries skipped, same as `list_dir_recursive`),
+/// collecting matching lines of text files until `max_results` is reached.
+fn search_dir_recursive(
+    root: &Path,
+    dir: &Path,
+    matcher: &regex::Regex,
+    include: Option<&regex::Regex>,
+    max_results: usize,
+    results: &mut Vec<SearchMatch>,
+) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        if results.len() >= max_results {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            search_dir_recursive(root, &path, matcher, include, max_results, results);
+            continue;
+        }
+        if !file_type.is_file() || include.is_some_and(|re| !re.is_match(&name)) {
+            continue;
+        }
+        if entry.metadata().map(|m| m.len() > SEARCH_MAX_FILE_BYTES).unwrap_or(true) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        // Skip binary files
+        if bytes[..bytes.len().min(8192)].contains(&0) {
+            continue;
+        }
+        let text = String::from_utf8_lossy(&bytes);
+        let file = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        for (i, line) in text.lines().enumerate() {
+            if !matcher.is_match(line) {
+                continue;
+            }
+            let content = crate::utils::truncate::safe_truncate(line.trim_end(), SEARCH_LINE_MAX_CHARS);
+            results.push(SearchMatch {
+                file: file.clone(),
+                line_number: (i + 1) as u32,
+                content: content.to_string(),
+            });
+            if results.len() >= max_results {
+                return;
+            }
+        }
+    }
+}
+
+/// Search `root` for lines containing `query` (literal text).
+pub fn search_files(
+    root: &Path,
+    query: &str,
+    include: Option<&str>,
+    case_sensitive: bool,
+    max_results: usize,
+) -> Result<Vec<SearchMatch>, String> {
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let matcher = regex::RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid query: {}", e))?;
+    let include = include
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+        .map(glob_to_regex)
+        .transpose()?;
+
+    let mut results = Vec::new();
+    search_dir_recursive(root, root, &matcher, include.as_ref(), max_results, &mut results);
+    Ok(results)
+}
+
+/// Full-text search across the agent sandbox (~/helix_workspace/) — the
+/// workspace-scoped counterpart of the agent's `grep_search` tool.
+#[tauri::command]
+pub async fn workspace_search(
+    query: String,
+    include: Option<String>,
+    case_sensitive: bool,
+    max_results: Option<usize>,
+) -> Result<Vec<SearchMatch>, String> {
+    let root = std::path::PathBuf::from(crate::modules::agent::tools::get_sandbox_path());
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    let max_results = max_results.unwrap_or(SEARCH_DEFAULT_MAX_RESULTS).max(1);
+    tokio::task::spawn_blocking(move || {
+        search_files(&root, &query, include.as_deref(), case_sensitive, max_results)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+// ============================================================================
+// Linting
+// ============================================================================
+
+/// One problem reported by a linter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LintIssue {
+    /// Path relative to the sandbox root
+    pub file: String,
+    /// 1-based line number (0 when the linter gave none)
+    pub line: u32,
+    pub column: Option<u32>,
+    /// "error" or "warning"
+    pub severity: String,
+    pub message: String,
+    /// Rule or lint name, e.g. `F401`, `no-unused-vars`, `clippy::needless_return`
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LintResult {
+    pub issues: Vec<LintIssue>,
+    pub file_count: u32,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Linter {
+    Clippy,
+    Ruff,
+    Eslint,
+    Markdownlint,
+}
+
+/// Program and arguments a linter is run with; file paths are appended
+/// (except for clippy, which lints the whole crate).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinterCommand {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl Linter {
+    const ALL: [Linter; 4] = [Linter::Clippy, Linter::Ruff, Linter::Eslint, Linter::Markdownlint];
+
+    fn name(self) -> &'static str {
+        match self {
+            Linter::Clippy => "clippy",
+            Linter::Ruff => "ruff",
+            Linter::Eslint => "eslint",
+            Linter::Markdownlint => "markdownlint",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        let name = if name == "cargo-clippy" || name == "cargo clippy" { "clippy" } else { name.as_str() };
+        Self::ALL.into_iter().find(|l| l.name() == name)
+    }
+
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Linter::Clippy => &["rs"],
+            Linter::Ruff => &["py"],
+            Linter::Eslint => &["js", "jsx", "mjs", "cjs", "ts", "tsx"],
+            Linter::Markdownlint => &["md", "markdown"],
+        }
+    }
+
+    fn for_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        Self::ALL.into_iter().find(|l| l.extensions().contains(&ext.as_str()))
+    }
+
+    fn default_command(self) -> LinterCommand {
+        let (command, args): (&str, &[&str]) = match self {
+            Linter::Clippy => ("cargo", &["clippy", "--message-format=json", "--quiet"]),
+            Linter::Ruff => ("ruff", &["check", "--output-format=json", "--exit-zero"]),
+            Linter::Eslint => ("eslint", &["--format", "json"]),
+            Linter::Markdownlint => ("markdownlint", &["--json"]),
+        };
+        LinterCommand { command: command.to_string(), args: args.iter().map(|a| a.to_string()).collect() }
+    }
+}
+
+/// Linter commands from ~/.helix/linter_config.json, e.g.
+/// `{"ruff": {"command": "/opt/bin/ruff", "args": ["check", "--output-format=json"]}}`.
+/// Linters missing from the file keep their defaults.
+fn load_linter_config() -> HashMap<String, LinterCommand> {
+    let Ok(path) = get_workspace_dir().map(|dir| dir.join(LINTER_CONFIG_FILE)) else {
+        return HashMap::new();
+    };
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return HashMap::new();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        warn!("Ignoring invalid {}: {}", path.display(), e);
+        HashMap::new()
+    })
+}
+
+/// Files under `path` (or `path` itself) to lint, grouped by linter. With a
+/// forced linter every collected file goes to it.
+fn collect_lint_files(path: &Path, forced: Option<Linter>) -> BTreeMap<Linter, Vec<PathBuf>> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            if files.len() >= LINT_MAX_FILES {
+                return;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if name.starts_with('.') || (file_type.is_dir() && LINT_SKIP_DIRS.contains(&name.as_str())) {
+                continue;
+            }
+            if file_type.is_dir() {
+                walk(&entry.path(), files);
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<Linter, Vec<PathBuf>> = BTreeMap::new();
+    if path.is_file() {
+        if let Some(linter) = forced.or_else(|| Linter::for_path(path)) {
+            groups.entry(linter).or_default().push(path.to_path_buf());
+        }
+        return groups;
+    }
+    let mut files = Vec::new();
+    walk(path, &mut files);
+    for file in files {
+        let linter = match forced {
+            Some(linter) => Some(linter).filter(|l| Linter::for_path(&file) == Some(*l)),
+            None => Linter::for_path(&file),
+        };
+        if let Some(linter) = linter {
+            groups.entry(linter).or_default().push(file);
+        }
+    }
+    groups
+}
+
+/// Directory of the closest Cargo.toml at or above `path`, not leaving `root`.
+fn find_manifest_dir(root: &Path, path: &Path) -> Option<PathBuf> {
+    let start = if path.is_dir() { path } else { path.parent()? };
+    start
+        .ancestors()
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// `file` as reported by a linter run in `cwd`, relative to the sandbox `root`.
+fn lint_relative_path(root: &Path, cwd: &Path, file: &str) -> String {
+    let path = cwd.join(file);
+    path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/")
+}
+
+fn json_u32(value: &serde_json::Value) -> Option<u32> {
+    value.as_u64().map(|n| n as u32)
+}
+
+/// `cargo clippy --message-format=json`: one JSON object per line.
+fn parse_clippy_json(output: &str) -> Option<Vec<(String, LintIssue)>> {
+    let mut issues = Vec::new();
+    let mut saw_json = false;
+    for line in output.lines().filter(|l| l.trim_start().starts_with('{')) {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        saw_json = true;
+        if value["reason"] != "compiler-message" {
+            continue;
+        }
+        let message = &value["message"];
+        let level = message["level"].as_str().unwrap_or("");
+        if level != "warning" && level != "error" {
+            continue;
+        }
+        let spans = message["spans"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let Some(span) = spans.iter().find(|s| s["is_primary"] == true).or(spans.first()) else {
+            continue; // Summary lines like "3 warnings emitted"
+        };
+        issues.push((
+            span["file_name"].as_str().unwrap_or("").to_string(),
+            LintIssue {
+                file: String::new(),
+                line: json_u32(&span["line_start"]).unwrap_or(0),
+                column: json_u32(&span["column_start"]),
+                severity: level.to_string(),
+                message: message["message"].as_str().unwrap_or("").to_string(),
+                rule: message["code"]["code"].as_str().map(str::to_string),
+            },
+        ));
+    }
+    saw_json.then_some(issues)
+}
+
+/// JSON reports of ruff, eslint and markdownlint.
+fn parse_lint_json(linter: Linter, output: &str) -> Option<Vec<(String, LintIssue)>> {
+    if linter == Linter::Clippy {
+        return parse_clippy_json(output);
+    }
+    let start = output.find('[')?;
+    let value: serde_json::Value = serde_json::from_str(output[start..].trim_end()).ok()?;
+    let entries = value.as_array()?;
+    let issue = |line, column, severity: &str, message: &str, rule: Option<&str>| LintIssue {
+        file: String::new(),
+        line,
+        column,
+        severity: severity.to_string(),
+        message: message.to_string(),
+        rule: rule.map(str::to_string),
+    };
+    let mut issues = Vec::new();
+    for entry in entries {
+        match linter {
+            Linter::Ruff => {
+                let file = entry["filename"].as_str().unwrap_or("").to_string();
+                let location = &entry["location"];
+                let rule = entry["code"].as_str();
+                // Syntax errors come without a rule code
+                let severity = if rule.is_none() { "error" } else { "warning" };
+                let message = entry["message"].as_str().unwrap_or("");
+                let line = json_u32(&location["row"]).unwrap_or(0);
+                issues.push((file, issue(line, json_u32(&location["column"]), severity, message, rule)));
+            }
+            Linter::Eslint => {
+                let file = entry["filePath"].as_str().unwrap_or("");
+                for message in entry["messages"].as_array().into_iter().flatten() {
+                    let is_error = message["severity"] == 2 || message["fatal"] == true;
+                    let severity = if is_error { "error" } else { "warning" };
+                    issues.push((
+                        file.to_string(),
+                        issue(
+                            json_u32(&message["line"]).unwrap_or(0),
+                            json_u32(&message["column"]),
+                            severity,
+                            message["message"].as_str().unwrap_or(""),
+                            message["ruleId"].as_str(),
+                        ),
+                    ));
+                }
+            }
+            Linter::Markdownlint => {
+                let file = entry["fileName"].as_str().unwrap_or("").to_string();
+                let mut message = entry["ruleDescription"].as_str().unwrap_or("").to_string();
+                if let Some(detail) = entry["errorDetail"].as_str().filter(|d| !d.is_empty()) {
+                    message = format!("{} [{}]", message, detail);
+                }
+                let rule = entry["ruleNames"].as_array().map(|names| {
+                    names.iter().filter_map(|n| n.as_str()).take(2).collect::<Vec<_>>().join("/")
+                });
+                let line = json_u32(&entry["lineNumber"]).unwrap_or(0);
+                let column = json_u32(&entry["errorRange"][0]);
+                issues.push((file, issue(line, column, "warning", &message, rule.as_deref())));
+            }
+            Linter::Clippy => unreachable!("clippy output is parsed line by line"),
+        }
+    }
+    Some(issues)
+}
+
+/// Fallback for plain-text output: `file:line[:col][:] message`, with an
+/// optional leading rule code such as `MD013/line-length` or `E501`.
+fn parse_lint_text(output: &str) -> Vec<(String, LintIssue)> {
+    static LINE_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"^(?P<file>[^:\s][^:]*):(?P<line>\d+)(?::(?P<col>\d+))?:?\s+(?P<msg>.+)$").unwrap()
+    });
+    static RULE_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"^(?P<rule>[A-Z]+[0-9]+(?:/[\w-]+)*)\s+(?P<rest>.+)$").unwrap()
+    });
+    output
+        .lines()
+        .filter_map(|line| LINE_RE.captures(line.trim()))
+        .map(|caps| {
+            let mut message = caps["msg"].trim().to_string();
+            let mut rule = None;
+            if let Some(rc) = RULE_RE.captures(&message) {
+                rule = Some(rc["rule"].to_string());
+                message = rc["rest"].to_string();
+            }
+            let lower = message.to_lowercase();
+            let severity = if lower.starts_with("error") || lower.contains(" error") { "error" } else { "warning" };
+            (
+                caps["file"].to_string(),
+                LintIssue {
+                    file: String::new(),
+                    line: caps["line"].parse().unwrap_or(0),
+                    column: caps.name("col").and_then(|c| c.as_str().parse().ok()),
+                    severity: severity.to_string(),
+                    message,
+                    rule,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Issues from a linter run in `cwd`, with paths made relative to `root`.
+fn parse_lint_output(linter: Linter, stdout: &str, stderr: &str, root: &Path, cwd: &Path) -> Vec<LintIssue> {
+    // markdownlint writes its report to stderr
+    let output = if stdout.trim().is_empty() { stderr } else { stdout };
+    parse_lint_json(linter, output)
+        .unwrap_or_else(|| parse_lint_text(output))
+        .into_iter()
+        .map(|(file, mut issue)| {
+            issue.file = lint_relative_path(root, cwd, &file);
+            issue
+        })
+        .collect()
+}
+
+async fn run_linter(
+    linter: Linter,
+    command: &LinterCommand,
+    files: &[PathBuf],
+    root: &Path,
+    cwd: &Path,
+) -> Result<Vec<LintIssue>, String> {
+    let mut cmd = tokio::process::Command::new(&command.command);
+    cmd.args(&command.args).current_dir(cwd).kill_on_drop(true);
+    if linter != Linter::Clippy {
+        cmd.args(files);
+    }
+    let output = tokio::time::timeout(std::time::Duration::from_secs(LINT_TIMEOUT_SECS), cmd.output())
+        .await
+        .map_err(|_| format!("{} timed out after {}s", linter.name(), LINT_TIMEOUT_SECS))?
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                format!(
+                    "Linter '{}' not found; install it or set its path in ~/.helix/{}",
+                    command.command, LINTER_CONFIG_FILE
+                )
+            } else {
+                format!("Failed to run {}: {}", linter.name(), e)
+            }
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let issues = parse_lint_output(linter, &stdout, &stderr, root, cwd);
+    // Linters exit non-zero when they find issues; only a silent failure is an error
+    if issues.is_empty() && !output.status.success() {
+        let excerpt = crate::utils::truncate::safe_truncate(stderr.trim(), SEARCH_LINE_MAX_CHARS);
+        return Err(format!("{} failed ({}): {}", linter.name(), output.status, excerpt));
+    }
+    Ok(issues)
+}
+
+/// Lint a file or directory of the sandbox. The linter is picked per file
+/// extension unless `linter` (clippy, ruff, eslint, markdownlint) is given;
+/// `overrides` replaces the default command of the linters it names.
+pub async fn lint_path(
+    root: &Path,
+    path: &Path,
+    linter: Option<&str>,
+    overrides: &HashMap<String, LinterCommand>,
+) -> Result<LintResult, String> {
+    let start = std::time::Instant::now();
+    let forced = linter
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|name| {
+            Linter::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = Linter::ALL.iter().map(|l| l.name()).collect();
+                format!("Unknown linter '{}' (expected one of: {})", name, known.join(", "))
+            })
+        })
+        .transpose()?;
+
+    let target = root.join(path);
+    let root = std::fs::canonicalize(root).map_err(|e| format!("Invalid sandbox dir: {}", e))?;
+    let target = std::fs::canonicalize(&target).map_err(|e| format!("Cannot lint '{}': {}", path.display(), e))?;
+    if !target.starts_with(&root) {
+        return Err("Access denied: path traversal".to_string());
+    }
+
+    let groups = {
+        let target = target.clone();
+        tokio::task::spawn_blocking(move || collect_lint_files(&target, forced))
+            .await
+            .map_err(|e| format!("Lint task failed: {}", e))?
+    };
+
+    let mut result = LintResult::default();
+    for (linter, files) in groups {
+        let command = overrides.get(linter.name()).cloned().unwrap_or_else(|| linter.default_command());
+        result.file_count += files.len() as u32;
+        if linter == Linter::Clippy {
+            // Clippy lints whole crates: run once per Cargo.toml
+            let mut crates = BTreeSet::new();
+            for file in &files {
+                let dir = find_manifest_dir(&root, file).ok_or_else(|| {
+                    format!("No Cargo.toml found for {}", lint_relative_path(&root, &root, &file.to_string_lossy()))
+                })?;
+                crates.insert(dir);
+            }
+            for dir in &crates {
+                result.issues.extend(run_linter(linter, &command, &files, &root, dir).await?);
+            }
+        } else {
+            let cwd = if target.is_dir() { target.clone() } else { target.parent().unwrap_or(&root).to_path_buf() };
+            result.issues.extend(run_linter(linter, &command, &files, &root, &cwd).await?);
+        }
+    }
+    result.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(result)
+}
+
+/// Run linters on a file or directory of the agent sandbox (~/helix_workspace/).
+/// Linter commands can be overridden in ~/.helix/linter_config.json.
+#[tauri::command]
+pub async fn workspace_lint(path: String, linter: Option<String>) -> Result<LintResult, String> {
+    let root = std::path::PathBuf::from(crate::modules::agent::tools::get_sandbox_path());
+    lint_path(&root, Path::new(path.trim()), linter.as_deref(), &load_linter_config()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_to_regex() {
+        let re = glob_to_regex("*.md").unwrap();
+        assert!(re.is_match("notes.md"));
+        assert!(!re.is_match("notes.md.bak"));
+        assert!(glob_to_regex("a?.txt").unwrap().is_match("ab.txt"));
+        assert!(!glob_to_regex("a?.txt").unwrap().is_match("a.txt"));
+    }
+
+    #[test]
+    fn test_search_files() {
+        let root = std::env::temp_dir().join(format!("helix-ws-search-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("a.md"), "Hello World\nnothing\nhello again").unwrap();
+        std::fs::write(root.join("sub/b.txt"), format!("hello {}", "x".repeat(300))).unwrap();
+        std::fs::write(root.join(".git/c.md"), "hello hidden").unwrap();
+        std::fs::write(root.join("bin.dat"), b"hello\0binary").unwrap();
+
+        let all = search_files(&root, "hello", None, false, 200).unwrap();
+        let found: Vec<(String, u32)> = all.iter().map(|m| (m.file.clone(), m.line_number)).collect();
+        assert_eq!(found, vec![("a.md".into(), 1), ("a.md".into(), 3), ("sub/b.txt".into(), 1)]);
+        assert_eq!(all[2].content.chars().count(), SEARCH_LINE_MAX_CHARS);
+
+        assert_eq!(search_files(&root, "hello", None, true, 200).unwrap().len(), 2);
+        assert_eq!(search_files(&root, "hello", Some("*.md"), false, 200).unwrap().len(), 2);
+        assert_eq!(search_files(&root, "hello", None, false, 1).unwrap().len(), 1);
+        assert!(search_files(&root, "", None, false, 200).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    fn lint_fixture_dir(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("helix-ws-lint-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_collect_lint_files() {
+        let root = lint_fixture_dir("collect");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        for file in ["app.py", "src/main.ts", "README.md", "notes.txt", "node_modules/dep/index.js", ".hidden.py"] {
+            std::fs::write(root.join(file), "x").unwrap();
+        }
+
+        let names = |groups: BTreeMap<Linter, Vec<PathBuf>>| -> Vec<(&'static str, Vec<String>)> {
+            groups
+                .into_iter()
+                .map(|(linter, files)| {
+                    let files = files.iter().map(|f| lint_relative_path(&root, &root, &f.to_string_lossy())).collect();
+                    (linter.name(), files)
+                })
+                .collect()
+        };
+        assert_eq!(
+            names(collect_lint_files(&root, None)),
+            vec![
+                ("ruff", vec!["app.py".to_string()]),
+                ("eslint", vec!["src/main.ts".to_string()]),
+                ("markdownlint", vec!["README.md".to_string()]),
+            ]
+        );
+        assert_eq!(names(collect_lint_files(&root, Some(Linter::Ruff))), vec![("ruff", vec!["app.py".to_string()])]);
+        // A single file is linted even with an unusual extension when the linter is forced
+        let forced = collect_lint_files(&root.join("notes.txt"), Some(Linter::Markdownlint));
+        assert_eq!(forced[&Linter::Markdownlint].len(), 1);
+        assert!(collect_lint_files(&root.join("notes.txt"), None).is_empty());
+
+        assert_eq!(Linter::from_name("Cargo Clippy"), Some(Linter::Clippy));
+        assert_eq!(Linter::from_name("pylint"), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_parse_lint_json() {
+        let root = Path::new("/ws");
+        let eslint = r#"[{"filePath":"/ws/web/app.js","messages":[
+            {"ruleId":"no-unused-vars","severity":2,"message":"'x' is assigned a value but never used.",
+             "line":1,"column":5},
+            {"ruleId":"semi","severity":1,"message":"Missing semicolon.","line":2,"column":12}],"errorCount":1}]"#;
+        let issues = parse_lint_output(Linter::Eslint, eslint, "", root, root);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0],
+            LintIssue {
+                file: "web/app.js".into(),
+                line: 1,
+                column: Some(5),
+                severity: "error".into(),
+                message: "'x' is assigned a value but never used.".into(),
+                rule: Some("no-unused-vars".into()),
+            }
+        );
+        assert_eq!(issues[1].severity, "warning");
+
+        // markdownlint reports on stderr, relative to its working directory
+        let markdownlint = r#"[{"fileName":"README.md","lineNumber":3,"ruleNames":["MD013","line-length"],
+            "ruleDescription":"Line length","errorDetail":"Expected: 80; Actual: 95","errorRange":null}]"#;
+        let issues = parse_lint_output(Linter::Markdownlint, "", markdownlint, root, &root.join("docs"));
+        assert_eq!(issues[0].file, "docs/README.md");
+        assert_eq!(issues[0].rule.as_deref(), Some("MD013/line-length"));
+        assert_eq!(issues[0].message, "Line length [Expected: 80; Actual: 95]");
+        assert_eq!(issues[0].column, None);
+
+        let clippy = concat!(
+            r#"{"reason":"compiler-artifact","package_id":"demo"}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"unneeded `return` statement","#,
+            r#""code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":3,"#,
+            r#""column_start":5,"is_primary":true}]}}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"1 warning emitted","#,
+            r#""code":null,"spans":[]}}"#, "\n",
+            r#"{"reason":"build-finished","success":true}"#,
+        );
+        let issues = parse_lint_output(Linter::Clippy, clippy, "", root, &root.join("demo"));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file, "demo/src/lib.rs");
+        assert_eq!((issues[0].line, issues[0].column), (3, Some(5)));
+        assert_eq!(issues[0].rule.as_deref(), Some("clippy::needless_return"));
+    }
+
+    #[test]
+    fn test_parse_lint_text() {
+        let output = "README.md:1 MD041/first-line-heading First line in a file should be a top-level heading\n\
+                      app.py:4:1: E302 expected 2 blank lines, found 1\n\
+                      src/x.ts:2:7: error Parsing error: Unexpected token\n\
+                      Found 2 errors.";
+        let issues = parse_lint_output(Linter::Ruff, output, "", Path::new("/ws"), Path::new("/ws"));
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.line, i.column, i.severity.as_str(), i.rule.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("README.md", 1, None, "warning", Some("MD041/first-line-heading")),
+                ("app.py", 4, Some(1), "warning", Some("E302")),
+                ("src/x.ts", 2, Some(7), "error", None),
+            ]
+        );
+        assert_eq!(issues[1].message, "expected 2 blank lines, found 1");
+    }
+
+    /// Runs the full pipeline on a fixture with known issues; the linter is
+    /// stood in by a script printing ruff's JSON report for it.
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_lint_path_fixture() {
+        let root = lint_fixture_dir("fixture");
+        std::fs::create_dir_all(root.join("pkg")).unwrap();
+        let fixture = root.join("pkg/bad.py");
+        std::fs::write(&fixture, "import os\ndef f( x ):\n    return 1\n").unwrap();
+        let canonical = std::fs::canonicalize(&fixture).unwrap();
+        let report = serde_json::json!([
+            {"code": "F401", "filename": canonical, "location": {"row": 1, "column": 8},
+             "message": "`os` imported but unused"},
+            {"code": null, "filename": canonical, "location": {"row": 2, "column": 7},
+             "message": "SyntaxError: unexpected whitespace"},
+        ]);
+        std::fs::write(root.join("pkg/bad.py.report.json"), report.to_string()).unwrap();
+        let overrides = HashMap::from([(
+            "ruff".to_string(),
+            LinterCommand { command: "sh".into(), args: vec!["-c".into(), "cat \"$0.report.json\"".into()] },
+        )]);
+
+        let result = lint_path(&root, Path::new("pkg"), None, &overrides).await.unwrap();
+        assert_eq!(result.file_count, 1);
+        let issues: Vec<(&str, u32, &str, Option<&str>)> = result
+            .issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.line, i.severity.as_str(), i.rule.as_deref()))
+            .collect();
+        assert_eq!(issues, vec![("pkg/bad.py", 1, "warning", Some("F401")), ("pkg/bad.py", 2, "error", None)]);
+
+        assert!(lint_path(&root, Path::new("../"), None, &overrides).await.unwrap_err().contains("traversal"));
+        assert!(lint_path(&root, Path::new("pkg"), Some("pylint"), &overrides).await.unwrap_err().contains("Unknown"));
+        let missing = HashMap::from([(
+            "ruff".to_string(),
+            LinterCommand { command: "helix-no-such-linter".into(), args: vec![] },
+        )]);
+        assert!(lint_path(&root, Path::new("pkg"), None, &missing).await.unwrap_err().contains("not found"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
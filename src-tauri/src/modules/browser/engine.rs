@@ -5,13 +5,38 @@ use futures::StreamExt;
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::info;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{info, warn};
 
 lazy_static::lazy_static! {
     static ref GLOBAL_BROWSER: Arc<Mutex<Option<BrowserSession>>> = Arc::new(Mutex::new(None));
+    /// At most `MAX_CONCURRENT_RENDERS` headless renders at a time
+    static ref RENDER_SLOTS: Semaphore = Semaphore::new(MAX_CONCURRENT_RENDERS);
 }
 
+const MAX_CONCURRENT_RENDERS: usize = 2;
+/// Watchdog: a render (launch → extract → screenshot) is killed after this
+const RENDER_WATCHDOG: Duration = Duration::from_secs(60);
+/// Time allowed for navigation + waiting on network idle / the selector
+const RENDER_NAV_TIMEOUT: Duration = Duration::from_secs(30);
+/// Pages whose serialized DOM exceeds this are rejected
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Extracted text returned to the agent is cut at this many bytes
+const MAX_RENDER_TEXT: usize = 15_000;
+
+/// Hosts never rendered: loopback, link-local / cloud metadata, private ranges.
+const DEFAULT_DENYLIST: &[&str] = &[
+    "localhost",
+    "127.0.0.1",
+    "0.0.0.0",
+    "::1",
+    "169.254.169.254",
+    "metadata.google.internal",
+    "*.local",
+    "*.internal",
+];
+
 /// Stores the current active page and the mapping of short IDs to backendNodeIds
 pub struct BrowserSession {
     browser: Browser,
@@ -33,12 +58,15 @@ impl BrowserSession {
         let home = std::env::var("HOME").unwrap_or_default();
         let user_data_dir = format!("{}/Library/Application Support/Google/Chrome", home);
         
-        let config = BrowserConfig::builder()
+        let mut builder = BrowserConfig::builder()
             .chrome_executable("/Applications/Google Chrome.app/Contents/MacOS/Google Chrome")
-            .with_head() // Show window for now so user can see it "挂机"
-            .user_data_dir(user_data_dir)
-            .build()
-            .map_err(|e| format!("BrowserConfig Error: {}", e))?;
+            .user_data_dir(user_data_dir);
+        if has_display() {
+            builder = builder.with_head(); // Show window for now so user can see it "挂机"
+        } else {
+            warn!("No X11 display available (pure Wayland session); launching Chromium headless");
+        }
+        let config = builder.build().map_err(|e| format!("BrowserConfig Error: {}", e))?;
 
         let (browser, mut handler) = Browser::launch(config)
             .await
@@ -199,4 +227,366 @@ impl BrowserSession {
 
         Ok(format!("Filled text into {}", ref_id))
     }
+
+    pub async fn is_active() -> bool {
+        GLOBAL_BROWSER.lock().await.is_some()
+    }
+
+    /// Capture the visible part of the active page over CDP. Goes through the
+    /// browser, not the display server, so it also works on Wayland.
+    pub async fn screenshot(path: &std::path::Path) -> Result<(), String> {
+        let global = GLOBAL_BROWSER.lock().await;
+        let session = global.as_ref().ok_or_else(|| "Browser not launched.".to_string())?;
+
+        let params = chromiumoxide::page::ScreenshotParams::builder()
+            .format(chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat::Png)
+            .build();
+        session.active_page.save_screenshot(params, path)
+            .await
+            .map_err(|e| format!("Screenshot Error: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Whether a headed Chromium can open a window. Chromium talks X11 on Linux,
+/// so a Wayland session without XWayland has to run it headless.
+fn has_display() -> bool {
+    if !cfg!(target_os = "linux") {
+        return true;
+    }
+    std::env::var("DISPLAY").map(|v| !v.trim().is_empty()).unwrap_or(false)
+}
+
+// ============================================================================
+// Headless rendering (browser_render tool)
+// ============================================================================
+
+/// Why a render failed. Serialized as `{"error": kind, "message": ...}` for the agent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderError {
+    /// URL is malformed, not http(s), or its host is denylisted
+    Blocked(String),
+    /// Navigation / wait took longer than allowed, or the watchdog fired
+    Timeout(String),
+    /// DOM larger than `MAX_PAGE_BYTES`
+    TooLarge(usize),
+    /// Chromium could not be started
+    Launch(String),
+    /// The renderer crashed or CDP failed mid-render
+    Renderer(String),
+}
+
+impl RenderError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RenderError::Blocked(_) => "blocked",
+            RenderError::Timeout(_) => "timeout",
+            RenderError::TooLarge(_) => "page_too_large",
+            RenderError::Launch(_) => "launch_failed",
+            RenderError::Renderer(_) => "renderer_error",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RenderError::Blocked(m) | RenderError::Timeout(m) | RenderError::Launch(m) | RenderError::Renderer(m) => {
+                m.clone()
+            }
+            RenderError::TooLarge(size) => {
+                format!("Page is {} bytes, limit is {} bytes", size, MAX_PAGE_BYTES)
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "error": self.kind(), "message": self.message() })
+    }
+}
+
+pub struct RenderOptions {
+    pub url: String,
+    /// CSS selector to wait for instead of network idle
+    pub wait_selector: Option<String>,
+    /// Write a full-page PNG here
+    pub screenshot_path: Option<std::path::PathBuf>,
+    /// Extra denylisted hosts (exact or `*.suffix`)
+    pub denylist: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedPage {
+    pub final_url: String,
+    pub title: String,
+    pub text: String,
+    pub truncated: bool,
+    pub screenshot_path: Option<String>,
+    pub elapsed_ms: u128,
 }
+
+fn host_matches(host: &str, pattern: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('.').to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host == suffix || host.ends_with(&format!(".{}", suffix)),
+        None => !pattern.is_empty() && host == pattern,
+    }
+}
+
+/// Validate a render target: http(s) only, no denylisted or private-network hosts.
+pub fn check_render_url(url: &str, denylist: &[String]) -> Result<reqwest::Url, RenderError> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| RenderError::Blocked(format!("Invalid URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(RenderError::Blocked(format!("Only http(s) URLs can be rendered, got '{}'", parsed.scheme())));
+    }
+    let host = match parsed.host() {
+        Some(url::Host::Domain(d)) => d.trim_end_matches('.').to_lowercase(),
+        Some(url::Host::Ipv4(ip)) => {
+            if ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() {
+                return Err(RenderError::Blocked(format!("Private address {} is not allowed", ip)));
+            }
+            ip.to_string()
+        }
+        Some(url::Host::Ipv6(ip)) => {
+            let first = ip.segments()[0];
+            // loopback, unique-local fc00::/7, link-local fe80::/10
+            if ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80 {
+                return Err(RenderError::Blocked(format!("Private address {} is not allowed", ip)));
+            }
+            ip.to_string()
+        }
+        None => return Err(RenderError::Blocked(format!("URL '{}' has no host", url))),
+    };
+    let denied = DEFAULT_DENYLIST
+        .iter()
+        .copied()
+        .chain(denylist.iter().map(String::as_str))
+        .find(|pattern| host_matches(&host, pattern));
+    if let Some(pattern) = denied {
+        return Err(RenderError::Blocked(format!("Host '{}' is denylisted ({})", host, pattern)));
+    }
+    Ok(parsed)
+}
+
+/// Collapse the whitespace runs `innerText` leaves behind: trim lines, keep at most one blank line.
+pub fn normalize_extracted_text(raw: &str) -> String {
+    let mut out = String::new();
+    let mut blank = false;
+    for line in raw.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+/// Readability-style extraction: drop chrome (nav, scripts, ads…), then take the
+/// `article`/`main` element or the block with the most paragraph text.
+const READABILITY_JS: &str = r#"(() => {
+  const doc = document.cloneNode(true);
+  doc.querySelectorAll('script,style,noscript,template,svg,iframe,nav,header,footer,aside,form,[role=navigation],[role=banner],[role=contentinfo],[aria-hidden=true]')
+    .forEach(el => el.remove());
+  let best = doc.querySelector('article') || doc.querySelector('main') || doc.querySelector('[role=main]');
+  if (!best) {
+    let bestScore = 0;
+    doc.querySelectorAll('div,section').forEach(el => {
+      const score = Array.from(el.querySelectorAll(':scope > p')).reduce((n, p) => n + p.textContent.length, 0);
+      if (score > bestScore) { bestScore = score; best = el; }
+    });
+  }
+  const root = best || doc.body || doc.documentElement;
+  const text = root.innerText !== undefined && root.isConnected ? root.innerText : root.textContent;
+  return { title: document.title || '', text: text || '' };
+})()"#;
+
+/// Resolves once `document.readyState` is complete and no new resources have
+/// loaded for 500ms (a cheap stand-in for CDP network idle).
+const NETWORK_IDLE_JS: &str = r#"new Promise(resolve => {
+  let last = -1, stable = 0;
+  const tick = () => {
+    const n = performance.getEntriesByType('resource').length;
+    if (document.readyState === 'complete' && n === last) { stable += 1; } else { stable = 0; }
+    last = n;
+    if (stable >= 5) { resolve(true); } else { setTimeout(tick, 100); }
+  };
+  tick();
+})"#;
+
+/// Headless browser owned by one render; killed in `shutdown` whatever the outcome.
+struct RenderBrowser {
+    browser: Browser,
+    handler: tokio::task::JoinHandle<()>,
+    profile_dir: std::path::PathBuf,
+}
+
+impl RenderBrowser {
+    async fn shutdown(mut self) {
+        if tokio::time::timeout(Duration::from_secs(5), self.browser.close()).await.is_err() {
+            warn!("Render browser did not close in time, killing it");
+        }
+        if let Some(Err(e)) = self.browser.kill().await {
+            warn!("Failed to kill render browser: {}", e);
+        }
+        let _ = self.browser.wait().await;
+        self.handler.abort();
+        let _ = std::fs::remove_dir_all(&self.profile_dir);
+    }
+}
+
+fn render_browser_config(profile_dir: &std::path::Path) -> Result<BrowserConfig, RenderError> {
+    let mut builder = BrowserConfig::builder()
+        .user_data_dir(profile_dir)
+        .request_timeout(RENDER_NAV_TIMEOUT)
+        .window_size(1280, 900)
+        .arg("--disable-extensions")
+        .arg("--mute-audio");
+    // Prefer an explicit path, then the macOS Chrome install used by BrowserSession,
+    // otherwise let chromiumoxide find Chrome/Chromium on PATH.
+    let mac_chrome = "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome";
+    if let Ok(path) = std::env::var("CHROME_PATH") {
+        builder = builder.chrome_executable(path);
+    } else if std::path::Path::new(mac_chrome).exists() {
+        builder = builder.chrome_executable(mac_chrome);
+    }
+    builder.build().map_err(RenderError::Launch)
+}
+
+async fn launch_render_browser() -> Result<RenderBrowser, RenderError> {
+    // Fresh profile per render: no cookies from the user's Chrome, nothing left behind
+    let profile_dir = std::env::temp_dir().join(format!("helix-render-{}", uuid::Uuid::new_v4()));
+    let config = render_browser_config(&profile_dir)?;
+    let (browser, mut handler) = Browser::launch(config).await.map_err(|e| {
+        let _ = std::fs::remove_dir_all(&profile_dir);
+        RenderError::Launch(format!("Browser Launch Error: {}", e))
+    })?;
+    let handler = tokio::task::spawn(async move {
+        while let Some(h) = handler.next().await {
+            if h.is_err() {
+                break;
+            }
+        }
+    });
+    Ok(RenderBrowser { browser, handler, profile_dir })
+}
+
+/// Navigate and wait for the selector (or network idle) within `RENDER_NAV_TIMEOUT`.
+async fn navigate(page: &Page, url: &str, wait_selector: Option<&str>) -> Result<(), RenderError> {
+    let work = async {
+        page.goto(url)
+            .await
+            .map_err(|e| RenderError::Renderer(format!("Goto Error: {}", e)))?;
+        match wait_selector {
+            Some(selector) => loop {
+                if page.find_element(selector).await.is_ok() {
+                    break Ok(());
+                }
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            },
+            None => page
+                .evaluate(NETWORK_IDLE_JS)
+                .await
+                .map(|_| ())
+                .map_err(|e| RenderError::Renderer(format!("Wait Error: {}", e))),
+        }
+    };
+    tokio::time::timeout(RENDER_NAV_TIMEOUT, work).await.unwrap_or_else(|_| {
+        Err(RenderError::Timeout(match wait_selector {
+            Some(s) => format!("Selector '{}' did not appear within {}s", s, RENDER_NAV_TIMEOUT.as_secs()),
+            None => format!("Page did not settle within {}s", RENDER_NAV_TIMEOUT.as_secs()),
+        }))
+    })
+}
+
+async fn render_with(browser: &Browser, url: &str, opts: &RenderOptions) -> Result<RenderedPage, RenderError> {
+    let start = Instant::now();
+    let page = browser
+        .new_page("about:blank")
+        .await
+        .map_err(|e| RenderError::Renderer(format!("New Page Error: {}", e)))?;
+    navigate(&page, url, opts.wait_selector.as_deref()).await?;
+    // A redirect (HTTP or script) may have landed on a denylisted or private host
+    let final_url = page.url().await.ok().flatten().unwrap_or_else(|| url.to_string());
+    if final_url != url {
+        if let Err(e) = check_render_url(&final_url, &opts.denylist) {
+            let _ = page.close().await;
+            return Err(RenderError::Blocked(format!("Redirected to a blocked URL: {}", e.message())));
+        }
+    }
+
+    let size: usize = page
+        .evaluate("document.documentElement.outerHTML.length")
+        .await
+        .ok()
+        .and_then(|r| r.into_value().ok())
+        .unwrap_or(0);
+    if size > MAX_PAGE_BYTES {
+        return Err(RenderError::TooLarge(size));
+    }
+
+    let extracted: serde_json::Value = page
+        .evaluate(READABILITY_JS)
+        .await
+        .map_err(|e| RenderError::Renderer(format!("Extract Error: {}", e)))?
+        .into_value()
+        .map_err(|e| RenderError::Renderer(format!("Extract Error: {}", e)))?;
+    let text = normalize_extracted_text(extracted["text"].as_str().unwrap_or(""));
+    let truncated = text.len() > MAX_RENDER_TEXT;
+    let text = if truncated {
+        text[..text.floor_char_boundary(MAX_RENDER_TEXT)].to_string()
+    } else {
+        text
+    };
+
+    let screenshot_path = match &opts.screenshot_path {
+        Some(path) => {
+            let params = chromiumoxide::page::ScreenshotParams::builder()
+                .format(chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat::Png)
+                .full_page(true)
+                .build();
+            page.save_screenshot(params, path)
+                .await
+                .map_err(|e| RenderError::Renderer(format!("Screenshot Error: {}", e)))?;
+            Some(path.display().to_string())
+        }
+        None => None,
+    };
+
+    let _ = page.close().await;
+    Ok(RenderedPage {
+        final_url,
+        title: extracted["title"].as_str().unwrap_or("").trim().to_string(),
+        text,
+        truncated,
+        screenshot_path,
+        elapsed_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Render a (possibly JS-heavy) page in a throwaway headless Chromium and
+/// extract its readable text. At most two renders run at once; a watchdog
+/// kills the browser after `RENDER_WATCHDOG` so no process outlives the call.
+pub async fn render_page(opts: RenderOptions) -> Result<RenderedPage, RenderError> {
+    let url = check_render_url(&opts.url, &opts.denylist)?;
+    let _slot = RENDER_SLOTS
+        .acquire()
+        .await
+        .map_err(|e| RenderError::Renderer(format!("Render queue closed: {}", e)))?;
+
+    let render = launch_render_browser().await?;
+    info!("Rendering {} (headless)", url);
+    let result = tokio::time::timeout(RENDER_WATCHDOG, render_with(&render.browser, url.as_str(), &opts))
+        .await
+        .unwrap_or_else(|_| {
+            warn!("Render watchdog fired for {}", url);
+            Err(RenderError::Timeout(format!("Render exceeded {}s and was killed", RENDER_WATCHDOG.as_secs())))
+        });
+    render.shutdown().await;
+    result
+}
+
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use super::engine::*;
+    use crate::modules::browser::engine::*;
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_browser_automation() {
@@ -55,4 +55,38 @@ mod tests {
         
         println!("=== Test Complete ===");
     }
+
+    #[test]
+    fn test_check_render_url() {
+        assert!(check_render_url("https://example.com/a?b=1", &[]).is_ok());
+        for blocked in [
+            "file:///etc/passwd",
+            "http://localhost:8080",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/",
+            "http://printer.local/",
+            "not a url",
+        ] {
+            assert_eq!(
+                check_render_url(blocked, &[]).unwrap_err().kind(),
+                "blocked",
+                "{} should be blocked",
+                blocked
+            );
+        }
+        let deny = vec!["*.tracker.example".to_string(), "ads.example.com".to_string()];
+        assert!(check_render_url("https://x.tracker.example/", &deny).is_err());
+        assert!(check_render_url("https://tracker.example/", &deny).is_err());
+        assert!(check_render_url("https://ADS.example.com./", &deny).is_err());
+        assert!(check_render_url("https://example.com/", &deny).is_ok());
+    }
+
+    #[test]
+    fn test_normalize_extracted_text() {
+        let raw = "  Title \n\n\n\n   Some   spaced\ttext \n\n\nEnd  \n\n";
+        assert_eq!(normalize_extracted_text(raw), "Title\n\nSome spaced text\n\nEnd");
+        assert_eq!(normalize_extracted_text("\n \n"), "");
+    }
 }
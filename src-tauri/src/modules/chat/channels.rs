@@ -3,9 +3,15 @@
 //! Unified channel registry, message routing, and session management.
 //! Supports: DingTalk, Telegram, Discord, QQ, iMessage, Feishu.
 
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::info;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
 // ============================================================================
 // Channel Types
@@ -79,6 +85,29 @@ pub struct OutboundMessage {
     pub reply_to: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub per_minute: u32,
+    /// Sends still allowed in the current one-minute window
+    pub remaining: u32,
+}
+
+/// Status of a channel as returned by `channels_resolve`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelInfo {
+    pub id: String,
+    pub name: String,
+    /// Transport: "webhook", "api", "smtp", "web", ...
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub configured: bool,
+    pub enabled: bool,
+    pub last_message_at: Option<String>,
+    /// Messages sent and received since local midnight
+    pub message_count_today: u64,
+    pub rate_limit: Option<RateLimit>,
+}
+
 // ============================================================================
 // Channel Registry
 // ============================================================================
@@ -148,12 +177,12 @@ pub fn list_channels() -> Vec<ChannelMeta> {
         ChannelMeta {
             id: ChannelId::WeCom,
             label: "企业微信".into(),
-            description: "企业微信群机器人 Webhook".into(),
+            description: "企业微信自建应用消息 (文本 / Markdown / 文件)".into(),
             icon: "💼".into(),
             supports_auto_reply: false,
-            supports_media: false,
+            supports_media: true,
             connected: false,
-            protocol: "webhook".into(),
+            protocol: "api".into(),
         },
     ]
 }
@@ -176,10 +205,182 @@ pub fn get_channel_meta(id: &ChannelId) -> Option<ChannelMeta> {
     list_channels().into_iter().find(|c| c.id == *id)
 }
 
+/// Channel key for `raw`, including the notification-only `smtp`.
+pub fn resolve_channel_key(raw: &str) -> Option<String> {
+    match raw.trim().to_lowercase().as_str() {
+        "smtp" | "email" | "mail" | "邮件" => Some("smtp".into()),
+        _ => resolve_channel_id(raw).map(|id| id.to_string()),
+    }
+}
+
+// ============================================================================
+// Channel Activity
+// ============================================================================
+
+/// In-memory message counters, updated by each channel's receive and send paths.
+#[derive(Debug, Default)]
+struct ChannelActivity {
+    last_message_at: Option<DateTime<Utc>>,
+    day: Option<NaiveDate>,
+    count_today: u64,
+    /// Send times within the last minute, oldest first
+    recent_sends: VecDeque<Instant>,
+}
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+impl ChannelActivity {
+    fn record(&mut self, at: DateTime<Utc>, today: NaiveDate) {
+        if self.day != Some(today) {
+            self.day = Some(today);
+            self.count_today = 0;
+        }
+        self.count_today += 1;
+        self.last_message_at = Some(at);
+    }
+
+    fn record_send(&mut self, now: Instant) {
+        self.prune(now);
+        self.recent_sends.push_back(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self.recent_sends.front().is_some_and(|t| now.saturating_duration_since(*t) >= RATE_WINDOW) {
+            self.recent_sends.pop_front();
+        }
+    }
+
+    fn count_on(&self, today: NaiveDate) -> u64 {
+        if self.day == Some(today) { self.count_today } else { 0 }
+    }
+}
+
+static ACTIVITY: Lazy<Mutex<HashMap<String, ChannelActivity>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Count a message received on `channel`.
+pub fn record_inbound(channel: &str) {
+    let mut activity = ACTIVITY.lock();
+    activity.entry(channel.to_string()).or_default().record(Utc::now(), Local::now().date_naive());
+}
+
+/// Count a message sent on `channel`; also feeds its rate limit window.
+pub fn record_outbound(channel: &str) {
+    let mut activity = ACTIVITY.lock();
+    let entry = activity.entry(channel.to_string()).or_default();
+    entry.record(Utc::now(), Local::now().date_naive());
+    entry.record_send(Instant::now());
+}
+
+/// Documented per-bot send limits.
+fn rate_limit_per_minute(channel: &str) -> Option<u32> {
+    match channel {
+        "feishu" => Some(100),
+        "dingtalk" | "wecom" => Some(20),
+        "telegram" | "discord" => Some(30),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Channel Status
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ChannelProbe {
+    configured: bool,
+    enabled: bool,
+}
+
+impl ChannelProbe {
+    fn from_configured(configured: bool) -> Self {
+        Self { configured, enabled: configured }
+    }
+}
+
+fn env_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| !v.trim().is_empty())
+}
+
+/// Read the channel's config from its state/config files and environment.
+fn probe_channel(key: &str) -> ChannelProbe {
+    match key {
+        "feishu" | "dingtalk" => ChannelProbe::from_configured(
+            crate::modules::notifications::is_channel_configured(key)
+                || (key == "feishu" && env_set("FEISHU_WEBHOOK_URL")),
+        ),
+        "smtp" => ChannelProbe::from_configured(crate::modules::smtp::is_configured()),
+        "wecom" => {
+            let app = crate::modules::wecom::load_config().ok();
+            let webhook = env_set("WECOM_WEBHOOK_URL");
+            ChannelProbe {
+                configured: app.is_some() || webhook,
+                enabled: app.is_some_and(|c| c.enabled) || webhook,
+            }
+        }
+        "telegram" => ChannelProbe { configured: env_set("TELEGRAM_BOT_TOKEN"), enabled: false },
+        "discord" => ChannelProbe::from_configured(env_set("DISCORD_BOT_TOKEN")),
+        "qq" => ChannelProbe::from_configured(env_set("QQ_ONEBOT_URL")),
+        "imessage" => ChannelProbe::from_configured(cfg!(target_os = "macos")),
+        _ => ChannelProbe::default(),
+    }
+}
+
+/// Display name and transport of a channel key.
+fn channel_labels(key: &str) -> (String, String) {
+    match key {
+        "smtp" => ("邮件 (SMTP)".into(), "smtp".into()),
+        _ => resolve_channel_id(key)
+            .and_then(|id| get_channel_meta(&id))
+            .map(|meta| (meta.label, meta.protocol))
+            .unwrap_or_else(|| (key.to_string(), "custom".into())),
+    }
+}
+
+fn build_channel_info(key: &str, probe: ChannelProbe, activity: Option<&mut ChannelActivity>) -> ChannelInfo {
+    let (name, type_) = channel_labels(key);
+    let today = Local::now().date_naive();
+    let (last_message_at, message_count_today, sent_last_minute) = match activity {
+        Some(a) => {
+            a.prune(Instant::now());
+            (a.last_message_at.map(|t| t.to_rfc3339()), a.count_on(today), a.recent_sends.len() as u32)
+        }
+        None => (None, 0, 0),
+    };
+    ChannelInfo {
+        id: key.to_string(),
+        name,
+        type_,
+        configured: probe.configured,
+        enabled: probe.enabled,
+        last_message_at,
+        message_count_today,
+        rate_limit: rate_limit_per_minute(key)
+            .map(|per_minute| RateLimit { per_minute, remaining: per_minute.saturating_sub(sent_last_minute) }),
+    }
+}
+
+pub fn channel_info(key: &str) -> ChannelInfo {
+    let probe = probe_channel(key);
+    build_channel_info(key, probe, ACTIVITY.lock().get_mut(key))
+}
+
 // ============================================================================
 // Message Router
 // ============================================================================
 
+/// Channels download received files here; only files inside it are attached
+/// to an agent turn, so a sender cannot point `media_url` at local files.
+pub fn inbound_media_dir() -> Result<PathBuf, String> {
+    Ok(crate::modules::config::get_data_dir()?.join("media").join("inbound"))
+}
+
+/// The canonical path of `path` if it is a file inside `media_dir`.
+fn downloaded_media_path(path: &str, media_dir: &Path) -> Option<PathBuf> {
+    let file = std::fs::canonicalize(path).ok()?;
+    let dir = std::fs::canonicalize(media_dir).ok()?;
+    (file.starts_with(&dir) && file.is_file()).then_some(file)
+}
+
 pub async fn route_inbound_message(msg: &InboundMessage) -> Result<String, String> {
     info!(
         "[{}] Inbound from {}: '{}'",
@@ -187,6 +388,29 @@ pub async fn route_inbound_message(msg: &InboundMessage) -> Result<String, Strin
         msg.sender,
         &msg.content[..msg.content.len().min(50)]
     );
+    record_inbound(&msg.channel.to_string());
+    crate::modules::desktop_notify::notify_inbound(
+        &msg.channel.to_string(),
+        &msg.session_key,
+        &msg.sender,
+        &msg.content,
+        false,
+    );
+
+    // A received file is attached to the session's next message, e.g. a PDF and then a question about it
+    if msg.msg_type == "file" {
+        if let Some(url) = msg.media_url.as_deref() {
+            let media_dir = inbound_media_dir()?;
+            match downloaded_media_path(url, &media_dir) {
+                Some(path) => {
+                    crate::modules::agent::attachments::queue_pending(&msg.session_key, &path.to_string_lossy());
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+                    return Ok(format!("📎 已收到文件「{}」，请在下一条消息中提问", name));
+                }
+                None => warn!("[{}] Ignoring file outside {}: {}", msg.channel, media_dir.display(), url),
+            }
+        }
+    }
 
     let reply = crate::modules::agent::agent_process_message(&msg.session_key, &msg.content, None).await?;
 
@@ -200,6 +424,15 @@ pub async fn route_inbound_message(msg: &InboundMessage) -> Result<String, Strin
 }
 
 pub async fn dispatch_outbound_message(msg: &OutboundMessage) -> Result<(), String> {
+    let result = send_outbound(msg).await;
+    // DingTalk goes through `notifications::send_dingtalk`, which counts itself
+    if result.is_ok() && msg.channel != ChannelId::DingTalk {
+        record_outbound(&msg.channel.to_string());
+    }
+    result
+}
+
+async fn send_outbound(msg: &OutboundMessage) -> Result<(), String> {
     match &msg.channel {
         ChannelId::DingTalk => {
             let config = crate::modules::config::load_app_config().map_err(|e| e.to_string())?;
@@ -364,7 +597,81 @@ pub async fn channels_send(channel: String, session_key: String, content: String
     .await
 }
 
+/// Resolve a channel name or alias and report its config, activity and rate limit.
 #[tauri::command]
-pub async fn channels_resolve(raw: String) -> Result<Option<String>, String> {
-    Ok(resolve_channel_id(&raw).map(|id| id.to_string()))
+pub async fn channels_resolve(raw: String) -> Result<Option<ChannelInfo>, String> {
+    Ok(resolve_channel_key(&raw).map(|key| channel_info(&key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_channel_key() {
+        assert_eq!(resolve_channel_key(" Lark ").as_deref(), Some("feishu"));
+        assert_eq!(resolve_channel_key("email").as_deref(), Some("smtp"));
+        assert_eq!(resolve_channel_key("企微").as_deref(), Some("wecom"));
+        assert_eq!(resolve_channel_key("fax"), None);
+    }
+
+    #[test]
+    fn test_unconfigured_channel_info() {
+        let info = build_channel_info("dingtalk", ChannelProbe::default(), None);
+        assert!(!info.configured && !info.enabled);
+        assert_eq!((info.name.as_str(), info.type_.as_str()), ("钉钉", "webhook"));
+        assert_eq!((info.last_message_at, info.message_count_today), (None, 0));
+        assert_eq!(info.rate_limit, Some(RateLimit { per_minute: 20, remaining: 20 }));
+        assert_eq!(build_channel_info("smtp", ChannelProbe::default(), None).rate_limit, None);
+    }
+
+    #[tokio::test]
+    async fn test_wechat_not_resolved() {
+        // No WeChat client in this tree, so it must not be advertised with a rate limit
+        assert_eq!(resolve_channel_key("wechat"), None);
+        assert_eq!(resolve_channel_key("微信"), None);
+        assert_eq!(rate_limit_per_minute("wechat"), None);
+        assert!(channels_resolve("wechat".into()).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_activity_counts_and_rate_window() {
+        let now = Instant::now();
+        let today = Local::now().date_naive();
+        let mut activity = ChannelActivity::default();
+        activity.record(Utc::now(), today.pred_opt().unwrap());
+        activity.record(Utc::now(), today);
+        activity.record_send(now);
+        activity.record_send(now);
+        assert_eq!(activity.count_on(today), 1);
+
+        let info = build_channel_info("wecom", ChannelProbe::from_configured(true), Some(&mut activity));
+        assert!(info.configured && info.enabled && info.last_message_at.is_some());
+        assert_eq!(info.rate_limit, Some(RateLimit { per_minute: 20, remaining: 18 }));
+
+        activity.prune(now + RATE_WINDOW);
+        assert!(activity.recent_sends.is_empty());
+    }
+
+    #[test]
+    fn test_downloaded_media_path() {
+        let root = std::env::temp_dir().join(format!("helix-media-{}", std::process::id()));
+        let media_dir = root.join("inbound");
+        std::fs::create_dir_all(&media_dir).unwrap();
+        std::fs::write(media_dir.join("report.pdf"), b"%PDF").unwrap();
+        std::fs::write(root.join("id_rsa"), b"secret").unwrap();
+
+        let inside = media_dir.join("report.pdf");
+        assert_eq!(downloaded_media_path(&inside.to_string_lossy(), &media_dir), Some(inside.canonicalize().unwrap()));
+        let escape = media_dir.join("..").join("id_rsa");
+        assert_eq!(downloaded_media_path(&escape.to_string_lossy(), &media_dir), None);
+        assert_eq!(downloaded_media_path(&root.join("id_rsa").to_string_lossy(), &media_dir), None);
+        assert_eq!(downloaded_media_path(&media_dir.to_string_lossy(), &media_dir), None);
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("id_rsa"), media_dir.join("link.pdf")).unwrap();
+            assert_eq!(downloaded_media_path(&media_dir.join("link.pdf").to_string_lossy(), &media_dir), None);
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
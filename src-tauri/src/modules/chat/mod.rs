@@ -1,3 +1,6 @@
 pub mod channels;
 pub mod sessions;
+pub mod session_export;
 pub mod messaging;
+pub mod smtp;
+pub mod wecom;
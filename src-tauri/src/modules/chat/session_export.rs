@@ -0,0 +1,284 @@
+//! Session Export — render a session's conversation into a single
+//! self-contained HTML file (inline CSS, no scripts) for sharing.
+//!
+//! All message content is HTML-escaped before the small markdown subset
+//! (headings, lists, quotes, code, bold/italic, http(s) links) is applied,
+//! so nothing in a message can inject markup into the exported page.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::modules::database::{self, ConversationEntry};
+
+/// Upper bound on exported messages (conversation_history is compacted well below this)
+const MAX_EXPORT_MESSAGES: i64 = 10_000;
+
+static RE_BOLD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+static RE_ITALIC: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*([^*\s][^*]*)\*").unwrap());
+static RE_LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[([^\]]+)\]\((https?://[^\s)]+)\)").unwrap());
+
+const STYLE: &str = "
+body { margin: 0; background: #f5f5f7; color: #1d1d1f; font: 15px/1.6 -apple-system, BlinkMacSystemFont, 'Segoe UI', 'PingFang SC', sans-serif; }
+main { max-width: 820px; margin: 0 auto; padding: 32px 20px 64px; }
+header { border-bottom: 1px solid #d2d2d7; margin-bottom: 24px; }
+header h1 { font-size: 22px; margin: 0 0 4px; }
+header p { color: #6e6e73; font-size: 13px; margin: 0 0 16px; }
+.msg { border-radius: 12px; padding: 12px 16px; margin: 12px 0; background: #fff; box-shadow: 0 1px 2px rgba(0,0,0,.06); }
+.msg.user { background: #e8f1ff; }
+.msg.system { background: #fff8e1; font-size: 13px; }
+.meta { font-size: 12px; color: #6e6e73; margin-bottom: 6px; }
+.role { font-weight: 600; margin-right: 8px; text-transform: capitalize; }
+.content > :first-child { margin-top: 0; }
+.content > :last-child { margin-bottom: 0; }
+pre { background: #1e1e1e; color: #d4d4d4; padding: 12px; border-radius: 8px; overflow-x: auto; font-size: 13px; }
+code { font-family: 'SF Mono', Menlo, Consolas, monospace; }
+:not(pre) > code { background: rgba(0,0,0,.06); padding: 1px 4px; border-radius: 4px; }
+blockquote { border-left: 3px solid #d2d2d7; margin: 8px 0; padding-left: 12px; color: #515154; }
+details summary { cursor: pointer; color: #6e6e73; }
+footer { color: #86868b; font-size: 12px; text-align: center; margin-top: 32px; }
+@media (prefers-color-scheme: dark) {
+  body { background: #1c1c1e; color: #f5f5f7; }
+  .msg { background: #2c2c2e; }
+  .msg.user { background: #1f3a5f; }
+  .msg.system { background: #3a3220; }
+  header { border-color: #3a3a3c; }
+}
+";
+
+/// Escape text for safe inclusion in HTML element content and attributes.
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Inline markdown on already-escaped text: `code`, **bold**, *italic*, [links](https://…).
+fn render_inline(text: &str) -> String {
+    let mut out = String::new();
+    // Odd segments between backticks are code spans and get no further formatting
+    for (i, part) in escape_html(text).split('`').enumerate() {
+        if i % 2 == 1 {
+            out.push_str(&format!("<code>{}</code>", part));
+        } else {
+            let s = RE_LINK.replace_all(
+                part,
+                r#"<a href="$2" target="_blank" rel="noopener noreferrer">$1</a>"#,
+            );
+            let s = RE_BOLD.replace_all(&s, "<strong>$1</strong>");
+            let s = RE_ITALIC.replace_all(&s, "<em>$1</em>");
+            out.push_str(&s);
+        }
+    }
+    out
+}
+
+#[derive(PartialEq)]
+enum Block {
+    None,
+    Paragraph,
+    Unordered,
+    Ordered,
+    Quote,
+}
+
+fn close_block(out: &mut String, block: &mut Block) {
+    out.push_str(match block {
+        Block::None => "",
+        Block::Paragraph => "</p>\n",
+        Block::Unordered => "</ul>\n",
+        Block::Ordered => "</ol>\n",
+        Block::Quote => "</blockquote>\n",
+    });
+    *block = Block::None;
+}
+
+fn open_block(out: &mut String, block: &mut Block, next: Block) {
+    if *block == next {
+        return;
+    }
+    close_block(out, block);
+    out.push_str(match next {
+        Block::None => "",
+        Block::Paragraph => "<p>",
+        Block::Unordered => "<ul>\n",
+        Block::Ordered => "<ol>\n",
+        Block::Quote => "<blockquote>",
+    });
+    *block = next;
+}
+
+/// Render the markdown subset used in chat replies to sanitized HTML.
+pub fn render_markdown(src: &str) -> String {
+    let mut out = String::new();
+    let mut block = Block::None;
+    let mut code: Option<Vec<&str>> = None;
+    let mut code_lang = String::new();
+
+    for line in src.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some(fence) = trimmed.strip_prefix("```") {
+            match code.take() {
+                Some(lines) => {
+                    let class = if code_lang.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" class=\"language-{}\"", escape_html(&code_lang))
+                    };
+                    out.push_str(&format!("<pre><code{}>{}</code></pre>\n", class, escape_html(&lines.join("\n"))));
+                }
+                None => {
+                    close_block(&mut out, &mut block);
+                    code_lang = fence.trim().to_string();
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+        if let Some(lines) = code.as_mut() {
+            lines.push(line);
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            close_block(&mut out, &mut block);
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            close_block(&mut out, &mut block);
+            out.push_str(&format!("<h{0}>{1}</h{0}>\n", level, render_inline(trimmed[level..].trim())));
+        } else if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            open_block(&mut out, &mut block, Block::Unordered);
+            out.push_str(&format!("<li>{}</li>\n", render_inline(item)));
+        } else if let Some(item) = trimmed
+            .split_once(". ")
+            .filter(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+            .map(|(_, rest)| rest)
+        {
+            open_block(&mut out, &mut block, Block::Ordered);
+            out.push_str(&format!("<li>{}</li>\n", render_inline(item)));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            if block == Block::Quote {
+                out.push_str("<br>");
+            }
+            open_block(&mut out, &mut block, Block::Quote);
+            out.push_str(&render_inline(quote.trim_start()));
+        } else {
+            if block == Block::Paragraph {
+                out.push_str("<br>\n");
+            }
+            open_block(&mut out, &mut block, Block::Paragraph);
+            out.push_str(&render_inline(line.trim_end()));
+        }
+    }
+
+    // Unterminated fence: still render what we have as code
+    if let Some(lines) = code {
+        out.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&lines.join("\n"))));
+    }
+    close_block(&mut out, &mut block);
+    out
+}
+
+fn render_message(entry: &ConversationEntry) -> String {
+    let role = match entry.role.as_str() {
+        "user" | "assistant" | "system" | "tool" => entry.role.as_str(),
+        _ => "other",
+    };
+    let body = if role == "tool" {
+        // Tool steps are collapsed; their output is shown verbatim
+        format!(
+            "<details><summary>Tool output</summary><pre><code>{}</code></pre></details>",
+            escape_html(&entry.content)
+        )
+    } else {
+        render_markdown(&entry.content)
+    };
+    format!(
+        "<section class=\"msg {role}\">\n<div class=\"meta\"><span class=\"role\">{label}</span><time>{time}</time></div>\n<div class=\"content\">\n{body}</div>\n</section>\n",
+        role = role,
+        label = escape_html(&entry.role),
+        time = escape_html(&entry.created_at),
+        body = body,
+    )
+}
+
+/// Build the full HTML document for a conversation.
+pub fn render_session_html(title: &str, entries: &[ConversationEntry]) -> String {
+    let messages: String = entries.iter().map(render_message).collect();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta http-equiv=\"Content-Security-Policy\" content=\"default-src 'none'; style-src 'unsafe-inline'; img-src data:\">\n\
+         <title>{title}</title>\n<style>{style}</style>\n</head>\n<body>\n<main>\n\
+         <header><h1>{title}</h1><p>{count} messages · exported {exported}</p></header>\n\
+         {messages}\
+         <footer>Exported from Helix</footer>\n</main>\n</body>\n</html>\n",
+        title = escape_html(title),
+        style = STYLE,
+        count = entries.len(),
+        exported = chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        messages = messages,
+    )
+}
+
+/// Export a session's conversation to `path` as a self-contained HTML file.
+/// Returns the written path.
+pub fn export_session_html(session_id: &str, path: &str) -> Result<String, String> {
+    let entries = database::get_conversation_history(session_id, MAX_EXPORT_MESSAGES)?;
+    if entries.is_empty() {
+        return Err(format!("Session '{}' has no messages to export", session_id));
+    }
+
+    let title = super::sessions::get_session(session_id)
+        .ok()
+        .and_then(|s| s.label)
+        .unwrap_or_else(|| session_id.to_string());
+    let html = render_session_html(&title, &entries);
+
+    let target = PathBuf::from(crate::modules::agent_tools::expand_path(path));
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("create dir: {}", e))?;
+    }
+    std::fs::write(&target, html).map_err(|e| format!("write export: {}", e))?;
+
+    info!("[sessions] Exported '{}' ({} messages) to {}", session_id, entries.len(), target.display());
+    Ok(target.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown_escapes_html() {
+        let html = render_markdown("<script>alert(1)</script> **hi** [x](javascript:alert(1))");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("<strong>hi</strong>"));
+        assert!(!html.contains("href=\"javascript"));
+    }
+
+    #[test]
+    fn test_render_markdown_blocks() {
+        let html = render_markdown("# Title\n\n- a\n- `b<c>`\n\n```rust\nlet x = \"<y>\";\n```\n[docs](https://example.com/?a=1&b=2)");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<ul>\n<li>a</li>\n<li><code>b&lt;c&gt;</code></li>\n</ul>"));
+        assert!(html.contains("<pre><code class=\"language-rust\">let x = &quot;&lt;y&gt;&quot;;</code></pre>"));
+        assert!(html.contains("href=\"https://example.com/?a=1&amp;b=2\""));
+    }
+}
@@ -7,10 +7,15 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::modules::config::get_data_dir;
 
+/// Total characters of pinned context files injected per turn.
+const CONTEXT_FILES_BUDGET_CHARS: usize = 16_000;
+/// Max files pinned to one session.
+const MAX_CONTEXT_FILES: usize = 10;
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -27,6 +32,19 @@ pub struct SessionEntry {
     pub last_activity: String,
     pub message_count: i64,
     pub metadata: Option<String>,
+    /// Session this one was forked from (None for root sessions)
+    pub parent_session_key: Option<String>,
+    /// Last message copied from the parent when forking
+    pub forked_from_message_id: Option<i64>,
+}
+
+/// A sandbox file pinned into a session's prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextFile {
+    pub path: String,
+    pub added_at: String,
+    /// Current size on disk (None if the file is gone)
+    pub size: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +74,16 @@ fn open_session_db() -> Result<rusqlite::Connection, String> {
     Ok(conn)
 }
 
+const CONTEXT_FILES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS session_context_files (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_key     TEXT NOT NULL,
+        path            TEXT NOT NULL,
+        added_at        TEXT NOT NULL,
+        UNIQUE(session_key, path)
+    );
+";
+
 pub fn init_session_tables() -> Result<(), String> {
     let conn = SESSION_DB.lock();
     conn.execute_batch(
@@ -77,6 +105,13 @@ pub fn init_session_tables() -> Result<(), String> {
         ",
     )
     .map_err(|e| format!("create session tables: {}", e))?;
+
+    // Branching columns (migration for existing DBs)
+    let _ = conn.execute("ALTER TABLE sessions ADD COLUMN parent_session_key TEXT", []);
+    let _ = conn.execute("ALTER TABLE sessions ADD COLUMN forked_from_message_id INTEGER", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_session_parent ON sessions(parent_session_key)", []);
+    conn.execute_batch(CONTEXT_FILES_SCHEMA)
+        .map_err(|e| format!("create context file table: {}", e))?;
     info!("Session tables initialized");
     Ok(())
 }
@@ -118,7 +153,8 @@ pub fn upsert_session(session_key: &str, channel: &str, label: Option<&str>) ->
 pub fn get_session(session_key: &str) -> Result<SessionEntry, String> {
     let conn = SESSION_DB.lock();
     conn.query_row(
-        "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata
+        "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata,
+             parent_session_key, forked_from_message_id
          FROM sessions WHERE session_key = ?1",
         params![session_key],
         |row| {
@@ -133,6 +169,8 @@ pub fn get_session(session_key: &str) -> Result<SessionEntry, String> {
                 last_activity: row.get(7)?,
                 message_count: row.get(8)?,
                 metadata: row.get(9)?,
+                parent_session_key: row.get(10)?,
+                forked_from_message_id: row.get(11)?,
             })
         },
     )
@@ -143,13 +181,15 @@ pub fn list_sessions(channel: Option<&str>, limit: i64) -> Result<Vec<SessionEnt
     let conn = SESSION_DB.lock();
     let query = if let Some(ch) = channel {
         format!(
-            "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata
+            "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata,
+             parent_session_key, forked_from_message_id
              FROM sessions WHERE channel = '{}' ORDER BY last_activity DESC LIMIT {}",
             ch, limit
         )
     } else {
         format!(
-            "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata
+            "SELECT id, session_key, channel, label, chat_type, model_override, send_policy, last_activity, message_count, metadata,
+             parent_session_key, forked_from_message_id
              FROM sessions ORDER BY last_activity DESC LIMIT {}",
             limit
         )
@@ -169,6 +209,8 @@ pub fn list_sessions(channel: Option<&str>, limit: i64) -> Result<Vec<SessionEnt
                 last_activity: row.get(7)?,
                 message_count: row.get(8)?,
                 metadata: row.get(9)?,
+                parent_session_key: row.get(10)?,
+                forked_from_message_id: row.get(11)?,
             })
         })
         .map_err(|e| format!("map: {}", e))?
@@ -215,9 +257,51 @@ pub fn delete_session(session_key: &str) -> Result<(), String> {
     let conn = SESSION_DB.lock();
     conn.execute("DELETE FROM sessions WHERE session_key = ?1", params![session_key])
         .map_err(|e| format!("delete session: {}", e))?;
+    drop(conn);
+    crate::modules::agent::scratchpad::clear_session(session_key);
     Ok(())
 }
 
+/// Fork a session: copy its conversation up to `up_to_message_id` into a new
+/// session linked to the original via `parent_session_key`.
+pub fn fork_session(session_key: &str, up_to_message_id: i64) -> Result<SessionEntry, String> {
+    let parent = get_session(session_key)?;
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let fork_key = format!("{}:fork-{}", session_key, &suffix[..8]);
+
+    let copied = crate::modules::database::copy_conversation_until(session_key, &fork_key, up_to_message_id)?;
+
+    let label = format!("{} (fork)", parent.label.as_deref().unwrap_or(session_key));
+    let now = chrono::Utc::now().to_rfc3339();
+    {
+        let conn = SESSION_DB.lock();
+        conn.execute(
+            "INSERT INTO sessions (session_key, channel, label, chat_type, model_override, send_policy,
+                                   last_activity, message_count, parent_session_key, forked_from_message_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                fork_key,
+                parent.channel,
+                label,
+                parent.chat_type,
+                parent.model_override,
+                parent.send_policy,
+                now,
+                copied as i64,
+                session_key,
+                up_to_message_id
+            ],
+        )
+        .map_err(|e| format!("insert fork: {}", e))?;
+    }
+
+    info!(
+        "[sessions] Forked '{}' at message {} -> '{}' ({} messages)",
+        session_key, up_to_message_id, fork_key, copied
+    );
+    get_session(&fork_key)
+}
+
 /// Resolve send policy for a session: check session-level override, then defaults.
 pub fn resolve_send_policy(session_key: &str) -> String {
     match get_session(session_key) {
@@ -232,99 +316,288 @@ pub fn get_model_for_session(session_key: &str) -> Option<String> {
 }
 
 // ============================================================================
-// Conversation Compaction
+// Context Files
 // ============================================================================
 
-/// Compact conversation history by summarizing old turns.
-/// Keeps the most recent `keep_recent` messages and summarizes the rest
-/// into a single context injection at the start of history.
-pub async fn compact_session_history(
-    account_id: &str,
-    keep_recent: i64,
-) -> Result<String, String> {
-    use crate::modules::database;
-    use crate::modules::config::load_app_config;
+fn sandbox_dir() -> std::path::PathBuf {
+    std::path::PathBuf::from(crate::modules::agent::tools::get_sandbox_path())
+}
 
-    // 1. Load full history
-    let history = database::get_conversation_history(account_id, 200)?;
-    let total = history.len() as i64;
+/// `path` made absolute: as given, or relative to `sandbox`.
+fn sandbox_join(sandbox: &std::path::Path, path: &str) -> std::path::PathBuf {
+    let expanded = std::path::PathBuf::from(crate::modules::agent::tools::expand_path(path));
+    if expanded.is_absolute() { expanded } else { sandbox.join(expanded) }
+}
 
-    if total <= keep_recent {
-        return Ok(format!("No compaction needed ({} messages, threshold {})", total, keep_recent));
+/// Resolve `path` (absolute, or relative to `sandbox`) to a canonical
+/// path inside the sandbox.
+fn resolve_sandbox_file(sandbox: &std::path::Path, path: &str) -> Result<std::path::PathBuf, String> {
+    let full = sandbox_join(sandbox, path);
+    let canonical = std::fs::canonicalize(&full).map_err(|e| format!("文件不存在 '{}': {}", path, e))?;
+    let canonical_sandbox = std::fs::canonicalize(sandbox).unwrap_or_else(|_| sandbox.to_path_buf());
+    if !canonical.starts_with(&canonical_sandbox) {
+        return Err(format!("只能固定沙盒目录内的文件: {}", canonical_sandbox.display()));
+    }
+    if !canonical.is_file() {
+        return Err(format!("不是文件: {}", canonical.display()));
     }
+    Ok(canonical)
+}
 
-    // 2. Build summary of old messages
-    let old_count = (total - keep_recent) as usize;
-    let old_messages: Vec<String> = history[..old_count]
-        .iter()
-        .map(|m| format!("[{}]: {}", m.role, &m.content[..m.content.len().min(200)]))
-        .collect();
+/// Pin a sandbox file into a session's prompt.
+pub fn add_context_file(session_key: &str, path: &str) -> Result<ContextFile, String> {
+    add_context_file_in(&SESSION_DB.lock(), &sandbox_dir(), session_key, path)
+}
+
+fn add_context_file_in(
+    conn: &rusqlite::Connection,
+    sandbox: &std::path::Path,
+    session_key: &str,
+    path: &str,
+) -> Result<ContextFile, String> {
+    let resolved = resolve_sandbox_file(sandbox, path)?.to_string_lossy().to_string();
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM session_context_files WHERE session_key = ?1 AND path != ?2",
+            params![session_key, resolved],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+    if count as usize >= MAX_CONTEXT_FILES {
+        return Err(format!("每个会话最多固定 {} 个文件", MAX_CONTEXT_FILES));
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT OR IGNORE INTO session_context_files (session_key, path, added_at) VALUES (?1, ?2, ?3)",
+        params![session_key, resolved, now],
+    )
+    .map_err(|e| format!("add context file: {}", e))?;
+    info!("[sessions] Pinned '{}' to '{}'", resolved, session_key);
+    Ok(ContextFile {
+        size: std::fs::metadata(&resolved).ok().map(|m| m.len()),
+        path: resolved,
+        added_at: now,
+    })
+}
 
-    let summary_input = old_messages.join("\n");
+pub fn list_context_files(session_key: &str) -> Result<Vec<ContextFile>, String> {
+    let conn = SESSION_DB.lock();
+    let mut stmt = conn
+        .prepare("SELECT path, added_at FROM session_context_files WHERE session_key = ?1 ORDER BY id")
+        .map_err(|e| format!("prepare: {}", e))?;
+    let rows = stmt
+        .query_map(params![session_key], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("query: {}", e))?;
+    Ok(rows
+        .flatten()
+        .map(|(path, added_at)| ContextFile {
+            size: std::fs::metadata(&path).ok().map(|m| m.len()),
+            path,
+            added_at,
+        })
+        .collect())
+}
 
-    // 3. Use AI to generate a summary
-    let config = load_app_config().map_err(|e| format!("config: {}", e))?;
-    let ai = &config.ai_config;
-
-    if ai.api_key.is_empty() {
-        // Fallback: use simple text truncation
-        let summary = format!(
-            "[Compacted {} older messages. Key topics discussed: {}]",
-            old_count,
-            &summary_input[..summary_input.len().min(500)]
-        );
-        // Delete old messages and inject summary
-        database::clear_messages(account_id)?;
-        let _ = database::save_conversation_message(account_id, "system", &summary);
-        // Re-save recent messages
-        for m in &history[old_count..] {
-            let _ = database::save_conversation_message(account_id, &m.role, &m.content);
-        }
-        return Ok(format!("Compacted {} messages (fallback mode)", old_count));
+/// Unpin a file, given the same way as to `add_context_file`.
+pub fn remove_context_file(session_key: &str, path: &str) -> Result<(), String> {
+    remove_context_file_in(&SESSION_DB.lock(), &sandbox_dir(), session_key, path)
+}
+
+fn remove_context_file_in(
+    conn: &rusqlite::Connection,
+    sandbox: &std::path::Path,
+    session_key: &str,
+    path: &str,
+) -> Result<(), String> {
+    // A file deleted since it was pinned no longer canonicalizes; match its stored path
+    let resolved = resolve_sandbox_file(sandbox, path).unwrap_or_else(|_| sandbox_join(sandbox, path));
+    let removed = conn
+        .execute(
+            "DELETE FROM session_context_files WHERE session_key = ?1 AND path = ?2",
+            params![session_key, resolved.to_string_lossy()],
+        )
+        .map_err(|e| format!("remove context file: {}", e))?;
+    if removed == 0 {
+        return Err(format!("文件未固定: {}", path));
     }
+    Ok(())
+}
 
-    // Use the streaming engine for summarization
-    let provider = crate::modules::providers::resolve_provider_config(
-        &ai.model,
-        Some(&ai.base_url),
-        Some(&ai.api_key),
-        None,
+/// Render pinned files into a prompt section, splitting `budget` evenly
+/// between files (unused share of short files goes to the later ones).
+fn render_context_files(files: &[(String, Result<String, String>)], budget: usize) -> String {
+    let mut out = String::from(
+        "## Pinned Context Files\nThe user pinned these documents to this conversation; treat them as reference material.\n",
     );
+    let mut remaining = budget;
+    for (i, (path, content)) in files.iter().enumerate() {
+        let share = remaining / (files.len() - i);
+        match content {
+            Ok(text) => {
+                let truncated = crate::utils::truncate::safe_truncate(text, share);
+                remaining -= truncated.chars().count();
+                out.push_str(&format!("\n### {}\n```\n{}\n```\n", path, truncated));
+                if truncated.len() < text.len() {
+                    out.push_str(&format!("[truncated — {} of {} chars shown]\n", truncated.chars().count(), text.chars().count()));
+                }
+            }
+            Err(e) => out.push_str(&format!("\n### {}\n[unavailable: {}]\n", path, e)),
+        }
+    }
+    out.trim_end().to_string()
+}
 
-    let summarize_prompt = format!(
-        "Summarize the following conversation history into a brief context paragraph (2-3 sentences). \
-         Focus on key facts, decisions, and ongoing tasks. Be concise.\n\n{}",
-        &summary_input[..summary_input.len().min(3000)]
-    );
+/// Prompt section with the current contents of the session's pinned files.
+/// Files are re-read on every call so edits are picked up each turn.
+pub fn build_context_files_prompt(session_key: &str) -> Option<String> {
+    let files = list_context_files(session_key)
+        .map_err(|e| warn!("[sessions] context files for '{}': {}", session_key, e))
+        .ok()?;
+    if files.is_empty() {
+        return None;
+    }
+    let contents: Vec<(String, Result<String, String>)> = files
+        .into_iter()
+        .map(|f| {
+            let content = std::fs::read_to_string(&f.path).map_err(|e| e.to_string());
+            (f.path, content)
+        })
+        .collect();
+    Some(render_context_files(&contents, CONTEXT_FILES_BUDGET_CHARS))
+}
 
-    let body = crate::modules::providers::build_openai_request(
-        &ai.model,
-        &[serde_json::json!({"role": "user", "content": summarize_prompt})],
-        None,
-        300,
-        false,
-    );
+// ============================================================================
+// Conversation Compaction
+// ============================================================================
 
-    let result = crate::modules::streaming::complete_simple(&provider, &body).await?;
-    let summary = if result.content.is_empty() {
-        format!("[Compacted {} older messages]", old_count)
-    } else {
-        format!("[Context from {} earlier messages: {}]", old_count, result.content)
-    };
+/// Messages left untouched by compaction.
+pub const COMPACT_KEEP_RECENT: i64 = 5;
+
+const COMPACT_SUMMARY_PROMPT: &str =
+    "Summarize this conversation in 3-5 sentences preserving key decisions, facts, and action items";
+
+/// Per-message and total caps on the transcript sent for summarization.
+const COMPACT_MESSAGE_CHARS: usize = 2000;
+const COMPACT_TRANSCRIPT_CHARS: usize = 24_000;
+
+/// What compaction would do to a session, without applying it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactPreview {
+    pub summary: String,
+    pub total_messages: usize,
+    /// Messages that would be replaced by the summary.
+    pub summarized: usize,
+    pub kept: usize,
+}
+
+fn build_transcript(history: &[crate::modules::database::ConversationEntry]) -> String {
+    let mut transcript = String::new();
+    for m in history {
+        let content: String = m.content.chars().take(COMPACT_MESSAGE_CHARS).collect();
+        transcript.push_str(&format!("[{}]: {}\n", m.role, content));
+    }
+    // Keep the tail if over budget — recent turns matter most
+    let total = transcript.chars().count();
+    if total > COMPACT_TRANSCRIPT_CHARS {
+        transcript = transcript.chars().skip(total - COMPACT_TRANSCRIPT_CHARS).collect();
+    }
+    transcript
+}
+
+/// Summarize the whole session and report what compaction would remove.
+/// Returns `None` when the session has no more than `keep_recent` messages.
+async fn preview_compaction(account_id: &str, keep_recent: i64) -> Result<Option<CompactPreview>, String> {
+    use crate::modules::ai_chat::{chat_complete, AiMessage};
+    use crate::modules::config::load_app_config;
+    use crate::modules::database;
 
-    // 4. Replace history: delete all, inject summary + recent
-    database::clear_messages(account_id)?;
-    let _ = database::save_conversation_message(account_id, "system", &summary);
-    for m in &history[old_count..] {
-        let _ = database::save_conversation_message(account_id, &m.role, &m.content);
+    let history = database::get_conversation_history(account_id, i64::MAX)?;
+    let total = history.len();
+    let kept = total.min(keep_recent.max(0) as usize);
+    if total == kept {
+        return Ok(None);
     }
 
+    let config = load_app_config().map_err(|e| format!("config: {}", e))?;
+    let messages = vec![
+        AiMessage { role: "system".to_string(), content: COMPACT_SUMMARY_PROMPT.to_string() },
+        AiMessage { role: "user".to_string(), content: build_transcript(&history) },
+    ];
+    let response = chat_complete(&config.ai_config, messages)
+        .await
+        .map_err(|e| e.to_string())?;
+    let summary = response.content.trim().to_string();
+    if summary.is_empty() {
+        return Err("Summarization returned an empty response".to_string());
+    }
+
+    Ok(Some(CompactPreview { summary, total_messages: total, summarized: total - kept, kept }))
+}
+
+/// Compact conversation history: summarize every message with the AI, then
+/// replace all but the most recent `keep_recent` with a single system
+/// summary message at the start of history.
+pub async fn compact_session_history(
+    account_id: &str,
+    keep_recent: i64,
+) -> Result<String, String> {
+    let Some(preview) = preview_compaction(account_id, keep_recent).await? else {
+        return Ok(format!("No compaction needed (threshold {})", keep_recent));
+    };
+
+    let removed = crate::modules::database::replace_history_with_summary(account_id, &preview.summary, keep_recent)?;
+
     info!(
         "[sessions] Compacted {} old messages for '{}', kept {} recent",
-        old_count, account_id, keep_recent
+        removed, account_id, preview.kept
     );
 
-    Ok(format!("Compacted {} messages, kept {} recent", old_count, keep_recent))
+    Ok(format!("Compacted {} messages, kept {} recent", removed, preview.kept))
+}
+
+// ============================================================================
+// Context Window Preview
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextMessage {
+    pub role: String,
+    pub content: String,
+    pub token_count: u32,
+}
+
+/// The exact request the agent would send for a message, with token estimates.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextWindow {
+    pub messages: Vec<ContextMessage>,
+    pub system_prompt: Option<String>,
+    pub total_tokens: u32,
+    pub model: String,
+    pub context_limit: u32,
+    pub available_tokens: u32,
+}
+
+fn context_window(preview: crate::modules::agent::AgentContextPreview, context_limit: usize) -> ContextWindow {
+    let message = |role: &str, content: String| ContextMessage {
+        role: role.to_string(),
+        token_count: crate::modules::agent::context_manager::estimate_tokens(&content) as u32,
+        content,
+    };
+    let system_prompt = Some(preview.system_prompt).filter(|p| !p.is_empty());
+    let mut messages = Vec::new();
+    if let Some(prompt) = &system_prompt {
+        messages.push(message("system", prompt.clone()));
+    }
+    messages.push(message("user", preview.user_message));
+    let total_tokens = messages.iter().map(|m| m.token_count).sum::<u32>();
+    ContextWindow {
+        messages,
+        system_prompt,
+        total_tokens,
+        model: preview.model,
+        context_limit: context_limit as u32,
+        available_tokens: (context_limit as u32).saturating_sub(total_tokens),
+    }
 }
 
 // ============================================================================
@@ -343,6 +616,10 @@ pub async fn sessions_get(session_key: String) -> Result<SessionEntry, String> {
 
 #[tauri::command]
 pub async fn sessions_set_model(session_key: String, model: Option<String>) -> Result<(), String> {
+    if let Some(model) = &model {
+        let config = crate::modules::config::load_app_config()?;
+        crate::modules::model_selection::override_model_config(model, &config)?;
+    }
     set_model_override(&session_key, model.as_deref())
 }
 
@@ -361,8 +638,130 @@ pub async fn sessions_delete(session_key: String) -> Result<(), String> {
     delete_session(&session_key)
 }
 
+#[tauri::command]
+pub async fn sessions_fork(session_id: String, up_to_message_id: i64) -> Result<SessionEntry, String> {
+    fork_session(&session_id, up_to_message_id)
+}
+
+/// Export a session as a self-contained HTML file; returns the written path.
+#[tauri::command]
+pub async fn sessions_export_html(session_id: String, path: String) -> Result<String, String> {
+    super::session_export::export_session_html(&session_id, &path)
+}
+
+/// Pin a sandbox file into the session's prompt (re-read every turn).
+#[tauri::command]
+pub async fn sessions_add_context_file(session_id: String, path: String) -> Result<ContextFile, String> {
+    add_context_file(&session_id, &path)
+}
+
+#[tauri::command]
+pub async fn sessions_list_context_files(session_id: String) -> Result<Vec<ContextFile>, String> {
+    list_context_files(&session_id)
+}
+
+#[tauri::command]
+pub async fn sessions_remove_context_file(session_id: String, path: String) -> Result<(), String> {
+    remove_context_file(&session_id, &path)
+}
+
 #[tauri::command]
 pub async fn sessions_compact(account_id: String, keep_recent: Option<i64>) -> Result<String, String> {
-    compact_session_history(&account_id, keep_recent.unwrap_or(20)).await
+    compact_session_history(&account_id, keep_recent.unwrap_or(COMPACT_KEEP_RECENT)).await
+}
+
+/// Show what would be sent to the AI if `new_message` were sent to the session
+/// now: system prompt, history after sliding-window folding, and token counts.
+/// Without `new_message` the preview is for an empty message.
+#[tauri::command]
+pub async fn sessions_get_context_window(
+    session_id: String,
+    new_message: Option<String>,
+) -> Result<ContextWindow, String> {
+    let preview =
+        crate::modules::agent::preview_agent_context(&session_id, new_message.as_deref().unwrap_or_default(), None)
+            .await?;
+    Ok(context_window(preview, crate::modules::agent::AGENT_CONTEXT_LIMIT))
+}
+
+/// Generate the compaction summary without modifying the session.
+#[tauri::command]
+pub async fn sessions_compact_dry_run(session_id: String) -> Result<CompactPreview, String> {
+    preview_compaction(&session_id, COMPACT_KEEP_RECENT)
+        .await?
+        .ok_or_else(|| format!("No compaction needed (threshold {})", COMPACT_KEEP_RECENT))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_context_files_budget() {
+        let files = vec![
+            ("/w/short.md".to_string(), Ok("tiny".to_string())),
+            ("/w/long.md".to_string(), Ok("x".repeat(100))),
+            ("/w/gone.md".to_string(), Err("No such file".to_string())),
+        ];
+        let out = render_context_files(&files, 60);
+        assert!(out.starts_with("## Pinned Context Files"));
+        assert!(out.contains("### /w/short.md\n```\ntiny\n```"));
+        // short.md used 4 of its 20; long.md gets half of the remaining 56
+        assert!(out.contains(&format!("```\n{}\n```\n[truncated — 28 of 100 chars shown]", "x".repeat(28))));
+        assert!(out.contains("### /w/gone.md\n[unavailable: No such file]"));
+    }
+
+    #[test]
+    fn test_add_and_remove_context_file_by_relative_path() {
+        let sandbox = std::env::temp_dir().join(format!("helix-pin-test-{}", std::process::id()));
+        std::fs::create_dir_all(sandbox.join("notes")).unwrap();
+        std::fs::write(sandbox.join("notes/plan.md"), "plan").unwrap();
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(CONTEXT_FILES_SCHEMA).unwrap();
+        let count = || -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM session_context_files", [], |row| row.get(0)).unwrap()
+        };
+
+        let pinned = add_context_file_in(&conn, &sandbox, "s1", "notes/plan.md").unwrap();
+        assert!(std::path::Path::new(&pinned.path).is_absolute());
+        assert_eq!(count(), 1);
+        assert!(remove_context_file_in(&conn, &sandbox, "s1", "other.md").is_err());
+        remove_context_file_in(&conn, &sandbox, "s1", "notes/plan.md").unwrap();
+        assert_eq!(count(), 0);
+        assert!(remove_context_file_in(&conn, &sandbox, "s1", "notes/plan.md").is_err());
+
+        // A pinned file that was deleted afterwards can still be unpinned
+        add_context_file_in(&conn, &sandbox, "s1", "notes/plan.md").unwrap();
+        std::fs::remove_file(sandbox.join("notes/plan.md")).unwrap();
+        remove_context_file_in(&conn, &sandbox, "s1", &pinned.path).unwrap();
+        assert_eq!(count(), 0);
+        std::fs::remove_dir_all(&sandbox).unwrap();
+    }
+
+    #[test]
+    fn test_context_window_counts_tokens() {
+        let preview = crate::modules::agent::AgentContextPreview {
+            system_prompt: "x".repeat(300),
+            user_message: "hello".to_string(),
+            model: "gpt-4o".to_string(),
+        };
+        let window = context_window(preview, 1000);
+        let roles: Vec<&str> = window.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user"]);
+        assert_eq!(window.messages[0].token_count, 100);
+        assert_eq!(window.messages[1].token_count, 2);
+        assert_eq!(window.total_tokens, 102);
+        assert_eq!(window.available_tokens, 898);
+        assert_eq!(window.system_prompt.as_deref().map(str::len), Some(300));
+
+        let tiny = crate::modules::agent::AgentContextPreview {
+            system_prompt: String::new(),
+            user_message: "y".repeat(30),
+            model: "m".to_string(),
+        };
+        let window = context_window(tiny, 5);
+        assert_eq!(window.messages.len(), 1);
+        assert_eq!(window.system_prompt, None);
+        assert_eq!(window.available_tokens, 0);
+    }
+}
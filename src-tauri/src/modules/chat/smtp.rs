@@ -0,0 +1,275 @@
+//! SMTP email channel — send plain/HTML mail and attachments via `lettre`.
+//!
+//! Config is persisted to ~/.helix/smtp.json. Also exposed as the `smtp`
+//! channel of `notifications::send_notification`, which mails `notify_to`
+//! (falling back to `from_addr`).
+
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tracing::info;
+
+// ============================================================================
+// Config
+// ============================================================================
+
+/// Transport security mode
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection (local relays only)
+    None,
+    /// Upgrade via STARTTLS (usually port 587)
+    #[default]
+    Starttls,
+    /// Implicit TLS (usually port 465)
+    Tls,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub from_addr: String,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// Recipient for notifications; defaults to `from_addr`
+    #[serde(default)]
+    pub notify_to: Option<String>,
+}
+
+/// Path to the SMTP config file
+pub(crate) fn get_smtp_path() -> Result<std::path::PathBuf, String> {
+    let helix_dir = dirs::home_dir()
+        .ok_or_else(|| "Cannot determine home directory".to_string())?
+        .join(".helix");
+    std::fs::create_dir_all(&helix_dir)
+        .map_err(|e| format!("Failed to create dir: {}", e))?;
+    Ok(helix_dir.join("smtp.json"))
+}
+
+/// Load SMTP config (error if not configured yet)
+pub fn load_config() -> Result<SmtpConfig, String> {
+    let path = get_smtp_path()?;
+    if !path.exists() {
+        return Err("SMTP 未配置，请先调用 smtp_configure".to_string());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read smtp config: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse smtp config: {}", e))
+}
+
+fn save_config(config: &SmtpConfig) -> Result<(), String> {
+    let path = get_smtp_path()?;
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize smtp config: {}", e))?;
+    crate::modules::config::write_private_file(&path, &content)
+        .map_err(|e| format!("Failed to write smtp config: {}", e))
+}
+
+/// Whether an SMTP config has been saved.
+pub fn is_configured() -> bool {
+    load_config().is_ok()
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+fn build_transport(config: &SmtpConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let builder = match config.tls {
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+            .map_err(|e| format!("SMTP STARTTLS setup failed: {}", e))?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+            .map_err(|e| format!("SMTP TLS setup failed: {}", e))?,
+    };
+
+    let mut builder = builder.port(config.port);
+    if !config.username.is_empty() {
+        builder = builder.credentials(Credentials::new(
+            config.username.clone(),
+            config.password.clone(),
+        ));
+    }
+    Ok(builder.build())
+}
+
+/// Parse a comma/semicolon separated recipient list.
+fn parse_recipients(to: &str) -> Result<Vec<Mailbox>, String> {
+    let recipients: Vec<Mailbox> = to
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|e| format!("Invalid recipient '{}': {}", s, e)))
+        .collect::<Result<_, _>>()?;
+    if recipients.is_empty() {
+        return Err("No recipient specified".to_string());
+    }
+    Ok(recipients)
+}
+
+fn message_builder(config: &SmtpConfig, to: &str, subject: &str) -> Result<lettre::message::MessageBuilder, String> {
+    let from: Mailbox = config
+        .from_addr
+        .parse()
+        .map_err(|e| format!("Invalid from address '{}': {}", config.from_addr, e))?;
+    let mut builder = Message::builder().from(from).subject(subject);
+    for mailbox in parse_recipients(to)? {
+        builder = builder.to(mailbox);
+    }
+    Ok(builder)
+}
+
+/// Guess a MIME type from the file extension (attachments only).
+fn guess_content_type(path: &Path) -> ContentType {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let mime = match ext.as_str() {
+        "txt" | "log" | "md" => "text/plain",
+        "html" | "htm" => "text/html",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    };
+    ContentType::parse(mime).unwrap_or(ContentType::TEXT_PLAIN)
+}
+
+async fn deliver(config: &SmtpConfig, message: Message) -> Result<(), String> {
+    build_transport(config)?
+        .send(message)
+        .await
+        .map_err(|e| format!("SMTP send failed: {}", e))?;
+    super::channels::record_outbound("smtp");
+    Ok(())
+}
+
+/// Send a plain-text or HTML email. `to` may contain several addresses separated by `,`.
+pub async fn send_mail(to: &str, subject: &str, body: &str, html: bool) -> Result<(), String> {
+    let config = load_config()?;
+    let content_type = if html { ContentType::TEXT_HTML } else { ContentType::TEXT_PLAIN };
+    let message = message_builder(&config, to, subject)?
+        .header(content_type)
+        .body(body.to_string())
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    deliver(&config, message).await?;
+    info!("Email sent to {}: {}", to, subject);
+    Ok(())
+}
+
+/// Send an email with a single file attachment.
+pub async fn send_mail_with_attachment(
+    to: &str,
+    subject: &str,
+    body: &str,
+    attachment_path: &str,
+) -> Result<(), String> {
+    let config = load_config()?;
+    let path = Path::new(attachment_path);
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read attachment {}: {}", attachment_path, e))?;
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+
+    let multipart = MultiPart::mixed()
+        .singlepart(SinglePart::plain(body.to_string()))
+        .singlepart(Attachment::new(filename.clone()).body(bytes, guess_content_type(path)));
+    let message = message_builder(&config, to, subject)?
+        .multipart(multipart)
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    deliver(&config, message).await?;
+    info!("Email with attachment {} sent to {}: {}", filename, to, subject);
+    Ok(())
+}
+
+/// Notification entrypoint used by `notifications::send_notification`.
+pub async fn send_notification(title: &str, body: &str) -> Result<(), String> {
+    let config = load_config()?;
+    let to = config
+        .notify_to
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| config.from_addr.clone());
+    send_mail(&to, title, body, false).await
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn smtp_configure(config: SmtpConfig) -> Result<String, String> {
+    if config.host.trim().is_empty() {
+        return Err("SMTP host is required".to_string());
+    }
+    config
+        .from_addr
+        .parse::<Mailbox>()
+        .map_err(|e| format!("Invalid from address '{}': {}", config.from_addr, e))?;
+    save_config(&config)?;
+    info!("SMTP config saved: {}:{}", config.host, config.port);
+    Ok("SMTP 配置已保存".to_string())
+}
+
+/// Verify the connection and credentials without sending mail.
+#[tauri::command]
+pub async fn smtp_test() -> Result<String, String> {
+    let config = load_config()?;
+    let ok = build_transport(&config)?
+        .test_connection()
+        .await
+        .map_err(|e| format!("SMTP 连接失败: {}", e))?;
+    if !ok {
+        return Err(format!("SMTP 服务器 {}:{} 未响应", config.host, config.port));
+    }
+    Ok(format!("✅ SMTP 连接成功 ({}:{})", config.host, config.port))
+}
+
+#[tauri::command]
+pub async fn smtp_send(to: String, subject: String, body: String, html: bool) -> Result<(), String> {
+    send_mail(&to, &subject, &body, html).await
+}
+
+#[tauri::command]
+pub async fn smtp_send_attachment(
+    to: String,
+    subject: String,
+    body: String,
+    attachment_path: String,
+) -> Result<(), String> {
+    send_mail_with_attachment(&to, &subject, &body, &attachment_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recipients() {
+        let list = parse_recipients("a@example.com, Bob <b@example.com>; ").unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].email.to_string(), "b@example.com");
+        assert!(parse_recipients(" , ").is_err());
+        assert!(parse_recipients("not-an-address").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! WeCom (企业微信) channel — send text, markdown and files as a self-built app.
+//!
+//! Config is persisted to ~/.helix/wecom.json. The access token is cached
+//! until shortly before it expires and refreshed once if WeCom reports it
+//! invalid. Also exposed as the `wecom` channel of
+//! `notifications::send_notification`, which sends markdown to `notify_to`
+//! (falling back to `@all`).
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+const API_BASE: &str = "https://qyapi.weixin.qq.com/cgi-bin";
+/// Refresh the token this long before WeCom says it expires.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+/// errcodes meaning the access token is invalid or expired.
+const TOKEN_ERRCODES: &[i64] = &[40014, 42001];
+
+static HTTP: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+});
+
+// ============================================================================
+// Config
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WecomConfig {
+    pub corp_id: String,
+    pub corp_secret: String,
+    pub agent_id: u64,
+    #[serde(default)]
+    pub enabled: bool,
+    /// Recipients for notifications (`user1|user2`); defaults to `@all`
+    #[serde(default)]
+    pub notify_to: Option<String>,
+}
+
+/// Path to the WeCom config file
+pub(crate) fn get_wecom_path() -> Result<std::path::PathBuf, String> {
+    let helix_dir = dirs::home_dir()
+        .ok_or_else(|| "Cannot determine home directory".to_string())?
+        .join(".helix");
+    std::fs::create_dir_all(&helix_dir)
+        .map_err(|e| format!("Failed to create dir: {}", e))?;
+    Ok(helix_dir.join("wecom.json"))
+}
+
+/// Load WeCom config (error if not configured yet)
+pub fn load_config() -> Result<WecomConfig, String> {
+    let path = get_wecom_path()?;
+    if !path.exists() {
+        return Err("企业微信未配置，请先调用 wecom_configure".to_string());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read wecom config: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse wecom config: {}", e))
+}
+
+fn save_config(config: &WecomConfig) -> Result<(), String> {
+    let path = get_wecom_path()?;
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize wecom config: {}", e))?;
+    crate::modules::config::write_private_file(&path, &content)
+        .map_err(|e| format!("Failed to write wecom config: {}", e))
+}
+
+/// Load the config, failing if the channel is disabled.
+fn load_enabled_config() -> Result<WecomConfig, String> {
+    let config = load_config()?;
+    if !config.enabled {
+        return Err("企业微信通道未启用".to_string());
+    }
+    Ok(config)
+}
+
+/// Whether a WeCom config has been saved and enabled.
+pub fn is_configured() -> bool {
+    load_enabled_config().is_ok()
+}
+
+// ============================================================================
+// Access Token
+// ============================================================================
+
+struct CachedToken {
+    /// `corp_id:agent_id` the token was issued for
+    owner: String,
+    token: String,
+    refresh_at: Instant,
+}
+
+static TOKEN_CACHE: Lazy<Mutex<Option<CachedToken>>> = Lazy::new(|| Mutex::new(None));
+
+fn token_owner(config: &WecomConfig) -> String {
+    format!("{}:{}", config.corp_id, config.agent_id)
+}
+
+fn clear_token_cache() {
+    *TOKEN_CACHE.lock() = None;
+}
+
+/// Check a WeCom API response, turning a non-zero `errcode` into an error.
+fn check_errcode(data: &Value, action: &str) -> Result<(), (i64, String)> {
+    let code = data["errcode"].as_i64().unwrap_or(0);
+    if code == 0 {
+        return Ok(());
+    }
+    let msg = data["errmsg"].as_str().unwrap_or("unknown error");
+    Err((code, format!("企业微信{}失败 ({}): {}", action, code, msg)))
+}
+
+/// Get a cached access token, fetching a new one when missing or about to expire.
+async fn access_token(config: &WecomConfig) -> Result<String, String> {
+    let owner = token_owner(config);
+    let cached = TOKEN_CACHE
+        .lock()
+        .as_ref()
+        .filter(|c| c.owner == owner && Instant::now() < c.refresh_at)
+        .map(|c| c.token.clone());
+    if let Some(token) = cached {
+        return Ok(token);
+    }
+
+    let data: Value = HTTP
+        .get(format!("{}/gettoken", API_BASE))
+        .query(&[("corpid", &config.corp_id), ("corpsecret", &config.corp_secret)])
+        .send()
+        .await
+        .map_err(|e| format!("WeCom gettoken request failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("WeCom gettoken parse failed: {}", e))?;
+    check_errcode(&data, "获取 access_token").map_err(|(_, e)| e)?;
+
+    let token = data["access_token"]
+        .as_str()
+        .ok_or("WeCom gettoken returned no access_token")?
+        .to_string();
+    let expires_in = Duration::from_secs(data["expires_in"].as_u64().unwrap_or(7200));
+    *TOKEN_CACHE.lock() = Some(CachedToken {
+        owner,
+        token: token.clone(),
+        refresh_at: Instant::now() + expires_in.saturating_sub(TOKEN_REFRESH_MARGIN),
+    });
+    Ok(token)
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+/// Build the `message/send` body for one message type.
+fn message_body(config: &WecomConfig, to_user: &str, msgtype: &str, content: Value) -> Value {
+    let mut body = json!({
+        "touser": to_user,
+        "msgtype": msgtype,
+        "agentid": config.agent_id,
+    });
+    body[msgtype] = content;
+    body
+}
+
+/// POST `message/send`, retrying once with a fresh token if the cached one was rejected.
+async fn send_message(to_user: &str, msgtype: &str, content: Value) -> Result<(), String> {
+    let config = load_enabled_config()?;
+    if to_user.trim().is_empty() {
+        return Err("No recipient specified".to_string());
+    }
+    let body = message_body(&config, to_user, msgtype, content);
+
+    for attempt in 0..2 {
+        let token = access_token(&config).await?;
+        let data: Value = HTTP
+            .post(format!("{}/message/send", API_BASE))
+            .query(&[("access_token", &token)])
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("WeCom send request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("WeCom send parse failed: {}", e))?;
+        match check_errcode(&data, "发送消息") {
+            Ok(()) => {
+                super::channels::record_outbound("wecom");
+                info!("WeCom {} message sent to {}", msgtype, to_user);
+                return Ok(());
+            }
+            Err((code, _)) if attempt == 0 && TOKEN_ERRCODES.contains(&code) => clear_token_cache(),
+            Err((_, e)) => return Err(e),
+        }
+    }
+    Err("企业微信发送消息失败: access_token 无效".to_string())
+}
+
+/// Send a text message. `to_user` is a `|`-separated user id list or `@all`.
+pub async fn send_text(to_user: &str, content: &str) -> Result<(), String> {
+    send_message(to_user, "text", json!({ "content": content })).await
+}
+
+/// Send a markdown message (rendered in the WeCom client only).
+pub async fn send_markdown(to_user: &str, content: &str) -> Result<(), String> {
+    send_message(to_user, "markdown", json!({ "content": content })).await
+}
+
+/// Send a file previously uploaded with `upload_media`.
+pub async fn send_file(to_user: &str, media_id: &str) -> Result<(), String> {
+    send_message(to_user, "file", json!({ "media_id": media_id })).await
+}
+
+/// Upload a file as temporary media (valid for 3 days) and return its `media_id`.
+pub async fn upload_media(path: &str) -> Result<String, String> {
+    let config = load_enabled_config()?;
+    let file_path = Path::new(path);
+    let bytes = tokio::fs::read(file_path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let filename = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+
+    for attempt in 0..2 {
+        let token = access_token(&config).await?;
+        let form = reqwest::multipart::Form::new()
+            .part("media", reqwest::multipart::Part::bytes(bytes.clone()).file_name(filename.clone()));
+        let data: Value = HTTP
+            .post(format!("{}/media/upload", API_BASE))
+            .query(&[("access_token", token.as_str()), ("type", "file")])
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("WeCom upload request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("WeCom upload parse failed: {}", e))?;
+        match check_errcode(&data, "上传文件") {
+            Ok(()) => {
+                let media_id = data["media_id"]
+                    .as_str()
+                    .ok_or("WeCom upload returned no media_id")?
+                    .to_string();
+                info!("WeCom media uploaded: {} -> {}", filename, media_id);
+                return Ok(media_id);
+            }
+            Err((code, _)) if attempt == 0 && TOKEN_ERRCODES.contains(&code) => clear_token_cache(),
+            Err((_, e)) => return Err(e),
+        }
+    }
+    Err("企业微信上传文件失败: access_token 无效".to_string())
+}
+
+/// Notification entrypoint used by `notifications::send_notification`.
+pub async fn send_notification(title: &str, body: &str) -> Result<(), String> {
+    let config = load_enabled_config()?;
+    let to = config
+        .notify_to
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| "@all".to_string());
+    send_markdown(&to, &format!("## {}\n\n{}", title, body)).await
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn wecom_configure(config: WecomConfig) -> Result<String, String> {
+    if config.corp_id.trim().is_empty() || config.corp_secret.trim().is_empty() {
+        return Err("corp_id and corp_secret are required".to_string());
+    }
+    if config.agent_id == 0 {
+        return Err("agent_id is required".to_string());
+    }
+    save_config(&config)?;
+    clear_token_cache();
+    info!("WeCom config saved: corp {} agent {}", config.corp_id, config.agent_id);
+    Ok("企业微信配置已保存".to_string())
+}
+
+#[tauri::command]
+pub async fn wecom_get_config() -> Result<Option<WecomConfig>, String> {
+    match load_config() {
+        Ok(config) => Ok(Some(config)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Verify the credentials by fetching an access token.
+#[tauri::command]
+pub async fn wecom_test() -> Result<String, String> {
+    let config = load_config()?;
+    clear_token_cache();
+    access_token(&config).await?;
+    Ok(format!("✅ 企业微信连接成功 (agent {})", config.agent_id))
+}
+
+#[tauri::command]
+pub async fn wecom_send_text(to_user: String, content: String) -> Result<(), String> {
+    send_text(&to_user, &content).await
+}
+
+#[tauri::command]
+pub async fn wecom_send_markdown(to_user: String, content: String) -> Result<(), String> {
+    send_markdown(&to_user, &content).await
+}
+
+#[tauri::command]
+pub async fn wecom_send_file(to_user: String, media_id: String) -> Result<(), String> {
+    send_file(&to_user, &media_id).await
+}
+
+#[tauri::command]
+pub async fn wecom_upload_media(path: String) -> Result<String, String> {
+    upload_media(&path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_body_and_errcode() {
+        let config = WecomConfig {
+            corp_id: "ww1".into(),
+            corp_secret: "s".into(),
+            agent_id: 1000002,
+            enabled: true,
+            notify_to: None,
+        };
+        let body = message_body(&config, "alice|bob", "markdown", json!({ "content": "**hi**" }));
+        assert_eq!(
+            body,
+            json!({ "touser": "alice|bob", "msgtype": "markdown", "agentid": 1000002, "markdown": { "content": "**hi**" } })
+        );
+
+        assert!(check_errcode(&json!({ "errcode": 0, "errmsg": "ok" }), "x").is_ok());
+        assert!(check_errcode(&json!({ "access_token": "t" }), "x").is_ok());
+        let (code, msg) = check_errcode(&json!({ "errcode": 42001, "errmsg": "access_token expired" }), "发送消息").unwrap_err();
+        assert!(TOKEN_ERRCODES.contains(&code));
+        assert!(msg.contains("access_token expired"));
+    }
+}
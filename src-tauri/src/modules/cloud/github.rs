@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+
+const GITHUB_API_BASE: &str = "https://api.github.com";
+const ACCEPT_JSON: &str = "application/vnd.github+json";
+const ACCEPT_DIFF: &str = "application/vnd.github.v3.diff";
+/// list_issues 最多翻的页数（PR 占多数的仓库也不会无限翻页）
+const MAX_ISSUE_PAGES: u32 = 10;
+
+/// GitHub 配置（持久化到 ~/.helix/github.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubConfig {
+    pub token: String,
+    pub default_owner: String,
+    pub default_repo: String,
+}
+
+/// Issue 摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub html_url: String,
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    pub author: String,
+    pub created_at: String,
+}
+
+/// Pull Request 摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PR {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub html_url: String,
+    pub head: String,
+    pub base: String,
+    pub draft: bool,
+}
+
+// ----- JSON 解析用的内部结构 -----
+
+#[derive(Deserialize)]
+struct RawUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct RawLabel {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RawIssue {
+    number: u64,
+    title: String,
+    state: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<RawLabel>,
+    user: Option<RawUser>,
+    created_at: String,
+    /// GitHub 的 issues 接口会把 PR 一起返回，带此字段的就是 PR
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct RawRef {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct RawPull {
+    number: u64,
+    title: String,
+    state: String,
+    html_url: String,
+    head: RawRef,
+    base: RawRef,
+    #[serde(default)]
+    draft: bool,
+}
+
+impl From<RawIssue> for Issue {
+    fn from(raw: RawIssue) -> Self {
+        Issue {
+            number: raw.number,
+            title: raw.title,
+            state: raw.state,
+            html_url: raw.html_url,
+            body: raw.body,
+            labels: raw.labels.into_iter().map(|l| l.name).collect(),
+            author: raw.user.map(|u| u.login).unwrap_or_default(),
+            created_at: raw.created_at,
+        }
+    }
+}
+
+impl From<RawPull> for PR {
+    fn from(raw: RawPull) -> Self {
+        PR {
+            number: raw.number,
+            title: raw.title,
+            state: raw.state,
+            html_url: raw.html_url,
+            head: raw.head.name,
+            base: raw.base.name,
+            draft: raw.draft,
+        }
+    }
+}
+
+// ----- 配置 -----
+
+/// 获取配置文件路径
+pub(crate) fn get_config_path() -> Result<PathBuf, String> {
+    let helix_dir = dirs::home_dir()
+        .ok_or_else(|| "Cannot determine home directory".to_string())?
+        .join(".helix");
+    std::fs::create_dir_all(&helix_dir).map_err(|e| format!("Failed to create dir: {}", e))?;
+    Ok(helix_dir.join("github.json"))
+}
+
+/// 读取 GitHub 配置（未配置时返回错误）
+pub fn load_config() -> Result<GithubConfig, String> {
+    let path = get_config_path()?;
+    if !path.exists() {
+        return Err("GitHub 未配置，请先保存 token 和默认仓库".to_string());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取 GitHub 配置失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析 GitHub 配置失败: {}", e))
+}
+
+/// 保存 GitHub 配置
+pub fn save_config(config: &GithubConfig) -> Result<(), String> {
+    if config.token.trim().is_empty() {
+        return Err("GitHub token 不能为空".to_string());
+    }
+    let content = serde_json::to_string_pretty(config).map_err(|e| format!("序列化 GitHub 配置失败: {}", e))?;
+    crate::modules::config::write_private_file(&get_config_path()?, &content)
+        .map_err(|e| format!("写入 GitHub 配置失败: {}", e))
+}
+
+/// 是否已配置 token（决定是否注册 GitHub agent 工具）
+pub fn is_configured() -> bool {
+    load_config().is_ok_and(|c| !c.token.trim().is_empty())
+}
+
+/// 解析目标仓库：显式传入的 `owner/repo` 优先，否则使用默认仓库
+fn resolve_repo(config: &GithubConfig, repo: Option<&str>) -> Result<(String, String), String> {
+    if let Some(full) = repo.map(str::trim).filter(|r| !r.is_empty()) {
+        return full
+            .split_once('/')
+            .filter(|(o, r)| !o.is_empty() && !r.is_empty() && !r.contains('/'))
+            .map(|(o, r)| (o.to_string(), r.to_string()))
+            .ok_or_else(|| format!("仓库格式应为 owner/repo: {}", full));
+    }
+    if config.default_owner.is_empty() || config.default_repo.is_empty() {
+        return Err("未设置默认仓库，请指定 owner/repo".to_string());
+    }
+    Ok((config.default_owner.clone(), config.default_repo.clone()))
+}
+
+// ----- API 请求 -----
+
+fn request(config: &GithubConfig, method: reqwest::Method, path: &str, accept: &str) -> reqwest::RequestBuilder {
+    crate::utils::http::get_client()
+        .request(method, format!("{}{}", GITHUB_API_BASE, path))
+        .header(reqwest::header::USER_AGENT, "Helix")
+        .header(reqwest::header::ACCEPT, accept)
+        .header(reqwest::header::AUTHORIZATION, format!("token {}", config.token))
+}
+
+/// 发送请求并检查状态码，失败时带上 GitHub 返回的 message
+async fn send(builder: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let resp = builder.send().await.map_err(|e| format!("GitHub 请求失败: {}", e))?;
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let text = resp.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|v| v["message"].as_str().map(String::from))
+        .unwrap_or(text);
+    Err(format!("GitHub API 返回 {}: {}", status, message))
+}
+
+/// 创建 Issue
+pub async fn create_issue(repo: Option<&str>, title: &str, body: &str, labels: &[String]) -> Result<Issue, String> {
+    let config = load_config()?;
+    let (owner, name) = resolve_repo(&config, repo)?;
+    let mut payload = json!({ "title": title, "body": body });
+    if !labels.is_empty() {
+        payload["labels"] = json!(labels);
+    }
+    let resp = send(
+        request(&config, reqwest::Method::POST, &format!("/repos/{}/{}/issues", owner, name), ACCEPT_JSON).json(&payload),
+    )
+    .await?;
+    let raw: RawIssue = resp.json().await.map_err(|e| format!("解析 Issue 失败: {}", e))?;
+    Ok(raw.into())
+}
+
+/// 列出 Issue（不含 PR），state: open | closed | all
+pub async fn list_issues(repo: Option<&str>, state: &str, limit: u32) -> Result<Vec<Issue>, String> {
+    let config = load_config()?;
+    let (owner, name) = resolve_repo(&config, repo)?;
+    if !matches!(state, "open" | "closed" | "all") {
+        return Err(format!("无效的 state: {}（可选 open / closed / all）", state));
+    }
+    // /issues 接口会混入 PR，过滤后不足 limit 时继续翻页
+    let limit = limit.clamp(1, 100) as usize;
+    let mut issues = Vec::with_capacity(limit);
+    for page in 1..=MAX_ISSUE_PAGES {
+        let resp = send(request(
+            &config,
+            reqwest::Method::GET,
+            &format!("/repos/{}/{}/issues?state={}&per_page=100&page={}", owner, name, state, page),
+            ACCEPT_JSON,
+        ))
+        .await?;
+        let raw: Vec<RawIssue> = resp.json().await.map_err(|e| format!("解析 Issue 列表失败: {}", e))?;
+        let last_page = raw.len() < 100;
+        issues.extend(raw.into_iter().filter(|i| i.pull_request.is_none()).map(Issue::from));
+        if issues.len() >= limit || last_page {
+            break;
+        }
+    }
+    issues.truncate(limit);
+    Ok(issues)
+}
+
+/// 创建 Pull Request
+pub async fn create_pr(repo: Option<&str>, title: &str, body: &str, head: &str, base: &str) -> Result<PR, String> {
+    let config = load_config()?;
+    let (owner, name) = resolve_repo(&config, repo)?;
+    let payload = json!({ "title": title, "body": body, "head": head, "base": base });
+    let resp = send(
+        request(&config, reqwest::Method::POST, &format!("/repos/{}/{}/pulls", owner, name), ACCEPT_JSON).json(&payload),
+    )
+    .await?;
+    let raw: RawPull = resp.json().await.map_err(|e| format!("解析 PR 失败: {}", e))?;
+    Ok(raw.into())
+}
+
+/// 获取 PR 的 unified diff
+pub async fn get_pr_diff(repo: Option<&str>, pr_number: u64) -> Result<String, String> {
+    let config = load_config()?;
+    let (owner, name) = resolve_repo(&config, repo)?;
+    let resp = send(
+        request(
+            &config,
+            reqwest::Method::GET,
+            &format!("/repos/{}/{}/pulls/{}", owner, name, pr_number),
+            ACCEPT_DIFF,
+        ),
+    )
+    .await?;
+    resp.text().await.map_err(|e| format!("读取 diff 失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_repo() {
+        let config = GithubConfig {
+            token: "t".into(),
+            default_owner: "wangxiuwen".into(),
+            default_repo: "helix".into(),
+        };
+        assert_eq!(resolve_repo(&config, None).unwrap(), ("wangxiuwen".into(), "helix".into()));
+        assert_eq!(resolve_repo(&config, Some("rust-lang/rust")).unwrap(), ("rust-lang".into(), "rust".into()));
+        assert!(resolve_repo(&config, Some("no-slash")).is_err());
+        assert!(resolve_repo(&config, Some("a/b/c")).is_err());
+    }
+}
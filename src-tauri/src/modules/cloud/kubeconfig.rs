@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::process::Stdio;
+
+use crate::modules::environments::WithManagedEnv;
+
+/// kubectl 默认只允许的只读子命令
+pub const READ_ONLY_VERBS: &[&str] = &["get", "describe", "logs", "top"];
 
 /// Kubeconfig 集群信息（不含敏感证书数据）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +21,12 @@ pub struct KubeContext {
     pub cluster: String,
     pub user: String,
     pub namespace: Option<String>,
+    /// 对应集群的 API server 地址
+    #[serde(default)]
+    pub server: String,
+    /// 是否为 current-context
+    #[serde(default)]
+    pub current: bool,
 }
 
 /// 汇总的 Kubeconfig 信息
@@ -106,7 +118,7 @@ pub fn load_kube_info(custom_path: Option<&str>) -> Result<KubeInfo, String> {
     let raw: RawKubeConfig =
         serde_yaml::from_str(&content).map_err(|e| format!("解析 kubeconfig 失败: {}", e))?;
 
-    let clusters = raw
+    let clusters: Vec<KubeCluster> = raw
         .clusters
         .unwrap_or_default()
         .into_iter()
@@ -119,6 +131,7 @@ pub fn load_kube_info(custom_path: Option<&str>) -> Result<KubeInfo, String> {
         })
         .collect();
 
+    let current_context = raw.current_context;
     let contexts = raw
         .contexts
         .unwrap_or_default()
@@ -129,11 +142,19 @@ pub fn load_kube_info(custom_path: Option<&str>) -> Result<KubeInfo, String> {
                 user: None,
                 namespace: None,
             });
+            let cluster = ctx.cluster.unwrap_or_default();
+            let server = clusters
+                .iter()
+                .find(|k| k.name == cluster)
+                .map(|k| k.server.clone())
+                .unwrap_or_default();
             KubeContext {
+                current: current_context.as_deref() == Some(c.name.as_str()),
                 name: c.name,
-                cluster: ctx.cluster.unwrap_or_default(),
+                cluster,
                 user: ctx.user.unwrap_or_default(),
                 namespace: ctx.namespace,
+                server,
             }
         })
         .collect();
@@ -141,8 +162,338 @@ pub fn load_kube_info(custom_path: Option<&str>) -> Result<KubeInfo, String> {
     Ok(KubeInfo {
         clusters,
         contexts,
-        current_context: raw.current_context,
+        current_context,
         config_path: path_str,
         config_exists: true,
     })
 }
+
+/// 直接写在 kubeconfig 里的凭据字段（token、密码、客户端私钥），形如 `users[admin].token`
+pub fn inline_credentials(custom_path: Option<&str>) -> (PathBuf, Vec<String>) {
+    let config_path = get_kubeconfig_path(custom_path);
+    let found = std::fs::read_to_string(&config_path)
+        .map(|content| inline_credentials_in(&content))
+        .unwrap_or_default();
+    (config_path, found)
+}
+
+fn inline_credentials_in(content: &str) -> Vec<String> {
+    let Ok(doc) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return Vec::new();
+    };
+    let Some(users) = doc.get("users").and_then(|u| u.as_sequence()) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for entry in users {
+        let name = entry.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+        for field in ["token", "password", "client-key-data"] {
+            let value = entry.get("user").and_then(|u| u.get(field)).and_then(|v| v.as_str());
+            if value.is_some_and(|v| !v.is_empty()) {
+                found.push(format!("users[{}].{}", name, field));
+            }
+        }
+    }
+    found
+}
+
+/// 列出所有 context（含集群地址、namespace、是否当前）
+pub fn list_contexts(custom_path: Option<&str>) -> Result<Vec<KubeContext>, String> {
+    Ok(load_kube_info(custom_path)?.contexts)
+}
+
+/// 切换 current-context：只改这一个字段，先写临时文件再 rename，避免写坏 kubeconfig
+pub fn use_context(name: &str, custom_path: Option<&str>) -> Result<KubeContext, String> {
+    let config_path = get_kubeconfig_path(custom_path);
+    let content =
+        std::fs::read_to_string(&config_path).map_err(|e| format!("读取 kubeconfig 失败: {}", e))?;
+    let mut doc: serde_yaml::Value =
+        serde_yaml::from_str(&content).map_err(|e| format!("解析 kubeconfig 失败: {}", e))?;
+
+    let exists = doc["contexts"]
+        .as_sequence()
+        .is_some_and(|list| list.iter().any(|c| c["name"].as_str() == Some(name)));
+    if !exists {
+        return Err(format!("kubeconfig 中不存在 context: {}", name));
+    }
+
+    let map = doc
+        .as_mapping_mut()
+        .ok_or_else(|| "kubeconfig 格式无效".to_string())?;
+    map.insert(
+        serde_yaml::Value::String("current-context".to_string()),
+        serde_yaml::Value::String(name.to_string()),
+    );
+    let updated = serde_yaml::to_string(&doc).map_err(|e| format!("序列化 kubeconfig 失败: {}", e))?;
+
+    let tmp_path = config_path.with_extension("helix-tmp");
+    std::fs::write(&tmp_path, updated).map_err(|e| format!("写入 kubeconfig 失败: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(&config_path) {
+            let _ = std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(meta.permissions().mode()));
+        }
+    }
+    std::fs::rename(&tmp_path, &config_path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("替换 kubeconfig 失败: {}", e)
+    })?;
+
+    list_contexts(custom_path)?
+        .into_iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| format!("kubeconfig 中不存在 context: {}", name))
+}
+
+// ----- kubectl 执行 -----
+
+/// 会改变目标集群或身份的 flag，context 由 helix 固定，命令里一律不允许出现
+const DENIED_FLAGS: &[&str] = &[
+    "context",
+    "kubeconfig",
+    "cluster",
+    "user",
+    "server",
+    "token",
+    "as",
+    "as-group",
+    "as-uid",
+    "username",
+    "password",
+    "client-certificate",
+    "client-key",
+    "certificate-authority",
+    "insecure-skip-tls-verify",
+    "tls-server-name",
+];
+/// `-s` 是 `--server` 的简写
+const DENIED_SHORTHANDS: &[char] = &['s'];
+/// 需要带值的常用 flag，子命令之前出现时跳过它们的值
+const VALUE_FLAGS: &[&str] = &[
+    "namespace",
+    "selector",
+    "output",
+    "filename",
+    "container",
+    "label-columns",
+    "field-selector",
+    "sort-by",
+    "template",
+    "request-timeout",
+    "cache-dir",
+    "v",
+    "vmodule",
+];
+const VALUE_SHORTHANDS: &[char] = &['n', 'l', 'o', 'f', 'c', 'L', 'v'];
+/// 子命令之前允许出现的布尔 flag
+const BOOL_FLAGS: &[&str] = &["all-namespaces", "match-server-version", "warnings-as-errors"];
+const BOOL_SHORTHANDS: &[char] = &['A'];
+
+/// 单个 flag 参数：长名（不含 `--`）或短名组合，以及是否已内联了值
+enum KubectlFlag<'a> {
+    Long { name: &'a str, inline_value: bool },
+    Short(&'a str),
+}
+
+fn parse_flag(arg: &str) -> Option<KubectlFlag<'_>> {
+    if let Some(long) = arg.strip_prefix("--") {
+        let (name, inline_value) = match long.split_once('=') {
+            Some((name, _)) => (name, true),
+            None => (long, false),
+        };
+        Some(KubectlFlag::Long { name, inline_value })
+    } else {
+        arg.strip_prefix('-').filter(|s| !s.is_empty()).map(KubectlFlag::Short)
+    }
+}
+
+/// 短 flag 组合（如 `-An`、`-ndefault`）中依次出现的简写；遇到带值的简写后其余字符是值。
+/// 返回简写列表以及是否还需要下一个参数作为值。
+fn short_flags(cluster: &str) -> (Vec<char>, bool) {
+    let mut flags = Vec::new();
+    for (i, c) in cluster.char_indices() {
+        flags.push(c);
+        if VALUE_SHORTHANDS.contains(&c) {
+            let rest = &cluster[i + c.len_utf8()..];
+            return (flags, rest.is_empty());
+        }
+    }
+    (flags, false)
+}
+
+/// 校验 kubectl 参数：禁止覆盖 context/kubeconfig 以及服务器地址、凭据和身份，
+/// 未开启写操作时只允许只读子命令。子命令之前只接受已知的全局 flag（如
+/// `-n default get pods`），以免带值 flag 的值被误当成子命令。
+fn check_kubectl_args(args: &[String], allow_mutations: bool) -> Result<(), String> {
+    let mut verb = None;
+    let mut expects_value = false;
+    for arg in args {
+        if arg == "--" {
+            // 之后的参数属于容器内命令（exec / run），不再是 kubectl 的 flag
+            break;
+        }
+        if expects_value {
+            expects_value = false;
+            continue;
+        }
+        let Some(flag) = parse_flag(arg) else {
+            verb.get_or_insert(arg.as_str());
+            continue;
+        };
+        let before_verb = verb.is_none();
+        match flag {
+            KubectlFlag::Long { name, inline_value } => {
+                if DENIED_FLAGS.contains(&name) {
+                    return Err(format!(
+                        "kubectl 命令中不允许使用 --{}：请用 context 参数选择集群，不要覆盖 kubeconfig、服务器地址或凭据",
+                        name
+                    ));
+                }
+                if before_verb {
+                    if VALUE_FLAGS.contains(&name) {
+                        expects_value = !inline_value;
+                    } else if !BOOL_FLAGS.contains(&name) {
+                        return Err(format!("请把 --{} 写在 kubectl 子命令之后", name));
+                    }
+                }
+            }
+            KubectlFlag::Short(cluster) => {
+                let (flags, needs_value) = short_flags(cluster);
+                if let Some(c) = flags.iter().find(|c| DENIED_SHORTHANDS.contains(c)) {
+                    return Err(format!("kubectl 命令中不允许使用 -{}（--server）", c));
+                }
+                if before_verb {
+                    if let Some(c) = flags
+                        .iter()
+                        .find(|c| !VALUE_SHORTHANDS.contains(c) && !BOOL_SHORTHANDS.contains(c))
+                    {
+                        return Err(format!("请把 -{} 写在 kubectl 子命令之后", c));
+                    }
+                    expects_value = needs_value;
+                }
+            }
+        }
+    }
+    let verb = verb.ok_or_else(|| "缺少 kubectl 子命令".to_string())?;
+    if !allow_mutations && !READ_ONLY_VERBS.contains(&verb) {
+        return Err(format!(
+            "kubectl {} 未被允许：当前仅允许只读命令（{}），如需修改集群请在设置中开启 kubectl 写操作",
+            verb,
+            READ_ONLY_VERBS.join(" / ")
+        ));
+    }
+    Ok(())
+}
+
+/// 在指定 context（默认 current-context）下执行 kubectl，不经过 shell
+pub async fn run_kubectl(
+    args: &[String],
+    context: Option<&str>,
+    namespace: Option<&str>,
+    allow_mutations: bool,
+    timeout_secs: u64,
+) -> Result<String, String> {
+    check_kubectl_args(args, allow_mutations)?;
+
+    let context = match context.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(name) => {
+            if !list_contexts(None)?.iter().any(|c| c.name == name) {
+                return Err(format!("kubeconfig 中不存在 context: {}", name));
+            }
+            name.to_string()
+        }
+        None => load_kube_info(None)?
+            .current_context
+            .ok_or_else(|| "kubeconfig 未设置 current-context".to_string())?,
+    };
+
+    let mut cmd = tokio::process::Command::new("kubectl");
+    cmd.with_managed_env().arg("--context").arg(&context);
+    if let Some(ns) = namespace.map(str::trim).filter(|n| !n.is_empty()) {
+        cmd.arg("--namespace").arg(ns);
+    }
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), cmd.output())
+        .await
+        .map_err(|_| format!("kubectl 执行超时（{}s）", timeout_secs))?
+        .map_err(|e| format!("无法执行 kubectl: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(format!(
+            "kubectl 退出码 {}: {}",
+            output.status.code().unwrap_or(-1),
+            stderr.trim()
+        ));
+    }
+    if stderr.trim().is_empty() {
+        Ok(stdout.to_string())
+    } else {
+        Ok(format!("{}\n[stderr]\n{}", stdout, stderr.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_inline_credentials() {
+        let yaml = r#"
+users:
+- name: admin
+  user:
+    client-certificate-data: Y2VydA==
+    client-key-data: a2V5
+- name: ci
+  user:
+    token: abc
+- name: sso
+  user:
+    exec:
+      command: aws
+"#;
+        assert_eq!(inline_credentials_in(yaml), vec!["users[admin].client-key-data", "users[ci].token"]);
+        assert!(inline_credentials_in("clusters: []").is_empty());
+    }
+
+    #[test]
+    fn test_check_kubectl_args() {
+        assert!(check_kubectl_args(&args(&["get", "pods", "-A"]), false).is_ok());
+        assert!(check_kubectl_args(&args(&["logs", "api-0", "--tail=100"]), false).is_ok());
+        assert!(check_kubectl_args(&args(&["delete", "pod", "api-0"]), false).is_err());
+        assert!(check_kubectl_args(&args(&["delete", "pod", "api-0"]), true).is_ok());
+        assert!(check_kubectl_args(&args(&["get", "pods", "--context=prod"]), true).is_err());
+        assert!(check_kubectl_args(&args(&[]), false).is_err());
+
+        // Global flags before the verb, with separate or inline values
+        assert!(check_kubectl_args(&args(&["-n", "default", "get", "pods"]), false).is_ok());
+        assert!(check_kubectl_args(&args(&["--namespace=kube-system", "-o", "wide", "get", "pods"]), false).is_ok());
+        assert!(check_kubectl_args(&args(&["-ndefault", "-A", "top", "pods"]), false).is_ok());
+        assert!(check_kubectl_args(&args(&["-n", "get", "delete", "pod", "x"]), false).is_err());
+        assert!(check_kubectl_args(&args(&["-n", "get", "delete", "pod", "x"]), true).is_ok());
+        assert!(check_kubectl_args(&args(&["--unknown", "get", "pods"]), false).is_err());
+
+        // Endpoint, credential and identity overrides
+        for flag in ["--server=https://evil:6443", "--token=abc", "--as=admin", "--insecure-skip-tls-verify", "--user=x"] {
+            assert!(check_kubectl_args(&args(&["get", "pods", flag]), true).is_err(), "{}", flag);
+        }
+        assert!(check_kubectl_args(&args(&["get", "pods", "--as", "system:admin"]), true).is_err());
+        assert!(check_kubectl_args(&args(&["-s", "https://evil:6443", "get", "pods"]), true).is_err());
+        assert!(check_kubectl_args(&args(&["get", "pods", "-As", "https://evil:6443"]), true).is_err());
+
+        // Arguments after `--` belong to the command run in the container
+        assert!(check_kubectl_args(&args(&["exec", "api-0", "--", "curl", "--token=x"]), true).is_ok());
+    }
+}
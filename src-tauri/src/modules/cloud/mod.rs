@@ -1,2 +1,4 @@
 pub mod kubeconfig;
 pub mod aliyun;
+pub mod aws;
+pub mod github;
@@ -6,13 +6,20 @@
 //!
 //! Hub URL: https://evomap.ai
 //! Protocol: GEP-A2A v1.0.0
+//!
+//! Published assets are signed with a local Ed25519 key (~/.helix/evomap_identity.json)
+//! and fetched assets are verified against a trust store of known publishers.
 
+use base64::Engine;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tracing::info;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tracing::{info, warn};
 
 use crate::modules::config::get_data_dir;
 
@@ -23,6 +30,14 @@ use crate::modules::config::get_data_dir;
 const HUB_URL: &str = "https://evomap.ai";
 const PROTOCOL: &str = "gep-a2a";
 const PROTOCOL_VERSION: &str = "1.0.0";
+/// Domain separator for asset signatures
+const SIGNING_CONTEXT: &str = "evomap-asset-sig-v1";
+
+/// Verification outcome stored with fetched assets
+pub const VERIFY_TRUSTED: &str = "trusted"; // valid signature from a trusted publisher (or ourselves)
+pub const VERIFY_UNTRUSTED: &str = "untrusted"; // valid signature, unknown publisher
+pub const VERIFY_UNSIGNED: &str = "unsigned";
+pub const VERIFY_INVALID: &str = "invalid"; // content or signature doesn't match
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvoMapConfig {
@@ -55,6 +70,32 @@ pub struct EvoAsset {
     pub status: String,     // "candidate", "promoted", "quarantined"
     pub data: Value,
     pub created_at: String,
+    /// trusted / untrusted / unsigned / invalid
+    #[serde(default)]
+    pub verification: String,
+}
+
+/// Signature block attached to an asset as its `signature` field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetSignature {
+    pub algorithm: String,
+    /// Base64 Ed25519 public key
+    pub public_key: String,
+    pub fingerprint: String,
+    /// SHA-256 of the canonical asset JSON without `signature`
+    pub content_hash: String,
+    pub node_id: String,
+    pub signed_at: String,
+    /// Base64 Ed25519 signature over the signing message
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedPublisher {
+    pub fingerprint: String,
+    pub public_key: String,
+    pub label: Option<String>,
+    pub added_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +107,8 @@ pub struct EvoMapStatus {
     pub last_sync: Option<String>,
     pub local_assets: i64,
     pub fetched_assets: i64,
+    /// Fingerprint of the local signing key
+    pub key_fingerprint: Option<String>,
 }
 
 // ============================================================================
@@ -108,9 +151,22 @@ pub fn init_evomap_tables() -> Result<(), String> {
 
         CREATE INDEX IF NOT EXISTS idx_evo_assets_type ON evo_assets(asset_type);
         CREATE INDEX IF NOT EXISTS idx_evo_assets_status ON evo_assets(status);
+
+        CREATE TABLE IF NOT EXISTS evo_trust (
+            fingerprint TEXT PRIMARY KEY,
+            public_key  TEXT NOT NULL,
+            label       TEXT,
+            added_at    TEXT NOT NULL
+        );
         ",
     )
     .map_err(|e| format!("create evomap tables: {}", e))?;
+
+    // Migration: signature verification result per asset
+    let _ = conn.execute(
+        "ALTER TABLE evo_assets ADD COLUMN verification TEXT NOT NULL DEFAULT 'unsigned'",
+        [],
+    );
     info!("EvoMap tables initialized");
     Ok(())
 }
@@ -177,6 +233,217 @@ fn now_iso() -> String {
     chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
 }
 
+// ============================================================================
+// Signing Identity & Trust Store
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct IdentityFile {
+    /// Base64 PKCS#8 Ed25519 key pair
+    pkcs8: String,
+    created_at: String,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn get_identity_path() -> Result<PathBuf, String> {
+    let helix_dir = dirs::home_dir()
+        .ok_or_else(|| "Cannot determine home directory".to_string())?
+        .join(".helix");
+    std::fs::create_dir_all(&helix_dir).map_err(|e| format!("Failed to create dir: {}", e))?;
+    Ok(helix_dir.join("evomap_identity.json"))
+}
+
+/// Load the local signing key, generating it on first use.
+fn get_or_create_identity() -> Result<Ed25519KeyPair, String> {
+    let path = get_identity_path()?;
+    if path.exists() {
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("read identity: {}", e))?;
+        let file: IdentityFile = serde_json::from_str(&content).map_err(|e| format!("parse identity: {}", e))?;
+        let pkcs8 = b64().decode(file.pkcs8).map_err(|e| format!("decode identity: {}", e))?;
+        return Ed25519KeyPair::from_pkcs8(&pkcs8).map_err(|e| format!("load identity: {}", e));
+    }
+
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).map_err(|e| format!("generate key: {}", e))?;
+    let file = IdentityFile {
+        pkcs8: b64().encode(pkcs8.as_ref()),
+        created_at: now_iso(),
+    };
+    let content = serde_json::to_string_pretty(&file).map_err(|e| format!("serialize identity: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("write identity: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+
+    let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|e| format!("load identity: {}", e))?;
+    info!("EvoMap signing key created: {}", fingerprint(key.public_key().as_ref()));
+    Ok(key)
+}
+
+/// Short, stable identifier of a public key: hex of the first 16 bytes of its SHA-256.
+pub fn fingerprint(public_key: &[u8]) -> String {
+    Sha256::digest(public_key)[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// JSON with object keys sorted recursively, so hashes don't depend on key order.
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|k| format!("{}:{}", Value::String(k.clone()), canonical_json(&map[k])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(",")),
+        other => other.to_string(),
+    }
+}
+
+/// SHA-256 (hex) of the asset without its `signature` field.
+fn asset_content_hash(asset: &Value) -> String {
+    let mut unsigned = asset.clone();
+    if let Some(map) = unsigned.as_object_mut() {
+        map.remove("signature");
+    }
+    format!("{:x}", Sha256::digest(canonical_json(&unsigned).as_bytes()))
+}
+
+fn signing_message(content_hash: &str, node_id: &str, signed_at: &str) -> String {
+    format!("{}\n{}\n{}\n{}", SIGNING_CONTEXT, content_hash, node_id, signed_at)
+}
+
+/// Attach a `signature` block to an asset object.
+fn sign_asset(asset: &mut Value, key: &Ed25519KeyPair, node_id: &str) -> Result<(), String> {
+    if !asset.is_object() {
+        return Err("EvoMap asset must be a JSON object".to_string());
+    }
+    let content_hash = asset_content_hash(asset);
+    let signed_at = now_iso();
+    let sig = key.sign(signing_message(&content_hash, node_id, &signed_at).as_bytes());
+    let public_key = key.public_key().as_ref();
+    let block = AssetSignature {
+        algorithm: "ed25519".to_string(),
+        public_key: b64().encode(public_key),
+        fingerprint: fingerprint(public_key),
+        content_hash,
+        node_id: node_id.to_string(),
+        signed_at,
+        signature: b64().encode(sig.as_ref()),
+    };
+    asset["signature"] = serde_json::to_value(block).map_err(|e| format!("serialize signature: {}", e))?;
+    Ok(())
+}
+
+/// Check an asset's signature. `is_trusted` decides whether a valid key fingerprint is known.
+fn verify_asset(asset: &Value, is_trusted: impl Fn(&str) -> bool) -> &'static str {
+    let Some(sig_value) = asset.get("signature").filter(|v| !v.is_null()) else {
+        return VERIFY_UNSIGNED;
+    };
+    let Ok(sig) = serde_json::from_value::<AssetSignature>(sig_value.clone()) else {
+        return VERIFY_INVALID;
+    };
+    let (Ok(public_key), Ok(signature)) = (b64().decode(&sig.public_key), b64().decode(&sig.signature)) else {
+        return VERIFY_INVALID;
+    };
+    if sig.algorithm != "ed25519"
+        || sig.fingerprint != fingerprint(&public_key)
+        || sig.content_hash != asset_content_hash(asset)
+    {
+        return VERIFY_INVALID;
+    }
+    let message = signing_message(&sig.content_hash, &sig.node_id, &sig.signed_at);
+    if UnparsedPublicKey::new(&ED25519, &public_key)
+        .verify(message.as_bytes(), &signature)
+        .is_err()
+    {
+        return VERIFY_INVALID;
+    }
+    if is_trusted(&sig.fingerprint) {
+        VERIFY_TRUSTED
+    } else {
+        VERIFY_UNTRUSTED
+    }
+}
+
+/// Trusted fingerprints, including our own key.
+fn trusted_fingerprints() -> std::collections::HashSet<String> {
+    let mut set: std::collections::HashSet<String> = list_trusted_publishers()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.fingerprint)
+        .collect();
+    if let Ok(key) = get_or_create_identity() {
+        set.insert(fingerprint(key.public_key().as_ref()));
+    }
+    set
+}
+
+pub fn add_trusted_publisher(public_key: &str, label: Option<&str>) -> Result<TrustedPublisher, String> {
+    let bytes = b64()
+        .decode(public_key.trim())
+        .map_err(|e| format!("Invalid public key (expected base64): {}", e))?;
+    if bytes.len() != 32 {
+        return Err(format!("Invalid Ed25519 public key length: {} bytes", bytes.len()));
+    }
+    let publisher = TrustedPublisher {
+        fingerprint: fingerprint(&bytes),
+        public_key: b64().encode(&bytes),
+        label: label.map(str::trim).filter(|l| !l.is_empty()).map(String::from),
+        added_at: now_iso(),
+    };
+    let conn = EVO_DB.lock();
+    conn.execute(
+        "INSERT OR REPLACE INTO evo_trust (fingerprint, public_key, label, added_at) VALUES (?1, ?2, ?3, ?4)",
+        params![publisher.fingerprint, publisher.public_key, publisher.label, publisher.added_at],
+    )
+    .map_err(|e| format!("add trust: {}", e))?;
+    info!("EvoMap trusted publisher added: {}", publisher.fingerprint);
+    Ok(publisher)
+}
+
+pub fn list_trusted_publishers() -> Result<Vec<TrustedPublisher>, String> {
+    let conn = EVO_DB.lock();
+    let mut stmt = conn
+        .prepare("SELECT fingerprint, public_key, label, added_at FROM evo_trust ORDER BY added_at DESC")
+        .map_err(|e| format!("query: {}", e))?;
+    let publishers = stmt
+        .query_map([], |row| {
+            Ok(TrustedPublisher {
+                fingerprint: row.get(0)?,
+                public_key: row.get(1)?,
+                label: row.get(2)?,
+                added_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("map: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("collect: {}", e))?;
+    Ok(publishers)
+}
+
+pub fn remove_trusted_publisher(fingerprint: &str) -> Result<(), String> {
+    let conn = EVO_DB.lock();
+    let removed = conn
+        .execute("DELETE FROM evo_trust WHERE fingerprint = ?1", params![fingerprint])
+        .map_err(|e| format!("remove trust: {}", e))?;
+    if removed == 0 {
+        return Err(format!("Publisher {} is not in the trust store", fingerprint));
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Protocol Envelope
 // ============================================================================
@@ -270,6 +537,7 @@ pub async fn fetch_assets(asset_type: Option<&str>) -> Result<Vec<EvoAsset>, Str
     let mut assets = Vec::new();
 
     if let Some(arr) = assets_arr {
+        let trusted = trusted_fingerprints();
         let conn = EVO_DB.lock();
         let now = now_iso();
 
@@ -278,12 +546,16 @@ pub async fn fetch_assets(asset_type: Option<&str>) -> Result<Vec<EvoAsset>, Str
             let asset_type = item.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
             let summary = item.get("summary").and_then(|v| v.as_str()).unwrap_or("");
             let status = item.get("status").and_then(|v| v.as_str()).unwrap_or("promoted");
+            let verification = verify_asset(item, |fp| trusted.contains(fp));
+            if verification == VERIFY_INVALID {
+                warn!("EvoMap fetch: asset {} has an invalid signature", asset_id);
+            }
 
             // Store locally
             let data_str = serde_json::to_string(item).unwrap_or_default();
             let _ = conn.execute(
-                "INSERT OR REPLACE INTO evo_assets (asset_id, asset_type, summary, status, data, source, created_at) VALUES (?1, ?2, ?3, ?4, ?5, 'remote', ?6)",
-                params![asset_id, asset_type, summary, status, data_str, now],
+                "INSERT OR REPLACE INTO evo_assets (asset_id, asset_type, summary, status, data, source, created_at, verification) VALUES (?1, ?2, ?3, ?4, ?5, 'remote', ?6, ?7)",
+                params![asset_id, asset_type, summary, status, data_str, now, verification],
             );
 
             assets.push(EvoAsset {
@@ -293,6 +565,7 @@ pub async fn fetch_assets(asset_type: Option<&str>) -> Result<Vec<EvoAsset>, Str
                 status: status.to_string(),
                 data: item.clone(),
                 created_at: now.clone(),
+                verification: verification.to_string(),
             });
         }
     }
@@ -305,11 +578,15 @@ pub async fn fetch_assets(asset_type: Option<&str>) -> Result<Vec<EvoAsset>, Str
 /// POST /a2a/publish — Publish Gene + Capsule bundle to hub
 pub async fn publish_bundle(gene: Value, capsule: Value, evolution_event: Option<Value>) -> Result<Value, String> {
     let node_id = get_or_create_node_id();
+    let key = get_or_create_identity()?;
 
     let mut assets = vec![gene, capsule];
     if let Some(ev) = evolution_event {
         assets.push(ev);
     }
+    for asset in &mut assets {
+        sign_asset(asset, &key, &node_id)?;
+    }
 
     let payload = json!({ "assets": assets });
     let envelope = build_envelope("publish", &node_id, payload);
@@ -341,12 +618,12 @@ pub fn list_local_assets(asset_type: Option<&str>, limit: i64) -> Result<Vec<Evo
 
     let query = if let Some(at) = asset_type {
         format!(
-            "SELECT asset_id, asset_type, summary, status, data, created_at FROM evo_assets WHERE asset_type = '{}' ORDER BY created_at DESC LIMIT {}",
+            "SELECT asset_id, asset_type, summary, status, data, created_at, verification FROM evo_assets WHERE asset_type = '{}' ORDER BY created_at DESC LIMIT {}",
             at, limit
         )
     } else {
         format!(
-            "SELECT asset_id, asset_type, summary, status, data, created_at FROM evo_assets ORDER BY created_at DESC LIMIT {}",
+            "SELECT asset_id, asset_type, summary, status, data, created_at, verification FROM evo_assets ORDER BY created_at DESC LIMIT {}",
             limit
         )
     };
@@ -363,6 +640,7 @@ pub fn list_local_assets(asset_type: Option<&str>, limit: i64) -> Result<Vec<Evo
                 status: row.get(3)?,
                 data,
                 created_at: row.get(5)?,
+                verification: row.get(6)?,
             })
         })
         .map_err(|e| format!("map: {}", e))?
@@ -372,6 +650,77 @@ pub fn list_local_assets(asset_type: Option<&str>, limit: i64) -> Result<Vec<Evo
     Ok(assets)
 }
 
+/// Install a fetched asset as a skill (~/.helix/skills/evomap-<id>/SKILL.md).
+/// Only trusted assets install by default; unsigned/untrusted ones need
+/// `allow_unsigned`, and assets with an invalid signature are always refused.
+pub fn install_asset(asset_id: &str, allow_unsigned: bool) -> Result<String, String> {
+    let (asset_type, summary, data_str, verification): (String, String, String, String) = {
+        let conn = EVO_DB.lock();
+        conn.query_row(
+            "SELECT asset_type, summary, data, verification FROM evo_assets WHERE asset_id = ?1",
+            params![asset_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|_| format!("Asset {} not found locally, fetch it first", asset_id))?
+    };
+
+    // Re-verify: the trust store may have changed since the asset was fetched
+    let data: Value = serde_json::from_str(&data_str).unwrap_or(json!({}));
+    let trusted = trusted_fingerprints();
+    let current = verify_asset(&data, |fp| trusted.contains(fp));
+    if current == VERIFY_INVALID {
+        return Err(format!("Asset {} has an invalid signature and cannot be installed", asset_id));
+    }
+    if current != VERIFY_TRUSTED && !allow_unsigned {
+        return Err(format!(
+            "Asset {} is {} — pass allow_unsigned to install it anyway",
+            asset_id, current
+        ));
+    }
+    if current != verification {
+        let conn = EVO_DB.lock();
+        let _ = conn.execute(
+            "UPDATE evo_assets SET verification = ?1 WHERE asset_id = ?2",
+            params![current, asset_id],
+        );
+    }
+
+    let name = format!(
+        "evomap-{}",
+        asset_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+            .collect::<String>()
+    );
+    let author = data["signature"]["fingerprint"].as_str().unwrap_or("unsigned");
+    let body = format!(
+        "# {}\n\n{}\n\n```json\n{}\n```\n",
+        asset_type,
+        summary,
+        serde_json::to_string_pretty(&data).unwrap_or_default()
+    );
+    let frontmatter = crate::modules::skills::SkillFrontmatter {
+        name: name.clone(),
+        description: Some(summary),
+        version: None,
+        author: Some(author.to_string()),
+        tags: Some(vec!["evomap".to_string(), asset_type.to_lowercase()]),
+        icon: Some("🧬".to_string()),
+        homepage: None,
+        permissions: None,
+        enabled: true,
+    };
+    let path = crate::modules::skills::write_skill(&frontmatter, &body)?;
+
+    let conn = EVO_DB.lock();
+    let _ = conn.execute(
+        "UPDATE evo_assets SET status = 'installed' WHERE asset_id = ?1",
+        params![asset_id],
+    );
+    info!("EvoMap asset {} installed as skill {} ({})", asset_id, name, current);
+    Ok(path)
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -391,6 +740,29 @@ pub async fn evomap_publish(gene: Value, capsule: Value, evolution_event: Option
     publish_bundle(gene, capsule, evolution_event).await
 }
 
+/// Install a fetched asset; unsigned/untrusted assets need `allow_unsigned: true`.
+#[tauri::command]
+pub async fn evomap_install(asset_id: String, allow_unsigned: Option<bool>) -> Result<String, String> {
+    install_asset(&asset_id, allow_unsigned.unwrap_or(false))
+}
+
+#[tauri::command]
+pub async fn evomap_trust_add(public_key: String, label: Option<String>) -> Result<TrustedPublisher, String> {
+    let _ = init_evomap_tables();
+    add_trusted_publisher(&public_key, label.as_deref())
+}
+
+#[tauri::command]
+pub async fn evomap_trust_list() -> Result<Vec<TrustedPublisher>, String> {
+    let _ = init_evomap_tables();
+    list_trusted_publishers()
+}
+
+#[tauri::command]
+pub async fn evomap_trust_remove(fingerprint: String) -> Result<(), String> {
+    remove_trusted_publisher(&fingerprint)
+}
+
 #[tauri::command]
 pub async fn evomap_list_assets(asset_type: Option<String>, limit: Option<i64>) -> Result<Vec<EvoAsset>, String> {
     list_local_assets(asset_type.as_deref(), limit.unwrap_or(50))
@@ -403,6 +775,9 @@ pub async fn evomap_status() -> Result<EvoMapStatus, String> {
     let claim_url = get_config_value("claim_url");
     let last_sync = get_config_value("last_sync");
     let enabled = get_config_value("enabled").map(|v| v == "true").unwrap_or(false);
+    let key_fingerprint = get_or_create_identity()
+        .map(|key| fingerprint(key.public_key().as_ref()))
+        .ok();
 
     let conn = EVO_DB.lock();
     let local_assets: i64 = conn
@@ -420,6 +795,7 @@ pub async fn evomap_status() -> Result<EvoMapStatus, String> {
         last_sync,
         local_assets,
         fetched_assets,
+        key_fingerprint,
     })
 }
 
@@ -428,3 +804,42 @@ pub async fn evomap_toggle(enabled: bool) -> Result<(), String> {
     let _ = init_evomap_tables();
     set_config_value("enabled", if enabled { "true" } else { "false" })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key() -> Ed25519KeyPair {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let a = json!({ "b": 1, "a": { "d": [1, { "f": 2, "e": 3 }], "c": "x" } });
+        let b = json!({ "a": { "c": "x", "d": [1, { "e": 3, "f": 2 }] }, "b": 1 });
+        assert_eq!(canonical_json(&a), canonical_json(&b));
+        assert_eq!(canonical_json(&a), r#"{"a":{"c":"x","d":[1,{"e":3,"f":2}]},"b":1}"#);
+    }
+
+    #[test]
+    fn test_sign_and_verify_asset() {
+        let key = test_key();
+        let fp = fingerprint(key.public_key().as_ref());
+        let mut asset = json!({ "asset_id": "g1", "type": "Gene", "summary": "retry on 429" });
+        sign_asset(&mut asset, &key, "node_test").unwrap();
+
+        assert_eq!(verify_asset(&asset, |f| f == fp), VERIFY_TRUSTED);
+        assert_eq!(verify_asset(&asset, |_| false), VERIFY_UNTRUSTED);
+        assert_eq!(verify_asset(&json!({ "asset_id": "g1" }), |_| true), VERIFY_UNSIGNED);
+
+        let mut tampered = asset.clone();
+        tampered["summary"] = json!("rm -rf ~");
+        assert_eq!(verify_asset(&tampered, |_| true), VERIFY_INVALID);
+
+        let mut forged = asset.clone();
+        forged["signature"]["node_id"] = json!("someone_else");
+        assert_eq!(verify_asset(&forged, |_| true), VERIFY_INVALID);
+    }
+}
@@ -20,6 +20,19 @@ use utoipa_swagger_ui::SwaggerUi;
 use crate::modules::agent;
 use crate::modules::database;
 
+use std::sync::atomic::{AtomicU16, Ordering};
+
+/// Port the API server is listening on (0 = not listening).
+static LISTENING_PORT: AtomicU16 = AtomicU16::new(0);
+
+/// Port the API server is currently listening on, if it started successfully.
+pub fn listening_port() -> Option<u16> {
+    match LISTENING_PORT.load(Ordering::Relaxed) {
+        0 => None,
+        port => Some(port),
+    }
+}
+
 // ============================================================================
 // OpenAPI Schema
 // ============================================================================
@@ -157,6 +170,7 @@ async fn agent_chat(Json(req): Json<AgentChatRequest>) -> impl IntoResponse {
     };
 
     info!("[API] agent_chat: account={}, msg={}", account_id, &req.message);
+    crate::modules::desktop_notify::notify_inbound("api", &account_id, "API", &req.message, false);
 
     match agent::agent_process_message(&account_id, &req.message, None).await {
         Ok(reply) => (
@@ -277,7 +291,7 @@ async fn tool_shell_exec(Json(req): Json<ToolShellRequest>) -> Json<ToolResponse
 pub fn start_api_server(port: u16) {
     info!("Starting API server on port {}", port);
 
-    tauri::async_runtime::spawn(async move {
+    super::shutdown::spawn("api-server", |shutdown| async move {
         let app = Router::new()
             // Health
             .route("/api/health", get(health))
@@ -302,12 +316,16 @@ pub fn start_api_server(port: u16) {
             }
         };
 
+        LISTENING_PORT.store(port, Ordering::Relaxed);
         info!("✅ API server listening on http://localhost:{}", port);
         info!("📖 Swagger UI: http://localhost:{}/swagger-ui/", port);
         info!("🤖 TG Bot API: http://localhost:{}/bot/getMe", port);
 
-        if let Err(e) = axum::serve(listener, app).await {
+        let server = axum::serve(listener, app).with_graceful_shutdown(super::shutdown::wait_for_shutdown());
+        if let Err(e) = server.await {
             error!("API server error: {}", e);
         }
+        LISTENING_PORT.store(0, Ordering::Relaxed);
+        drop(shutdown);
     });
 }
@@ -1,10 +1,11 @@
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use serde_json;
 
 use crate::models::AppConfig;
 
-const CONFIG_FILE: &str = "helix_config.json";
+pub(crate) const CONFIG_FILE: &str = "helix_config.json";
 
 /// 获取应用数据目录 (独立实现，不依赖已删除的 account 模块)
 pub fn get_data_dir() -> Result<PathBuf, String> {
@@ -52,3 +53,46 @@ pub fn save_app_config(config: &AppConfig) -> Result<(), String> {
     fs::write(&config_path, content)
         .map_err(|e| format!("failed_to_save_config: {}", e))
 }
+
+/// Write a file only the current user can read (mode 0600 on Unix), for
+/// configs that hold passwords or tokens. The mode is also re-applied to an
+/// existing file before the new content goes in.
+pub fn write_private_file(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("helix-private-{}.json", std::process::id()));
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, "{\"token\":\"secret\"}").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"token\":\"secret\"}");
+        fs::remove_file(&path).unwrap();
+
+        write_private_file(&path, "new").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -39,13 +39,8 @@ fn open_db() -> Result<Connection, String> {
     Ok(conn)
 }
 
-/// Initialize database — create tables if they don't exist.
-/// Call this once at app startup.
-pub fn init_db() -> Result<(), String> {
-    let conn = DB.lock().map_err(|e| format!("DB lock error: {}", e))?;
-
-    conn.execute_batch(
-        "
+/// Table and index definitions, applied by `init_db`.
+const SCHEMA: &str = "
         CREATE TABLE IF NOT EXISTS accounts (
             id          TEXT PRIMARY KEY,
             nickname    TEXT NOT NULL DEFAULT '',
@@ -68,6 +63,29 @@ pub fn init_db() -> Result<(), String> {
         CREATE INDEX IF NOT EXISTS idx_messages_account
             ON messages(account_id, created_at);
 
+        CREATE INDEX IF NOT EXISTS idx_messages_account_id
+            ON messages(account_id, id);
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            content,
+            content=messages,
+            content_rowid=id,
+            tokenize='unicode61'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+            INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+        END;
+
         CREATE TABLE IF NOT EXISTS conversation_history (
             id          INTEGER PRIMARY KEY AUTOINCREMENT,
             account_id  TEXT NOT NULL,
@@ -103,13 +121,84 @@ pub fn init_db() -> Result<(), String> {
 
         CREATE INDEX IF NOT EXISTS idx_files_account ON files(account_id);
         CREATE INDEX IF NOT EXISTS idx_files_msg_id ON files(msg_id);
-        "
-    ).map_err(|e| format!("Failed to create tables: {}", e))?;
+
+        CREATE TABLE IF NOT EXISTS message_annotations (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id  INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+            kind        TEXT NOT NULL,
+            note        TEXT,
+            created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_annotations_message ON message_annotations(message_id);
+        ";
+
+/// Tables created by `SCHEMA`.
+const TABLES: &[&str] =
+    &["accounts", "messages", "messages_fts", "conversation_history", "memory", "files", "message_annotations"];
+
+fn missing_tables_in(conn: &Connection) -> Result<Vec<&'static str>, String> {
+    let mut stmt = conn
+        .prepare("SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = ?1)")
+        .map_err(|e| format!("Prepare: {}", e))?;
+    let mut missing = Vec::new();
+    for table in TABLES {
+        let exists: bool = stmt.query_row(params![table], |row| row.get(0)).map_err(|e| format!("Query: {}", e))?;
+        if !exists {
+            missing.push(*table);
+        }
+    }
+    Ok(missing)
+}
+
+/// Tables of the current schema that helix.db lacks (empty when healthy).
+pub fn missing_tables() -> Result<Vec<&'static str>, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock error: {}", e))?;
+    missing_tables_in(&conn)
+}
+
+/// Initialize database — create tables if they don't exist.
+/// Call this once at app startup.
+pub fn init_db() -> Result<(), String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock error: {}", e))?;
+
+    let had_messages_fts: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'messages_fts')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to inspect schema: {}", e))?;
+
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create tables: {}", e))?;
+
+    // Index messages stored before full-text search existed
+    if !had_messages_fts {
+        conn.execute("INSERT INTO messages_fts(messages_fts) VALUES ('rebuild')", [])
+            .map_err(|e| format!("Failed to index messages: {}", e))?;
+    }
 
     info!("Database initialized at {:?}", db_path().unwrap_or_default());
     Ok(())
 }
 
+/// Fold the WAL back into helix.db and truncate it. Called on app exit so
+/// the next launch starts from a clean main file.
+pub fn checkpoint_wal() -> Result<(), String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock error: {}", e))?;
+    let (busy, log, checkpointed): (i64, i64, i64) = conn
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| format!("Failed to checkpoint WAL: {}", e))?;
+    if busy != 0 {
+        return Err(format!("WAL checkpoint blocked ({} of {} frames written)", checkpointed, log));
+    }
+    info!("WAL checkpointed ({} frames)", checkpointed);
+    Ok(())
+}
+
 
 // ============================================================================
 // Account operations
@@ -229,6 +318,9 @@ pub struct DbMessage {
     pub msg_type: i32,
     pub ai_reply: bool,
     pub created_at: String,
+    /// Only populated when annotations are requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<MessageAnnotation>>,
 }
 
 pub fn save_message(
@@ -298,6 +390,7 @@ pub fn get_messages(account_id: &str, limit: i64, offset: i64) -> Result<Vec<DbM
             msg_type: row.get(4)?,
             ai_reply: row.get::<_, i32>(5)? != 0,
             created_at: row.get(6)?,
+            annotations: None,
         })
     }).map_err(|e| format!("Query: {}", e))?;
 
@@ -339,6 +432,7 @@ pub fn get_updates(account_id: &str, offset: i64, limit: i64) -> Result<Vec<DbMe
             msg_type: row.get(4)?,
             ai_reply: row.get::<_, i32>(5)? != 0,
             created_at: row.get(6)?,
+            annotations: None,
         })
     }).map_err(|e| format!("Query: {}", e))?;
 
@@ -349,6 +443,166 @@ pub fn get_updates(account_id: &str, offset: i64, limit: i64) -> Result<Vec<DbMe
     Ok(messages)
 }
 
+/// Largest page `get_messages_page` returns.
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Filters and cursor of a message page. Without `after_id` pages walk
+/// backwards from the newest message.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct MessagePageQuery {
+    /// Only messages older than this id (the previous page's `next_cursor`)
+    pub before_id: Option<i64>,
+    /// Only messages newer than this id; the page then walks forwards
+    pub after_id: Option<i64>,
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<i64>,
+    /// Unix seconds, inclusive
+    pub from_ts: Option<i64>,
+    /// Unix seconds, exclusive
+    pub to_ts: Option<i64>,
+    pub msg_type: Option<i32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MessagePage {
+    /// Oldest first, like `get_messages`
+    pub messages: Vec<DbMessage>,
+    /// Pass as `before_id` (or `after_id` when paging forwards) for the next
+    /// page; None when there are no more messages
+    pub next_cursor: Option<i64>,
+}
+
+fn get_messages_page_in(conn: &Connection, account_id: &str, query: &MessagePageQuery) -> Result<MessagePage, String> {
+    let limit = query.limit.unwrap_or(100).clamp(1, MAX_PAGE_SIZE);
+    let forward = query.after_id.is_some();
+
+    // Keyset conditions on (account_id, id) so deep pages cost the same as the first
+    let mut conditions = vec!["account_id = ?1".to_string()];
+    let mut param_values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(account_id.to_string())];
+    let mut push = |condition: &str, value: Box<dyn rusqlite::ToSql>| {
+        param_values.push(value);
+        conditions.push(condition.replace('?', &format!("?{}", param_values.len())));
+    };
+    if let Some(before_id) = query.before_id {
+        push("id < ?", Box::new(before_id));
+    }
+    if let Some(after_id) = query.after_id {
+        push("id > ?", Box::new(after_id));
+    }
+    if let Some(from_ts) = query.from_ts {
+        push("created_at >= datetime(?, 'unixepoch')", Box::new(from_ts));
+    }
+    if let Some(to_ts) = query.to_ts {
+        push("created_at < datetime(?, 'unixepoch')", Box::new(to_ts));
+    }
+    if let Some(msg_type) = query.msg_type {
+        push("msg_type = ?", Box::new(msg_type));
+    }
+
+    let sql = format!(
+        "SELECT id, account_id, content, from_me, msg_type, ai_reply, created_at
+         FROM messages
+         WHERE {}
+         ORDER BY id {}
+         LIMIT {}",
+        conditions.join(" AND "),
+        if forward { "ASC" } else { "DESC" },
+        limit
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Prepare: {}", e))?;
+    let params_refs: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok(DbMessage {
+            id: row.get(0)?,
+            account_id: row.get(1)?,
+            content: row.get(2)?,
+            from_me: row.get::<_, i32>(3)? != 0,
+            msg_type: row.get(4)?,
+            ai_reply: row.get::<_, i32>(5)? != 0,
+            created_at: row.get(6)?,
+            annotations: None,
+        })
+    }).map_err(|e| format!("Query: {}", e))?;
+
+    let mut messages = Vec::new();
+    for row in rows {
+        messages.push(row.map_err(|e| format!("Row: {}", e))?);
+    }
+    // The last row read is the furthest from the cursor
+    let next_cursor = messages.last().map(|m| m.id).filter(|_| messages.len() as i64 == limit);
+    if !forward {
+        messages.reverse();
+    }
+    Ok(MessagePage { messages, next_cursor })
+}
+
+/// One page of an account's messages by cursor, for histories too long for
+/// `get_messages` (whose OFFSET scans every skipped row).
+pub fn get_messages_page(account_id: &str, query: &MessagePageQuery) -> Result<MessagePage, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    get_messages_page_in(&conn, account_id, query)
+}
+
+/// A message matched by full-text search.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MessageSearchResult {
+    pub message: DbMessage,
+    /// BM25 relevance, higher is better
+    pub score: f64,
+    /// Content excerpt with matches wrapped in `[` `]`
+    pub snippet: Option<String>,
+}
+
+/// Quote each word so FTS5 operators in user input are taken literally.
+fn message_match_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|w| format!("\"{}\"", w.replace('"', "")))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+fn search_messages_in(conn: &Connection, query: &str, limit: i64) -> Result<Vec<MessageSearchResult>, String> {
+    let fts_query = message_match_query(query);
+    if fts_query.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.account_id, m.content, m.from_me, m.msg_type, m.ai_reply, m.created_at,
+                bm25(messages_fts), snippet(messages_fts, 0, '[', ']', '…', 16)
+         FROM messages_fts f
+         JOIN messages m ON m.id = f.rowid
+         WHERE messages_fts MATCH ?1
+         ORDER BY bm25(messages_fts), m.id DESC
+         LIMIT ?2"
+    ).map_err(|e| format!("Prepare: {}", e))?;
+
+    let rows = stmt.query_map(params![fts_query, limit], |row| {
+        Ok(MessageSearchResult {
+            message: DbMessage {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                content: row.get(2)?,
+                from_me: row.get::<_, i32>(3)? != 0,
+                msg_type: row.get(4)?,
+                ai_reply: row.get::<_, i32>(5)? != 0,
+                created_at: row.get(6)?,
+                annotations: None,
+            },
+            score: -row.get::<_, f64>(7)?, // bm25 is negative (lower = better)
+            snippet: row.get(8)?,
+        })
+    }).map_err(|e| format!("Query: {}", e))?;
+    rows.map(|r| r.map_err(|e| format!("Row: {}", e))).collect()
+}
+
+/// Full-text search over all stored messages, best match first.
+pub fn search_messages(query: &str, limit: i64) -> Result<Vec<MessageSearchResult>, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    search_messages_in(&conn, query, limit)
+}
+
 // ============================================================================
 // File metadata operations
 // ============================================================================
@@ -434,6 +688,90 @@ pub fn get_file_by_id(id: i64) -> Result<DbFile, String> {
     ).map_err(|e| format!("File not found: {}", e))
 }
 
+const FILE_COLUMNS: &str =
+    "id, account_id, msg_id, file_name, file_path, file_size, mime_type, md5, created_at";
+
+fn row_to_file(row: &rusqlite::Row) -> rusqlite::Result<DbFile> {
+    Ok(DbFile {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        msg_id: row.get(2)?,
+        file_name: row.get(3)?,
+        file_path: row.get(4)?,
+        file_size: row.get(5)?,
+        mime_type: row.get(6)?,
+        md5: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+/// Escape `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Search file metadata by partial name across sessions (or within one), newest first.
+/// `mime_type` matches exactly, or by prefix when given as `image/*`.
+fn search_files_in(
+    conn: &Connection,
+    query: &str,
+    session_id: Option<&str>,
+    mime_type: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DbFile>, String> {
+    let mime_pattern = mime_type.map(|m| match m.strip_suffix('*') {
+        Some(prefix) => format!("{}%", escape_like(prefix)),
+        None => escape_like(m),
+    });
+    let sql = format!(
+        "SELECT {} FROM files
+         WHERE file_name LIKE ?1 ESCAPE '\\'
+           AND (?2 IS NULL OR account_id = ?2)
+           AND (?3 IS NULL OR mime_type LIKE ?3 ESCAPE '\\')
+         ORDER BY created_at DESC, id DESC
+         LIMIT ?4",
+        FILE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Prepare: {}", e))?;
+    let rows = stmt
+        .query_map(
+            params![format!("%{}%", escape_like(query)), session_id, mime_pattern, limit],
+            row_to_file,
+        )
+        .map_err(|e| format!("Query: {}", e))?;
+    rows.map(|r| r.map_err(|e| format!("Row: {}", e))).collect()
+}
+
+/// Exact file-name lookup across all sessions, newest first.
+fn files_by_name_in(conn: &Connection, file_name: &str) -> Result<Vec<DbFile>, String> {
+    let sql = format!(
+        "SELECT {} FROM files WHERE file_name = ?1 ORDER BY created_at DESC, id DESC",
+        FILE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Prepare: {}", e))?;
+    let rows = stmt
+        .query_map(params![file_name], row_to_file)
+        .map_err(|e| format!("Query: {}", e))?;
+    rows.map(|r| r.map_err(|e| format!("Row: {}", e))).collect()
+}
+
+/// Search file metadata by partial name, optionally filtered by session and MIME type.
+pub fn search_files(
+    query: &str,
+    session_id: Option<&str>,
+    mime_type: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DbFile>, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    search_files_in(&conn, query, session_id, mime_type, limit)
+}
+
+/// Files with exactly this name, across all sessions.
+pub fn get_files_by_name(file_name: &str) -> Result<Vec<DbFile>, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    files_by_name_in(&conn, file_name)
+}
+
 /// Delete a file record by id.
 pub fn delete_file_record(id: i64) -> Result<(), String> {
     let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
@@ -469,6 +807,35 @@ pub fn store_stats(account_id: &str) -> Result<serde_json::Value, String> {
     }))
 }
 
+/// Row counts and on-disk size of helix.db.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DbStats {
+    pub accounts: i64,
+    pub messages: i64,
+    pub files: i64,
+    pub conversation_history: i64,
+    pub db_size_bytes: u64,
+    pub wal_size_bytes: u64,
+}
+
+pub fn db_stats() -> Result<DbStats, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let count = |table: &str| -> Result<i64, String> {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            .map_err(|e| format!("Count {}: {}", table, e))
+    };
+    let path = db_path()?;
+    let size = |p: &std::path::Path| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    Ok(DbStats {
+        accounts: count("accounts")?,
+        messages: count("messages")?,
+        files: count("files")?,
+        conversation_history: count("conversation_history")?,
+        db_size_bytes: size(&path),
+        wal_size_bytes: size(&path.with_extension("db-wal")),
+    })
+}
+
 /// Delete messages older than `days` for an account.
 pub fn cleanup_old_messages(account_id: &str, days: i64) -> Result<i64, String> {
     let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
@@ -504,6 +871,139 @@ pub fn cleanup_old_files(account_id: &str, days: i64) -> Result<Vec<String>, Str
     Ok(paths)
 }
 
+// ============================================================================
+// Message annotations (reactions / flags / notes for review)
+// ============================================================================
+
+/// Reactions are mutually exclusive; starred is a single flag; notes can repeat.
+const REACTION_KINDS: &[&str] = &["thumbs_up", "thumbs_down"];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MessageAnnotation {
+    pub id: i64,
+    pub message_id: i64,
+    pub kind: String, // "thumbs_up" | "thumbs_down" | "starred" | "note"
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AnnotationInput {
+    pub kind: String,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Map user-facing aliases (👍 / 👎 / ⭐ / 📝) onto stored annotation kinds.
+fn normalize_annotation_kind(kind: &str) -> Option<&'static str> {
+    match kind.trim() {
+        "thumbs_up" | "up" | "good" | "👍" => Some("thumbs_up"),
+        "thumbs_down" | "down" | "bad" | "👎" => Some("thumbs_down"),
+        "starred" | "star" | "⭐" => Some("starred"),
+        "note" | "📝" => Some("note"),
+        _ => None,
+    }
+}
+
+/// Attach an annotation to a message. Returns the stored annotation.
+pub fn annotate_message(message_id: i64, input: &AnnotationInput) -> Result<MessageAnnotation, String> {
+    let kind = normalize_annotation_kind(&input.kind)
+        .ok_or_else(|| format!("Unknown annotation '{}': use thumbs_up, thumbs_down, starred or note", input.kind))?;
+    let note = input.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if kind == "note" && note.is_none() {
+        return Err("A note annotation needs text".to_string());
+    }
+
+    let mut conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let exists: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM messages WHERE id = ?1",
+        params![message_id],
+        |row| row.get(0),
+    ).map_err(|e| format!("Lookup message: {}", e))?;
+    if exists == 0 {
+        return Err(format!("Message {} not found", message_id));
+    }
+
+    let tx = conn.transaction().map_err(|e| format!("Begin: {}", e))?;
+    if REACTION_KINDS.contains(&kind) {
+        tx.execute(
+            "DELETE FROM message_annotations WHERE message_id = ?1 AND kind IN ('thumbs_up', 'thumbs_down')",
+            params![message_id],
+        ).map_err(|e| format!("Replace reaction: {}", e))?;
+    } else if kind == "starred" {
+        tx.execute(
+            "DELETE FROM message_annotations WHERE message_id = ?1 AND kind = 'starred'",
+            params![message_id],
+        ).map_err(|e| format!("Replace star: {}", e))?;
+    }
+    tx.execute(
+        "INSERT INTO message_annotations (message_id, kind, note) VALUES (?1, ?2, ?3)",
+        params![message_id, kind, note],
+    ).map_err(|e| format!("Insert annotation: {}", e))?;
+    let id = tx.last_insert_rowid();
+    let annotation = tx.query_row(
+        "SELECT id, message_id, kind, note, created_at FROM message_annotations WHERE id = ?1",
+        params![id],
+        |row| Ok(MessageAnnotation {
+            id: row.get(0)?,
+            message_id: row.get(1)?,
+            kind: row.get(2)?,
+            note: row.get(3)?,
+            created_at: row.get(4)?,
+        }),
+    ).map_err(|e| format!("Read annotation: {}", e))?;
+    tx.commit().map_err(|e| format!("Commit: {}", e))?;
+
+    Ok(annotation)
+}
+
+/// Remove a single annotation by id.
+pub fn remove_annotation(annotation_id: i64) -> Result<(), String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    conn.execute(
+        "DELETE FROM message_annotations WHERE id = ?1",
+        params![annotation_id],
+    ).map_err(|e| format!("Delete annotation: {}", e))?;
+    Ok(())
+}
+
+/// Fill `annotations` on each message (empty vec when none).
+pub fn attach_annotations(messages: &mut [DbMessage]) -> Result<(), String> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let placeholders = vec!["?"; messages.len()].join(",");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, message_id, kind, note, created_at
+         FROM message_annotations
+         WHERE message_id IN ({})
+         ORDER BY created_at ASC, id ASC",
+        placeholders
+    )).map_err(|e| format!("Prepare: {}", e))?;
+
+    let ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+    let rows = stmt.query_map(rusqlite::params_from_iter(ids.iter()), |row| {
+        Ok(MessageAnnotation {
+            id: row.get(0)?,
+            message_id: row.get(1)?,
+            kind: row.get(2)?,
+            note: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }).map_err(|e| format!("Query: {}", e))?;
+
+    let mut by_message: std::collections::HashMap<i64, Vec<MessageAnnotation>> = std::collections::HashMap::new();
+    for row in rows {
+        let annotation = row.map_err(|e| format!("Row: {}", e))?;
+        by_message.entry(annotation.message_id).or_default().push(annotation);
+    }
+    for message in messages.iter_mut() {
+        message.annotations = Some(by_message.remove(&message.id).unwrap_or_default());
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -513,13 +1013,53 @@ pub async fn db_list_accounts() -> Result<Vec<Account>, String> {
     list_accounts()
 }
 
+/// `db_get_messages` result: a plain list for limit/offset callers, a page
+/// with its `next_cursor` once any cursor or filter parameter is given.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(untagged)]
+pub enum MessagesResult {
+    List(Vec<DbMessage>),
+    Page(MessagePage),
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn db_get_messages(
     account_id: String,
     limit: Option<i64>,
     offset: Option<i64>,
-) -> Result<Vec<DbMessage>, String> {
-    get_messages(&account_id, limit.unwrap_or(100), offset.unwrap_or(0))
+    include_annotations: Option<bool>,
+    before_id: Option<i64>,
+    after_id: Option<i64>,
+    from_ts: Option<i64>,
+    to_ts: Option<i64>,
+    msg_type: Option<i32>,
+) -> Result<MessagesResult, String> {
+    let query = MessagePageQuery { before_id, after_id, limit, from_ts, to_ts, msg_type };
+    let keyset = [before_id, after_id, from_ts, to_ts].iter().any(Option::is_some) || msg_type.is_some();
+    let annotate = include_annotations.unwrap_or(false);
+    if keyset {
+        let mut page = get_messages_page(&account_id, &query)?;
+        if annotate {
+            attach_annotations(&mut page.messages)?;
+        }
+        return Ok(MessagesResult::Page(page));
+    }
+    let mut messages = get_messages(&account_id, limit.unwrap_or(100), offset.unwrap_or(0))?;
+    if annotate {
+        attach_annotations(&mut messages)?;
+    }
+    Ok(MessagesResult::List(messages))
+}
+
+#[tauri::command]
+pub async fn db_annotate_message(message_id: i64, annotation: AnnotationInput) -> Result<MessageAnnotation, String> {
+    annotate_message(message_id, &annotation)
+}
+
+#[tauri::command]
+pub async fn db_remove_annotation(annotation_id: i64) -> Result<(), String> {
+    remove_annotation(annotation_id)
 }
 
 #[tauri::command]
@@ -532,6 +1072,28 @@ pub async fn db_set_auto_reply(account_id: String, enabled: bool) -> Result<(),
     set_account_auto_reply(&account_id, enabled)
 }
 
+/// Search file metadata by partial name across sessions (default limit 50).
+#[tauri::command]
+pub async fn db_search_files(
+    query: String,
+    session_id: Option<String>,
+    mime_type: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<DbFile>, String> {
+    search_files(
+        &query,
+        session_id.as_deref().filter(|s| !s.is_empty()),
+        mime_type.as_deref().filter(|m| !m.is_empty()),
+        limit.unwrap_or(50).clamp(1, 1000),
+    )
+}
+
+/// Exact file-name lookup across sessions.
+#[tauri::command]
+pub async fn db_get_file_by_name(file_name: String) -> Result<Vec<DbFile>, String> {
+    get_files_by_name(&file_name)
+}
+
 // ============================================================================
 // Conversation History (for Agent multi-turn)
 // ============================================================================
@@ -595,6 +1157,28 @@ pub fn clear_messages(account_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Copy conversation history from one account to another, up to and including
+/// `up_to_id` (in conversation order). Used to fork a session. Returns rows copied.
+pub fn copy_conversation_until(from_account: &str, to_account: &str, up_to_id: i64) -> Result<usize, String> {
+    let conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let cutoff: Option<String> = conn
+        .query_row(
+            "SELECT created_at FROM conversation_history WHERE id = ?1 AND account_id = ?2",
+            params![up_to_id, from_account],
+            |row| row.get(0),
+        )
+        .ok();
+    let cutoff = cutoff.ok_or_else(|| format!("Message {} not found in '{}'", up_to_id, from_account))?;
+
+    conn.execute(
+        "INSERT INTO conversation_history (account_id, role, content, created_at)
+         SELECT ?2, role, content, created_at FROM conversation_history
+         WHERE account_id = ?1 AND (created_at < ?3 OR (created_at = ?3 AND id <= ?4))
+         ORDER BY created_at ASC, id ASC",
+        params![from_account, to_account, cutoff, up_to_id],
+    ).map_err(|e| format!("Copy conversation: {}", e))
+}
+
 /// Delete the oldest N messages from conversation history for an account.
 /// Used by memory compaction to remove messages that have been summarized.
 pub fn delete_old_messages(account_id: &str, count: i64) -> Result<(), String> {
@@ -612,6 +1196,43 @@ pub fn delete_old_messages(account_id: &str, count: i64) -> Result<(), String> {
     Ok(())
 }
 
+fn replace_history_with_summary_in(
+    conn: &mut Connection,
+    account_id: &str,
+    summary: &str,
+    keep_recent: i64,
+) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(|e| format!("Begin: {}", e))?;
+    let removed = tx.execute(
+        "DELETE FROM conversation_history
+         WHERE account_id = ?1 AND id NOT IN (
+             SELECT id FROM conversation_history
+             WHERE account_id = ?1
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?2
+         )",
+        params![account_id, keep_recent],
+    ).map_err(|e| format!("Delete summarized messages: {}", e))?;
+    // Date the summary just before the oldest kept message so it sorts first
+    tx.execute(
+        "INSERT INTO conversation_history (account_id, role, content, created_at)
+         VALUES (?1, 'system', ?2, COALESCE(
+             (SELECT datetime(MIN(created_at), '-1 second') FROM conversation_history WHERE account_id = ?1),
+             datetime('now')
+         ))",
+        params![account_id, format!("Previous conversation summary: {}", summary)],
+    ).map_err(|e| format!("Insert summary: {}", e))?;
+    tx.commit().map_err(|e| format!("Commit: {}", e))?;
+    Ok(removed)
+}
+
+/// Atomically replace all but the newest `keep_recent` messages with a single
+/// system summary message at the start of history. Returns messages removed.
+pub fn replace_history_with_summary(account_id: &str, summary: &str, keep_recent: i64) -> Result<usize, String> {
+    let mut conn = DB.lock().map_err(|e| format!("DB lock: {}", e))?;
+    replace_history_with_summary_in(&mut conn, account_id, summary, keep_recent)
+}
+
 // ============================================================================
 // Memory (long-term key-value store)
 // ============================================================================
@@ -645,3 +1266,213 @@ pub fn memory_recall(query: &str) -> Result<Vec<(String, String)>, String> {
     }
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        let files = [
+            ("chat:a", "report_2024.pdf", "application/pdf"),
+            ("chat:a", "photo.png", "image/png"),
+            ("chat:b", "Report-final.PDF", "application/pdf"),
+            ("chat:b", "report_2024.pdf", "application/pdf"),
+            ("chat:b", "100%_done.txt", "text/plain"),
+        ];
+        for (account, name, mime) in files {
+            conn.execute(
+                "INSERT INTO files (account_id, file_name, file_path, mime_type) VALUES (?1, ?2, ?3, ?4)",
+                params![account, name, format!("/tmp/{}", name), mime],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn names(files: &[DbFile]) -> Vec<(&str, &str)> {
+        files.iter().map(|f| (f.account_id.as_str(), f.file_name.as_str())).collect()
+    }
+
+    #[test]
+    fn test_search_files_across_sessions() {
+        let conn = test_db();
+        // LIKE is case-insensitive for ASCII; newest first
+        let all = search_files_in(&conn, "report", None, None, 50).unwrap();
+        assert_eq!(
+            names(&all),
+            vec![("chat:b", "report_2024.pdf"), ("chat:b", "Report-final.PDF"), ("chat:a", "report_2024.pdf")]
+        );
+
+        let a = search_files_in(&conn, "report", Some("chat:a"), None, 50).unwrap();
+        assert_eq!(names(&a), vec![("chat:a", "report_2024.pdf")]);
+
+        assert_eq!(search_files_in(&conn, "", None, Some("image/*"), 50).unwrap().len(), 1);
+        assert_eq!(search_files_in(&conn, "", None, Some("application/pdf"), 2).unwrap().len(), 2);
+        // Wildcards in the query are literal
+        assert_eq!(names(&search_files_in(&conn, "100%", None, None, 50).unwrap()), vec![("chat:b", "100%_done.txt")]);
+        assert_eq!(search_files_in(&conn, "t_2", None, None, 50).unwrap().len(), 2);
+        assert!(search_files_in(&conn, "r_2", None, None, 50).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_files_by_name() {
+        let conn = test_db();
+        let exact = files_by_name_in(&conn, "report_2024.pdf").unwrap();
+        assert_eq!(names(&exact), vec![("chat:b", "report_2024.pdf"), ("chat:a", "report_2024.pdf")]);
+        assert!(files_by_name_in(&conn, "report").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_messages() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute("INSERT INTO accounts (id) VALUES ('chat:a')", []).unwrap();
+        for content in ["deploy the staging cluster", "lunch?", "deploy failed again, deploy logs attached"] {
+            conn.execute("INSERT INTO messages (account_id, content) VALUES ('chat:a', ?1)", params![content])
+                .unwrap();
+        }
+
+        let hits = search_messages_in(&conn, "deploy", 10).unwrap();
+        let contents: Vec<&str> = hits.iter().map(|h| h.message.content.as_str()).collect();
+        assert_eq!(contents, vec!["deploy failed again, deploy logs attached", "deploy the staging cluster"]);
+        assert!(hits[0].score > hits[1].score);
+        assert!(hits[1].snippet.as_deref().unwrap().contains("[deploy]"));
+        // Operators are quoted, not parsed
+        assert_eq!(search_messages_in(&conn, "lunch OR", 10).unwrap().len(), 1);
+        assert!(search_messages_in(&conn, "  ", 10).unwrap().is_empty());
+
+        // Triggers keep the index in sync with edits and deletes
+        conn.execute("UPDATE messages SET content = 'rollback done' WHERE content LIKE 'deploy the%'", []).unwrap();
+        conn.execute("DELETE FROM messages WHERE content = 'lunch?'", []).unwrap();
+        assert_eq!(search_messages_in(&conn, "deploy", 10).unwrap().len(), 1);
+        assert_eq!(search_messages_in(&conn, "rollback", 10).unwrap().len(), 1);
+        assert!(search_messages_in(&conn, "lunch", 10).unwrap().is_empty());
+    }
+
+    fn page_db(count: i64) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute("INSERT INTO accounts (id) VALUES ('chat:a'), ('chat:b')", []).unwrap();
+        conn.execute(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+             INSERT INTO messages (account_id, content, msg_type, created_at)
+             SELECT CASE WHEN i % 10 = 0 THEN 'chat:b' ELSE 'chat:a' END, 'm' || i,
+                    CASE WHEN i % 3 = 0 THEN 3 ELSE 1 END, datetime(1700000000 + i * 60, 'unixepoch')
+             FROM n",
+            params![count],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_messages_page() {
+        let conn = page_db(100);
+        let ids = |page: &MessagePage| page.messages.iter().map(|m| m.id).collect::<Vec<_>>();
+        let query = |q: MessagePageQuery| get_messages_page_in(&conn, "chat:a", &q).unwrap();
+
+        let newest = query(MessagePageQuery { limit: Some(3), ..Default::default() });
+        assert_eq!(ids(&newest), vec![97, 98, 99]);
+        assert_eq!(newest.next_cursor, Some(97));
+        let older = query(MessagePageQuery { before_id: newest.next_cursor, limit: Some(3), ..Default::default() });
+        assert_eq!(ids(&older), vec![94, 95, 96]);
+
+        let forward = query(MessagePageQuery { after_id: Some(95), limit: Some(3), ..Default::default() });
+        assert_eq!(ids(&forward), vec![96, 97, 98]);
+        assert_eq!(forward.next_cursor, Some(98));
+        let last = query(MessagePageQuery { after_id: Some(98), limit: Some(3), ..Default::default() });
+        assert_eq!((ids(&last), last.next_cursor), (vec![99], None));
+
+        // Minutes 10..=20 after the epoch base, images only
+        let filtered = query(MessagePageQuery {
+            from_ts: Some(1700000000 + 10 * 60),
+            to_ts: Some(1700000000 + 21 * 60),
+            msg_type: Some(3),
+            ..Default::default()
+        });
+        assert_eq!((ids(&filtered), filtered.next_cursor), (vec![12, 15, 18], None));
+
+        let plan: Vec<String> = conn
+            .prepare("EXPLAIN QUERY PLAN SELECT id FROM messages WHERE account_id = ?1 AND id < ?2 ORDER BY id DESC")
+            .unwrap()
+            .query_map(params!["chat:a", 50], |row| row.get(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(plan.iter().any(|step| step.contains("idx_messages_account_id")), "{:?}", plan);
+    }
+
+    /// `cargo test --release -- --ignored bench_messages_page_depth --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_messages_page_depth() {
+        let conn = page_db(200_000);
+        let time_page = |before_id: Option<i64>| {
+            let q = MessagePageQuery { before_id, limit: Some(50), ..Default::default() };
+            let start = std::time::Instant::now();
+            for _ in 0..100 {
+                assert_eq!(get_messages_page_in(&conn, "chat:a", &q).unwrap().messages.len(), 50);
+            }
+            start.elapsed()
+        };
+        let (first, deep) = (time_page(None), time_page(Some(1_000)));
+        let offset = {
+            let start = std::time::Instant::now();
+            conn.prepare("SELECT id FROM messages WHERE account_id = 'chat:a' ORDER BY id DESC LIMIT 50 OFFSET 178000")
+                .unwrap()
+                .query_map([], |row| row.get::<_, i64>(0))
+                .unwrap()
+                .for_each(drop);
+            start.elapsed() * 100
+        };
+        println!("100 pages: newest {:?}, 178k deep {:?}, OFFSET equivalent {:?}", first, deep, offset);
+        assert!(deep < first * 3 + std::time::Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_missing_tables() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(missing_tables_in(&conn).unwrap(), TABLES.to_vec());
+        conn.execute_batch(SCHEMA).unwrap();
+        assert!(missing_tables_in(&conn).unwrap().is_empty());
+        conn.execute_batch("DROP TABLE message_annotations").unwrap();
+        assert_eq!(missing_tables_in(&conn).unwrap(), vec!["message_annotations"]);
+    }
+
+    #[test]
+    fn test_replace_history_with_summary() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        for i in 0..8 {
+            conn.execute(
+                "INSERT INTO conversation_history (account_id, role, content, created_at)
+                 VALUES ('chat:a', 'user', ?1, datetime('2024-01-01 00:00:00', ?2))",
+                params![format!("m{}", i), format!("+{} minutes", i)],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO conversation_history (account_id, role, content) VALUES ('chat:b', 'user', 'other')",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(replace_history_with_summary_in(&mut conn, "chat:a", "did things", 5).unwrap(), 3);
+        let rows: Vec<(String, String)> = conn
+            .prepare("SELECT role, content FROM conversation_history WHERE account_id = 'chat:a' ORDER BY created_at, id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(rows[0], ("system".to_string(), "Previous conversation summary: did things".to_string()));
+        let kept: Vec<&str> = rows[1..].iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(kept, vec!["m3", "m4", "m5", "m6", "m7"]);
+        let other: i64 = conn
+            .query_row("SELECT COUNT(*) FROM conversation_history WHERE account_id = 'chat:b'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(other, 1);
+    }
+}
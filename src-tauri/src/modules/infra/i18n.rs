@@ -1,5 +1,8 @@
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Tray text structure
 #[derive(Debug, Clone)]
@@ -13,6 +16,9 @@ pub struct TrayTexts {
     pub no_account: String,
     pub unknown_quota: String,
     pub forbidden: String,
+    pub pause_auto_reply: String,
+    pub pause_cron: String,
+    pub run_heartbeat: String,
 }
 
 /// Load translations from JSON
@@ -53,5 +59,374 @@ pub fn get_tray_texts(lang: &str) -> TrayTexts {
         no_account: t.get("no_account").cloned().unwrap_or_else(|| "No Account".to_string()),
         unknown_quota: t.get("unknown_quota").cloned().unwrap_or_else(|| "Unknown".to_string()),
         forbidden: t.get("forbidden").cloned().unwrap_or_else(|| "Account Forbidden".to_string()),
+        pause_auto_reply: t.get("pause_auto_reply").cloned().unwrap_or_else(|| "Pause Auto-Reply".to_string()),
+        pause_cron: t.get("pause_cron").cloned().unwrap_or_else(|| "Pause Cron Scheduler".to_string()),
+        run_heartbeat: t.get("run_heartbeat").cloned().unwrap_or_else(|| "Run Heartbeat Now".to_string()),
+    }
+}
+
+// ============================================================================
+// Backend error catalog
+// ============================================================================
+
+/// A user-facing backend message with per-locale templates.
+/// Placeholders use `{name}` syntax and are filled by `t()`.
+struct CatalogEntry {
+    key: &'static str,
+    en: &'static str,
+    zh: &'static str,
+}
+
+const ERROR_CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        key: "error.path_traversal",
+        en: "Invalid path: directory traversal is not allowed",
+        zh: "非法路径: 不允许目录遍历",
+    },
+    CatalogEntry {
+        key: "error.sensitive_path",
+        en: "Access denied: system path is protected ({prefix})",
+        zh: "安全拒绝: 禁止访问系统敏感路径 ({prefix})",
+    },
+    CatalogEntry {
+        key: "error.file_write_failed",
+        en: "Failed to write file: {error}",
+        zh: "写入文件失败: {error}",
+    },
+    CatalogEntry {
+        key: "error.file_read_failed",
+        en: "Failed to read file: {error}",
+        zh: "读取文件失败: {error}",
+    },
+    CatalogEntry {
+        key: "error.sandbox_write_denied",
+        en: "❌ Security restriction: files can only be written inside ~/{dir}. Please use a path in that directory.\nCurrent path: {path}",
+        zh: "❌ 安全限制: 只能在 ~/{dir} 目录下写入文件。请使用该目录下的路径。\n当前路径: {path}",
+    },
+    CatalogEntry {
+        key: "error.cron_invalid_expr",
+        en: "Invalid cron expression: {error}",
+        zh: "无效的 cron 表达式: {error}",
+    },
+    CatalogEntry {
+        key: "error.cron_no_script",
+        en: "Task has no script to execute",
+        zh: "任务没有可执行的脚本",
+    },
+    CatalogEntry {
+        key: "error.unsupported_locale",
+        en: "Unsupported locale: {locale}",
+        zh: "不支持的语言: {locale}",
+    },
+];
+
+/// Locales with a dedicated column in the error catalog.
+const CATALOG_LOCALES: &[&str] = &["en", "zh"];
+
+/// Active locale for backend messages. Initialized from `AppConfig.language`.
+/// Locales without a catalog column use their loaded strings, then English.
+static CURRENT_LOCALE: Lazy<RwLock<String>> = Lazy::new(|| {
+    let lang = crate::modules::config::load_app_config()
+        .map(|c| c.language)
+        .unwrap_or_else(|_| "zh".to_string());
+    RwLock::new(lang.trim().to_string())
+});
+
+/// Map a UI language code to a catalog locale. Anything that is not
+/// Chinese falls back to English.
+fn normalize_locale(lang: &str) -> &'static str {
+    if lang.to_lowercase().starts_with("zh") {
+        "zh"
+    } else {
+        "en"
+    }
+}
+
+/// Switch the active backend locale (does not persist).
+pub fn set_locale(lang: &str) {
+    *CURRENT_LOCALE.write() = lang.trim().to_string();
+}
+
+pub fn current_locale() -> String {
+    CURRENT_LOCALE.read().clone()
+}
+
+/// Render a catalog message for an explicit locale.
+fn translate(locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+    let template = match ERROR_CATALOG.iter().find(|e| e.key == key) {
+        Some(entry) => match normalize_locale(locale) {
+            "zh" => entry.zh,
+            _ => entry.en,
+        },
+        None => key,
+    };
+    fill(template.to_string(), args)
+}
+
+/// Translate a backend message key into the active locale, filling `{name}`
+/// placeholders from `args`. Unknown locales fall back to English.
+pub fn t(key: &str, args: &[(&str, &str)]) -> String {
+    let locale = current_locale();
+    match loaded_string(&locale, key) {
+        Some(template) => fill(template, args),
+        None => translate(&locale, key, args),
+    }
+}
+
+fn fill(template: String, args: &[(&str, &str)]) -> String {
+    let mut out = template;
+    for (name, value) in args {
+        out = out.replace(&format!("{{{}}}", name), value);
+    }
+    out
+}
+
+// ============================================================================
+// Locales loaded at runtime
+// ============================================================================
+
+/// Strings from `~/.helix/locales/{locale}.json`, keyed by locale then key.
+/// They override the catalog and the bundled UI strings for that locale.
+static TRANSLATIONS: Lazy<Mutex<HashMap<String, HashMap<String, String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Bundled English UI strings flattened to dotted keys (`tray.quit`).
+static BUILTIN_ENGLISH: Lazy<HashMap<String, String>> = Lazy::new(|| {
+    let bundle: Value = serde_json::from_str(include_str!("../../../../src/locales/en.json"))
+        .unwrap_or_else(|_| serde_json::json!({}));
+    let mut map = HashMap::new();
+    flatten_into(&mut map, "", &bundle);
+    map
+});
+
+fn flatten_into(map: &mut HashMap<String, String>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(map, &path, child);
+            }
+        }
+        Value::String(text) => {
+            map.insert(prefix.to_string(), text.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Get the locales directory: ~/.helix/locales/
+fn get_locales_dir() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(home.join(".helix").join("locales"))
+}
+
+/// Locale codes name files, so only `zh-TW`/`pt_BR`-style names are allowed.
+fn validate_locale_name(locale: &str) -> Result<&str, String> {
+    let locale = locale.trim();
+    let valid = !locale.is_empty()
+        && locale.len() <= 32
+        && locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(locale)
+    } else {
+        Err(t("error.unsupported_locale", &[("locale", locale)]))
+    }
+}
+
+/// Parse a flat `{key: value}` locale file; non-string values are ignored.
+fn parse_locale_file(content: &str) -> Result<HashMap<String, String>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid locale JSON: {}", e))?;
+    let object = value.as_object().ok_or("Locale file must be a JSON object of strings")?;
+    Ok(object
+        .iter()
+        .filter_map(|(key, value)| value.as_str().map(|text| (key.clone(), text.to_string())))
+        .collect())
+}
+
+/// Merge `{dir}/{locale}.json` into the loaded strings. Returns the number
+/// of strings read.
+fn load_locale_from(dir: &Path, locale: &str) -> Result<usize, String> {
+    let locale = validate_locale_name(locale)?;
+    let path = dir.join(format!("{}.json", locale));
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Read {}: {}", path.display(), e))?;
+    let strings = parse_locale_file(&content)?;
+    let count = strings.len();
+    TRANSLATIONS.lock().entry(locale.to_string()).or_default().extend(strings);
+    tracing::info!("[i18n] Loaded {} strings for locale {}", count, locale);
+    Ok(count)
+}
+
+fn loaded_string(locale: &str, key: &str) -> Option<String> {
+    TRANSLATIONS.lock().get(locale).and_then(|strings| strings.get(key)).cloned()
+}
+
+/// Look a key up for `locale`: loaded strings first, then the catalog, then
+/// the built-in English catalog and UI strings.
+fn lookup(key: &str, locale: &str) -> Option<String> {
+    if let Some(text) = loaded_string(locale, key) {
+        return Some(text);
+    }
+    let entry = ERROR_CATALOG.iter().find(|e| e.key == key);
+    if let Some(entry) = entry.filter(|_| normalize_locale(locale) == "zh") {
+        return Some(entry.zh.to_string());
+    }
+    entry.map(|e| e.en.to_string()).or_else(|| BUILTIN_ENGLISH.get(key).cloned())
+}
+
+fn list_locales_in(dir: &Path) -> Vec<String> {
+    let mut locales: Vec<String> = CATALOG_LOCALES.iter().map(|l| l.to_string()).collect();
+    locales.extend(TRANSLATIONS.lock().keys().cloned());
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    if validate_locale_name(stem).is_ok() {
+                        locales.push(stem.to_string());
+                    }
+                }
+            }
+        }
+    }
+    locales.sort();
+    locales.dedup();
+    locales
+}
+
+/// A locale can be selected when it has a catalog column (including regional
+/// variants such as `zh-TW`), loaded strings, or a file in `dir`.
+fn check_supported_locale<'a>(dir: &Path, locale: &'a str) -> Result<&'a str, String> {
+    let locale = validate_locale_name(locale)?;
+    let base = locale.split(['-', '_']).next().unwrap_or(locale).to_lowercase();
+    if CATALOG_LOCALES.contains(&base.as_str()) || list_locales_in(dir).iter().any(|l| l == locale) {
+        Ok(locale)
+    } else {
+        Err(t("error.unsupported_locale", &[("locale", locale)]))
+    }
+}
+
+/// Set the backend locale and persist it to `AppConfig.language`.
+#[tauri::command]
+pub async fn i18n_set_locale(locale: String) -> Result<(), String> {
+    let locale = check_supported_locale(&get_locales_dir()?, &locale)?;
+    let mut config = crate::modules::config::load_app_config()?;
+    config.language = locale.to_string();
+    crate::modules::config::save_app_config(&config)?;
+    set_locale(locale);
+    Ok(())
+}
+
+/// Load `~/.helix/locales/{locale}.json`, merging it over strings already
+/// loaded for that locale. Returns the number of strings read.
+#[tauri::command]
+pub async fn i18n_load_locale(locale: String) -> Result<usize, String> {
+    load_locale_from(&get_locales_dir()?, &locale)
+}
+
+/// Translate `key` into `locale` (the active locale when omitted), falling
+/// back to built-in English.
+#[tauri::command]
+pub async fn i18n_get(key: String, locale: Option<String>) -> Result<String, String> {
+    let locale = locale.unwrap_or_else(current_locale);
+    lookup(&key, locale.trim()).ok_or_else(|| format!("Unknown translation key: {}", key))
+}
+
+/// Built-in locales plus those loaded or available in ~/.helix/locales/.
+#[tauri::command]
+pub async fn i18n_list_locales() -> Result<Vec<String>, String> {
+    Ok(list_locales_in(&get_locales_dir()?))
+}
+
+/// Switch the backend locale for this run without saving it to the config,
+/// loading its locale file first when there is one and it is not loaded yet.
+#[tauri::command]
+pub async fn i18n_set_active_locale(locale: String) -> Result<(), String> {
+    let locale = validate_locale_name(&locale)?.to_string();
+    let builtin = normalize_locale(&locale) == "zh" || locale.to_lowercase().starts_with("en");
+    if !builtin && !TRANSLATIONS.lock().contains_key(&locale) {
+        load_locale_from(&get_locales_dir()?, &locale)
+            .map_err(|_| t("error.unsupported_locale", &[("locale", &locale)]))?;
+    }
+    set_locale(&locale);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholder_names(template: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let after = &rest[start + 1..];
+            match after.find('}') {
+                Some(end) => {
+                    names.push(after[..end].to_string());
+                    rest = &after[end + 1..];
+                }
+                None => break,
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn test_catalog_messages_are_translated() {
+        for entry in ERROR_CATALOG {
+            let names = placeholder_names(entry.en);
+            let args: Vec<(&str, &str)> = names.iter().map(|n| (n.as_str(), "x")).collect();
+            for locale in CATALOG_LOCALES {
+                let msg = translate(locale, entry.key, &args);
+                assert_ne!(msg, entry.key, "{} returned raw key for {}", entry.key, locale);
+                assert!(!msg.contains('{'), "{} left a placeholder for {}", entry.key, locale);
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_fallback() {
+        assert_eq!(normalize_locale("zh-TW"), "zh");
+        assert_eq!(normalize_locale("tr"), "en");
+        assert_eq!(
+            translate("en", "error.cron_no_script", &[]),
+            "Task has no script to execute"
+        );
+        assert_eq!(translate("zh-TW", "error.cron_no_script", &[]), "任务没有可执行的脚本");
+    }
+
+    #[test]
+    fn test_load_locale_file() {
+        let dir = std::env::temp_dir().join(format!("helix-locales-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("fr-test.json"),
+            r#"{"error.cron_no_script": "La tâche n'a aucun script", "tray.quit": "Quitter", "count": 3}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.json"), "[1, 2]").unwrap();
+
+        assert_eq!(load_locale_from(&dir, "fr-test").unwrap(), 2);
+        assert_eq!(lookup("error.cron_no_script", "fr-test").as_deref(), Some("La tâche n'a aucun script"));
+        assert_eq!(lookup("tray.quit", "fr-test").as_deref(), Some("Quitter"));
+        // Keys missing from the file fall back to built-in English
+        assert_eq!(lookup("error.cron_invalid_expr", "fr-test").as_deref(), Some("Invalid cron expression: {error}"));
+        assert_eq!(lookup("tray.quit", "de").as_deref(), Some("Quit Application"));
+        assert_eq!(lookup("error.cron_no_script", "zh").as_deref(), Some("任务没有可执行的脚本"));
+        assert_eq!(lookup("no.such.key", "fr-test"), None);
+
+        assert!(load_locale_from(&dir, "broken").is_err());
+        assert!(load_locale_from(&dir, "../etc/passwd").is_err());
+        assert!(load_locale_from(&dir, "missing").is_err());
+        let locales = list_locales_in(&dir);
+        assert!(["en", "zh", "fr-test", "broken"].iter().all(|l| locales.contains(&l.to_string())));
+
+        assert_eq!(check_supported_locale(&dir, " fr-test ").unwrap(), "fr-test");
+        assert_eq!(check_supported_locale(&dir, "zh-TW").unwrap(), "zh-TW");
+        assert_eq!(check_supported_locale(&dir, "en_US").unwrap(), "en_US");
+        assert!(check_supported_locale(&dir, "klingon").is_err());
+        assert!(check_supported_locale(&dir, "").is_err());
+        assert!(check_supported_locale(&dir, "../fr-test").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
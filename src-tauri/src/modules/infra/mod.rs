@@ -6,4 +6,7 @@ pub mod security;
 pub mod notifications;
 pub mod i18n;
 pub mod api_server;
+pub mod shutdown;
+pub mod diagnostics;
 
+pub mod self_test;
@@ -1,4 +1,4 @@
-//! Notification integrations — Feishu & DingTalk webhook senders.
+//! Notification integrations — Feishu & DingTalk webhook senders, SMTP email, WeCom.
 //!
 //! Provides a unified `send_notification(channel, title, body)` API
 //! used by cron jobs, hooks, and other modules.
@@ -14,8 +14,14 @@ use super::config;
 // ============================================================================
 
 /// Send a notification to the specified channel.
-/// `channel` — "feishu" or "dingtalk"
+/// `channel` — "feishu", "dingtalk", "smtp" or "wecom"
 pub async fn send_notification(channel: &str, title: &str, body: &str) -> Result<(), String> {
+    match channel {
+        "smtp" => return crate::modules::smtp::send_notification(title, body).await,
+        "wecom" => return crate::modules::wecom::send_notification(title, body).await,
+        _ => {}
+    }
+
     let webhook_url = get_webhook_url(channel)?;
 
     match channel {
@@ -78,6 +84,7 @@ pub async fn send_feishu(webhook_url: &str, title: &str, body: &str) -> Result<(
         return Err(format!("Feishu webhook returned {}: {}", status, text));
     }
 
+    crate::modules::channels::record_outbound("feishu");
     info!("Feishu notification sent: {}", title);
     Ok(())
 }
@@ -110,6 +117,7 @@ pub async fn send_dingtalk(webhook_url: &str, title: &str, body: &str) -> Result
         return Err(format!("DingTalk webhook returned {}: {}", status, text));
     }
 
+    crate::modules::channels::record_outbound("dingtalk");
     info!("DingTalk notification sent: {}", title);
     Ok(())
 }
@@ -118,6 +126,15 @@ pub async fn send_dingtalk(webhook_url: &str, title: &str, body: &str) -> Result
 // Config Helpers
 // ============================================================================
 
+/// Whether a webhook URL (or SMTP / WeCom config) is configured for the given channel.
+pub fn is_channel_configured(channel: &str) -> bool {
+    match channel {
+        "smtp" => crate::modules::smtp::is_configured(),
+        "wecom" => crate::modules::wecom::is_configured(),
+        _ => get_webhook_url(channel).is_ok(),
+    }
+}
+
 fn get_webhook_url(channel: &str) -> Result<String, String> {
     let cfg = config::load_app_config()?;
 
@@ -4,12 +4,14 @@
 //! insecure permissions, dangerous tool configs, and generates a structured report.
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use tracing::info;
 
 use super::config::get_data_dir;
 use super::config::load_app_config;
+use crate::models::AppConfig;
 
 // ============================================================================
 // Types
@@ -59,6 +61,24 @@ pub struct SystemInfo {
     pub data_dir: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityIssue {
+    pub severity: Severity,
+    pub category: String,
+    pub description: String,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityReport {
+    pub generated_at: String,
+    pub issues: Vec<SecurityIssue>,
+    pub recommendations: Vec<String>,
+    /// "high" with any critical issue, "medium" with any warning, otherwise "low"
+    pub risk_level: String,
+}
+
 // ============================================================================
 // Audit Checks
 // ============================================================================
@@ -280,6 +300,242 @@ fn check_environment() -> Vec<AuditFinding> {
     findings
 }
 
+// ============================================================================
+// Security Report
+// ============================================================================
+
+/// An issue plus the recommendation that resolves it.
+type ReportItem = (SecurityIssue, String);
+
+fn report_item(severity: Severity, category: &str, description: String, path: Option<String>, fix: &str) -> ReportItem {
+    (SecurityIssue { severity, category: category.into(), description, path }, fix.into())
+}
+
+/// Names of the config fields that hold a credential.
+fn configured_secrets(config: &AppConfig) -> Vec<String> {
+    let mut secrets = Vec::new();
+    if !config.ai_config.api_key.is_empty() {
+        secrets.push("ai_config.api_key".to_string());
+    }
+    for provider in config.providers.iter().filter(|p| p.keys.iter().any(|k| !k.is_empty())) {
+        secrets.push(format!("providers[{}].keys", provider.name));
+    }
+    if config.provider_keys.values().flatten().any(|k| !k.is_empty()) {
+        secrets.push("provider_keys".to_string());
+    }
+    if config.search_api_key.as_deref().is_some_and(|k| !k.is_empty()) {
+        secrets.push("search_api_key".to_string());
+    }
+    if config.github_token.as_deref().is_some_and(|k| !k.is_empty()) {
+        secrets.push("github_token".to_string());
+    }
+    secrets
+}
+
+/// Variable names that usually hold credentials.
+fn is_sensitive_env_name(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    ["SECRET", "KEY", "TOKEN"].iter().any(|word| upper.contains(word))
+}
+
+/// Credentials Helix keeps outside config.json, plus those inlined in the
+/// kubeconfig that `kubectl_exec` runs against, as `(file, secret names)`.
+fn stored_credential_files() -> Vec<(PathBuf, Vec<String>)> {
+    let mut files = Vec::new();
+    let mut add = |path: Result<PathBuf, String>, name: &str, value: Option<String>| {
+        if let (Ok(path), Some(_)) = (path, value.filter(|v| !v.trim().is_empty())) {
+            files.push((path, vec![name.to_string()]));
+        }
+    };
+    add(
+        crate::modules::smtp::get_smtp_path(),
+        "smtp.password",
+        crate::modules::smtp::load_config().ok().map(|c| c.password),
+    );
+    add(
+        crate::modules::wecom::get_wecom_path(),
+        "wecom.corp_secret",
+        crate::modules::wecom::load_config().ok().map(|c| c.corp_secret),
+    );
+    add(
+        crate::modules::github::get_config_path(),
+        "github.token",
+        crate::modules::github::load_config().ok().map(|c| c.token),
+    );
+    let (kubeconfig, inline) = crate::modules::kubeconfig::inline_credentials(None);
+    if !inline.is_empty() {
+        files.push((kubeconfig, inline));
+    }
+    files
+}
+
+/// Plaintext secrets in `file`, escalated to critical when the file is world-readable.
+fn report_secret_file(file: &Path, secrets: &[String], fix: &str) -> Vec<ReportItem> {
+    let mut items = Vec::new();
+    let path = Some(file.to_string_lossy().to_string());
+
+    if !secrets.is_empty() {
+        items.push(report_item(
+            Severity::Warning,
+            "credentials",
+            format!("{} 个密钥未加密，以明文保存在配置文件中: {}", secrets.len(), secrets.join(", ")),
+            path.clone(),
+            fix,
+        ));
+    }
+
+    #[cfg(unix)]
+    if let Ok(meta) = std::fs::metadata(file) {
+        let mode = meta.permissions().mode();
+        if mode & 0o004 != 0 {
+            let severity = if secrets.is_empty() { Severity::Warning } else { Severity::Critical };
+            items.push(report_item(
+                severity,
+                "filesystem",
+                format!("配置文件权限为 {:o}，所有用户均可读取", mode & 0o777),
+                path,
+                &format!("运行: chmod 600 {:?}", file),
+            ));
+        }
+    }
+
+    items
+}
+
+fn report_config_file(secrets: &[String]) -> Vec<ReportItem> {
+    let Ok(config_path) = get_data_dir().map(|d| d.join(super::config::CONFIG_FILE)) else {
+        return Vec::new();
+    };
+    report_secret_file(&config_path, secrets, "通过环境变量管理提供 API Key，或确保配置文件仅当前用户可读")
+}
+
+fn report_credential_files() -> Vec<ReportItem> {
+    stored_credential_files()
+        .iter()
+        .flat_map(|(file, secrets)| report_secret_file(file, secrets, "确保保存凭据的文件仅当前用户可读 (chmod 600)"))
+        .collect()
+}
+
+fn report_shell_tools() -> Vec<ReportItem> {
+    vec![report_item(
+        Severity::Warning,
+        "tools",
+        "shell_exec 未配置命令白名单，Agent 可以执行任意 shell 命令".into(),
+        None,
+        "只向可信的渠道和联系人开放 Agent，并定期查看 shell_exec 调用记录",
+    )]
+}
+
+fn report_api_server() -> Vec<ReportItem> {
+    let Some(port) = super::api_server::listening_port() else {
+        return Vec::new();
+    };
+    vec![report_item(
+        Severity::Critical,
+        "network",
+        format!("本地 API 服务监听 0.0.0.0:{} 且未配置鉴权，局域网内任何人都可调用 /api/tools/shell_exec", port),
+        None,
+        &format!("在防火墙中阻止外部访问端口 {}", port),
+    )]
+}
+
+fn report_shell_commands() -> Vec<ReportItem> {
+    crate::modules::commands::list_custom_commands()
+        .into_iter()
+        .filter(|cmd| cmd.kind == "shell")
+        .map(|cmd| {
+            report_item(
+                Severity::Warning,
+                "commands",
+                format!("自定义命令 '/{}' 会执行 shell 脚本", cmd.name),
+                None,
+                "审查 shell 类型的自定义命令模板，删除不再使用的命令",
+            )
+        })
+        .collect()
+}
+
+fn report_shell_skills() -> Vec<ReportItem> {
+    crate::modules::skills::list_all_skills()
+        .into_iter()
+        .filter(|skill| skill.enabled && skill.permissions.iter().any(|p| p.eq_ignore_ascii_case("shell")))
+        .map(|skill| {
+            report_item(
+                Severity::Warning,
+                "skills",
+                format!("技能 '{}' 声明了 shell 权限", skill.name),
+                Some(skill.path),
+                "审查声明 shell 权限的技能内容，禁用不再使用的技能",
+            )
+        })
+        .collect()
+}
+
+fn report_environment() -> Vec<ReportItem> {
+    let mut items = Vec::new();
+    let managed = crate::modules::environments::load_envs().unwrap_or_default();
+    let envs_path = crate::modules::environments::get_envs_path().ok().map(|p| p.to_string_lossy().to_string());
+
+    let unmasked: Vec<&str> = managed
+        .iter()
+        .filter(|v| !v.secret && is_sensitive_env_name(&v.key))
+        .map(|v| v.key.as_str())
+        .collect();
+    if !unmasked.is_empty() {
+        items.push(report_item(
+            Severity::Warning,
+            "environment",
+            format!("环境变量 {} 看起来是密钥，但未标记为 secret，值会在界面中明文显示", unmasked.join(", ")),
+            envs_path,
+            "在环境变量管理中将这些变量标记为 secret",
+        ));
+    }
+
+    let mut inherited: Vec<String> = std::env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| is_sensitive_env_name(name) && !managed.iter().any(|v| &v.key == name))
+        .collect();
+    inherited.sort();
+    if !inherited.is_empty() {
+        items.push(report_item(
+            Severity::Info,
+            "environment",
+            format!("进程环境中有 {} 个疑似密钥的变量 ({})，shell_exec 输出和日志可能泄露其值", inherited.len(), inherited.join(", ")),
+            None,
+            "避免让 Agent 执行 env/printenv 等会回显环境变量的命令",
+        ));
+    }
+
+    items
+}
+
+fn risk_level(issues: &[SecurityIssue]) -> &'static str {
+    if issues.iter().any(|i| i.severity == Severity::Critical) {
+        "high"
+    } else if issues.iter().any(|i| i.severity == Severity::Warning) {
+        "medium"
+    } else {
+        "low"
+    }
+}
+
+fn build_security_report(items: Vec<ReportItem>) -> SecurityReport {
+    let mut issues = Vec::new();
+    let mut recommendations: Vec<String> = Vec::new();
+    for (issue, fix) in items {
+        if !recommendations.contains(&fix) {
+            recommendations.push(fix);
+        }
+        issues.push(issue);
+    }
+    SecurityReport {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        risk_level: risk_level(&issues).to_string(),
+        issues,
+        recommendations,
+    }
+}
+
 // ============================================================================
 // Public API
 // ============================================================================
@@ -327,3 +583,88 @@ pub fn run_security_audit() -> AuditReport {
 pub async fn security_audit() -> Result<AuditReport, String> {
     Ok(run_security_audit())
 }
+
+/// Audit credentials, file permissions, exposed tools, commands and skills into a readable report.
+#[tauri::command]
+pub async fn security_generate_report() -> Result<SecurityReport, String> {
+    let config = load_app_config()?;
+    let secrets = configured_secrets(&config);
+
+    let mut items = report_config_file(&secrets);
+    items.extend(report_credential_files());
+    items.extend(report_shell_tools());
+    items.extend(report_shell_commands());
+    items.extend(report_api_server());
+    items.extend(report_shell_skills());
+    items.extend(report_environment());
+
+    let report = build_security_report(items);
+    info!("Security report generated: risk {} with {} issues", report.risk_level, report.issues.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::config::NamedProvider;
+
+    #[test]
+    fn test_sensitive_env_names() {
+        assert!(is_sensitive_env_name("OPENAI_API_KEY"));
+        assert!(is_sensitive_env_name("github_token"));
+        assert!(is_sensitive_env_name("CLIENT_SECRET"));
+        assert!(!is_sensitive_env_name("PATH"));
+        assert!(!is_sensitive_env_name("SSH_AUTH_SOCK"));
+    }
+
+    #[test]
+    fn test_configured_secrets() {
+        let mut config = AppConfig::new();
+        assert!(configured_secrets(&config).is_empty());
+
+        config.ai_config.api_key = "sk-live".into();
+        config.github_token = Some("ghp_x".into());
+        config.providers.push(NamedProvider {
+            name: "team".into(),
+            base_url: "https://api.example.com".into(),
+            keys: vec!["k1".into()],
+            default_model: None,
+            rotation: Default::default(),
+        });
+        assert_eq!(configured_secrets(&config), vec!["ai_config.api_key", "providers[team].keys", "github_token"]);
+    }
+
+    #[test]
+    fn test_report_risk_level_and_recommendations() {
+        let fix = "chmod 600";
+        let report = build_security_report(vec![
+            report_item(Severity::Warning, "filesystem", "a".into(), None, fix),
+            report_item(Severity::Info, "environment", "b".into(), None, fix),
+        ]);
+        assert_eq!(report.risk_level, "medium");
+        assert_eq!(report.recommendations, vec![fix]);
+        assert_eq!(build_security_report(Vec::new()).risk_level, "low");
+
+        let critical = report_item(Severity::Critical, "network", "c".into(), None, "firewall");
+        assert_eq!(build_security_report(vec![critical]).risk_level, "high");
+    }
+    #[cfg(unix)]
+    #[test]
+    fn test_report_secret_file() {
+        let file = std::env::temp_dir().join(format!("helix-secret-{}.json", std::process::id()));
+        std::fs::write(&file, "{}").unwrap();
+        let secrets = vec!["smtp.password".to_string()];
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        let items = report_secret_file(&file, &secrets, "chmod 600");
+        assert_eq!(items.len(), 1);
+        assert!(items[0].0.description.contains("smtp.password"));
+
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let items = report_secret_file(&file, &secrets, "chmod 600");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].0.severity, Severity::Critical);
+        assert!(report_secret_file(&file, &[], "chmod 600").iter().all(|(i, _)| i.severity == Severity::Warning));
+        std::fs::remove_file(&file).unwrap();
+    }
+}
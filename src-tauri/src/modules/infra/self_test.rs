@@ -0,0 +1,210 @@
+//! End-to-end self test ("is my install healthy?").
+//!
+//! `run_self_test` exercises each core subsystem for real — database schema,
+//! config and AI credentials, a memory store/search roundtrip, a sandbox file
+//! write/read and the local API server — and reports pass/fail per check so
+//! a broken install points at the subsystem to look at. Checks clean up the
+//! data they create.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::modules::ai_chat::ConnectionReport;
+use crate::modules::{api_server, config, database, memory};
+
+/// Owner of the probe memory, so it never shows up in recall for real sessions.
+const SELF_TEST_OWNER: &str = "__helix_self_test__";
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    /// What was verified, on success
+    pub detail: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// Every check passed
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+    pub ran_at: String,
+}
+
+async fn run_check(name: &str, check: impl Future<Output = Result<String, String>>) -> SelfTestCheck {
+    let started = Instant::now();
+    let result = check.await;
+    SelfTestCheck {
+        name: name.to_string(),
+        passed: result.is_ok(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        detail: result.as_ref().ok().cloned(),
+        error: result.err(),
+    }
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+async fn check_database() -> Result<String, String> {
+    let missing = database::missing_tables()?;
+    if !missing.is_empty() {
+        return Err(format!("Missing tables: {}", missing.join(", ")));
+    }
+    let stats = database::db_stats()?;
+    Ok(format!("{} messages, {} files, {} bytes", stats.messages, stats.files, stats.db_size_bytes))
+}
+
+/// Chat must work; the embeddings and model listing probes are optional.
+fn connection_result(report: &ConnectionReport) -> Result<String, String> {
+    let summary = format!("{} ({})", report.model, report.provider);
+    let failure = if !report.reachable {
+        Some("provider unreachable")
+    } else if !report.auth_ok {
+        Some("credentials rejected")
+    } else if !report.chat_ok {
+        Some("chat completion failed")
+    } else {
+        None
+    };
+    match failure {
+        Some(reason) => {
+            let probe_error = report.probes.get("chat").and_then(|p| p.error.clone());
+            Err(match probe_error {
+                Some(error) => format!("{}: {}: {}", summary, reason, error),
+                None => format!("{}: {}", summary, reason),
+            })
+        }
+        None if report.embeddings_ok => Ok(summary),
+        None => Ok(format!("{}, embeddings unavailable", summary)),
+    }
+}
+
+async fn check_ai() -> Result<String, String> {
+    let config = config::load_app_config()?;
+    let report = crate::modules::ai_chat::ai_test_connection().await.map_err(|e| e.message)?;
+    connection_result(&report).map(|ok| format!("config loaded (language {}), {}", config.language, ok))
+}
+
+async fn check_memory() -> Result<String, String> {
+    let marker = format!("selftest{}", uuid::Uuid::new_v4().simple());
+    let entry = memory::store_memory(
+        &format!("{}:{}", SELF_TEST_OWNER, marker),
+        &format!("Helix self test probe {}", marker),
+        "note",
+        &[],
+        Some(SELF_TEST_OWNER),
+    )?;
+    let found = memory::search_fts(&marker, 5, Some(SELF_TEST_OWNER));
+    let cleanup = memory::delete_memory(entry.id).map_err(|e| e.message);
+    match found {
+        Ok(results) if results.iter().any(|r| r.entry.id == entry.id) => {
+            cleanup.map(|_| "stored, found and deleted".to_string())
+        }
+        Ok(_) => Err("Stored memory was not found by search".into()),
+        Err(e) => Err(format!("Search failed: {}", e)),
+    }
+}
+
+async fn check_sandbox() -> Result<String, String> {
+    let sandbox = std::path::PathBuf::from(crate::modules::agent::tools::get_sandbox_path());
+    tokio::fs::create_dir_all(&sandbox).await.map_err(|e| format!("Create {}: {}", sandbox.display(), e))?;
+    let path = sandbox.join(format!(".helix-self-test-{}", uuid::Uuid::new_v4().simple()));
+    let expected = "helix self test\n";
+    let write = tokio::fs::write(&path, expected).await.map_err(|e| format!("Write {}: {}", path.display(), e));
+    let read = match write {
+        Ok(()) => tokio::fs::read_to_string(&path).await.map_err(|e| format!("Read {}: {}", path.display(), e)),
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+    match read? {
+        text if text == expected => Ok(format!("read/write ok in {}", sandbox.display())),
+        _ => Err("File read back differs from what was written".into()),
+    }
+}
+
+async fn check_api_server() -> Result<String, String> {
+    let port = api_server::listening_port().ok_or("API server is not listening")?;
+    let url = format!("http://127.0.0.1:{}/api/health", port);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("GET {}: {}", url, e))?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    if status.is_success() && body["status"] == "ok" {
+        Ok(format!("healthy on port {}", port))
+    } else {
+        Err(format!("GET {} returned {} {}", url, status, body))
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Run every check and report which subsystems work.
+#[tauri::command]
+pub async fn run_self_test() -> Result<SelfTestReport, String> {
+    let (database, ai, memory, sandbox, api_server) = tokio::join!(
+        run_check("database", check_database()),
+        run_check("ai_connection", check_ai()),
+        run_check("memory", check_memory()),
+        run_check("sandbox", check_sandbox()),
+        run_check("api_server", check_api_server()),
+    );
+    let checks = vec![database, ai, memory, sandbox, api_server];
+    let passed = checks.iter().all(|c| c.passed);
+    let failed: Vec<&str> = checks.iter().filter(|c| !c.passed).map(|c| c.name.as_str()).collect();
+    info!("Self test finished: {}", if passed { "all passed".to_string() } else { format!("failed {:?}", failed) });
+    Ok(SelfTestReport { passed, checks, ran_at: chrono::Utc::now().to_rfc3339() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::ai_chat::ProbeResult;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_run_check_records_outcome() {
+        let ok = run_check("ok", async { Ok("fine".to_string()) }).await;
+        assert!(ok.passed);
+        assert_eq!(ok.detail.as_deref(), Some("fine"));
+        let failed = run_check("bad", async { Err("broken".to_string()) }).await;
+        assert!(!failed.passed);
+        assert_eq!((failed.detail, failed.error.as_deref()), (None, Some("broken")));
+    }
+
+    #[test]
+    fn test_connection_result() {
+        let mut report = ConnectionReport {
+            reachable: true,
+            auth_ok: true,
+            chat_ok: true,
+            embeddings_ok: false,
+            models_ok: true,
+            provider: "openai".into(),
+            model: "gpt-4o".into(),
+            models: vec![],
+            probes: HashMap::new(),
+        };
+        assert_eq!(connection_result(&report).unwrap(), "gpt-4o (openai), embeddings unavailable");
+
+        report.auth_ok = false;
+        report.chat_ok = false;
+        report.probes.insert(
+            "chat".into(),
+            ProbeResult { ok: false, status: Some(401), latency_ms: 5, error: Some("invalid key".into()) },
+        );
+        assert_eq!(connection_result(&report).unwrap_err(), "gpt-4o (openai): credentials rejected: invalid key");
+    }
+}
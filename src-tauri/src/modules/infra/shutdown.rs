@@ -0,0 +1,196 @@
+//! Shutdown coordinator — lets background tasks stop cleanly on app exit.
+//!
+//! Every long-running task registers itself with [`register`] (or starts via
+//! [`spawn`]) and holds the returned [`TaskGuard`] for its lifetime. On
+//! `RunEvent::Exit`, [`on_exit`] broadcasts shutdown, waits for the guards to
+//! drop, then flushes persistent state. The free functions use the app-wide
+//! [`Coordinator`]; tests build their own.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// How long `on_exit` waits for tasks to acknowledge shutdown.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct CoordinatorState {
+    /// `true` once shutdown has been broadcast.
+    shutdown: watch::Sender<bool>,
+    /// Registered tasks by id. Empty once every task has acknowledged.
+    tasks: watch::Sender<BTreeMap<u64, String>>,
+    next_task_id: AtomicU64,
+}
+
+/// A set of tasks that are shut down together.
+#[derive(Clone)]
+pub struct Coordinator {
+    state: Arc<CoordinatorState>,
+}
+
+impl Default for Coordinator {
+    fn default() -> Self {
+        Self {
+            state: Arc::new(CoordinatorState {
+                shutdown: watch::channel(false).0,
+                tasks: watch::channel(BTreeMap::new()).0,
+                next_task_id: AtomicU64::new(1),
+            }),
+        }
+    }
+}
+
+static COORDINATOR: Lazy<Coordinator> = Lazy::new(Coordinator::default);
+
+/// Registration handle for a background task. Dropping it tells the
+/// coordinator the task has stopped.
+pub struct TaskGuard {
+    id: u64,
+    rx: watch::Receiver<bool>,
+    state: Arc<CoordinatorState>,
+}
+
+impl TaskGuard {
+    pub fn is_shutting_down(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolve once shutdown has been broadcast. Use as a `select!` arm
+    /// next to the task's sleep/tick so it exits between iterations.
+    pub async fn cancelled(&mut self) {
+        let _ = self.rx.wait_for(|stop| *stop).await;
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.state.tasks.send_modify(|tasks| {
+            tasks.remove(&self.id);
+        });
+    }
+}
+
+impl Coordinator {
+    /// Register a background task under `name`.
+    pub fn register(&self, name: &str) -> TaskGuard {
+        let id = self.state.next_task_id.fetch_add(1, Ordering::Relaxed);
+        self.state.tasks.send_modify(|tasks| {
+            tasks.insert(id, name.to_string());
+        });
+        TaskGuard { id, rx: self.state.shutdown.subscribe(), state: self.state.clone() }
+    }
+
+    /// Resolve once shutdown has been broadcast.
+    pub async fn wait_for_shutdown(&self) {
+        let mut rx = self.state.shutdown.subscribe();
+        let _ = rx.wait_for(|stop| *stop).await;
+    }
+
+    /// Names of tasks that are still registered.
+    pub fn active_tasks(&self) -> Vec<String> {
+        self.state.tasks.borrow().values().cloned().collect()
+    }
+
+    /// Broadcast shutdown and wait up to `timeout` for all tasks to stop.
+    /// Returns the names of tasks that did not acknowledge in time.
+    pub async fn shutdown(&self, timeout: Duration) -> Vec<String> {
+        self.state.shutdown.send_replace(true);
+        let mut rx = self.state.tasks.subscribe();
+        let stopped = tokio::time::timeout(timeout, rx.wait_for(|tasks| tasks.is_empty()))
+            .await
+            .is_ok();
+        if stopped {
+            Vec::new()
+        } else {
+            self.active_tasks()
+        }
+    }
+}
+
+/// Register a background task under `name`.
+pub fn register(name: &str) -> TaskGuard {
+    COORDINATOR.register(name)
+}
+
+/// Register `name` and spawn the task built by `f`. The guard is released
+/// when the task's future completes.
+pub fn spawn<F, Fut>(name: &str, f: F)
+where
+    F: FnOnce(TaskGuard) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let guard = register(name);
+    tauri::async_runtime::spawn(f(guard));
+}
+
+/// Resolve once shutdown has been broadcast (for `with_graceful_shutdown`).
+pub async fn wait_for_shutdown() {
+    COORDINATOR.wait_for_shutdown().await
+}
+
+/// Names of tasks that are still registered.
+pub fn active_tasks() -> Vec<String> {
+    COORDINATOR.active_tasks()
+}
+
+/// Broadcast shutdown and wait up to `timeout` for all tasks to stop.
+/// Returns the names of tasks that did not acknowledge in time.
+pub async fn shutdown(timeout: Duration) -> Vec<String> {
+    COORDINATOR.shutdown(timeout).await
+}
+
+/// Exit sequence for `RunEvent::Exit`: stop background tasks, then mark
+/// orphaned cron runs and checkpoint the SQLite WAL.
+pub fn on_exit() {
+    info!("Application exiting, stopping {} background task(s)...", active_tasks().len());
+
+    let stragglers = tauri::async_runtime::block_on(shutdown(SHUTDOWN_TIMEOUT));
+    if stragglers.is_empty() {
+        info!("All background tasks stopped");
+    } else {
+        warn!("Background tasks did not stop within {:?}: {}", SHUTDOWN_TIMEOUT, stragglers.join(", "));
+    }
+
+    match crate::modules::cron::abort_running_runs() {
+        Ok(0) => {}
+        Ok(n) => info!("Marked {} in-flight cron run(s) as aborted", n),
+        Err(e) => warn!("Failed to mark cron runs aborted: {}", e),
+    }
+
+    if let Err(e) = crate::modules::database::checkpoint_wal() {
+        warn!("WAL checkpoint failed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_guards() {
+        let coordinator = Coordinator::default();
+        let other = Coordinator::default();
+        let mut fast = coordinator.register("fast");
+        let slow = coordinator.register("slow");
+        let bystander = other.register("other");
+        let worker = tokio::spawn(async move {
+            fast.cancelled().await;
+        });
+
+        let stragglers = coordinator.shutdown(Duration::from_millis(200)).await;
+        worker.await.unwrap();
+        assert_eq!(stragglers, vec!["slow".to_string()]);
+
+        drop(slow);
+        assert!(coordinator.shutdown(Duration::from_millis(200)).await.is_empty());
+        assert!(coordinator.register("late").is_shutting_down());
+        // Shutting one coordinator down leaves the others running
+        assert!(!bystander.is_shutting_down());
+        assert_eq!(other.active_tasks(), vec!["other".to_string()]);
+    }
+}
@@ -32,11 +32,13 @@ pub async fn start_lan_server(app_handle: Option<tauri::AppHandle>, port: u16) -
 
     let bind_str = format!("0.0.0.0:{}", port);
     
-    tokio::spawn(async move {
+    crate::modules::infra::shutdown::spawn("lan-server", |shutdown| async move {
         match tokio::net::TcpListener::bind(&bind_str).await {
             Ok(listener) => {
                 info!("LAN HTTP Server listening on {}", bind_str);
-                if let Err(e) = axum::serve(listener, app).await {
+                let server = axum::serve(listener, app)
+                    .with_graceful_shutdown(crate::modules::infra::shutdown::wait_for_shutdown());
+                if let Err(e) = server.await {
                     tracing::error!("LAN server error: {}", e);
                 }
             }
@@ -44,6 +46,7 @@ pub async fn start_lan_server(app_handle: Option<tauri::AppHandle>, port: u16) -
                 tracing::error!("LAN server bind error on {}: {}", bind_str, e);
             }
         }
+        drop(shutdown);
     });
 
     Ok(())
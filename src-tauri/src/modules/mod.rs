@@ -8,8 +8,8 @@ pub mod infra;     // config, logger, database, security, api_server, etc.
 pub mod app;       // tray, scheduler, cron, update_checker, cloudflared
 pub mod agent;     // AI agent, tools, skills, hooks, commands, memory, plugins
 pub mod ai;        // providers, streaming, model_selection, ai_chat
-pub mod chat;      // channels, sessions, messaging
-pub mod cloud;     // kubeconfig, aliyun
+pub mod chat;      // channels, sessions, messaging, smtp
+pub mod cloud;     // kubeconfig, aliyun, aws, github
 pub mod browser;   // browser engine
 pub mod evomap;    // EvoMap
 pub mod lan;       // LocalSend P2P Protocol
@@ -29,9 +29,16 @@ pub use infra::security;
 pub use infra::notifications;
 pub use infra::i18n;
 pub use infra::api_server;
+pub use infra::shutdown;
+pub use infra::diagnostics;
+pub use infra::self_test;
 
 // app
 pub use app::tray;
+pub use app::hotkey;
+pub use app::quick_ask;
+pub use app::desktop_notify;
+pub use app::quick_actions;
 pub use app::scheduler;
 pub use app::cron;
 pub use app::update_checker;
@@ -39,6 +46,8 @@ pub use app::cloudflared;
 pub use app::workspace;
 pub use app::environments;
 pub use app::mcp;
+pub use app::mcp_client;
+pub use app::search;
 
 // agent (core re-exported via agent/mod.rs `pub use core::*`)
 pub use agent::tools as agent_tools;
@@ -60,15 +69,20 @@ pub use ai::stream_events;
 pub use ai::usage;
 pub use ai::link_understanding;
 pub use ai::media_understanding;
+pub use ai::prompt_templates;
 
 // chat
 pub use chat::channels;
 pub use chat::sessions;
 pub use chat::messaging;
+pub use chat::smtp;
+pub use chat::wecom;
 
 // cloud
 pub use cloud::kubeconfig;
 pub use cloud::aliyun;
+pub use cloud::aws;
+pub use cloud::github;
 
 // browser
 pub use browser::engine as browser_engine;
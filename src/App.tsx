@@ -13,6 +13,7 @@ import { useEffect } from 'react';
 import { useConfigStore } from './stores/useConfigStore';
 import { useTranslation } from 'react-i18next';
 import KnowledgeItems from './pages/KnowledgeItems';
+import QuickAsk from './pages/QuickAsk';
 
 const router = createBrowserRouter([
   {
@@ -53,6 +54,11 @@ const router = createBrowserRouter([
       },
     ],
   },
+  {
+    // Quick-ask window (opened by the global hotkey), no app chrome
+    path: '/quick-ask',
+    element: <QuickAsk />,
+  },
 ]);
 
 function App() {
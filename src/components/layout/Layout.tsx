@@ -52,7 +52,7 @@ function Layout() {
     const [envVars, setEnvVars] = useState<EnvVar[]>([]);
     const [envShowAdd, setEnvShowAdd] = useState(false);
     const [envNew, setEnvNew] = useState({ key: '', value: '', secret: false });
-    const [envShowKeys, setEnvShowKeys] = useState<Record<string, boolean>>({});
+    const [envRevealed, setEnvRevealed] = useState<Record<string, string>>({});
 
     // App Avatar state
     const [showAvatarPicker, setShowAvatarPicker] = useState(false);
@@ -76,7 +76,7 @@ function Layout() {
     }, []);
 
     const loadEnvVars = useCallback(async () => {
-        try { setEnvVars(await invoke<EnvVar[]>('envs_list')); } catch (e) { console.error('envs_list', e); }
+        try { setEnvVars(await invoke<EnvVar[]>('envs_list')); setEnvRevealed({}); } catch (e) { console.error('envs_list', e); }
     }, []);
 
     useEffect(() => {
@@ -177,6 +177,16 @@ function Layout() {
         };
     }, []);
 
+    // Desktop notification clicked: open the channel the message came from
+    useEffect(() => {
+        const unlisten = listen<{ channel: string; session_key: string }>('notification://open-session', (event) => {
+            navigate('/channels', { state: { channel: event.payload.channel, sessionKey: event.payload.session_key } });
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, [navigate]);
+
     // Workspace handlers
     const wsSelectFile = async (name: string) => {
         try {
@@ -199,6 +209,13 @@ function Layout() {
         try { await invoke('envs_set', { key: envNew.key, value: envNew.value, secret: envNew.secret }); setEnvNew({ key: '', value: '', secret: false }); setEnvShowAdd(false); loadEnvVars(); } catch (e) { console.error(e); }
     };
     const envDelete = async (key: string) => { try { await invoke('envs_delete', { key }); loadEnvVars(); } catch (e) { console.error(e); } };
+    const envToggleReveal = async (key: string) => {
+        if (key in envRevealed) {
+            setEnvRevealed(p => { const next = { ...p }; delete next[key]; return next; });
+            return;
+        }
+        try { const value = await invoke<string>('envs_reveal', { key }); setEnvRevealed(p => ({ ...p, [key]: value })); } catch (e) { console.error('envs_reveal', e); }
+    };
 
 
 
@@ -529,15 +546,13 @@ function Layout() {
                                         <span className="font-mono text-xs font-medium text-gray-800 dark:text-gray-200">{env.key}</span>
                                         <span className="mx-1.5 text-gray-300">=</span>
                                         <span className="font-mono text-xs text-gray-500">
-                                            {env.secret && !envShowKeys[env.key] ? '••••••••' : env.value}
+                                            {envRevealed[env.key] ?? (env.secret ? '••••••••' : env.value)}
                                         </span>
                                     </div>
                                     <div className="flex items-center gap-1 shrink-0">
-                                        {env.secret && (
-                                            <button className="text-gray-400 hover:text-gray-600" onClick={() => setEnvShowKeys(p => ({ ...p, [env.key]: !p[env.key] }))}>
-                                                {envShowKeys[env.key] ? <EyeOff size={12} /> : <Eye size={12} />}
-                                            </button>
-                                        )}
+                                        <button className="text-gray-400 hover:text-gray-600" onClick={() => envToggleReveal(env.key)}>
+                                            {env.key in envRevealed ? <EyeOff size={12} /> : <Eye size={12} />}
+                                        </button>
                                         <button className="text-red-400 hover:text-red-500" onClick={() => envDelete(env.key)}>
                                             <Trash2 size={12} />
                                         </button>
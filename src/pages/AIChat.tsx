@@ -1400,7 +1400,7 @@ function AIChat() {
                                         {isSessionLoading ? (
                                             <button
                                                 className="px-4 py-1.5 text-xs bg-red-500 hover:bg-red-600 text-white rounded-full transition-colors flex items-center gap-1.5"
-                                                onClick={() => invoke('agent_cancel', { sessionId: activeChatId })}
+                                                onClick={() => invoke('agent_cancel', { sessionId: `chat:${activeChatId}` })}
                                             >
                                                 <Square size={11} fill="white" />
                                                 {t('chat.stop', '停止')}
@@ -14,6 +14,7 @@ interface CronTask {
     next_run: string | null;
     run_count: number;
     notify_channel: 'feishu' | 'dingtalk' | 'wecom' | null;
+    group_name?: string;
 }
 
 interface CronRun {
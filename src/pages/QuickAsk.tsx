@@ -0,0 +1,117 @@
+import { KeyboardEvent, useEffect, useRef, useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { Loader2, Sparkles } from 'lucide-react';
+
+// Agent session that quick-ask questions are routed to (QUICK_ASK_SESSION in quick_ask.rs)
+const QUICK_ASK_SESSION = 'quick';
+
+interface ProgressEvent {
+    seq: number;
+    type: string;
+    data: any;
+    ts: number;
+}
+
+export default function QuickAsk() {
+    const { t } = useTranslation();
+    const [question, setQuestion] = useState('');
+    const [answer, setAnswer] = useState('');
+    const [steps, setSteps] = useState<string[]>([]);
+    const [running, setRunning] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const inputRef = useRef<HTMLInputElement>(null);
+    const unlistenRef = useRef<UnlistenFn | null>(null);
+
+    useEffect(() => {
+        inputRef.current?.focus();
+        const onFocus = () => inputRef.current?.focus();
+        window.addEventListener('focus', onFocus);
+        return () => {
+            window.removeEventListener('focus', onFocus);
+            unlistenRef.current?.();
+        };
+    }, []);
+
+    const submit = async () => {
+        const content = question.trim();
+        if (!content || running) return;
+        const requestId = crypto.randomUUID();
+        setRunning(true);
+        setAnswer('');
+        setSteps([]);
+        setError(null);
+
+        unlistenRef.current?.();
+        unlistenRef.current = await listen<{ request_id: string; events: ProgressEvent[] }>(
+            `agent://progress/${requestId}`,
+            (event) => {
+                for (const e of event.payload.events) {
+                    if (e.type === 'tool_started') {
+                        setSteps((prev) => [...prev, `🔧 ${e.data?.name ?? ''}`]);
+                    } else if (e.type === 'model_turn_started') {
+                        // Text streamed before a tool call is superseded by the next turn
+                        setAnswer('');
+                    } else if (e.type === 'text_delta') {
+                        setAnswer((prev) => prev + (e.data?.text ?? ''));
+                    }
+                }
+            },
+        );
+
+        try {
+            const result = await invoke<{ content: string }>('agent_chat', {
+                accountId: QUICK_ASK_SESSION,
+                content,
+                requestId,
+                stream: true,
+            });
+            setAnswer(result.content || t('quick_ask.empty'));
+            setQuestion('');
+        } catch (e: any) {
+            setError(typeof e === 'string' ? e : e?.message || String(e));
+        } finally {
+            setRunning(false);
+            unlistenRef.current?.();
+            unlistenRef.current = null;
+        }
+    };
+
+    const onKeyDown = (e: KeyboardEvent<HTMLInputElement>) => {
+        if (e.key === 'Enter' && !e.nativeEvent.isComposing) {
+            e.preventDefault();
+            submit();
+        } else if (e.key === 'Escape') {
+            invoke('quickask_hide');
+        }
+    };
+
+    return (
+        <div className="h-screen w-screen p-2 bg-transparent">
+            <div className="h-full flex flex-col rounded-xl bg-white/95 dark:bg-gray-900/95 shadow-2xl border border-gray-200 dark:border-gray-700 overflow-hidden">
+                <div data-tauri-drag-region className="flex items-center gap-3 px-4 py-3 border-b border-gray-100 dark:border-gray-800">
+                    {running ? <Loader2 size={18} className="animate-spin text-[#07c160]" /> : <Sparkles size={18} className="text-[#07c160]" />}
+                    <input
+                        ref={inputRef}
+                        value={question}
+                        onChange={(e) => setQuestion(e.target.value)}
+                        onKeyDown={onKeyDown}
+                        placeholder={t('quick_ask.placeholder')}
+                        className="flex-1 bg-transparent outline-none text-base text-gray-900 dark:text-gray-100"
+                    />
+                </div>
+                <div className="flex-1 overflow-y-auto px-4 py-3 text-sm text-gray-700 dark:text-gray-300 whitespace-pre-wrap">
+                    {steps.length > 0 && (
+                        <div className="mb-2 text-xs text-gray-400 space-y-0.5">
+                            {steps.map((step, i) => <div key={i}>{step}</div>)}
+                        </div>
+                    )}
+                    {error && <div className="text-red-500">{error}</div>}
+                    {answer}
+                    {!answer && !error && !running && <div className="text-gray-400">{t('quick_ask.hint')}</div>}
+                </div>
+            </div>
+        </div>
+    );
+}
@@ -33,6 +33,7 @@ interface Skill {
     enabled: boolean;
     body: string;
     homepage: string;
+    permissions: string[];
 }
 
 // Built-in curated skill registry for Skills Hub (matching CoPaw's hub)
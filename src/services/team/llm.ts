@@ -1,5 +1,6 @@
 import { useDevOpsStore } from '../../stores/useDevOpsStore';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../../utils/error';
 
 export class LLMProvider {
     async chat(messages: any[], tools: any[] = []): Promise<any> {
@@ -39,11 +40,12 @@ export class LLMProvider {
                     content,
                     images: [],
                     workspace: null,
+                    events: false,
                 });
 
                 return { role: 'assistant', content: result.content || '' };
             } catch (err: any) {
-                throw new Error(`LLM API Error: ${err}`);
+                throw new Error(`LLM API Error: ${errorMessage(err)}`);
             }
         }
 
@@ -74,7 +76,7 @@ export class LLMProvider {
             });
             return data.choices[0].message;
         } catch (err: any) {
-            throw new Error(`LLM API Error: ${err}`);
+            throw new Error(`LLM API Error: ${errorMessage(err)}`);
         }
     }
 }
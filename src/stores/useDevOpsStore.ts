@@ -3,6 +3,7 @@ import { persist } from 'zustand/middleware';
 import { arrayMove } from '@dnd-kit/sortable';
 import { executeTool, setSkillEnabled, addCustomSkill as addSkillToRegistry, removeCustomSkill as removeSkillFromRegistry, syncSkillStates, loadCustomSkills, loadAllAgentSkills, type OpsSkill, type ToolParameter, type AgentSkill } from '../services/opsTools';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../utils/error';
 
 function syncAIProviderToBackend(providers: AIProvider[]) {
     // We now allow multiple providers, so syncing a global default is less strict.
@@ -476,7 +477,7 @@ export const useDevOpsStore = create<helixState>()(
                 } catch (err: any) {
                     const errorMsg: ChatMessage = {
                         id: generateId(), role: 'assistant',
-                        content: `❌ 请求失败: ${typeof err === 'string' ? err : err?.message || JSON.stringify(err)}`,
+                        content: `❌ 请求失败: ${errorMessage(err)}`,
                         timestamp: new Date().toISOString(),
                     };
                     set((s) => {
@@ -2,6 +2,29 @@ export interface AppConfig {
     language: string;
     theme: string;
     app_avatar_url?: string;
+    global_hotkey?: string | null;
+    quick_ask_hotkey?: string | null;
+    model_pricing?: Record<string, { input_per_m: number; output_per_m: number }>;
+    provider_keys?: Record<string, string[]>;
+    providers?: {
+        name: string;
+        base_url: string;
+        keys: string[];
+        default_model?: string | null;
+        rotation?: 'round_robin' | 'failover';
+    }[];
+    ai_retry?: { max_attempts: number; base_delay_ms: number; max_delay_ms: number };
+    auto_recall?: { enabled: boolean; top_k: number; min_score: number };
+    usage_report?: { enabled: boolean; time: string; channel: 'feishu' | 'dingtalk' | 'smtp' | 'wecom' };
+    cron_max_runs?: number;
+    heartbeat?: { enabled: boolean; interval_minutes: number; catch_up?: boolean };
+    scheduler?: { resume_stagger_secs: number };
+    desktop_notifications?: {
+        enabled: boolean;
+        channels?: Record<string, boolean>;
+        quiet_hours_start?: string | null;
+        quiet_hours_end?: string | null;
+    };
     hidden_menu_items?: string[];
     ai_config?: {
         provider: string;
@@ -12,4 +35,10 @@ export interface AppConfig {
         system_prompt: string;
         auto_reply: boolean;
     };
+    tool_policy?: {
+        kubectl_enabled: boolean;
+        kubectl_allow_mutations: boolean;
+        aliyun_enabled: boolean;
+        browser_denylist?: string[];
+    };
 }
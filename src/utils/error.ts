@@ -0,0 +1,25 @@
+import i18n from '../i18n';
+
+/** Structured error returned by converted Tauri commands (see src-tauri/src/error.rs). */
+export interface HelixError {
+    code: string;
+    message: string;
+    detail?: unknown;
+    retriable: boolean;
+}
+
+export function isHelixError(err: unknown): err is HelixError {
+    return typeof err === 'object' && err !== null
+        && typeof (err as HelixError).code === 'string'
+        && typeof (err as HelixError).message === 'string';
+}
+
+/** User-facing text for an invoke error: translated by code, falling back to the backend message. */
+export function errorMessage(err: unknown): string {
+    if (typeof err === 'string') return err;
+    if (isHelixError(err)) {
+        return i18n.t(`errors.${err.code}`, { defaultValue: err.message });
+    }
+    if (err instanceof Error) return err.message;
+    return JSON.stringify(err);
+}